@@ -0,0 +1,174 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A benchmark measuring the main operations on symtypes corpuses: loading split data,
+//! consolidating it, splitting it again, and comparing two corpuses.
+//!
+//! The benchmark generates a synthetic corpus in a temporary directory, together with a second
+//! corpus in which a small part of the types is modified. The size of the corpuses is set by the
+//! `BENCH_CORPUS_FILES` environment variable, which specifies the number of symtypes files, and
+//! the number of workers by `BENCH_CORPUS_JOBS`.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Instant;
+use std::{env, fs, io, process};
+use suse_kabi_tools::burst::JobControl;
+use suse_kabi_tools::symtypes::{ConsolidateOrder, SymtypesCorpus};
+use suse_kabi_tools::text::DirectoryWriter;
+
+/// The default number of symtypes files in each generated corpus.
+const DEFAULT_NUM_FILES: usize = 2000;
+
+/// The number of types defined in each file.
+const TYPES_PER_FILE: usize = 100;
+
+/// The number of exports defined in each file.
+const EXPORTS_PER_FILE: usize = 10;
+
+/// Every `MODIFIED_TYPE_RATIO`-th type is modified in the second corpus.
+const MODIFIED_TYPE_RATIO: usize = 50;
+
+/// The number of measurements performed for each operation.
+const ITERATIONS: usize = 3;
+
+/// Generates the content of the symtypes file with the given index. The `modified` flag selects
+/// whether some of the types are changed.
+fn generate_symfile(file_idx: usize, num_types: usize, modified: bool) -> String {
+    let mut data = String::new();
+    let type_idxs = (0..TYPES_PER_FILE)
+        .map(|i| (file_idx * 37 + i * 11) % num_types)
+        .collect::<Vec<_>>();
+    for &type_idx in &type_idxs {
+        let member = if modified && type_idx % MODIFIED_TYPE_RATIO == 0 {
+            "long long"
+        } else {
+            "long"
+        };
+        // Link the types into chains so the comparison has to follow type references.
+        let next_idx = (type_idx + 1) % num_types;
+        if type_idxs.contains(&next_idx) {
+            writeln!(
+                data,
+                "s#t{0} struct t{0} {{ int a ; {1} b ; s#t{2} * next ; }}",
+                type_idx, member, next_idx
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                data,
+                "s#t{0} struct t{0} {{ int a ; {1} b ; void * next ; }}",
+                type_idx, member
+            )
+            .unwrap();
+        }
+    }
+    for export_idx in 0..EXPORTS_PER_FILE {
+        let type_a = type_idxs[export_idx % TYPES_PER_FILE];
+        let type_b = type_idxs[(export_idx * 7 + 3) % TYPES_PER_FILE];
+        writeln!(
+            data,
+            "f{0}_{1} int f{0}_{1} ( s#t{2} * , s#t{3} * )",
+            file_idx, export_idx, type_a, type_b
+        )
+        .unwrap();
+    }
+    data
+}
+
+/// Writes a corpus of `num_files` symtypes files into the directory `corpus_dir`.
+fn generate_corpus(corpus_dir: &Path, num_files: usize, modified: bool) {
+    let num_types = num_files.max(TYPES_PER_FILE);
+    for file_idx in 0..num_files {
+        let dir = corpus_dir.join(format!("dir{}", file_idx % 50));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(format!("file{}.symtypes", file_idx)),
+            generate_symfile(file_idx, num_types, modified),
+        )
+        .unwrap();
+    }
+}
+
+/// Reads a numeric setting from the given environment variable.
+fn env_number(name: &str, default: usize) -> usize {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid value '{}' of {}", value, name)),
+        Err(_) => default,
+    }
+}
+
+/// Runs the given operation several times and prints the best time it took.
+fn measure<F: FnMut()>(name: &str, mut operation: F) {
+    let mut best = f64::MAX;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        operation();
+        best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    println!("    {:<16}{:12.3}", name, best);
+}
+
+fn main() {
+    let num_files = env_number("BENCH_CORPUS_FILES", DEFAULT_NUM_FILES);
+    let default_jobs = std::thread::available_parallelism().map_or(4, |num| num.get());
+    let jobs = env_number("BENCH_CORPUS_JOBS", default_jobs) as i32;
+
+    let bench_dir = env::temp_dir().join(format!("ksymtypes-bench-ops-{}", process::id()));
+    let corpus_dir = bench_dir.join("a");
+    let corpus_dir2 = bench_dir.join("b");
+    generate_corpus(&corpus_dir, num_files, false);
+    generate_corpus(&corpus_dir2, num_files, true);
+
+    let load = |path: &Path| {
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes
+            .load(path, io::sink(), &mut JobControl::new_simple(jobs))
+            .unwrap();
+        symtypes
+    };
+    let symtypes = load(&corpus_dir);
+    let symtypes2 = load(&corpus_dir2);
+
+    println!(
+        "Processing {} symtypes files with {} records each using {} jobs, best of {} runs:",
+        num_files,
+        TYPES_PER_FILE + EXPORTS_PER_FILE,
+        jobs,
+        ITERATIONS
+    );
+    println!("    Operation          Time [ms]");
+    measure("load", || {
+        load(&corpus_dir);
+    });
+    measure("consolidate", || {
+        symtypes
+            .write_consolidated_buffer(io::sink(), ConsolidateOrder::Path)
+            .unwrap();
+    });
+    measure("split", || {
+        symtypes
+            .write_split_buffer(
+                &mut DirectoryWriter::new_buffer("split"),
+                &mut JobControl::new_simple(jobs),
+            )
+            .unwrap();
+    });
+    measure("compare", || {
+        let result = symtypes
+            .compare(
+                &symtypes2,
+                None,
+                None,
+                None,
+                None,
+                &mut JobControl::new_simple(jobs),
+            )
+            .unwrap();
+        assert!(!result.changed_types.is_empty());
+    });
+
+    fs::remove_dir_all(&bench_dir).unwrap();
+}
@@ -0,0 +1,110 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A benchmark measuring how loading a split symtypes corpus scales with the number of workers.
+//!
+//! The benchmark generates a synthetic corpus in a temporary directory. All files share most of
+//! their types, similarly to the symtypes data of a real kernel build, which puts pressure on the
+//! merging of the types into the loaded corpus.
+
+use std::fmt::Write as _;
+use std::time::Instant;
+use std::{env, fs, io, process};
+use suse_kabi_tools::burst::JobControl;
+use suse_kabi_tools::symtypes::SymtypesCorpus;
+
+/// The number of symtypes files in the generated corpus.
+const NUM_FILES: usize = 4000;
+
+/// The number of distinct type names shared by the files.
+const NUM_SHARED_TYPES: usize = 2000;
+
+/// The number of shared types defined in each file.
+const TYPES_PER_FILE: usize = 200;
+
+/// The number of exports defined in each file.
+const EXPORTS_PER_FILE: usize = 20;
+
+/// The number of measurements performed for each configuration.
+const ITERATIONS: usize = 3;
+
+/// Generates the content of the symtypes file with the given index.
+fn generate_symfile(file_idx: usize) -> String {
+    let mut data = String::new();
+    let type_idxs = (0..TYPES_PER_FILE)
+        .map(|i| (file_idx * 37 + i * 11) % NUM_SHARED_TYPES)
+        .collect::<Vec<_>>();
+    for &type_idx in &type_idxs {
+        // Give every tenth type several variants, as can happen with configuration-dependent
+        // members.
+        let variant = if type_idx % 10 == 0 { file_idx % 3 } else { 0 };
+        writeln!(
+            data,
+            "s#t{0} struct t{0} {{ int a ; long b{1} ; void * c ; }}",
+            type_idx, variant
+        )
+        .unwrap();
+    }
+    for export_idx in 0..EXPORTS_PER_FILE {
+        let type_a = type_idxs[export_idx % TYPES_PER_FILE];
+        let type_b = type_idxs[(export_idx * 7 + 3) % TYPES_PER_FILE];
+        writeln!(
+            data,
+            "f{0}_{1} int f{0}_{1} ( s#t{2} * , s#t{3} * )",
+            file_idx, export_idx, type_a, type_b
+        )
+        .unwrap();
+    }
+    data
+}
+
+fn main() {
+    let corpus_dir = env::temp_dir().join(format!("ksymtypes-bench-{}", process::id()));
+    for file_idx in 0..NUM_FILES {
+        let dir = corpus_dir.join(format!("dir{}", file_idx % 50));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(format!("file{}.symtypes", file_idx)),
+            generate_symfile(file_idx),
+        )
+        .unwrap();
+    }
+
+    let max_jobs = std::thread::available_parallelism().map_or(4, |num| num.get());
+    let mut job_counts = vec![1];
+    while job_counts.last().unwrap() * 2 <= max_jobs {
+        job_counts.push(job_counts.last().unwrap() * 2);
+    }
+    if *job_counts.last().unwrap() != max_jobs {
+        job_counts.push(max_jobs);
+    }
+
+    println!(
+        "Loading {} symtypes files with {} records each, best of {} runs:",
+        NUM_FILES,
+        TYPES_PER_FILE + EXPORTS_PER_FILE,
+        ITERATIONS
+    );
+    println!("    Jobs      Time [ms]  Speedup");
+    let mut base = None;
+    for jobs in job_counts {
+        let mut best = f64::MAX;
+        for _ in 0..ITERATIONS {
+            let mut symtypes = SymtypesCorpus::new();
+            let start = Instant::now();
+            symtypes
+                .load(
+                    &corpus_dir,
+                    io::sink(),
+                    &mut JobControl::new_simple(jobs as i32),
+                )
+                .unwrap();
+            best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        let base = *base.get_or_insert(best);
+        println!("{:8}{:15.3}{:9.2}", jobs, best, base / best);
+    }
+
+    fs::remove_dir_all(&corpus_dir).unwrap();
+}
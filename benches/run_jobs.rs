@@ -0,0 +1,57 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A benchmark measuring the scheduling overhead of `burst::run_jobs()` with many tiny works, for
+//! various chunk sizes.
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use suse_kabi_tools::burst::{self, JobControl};
+
+/// The number of works processed in each measurement.
+const NUM_WORKS: usize = 1_000_000;
+
+/// The number of measurements performed for each configuration.
+const ITERATIONS: usize = 5;
+
+fn main() {
+    let max_jobs = std::thread::available_parallelism().map_or(4, |num| num.get().min(16));
+    let chunk_sizes = [Some(1), Some(4), Some(16), Some(64), Some(256), None];
+
+    println!(
+        "run_jobs() with {} tiny works, best of {} runs:",
+        NUM_WORKS, ITERATIONS
+    );
+    println!("    Jobs  Chunk      Time [ms]");
+    let mut job_counts = vec![1];
+    if max_jobs > 1 {
+        job_counts.push(max_jobs);
+    }
+    for jobs in job_counts {
+        for maybe_chunk_size in chunk_sizes {
+            let mut job_slots = JobControl::new_simple(jobs as i32);
+            job_slots.set_chunk_size(maybe_chunk_size);
+
+            let mut best = f64::MAX;
+            for _ in 0..ITERATIONS {
+                let sum = AtomicUsize::new(0);
+                let start = Instant::now();
+                burst::run_jobs(
+                    |work_idx| {
+                        sum.fetch_add(black_box(work_idx) & 1, Ordering::Relaxed);
+                        Ok(())
+                    },
+                    NUM_WORKS,
+                    &mut job_slots,
+                )
+                .unwrap();
+                best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+                assert_eq!(sum.into_inner(), NUM_WORKS / 2);
+            }
+
+            let chunk = maybe_chunk_size.map_or("auto".to_string(), |size| size.to_string());
+            println!("{:8}  {:<5}{:14.3}", jobs, chunk, best);
+        }
+    }
+}
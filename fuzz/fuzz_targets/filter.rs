@@ -0,0 +1,17 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use suse_kabi_tools::text::Filter;
+
+fuzz_target!(|data: &[u8]| {
+    let mut filter = Filter::new();
+    if filter.load_buffer("fuzz.filter", data).is_ok() {
+        // Match the patterns also against text derived from the input, to exercise them with
+        // unusual characters.
+        let _ = filter.matches("foo");
+        let _ = filter.matches(&String::from_utf8_lossy(data));
+    }
+});
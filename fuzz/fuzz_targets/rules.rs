@@ -0,0 +1,14 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use suse_kabi_tools::rules::Rules;
+
+fuzz_target!(|data: &[u8]| {
+    let mut rules = Rules::new();
+    if rules.load_buffer("fuzz.severities", data).is_ok() {
+        let _ = rules.is_tolerated("foo", "drivers/foo", None);
+    }
+});
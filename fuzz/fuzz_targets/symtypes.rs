@@ -0,0 +1,19 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use suse_kabi_tools::symtypes::{ConsolidateOrder, SymtypesCorpus};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = SymtypesCorpus::validate_buffer("fuzz.symtypes", data);
+
+    let mut symtypes = SymtypesCorpus::new();
+    if symtypes
+        .load_buffer("fuzz.symtypes", data, &mut Vec::new())
+        .is_ok()
+    {
+        let _ = symtypes.write_consolidated_buffer(&mut Vec::new(), ConsolidateOrder::Path);
+    }
+});
@@ -0,0 +1,12 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use suse_kabi_tools::symvers::SymversCorpus;
+
+fuzz_target!(|data: &[u8]| {
+    let mut symvers = SymversCorpus::new();
+    let _ = symvers.load_buffer("fuzz.symvers", data);
+});
@@ -0,0 +1,69 @@
+/* Copyright (C) 2025 SUSE LLC */
+/* SPDX-License-Identifier: GPL-2.0-or-later */
+
+/*
+ * C bindings for the suse-kabi-tools comparison engine, provided by libsuse_kabi_tools.so.
+ *
+ * Corpuses, filters, rules and reports are opaque handles which must be released by the matching
+ * *_free() function. A function that fails returns NULL, or -1 if it returns an integer, and
+ * records a description of the problem which can be obtained by kabi_last_error().
+ */
+
+#ifndef SUSE_KABI_TOOLS_H
+#define SUSE_KABI_TOOLS_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The outcome of a comparison, returned by kabi_report_status(). */
+#define KABI_STATUS_SAME 0
+#define KABI_STATUS_TOLERATED 1
+#define KABI_STATUS_BREAKING 2
+
+typedef struct kabi_symtypes kabi_symtypes;
+typedef struct kabi_symvers kabi_symvers;
+typedef struct kabi_filter kabi_filter;
+typedef struct kabi_rules kabi_rules;
+typedef struct kabi_report kabi_report;
+
+/* Returns the description of the last error on the calling thread, or NULL. */
+const char *kabi_last_error(void);
+
+/* Loads symtypes data from a file, directory or tar archive, using the given number of workers. */
+kabi_symtypes *kabi_symtypes_load(const char *path, int jobs);
+void kabi_symtypes_free(kabi_symtypes *symtypes);
+
+/* Loads symvers data from a file. */
+kabi_symvers *kabi_symvers_load(const char *path);
+void kabi_symvers_free(kabi_symvers *symvers);
+
+/* Loads filter patterns from a file, one pattern per line. */
+kabi_filter *kabi_filter_load(const char *path);
+void kabi_filter_free(kabi_filter *filter);
+
+/* Loads severity rules from a file, optionally restricted to the given architecture. */
+kabi_rules *kabi_rules_load(const char *path, const char *arch);
+void kabi_rules_free(kabi_rules *rules);
+
+/*
+ * Compares two corpuses. The filter, rules and format arguments can be NULL. The format takes the
+ * same names as the --format option of the tools and defaults to "pretty".
+ */
+kabi_report *kabi_compare(const kabi_symtypes *symtypes, const kabi_symtypes *symtypes2,
+                          const kabi_filter *filter, const char *format, int jobs);
+kabi_report *kabi_symvers_compare(const kabi_symvers *symvers, const kabi_symvers *symvers2,
+                                  const kabi_filter *filter, const kabi_rules *rules,
+                                  const char *format);
+
+/* Returns the outcome of a comparison as one of the KABI_STATUS_* values. */
+int kabi_report_status(const kabi_report *report);
+/* Returns the text of a report, valid until the report is released. */
+const char *kabi_report_text(const kabi_report *report);
+void kabi_report_free(kabi_report *report);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SUSE_KABI_TOOLS_H */
@@ -0,0 +1,425 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A stable interface for using the crate as a library.
+//!
+//! The remaining modules of the crate follow the needs of the `ksymtypes` and `ksymvers` tools and
+//! their interfaces can change between releases. This module provides a small curated subset of
+//! the functionality that external projects can depend on. Its enums are marked as
+//! `#[non_exhaustive]` and its options are set through builder methods, so new variants and options
+//! can be added without breaking existing users.
+//!
+//! A typical comparison of two symtypes corpuses looks as follows:
+//!
+//! ```no_run
+//! use suse_kabi_tools::api::{CompareOptions, LoadOptions, ReportFormat, Symtypes};
+//!
+//! # fn main() -> Result<(), suse_kabi_tools::api::Error> {
+//! let load_options = LoadOptions::new().jobs(4);
+//! let reference = Symtypes::load("kabi/symtypes", &load_options)?;
+//! let new = Symtypes::load("build/symtypes", &load_options)?;
+//! let report = reference.compare(&new, &CompareOptions::new().format(ReportFormat::Short))?;
+//! print!("{}", report.text());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::burst::JobControl;
+use crate::warnings::{Warnings, WarningsMode};
+use crate::{CompareStatus, rules, symtypes, symvers, text};
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+pub use crate::Error;
+
+/// An outcome of a comparison of two corpuses.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum ComparisonStatus {
+    /// No differences were found.
+    Same,
+    /// All found differences are tolerated.
+    Tolerated,
+    /// At least one found difference is breaking.
+    Breaking,
+}
+
+impl From<CompareStatus> for ComparisonStatus {
+    fn from(status: CompareStatus) -> Self {
+        match status {
+            CompareStatus::Same => Self::Same,
+            CompareStatus::Tolerated => Self::Tolerated,
+            CompareStatus::Breaking | CompareStatus::ProtectedRemoved => Self::Breaking,
+        }
+    }
+}
+
+/// The format of the text in a [`ComparisonReport`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReportFormat {
+    /// No text.
+    Null,
+    /// Verbose human-readable output.
+    #[default]
+    Pretty,
+    /// Compact human-readable output.
+    Short,
+    /// A list of all added, removed, or modified symbols.
+    Symbols,
+    /// A list of all modified symbols only.
+    ModSymbols,
+    /// A standalone HTML report.
+    Html,
+    /// A single line with the numbers of breaking, tolerated and unchanged exports.
+    Summary,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    /// Obtains a [`ReportFormat`] matching the given format type, specified as a string.
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "null" => Ok(Self::Null),
+            "pretty" => Ok(Self::Pretty),
+            "short" => Ok(Self::Short),
+            "symbols" => Ok(Self::Symbols),
+            "mod-symbols" => Ok(Self::ModSymbols),
+            "html" => Ok(Self::Html),
+            "summary" => Ok(Self::Summary),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized format '{}'",
+                format
+            ))),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// Returns the matching symtypes comparison format.
+    fn to_symtypes(self) -> symtypes::CompareFormat {
+        match self {
+            Self::Null => symtypes::CompareFormat::Null,
+            Self::Pretty => symtypes::CompareFormat::Pretty,
+            Self::Short => symtypes::CompareFormat::Short,
+            Self::Symbols => symtypes::CompareFormat::Symbols,
+            Self::ModSymbols => symtypes::CompareFormat::ModSymbols,
+            Self::Html => symtypes::CompareFormat::Html,
+            Self::Summary => symtypes::CompareFormat::Summary,
+        }
+    }
+
+    /// Returns the matching symvers comparison format.
+    fn to_symvers(self) -> symvers::CompareFormat {
+        match self {
+            Self::Null => symvers::CompareFormat::Null,
+            Self::Pretty => symvers::CompareFormat::Pretty,
+            Self::Short => symvers::CompareFormat::Short,
+            Self::Symbols => symvers::CompareFormat::Symbols,
+            Self::ModSymbols => symvers::CompareFormat::ModSymbols,
+            Self::Html => symvers::CompareFormat::Html,
+            Self::Summary => symvers::CompareFormat::Summary,
+        }
+    }
+}
+
+/// A result of a comparison of two corpuses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComparisonReport {
+    status: ComparisonStatus,
+    text: String,
+}
+
+impl ComparisonReport {
+    /// Returns the outcome of the comparison.
+    pub fn status(&self) -> ComparisonStatus {
+        self.status
+    }
+
+    /// Returns whether at least one found difference is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.status == ComparisonStatus::Breaking
+    }
+
+    /// Returns the text of the report, formatted as requested by [`CompareOptions::format()`].
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// A set of name patterns, used to select symbols or types.
+#[derive(Debug)]
+pub struct Filter(text::Filter);
+
+impl Filter {
+    /// Loads filter patterns from the specified file, one pattern per line.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut filter = text::Filter::new();
+        filter.load(path)?;
+        Ok(Self(filter))
+    }
+
+    /// Creates a new `Filter` from the given patterns.
+    ///
+    /// Each pattern is either a literal name or a shell wildcard pattern.
+    pub fn from_patterns<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        patterns: I,
+    ) -> Result<Self, Error> {
+        let mut data = String::new();
+        for pattern in patterns {
+            data.push_str(pattern.as_ref());
+            data.push('\n');
+        }
+        let mut filter = text::Filter::new();
+        filter.load_buffer("<patterns>", data.as_bytes())?;
+        Ok(Self(filter))
+    }
+
+    /// Returns whether the given name matches any pattern in the filter.
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.matches(name)
+    }
+}
+
+/// A set of severity rules, used to tolerate changes in a symvers comparison.
+#[derive(Debug)]
+pub struct Rules(rules::Rules);
+
+impl Rules {
+    /// Loads severity rules from the specified file.
+    ///
+    /// If `maybe_arch` is provided, only the rules applicable to that architecture are in effect.
+    pub fn load<P: AsRef<Path>>(path: P, maybe_arch: Option<&str>) -> Result<Self, Error> {
+        let mut rules = rules::Rules::new();
+        if let Some(arch) = maybe_arch {
+            rules.set_arch(arch);
+        }
+        rules.load(path)?;
+        Ok(Self(rules))
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Options for loading a corpus.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoadOptions {
+    jobs: i32,
+    keep_going: bool,
+    strict: bool,
+}
+
+impl LoadOptions {
+    /// Creates new default options, which load the data using a single worker, stop at the first
+    /// invalid record and print warnings to the standard error output.
+    pub fn new() -> Self {
+        Self {
+            jobs: 1,
+            keep_going: false,
+            strict: false,
+        }
+    }
+
+    /// Sets the number of workers used to load the data.
+    pub fn jobs(mut self, jobs: i32) -> Self {
+        assert!(jobs > 0);
+        self.jobs = jobs;
+        self
+    }
+
+    /// Enables or disables continuing past invalid records and reporting all errors at the end.
+    pub fn keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+
+    /// Enables or disables turning the first warning into an error.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns the mode of handling warnings.
+    fn warnings_mode(&self) -> WarningsMode {
+        if self.strict {
+            WarningsMode::Error
+        } else {
+            WarningsMode::Print
+        }
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options for comparing two corpuses.
+#[derive(Clone, Copy, Debug)]
+pub struct CompareOptions<'a> {
+    jobs: i32,
+    format: ReportFormat,
+    maybe_filter: Option<&'a Filter>,
+    maybe_type_filter: Option<&'a Filter>,
+    maybe_ignore_types: Option<&'a Filter>,
+    maybe_rules: Option<&'a Rules>,
+}
+
+impl<'a> CompareOptions<'a> {
+    /// Creates new default options, which compare all symbols using a single worker and produce a
+    /// report in the [`ReportFormat::Pretty`] format.
+    pub fn new() -> Self {
+        Self {
+            jobs: 1,
+            format: ReportFormat::Pretty,
+            maybe_filter: None,
+            maybe_type_filter: None,
+            maybe_ignore_types: None,
+            maybe_rules: None,
+        }
+    }
+
+    /// Sets the number of workers used to perform the comparison.
+    pub fn jobs(mut self, jobs: i32) -> Self {
+        assert!(jobs > 0);
+        self.jobs = jobs;
+        self
+    }
+
+    /// Sets the format of the report text.
+    pub fn format(mut self, format: ReportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Restricts the comparison to the symbols matching the filter.
+    pub fn filter(mut self, filter: &'a Filter) -> Self {
+        self.maybe_filter = Some(filter);
+        self
+    }
+
+    /// Restricts the reported type changes to the types matching the filter.
+    ///
+    /// The option applies only to symtypes comparisons.
+    pub fn type_filter(mut self, filter: &'a Filter) -> Self {
+        self.maybe_type_filter = Some(filter);
+        self
+    }
+
+    /// Ignores changes in the types matching the filter.
+    ///
+    /// The option applies only to symtypes comparisons.
+    pub fn ignore_types(mut self, filter: &'a Filter) -> Self {
+        self.maybe_ignore_types = Some(filter);
+        self
+    }
+
+    /// Sets the severity rules used to tolerate changes.
+    ///
+    /// For symvers comparisons, the rules apply to symbols. For symtypes comparisons, only type
+    /// rules apply.
+    pub fn rules(mut self, rules: &'a Rules) -> Self {
+        self.maybe_rules = Some(rules);
+        self
+    }
+}
+
+impl Default for CompareOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A corpus of symtypes data.
+#[derive(Debug)]
+pub struct Symtypes(symtypes::SymtypesCorpus);
+
+impl Symtypes {
+    /// Loads symtypes data from the specified file, directory or tar archive.
+    pub fn load<P: AsRef<Path>>(path: P, options: &LoadOptions) -> Result<Self, Error> {
+        let mut symtypes = symtypes::SymtypesCorpus::new();
+        symtypes.set_keep_going(options.keep_going);
+        symtypes.load(
+            path,
+            Warnings::new(io::stderr(), options.warnings_mode(), None),
+            &mut JobControl::new_simple(options.jobs),
+        )?;
+        Ok(Self(symtypes))
+    }
+
+    /// Compares this corpus with another one.
+    pub fn compare(
+        &self,
+        other: &Symtypes,
+        options: &CompareOptions,
+    ) -> Result<ComparisonReport, Error> {
+        let mut text = Vec::new();
+        let status = self.0.compare_with_buffer(
+            &other.0,
+            options.maybe_filter.map(|filter| &filter.0),
+            options.maybe_type_filter.map(|filter| &filter.0),
+            options.maybe_ignore_types.map(|filter| &filter.0),
+            options.maybe_rules.map(|rules| &rules.0),
+            None,
+            &text::DiffOptions::default(),
+            symtypes::ExportGrouping::None,
+            &mut [(options.format.to_symtypes(), &mut text)],
+            &mut JobControl::new_simple(options.jobs),
+        )?;
+        Ok(ComparisonReport {
+            status: status.into(),
+            text: String::from_utf8_lossy(&text).into_owned(),
+        })
+    }
+}
+
+/// A corpus of symvers data.
+#[derive(Debug)]
+pub struct Symvers(symvers::SymversCorpus);
+
+impl Symvers {
+    /// Loads symvers data from the specified file.
+    pub fn load<P: AsRef<Path>>(path: P, options: &LoadOptions) -> Result<Self, Error> {
+        let mut symvers = symvers::SymversCorpus::new();
+        symvers.set_keep_going(options.keep_going);
+        symvers.load(path)?;
+        Ok(Self(symvers))
+    }
+
+    /// Returns the CRC of the given export, or `None` if the corpus doesn't contain the export.
+    pub fn crc(&self, name: &str) -> Option<u32> {
+        self.0.get(name).map(|info| info.crc())
+    }
+
+    /// Compares this corpus with another one.
+    pub fn compare(
+        &self,
+        other: &Symvers,
+        options: &CompareOptions,
+    ) -> Result<ComparisonReport, Error> {
+        let mut text = Vec::new();
+        let status = self.0.compare_with_buffer(
+            &other.0,
+            options.maybe_filter.map(|filter| &filter.0),
+            options.maybe_rules.map(|rules| &rules.0),
+            None,
+            None,
+            &mut [(options.format.to_symvers(), &mut text)],
+        )?;
+        Ok(ComparisonReport {
+            status: status.into(),
+            text: String::from_utf8_lossy(&text).into_owned(),
+        })
+    }
+}
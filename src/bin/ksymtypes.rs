@@ -1,158 +1,1944 @@
 // Copyright (C) 2024 SUSE LLC
 // SPDX-License-Identifier: GPL-2.0-or-later
 
+use std::io::prelude::*;
+use std::path::Path;
 use std::process::ExitCode;
 use std::str::FromStr;
-use std::{env, io, thread};
-use suse_kabi_tools::burst::JobControl;
-use suse_kabi_tools::cli::{handle_value_option, process_global_args};
-use suse_kabi_tools::symtypes::{CompareFormat, SymtypesCorpus};
-use suse_kabi_tools::text::Filter;
-use suse_kabi_tools::{Error, Timing};
-
-const USAGE_MSG: &str = concat!(
-    "Usage: ksymtypes [OPTION]... COMMAND ...\n",
-    "\n",
-    "Options:\n",
-    "  -d, --debug                   enable debug output\n",
-    "  -h, --help                    display this help and exit\n",
-    "  --version                     output version information and exit\n",
-    "\n",
-    "Commands:\n",
-    "  consolidate                   consolidate symtypes into a single file\n",
-    "  split                         split a consolidated symtypes file into\n",
-    "                                individual files\n",
-    "  compare                       show differences between two symtypes corpuses\n",
-    "\n",
-    "See 'ksymtypes COMMAND --help' for more information on a specific command.\n",
-);
-
-const CONSOLIDATE_USAGE_MSG: &str = concat!(
-    "Usage: ksymtypes consolidate -o FILE [OPTION]... PATH\n",
-    "\n",
-    "Consolidate symtypes into a single file.\n",
-    "\n",
-    "Options:\n",
-    "  -h, --help                    display this help and exit\n",
-    "  -j NUM, --jobs=NUM            use NUM workers to perform the operation\n",
-    "  -o FILE, --output=FILE        write the result in FILE\n",
-);
-
-const SPLIT_USAGE_MSG: &str = concat!(
-    "Usage: ksymtypes split -o DIR [OPTION]... PATH\n",
-    "\n",
-    "Split a consolidated symtypes file into individual files.\n",
-    "\n",
-    "Options:\n",
-    "  -h, --help                    display this help and exit\n",
-    "  -j NUM, --jobs=NUM            use NUM workers to perform the operation\n",
-    "  -o DIR, --output=DIR          write the result to DIR\n",
-);
-
-const COMPARE_USAGE_MSG: &str = concat!(
-    "Usage: ksymtypes compare [OPTION]... PATH PATH2\n",
-    "\n",
-    "Show differences between two symtypes corpuses.\n",
-    "\n",
-    "Options:\n",
-    "  -h, --help                    display this help and exit\n",
-    "  -j NUM, --jobs=NUM            use NUM workers to perform the operation\n",
-    "  --filter-symbol-list=FILE     consider only symbols matching patterns in FILE\n",
-    "  -f TYPE[:FILE], --format=TYPE[:FILE]\n",
-    "                                change the output format to TYPE, or write the\n",
-    "                                TYPE-formatted output to FILE\n",
-);
-
-/// Handles the `-j`/`--jobs` option which specifies the number of workers to perform a given
-/// operation simultaneously.
-fn handle_jobs_option<I: Iterator<Item = String>>(
-    arg: &str,
-    args: &mut I,
-) -> Result<Option<i32>, Error> {
-    if let Some(value) = handle_value_option(arg, args, "-j", "--jobs")? {
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use std::{env, fs, io, thread};
+#[cfg(feature = "browse")]
+use suse_kabi_tools::browse::{self, Browser};
+use suse_kabi_tools::burst::{JobControl, JobSlots, Jobserver};
+use suse_kabi_tools::cli::{
+    CommandSpec, FailOn, OptionSpec, ParsedArgs, ToolSpec, apply_config, process_global_args,
+};
+use suse_kabi_tools::rules::Rules;
+use suse_kabi_tools::suppressions::Suppressions;
+use suse_kabi_tools::symtypes::{
+    CompareFormat, ConsolidateOrder, DuplicateExportPolicy, DuplicatePolicy, ExportGrouping,
+    FileMetadata, SymtypesCorpus, cross_check, format_type, write_history,
+};
+use suse_kabi_tools::symvers::SymversCorpus;
+use suse_kabi_tools::text::{
+    DiffOptions, DirectoryWriter, Filter, ManifestWriter, TarWriter, WriteGenerator, Writer,
+    is_tar_path, read_lines,
+};
+use suse_kabi_tools::warnings::{Warnings, WarningsMode, WarningsSink};
+use suse_kabi_tools::{
+    Error, MapIOErr, PathFile, Phase, Progress, Timing, debug, write_timing_report,
+};
+
+/// The `-j`/`--jobs` option which specifies the number of workers to perform a given operation
+/// simultaneously.
+const JOBS_OPTIONS: &[OptionSpec] = &[OptionSpec::value(
+    "--jobs",
+    "NUM",
+    concat!(
+        "use NUM workers to perform the operation, or\n",
+        "'auto' for one per available CPU",
+    ),
+)
+.with_short("-j")];
+
+/// The `--warnings`, `--ignore-warnings-list` and `--keep-going` options.
+const WARNINGS_OPTIONS: &[OptionSpec] = &[
+    OptionSpec::value(
+        "--warnings",
+        "MODE",
+        concat!(
+            "handle warnings: 'print' them (default),\n",
+            "'ignore' them, or treat them as 'error'",
+        ),
+    ),
+    OptionSpec::value(
+        "--ignore-warnings-list",
+        "FILE",
+        "silence warnings about names listed in FILE",
+    ),
+    OptionSpec::flag(
+        "--keep-going",
+        concat!(
+            "report all parse errors instead of stopping at\n",
+            "the first one",
+        ),
+    ),
+    OptionSpec::value(
+        "--duplicates",
+        "POLICY",
+        concat!(
+            "resolve duplicate exports by keeping the 'first'\n",
+            "(default) or 'last' one, 'prefer-vmlinux', or\n",
+            "treat them as an 'error'",
+        ),
+    ),
+];
+
+/// The `-j`/`--jobs` option for commands that use one worker per available CPU by default.
+const JOBS_AUTO_OPTIONS: &[OptionSpec] = &[OptionSpec::value(
+    "--jobs",
+    "NUM",
+    concat!(
+        "use NUM workers to perform the operation, or\n",
+        "'auto' for one per available CPU (default)",
+    ),
+)
+.with_short("-j")];
+
+const CONSOLIDATE_COMMAND: CommandSpec = CommandSpec {
+    name: "consolidate",
+    summary: "consolidate symtypes into a single file",
+    synopsis: &["ksymtypes consolidate {-o FILE | --update=FILE} [OPTION]... PATH..."],
+    description: "Consolidate symtypes into a single file.",
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[
+            OptionSpec::value("--output", "FILE", "write the result in FILE").with_short("-o"),
+            OptionSpec::value(
+                "--update",
+                "FILE",
+                concat!(
+                    "reuse unmodified data from the consolidated FILE\n",
+                    "and write the result back to it, unless -o is\n",
+                    "specified",
+                ),
+            ),
+            OptionSpec::value(
+                "--on-duplicate",
+                "POLICY",
+                concat!(
+                    "resolve a file found in multiple PATHs by\n",
+                    "reporting an 'error' (default), keeping the\n",
+                    "'first' or 'last' copy, or keeping all copies\n",
+                    "and renaming the later ones ('rename')",
+                ),
+            ),
+            OptionSpec::value(
+                "--sort",
+                "ORDER",
+                concat!(
+                    "order files and records by 'path' (default),\n",
+                    "'symbol', or as the 'old-script' did",
+                ),
+            ),
+            OptionSpec::value(
+                "--max-variants",
+                "NUM",
+                "warn about types with more than NUM variants",
+            ),
+            OptionSpec::flag(
+                "--with-metadata",
+                concat!(
+                    "record the object path and other metadata of
+",
+                    "each file",
+                ),
+            ),
+            OptionSpec::value(
+                "--arch",
+                "ARCH",
+                "record ARCH as the architecture of all files",
+            ),
+            OptionSpec::value(
+                "--flavor",
+                "FLAVOR",
+                "record FLAVOR as the config flavor of all files",
+            ),
+            OptionSpec::flag(
+                "--low-memory",
+                concat!(
+                    "process the files one by one and keep type\n",
+                    "variants on disk, trading speed for memory",
+                ),
+            ),
+        ],
+    ],
+};
+
+const SPLIT_COMMAND: CommandSpec = CommandSpec {
+    name: "split",
+    summary: concat!(
+        "split a consolidated symtypes file into\n",
+        "individual files",
+    ),
+    synopsis: &["ksymtypes split -o DIR|FILE.tar [OPTION]... PATH"],
+    description: "Split a consolidated symtypes file into individual files.",
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[
+            OptionSpec::value(
+                "--output",
+                "DIR",
+                concat!(
+                    "write the result to DIR, or into a tar archive\n",
+                    "if DIR has the '.tar' extension",
+                ),
+            )
+            .with_short("-o"),
+            OptionSpec::value(
+                "--manifest",
+                "FILE",
+                concat!(
+                    "write a sorted list of the output files with\n",
+                    "their SHA-256 digests to FILE",
+                ),
+            ),
+            OptionSpec::value(
+                "--only",
+                "GLOB",
+                concat!(
+                    "extract only files matching GLOB, scanning the\n",
+                    "input without loading it (can be repeated)",
+                ),
+            ),
+        ],
+    ],
+};
+
+const COMPARE_COMMAND: CommandSpec = CommandSpec {
+    name: "compare",
+    summary: "show differences between two symtypes corpuses",
+    synopsis: &["ksymtypes compare [OPTION]... PATH PATH2"],
+    description: "Show differences between two symtypes corpuses.",
+    options: &[
+        JOBS_AUTO_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[
+            OptionSpec::value(
+                "--filter-symbol-list",
+                "FILE",
+                "consider only symbols matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--exclude-symbol-list",
+                "FILE",
+                "ignore symbols matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--filter-symbol",
+                "PATTERN",
+                "consider only symbols matching PATTERN",
+            ),
+            OptionSpec::value(
+                "--exclude-symbol",
+                "PATTERN",
+                "ignore symbols matching PATTERN",
+            ),
+            OptionSpec::value(
+                "--filter-type-list",
+                "FILE",
+                concat!("report only changed types matching patterns in\n", "FILE",),
+            ),
+            OptionSpec::value(
+                "--ignore-types",
+                "FILE",
+                "treat types matching patterns in FILE as equal",
+            ),
+            OptionSpec::value(
+                "--rules",
+                "FILE",
+                concat!(
+                    "tolerate changes in types matched by severity\n",
+                    "rules in FILE",
+                ),
+            )
+            .with_short("-r"),
+            OptionSpec::value(
+                "--arch",
+                "NAME",
+                "apply severity rules guarded by architecture NAME",
+            ),
+            OptionSpec::value(
+                "--suppressions",
+                "FILE",
+                "ignore acknowledged differences listed in FILE",
+            ),
+            OptionSpec::value(
+                "--cache-dir",
+                "DIR",
+                "cache the parsed symtypes corpuses in DIR",
+            ),
+            OptionSpec::value(
+                "--symbol-prefix",
+                "PREFIX",
+                concat!(
+                    "strip PREFIX from export names in both\n",
+                    "corpuses (default: detect '_' automatically)",
+                ),
+            ),
+            OptionSpec::value(
+                "--format",
+                "TYPE[:FILE]",
+                concat!(
+                    "change the output format to TYPE, or write the\n",
+                    "TYPE-formatted output to FILE",
+                ),
+            )
+            .with_short("-f"),
+            OptionSpec::value(
+                "--diff-context",
+                "NUM",
+                concat!(
+                    "show NUM lines of context around type changes\n",
+                    "(default 3)",
+                ),
+            ),
+            OptionSpec::flag("--word-diff", "mark changed words within type changes"),
+            OptionSpec::flag("--demangle", "show Rust export names in demangled form"),
+            OptionSpec::flag(
+                "--strict",
+                concat!(
+                    "report also cosmetic differences of types, such\n",
+                    "as glued punctuation or reordered attributes",
+                ),
+            ),
+            OptionSpec::flag(
+                "--suggest-fixup",
+                concat!(
+                    "suggest kABI workarounds for changed types\n",
+                    "(experimental)",
+                ),
+            ),
+            OptionSpec::value(
+                "--color",
+                "WHEN",
+                concat!(
+                    "colorize the pretty and short output 'always',\n",
+                    "'never', or when writing to a terminal ('auto',\n",
+                    "default)",
+                ),
+            ),
+            OptionSpec::flag(
+                "--with-header",
+                concat!(
+                    "prepend a header describing the tool, inputs and\n",
+                    "options to the pretty and short output",
+                ),
+            ),
+            OptionSpec::flag(
+                "--reproducible",
+                "omit the timestamp from the output header",
+            ),
+            OptionSpec::value(
+                "--group-by",
+                "MODE",
+                concat!(
+                    "group exports affected by a change by their\n",
+                    "'module', 'file', or list them flat ('none',\n",
+                    "default)",
+                ),
+            ),
+            OptionSpec::value(
+                "--fail-on",
+                "POLICY",
+                concat!(
+                    "exit with 1 on 'breaking' changes (default),\n",
+                    "'any' changes, or 'none'",
+                ),
+            ),
+            OptionSpec::flag(
+                "--short-circuit",
+                concat!(
+                    "stop at the first failing change and exit with 1\n",
+                    "without showing any differences",
+                ),
+            ),
+            OptionSpec::flag(
+                "--per-file",
+                "compare all records of files with the same path",
+            ),
+        ],
+    ],
+};
+
+const SHOW_COMMAND: CommandSpec = CommandSpec {
+    name: "show",
+    summary: "show the definition of an export or type",
+    synopsis: &["ksymtypes show [OPTION]... SYMBOL PATH"],
+    description: "Show the definition of an export or type.",
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[
+            OptionSpec::flag("--recursive", "show also all recursively referenced types"),
+            OptionSpec::value(
+                "--max-depth",
+                "NUM",
+                "show referenced types only up to depth NUM",
+            ),
+        ],
+    ],
+};
+
+const HISTORY_COMMAND: CommandSpec = CommandSpec {
+    name: "history",
+    summary: concat!(
+        "show how an export or type changed across\n",
+        "multiple corpuses",
+    ),
+    synopsis: &["ksymtypes history [OPTION]... SYMBOL PATH..."],
+    description: "Show how an export or type changed across multiple corpuses.",
+    options: &[JOBS_OPTIONS, WARNINGS_OPTIONS],
+};
+
+const CROSS_CHECK_COMMAND: CommandSpec = CommandSpec {
+    name: "cross-check",
+    summary: concat!(
+        "check that exports are defined consistently\n",
+        "across multiple corpuses",
+    ),
+    synopsis: &["ksymtypes cross-check [OPTION]... PATH PATH..."],
+    description: concat!(
+        "Check that exports are defined consistently across multiple corpuses, such as\n",
+        "the corpuses of individual kernel flavors.",
+    ),
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[OptionSpec::value(
+            "--filter-type-list",
+            "FILE",
+            "check only types matching patterns in FILE",
+        )],
+    ],
+};
+
+#[cfg(feature = "browse")]
+const BROWSE_COMMAND: CommandSpec = CommandSpec {
+    name: "browse",
+    summary: concat!(
+        "browse differences between two symtypes\n",
+        "corpuses interactively",
+    ),
+    synopsis: &["ksymtypes browse [OPTION]... PATH PATH2"],
+    description: concat!(
+        "Show differences between two symtypes corpuses in an interactive terminal\n",
+        "browser.",
+    ),
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[
+            OptionSpec::value(
+                "--filter-symbol-list",
+                "FILE",
+                "consider only symbols matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--filter-type-list",
+                "FILE",
+                "show only types matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--diff-context",
+                "NUM",
+                concat!(
+                    "show NUM lines of context around type changes\n",
+                    "(default 3)",
+                ),
+            ),
+            OptionSpec::flag("--word-diff", "mark changed words within type changes"),
+        ],
+    ],
+};
+
+const STATS_COMMAND: CommandSpec = CommandSpec {
+    name: "stats",
+    summary: "show summary statistics about a symtypes corpus",
+    synopsis: &["ksymtypes stats [OPTION]... PATH"],
+    description: "Show summary statistics about a symtypes corpus.",
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[OptionSpec::value(
+            "--top",
+            "NUM",
+            concat!("list NUM types with the most variants\n", "(default 10)",),
+        )],
+    ],
+};
+
+const GRAPH_COMMAND: CommandSpec = CommandSpec {
+    name: "graph",
+    summary: "output the type-reference graph of an export",
+    synopsis: &["ksymtypes graph [OPTION]... SYMBOL PATH"],
+    description: "Output the type-reference graph of an export in the Graphviz DOT format.",
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[OptionSpec::value(
+            "--output",
+            "FILE",
+            "write the result in FILE, instead of stdout",
+        )
+        .with_short("-o")],
+    ],
+};
+
+const USERS_COMMAND: CommandSpec = CommandSpec {
+    name: "users",
+    summary: "show exports that reference a type",
+    synopsis: &["ksymtypes users [OPTION]... TYPE PATH"],
+    description: "Show exports that directly or indirectly reference a type.",
+    options: &[JOBS_OPTIONS, WARNINGS_OPTIONS],
+};
+
+const UNKNOWNS_COMMAND: CommandSpec = CommandSpec {
+    name: "unknowns",
+    summary: concat!("show UNKNOWN types and exports depending on\n", "them"),
+    synopsis: &["ksymtypes unknowns [OPTION]... PATH"],
+    description: "Show types declared as UNKNOWN and exports that depend on them.",
+    options: &[JOBS_OPTIONS, WARNINGS_OPTIONS],
+};
+
+const CRC_COMMAND: CommandSpec = CommandSpec {
+    name: "crc",
+    summary: "compute CRCs of exports from their definitions",
+    synopsis: &["ksymtypes crc [OPTION]... PATH [SYMBOL]..."],
+    description: "Compute modversion CRCs of exports from their symtypes definitions.",
+    options: &[
+        JOBS_OPTIONS,
+        WARNINGS_OPTIONS,
+        &[
+            OptionSpec::value(
+                "--symvers",
+                "FILE",
+                "report exports whose CRC differs from FILE",
+            ),
+            OptionSpec::value(
+                "--output",
+                "FILE",
+                "write the result in FILE, instead of stdout",
+            )
+            .with_short("-o"),
+        ],
+    ],
+};
+
+const VALIDATE_COMMAND: CommandSpec = CommandSpec {
+    name: "validate",
+    summary: "check a symtypes corpus for problems",
+    synopsis: &["ksymtypes validate [OPTION]... PATH"],
+    description: "Check a symtypes corpus for problems and report all of them.",
+    options: &[JOBS_OPTIONS],
+};
+
+const FORMAT_COMMAND: CommandSpec = CommandSpec {
+    name: "format",
+    summary: concat!(
+        "pretty format symtypes records read from the\n",
+        "standard input",
+    ),
+    synopsis: &["ksymtypes format [OPTION]..."],
+    description: "Pretty format symtypes records read from the standard input.",
+    options: &[],
+};
+
+const TOOL: ToolSpec = ToolSpec {
+    name: "ksymtypes",
+    summary: "a tool to work with Linux-kernel symtypes files",
+    commands: &[
+        CONSOLIDATE_COMMAND,
+        SPLIT_COMMAND,
+        COMPARE_COMMAND,
+        SHOW_COMMAND,
+        HISTORY_COMMAND,
+        CROSS_CHECK_COMMAND,
+        #[cfg(feature = "browse")]
+        BROWSE_COMMAND,
+        STATS_COMMAND,
+        GRAPH_COMMAND,
+        USERS_COMMAND,
+        UNKNOWNS_COMMAND,
+        CRC_COMMAND,
+        VALIDATE_COMMAND,
+        FORMAT_COMMAND,
+    ],
+};
+
+/// Obtains the number of workers specified by the `-j`/`--jobs` option.
+fn parse_jobs_option(parsed: &ParsedArgs) -> Result<Option<i32>, Error> {
+    parsed.convert("--jobs", |value| {
+        if value == "auto" {
+            return Ok(auto_num_workers());
+        }
         match value.parse::<i32>() {
-            Ok(jobs) => {
-                if jobs < 1 {
-                    return Err(Error::new_cli(format!(
-                        "Invalid value for '{}': must be positive",
-                        arg
-                    )));
-                }
-                return Ok(Some(jobs));
-            }
-            Err(err) => {
+            Ok(jobs) if jobs < 1 => Err("must be positive".to_string()),
+            Ok(jobs) => Ok(jobs),
+            Err(err) => Err(err.to_string()),
+        }
+    })
+}
+
+/// Options that specify how to handle warnings and errors reported while reading symtypes data.
+struct WarningsOptions {
+    mode: WarningsMode,
+    maybe_filter_path: Option<String>,
+    keep_going: bool,
+    duplicate_exports: DuplicateExportPolicy,
+}
+
+impl WarningsOptions {
+    /// Obtains the `--warnings`, `--ignore-warnings-list`, `--keep-going` and `--duplicates`
+    /// options.
+    fn from_parsed(parsed: &ParsedArgs) -> Result<Self, Error> {
+        Ok(Self {
+            mode: parsed
+                .parse_value("--warnings")?
+                .unwrap_or(WarningsMode::Print),
+            maybe_filter_path: parsed.value("--ignore-warnings-list").map(str::to_string),
+            keep_going: parsed.is_set("--keep-going"),
+            duplicate_exports: parsed.parse_value("--duplicates")?.unwrap_or_default(),
+        })
+    }
+
+    /// Reads the list of names whose warnings should be silenced, if specified.
+    fn read_filter(&self, do_timing: bool) -> Result<Option<Filter>, Error> {
+        match &self.maybe_filter_path {
+            Some(filter_path) => Ok(Some(read_filter(do_timing, "warning", filter_path)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Handles the `consolidate` command which consolidates symtypes into a single file.
+fn do_consolidate<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = CONSOLIDATE_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let maybe_output = parsed.value("--output").map(str::to_string);
+    let maybe_update = parsed.value("--update").map(str::to_string);
+    let on_duplicate = parsed
+        .parse_value("--on-duplicate")?
+        .unwrap_or(DuplicatePolicy::Error);
+    let order = parsed
+        .parse_value("--sort")?
+        .unwrap_or(ConsolidateOrder::Path);
+    let maybe_max_variants = parsed.parse_number::<usize>("--max-variants")?;
+    let with_metadata = parsed.is_set("--with-metadata");
+    let mut metadata = FileMetadata::new();
+    for (option, key) in [("--arch", "arch"), ("--flavor", "flavor")] {
+        if let Some(value) = parsed.value(option) {
+            if !with_metadata {
                 return Err(Error::new_cli(format!(
-                    "Invalid value for '{}': {}",
-                    arg, err
+                    "The {} option requires --with-metadata",
+                    option
                 )));
             }
+            metadata.insert(key.to_string(), value.to_string());
+        }
+    }
+    let low_memory = parsed.is_set("--low-memory");
+    let paths = parsed.into_positionals();
+
+    let output = maybe_output
+        .or_else(|| maybe_update.clone())
+        .ok_or_else(|| Error::new_cli("The consolidate output is missing"))?;
+    if paths.is_empty() {
+        return Err(Error::new_cli("The consolidate source is missing"));
+    }
+    if maybe_update.is_some() && paths.len() > 1 {
+        return Err(Error::new_cli(
+            "The --update option cannot be used with multiple consolidate sources",
+        ));
+    }
+    if low_memory {
+        if maybe_update.is_some() {
+            return Err(Error::new_cli(
+                "The --low-memory option cannot be used together with --update",
+            ));
+        }
+        if maybe_max_variants.is_some() {
+            return Err(Error::new_cli(
+                "The --low-memory option cannot be used together with --max-variants",
+            ));
+        }
+        if paths.iter().any(|path| path == "-") {
+            return Err(Error::new_cli(
+                "The --low-memory option cannot be used with the standard input",
+            ));
+        }
+    }
+    let paths_desc = paths.join("', '");
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    // Consolidate the files one by one in the low-memory mode.
+    if low_memory {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Merge,
+            format!(
+                "Consolidating symtypes from '{}' to '{}'",
+                paths_desc, output
+            ),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .write_consolidated_low_memory(
+                &paths,
+                on_duplicate,
+                order,
+                with_metadata.then_some(&metadata),
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &output,
+            )
+            .map_err(|err| {
+                Error::new_context(
+                    format!(
+                        "Failed to consolidate symtypes from '{}' to '{}'",
+                        paths_desc, output
+                    ),
+                    err,
+                )
+            })?;
+
+        return Ok(ExitCode::from(0));
+    }
+
+    // Do the consolidation.
+    let symtypes = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Merge,
+            format!("Reading symtypes from '{}'", paths_desc),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        let result = match &maybe_update {
+            Some(update) => symtypes.load_split_incremental(
+                &paths[0],
+                update,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", paths_desc),
+                ),
+            ),
+            None if paths.len() == 1 && paths[0] == "-" => symtypes.load(
+                &paths[0],
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", paths_desc),
+                ),
+            ),
+            None if paths.len() == 1 => symtypes.load_split(
+                &paths[0],
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", paths_desc),
+                ),
+            ),
+            None => symtypes.load_split_multiple(
+                &paths,
+                on_duplicate,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", paths_desc),
+                ),
+            ),
+        };
+        result
+            .and_then(|()| match maybe_max_variants {
+                Some(max_variants) => symtypes.check_variants(
+                    max_variants,
+                    Warnings::new(
+                        io::stderr(),
+                        warnings_opts.mode,
+                        maybe_warnings_filter.as_ref(),
+                    ),
+                ),
+                None => Ok(()),
+            })
+            .map_err(|err| {
+                Error::new_context(
+                    format!("Failed to read symtypes from '{}'", paths_desc),
+                    err,
+                )
+            })?;
+        symtypes
+    };
+
+    {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing consolidated symtypes to '{}'", output),
+        );
+
+        let result = if with_metadata {
+            symtypes.write_consolidated_with_metadata(&output, order, &metadata)
+        } else {
+            symtypes.write_consolidated(&output, order)
+        };
+        result.map_err(|err| {
+            Error::new_context(
+                format!("Failed to write consolidated symtypes to '{}'", output),
+                err,
+            )
+        })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `split` command which splits a consolidated symtypes file into individual files.
+fn do_split<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = SPLIT_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let maybe_output = parsed.value("--output").map(str::to_string);
+    let maybe_manifest = parsed.value("--manifest").map(str::to_string);
+    let only_patterns = parsed
+        .values("--only")
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
+
+    let output = maybe_output.ok_or_else(|| Error::new_cli("The split output is missing"))?;
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The split source is missing"))?;
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    // Extract only the selected files in a single pass over the input, if requested.
+    if !only_patterns.is_empty() {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing split symtypes to '{}'", output),
+        );
+
+        let reader: Box<dyn Read> =
+            if path == "-" {
+                Box::new(io::stdin().lock())
+            } else {
+                Box::new(PathFile::open(&path).map_err(|err| {
+                    Error::new_io(format!("Failed to open the file '{}'", path), err)
+                })?)
+            };
+        let split_source = SplitSource::Selected(&path, reader, &only_patterns);
+        write_split(split_source, &output, maybe_manifest.as_deref())?;
+        return Ok(ExitCode::from(0));
+    }
+
+    // Do the split.
+    let symtypes = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load_consolidated(
+                &path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        symtypes
+    };
+
+    {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing split symtypes to '{}'", output),
+        );
+
+        let job_slots = new_job_slots(
+            maybe_num_workers,
+            1,
+            format!("Writing split symtypes to '{}'", output),
+        );
+        write_split(
+            SplitSource::Corpus(&symtypes, job_slots),
+            &output,
+            maybe_manifest.as_deref(),
+        )?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// The source of split symtypes files.
+enum SplitSource<'a> {
+    /// A loaded corpus, written using the given job slots.
+    Corpus(&'a SymtypesCorpus, JobSlots),
+    /// Consolidated data at the given path, read from the reader and scanned for files matching
+    /// the patterns.
+    Selected(&'a str, Box<dyn Read + 'a>, &'a [String]),
+}
+
+/// Writes the split symtypes files from `split_source` to `output`, which is either a directory or
+/// a tar archive, and optionally records a manifest of the written files in `maybe_manifest`.
+fn write_split(
+    split_source: SplitSource,
+    output: &str,
+    maybe_manifest: Option<&str>,
+) -> Result<(), Error> {
+    if is_tar_path(Path::new(output)) {
+        let err_context = |err| {
+            Error::new_context(
+                format!("Failed to write split symtypes to '{}'", output),
+                err,
+            )
         };
+        let mut tar_writer =
+            TarWriter::new(Writer::new_compressed_file(output).map_err(err_context)?);
+        write_split_to(split_source, output, &mut tar_writer, maybe_manifest)?;
+        tar_writer
+            .finish()
+            .and_then(|writer| writer.finish())
+            .map_err(err_context)
+    } else {
+        let mut dir_writer = DirectoryWriter::new_file(output);
+        write_split_to(split_source, output, &mut dir_writer, maybe_manifest)
+    }
+}
+
+/// Writes the split symtypes files from `split_source` to the provided output stream factory for
+/// `output`, and optionally records a manifest of the written files in `maybe_manifest`.
+fn write_split_to<W: Write, WG: WriteGenerator<W> + Send>(
+    split_source: SplitSource,
+    output: &str,
+    dir_writer: WG,
+    maybe_manifest: Option<&str>,
+) -> Result<(), Error> {
+    let err_context = |err| {
+        Error::new_context(
+            format!("Failed to write split symtypes to '{}'", output),
+            err,
+        )
+    };
+
+    // A helper function to write the files to the given output stream factory.
+    fn write_files<W: Write, WG: WriteGenerator<W> + Send>(
+        split_source: SplitSource,
+        dir_writer: WG,
+    ) -> Result<(), Error> {
+        match split_source {
+            SplitSource::Corpus(symtypes, mut job_slots) => {
+                symtypes.write_split_buffer(dir_writer, &mut job_slots)
+            }
+            SplitSource::Selected(path, reader, patterns) => {
+                SymtypesCorpus::split_selected_buffer(path, reader, patterns, dir_writer)
+            }
+        }
+    }
+
+    match maybe_manifest {
+        Some(manifest) => {
+            let mut manifest_writer = ManifestWriter::new(dir_writer);
+            write_files(split_source, &mut manifest_writer).map_err(err_context)?;
+            manifest_writer.write_manifest(manifest).map_err(|err| {
+                Error::new_context(
+                    format!("Failed to write the manifest to '{}'", manifest),
+                    err,
+                )
+            })
+        }
+        None => write_files(split_source, dir_writer).map_err(err_context),
+    }
+}
+
+/// Returns the number of workers selected by `-j auto`, which is the number of CPUs available to
+/// the process.
+fn auto_num_workers() -> i32 {
+    thread::available_parallelism().map_or(1, |num| num.get().min(i32::MAX as usize) as i32)
+}
+
+/// Creates a job controller for running up to `maybe_num_workers` jobs.
+///
+/// If the number of workers is not specified and the tool is invoked by GNU make with an available
+/// jobserver, the controller joins the jobserver so that the tool shares the job slots with the
+/// rest of the build. Otherwise, `default_num_workers` are used.
+fn new_job_control(
+    maybe_num_workers: Option<i32>,
+    default_num_workers: i32,
+) -> Arc<Mutex<JobControl>> {
+    if maybe_num_workers.is_none() {
+        match Jobserver::from_env() {
+            Ok(Some(jobserver)) => return JobControl::new_jobserver(i32::MAX, jobserver),
+            Ok(None) => {}
+            // Make closes the jobserver descriptors for commands that are not marked as
+            // recursive, which is not an error.
+            Err(err) => debug!("Not using the make jobserver: {}", err),
+        }
+    }
+    JobControl::new(maybe_num_workers.unwrap_or(default_num_workers))
+}
+
+/// Creates job slots for running up to `maybe_num_workers` jobs, or `default_num_workers` if not
+/// specified, reporting the progress of the operation described by `desc` if progress reporting is
+/// enabled.
+fn new_job_slots<S: Into<String>>(
+    maybe_num_workers: Option<i32>,
+    default_num_workers: i32,
+    desc: S,
+) -> JobSlots {
+    let job_control_rc = new_job_control(maybe_num_workers, default_num_workers);
+    let mut job_slots = JobControl::new_slots(&job_control_rc, 1);
+    job_slots.set_progress(Progress::callback(desc));
+    job_slots
+}
+
+/// Reads filters of the given kind, such as "symbol" or "type", from the specified file.
+fn read_filter(do_timing: bool, kind: &str, path: &str) -> Result<Filter, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading {} filters from '{}'", kind, path),
+    );
+
+    let mut filter = Filter::new();
+    filter.load(path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read {} filters from '{}'", kind, path),
+            err,
+        )
+    })?;
+    Ok(filter)
+}
+
+/// Reads exclusion patterns from the specified file into an existing filter.
+fn read_exclusions(
+    do_timing: bool,
+    kind: &str,
+    filter: &mut Filter,
+    path: &str,
+) -> Result<(), Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading {} exclusions from '{}'", kind, path),
+    );
+
+    filter.load_exclusions(path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read {} exclusions from '{}'", kind, path),
+            err,
+        )
+    })
+}
+
+/// Reads acknowledged differences from the specified file.
+fn read_suppressions(do_timing: bool, path: &str) -> Result<Suppressions, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading suppressions from '{}'", path),
+    );
+
+    let mut suppressions = Suppressions::new();
+    suppressions.load(path).map_err(|err| {
+        Error::new_context(format!("Failed to read suppressions from '{}'", path), err)
+    })?;
+    Ok(suppressions)
+}
+
+/// Reads severity rules from the specified path.
+///
+/// The `keep_going` flag selects whether all parse errors are collected.
+fn read_rules(
+    do_timing: bool,
+    path: &str,
+    maybe_arch: Option<String>,
+    keep_going: bool,
+) -> Result<Rules, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading severity rules from '{}'", path),
+    );
+
+    let mut rules = Rules::new();
+    rules.set_keep_going(keep_going);
+    if let Some(arch) = maybe_arch {
+        rules.set_arch(arch);
+    }
+    rules.load(path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read severity rules from '{}'", path),
+            err,
+        )
+    })?;
+    Ok(rules)
+}
+
+/// Reads a symtypes corpus from the specified path, optionally using a cache in the given
+/// directory.
+///
+/// If a valid cache for the path exists, the corpus is loaded from it. Otherwise, the symtypes data
+/// is parsed, reporting any warnings to `warnings`, and the cache is written for later use.
+/// The `keep_going` flag selects whether all parse errors are collected and `duplicate_exports`
+/// selects how duplicate exports are resolved. The policy is a part of the cache file name.
+#[allow(clippy::too_many_arguments)]
+fn read_symtypes_cached<W: WarningsSink + Send>(
+    do_timing: bool,
+    path: &str,
+    maybe_cache_dir: Option<&str>,
+    keep_going: bool,
+    duplicate_exports: DuplicateExportPolicy,
+    warnings: W,
+    job_slots: &mut JobSlots,
+) -> Result<SymtypesCorpus, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading symtypes from '{}'", path),
+    );
+
+    job_slots.set_progress(Progress::callback(format!(
+        "Reading symtypes from '{}'",
+        path
+    )));
+
+    let err_context =
+        |err| Error::new_context(format!("Failed to read symtypes from '{}'", path), err);
+
+    // The standard input cannot be cached. A non-default duplicate export policy can result in
+    // a different corpus, which is cached separately.
+    let maybe_cache_path = match maybe_cache_dir {
+        Some(cache_dir) if path != "-" => {
+            let mut cache_name = SymtypesCorpus::cache_file_name(path).map_err(err_context)?;
+            if duplicate_exports != DuplicateExportPolicy::default() {
+                cache_name = format!("{}.{}", duplicate_exports, cache_name);
+            }
+            Some(Path::new(cache_dir).join(cache_name))
+        }
+        _ => None,
+    };
+
+    if let Some(cache_path) = &maybe_cache_path
+        && let Some(symtypes) = SymtypesCorpus::load_cache(cache_path, path).map_err(|err| {
+            Error::new_context(
+                format!(
+                    "Failed to read the symtypes cache '{}'",
+                    cache_path.display()
+                ),
+                err,
+            )
+        })?
+    {
+        return Ok(symtypes);
+    }
+
+    let mut symtypes = SymtypesCorpus::new();
+    symtypes.set_keep_going(keep_going);
+    symtypes.set_duplicate_exports(duplicate_exports);
+    symtypes
+        .load(path, warnings, job_slots)
+        .map_err(err_context)?;
+
+    if let Some(cache_path) = &maybe_cache_path {
+        let err_context = |err| {
+            Error::new_context(
+                format!(
+                    "Failed to write the symtypes cache '{}'",
+                    cache_path.display()
+                ),
+                err,
+            )
+        };
+        fs::create_dir_all(cache_path.parent().unwrap()).map_err(|err| {
+            err_context(Error::new_io(
+                format!(
+                    "Failed to create the directory '{}'",
+                    cache_path.parent().unwrap().display()
+                ),
+                err,
+            ))
+        })?;
+        symtypes.save_cache(cache_path, path).map_err(err_context)?;
+    }
+
+    Ok(symtypes)
+}
+
+/// Restricts a symtypes corpus read from a kernel build directory to symbols that are actually
+/// exported by the final build.
+///
+/// If the `path` is a directory containing `Module.symvers` or `vmlinux.symvers`, exports not
+/// listed in these files are removed from `symtypes`. This skips objects that were compiled but
+/// not linked into vmlinux or any module. Otherwise, the corpus is left unchanged.
+fn restrict_to_build_exports(
+    do_timing: bool,
+    path: &str,
+    symtypes: &mut SymtypesCorpus,
+) -> Result<(), Error> {
+    if !Path::new(path).is_dir() {
+        return Ok(());
+    }
+
+    let mut symvers = SymversCorpus::new();
+    let mut found = false;
+    for file_name in ["Module.symvers", "vmlinux.symvers"] {
+        let symvers_path = Path::new(path).join(file_name);
+        if !symvers_path.is_file() {
+            continue;
+        }
+
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symvers from '{}'", symvers_path.display()),
+        );
+        symvers.merge(&symvers_path).map_err(|err| {
+            Error::new_context(
+                format!("Failed to read symvers from '{}'", symvers_path.display()),
+                err,
+            )
+        })?;
+        found = true;
+    }
+
+    if found {
+        symtypes.retain_exports(|name| symvers.contains_export(name));
+    }
+    Ok(())
+}
+
+/// Strips a symbol prefix from the export names of two corpuses that are about to be compared.
+///
+/// If `maybe_symbol_prefix` is specified, the prefix is stripped from both corpuses. Otherwise, the
+/// `_` prefix is detected automatically and stripped from a corpus only if all its exports have it
+/// and the other corpus doesn't use it.
+fn normalize_symbol_prefix(
+    symtypes: &mut SymtypesCorpus,
+    symtypes2: &mut SymtypesCorpus,
+    maybe_symbol_prefix: Option<&str>,
+) -> Result<(), Error> {
+    match maybe_symbol_prefix {
+        Some(prefix) => {
+            symtypes.strip_symbol_prefix(prefix)?;
+            symtypes2.strip_symbol_prefix(prefix)?;
+        }
+        None => {
+            const AUTO_PREFIX: &str = "_";
+            let has_prefix = symtypes.has_symbol_prefix(AUTO_PREFIX);
+            let has_prefix2 = symtypes2.has_symbol_prefix(AUTO_PREFIX);
+            if has_prefix && !has_prefix2 {
+                symtypes.strip_symbol_prefix(AUTO_PREFIX)?;
+            } else if has_prefix2 && !has_prefix {
+                symtypes2.strip_symbol_prefix(AUTO_PREFIX)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles the `compare` command which shows differences between two symtypes corpuses.
+fn do_compare<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = COMPARE_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let maybe_symbol_filter_path = parsed.value("--filter-symbol-list").map(str::to_string);
+    let maybe_symbol_exclude_path = parsed.value("--exclude-symbol-list").map(str::to_string);
+    let symbol_patterns = parsed
+        .values("--filter-symbol")
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let symbol_exclusions = parsed
+        .values("--exclude-symbol")
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let maybe_type_filter_path = parsed.value("--filter-type-list").map(str::to_string);
+    let maybe_ignore_types_path = parsed.value("--ignore-types").map(str::to_string);
+    let maybe_rules_path = parsed.value("--rules").map(str::to_string);
+    let maybe_arch = parsed.value("--arch").map(str::to_string);
+    let maybe_suppressions_path = parsed.value("--suppressions").map(str::to_string);
+    let maybe_cache_dir = parsed.value("--cache-dir").map(str::to_string);
+    let maybe_symbol_prefix = parsed.value("--symbol-prefix").map(str::to_string);
+    let mut writers_conf = vec![(CompareFormat::Pretty, "-".to_string())];
+    for value in parsed.values("--format") {
+        match value.split_once(':') {
+            Some((format, path)) => {
+                writers_conf.push((CompareFormat::from_str(format)?, path.to_string()))
+            }
+            None => writers_conf[0].0 = CompareFormat::from_str(value)?,
+        }
+    }
+    let format_specified = parsed.is_set("--format");
+    let mut diff_options = DiffOptions::default();
+    if let Some(context_size) = parsed.parse_number::<usize>("--diff-context")? {
+        diff_options.context_size = context_size;
+    }
+    diff_options.word_diff = parsed.is_set("--word-diff");
+    let demangle = parsed.is_set("--demangle");
+    let strict = parsed.is_set("--strict");
+    let suggest_fixup = parsed.is_set("--suggest-fixup");
+    let color = parsed.parse_value("--color")?.unwrap_or_default();
+    let maybe_output_header = parsed.is_set("--with-header").then(|| {
+        let maybe_time = (!parsed.is_set("--reproducible")).then(SystemTime::now);
+        parsed.output_header("ksymtypes", env!("SUSE_KABI_TOOLS_VERSION"), maybe_time)
+    });
+    let grouping = parsed
+        .parse_value("--group-by")?
+        .unwrap_or(ExportGrouping::None);
+    let fail_on = parsed.parse_value("--fail-on")?.unwrap_or(FailOn::Breaking);
+    let short_circuit = parsed.is_set("--short-circuit");
+    let per_file = parsed.is_set("--per-file");
+    parsed.check_max_positionals(2)?;
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_path = positionals.next();
+    let maybe_path2 = positionals.next();
+
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The first compare source is missing"))?;
+    let path2 =
+        maybe_path2.ok_or_else(|| Error::new_cli("The second compare source is missing"))?;
+    if path == "-" && path2 == "-" {
+        return Err(Error::new_cli(
+            "Only one compare source can be read from the standard input",
+        ));
+    }
+    if per_file
+        && writers_conf.iter().any(|(format, _)| {
+            *format == CompareFormat::Html
+                || *format == CompareFormat::Summary
+                || *format == CompareFormat::Json
+        })
+    {
+        return Err(Error::new_cli(
+            "The html, summary and json formats cannot be used with '--per-file'",
+        ));
+    }
+    if per_file && maybe_rules_path.is_some() {
+        return Err(Error::new_cli(
+            "The --rules option cannot be used together with --per-file",
+        ));
+    }
+    if short_circuit {
+        if per_file {
+            return Err(Error::new_cli(
+                "The --short-circuit option cannot be used together with --per-file",
+            ));
+        }
+        if format_specified {
+            return Err(Error::new_cli(
+                "The --short-circuit option cannot be used together with --format",
+            ));
+        }
+        if fail_on.failing_status().is_none() {
+            return Err(Error::new_cli(
+                "The --short-circuit option cannot be used together with --fail-on=none",
+            ));
+        }
+    }
+
+    let mut maybe_symbol_filter = match maybe_symbol_filter_path {
+        Some(symbol_filter_path) => Some(read_filter(do_timing, "symbol", &symbol_filter_path)?),
+        None => None,
+    };
+    if let Some(symbol_exclude_path) = maybe_symbol_exclude_path {
+        read_exclusions(
+            do_timing,
+            "symbol",
+            maybe_symbol_filter.get_or_insert_with(Filter::new),
+            &symbol_exclude_path,
+        )?;
+    }
+    for pattern in &symbol_patterns {
+        maybe_symbol_filter
+            .get_or_insert_with(Filter::new)
+            .add_pattern(pattern);
+    }
+    for pattern in &symbol_exclusions {
+        maybe_symbol_filter
+            .get_or_insert_with(Filter::new)
+            .add_exclusion(pattern);
+    }
+
+    let maybe_type_filter = match maybe_type_filter_path {
+        Some(type_filter_path) => Some(read_filter(do_timing, "type", &type_filter_path)?),
+        None => None,
+    };
+
+    let maybe_ignore_types = match maybe_ignore_types_path {
+        Some(ignore_types_path) => {
+            Some(read_filter(do_timing, "ignored type", &ignore_types_path)?)
+        }
+        None => None,
+    };
+
+    let maybe_rules = match maybe_rules_path {
+        Some(rules_path) => Some(read_rules(
+            do_timing,
+            &rules_path,
+            maybe_arch,
+            warnings_opts.keep_going,
+        )?),
+        None => None,
+    };
+
+    let maybe_suppressions = match maybe_suppressions_path {
+        Some(suppressions_path) => Some(read_suppressions(do_timing, &suppressions_path)?),
+        None => None,
+    };
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    // Reserve a slot for each reader if possible. Otherwise, the second reader waits for a slot
+    // in `ensure_one_reserved()`.
+    let job_control_rc = new_job_control(maybe_num_workers, auto_num_workers());
+    let job_slots = JobControl::new_slots(&job_control_rc, 1);
+    let reserved2 = job_control_rc.lock().unwrap().reservable().min(1);
+    let job_slots2 = JobControl::new_slots(&job_control_rc, reserved2);
+
+    let (mut symtypes, mut symtypes2) = thread::scope(|scope| {
+        let read_thread = scope.spawn(|| {
+            let mut job_slots = job_slots;
+            job_slots.ensure_one_reserved();
+
+            let mut symtypes = read_symtypes_cached(
+                do_timing,
+                &path,
+                maybe_cache_dir.as_deref(),
+                warnings_opts.keep_going,
+                warnings_opts.duplicate_exports,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut job_slots,
+            )?;
+            restrict_to_build_exports(do_timing, &path, &mut symtypes)?;
+            Ok::<_, Error>(symtypes)
+        });
+
+        let read_thread2 = scope.spawn(|| {
+            let mut job_slots2 = job_slots2;
+            job_slots2.ensure_one_reserved();
+
+            let mut symtypes = read_symtypes_cached(
+                do_timing,
+                &path2,
+                maybe_cache_dir.as_deref(),
+                warnings_opts.keep_going,
+                warnings_opts.duplicate_exports,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut job_slots2,
+            )?;
+            restrict_to_build_exports(do_timing, &path2, &mut symtypes)?;
+            Ok::<_, Error>(symtypes)
+        });
+
+        let symtypes = read_thread.join().unwrap()?;
+        let symtypes2 = read_thread2.join().unwrap()?;
+
+        Ok((symtypes, symtypes2))
+    })?;
+
+    normalize_symbol_prefix(
+        &mut symtypes,
+        &mut symtypes2,
+        maybe_symbol_prefix.as_deref(),
+    )
+    .map_err(|err| {
+        Error::new_context(
+            format!(
+                "Failed to normalize symbol names of '{}' and '{}'",
+                path, path2
+            ),
+            err,
+        )
+    })?;
+    symtypes.set_demangle(demangle);
+    symtypes.set_strict(strict);
+    symtypes.set_suggest_fixup(suggest_fixup);
+    symtypes.set_color(color);
+    symtypes.set_output_header(maybe_output_header);
+
+    // Both corpuses are held in memory for the whole comparison, which dominates the peak usage.
+    {
+        let _timing = Timing::new(do_timing, Phase::Analyze, "Optimizing symtypes");
+
+        thread::scope(|scope| {
+            scope.spawn(|| symtypes2.optimize());
+            symtypes.optimize();
+        });
+    }
+
+    let status = {
+        let _timing = Timing::new(do_timing, Phase::Compare, "Comparison");
+
+        if short_circuit {
+            symtypes.compare_status(
+                &symtypes2,
+                maybe_symbol_filter.as_ref(),
+                maybe_type_filter.as_ref(),
+                maybe_ignore_types.as_ref(),
+                maybe_rules.as_ref(),
+                maybe_suppressions.as_ref(),
+                &mut new_job_slots(maybe_num_workers, auto_num_workers(), "Comparison"),
+            )
+        } else if per_file {
+            symtypes.compare_files_with(
+                &symtypes2,
+                maybe_symbol_filter.as_ref(),
+                maybe_type_filter.as_ref(),
+                maybe_ignore_types.as_ref(),
+                &diff_options,
+                &writers_conf[..],
+            )
+        } else {
+            symtypes.compare_with(
+                &symtypes2,
+                maybe_symbol_filter.as_ref(),
+                maybe_type_filter.as_ref(),
+                maybe_ignore_types.as_ref(),
+                maybe_rules.as_ref(),
+                maybe_suppressions.as_ref(),
+                &diff_options,
+                grouping,
+                &writers_conf[..],
+                &mut new_job_slots(maybe_num_workers, auto_num_workers(), "Comparison"),
+            )
+        }
+        .map_err(|err| {
+            Error::new_context(
+                format!("Failed to compare symtypes from '{}' and '{}'", path, path2),
+                err,
+            )
+        })?
+    };
+
+    Ok(fail_on.exit_code(status))
+}
+
+/// Handles the `show` command which shows the definition of an export or type.
+fn do_show<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = SHOW_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let recursive = parsed.is_set("--recursive");
+    let mut maybe_max_depth = parsed.parse_number::<usize>("--max-depth")?;
+    parsed.check_max_positionals(2)?;
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_symbol = positionals.next();
+    let maybe_path = positionals.next();
+
+    let symbol = maybe_symbol.ok_or_else(|| Error::new_cli("The symbol to show is missing"))?;
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The show source is missing"))?;
+
+    // The maximum depth implies the recursive mode, which is otherwise unlimited.
+    if recursive && maybe_max_depth.is_none() {
+        maybe_max_depth = Some(usize::MAX);
+    }
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    let symtypes = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load(
+                &path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        symtypes
+    };
+
+    let found = {
+        let _timing = Timing::new(do_timing, Phase::Analyze, format!("Showing '{}'", symbol));
+
+        symtypes
+            .show(&symbol, maybe_max_depth, "-")
+            .map_err(|err| Error::new_context(format!("Failed to show '{}'", symbol), err))?
+    };
+    if !found {
+        return Err(Error::new_cli(format!(
+            "Symbol '{}' is not found in '{}'",
+            symbol, path
+        )));
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `history` command which shows how an export or type changed across multiple
+/// corpuses.
+fn do_history<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = HISTORY_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_symbol = positionals.next();
+    let mut paths = positionals.collect::<Vec<_>>();
+
+    let symbol =
+        maybe_symbol.ok_or_else(|| Error::new_cli("The symbol to show history of is missing"))?;
+    if paths.is_empty() {
+        return Err(Error::new_cli("The history source is missing"));
+    }
+    if paths.iter().filter(|&path| path == "-").count() > 1 {
+        return Err(Error::new_cli(
+            "Only one history source can be read from the standard input",
+        ));
+    }
+
+    // A single directory is a collection of consolidated symtypes files, ordered by their name.
+    if paths.len() == 1 && Path::new(&paths[0]).is_dir() {
+        let dir = paths.pop().unwrap();
+        let dir_iter = fs::read_dir(&dir)
+            .map_err(|err| Error::new_io(format!("Failed to read the directory '{}'", dir), err))?;
+        for entry in dir_iter {
+            let entry = entry.map_err(|err| {
+                Error::new_io(format!("Failed to read the directory '{}'", dir), err)
+            })?;
+            if entry.path().is_file() {
+                paths.push(entry.path().to_string_lossy().into_owned());
+            }
+        }
+        paths.sort();
+    }
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    let corpuses = paths.iter().map(|path| {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load(
+                path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        Ok((path.clone(), symtypes))
+    });
+
+    // Errors are not wrapped in an additional context as the loading of each corpus already
+    // provides its own.
+    write_history(&symbol, corpuses, "-")?;
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `cross-check` command which checks that exports are defined consistently across
+/// multiple symtypes corpuses.
+fn do_cross_check<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = CROSS_CHECK_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let maybe_type_filter_path = parsed.value("--filter-type-list").map(str::to_string);
+    let paths = parsed.into_positionals();
+
+    if paths.len() < 2 {
+        return Err(Error::new_cli(
+            "The cross-check requires at least two sources",
+        ));
+    }
+    if paths.iter().filter(|&path| path == "-").count() > 1 {
+        return Err(Error::new_cli(
+            "Only one cross-check source can be read from the standard input",
+        ));
+    }
+
+    let maybe_type_filter = match maybe_type_filter_path {
+        Some(type_filter_path) => Some(read_filter(do_timing, "type", &type_filter_path)?),
+        None => None,
+    };
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    // Read all corpuses.
+    let mut corpuses = Vec::new();
+    for path in &paths {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load(
+                path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        corpuses.push((path.clone(), symtypes));
+    }
+
+    // Check the corpuses.
+    let has_differences = {
+        let _timing = Timing::new(do_timing, Phase::Compare, "Cross-check");
+
+        cross_check(&corpuses, maybe_type_filter.as_ref(), "-")
+            .map_err(|err| Error::new_context("Failed to cross-check symtypes", err))?
+    };
+
+    Ok(ExitCode::from(if has_differences { 1 } else { 0 }))
+}
+
+/// Handles the `browse` command which shows differences between two symtypes corpuses in an
+/// interactive terminal browser.
+#[cfg(feature = "browse")]
+fn do_browse<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = BROWSE_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let maybe_symbol_filter_path = parsed.value("--filter-symbol-list").map(str::to_string);
+    let maybe_type_filter_path = parsed.value("--filter-type-list").map(str::to_string);
+    let mut diff_options = DiffOptions::default();
+    if let Some(context_size) = parsed.parse_number::<usize>("--diff-context")? {
+        diff_options.context_size = context_size;
+    }
+    diff_options.word_diff = parsed.is_set("--word-diff");
+    parsed.check_max_positionals(2)?;
+    let mut paths = parsed.into_positionals().into_iter();
+    let path = paths
+        .next()
+        .ok_or_else(|| Error::new_cli("The first browse source is missing"))?;
+    let path2 = paths
+        .next()
+        .ok_or_else(|| Error::new_cli("The second browse source is missing"))?;
+    if path == "-" && path2 == "-" {
+        return Err(Error::new_cli(
+            "Only one browse source can be read from the standard input",
+        ));
     }
 
-    Ok(None)
-}
+    let maybe_symbol_filter = match maybe_symbol_filter_path {
+        Some(symbol_filter_path) => Some(read_filter(do_timing, "symbol", &symbol_filter_path)?),
+        None => None,
+    };
+    let maybe_type_filter = match maybe_type_filter_path {
+        Some(type_filter_path) => Some(read_filter(do_timing, "type", &type_filter_path)?),
+        None => None,
+    };
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
 
-/// Handles the `consolidate` command which consolidates symtypes into a single file.
-fn do_consolidate<I: IntoIterator<Item = String>>(
-    do_timing: bool,
-    args: I,
-) -> Result<ExitCode, Error> {
-    // Parse specific command options.
-    let mut args = args.into_iter();
-    let mut num_workers = 1;
-    let mut maybe_output = None;
-    let mut past_dash_dash = false;
-    let mut maybe_path = None;
-
-    while let Some(arg) = args.next() {
-        if !past_dash_dash {
-            if let Some(value) = handle_jobs_option(&arg, &mut args)? {
-                num_workers = value;
-                continue;
-            }
-            if let Some(value) = handle_value_option(&arg, &mut args, "-o", "--output")? {
-                maybe_output = Some(value);
-                continue;
-            }
-            if arg == "-h" || arg == "--help" {
-                print!("{}", CONSOLIDATE_USAGE_MSG);
-                return Ok(ExitCode::from(0));
-            }
-            if arg == "--" {
-                past_dash_dash = true;
-                continue;
-            }
-            if arg.starts_with('-') {
-                return Err(Error::new_cli(format!(
-                    "Unrecognized consolidate option '{}'",
-                    arg
-                )));
-            }
-        }
+    // Read both corpuses.
+    let mut corpuses = Vec::new();
+    for path in [&path, &path2] {
+        corpuses.push(read_symtypes_cached(
+            do_timing,
+            path,
+            None,
+            warnings_opts.keep_going,
+            warnings_opts.duplicate_exports,
+            Warnings::new(
+                io::stderr(),
+                warnings_opts.mode,
+                maybe_warnings_filter.as_ref(),
+            ),
+            &mut new_job_slots(
+                maybe_num_workers,
+                auto_num_workers(),
+                format!("Reading symtypes from '{}'", path),
+            ),
+        )?);
+    }
 
-        if maybe_path.is_none() {
-            maybe_path = Some(arg);
-            continue;
+    // The corpuses are kept in memory for the whole browsing session.
+    {
+        let _timing = Timing::new(do_timing, Phase::Analyze, "Optimizing symtypes");
+
+        for symtypes in &mut corpuses {
+            symtypes.optimize();
         }
-        return Err(Error::new_cli(format!(
-            "Excess consolidate argument '{}' specified",
-            arg
-        )));
     }
 
-    let output = maybe_output.ok_or_else(|| Error::new_cli("The consolidate output is missing"))?;
-    let path = maybe_path.ok_or_else(|| Error::new_cli("The consolidate source is missing"))?;
+    let result = {
+        let _timing = Timing::new(do_timing, Phase::Compare, "Comparison");
+
+        corpuses[0]
+            .compare(
+                &corpuses[1],
+                maybe_symbol_filter.as_ref(),
+                maybe_type_filter.as_ref(),
+                None,
+                None,
+                &mut new_job_slots(maybe_num_workers, auto_num_workers(), "Comparison"),
+            )
+            .map_err(|err| {
+                Error::new_context(
+                    format!("Failed to compare symtypes from '{}' and '{}'", path, path2),
+                    err,
+                )
+            })?
+    };
+
+    let mut browser = Browser::new(result, diff_options);
+    browse::run(&mut browser)
+        .map_err(|err| Error::new_context("Failed to run the browser", err))?;
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `stats` command which shows summary statistics about a symtypes corpus.
+fn do_stats<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = STATS_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let top_count = parsed.parse_number::<usize>("--top")?.unwrap_or(10);
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
+
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The stats source is missing"))?;
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
 
-    // Do the consolidation.
     let symtypes = {
-        let _timing = Timing::new(do_timing, format!("Reading symtypes from '{}'", path));
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
 
         let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
         symtypes
-            .load_split(
+            .load(
                 &path,
-                io::stderr(),
-                &mut JobControl::new_simple(num_workers),
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
             )
             .map_err(|err| {
                 Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
@@ -161,80 +1947,200 @@ fn do_consolidate<I: IntoIterator<Item = String>>(
     };
 
     {
+        let _timing = Timing::new(do_timing, Phase::Write, "Writing statistics");
+
+        symtypes.write_stats(top_count, "-").map_err(|err| {
+            Error::new_context(format!("Failed to write statistics about '{}'", path), err)
+        })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `graph` command which outputs the type-reference graph of an export.
+fn do_graph<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = GRAPH_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let output = parsed.value("--output").unwrap_or("-").to_string();
+    parsed.check_max_positionals(2)?;
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_symbol = positionals.next();
+    let maybe_path = positionals.next();
+
+    let symbol = maybe_symbol.ok_or_else(|| Error::new_cli("The graph symbol is missing"))?;
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The graph source is missing"))?;
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    let symtypes = {
         let _timing = Timing::new(
             do_timing,
-            format!("Writing consolidated symtypes to '{}'", output),
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load(
+                &path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        symtypes
+    };
+
+    let found = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing the graph of '{}'", symbol),
         );
 
-        symtypes.write_consolidated(&output).map_err(|err| {
+        symtypes.write_graph(&symbol, &output).map_err(|err| {
             Error::new_context(
-                format!("Failed to write consolidated symtypes to '{}'", output),
+                format!("Failed to write the graph of '{}' to '{}'", symbol, output),
                 err,
             )
-        })?;
+        })?
+    };
+    if !found {
+        return Err(Error::new_cli(format!(
+            "Export '{}' is not found in '{}'",
+            symbol, path
+        )));
     }
 
     Ok(ExitCode::from(0))
 }
 
-/// Handles the `split` command which splits a consolidated symtypes file into individual files.
-fn do_split<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+/// Handles the `users` command which shows exports that reference a type.
+fn do_users<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
     // Parse specific command options.
-    let mut args = args.into_iter();
-    let mut num_workers = 1;
-    let mut maybe_output = None;
-    let mut past_dash_dash = false;
-    let mut maybe_path = None;
-
-    while let Some(arg) = args.next() {
-        if !past_dash_dash {
-            if let Some(value) = handle_jobs_option(&arg, &mut args)? {
-                num_workers = value;
-                continue;
-            }
-            if let Some(value) = handle_value_option(&arg, &mut args, "-o", "--output")? {
-                maybe_output = Some(value);
-                continue;
-            }
-            if arg == "-h" || arg == "--help" {
-                print!("{}", SPLIT_USAGE_MSG);
-                return Ok(ExitCode::from(0));
-            }
-            if arg == "--" {
-                past_dash_dash = true;
-                continue;
-            }
-            if arg.starts_with('-') {
-                return Err(Error::new_cli(format!(
-                    "Unrecognized split option '{}'",
-                    arg
-                )));
-            }
-        }
+    let Some(parsed) = USERS_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(2)?;
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_type_name = positionals.next();
+    let maybe_path = positionals.next();
 
-        if maybe_path.is_none() {
-            maybe_path = Some(arg);
-            continue;
-        }
+    let type_name = maybe_type_name.ok_or_else(|| Error::new_cli("The users type is missing"))?;
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The users source is missing"))?;
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
+
+    let symtypes = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load(
+                &path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        symtypes
+    };
+
+    let found = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Analyze,
+            format!("Finding users of '{}'", type_name),
+        );
+
+        symtypes.write_users(&type_name, "-").map_err(|err| {
+            Error::new_context(format!("Failed to show users of '{}'", type_name), err)
+        })?
+    };
+    if !found {
         return Err(Error::new_cli(format!(
-            "Excess split argument '{}' specified",
-            arg
+            "Type '{}' is not found in '{}'",
+            type_name, path
         )));
     }
 
-    let output = maybe_output.ok_or_else(|| Error::new_cli("The split output is missing"))?;
-    let path = maybe_path.ok_or_else(|| Error::new_cli("The split source is missing"))?;
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `unknowns` command which shows types declared as UNKNOWN and exports that depend
+/// on them.
+fn do_unknowns<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = UNKNOWNS_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
+
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The unknowns source is missing"))?;
+
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
 
-    // Do the split.
     let symtypes = {
-        let _timing = Timing::new(do_timing, format!("Reading symtypes from '{}'", path));
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
 
         let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
         symtypes
-            .load_consolidated(
+            .load(
                 &path,
-                io::stderr(),
-                &mut JobControl::new_simple(num_workers),
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
             )
             .map_err(|err| {
                 Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
@@ -243,180 +2149,220 @@ fn do_split<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<
     };
 
     {
-        let _timing = Timing::new(do_timing, format!("Writing split symtypes to '{}'", output));
+        let _timing = Timing::new(do_timing, Phase::Analyze, "Finding unknown types");
 
-        symtypes
-            .write_split(&output, &mut JobControl::new_simple(num_workers))
-            .map_err(|err| {
-                Error::new_context(
-                    format!("Failed to write split symtypes to '{}'", output),
-                    err,
-                )
-            })?;
+        symtypes.write_unknowns("-").map_err(|err| {
+            Error::new_context(format!("Failed to show unknown types in '{}'", path), err)
+        })?;
     }
 
     Ok(ExitCode::from(0))
 }
 
-/// Handles the `compare` command which shows differences between two symtypes corpuses.
-fn do_compare<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+/// Handles the `crc` command which computes CRCs of exports from their symtypes definitions.
+fn do_crc<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
     // Parse specific command options.
-    let mut args = args.into_iter();
-    let mut num_workers = 1;
-    let mut maybe_symbol_filter_path = None;
-    let mut writers_conf = vec![(CompareFormat::Pretty, "-".to_string())];
-    let mut past_dash_dash = false;
-    let mut maybe_path = None;
-    let mut maybe_path2 = None;
-
-    while let Some(arg) = args.next() {
-        if !past_dash_dash {
-            if let Some(value) = handle_jobs_option(&arg, &mut args)? {
-                num_workers = value;
-                continue;
-            }
-            if let Some(value) = handle_value_option(&arg, &mut args, None, "--filter-symbol-list")?
-            {
-                maybe_symbol_filter_path = Some(value);
-                continue;
-            }
-            if let Some(value) = handle_value_option(&arg, &mut args, "-f", "--format")? {
-                match value.split_once(':') {
-                    Some((format, path)) => {
-                        writers_conf.push((CompareFormat::from_str(format)?, path.to_string()))
-                    }
-                    None => writers_conf[0].0 = CompareFormat::from_str(&value)?,
-                }
-                continue;
-            }
-            if arg == "-h" || arg == "--help" {
-                print!("{}", COMPARE_USAGE_MSG);
-                return Ok(ExitCode::from(0));
-            }
-            if arg == "--" {
-                past_dash_dash = true;
-                continue;
-            }
-            if arg.starts_with('-') {
-                return Err(Error::new_cli(format!(
-                    "Unrecognized compare option '{}'",
-                    arg
-                )));
-            }
-        }
+    let Some(parsed) = CRC_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    let warnings_opts = WarningsOptions::from_parsed(&parsed)?;
+    let maybe_symvers_path = parsed.value("--symvers").map(str::to_string);
+    let output = parsed.value("--output").unwrap_or("-").to_string();
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_path = positionals.next();
+    let mut symbols = positionals.collect::<Vec<_>>();
 
-        if maybe_path.is_none() {
-            maybe_path = Some(arg);
-            continue;
-        }
-        if maybe_path2.is_none() {
-            maybe_path2 = Some(arg);
-            continue;
-        }
-        return Err(Error::new_cli(format!(
-            "Excess compare argument '{}' specified",
-            arg
-        )));
-    }
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The crc source is missing"))?;
 
-    let path = maybe_path.ok_or_else(|| Error::new_cli("The first compare source is missing"))?;
-    let path2 =
-        maybe_path2.ok_or_else(|| Error::new_cli("The second compare source is missing"))?;
+    let maybe_warnings_filter = warnings_opts.read_filter(do_timing)?;
 
-    let maybe_symbol_filter = match maybe_symbol_filter_path {
-        Some(symbol_filter_path) => {
+    let symtypes = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading symtypes from '{}'", path),
+        );
+
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_keep_going(warnings_opts.keep_going);
+        symtypes.set_duplicate_exports(warnings_opts.duplicate_exports);
+        symtypes
+            .load(
+                &path,
+                Warnings::new(
+                    io::stderr(),
+                    warnings_opts.mode,
+                    maybe_warnings_filter.as_ref(),
+                ),
+                &mut new_job_slots(
+                    maybe_num_workers,
+                    1,
+                    format!("Reading symtypes from '{}'", path),
+                ),
+            )
+            .map_err(|err| {
+                Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+            })?;
+        symtypes
+    };
+
+    let maybe_symvers = match maybe_symvers_path {
+        Some(symvers_path) => {
             let _timing = Timing::new(
                 do_timing,
-                format!("Reading symbol filters from '{}'", symbol_filter_path),
+                Phase::Load,
+                format!("Reading symvers from '{}'", symvers_path),
             );
 
-            let mut symbol_filter = Filter::new();
-            symbol_filter.load(&symbol_filter_path).map_err(|err| {
+            let mut symvers = SymversCorpus::new();
+            symvers.load(&symvers_path).map_err(|err| {
                 Error::new_context(
-                    format!(
-                        "Failed to read symbol filters from '{}'",
-                        symbol_filter_path
-                    ),
+                    format!("Failed to read symvers from '{}'", symvers_path),
                     err,
                 )
             })?;
-            Some(symbol_filter)
+            Some(symvers)
         }
         None => None,
     };
 
-    let job_control_rc = JobControl::new(num_workers);
-    let job_slots = JobControl::new_slots(&job_control_rc, 1);
-    let job_slots2 = JobControl::new_slots(&job_control_rc, if num_workers > 1 { 1 } else { 0 });
+    // Compute the CRCs of the selected exports, or of all exports if none is specified.
+    if symbols.is_empty() {
+        symtypes.for_each_export(|name, _| symbols.push(name.to_string()));
+        symbols.sort();
+    }
 
-    let (symtypes, symtypes2) = thread::scope(|scope| {
-        let read_thread = scope.spawn(|| {
-            let _timing = Timing::new(do_timing, format!("Reading symtypes from '{}'", path));
+    let _timing = Timing::new(do_timing, Phase::Analyze, "Computing CRCs");
 
-            let mut job_slots = job_slots;
-            job_slots.ensure_one_reserved();
+    let err_desc = "Failed to write CRCs";
+    let mut writer = Writer::new_file(&output)?;
+    let mut mismatch = false;
+    for symbol in &symbols {
+        let crc = symtypes.export_crc(symbol).ok_or_else(|| {
+            Error::new_cli(format!("Export '{}' is not found in '{}'", symbol, path))
+        })?;
 
-            let mut symtypes = SymtypesCorpus::new();
-            symtypes
-                .load(&path, io::stderr(), &mut job_slots)
-                .map_err(|err| {
-                    Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
-                })?;
-            Ok(symtypes)
-        });
+        match &maybe_symvers {
+            Some(symvers) => {
+                if let Some(info) = symvers.get(symbol)
+                    && info.crc() != crc
+                {
+                    writeln!(
+                        writer,
+                        "Export '{}' has CRC '{:#010x}' in symtypes but '{:#010x}' in symvers",
+                        symbol,
+                        crc,
+                        info.crc()
+                    )
+                    .map_io_err(err_desc)?;
+                    mismatch = true;
+                }
+            }
+            None => writeln!(writer, "{:#010x} {}", crc, symbol).map_io_err(err_desc)?,
+        }
+    }
+    writer.flush().map_io_err(err_desc)?;
 
-        let read_thread2 = scope.spawn(|| {
-            let _timing = Timing::new(do_timing, format!("Reading symtypes from '{}'", path2));
+    Ok(ExitCode::from(if mismatch { 1 } else { 0 }))
+}
 
-            let mut job_slots2 = job_slots2;
-            job_slots2.ensure_one_reserved();
+/// Handles the `validate` command which checks a symtypes corpus for problems.
+fn do_validate<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = VALIDATE_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_num_workers = parse_jobs_option(&parsed)?;
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
 
-            let mut symtypes2 = SymtypesCorpus::new();
-            symtypes2
-                .load(&path2, io::stderr(), &mut job_slots2)
-                .map_err(|err| {
-                    Error::new_context(format!("Failed to read symtypes from '{}'", path2), err)
-                })?;
-            Ok(symtypes2)
-        });
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The validate source is missing"))?;
 
-        let symtypes = read_thread.join().unwrap()?;
-        let symtypes2 = read_thread2.join().unwrap()?;
+    let problems = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Analyze,
+            format!("Validating symtypes from '{}'", path),
+        );
 
-        Ok((symtypes, symtypes2))
-    })?;
+        SymtypesCorpus::validate(
+            &path,
+            &mut new_job_slots(
+                maybe_num_workers,
+                1,
+                format!("Validating symtypes from '{}'", path),
+            ),
+        )
+        .map_err(|err| {
+            Error::new_context(format!("Failed to validate symtypes from '{}'", path), err)
+        })?
+    };
 
-    let is_equal = {
-        let _timing = Timing::new(do_timing, "Comparison");
+    {
+        let _timing = Timing::new(do_timing, Phase::Write, "Writing validation problems");
 
-        symtypes
-            .compare_with(
-                &symtypes2,
-                maybe_symbol_filter.as_ref(),
-                &writers_conf[..],
-                &mut JobControl::new_simple(num_workers),
-            )
-            .map_err(|err| {
-                Error::new_context(
-                    format!("Failed to compare symtypes from '{}' and '{}'", path, path2),
-                    err,
-                )
-            })?
+        let err_desc = "Failed to write validation problems";
+        let mut writer = Writer::new_file("-")?;
+        for problem in &problems {
+            writeln!(writer, "{}", problem).map_io_err(err_desc)?;
+        }
+        writer.flush().map_io_err(err_desc)?;
+    }
+
+    Ok(ExitCode::from(if problems.is_empty() { 0 } else { 1 }))
+}
+
+fn do_format<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = FORMAT_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    parsed.check_max_positionals(0)?;
+
+    let lines = {
+        let _timing = Timing::new(do_timing, Phase::Load, "Reading the standard input");
+
+        read_lines(io::stdin())
+            .map_err(|err| Error::new_io("Failed to read the standard input", err))?
     };
 
-    Ok(ExitCode::from(if is_equal { 0 } else { 1 }))
+    {
+        let _timing = Timing::new(do_timing, Phase::Analyze, "Formatting types");
+
+        let err_desc = "Failed to write the formatted types";
+        let mut writer = Writer::new_file("-")?;
+        let mut is_first = true;
+        for line in lines.iter().filter(|line| !line.trim_ascii().is_empty()) {
+            if !is_first {
+                writeln!(writer).map_io_err(err_desc)?;
+            }
+            is_first = false;
+            for pretty_line in format_type(line) {
+                writeln!(writer, "{}", pretty_line).map_io_err(err_desc)?;
+            }
+        }
+        writer.flush().map_io_err(err_desc)?;
+    }
+
+    Ok(ExitCode::from(0))
 }
 
 fn main() -> ExitCode {
     // Process global arguments.
     let mut args = env::args();
     let mut do_timing = false;
+    let mut maybe_config_path = None;
 
     let result = process_global_args(
         &mut args,
-        USAGE_MSG,
+        &TOOL,
         &format!("ksymtypes {}\n", env!("SUSE_KABI_TOOLS_VERSION")),
         &mut do_timing,
+        &mut maybe_config_path,
     );
     let command = match result {
         Ok(Some(command)) => command,
@@ -427,22 +2373,47 @@ fn main() -> ExitCode {
         }
     };
 
+    // Apply defaults from the configuration file.
+    let args = match apply_config("ksymtypes", &command, maybe_config_path.as_deref(), args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::from(2);
+        }
+    };
+
     // Process the specified command.
     let result = match command.as_str() {
         "consolidate" => do_consolidate(do_timing, args),
         "split" => do_split(do_timing, args),
         "compare" => do_compare(do_timing, args),
+        "show" => do_show(do_timing, args),
+        "history" => do_history(do_timing, args),
+        "cross-check" => do_cross_check(do_timing, args),
+        #[cfg(feature = "browse")]
+        "browse" => do_browse(do_timing, args),
+        "stats" => do_stats(do_timing, args),
+        "graph" => do_graph(do_timing, args),
+        "users" => do_users(do_timing, args),
+        "unknowns" => do_unknowns(do_timing, args),
+        "crc" => do_crc(do_timing, args),
+        "validate" => do_validate(do_timing, args),
+        "format" => do_format(do_timing, args),
         _ => Err(Error::new_cli(format!(
             "Unrecognized command '{}'",
             command
         ))),
     };
 
-    match result {
+    let code = match result {
         Ok(code) => code,
         Err(err) => {
             eprintln!("{}", err);
             ExitCode::from(2)
         }
-    }
+    };
+
+    write_timing_report();
+
+    code
 }
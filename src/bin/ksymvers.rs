@@ -1,188 +1,954 @@
 // Copyright (C) 2025 SUSE LLC
 // SPDX-License-Identifier: GPL-2.0-or-later
 
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
 use std::process::ExitCode;
 use std::str::FromStr;
-use std::{env, io};
-use suse_kabi_tools::cli::{handle_value_option, process_global_args};
+use std::time::SystemTime;
+use std::{env, io, iter, slice};
+use suse_kabi_tools::burst::JobControl;
+use suse_kabi_tools::cli::{
+    CommandSpec, FailOn, OptionSpec, ParsedArgs, ToolSpec, apply_config, process_global_args,
+};
+use suse_kabi_tools::rpm;
 use suse_kabi_tools::rules::{Rules, UsedRules};
-use suse_kabi_tools::symvers::{CompareFormat, SymversCorpus};
-use suse_kabi_tools::text::Filter;
-use suse_kabi_tools::{Error, Timing};
-
-const USAGE_MSG: &str = concat!(
-    "Usage: ksymvers [OPTION]... COMMAND ...\n",
-    "\n",
-    "Options:\n",
-    "  -d, --debug                   enable debug output\n",
-    "  -h, --help                    display this help and exit\n",
-    "  --version                     output version information and exit\n",
-    "\n",
-    "Commands:\n",
-    "  compare                       show differences between two symvers files\n",
-    "  unused-rules                  detect unused severity rules\n",
-    "\n",
-    "See 'ksymvers COMMAND --help' for more information on a specific command.\n",
-);
-
-const COMPARE_USAGE_MSG: &str = concat!(
-    "Usage: ksymvers compare [OPTION]... FILE FILE2\n",
-    "\n",
-    "Show differences between two symvers files.\n",
-    "\n",
-    "Options:\n",
-    "  -h, --help                    display this help and exit\n",
-    "  --filter-symbol-list=FILE     consider only symbols matching patterns in FILE\n",
-    "  -r FILE, --rules=FILE         load severity rules from FILE\n",
-    "  -f TYPE[:FILE], --format=TYPE[:FILE]\n",
-    "                                change the output format to TYPE, or write the\n",
-    "                                TYPE-formatted output to FILE\n",
-);
-
-const UNUSED_RULES_USAGE_MSG: &str = concat!(
-    "Usage: ksymvers unused-rules [OPTION]... FILE...\n",
-    "\n",
-    "Detect severity rules not matching any records in the specified symvers files.\n",
-    "\n",
-    "Options:\n",
-    "  -h, --help                    display this help and exit\n",
-    "  -r FILE, --rules=FILE         load severity rules from FILE\n",
-);
+use suse_kabi_tools::suppressions::Suppressions;
+use suse_kabi_tools::symtypes::SymtypesCorpus;
+use suse_kabi_tools::symvers::{CompareFormat, SymversCorpus, SymversFormat, WriteOrder};
+use suse_kabi_tools::text::{DirectoryWriter, Filter, ManifestWriter};
+use suse_kabi_tools::{CompareStatus, Error, Phase, Progress, Timing, write_timing_report};
+
+/// The `--lenient`, `--symvers-format` and `--keep-going` options, accepted by all commands.
+const SYMVERS_OPTIONS: &[OptionSpec] = &[
+    OptionSpec::flag(
+        "--lenient",
+        concat!(
+            "accept and preserve unknown trailing columns\n",
+            "in symvers records",
+        ),
+    ),
+    OptionSpec::value(
+        "--symvers-format",
+        "FORMAT",
+        concat!(
+            "parse symvers records in FORMAT: 'auto' (default),\n",
+            "'modern', or 'legacy'",
+        ),
+    ),
+    OptionSpec::flag(
+        "--keep-going",
+        concat!(
+            "report all parse errors instead of stopping at\n",
+            "the first one",
+        ),
+    ),
+];
+
+/// Options selecting severity rules and how they are applied.
+const RULES_OPTIONS: &[OptionSpec] = &[
+    OptionSpec::value("--rules", "FILE", "load severity rules from FILE").with_short("-r"),
+    OptionSpec::value(
+        "--arch",
+        "NAME",
+        "apply severity rules guarded by architecture NAME",
+    ),
+    OptionSpec::value(
+        "--module-match",
+        "LIST",
+        concat!(
+            "normalize module names matched by severity rules\n",
+            "with a comma-separated LIST of 'basename',\n",
+            "'strip-ko' and 'ignore-case', or 'exact' (default)",
+        ),
+    ),
+];
+
+const CONSOLIDATE_COMMAND: CommandSpec = CommandSpec {
+    name: "consolidate",
+    summary: "consolidate symvers files into a single file",
+    synopsis: &["ksymvers consolidate -o FILE [OPTION]... FILE..."],
+    description: "Consolidate symvers files into a single file.",
+    options: &[
+        &[
+            OptionSpec::value("--output", "FILE", "write the result in FILE").with_short("-o"),
+            OptionSpec::value(
+                "--order",
+                "ORDER",
+                concat!(
+                    "write records in ORDER, either 'sorted' by name\n",
+                    "(the default) or in the 'input' order",
+                ),
+            ),
+        ],
+        SYMVERS_OPTIONS,
+    ],
+};
+
+const SPLIT_COMMAND: CommandSpec = CommandSpec {
+    name: "split",
+    summary: "split a symvers file into per-module files",
+    synopsis: &["ksymvers split -o DIR [OPTION]... FILE"],
+    description: "Split a symvers file into per-module files.",
+    options: &[
+        &[
+            OptionSpec::value("--output", "DIR", "write the result to DIR").with_short("-o"),
+            OptionSpec::value(
+                "--manifest",
+                "FILE",
+                concat!(
+                    "write a sorted list of the output files with\n",
+                    "their SHA-256 digests to FILE",
+                ),
+            ),
+        ],
+        SYMVERS_OPTIONS,
+    ],
+};
+
+const COMPARE_COMMAND: CommandSpec = CommandSpec {
+    name: "compare",
+    summary: "show differences between two symvers files",
+    synopsis: &[
+        "ksymvers compare [OPTION]... FILE FILE2",
+        "ksymvers compare [OPTION]... --baseline=FILE FILE2...",
+        "ksymvers compare [OPTION]... --baseline=NAME:PATH[:RULES]... FILE",
+    ],
+    description: concat!(
+        "Show differences between two symvers files, between a baseline symvers file and\n",
+        "several other symvers files, or between several named baselines and a symvers file.\n",
+        "Each FILE can be also a kernel RPM package, from which the symvers file is extracted.",
+    ),
+    options: &[
+        &[
+            OptionSpec::value(
+                "--filter-symbol-list",
+                "FILE",
+                "consider only symbols matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--exclude-symbol-list",
+                "FILE",
+                "ignore symbols matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--filter-symbol",
+                "PATTERN",
+                "consider only symbols matching PATTERN",
+            ),
+            OptionSpec::value(
+                "--exclude-symbol",
+                "PATTERN",
+                "ignore symbols matching PATTERN",
+            ),
+            OptionSpec::value("--namespace", "NS", "consider only symbols in namespace NS"),
+        ],
+        RULES_OPTIONS,
+        &[
+            OptionSpec::value(
+                "--suppressions",
+                "FILE",
+                "ignore acknowledged differences listed in FILE",
+            ),
+            OptionSpec::value(
+                "--protected-list",
+                "FILE",
+                concat!(
+                    "treat any changes of symbols matching patterns in\n",
+                    "FILE as breaking, regardless of rules",
+                ),
+            ),
+            OptionSpec::value(
+                "--baseline",
+                "FILE",
+                concat!(
+                    "compare FILE with each of the other files and show\n",
+                    "a matrix of changed symbols; in the repeatable\n",
+                    "form NAME:PATH[:RULES], compare the baseline PATH\n",
+                    "with the file, using its own RULES",
+                ),
+            ),
+            OptionSpec::value(
+                "--explain-with-symtypes",
+                "PATH:PATH2",
+                concat!(
+                    "describe type changes behind each CRC change,\n",
+                    "using symtypes corpuses from PATH and PATH2",
+                ),
+            ),
+            OptionSpec::value(
+                "--format",
+                "TYPE[:FILE]",
+                concat!(
+                    "change the output format to TYPE, or write the\n",
+                    "TYPE-formatted output to FILE",
+                ),
+            )
+            .with_short("-f"),
+            OptionSpec::flag("--demangle", "show Rust export names in demangled form"),
+            OptionSpec::value(
+                "--color",
+                "WHEN",
+                concat!(
+                    "colorize the pretty and short output 'always',\n",
+                    "'never', or when writing to a terminal ('auto',\n",
+                    "default)",
+                ),
+            ),
+            OptionSpec::flag(
+                "--with-header",
+                concat!(
+                    "prepend a header describing the tool, inputs and\n",
+                    "options to the pretty and short output",
+                ),
+            ),
+            OptionSpec::flag(
+                "--reproducible",
+                "omit the timestamp from the output header",
+            ),
+            OptionSpec::value(
+                "--fail-on",
+                "POLICY",
+                concat!(
+                    "exit with 1 on 'breaking' changes (default),\n",
+                    "'any' changes, or 'none'",
+                ),
+            ),
+            OptionSpec::flag(
+                "--short-circuit",
+                concat!(
+                    "stop at the first failing change and exit with 1\n",
+                    "without showing any differences",
+                ),
+            ),
+        ],
+        SYMVERS_OPTIONS,
+    ],
+};
+
+const UNUSED_RULES_COMMAND: CommandSpec = CommandSpec {
+    name: "unused-rules",
+    summary: "detect unused severity rules",
+    synopsis: &["ksymvers unused-rules [OPTION]... FILE..."],
+    description: "Detect severity rules not matching any records in the specified symvers files.",
+    options: &[RULES_OPTIONS, SYMVERS_OPTIONS],
+};
+
+const RULES_CHECK_COMMAND: CommandSpec = CommandSpec {
+    name: "rules-check",
+    summary: "show which severity rule matches each symbol",
+    synopsis: &["ksymvers rules-check [OPTION]... FILE"],
+    description: "Show which severity rule matches each record in the specified symvers file.",
+    options: &[RULES_OPTIONS, SYMVERS_OPTIONS],
+};
+
+const NAMESPACES_COMMAND: CommandSpec = CommandSpec {
+    name: "namespaces",
+    summary: "list export namespaces with their sizes",
+    synopsis: &["ksymvers namespaces [OPTION]... FILE"],
+    description: "List all export namespaces in the specified symvers file with their sizes.",
+    options: &[SYMVERS_OPTIONS],
+};
+
+const RULES_SUGGEST_COMMAND: CommandSpec = CommandSpec {
+    name: "rules-suggest",
+    summary: "suggest severity rules tolerating all changes",
+    synopsis: &["ksymvers rules-suggest [OPTION]... FILE FILE2"],
+    description: "Suggest severity rules tolerating all breaking changes between two symvers files.",
+    options: &[
+        &[
+            OptionSpec::value(
+                "--filter-symbol-list",
+                "FILE",
+                "consider only symbols matching patterns in FILE",
+            ),
+            OptionSpec::value(
+                "--output",
+                "FILE",
+                "write the result in FILE, instead of stdout",
+            )
+            .with_short("-o"),
+        ],
+        SYMVERS_OPTIONS,
+    ],
+};
+
+const TREND_COMMAND: CommandSpec = CommandSpec {
+    name: "trend",
+    summary: "show the churn of exports over snapshots",
+    synopsis: &["ksymvers trend [OPTION]... DIR"],
+    description: concat!(
+        "Show the numbers of changed exports between consecutive symvers snapshots in the specified\n",
+        "directory, ordered by their file name, and the stability score of each export.",
+    ),
+    options: &[
+        &[OptionSpec::value(
+            "--output",
+            "FILE",
+            "write the result in FILE, instead of stdout",
+        )
+        .with_short("-o")],
+        SYMVERS_OPTIONS,
+    ],
+};
+
+const TOOL: ToolSpec = ToolSpec {
+    name: "ksymvers",
+    summary: "a tool to work with Linux-kernel symvers files",
+    commands: &[
+        CONSOLIDATE_COMMAND,
+        SPLIT_COMMAND,
+        COMPARE_COMMAND,
+        UNUSED_RULES_COMMAND,
+        RULES_SUGGEST_COMMAND,
+        RULES_CHECK_COMMAND,
+        NAMESPACES_COMMAND,
+        TREND_COMMAND,
+    ],
+};
+
+/// Options controlling how symvers files are parsed, accepted by all commands.
+struct SymversOptions {
+    lenient: bool,
+    format: SymversFormat,
+    keep_going: bool,
+}
+
+impl SymversOptions {
+    /// Obtains the `--lenient`, `--symvers-format` and `--keep-going` options.
+    fn from_parsed(parsed: &ParsedArgs) -> Result<Self, Error> {
+        Ok(Self {
+            lenient: parsed.is_set("--lenient"),
+            format: parsed.parse_value("--symvers-format")?.unwrap_or_default(),
+            keep_going: parsed.is_set("--keep-going"),
+        })
+    }
+
+    /// Configures the given corpus to parse symvers files according to the options.
+    fn apply(&self, symvers: &mut SymversCorpus) {
+        symvers.set_lenient(self.lenient);
+        symvers.set_format(self.format);
+        symvers.set_keep_going(self.keep_going);
+    }
+}
+
+/// Reads a symvers corpus from the specified file, or from a kernel build tree if the path is a
+/// directory.
+fn read_symvers(
+    do_timing: bool,
+    path: &str,
+    symvers_opts: &SymversOptions,
+) -> Result<SymversCorpus, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading symvers from '{}'", path),
+    );
+
+    let mut symvers = SymversCorpus::new();
+    symvers_opts.apply(&mut symvers);
+    let result = if Path::new(path).is_dir() {
+        symvers.load_build_dir(path)
+    } else if rpm::is_rpm_path(Path::new(path)) {
+        rpm::extract_file(path, rpm::SYMVERS_PATTERNS)
+            .and_then(|(name, data)| symvers.load_buffer(format!("{}:{}", path, name), &data[..]))
+    } else {
+        symvers.load(path)
+    };
+    result.map_err(|err| {
+        Error::new_context(format!("Failed to read symvers from '{}'", path), err)
+    })?;
+    Ok(symvers)
+}
+
+/// Reads symbol filters from the specified file.
+fn read_symbol_filter(do_timing: bool, path: &str) -> Result<Filter, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading symbol filters from '{}'", path),
+    );
+
+    let mut symbol_filter = Filter::new();
+    symbol_filter.load(path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read symbol filters from '{}'", path),
+            err,
+        )
+    })?;
+    Ok(symbol_filter)
+}
+
+/// Reads symbol exclusion patterns from the specified file into an existing filter.
+fn read_symbol_exclusions(
+    do_timing: bool,
+    symbol_filter: &mut Filter,
+    path: &str,
+) -> Result<(), Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading symbol exclusions from '{}'", path),
+    );
+
+    symbol_filter.load_exclusions(path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read symbol exclusions from '{}'", path),
+            err,
+        )
+    })
+}
+
+/// Reads the list of protected symbols from the specified file.
+fn read_protected_list(do_timing: bool, path: &str) -> Result<Filter, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading protected symbols from '{}'", path),
+    );
+
+    let mut protected = Filter::new();
+    protected.load(path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read protected symbols from '{}'", path),
+            err,
+        )
+    })?;
+    Ok(protected)
+}
+
+/// Reads acknowledged differences from the specified file.
+fn read_suppressions(do_timing: bool, path: &str) -> Result<Suppressions, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading suppressions from '{}'", path),
+    );
+
+    let mut suppressions = Suppressions::new();
+    suppressions.load(path).map_err(|err| {
+        Error::new_context(format!("Failed to read suppressions from '{}'", path), err)
+    })?;
+    Ok(suppressions)
+}
+
+/// Reads a symtypes corpus from the specified path, which can be either a directory with symtypes
+/// files or a consolidated symtypes file.
+fn read_symtypes(do_timing: bool, path: &str) -> Result<SymtypesCorpus, Error> {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Load,
+        format!("Reading symtypes from '{}'", path),
+    );
+
+    let mut job_slots = JobControl::new_simple(1);
+    job_slots.set_progress(Progress::callback(format!(
+        "Reading symtypes from '{}'",
+        path
+    )));
+
+    let mut symtypes = SymtypesCorpus::new();
+    let result = if rpm::is_rpm_path(Path::new(path)) {
+        rpm::extract_file(path, rpm::SYMTYPES_PATTERNS).and_then(|(name, data)| {
+            symtypes.load_buffer(format!("{}:{}", path, name), &data[..], io::stderr())
+        })
+    } else {
+        symtypes.load(path, io::stderr(), &mut job_slots)
+    };
+    result.map_err(|err| {
+        Error::new_context(format!("Failed to read symtypes from '{}'", path), err)
+    })?;
+    Ok(symtypes)
+}
+
+/// Parses a value of the `--baseline` option in the form `NAME:PATH[:RULES]`.
+fn parse_named_baseline(value: &str) -> Result<(String, String, Option<String>), Error> {
+    let mut parts = value.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(name), Some(path), maybe_rules_path)
+            if !name.is_empty() && !path.is_empty() && maybe_rules_path != Some("") =>
+        {
+            Ok((
+                name.to_string(),
+                path.to_string(),
+                maybe_rules_path.map(str::to_string),
+            ))
+        }
+        _ => Err(Error::new_cli(format!(
+            "Invalid value for '--baseline={}': must be in the form FILE or NAME:PATH[:RULES]",
+            value
+        ))),
+    }
+}
+
+/// Restricts symvers corpuses to exports that are in the given namespace in any of them.
+fn restrict_to_namespace(
+    do_timing: bool,
+    namespace: &str,
+    symvers: &mut SymversCorpus,
+    others: &mut [SymversCorpus],
+) {
+    let _timing = Timing::new(
+        do_timing,
+        Phase::Analyze,
+        format!("Restricting to namespace '{}'", namespace),
+    );
+
+    let mut names = HashSet::new();
+    for corpus in iter::once(&*symvers).chain(others.iter()) {
+        corpus.for_each_export(|name, info| {
+            if info.namespace() == Some(namespace) {
+                names.insert(name.to_string());
+            }
+        });
+    }
+
+    for corpus in iter::once(symvers).chain(others.iter_mut()) {
+        corpus.retain_exports(|name, _| names.contains(name));
+    }
+}
+
+/// Handles the `consolidate` command which consolidates symvers files into a single file.
+fn do_consolidate<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = CONSOLIDATE_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_output = parsed.value("--output").map(str::to_string);
+    let order = parsed.parse_value("--order")?.unwrap_or(WriteOrder::Sorted);
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    let paths = parsed.into_positionals();
+
+    let output = maybe_output.ok_or_else(|| Error::new_cli("The consolidate output is missing"))?;
+    if paths.is_empty() {
+        return Err(Error::new_cli("No symvers file is specified"));
+    }
+
+    // Do the consolidation.
+    let mut symvers = SymversCorpus::new();
+    symvers_opts.apply(&mut symvers);
+    for path in paths {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Merge,
+            format!("Reading symvers from '{}'", path),
+        );
+
+        let result = if Path::new(&path).is_dir() {
+            symvers.load_build_dir(&path)
+        } else {
+            symvers.merge(&path)
+        };
+        result.map_err(|err| {
+            Error::new_context(format!("Failed to read symvers from '{}'", path), err)
+        })?;
+    }
+
+    {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing consolidated symvers to '{}'", output),
+        );
+
+        symvers.write(&output, order).map_err(|err| {
+            Error::new_context(
+                format!("Failed to write consolidated symvers to '{}'", output),
+                err,
+            )
+        })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `split` command which splits a symvers file into per-module files.
+fn do_split<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = SPLIT_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_output = parsed.value("--output").map(str::to_string);
+    let maybe_manifest = parsed.value("--manifest").map(str::to_string);
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
+
+    let output = maybe_output.ok_or_else(|| Error::new_cli("The split output is missing"))?;
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The split source is missing"))?;
+
+    // Do the split.
+    let symvers = read_symvers(do_timing, &path, &symvers_opts)?;
+
+    {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing split symvers to '{}'", output),
+        );
+
+        let err_context = |err| {
+            Error::new_context(
+                format!("Failed to write split symvers to '{}'", output),
+                err,
+            )
+        };
+        match &maybe_manifest {
+            Some(manifest) => {
+                let mut dir_writer = DirectoryWriter::new_file(&output);
+                let mut manifest_writer = ManifestWriter::new(&mut dir_writer);
+                symvers
+                    .write_split_buffer(&mut manifest_writer)
+                    .map_err(err_context)?;
+                manifest_writer.write_manifest(manifest).map_err(|err| {
+                    Error::new_context(
+                        format!("Failed to write the manifest to '{}'", manifest),
+                        err,
+                    )
+                })?;
+            }
+            None => symvers.write_split(&output).map_err(err_context)?,
+        }
+    }
+
+    Ok(ExitCode::from(0))
+}
 
 /// Handles the `compare` command which shows differences between two symvers files.
 fn do_compare<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
     // Parse specific command options.
-    let mut args = args.into_iter();
-    let mut maybe_symbol_filter_path = None;
-    let mut maybe_rules_path = None;
-    let mut writers_conf = vec![(CompareFormat::Pretty, "-".to_string())];
-    let mut past_dash_dash = false;
-    let mut maybe_path = None;
-    let mut maybe_path2 = None;
-
-    while let Some(arg) = args.next() {
-        if !past_dash_dash {
-            if let Some(value) = handle_value_option(&arg, &mut args, None, "--filter-symbol-list")?
-            {
-                maybe_symbol_filter_path = Some(value);
-                continue;
-            }
-            if let Some(value) = handle_value_option(&arg, &mut args, "-r", "--rules")? {
-                maybe_rules_path = Some(value);
-                continue;
-            }
-            if let Some(value) = handle_value_option(&arg, &mut args, "-f", "--format")? {
-                match value.split_once(':') {
-                    Some((format, path)) => {
-                        writers_conf.push((CompareFormat::from_str(format)?, path.to_string()))
-                    }
-                    None => writers_conf[0].0 = CompareFormat::from_str(&value)?,
-                }
-                continue;
-            }
-            if arg == "-h" || arg == "--help" {
-                print!("{}", COMPARE_USAGE_MSG);
-                return Ok(ExitCode::from(0));
+    let Some(parsed) = COMPARE_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_symbol_filter_path = parsed.value("--filter-symbol-list").map(str::to_string);
+    let maybe_symbol_exclude_path = parsed.value("--exclude-symbol-list").map(str::to_string);
+    let symbol_patterns = parsed
+        .values("--filter-symbol")
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let symbol_exclusions = parsed
+        .values("--exclude-symbol")
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let maybe_namespace = parsed.value("--namespace").map(str::to_string);
+    let maybe_rules_path = parsed.value("--rules").map(str::to_string);
+    let maybe_arch = parsed.value("--arch").map(str::to_string);
+    let module_matching = parsed.parse_value("--module-match")?.unwrap_or_default();
+    let maybe_suppressions_path = parsed.value("--suppressions").map(str::to_string);
+    let maybe_protected_path = parsed.value("--protected-list").map(str::to_string);
+    let baseline_values = parsed.values("--baseline");
+    let mut maybe_baseline_path = None;
+    let mut named_baselines = Vec::new();
+    if let [value] = baseline_values[..]
+        && !value.contains(':')
+    {
+        maybe_baseline_path = Some(value.to_string());
+    } else {
+        for value in baseline_values {
+            if !value.contains(':') {
+                return Err(Error::new_cli(
+                    "The --baseline option can be repeated only in the form NAME:PATH[:RULES]",
+                ));
             }
-            if arg == "--" {
-                past_dash_dash = true;
-                continue;
+            named_baselines.push(parse_named_baseline(value)?);
+        }
+    }
+    let is_baseline = maybe_baseline_path.is_some() || !named_baselines.is_empty();
+    let maybe_explain_paths = parsed.convert("--explain-with-symtypes", |value| {
+        match value.split_once(':') {
+            Some((symtypes_path, symtypes_path2)) => {
+                Ok((symtypes_path.to_string(), symtypes_path2.to_string()))
             }
-            if arg.starts_with('-') {
-                return Err(Error::new_cli(format!(
-                    "Unrecognized compare option '{}'",
-                    arg
-                )));
+            None => Err("must be in the form PATH:PATH2"),
+        }
+    })?;
+    let mut writers_conf = vec![(CompareFormat::Pretty, "-".to_string())];
+    for value in parsed.values("--format") {
+        match value.split_once(':') {
+            Some((format, path)) => {
+                writers_conf.push((CompareFormat::from_str(format)?, path.to_string()))
             }
+            None => writers_conf[0].0 = CompareFormat::from_str(value)?,
         }
+    }
+    let format_specified = parsed.is_set("--format");
+    let demangle = parsed.is_set("--demangle");
+    let color = parsed.parse_value("--color")?.unwrap_or_default();
+    let maybe_output_header = parsed.is_set("--with-header").then(|| {
+        let maybe_time = (!parsed.is_set("--reproducible")).then(SystemTime::now);
+        parsed.output_header("ksymvers", env!("SUSE_KABI_TOOLS_VERSION"), maybe_time)
+    });
+    let fail_on = parsed.parse_value("--fail-on")?.unwrap_or(FailOn::Breaking);
+    let short_circuit = parsed.is_set("--short-circuit");
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    let mut paths = parsed.into_positionals();
 
-        if maybe_path.is_none() {
-            maybe_path = Some(arg);
-            continue;
+    if is_baseline {
+        if paths.is_empty() {
+            return Err(Error::new_cli("The compare source is missing"));
         }
-        if maybe_path2.is_none() {
-            maybe_path2 = Some(arg);
-            continue;
+        if format_specified {
+            return Err(Error::new_cli(
+                "The --format option cannot be used together with --baseline",
+            ));
         }
+        if maybe_explain_paths.is_some() {
+            return Err(Error::new_cli(
+                "The --explain-with-symtypes option cannot be used together with --baseline",
+            ));
+        }
+    }
+    if let Some(baseline_path) = &maybe_baseline_path {
+        paths.insert(0, baseline_path.clone());
+    } else if let Some(arg) = paths.get(if is_baseline { 1 } else { 2 }) {
         return Err(Error::new_cli(format!(
             "Excess compare argument '{}' specified",
             arg
         )));
     }
+    let mut paths = paths.into_iter();
+    let path = paths
+        .next()
+        .ok_or_else(|| Error::new_cli("The first compare source is missing"))?;
+    let maybe_path2 = paths.next();
 
-    let path = maybe_path.ok_or_else(|| Error::new_cli("The first compare source is missing"))?;
-    let path2 =
-        maybe_path2.ok_or_else(|| Error::new_cli("The second compare source is missing"))?;
-
-    let maybe_symbol_filter = match maybe_symbol_filter_path {
-        Some(symbol_filter_path) => {
-            let _timing = Timing::new(
-                do_timing,
-                format!("Reading symbol filters from '{}'", symbol_filter_path),
-            );
-
-            let mut symbol_filter = Filter::new();
-            symbol_filter.load(&symbol_filter_path).map_err(|err| {
-                Error::new_context(
-                    format!(
-                        "Failed to read symbol filters from '{}'",
-                        symbol_filter_path
-                    ),
-                    err,
-                )
-            })?;
-            Some(symbol_filter)
+    let maybe_stop_at = if short_circuit {
+        if is_baseline {
+            return Err(Error::new_cli(
+                "The --short-circuit option cannot be used together with --baseline",
+            ));
+        }
+        if format_specified {
+            return Err(Error::new_cli(
+                "The --short-circuit option cannot be used together with --format",
+            ));
         }
+        if maybe_explain_paths.is_some() {
+            return Err(Error::new_cli(
+                "The --short-circuit option cannot be used together with --explain-with-symtypes",
+            ));
+        }
+        let failing_status = fail_on.failing_status().ok_or_else(|| {
+            Error::new_cli("The --short-circuit option cannot be used together with --fail-on=none")
+        })?;
+        // A removal of a protected export has its own exit status, so the comparison can stop
+        // early only when one is found.
+        Some(if maybe_protected_path.is_some() {
+            CompareStatus::ProtectedRemoved
+        } else {
+            failing_status
+        })
+    } else {
+        None
+    };
+
+    let mut maybe_symbol_filter = match maybe_symbol_filter_path {
+        Some(symbol_filter_path) => Some(read_symbol_filter(do_timing, &symbol_filter_path)?),
         None => None,
     };
+    if let Some(symbol_exclude_path) = maybe_symbol_exclude_path {
+        read_symbol_exclusions(
+            do_timing,
+            maybe_symbol_filter.get_or_insert_with(Filter::new),
+            &symbol_exclude_path,
+        )?;
+    }
+    for pattern in &symbol_patterns {
+        maybe_symbol_filter
+            .get_or_insert_with(Filter::new)
+            .add_pattern(pattern);
+    }
+    for pattern in &symbol_exclusions {
+        maybe_symbol_filter
+            .get_or_insert_with(Filter::new)
+            .add_exclusion(pattern);
+    }
 
-    let maybe_rules = match maybe_rules_path {
-        Some(rules_path) => {
-            let _timing = Timing::new(
-                do_timing,
-                format!("Reading severity rules from '{}'", rules_path),
-            );
+    let read_rules = |rules_path: &str| {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading severity rules from '{}'", rules_path),
+        );
 
-            let mut rules = Rules::new();
-            rules.load(&rules_path).map_err(|err| {
-                Error::new_context(
-                    format!("Failed to read severity rules from '{}'", rules_path),
-                    err,
-                )
-            })?;
-            Some(rules)
+        let mut rules = Rules::new();
+        rules.set_keep_going(symvers_opts.keep_going);
+        rules.set_module_matching(module_matching);
+        if let Some(arch) = &maybe_arch {
+            rules.set_arch(arch.clone());
         }
+        rules.load(rules_path).map_err(|err| {
+            Error::new_context(
+                format!("Failed to read severity rules from '{}'", rules_path),
+                err,
+            )
+        })?;
+        Ok::<_, Error>(rules)
+    };
+
+    let maybe_rules = match &maybe_rules_path {
+        Some(rules_path) => Some(read_rules(rules_path)?),
         None => None,
     };
 
-    let symvers = {
-        let _timing = Timing::new(do_timing, format!("Reading symvers from '{}'", path));
+    let maybe_suppressions = match maybe_suppressions_path {
+        Some(suppressions_path) => Some(read_suppressions(do_timing, &suppressions_path)?),
+        None => None,
+    };
 
-        let mut symvers = SymversCorpus::new();
-        symvers.load(&path).map_err(|err| {
-            Error::new_context(format!("Failed to read symvers from '{}'", path), err)
-        })?;
-        symvers
+    let maybe_protected = match maybe_protected_path {
+        Some(protected_path) => Some(read_protected_list(do_timing, &protected_path)?),
+        None => None,
     };
 
-    let symvers2 = {
-        let _timing = Timing::new(do_timing, format!("Reading symvers from '{}'", path2));
+    if !named_baselines.is_empty() {
+        let mut baselines = Vec::new();
+        let mut baselines_rules = Vec::new();
+        for (_, baseline_path, maybe_baseline_rules_path) in &named_baselines {
+            let mut baseline = read_symvers(do_timing, baseline_path, &symvers_opts)?;
+            baseline.set_demangle(demangle);
+            baseline.set_protected(maybe_protected.clone());
+            baselines.push(baseline);
+            // A baseline without its own rules uses the ones from the --rules option.
+            baselines_rules.push(match maybe_baseline_rules_path {
+                Some(rules_path) => Some(read_rules(rules_path)?),
+                None => None,
+            });
+        }
+        let mut symvers = read_symvers(do_timing, &path, &symvers_opts)?;
+        symvers.set_output_header(maybe_output_header);
+        if let Some(namespace) = &maybe_namespace {
+            restrict_to_namespace(do_timing, namespace, &mut symvers, &mut baselines);
+        }
+
+        let status = {
+            let _timing = Timing::new(do_timing, Phase::Compare, "Comparison");
 
-        let mut symvers2 = SymversCorpus::new();
-        symvers2.load(&path2).map_err(|err| {
-            Error::new_context(format!("Failed to read symvers from '{}'", path2), err)
-        })?;
-        symvers2
+            let labeled = named_baselines
+                .iter()
+                .zip(&baselines)
+                .zip(&baselines_rules)
+                .map(|(((name, _, _), baseline), maybe_baseline_rules)| {
+                    (
+                        name.as_str(),
+                        baseline,
+                        maybe_baseline_rules.as_ref().or(maybe_rules.as_ref()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            symvers
+                .compare_baselines_with(
+                    &labeled,
+                    maybe_symbol_filter.as_ref(),
+                    maybe_suppressions.as_ref(),
+                    "-",
+                )
+                .map_err(|err| {
+                    Error::new_context(
+                        format!("Failed to compare symvers from '{}' with baselines", path),
+                        err,
+                    )
+                })?
+        };
+
+        return Ok(fail_on.exit_code(status));
+    }
+
+    let mut symvers = read_symvers(do_timing, &path, &symvers_opts)?;
+    symvers.set_demangle(demangle);
+    symvers.set_color(color);
+    symvers.set_protected(maybe_protected);
+    symvers.set_output_header(maybe_output_header);
+
+    let path2 =
+        maybe_path2.ok_or_else(|| Error::new_cli("The second compare source is missing"))?;
+
+    if maybe_baseline_path.is_some() {
+        let other_paths = [path2].into_iter().chain(paths).collect::<Vec<_>>();
+        let mut other_symvers = Vec::new();
+        for other_path in &other_paths {
+            other_symvers.push(read_symvers(do_timing, other_path, &symvers_opts)?);
+        }
+        if let Some(namespace) = &maybe_namespace {
+            restrict_to_namespace(do_timing, namespace, &mut symvers, &mut other_symvers);
+        }
+
+        let status = {
+            let _timing = Timing::new(do_timing, Phase::Compare, "Comparison");
+
+            let others = other_paths
+                .iter()
+                .map(String::as_str)
+                .zip(&other_symvers)
+                .collect::<Vec<_>>();
+            symvers
+                .compare_many_with(
+                    &others,
+                    maybe_symbol_filter.as_ref(),
+                    maybe_rules.as_ref(),
+                    maybe_suppressions.as_ref(),
+                    "-",
+                )
+                .map_err(|err| {
+                    Error::new_context(
+                        format!("Failed to compare symvers from baseline '{}'", path),
+                        err,
+                    )
+                })?
+        };
+
+        return Ok(fail_on.exit_code(status));
+    }
+
+    let mut symvers2 = read_symvers(do_timing, &path2, &symvers_opts)?;
+    if let Some(namespace) = &maybe_namespace {
+        restrict_to_namespace(
+            do_timing,
+            namespace,
+            &mut symvers,
+            slice::from_mut(&mut symvers2),
+        );
+    }
+
+    if let Some(stop_at) = maybe_stop_at {
+        let _timing = Timing::new(do_timing, Phase::Compare, "Comparison");
+
+        let status = symvers.compare_status(
+            &symvers2,
+            maybe_symbol_filter.as_ref(),
+            maybe_rules.as_ref(),
+            maybe_suppressions.as_ref(),
+            stop_at,
+        );
+        return Ok(fail_on.exit_code(status));
+    }
+
+    let maybe_explain = match maybe_explain_paths {
+        Some((symtypes_path, symtypes_path2)) => Some((
+            read_symtypes(do_timing, &symtypes_path)?,
+            read_symtypes(do_timing, &symtypes_path2)?,
+        )),
+        None => None,
     };
 
-    let is_equal = {
-        let _timing = Timing::new(do_timing, "Comparison");
+    let status = {
+        let _timing = Timing::new(do_timing, Phase::Compare, "Comparison");
 
         symvers
             .compare_with(
                 &symvers2,
                 maybe_symbol_filter.as_ref(),
                 maybe_rules.as_ref(),
+                maybe_suppressions.as_ref(),
+                maybe_explain
+                    .as_ref()
+                    .map(|(symtypes, symtypes2)| (symtypes, symtypes2)),
                 &writers_conf[..],
             )
             .map_err(|err| {
@@ -193,7 +959,7 @@ fn do_compare<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Resul
             })?
     };
 
-    Ok(ExitCode::from(if is_equal { 0 } else { 1 }))
+    Ok(fail_on.exit_code(status))
 }
 
 /// Handles the `unused-rules` command which detects unused severity rules.
@@ -202,35 +968,14 @@ fn do_unused_rules<I: IntoIterator<Item = String>>(
     args: I,
 ) -> Result<ExitCode, Error> {
     // Parse specific command options.
-    let mut args = args.into_iter();
-    let mut maybe_rules_path = None;
-    let mut past_dash_dash = false;
-    let mut paths = Vec::new();
-
-    while let Some(arg) = args.next() {
-        if !past_dash_dash {
-            if let Some(value) = handle_value_option(&arg, &mut args, "-r", "--rules")? {
-                maybe_rules_path = Some(value);
-                continue;
-            }
-            if arg == "-h" || arg == "--help" {
-                print!("{}", UNUSED_RULES_USAGE_MSG);
-                return Ok(ExitCode::from(0));
-            }
-            if arg == "--" {
-                past_dash_dash = true;
-                continue;
-            }
-            if arg.starts_with('-') {
-                return Err(Error::new_cli(format!(
-                    "Unrecognized unused-rules option '{}'",
-                    arg
-                )));
-            }
-        }
-
-        paths.push(arg);
-    }
+    let Some(parsed) = UNUSED_RULES_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_rules_path = parsed.value("--rules").map(str::to_string);
+    let maybe_arch = parsed.value("--arch").map(str::to_string);
+    let module_matching = parsed.parse_value("--module-match")?.unwrap_or_default();
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    let paths = parsed.into_positionals();
 
     let rules_path = maybe_rules_path.ok_or_else(|| Error::new_cli("The rules file is missing"))?;
     if paths.is_empty() {
@@ -240,10 +985,16 @@ fn do_unused_rules<I: IntoIterator<Item = String>>(
     let rules = {
         let _timing = Timing::new(
             do_timing,
+            Phase::Load,
             format!("Reading severity rules from '{}'", rules_path),
         );
 
         let mut rules = Rules::new();
+        rules.set_keep_going(symvers_opts.keep_going);
+        rules.set_module_matching(module_matching);
+        if let Some(arch) = maybe_arch {
+            rules.set_arch(arch);
+        }
         rules.load(&rules_path).map_err(|err| {
             Error::new_context(
                 format!("Failed to read severity rules from '{}'", rules_path),
@@ -254,26 +1005,34 @@ fn do_unused_rules<I: IntoIterator<Item = String>>(
     };
 
     let mut used_rules = UsedRules::new();
+    let mut modules = HashSet::new();
     for path in paths {
-        let symvers = {
-            let _timing = Timing::new(do_timing, format!("Reading symvers from '{}'", path));
-
-            let mut symvers = SymversCorpus::new();
-            symvers.load(&path).map_err(|err| {
-                Error::new_context(format!("Failed to read symvers from '{}'", path), err)
-            })?;
-            symvers
-        };
+        let symvers = read_symvers(do_timing, &path, &symvers_opts)?;
 
-        let _timing = Timing::new(do_timing, format!("Matching records in '{}'", path));
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Analyze,
+            format!("Matching records in '{}'", path),
+        );
         symvers.mark_used_rules(&rules, &mut used_rules);
+        symvers.for_each_export(|_, info| {
+            if !modules.contains(info.module()) {
+                modules.insert(info.module().to_string());
+            }
+        });
     }
 
     {
-        let _timing = Timing::new(do_timing, "Reporting unused rules");
+        let _timing = Timing::new(do_timing, Phase::Write, "Reporting unused rules");
 
         rules
             .write_unused_rules_buffer(&used_rules, io::stdout())
+            .and_then(|_| {
+                rules.write_unmatched_module_rules_buffer(
+                    modules.iter().map(String::as_str),
+                    io::stdout(),
+                )
+            })
             .map_err(|err| {
                 Error::new_context(
                     format!("Failed to report unused rules in '{}'", rules_path),
@@ -285,16 +1044,226 @@ fn do_unused_rules<I: IntoIterator<Item = String>>(
     Ok(ExitCode::from(0))
 }
 
+/// Handles the `rules-check` command which shows which severity rule matches each symbol.
+fn do_rules_check<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = RULES_CHECK_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_rules_path = parsed.value("--rules").map(str::to_string);
+    let maybe_arch = parsed.value("--arch").map(str::to_string);
+    let module_matching = parsed.parse_value("--module-match")?.unwrap_or_default();
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
+
+    let rules_path = maybe_rules_path.ok_or_else(|| Error::new_cli("The rules file is missing"))?;
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The rules-check source is missing"))?;
+
+    let rules = {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Load,
+            format!("Reading severity rules from '{}'", rules_path),
+        );
+
+        let mut rules = Rules::new();
+        rules.set_keep_going(symvers_opts.keep_going);
+        rules.set_module_matching(module_matching);
+        if let Some(arch) = maybe_arch {
+            rules.set_arch(arch);
+        }
+        rules.load(&rules_path).map_err(|err| {
+            Error::new_context(
+                format!("Failed to read severity rules from '{}'", rules_path),
+                err,
+            )
+        })?;
+        rules
+    };
+
+    let symvers = read_symvers(do_timing, &path, &symvers_opts)?;
+
+    {
+        let _timing = Timing::new(do_timing, Phase::Analyze, "Checking severity rules");
+
+        symvers
+            .check_rules_buffer(&rules, io::stdout())
+            .map_err(|err| {
+                Error::new_context(
+                    format!("Failed to check severity rules against '{}'", path),
+                    err,
+                )
+            })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `namespaces` command which lists export namespaces with their sizes.
+fn do_namespaces<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = NAMESPACES_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(1)?;
+    let maybe_path = parsed.into_positionals().into_iter().next();
+
+    let path = maybe_path.ok_or_else(|| Error::new_cli("The namespaces source is missing"))?;
+
+    let symvers = read_symvers(do_timing, &path, &symvers_opts)?;
+
+    {
+        let _timing = Timing::new(do_timing, Phase::Write, "Writing namespaces");
+
+        symvers
+            .write_namespaces_buffer(io::stdout())
+            .map_err(|err| {
+                Error::new_context(format!("Failed to write namespaces of '{}'", path), err)
+            })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `rules-suggest` command which suggests severity rules tolerating all breaking
+/// changes between two symvers files.
+fn do_rules_suggest<I: IntoIterator<Item = String>>(
+    do_timing: bool,
+    args: I,
+) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = RULES_SUGGEST_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let maybe_symbol_filter_path = parsed.value("--filter-symbol-list").map(str::to_string);
+    let output = parsed.value("--output").unwrap_or("-").to_string();
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(2)?;
+    let mut positionals = parsed.into_positionals().into_iter();
+    let maybe_path = positionals.next();
+    let maybe_path2 = positionals.next();
+
+    let path =
+        maybe_path.ok_or_else(|| Error::new_cli("The first rules-suggest source is missing"))?;
+    let path2 =
+        maybe_path2.ok_or_else(|| Error::new_cli("The second rules-suggest source is missing"))?;
+
+    let maybe_symbol_filter = match maybe_symbol_filter_path {
+        Some(symbol_filter_path) => Some(read_symbol_filter(do_timing, &symbol_filter_path)?),
+        None => None,
+    };
+
+    let symvers = read_symvers(do_timing, &path, &symvers_opts)?;
+    let symvers2 = read_symvers(do_timing, &path2, &symvers_opts)?;
+
+    let rules = {
+        let _timing = Timing::new(do_timing, Phase::Analyze, "Suggesting rules");
+
+        symvers.suggest_rules(&symvers2, maybe_symbol_filter.as_ref())
+    };
+
+    {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing suggested severity rules to '{}'", output),
+        );
+
+        rules.write(&output).map_err(|err| {
+            Error::new_context(
+                format!("Failed to write suggested severity rules to '{}'", output),
+                err,
+            )
+        })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
+/// Handles the `trend` command which shows the churn of exports over a series of symvers
+/// snapshots.
+fn do_trend<I: IntoIterator<Item = String>>(do_timing: bool, args: I) -> Result<ExitCode, Error> {
+    // Parse specific command options.
+    let Some(parsed) = TREND_COMMAND.parse(args)? else {
+        return Ok(ExitCode::from(0));
+    };
+    let output = parsed.value("--output").unwrap_or("-").to_string();
+    let symvers_opts = SymversOptions::from_parsed(&parsed)?;
+    parsed.check_max_positionals(1)?;
+    let maybe_dir = parsed.into_positionals().into_iter().next();
+
+    let dir = maybe_dir.ok_or_else(|| Error::new_cli("The trend source is missing"))?;
+
+    // The directory contains the snapshots, ordered by their name.
+    let dir_iter = fs::read_dir(&dir)
+        .map_err(|err| Error::new_io(format!("Failed to read the directory '{}'", dir), err))?;
+    let mut snapshot_paths = Vec::new();
+    for entry in dir_iter {
+        let entry = entry
+            .map_err(|err| Error::new_io(format!("Failed to read the directory '{}'", dir), err))?;
+        if entry.path().is_file() {
+            snapshot_paths.push((
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.path().to_string_lossy().into_owned(),
+            ));
+        }
+    }
+    snapshot_paths.sort();
+    if snapshot_paths.len() < 2 {
+        return Err(Error::new_cli(format!(
+            "The trend directory '{}' must contain at least two snapshots",
+            dir
+        )));
+    }
+
+    let mut snapshots = Vec::new();
+    for (_, path) in &snapshot_paths {
+        snapshots.push(read_symvers(do_timing, path, &symvers_opts)?);
+    }
+
+    {
+        let _timing = Timing::new(
+            do_timing,
+            Phase::Write,
+            format!("Writing the export trend to '{}'", output),
+        );
+
+        let labeled = snapshot_paths
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .zip(&snapshots)
+            .collect::<Vec<_>>();
+        SymversCorpus::write_trend(&labeled, &output).map_err(|err| {
+            Error::new_context(
+                format!("Failed to write the export trend to '{}'", output),
+                err,
+            )
+        })?;
+    }
+
+    Ok(ExitCode::from(0))
+}
+
 fn main() -> ExitCode {
     // Process global arguments.
     let mut args = env::args();
     let mut do_timing = false;
+    let mut maybe_config_path = None;
 
     let result = process_global_args(
         &mut args,
-        USAGE_MSG,
+        &TOOL,
         &format!("ksymvers {}\n", env!("SUSE_KABI_TOOLS_VERSION")),
         &mut do_timing,
+        &mut maybe_config_path,
     );
     let command = match result {
         Ok(Some(command)) => command,
@@ -305,21 +1274,40 @@ fn main() -> ExitCode {
         }
     };
 
+    // Apply defaults from the configuration file.
+    let args = match apply_config("ksymvers", &command, maybe_config_path.as_deref(), args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::from(2);
+        }
+    };
+
     // Process the specified command.
     let result = match command.as_str() {
+        "consolidate" => do_consolidate(do_timing, args),
+        "split" => do_split(do_timing, args),
         "compare" => do_compare(do_timing, args),
         "unused-rules" => do_unused_rules(do_timing, args),
+        "rules-suggest" => do_rules_suggest(do_timing, args),
+        "rules-check" => do_rules_check(do_timing, args),
+        "namespaces" => do_namespaces(do_timing, args),
+        "trend" => do_trend(do_timing, args),
         _ => Err(Error::new_cli(format!(
             "Unrecognized command '{}'",
             command
         ))),
     };
 
-    match result {
+    let code = match result {
         Ok(code) => code,
         Err(err) => {
             eprintln!("{}", err);
             ExitCode::from(2)
         }
-    }
+    };
+
+    write_timing_report();
+
+    code
 }
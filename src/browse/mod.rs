@@ -0,0 +1,529 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! An interactive terminal browser of symtypes comparison results.
+//!
+//! The browser shows a list of changed exports on the left and the details of the selected export,
+//! such as the diffs of its changed types, on the right. The list can be filtered by a shell
+//! wildcard pattern and searched for a substring.
+//!
+//! The terminal is controlled by ANSI escape sequences and switched to the raw mode by the external
+//! `stty` tool, which means the browser requires a Unix-like system.
+
+use crate::suppressions::ChangeKind;
+use crate::symtypes::ComparisonResult;
+use crate::text::{DiffOptions, STYLE_RESET, expand_tabs, line_style, matches_wildcard};
+use crate::{Error, MapIOErr};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, prelude::*};
+use std::{iter, process};
+
+#[cfg(test)]
+mod tests;
+
+// ANSI escape sequences used to control the terminal.
+const ENTER_SCREEN: &str = "\x1b[?1049h\x1b[?25l";
+const LEAVE_SCREEN: &str = "\x1b[?25h\x1b[?1049l";
+const CURSOR_HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const STYLE_SELECTED: &str = "\x1b[7m";
+
+/// A key pressed by the user.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Escape,
+    Backspace,
+    Char(char),
+}
+
+/// Decodes the keys contained in the given input read from a terminal.
+///
+/// The escape sequences of the cursor and page keys are recognized, other escape sequences are
+/// skipped. A lone escape character is decoded as [`Key::Escape`].
+pub fn parse_keys(input: &[u8]) -> Vec<Key> {
+    let input = String::from_utf8_lossy(input);
+    let mut chars = input.chars().peekable();
+    let mut keys = Vec::new();
+
+    while let Some(ch) = chars.next() {
+        let key = match ch {
+            '\x1b' => {
+                if !matches!(chars.peek(), Some('[' | 'O')) {
+                    keys.push(Key::Escape);
+                    continue;
+                }
+                chars.next();
+
+                // Collect the parameters and the final character of the sequence.
+                let mut params = String::new();
+                let mut maybe_final = None;
+                for ch in chars.by_ref() {
+                    if ch.is_ascii_digit() || ch == ';' {
+                        params.push(ch);
+                    } else {
+                        maybe_final = Some(ch);
+                        break;
+                    }
+                }
+                match (params.as_str(), maybe_final) {
+                    ("", Some('A')) => Key::Up,
+                    ("", Some('B')) => Key::Down,
+                    ("", Some('H')) | ("1" | "7", Some('~')) => Key::Home,
+                    ("", Some('F')) | ("4" | "8", Some('~')) => Key::End,
+                    ("5", Some('~')) => Key::PageUp,
+                    ("6", Some('~')) => Key::PageDown,
+                    _ => continue,
+                }
+            }
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            _ => Key::Char(ch),
+        };
+        keys.push(key);
+    }
+
+    keys
+}
+
+/// A changed export listed by the browser.
+struct Entry {
+    name: String,
+    kind: ChangeKind,
+    /// Indices of the changed types that affect the export.
+    type_changes: Vec<usize>,
+}
+
+/// A line of text entered by the user.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Prompt {
+    Search,
+    Filter,
+}
+
+/// The state of the browser, independent of the terminal.
+pub struct Browser {
+    result: ComparisonResult,
+    diff_options: DiffOptions,
+    entries: Vec<Entry>,
+    /// Indices of the entries matching the current filter.
+    visible: Vec<usize>,
+    /// The position of the selected entry in `visible`.
+    selected: usize,
+    /// The position of the first shown entry in `visible`.
+    list_top: usize,
+    /// The details of the selected entry and the index of their first shown line.
+    details: Vec<String>,
+    details_top: usize,
+    filter: String,
+    search: String,
+    /// The prompt being answered and the text entered so far.
+    maybe_prompt: Option<(Prompt, String)>,
+    /// A message shown in the status line until the next key is pressed.
+    maybe_message: Option<String>,
+    /// The number of rows of both panes in the last rendered frame.
+    page_size: usize,
+}
+
+impl Browser {
+    /// Creates a new [`Browser`] listing all exports changed in the given comparison result. The
+    /// diffs of changed types are formatted according to `diff_options`.
+    pub fn new(result: ComparisonResult, diff_options: DiffOptions) -> Self {
+        let mut entries = BTreeMap::<&str, (ChangeKind, Vec<usize>)>::new();
+        for name in &result.added_exports {
+            entries.insert(name, (ChangeKind::Added, Vec::new()));
+        }
+        for name in &result.removed_exports {
+            entries.insert(name, (ChangeKind::Removed, Vec::new()));
+        }
+        for (change_idx, change) in result.changed_types.iter().enumerate() {
+            for name in &change.exports {
+                entries
+                    .entry(name)
+                    .or_insert_with(|| (ChangeKind::Definition, Vec::new()))
+                    .1
+                    .push(change_idx);
+            }
+        }
+        let entries = entries
+            .into_iter()
+            .map(|(name, (kind, type_changes))| Entry {
+                name: name.to_string(),
+                kind,
+                type_changes,
+            })
+            .collect::<Vec<_>>();
+
+        let maybe_message = entries
+            .is_empty()
+            .then(|| "No differences found".to_string());
+        let mut browser = Self {
+            visible: (0..entries.len()).collect(),
+            result,
+            diff_options,
+            entries,
+            selected: 0,
+            list_top: 0,
+            details: Vec::new(),
+            details_top: 0,
+            filter: String::new(),
+            search: String::new(),
+            maybe_prompt: None,
+            maybe_message,
+            page_size: 1,
+        };
+        browser.update_details();
+        browser
+    }
+
+    /// Returns whether there are no changed exports to browse.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Recreates the details of the selected entry.
+    fn update_details(&mut self) {
+        self.details.clear();
+        self.details_top = 0;
+
+        let Some(&entry_idx) = self.visible.get(self.selected) else {
+            return;
+        };
+        let entry = &self.entries[entry_idx];
+        match entry.kind {
+            ChangeKind::Added | ChangeKind::Removed => {
+                self.details
+                    .push(format!("Export '{}' has been {}", entry.name, entry.kind));
+            }
+            _ => {
+                for (i, &change_idx) in entry.type_changes.iter().enumerate() {
+                    if i > 0 {
+                        self.details.push(String::new());
+                    }
+                    let mut diff = Vec::new();
+                    match self.result.changed_types[change_idx]
+                        .write_diff(&self.diff_options, &mut diff)
+                    {
+                        Ok(()) => self
+                            .details
+                            .extend(String::from_utf8_lossy(&diff).lines().map(String::from)),
+                        Err(err) => self.details.push(err.to_string()),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Selects the entry at the given position in the visible list, clamped to the list.
+    fn select(&mut self, pos: usize) {
+        let pos = pos.min(self.visible.len().saturating_sub(1));
+        if pos != self.selected {
+            self.selected = pos;
+            self.update_details();
+        }
+    }
+
+    /// Scrolls the details by the given number of lines.
+    fn scroll_details(&mut self, delta: isize) {
+        let max_top = self.details.len().saturating_sub(self.page_size);
+        self.details_top = self.details_top.saturating_add_signed(delta).min(max_top);
+    }
+
+    /// Selects the first visible entry containing the current search text, starting at the given
+    /// position and wrapping around the end of the list.
+    fn find_next(&mut self, start: usize) {
+        let len = self.visible.len();
+        let found = (0..len)
+            .map(|i| (start + i) % len)
+            .find(|&pos| self.entries[self.visible[pos]].name.contains(&self.search));
+        match found {
+            Some(pos) => self.select(pos),
+            None => self.maybe_message = Some(format!("Pattern '{}' not found", self.search)),
+        }
+    }
+
+    /// Restricts the list to entries matching the given wildcard pattern. An empty pattern shows
+    /// all entries. The selected entry is kept if it still matches.
+    fn set_filter(&mut self, pattern: String) {
+        let maybe_selected = self.visible.get(self.selected).copied();
+
+        self.visible = (0..self.entries.len())
+            .filter(|&idx| {
+                pattern.is_empty() || matches_wildcard(&self.entries[idx].name, &pattern)
+            })
+            .collect();
+        if self.visible.is_empty() {
+            self.maybe_message = Some(format!("No exports match '{}'", pattern));
+        }
+        self.filter = pattern;
+
+        self.selected = maybe_selected
+            .and_then(|idx| {
+                self.visible
+                    .iter()
+                    .position(|&visible_idx| visible_idx == idx)
+            })
+            .unwrap_or(0);
+        self.list_top = 0;
+        self.update_details();
+    }
+
+    /// Handles a key pressed by the user. Returns `false` if the browser should quit.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        self.maybe_message = None;
+
+        if let Some((_, text)) = &mut self.maybe_prompt {
+            match key {
+                Key::Char(ch) if !ch.is_control() => text.push(ch),
+                Key::Backspace => {
+                    text.pop();
+                }
+                Key::Enter => {
+                    let (prompt, text) = self.maybe_prompt.take().unwrap();
+                    match prompt {
+                        Prompt::Search => {
+                            self.search = text;
+                            if !self.search.is_empty() {
+                                self.find_next(self.selected);
+                            }
+                        }
+                        Prompt::Filter => self.set_filter(text),
+                    }
+                }
+                Key::Escape | Key::Char('\x03') => self.maybe_prompt = None,
+                _ => {}
+            }
+            return true;
+        }
+
+        let page = self.page_size.max(1);
+        match key {
+            Key::Char('q' | '\x03') => return false,
+            Key::Up | Key::Char('k') => self.select(self.selected.saturating_sub(1)),
+            Key::Down | Key::Char('j') => self.select(self.selected + 1),
+            Key::PageUp => self.select(self.selected.saturating_sub(page)),
+            Key::PageDown => self.select(self.selected + page),
+            Key::Home | Key::Char('g') => self.select(0),
+            Key::End | Key::Char('G') => self.select(usize::MAX),
+            Key::Char('K') => self.scroll_details(-1),
+            Key::Char('J') => self.scroll_details(1),
+            Key::Char('b') => self.scroll_details(-(page as isize)),
+            Key::Char(' ') => self.scroll_details(page as isize),
+            Key::Char('/') => self.maybe_prompt = Some((Prompt::Search, String::new())),
+            Key::Char('n') if !self.search.is_empty() && !self.visible.is_empty() => {
+                self.find_next(self.selected + 1)
+            }
+            Key::Char('f') => self.maybe_prompt = Some((Prompt::Filter, self.filter.clone())),
+            _ => {}
+        }
+        true
+    }
+
+    /// Renders the browser into lines of a frame with the given dimensions.
+    ///
+    /// The selected entry and the status line are highlighted, and the details are colorized the
+    /// same way as the pretty comparison report.
+    pub fn render(&mut self, width: usize, height: usize) -> Vec<String> {
+        let rows = height.saturating_sub(1);
+        self.page_size = rows;
+
+        // Keep the selected entry in view.
+        if self.selected < self.list_top {
+            self.list_top = self.selected;
+        } else if rows > 0 && self.selected >= self.list_top + rows {
+            self.list_top = self.selected + 1 - rows;
+        }
+
+        // Size the list to fit the longest name, using at most a third of the width.
+        let list_width = self
+            .entries
+            .iter()
+            .map(|entry| entry.name.chars().count() + 2)
+            .max()
+            .unwrap_or(0)
+            .clamp(12, (width / 3).max(12))
+            .min(width);
+        let details_width = width.saturating_sub(list_width + 3);
+
+        let mut frame = Vec::new();
+        for row in 0..rows {
+            let mut line = String::new();
+
+            let pos = self.list_top + row;
+            match self.visible.get(pos) {
+                Some(&entry_idx) => {
+                    let entry = &self.entries[entry_idx];
+                    let marker = match entry.kind {
+                        ChangeKind::Added => '+',
+                        ChangeKind::Removed => '-',
+                        _ => '~',
+                    };
+                    let cell = fit(&format!("{} {}", marker, entry.name), list_width);
+                    if pos == self.selected {
+                        line.push_str(STYLE_SELECTED);
+                        line.push_str(&cell);
+                        line.push_str(STYLE_RESET);
+                    } else {
+                        line.push_str(&cell);
+                    }
+                }
+                None => line.push_str(&fit("", list_width)),
+            }
+
+            if details_width > 0 {
+                line.push_str(" | ");
+                if let Some(detail) = self.details.get(self.details_top + row) {
+                    let cell = fit(&expand_tabs(detail), details_width);
+                    let cell = cell.trim_end();
+                    match line_style(detail.as_bytes()) {
+                        Some(style) => {
+                            line.push_str(style);
+                            line.push_str(cell);
+                            line.push_str(STYLE_RESET);
+                        }
+                        None => line.push_str(cell),
+                    }
+                }
+            }
+
+            frame.push(line.trim_end().to_string());
+        }
+
+        if height > 0 {
+            let status = match (&self.maybe_prompt, &self.maybe_message) {
+                (Some((Prompt::Search, text)), _) => format!("Search: {}", text),
+                (Some((Prompt::Filter, text)), _) => format!("Filter: {}", text),
+                (None, Some(message)) => message.clone(),
+                (None, None) => {
+                    let mut status = format!(
+                        "Export {}/{}",
+                        if self.visible.is_empty() {
+                            0
+                        } else {
+                            self.selected + 1
+                        },
+                        self.visible.len()
+                    );
+                    if !self.filter.is_empty() {
+                        status.push_str(&format!(" matching '{}'", self.filter));
+                    }
+                    status.push_str(" | / search, n next, f filter, space/b scroll, q quit");
+                    status
+                }
+            };
+            frame.push(format!(
+                "{}{}{}",
+                STYLE_SELECTED,
+                fit(&status, width),
+                STYLE_RESET
+            ));
+        }
+
+        frame
+    }
+}
+
+/// Truncates or pads the given text with spaces to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let mut res = text.chars().take(width).collect::<String>();
+    let len = res.chars().count();
+    res.extend(iter::repeat_n(' ', width - len));
+    res
+}
+
+/// Runs the `stty` tool on the given terminal and returns its output.
+fn stty(tty: &File, args: &[&str]) -> Result<String, Error> {
+    let err_desc = format!("Failed to run 'stty {}'", args.join(" "));
+
+    let stdin = tty
+        .try_clone()
+        .map_err(|err| Error::new_io(&err_desc, err))?;
+    let output = process::Command::new("stty")
+        .args(args)
+        .stdin(stdin)
+        .output()
+        .map_err(|err| Error::new_io(&err_desc, err))?;
+    if !output.status.success() {
+        return Err(Error::new_io(
+            err_desc,
+            io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Obtains the number of rows and columns of the given terminal.
+fn terminal_size(tty: &File) -> Result<(usize, usize), Error> {
+    let size = stty(tty, &["size"])?;
+    let parsed = size
+        .split_once(' ')
+        .and_then(|(rows, cols)| Some((rows.parse().ok()?, cols.parse().ok()?)));
+    parsed.ok_or_else(|| Error::new_parse(format!("Unrecognized terminal size '{}'", size)))
+}
+
+/// Shows the browser on the given terminal and handles the keys until the user quits.
+fn run_loop(browser: &mut Browser, tty: &mut File) -> Result<(), Error> {
+    let err_desc = "Failed to write to the terminal";
+
+    tty.write_all(ENTER_SCREEN.as_bytes())
+        .map_io_err(err_desc)?;
+
+    let mut input = [0; 64];
+    loop {
+        let (rows, cols) = terminal_size(tty)?;
+        let mut output = String::from(CURSOR_HOME);
+        for (row, line) in browser.render(cols, rows).iter().enumerate() {
+            if row > 0 {
+                output.push_str("\r\n");
+            }
+            output.push_str(line);
+            output.push_str(CLEAR_LINE);
+        }
+        tty.write_all(output.as_bytes()).map_io_err(err_desc)?;
+        tty.flush().map_io_err(err_desc)?;
+
+        let size = tty
+            .read(&mut input)
+            .map_err(|err| Error::new_io("Failed to read from the terminal", err))?;
+        if size == 0 {
+            return Ok(());
+        }
+        for key in parse_keys(&input[..size]) {
+            if !browser.handle_key(key) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Runs the browser on the controlling terminal until the user quits it.
+///
+/// The terminal is switched to the raw mode and the alternate screen for the duration of the
+/// browsing, and restored afterwards.
+pub fn run(browser: &mut Browser) -> Result<(), Error> {
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|err| Error::new_io("Failed to open the terminal '/dev/tty'", err))?;
+
+    let saved_mode = stty(&tty, &["-g"])?;
+    stty(&tty, &["raw", "-echo"])?;
+
+    let result = run_loop(browser, &mut tty);
+
+    // Restore the terminal, even if the browsing failed.
+    let leave_result = tty
+        .write_all(LEAVE_SCREEN.as_bytes())
+        .and_then(|_| tty.flush())
+        .map_io_err("Failed to write to the terminal");
+    let restore_result = stty(&tty, &[&saved_mode]);
+    result.and(leave_result).and(restore_result.map(|_| ()))
+}
@@ -0,0 +1,168 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::burst::JobControl;
+use crate::symtypes::SymtypesCorpus;
+use crate::{assert_ok, bytes};
+
+/// Creates a browser of the differences between two small symtypes corpuses.
+fn new_browser() -> Browser {
+    let mut warnings = Vec::new();
+    let mut symtypes = SymtypesCorpus::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#foo )\n",
+            "old void old ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#foo )\n",
+            "new void new ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let result = symtypes.compare(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        &mut JobControl::new_simple(1),
+    );
+    Browser::new(result.unwrap(), DiffOptions::default())
+}
+
+/// Presses the given keys in the browser, returning whether it should keep running.
+fn press(browser: &mut Browser, keys: &str) -> bool {
+    parse_keys(keys.as_bytes())
+        .into_iter()
+        .all(|key| browser.handle_key(key))
+}
+
+#[test]
+fn keys_parse() {
+    // Check that plain characters and the escape sequences of special keys are decoded.
+    assert_eq!(
+        parse_keys(b"q/\r\x7f"),
+        vec![Key::Char('q'), Key::Char('/'), Key::Enter, Key::Backspace]
+    );
+    assert_eq!(
+        parse_keys(b"\x1b[A\x1b[B\x1bOA\x1b[5~\x1b[6~\x1b[H\x1b[4~"),
+        vec![
+            Key::Up,
+            Key::Down,
+            Key::Up,
+            Key::PageUp,
+            Key::PageDown,
+            Key::Home,
+            Key::End
+        ]
+    );
+    assert_eq!(
+        parse_keys(b"\x1b\x1b[1;5Cj"),
+        vec![Key::Escape, Key::Char('j')]
+    );
+}
+
+#[test]
+fn browse_render() {
+    // Check that the list of changed exports and the details of the selected one are rendered
+    // side by side.
+    let mut browser = new_browser();
+    assert!(!browser.is_empty());
+    assert_eq!(
+        browser.render(70, 6),
+        vec![
+            "\x1b[7m~ bar       \x1b[0m | because of a changed 's#foo' (member type changed, brea",
+            "~ baz        | \x1b[36m@@ -1,3 +1,3 @@\x1b[0m",
+            "+ new        |  struct foo {",
+            "- old        | \x1b[31m-       int a;\x1b[0m",
+            "             | \x1b[32m+       long a;\x1b[0m",
+            "\x1b[7mExport 1/4 | / search, n next, f filter, space/b scroll, q quit       \x1b[0m",
+        ]
+    );
+}
+
+#[test]
+fn browse_empty() {
+    // Check that a browser without any changed exports reports that no differences are found.
+    let mut browser = Browser::new(ComparisonResult::default(), DiffOptions::default());
+    assert!(browser.is_empty());
+    assert_eq!(
+        browser.render(40, 2),
+        vec![
+            "             |",
+            "\x1b[7mNo differences found                    \x1b[0m",
+        ]
+    );
+}
+
+#[test]
+fn browse_move() {
+    // Check that the selection moves within the list and the details follow it.
+    let mut browser = new_browser();
+    assert!(press(&mut browser, "jj"));
+    assert_eq!(browser.selected, 2);
+    assert_eq!(browser.details, vec!["Export 'new' has been added"]);
+    assert!(press(&mut browser, "\x1b[4~"));
+    assert_eq!(browser.selected, 3);
+    assert_eq!(browser.details, vec!["Export 'old' has been removed"]);
+    assert!(press(&mut browser, "jg"));
+    assert_eq!(browser.selected, 0);
+    assert!(!press(&mut browser, "q"));
+}
+
+#[test]
+fn browse_search() {
+    // Check that the search selects the next export containing the text and reports a failure.
+    let mut browser = new_browser();
+    assert!(press(&mut browser, "/ba\r"));
+    assert_eq!(browser.selected, 0);
+    assert!(press(&mut browser, "n"));
+    assert_eq!(browser.selected, 1);
+    assert!(press(&mut browser, "n"));
+    assert_eq!(browser.selected, 0);
+    assert!(press(&mut browser, "/qux\r"));
+    assert_eq!(browser.selected, 0);
+    assert_eq!(
+        browser.maybe_message.as_deref(),
+        Some("Pattern 'qux' not found")
+    );
+}
+
+#[test]
+fn browse_filter() {
+    // Check that the filter restricts the list to matching exports and keeps the selected one if
+    // possible.
+    let mut browser = new_browser();
+    assert!(press(&mut browser, "j"));
+    assert!(press(&mut browser, "fba*\r"));
+    assert_eq!(browser.visible, vec![0, 1]);
+    assert_eq!(browser.selected, 1);
+    assert!(press(&mut browser, "f\x7f\x7f\x7fo*\r"));
+    assert_eq!(browser.visible, vec![3]);
+    assert_eq!(browser.selected, 0);
+    assert_eq!(browser.details, vec!["Export 'old' has been removed"]);
+    assert!(press(&mut browser, "f\x7f\x7fx*\r"));
+    assert!(browser.visible.is_empty());
+    assert!(browser.details.is_empty());
+    assert_eq!(
+        browser.maybe_message.as_deref(),
+        Some("No exports match 'x*'")
+    );
+    assert!(press(&mut browser, "f\x7f\x7f\r"));
+    assert_eq!(browser.visible, vec![0, 1, 2, 3]);
+}
@@ -3,16 +3,40 @@
 
 //! Support for running multiple tasks in parallel, with each task using own thread.
 
-use crate::Error;
+use crate::{Error, debug};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, prelude::*};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 #[cfg(test)]
 mod tests;
 
+/// A callback invoked by [`run_jobs()`] after each processed work, receiving the number of
+/// completed works and the total number of works.
+pub type ProgressFn = dyn Fn(usize, usize) + Send + Sync;
+
+/// The number of chunks per job that [`run_jobs()`] aims for when auto-tuning the chunk size. Using
+/// more chunks than jobs keeps the workers balanced when individual works differ in their cost.
+const CHUNKS_PER_JOB: usize = 16;
+
+/// The maximum chunk size selected by [`run_jobs()`] when auto-tuning the chunk size.
+const MAX_AUTO_CHUNK_SIZE: usize = 64;
+
+/// The interval in which [`JobSlots`] re-check a GNU make jobserver for available tokens. Tokens
+/// returned by other processes don't produce any message on the control channel, so the jobserver
+/// needs to be polled.
+const JOBSERVER_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The `O_NONBLOCK` flag of `open()`, with the value used on Linux by all common architectures.
+#[cfg(target_os = "linux")]
+const O_NONBLOCK: i32 = 0o4000;
+
 /// A message type passed over a [`std::sync::mpsc::channel`] for coordinating invoked threads.
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum JobMessage {
@@ -23,6 +47,176 @@ pub enum JobMessage {
     Completed,
 }
 
+/// A client of a GNU make jobserver.
+///
+/// The jobserver provides a pool of tokens shared by all processes of a parallel make build. Each
+/// process implicitly owns one job slot and needs to acquire a token for each additional job that
+/// it wants to run at the same time. All acquired tokens are returned when they are no longer
+/// needed, at the latest when the `Jobserver` is dropped.
+#[derive(Debug)]
+pub struct Jobserver {
+    /// The file from which tokens are read. It is opened in the non-blocking mode.
+    reader: File,
+
+    /// The file to which tokens are written back.
+    writer: File,
+
+    /// The currently held tokens. Make requires that each token is returned with its original
+    /// value.
+    tokens: Vec<u8>,
+}
+
+impl Jobserver {
+    /// Connects to the jobserver advertised in the `MAKEFLAGS` environment variable.
+    ///
+    /// Returns `Ok(None)` if the variable is not set or doesn't describe any jobserver.
+    pub fn from_env() -> Result<Option<Self>, Error> {
+        match env::var("MAKEFLAGS") {
+            Ok(makeflags) => Self::from_makeflags(&makeflags),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Connects to the jobserver described by the given `MAKEFLAGS` value.
+    ///
+    /// Both the `--jobserver-auth=R,W` form with inherited file descriptors and the
+    /// `--jobserver-auth=fifo:PATH` form with a named pipe are recognized, as well as the older
+    /// `--jobserver-fds=R,W` option. Returns `Ok(None)` if no jobserver is described.
+    pub fn from_makeflags(makeflags: &str) -> Result<Option<Self>, Error> {
+        // Find the last jobserver option. Any words after '--' are variable assignments.
+        let maybe_auth = makeflags
+            .split_whitespace()
+            .take_while(|&word| word != "--")
+            .filter_map(|word| {
+                word.strip_prefix("--jobserver-auth=")
+                    .or_else(|| word.strip_prefix("--jobserver-fds="))
+            })
+            .last();
+        let auth = match maybe_auth {
+            Some(auth) => auth,
+            None => return Ok(None),
+        };
+        debug!("Connecting to the jobserver '{}'", auth);
+
+        if let Some(path) = auth.strip_prefix("fifo:") {
+            let reader = open_nonblocking(path)?;
+            let writer = open_writer(path)?;
+            return Ok(Some(Self::new(reader, writer)));
+        }
+
+        let fds = auth
+            .split_once(',')
+            .and_then(|(read_fd, write_fd)| {
+                Some((read_fd.parse::<i32>().ok()?, write_fd.parse::<i32>().ok()?))
+            })
+            .ok_or_else(|| {
+                Error::new_parse(format!("Invalid jobserver specification '{}'", auth))
+            })?;
+        // Negative descriptors indicate that make disabled the jobserver for this process.
+        if fds.0 < 0 || fds.1 < 0 {
+            return Ok(None);
+        }
+
+        // Open the descriptors through /proc to obtain separate file descriptions. This allows
+        // setting the non-blocking mode without affecting the descriptors shared with make.
+        let reader = open_nonblocking(&format!("/proc/self/fd/{}", fds.0))?;
+        let writer = open_writer(&format!("/proc/self/fd/{}", fds.1))?;
+        Ok(Some(Self::new(reader, writer)))
+    }
+
+    /// Creates a new `Jobserver` using the given reader and writer files.
+    fn new(reader: File, writer: File) -> Self {
+        Self {
+            reader,
+            writer,
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Attempts to acquire one token without blocking.
+    ///
+    /// Returns `true` if a token was obtained, or `false` if none is currently available.
+    fn try_acquire(&mut self) -> bool {
+        let mut token = [0u8; 1];
+        loop {
+            match self.reader.read(&mut token) {
+                Ok(1) => {
+                    self.tokens.push(token[0]);
+                    return true;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                // No token is available, make closed the pipe, or reading failed. In all cases,
+                // the job has to wait.
+                _ => return false,
+            }
+        }
+    }
+
+    /// Returns one previously acquired token.
+    fn release(&mut self) {
+        // INVARIANT: The caller can only release a token that it previously acquired.
+        let token = self.tokens.pop().unwrap();
+        // A failed write means that make is gone and there is nobody to return the token to.
+        let _ = self.writer.write_all(&[token]);
+    }
+}
+
+impl Drop for Jobserver {
+    fn drop(&mut self) {
+        while !self.tokens.is_empty() {
+            self.release();
+        }
+    }
+}
+
+/// Opens the specified jobserver pipe for reading in the non-blocking mode.
+#[cfg(target_os = "linux")]
+fn open_nonblocking(path: &str) -> Result<File, Error> {
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+    let file = OpenOptions::new()
+        .read(true)
+        .custom_flags(O_NONBLOCK)
+        .open(path)
+        .map_err(|err| {
+            Error::new_io(format!("Failed to open the jobserver pipe '{}'", path), err)
+        })?;
+
+    // Check that the file is really a pipe, as make might have closed the descriptor and its
+    // number might have been reused.
+    let metadata = file.metadata().map_err(|err| {
+        Error::new_io(
+            format!("Failed to query the jobserver pipe '{}'", path),
+            err,
+        )
+    })?;
+    if !metadata.file_type().is_fifo() {
+        return Err(Error::new_parse(format!(
+            "The jobserver file '{}' is not a pipe",
+            path
+        )));
+    }
+
+    Ok(file)
+}
+
+/// Opens the specified jobserver pipe for reading in the non-blocking mode.
+#[cfg(not(target_os = "linux"))]
+fn open_nonblocking(path: &str) -> Result<File, Error> {
+    Err(Error::new_parse(format!(
+        "Connecting to the jobserver '{}' is not supported on this platform",
+        path
+    )))
+}
+
+/// Opens the specified jobserver pipe for writing.
+fn open_writer(path: &str) -> Result<File, Error> {
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|err| Error::new_io(format!("Failed to open the jobserver pipe '{}'", path), err))
+}
+
 /// A controller for running multiple jobs, similar to a make jobserver.
 ///
 /// The controller facility consists of two components: a parent `JobControl` and its subordinate
@@ -74,6 +268,10 @@ pub enum JobMessage {
 ///
 /// If the operation has only one "top" task, then [`JobControl::new_simple()`] provides
 /// a simplified interface for this scenario.
+///
+/// Instead of managing its own pool of job slots, the controller can join an existing GNU make
+/// jobserver using [`JobControl::new_jobserver()`]. Apart from the first slot, which the process
+/// owns implicitly, each slot is then backed by a token obtained from the jobserver.
 pub struct JobControl {
     /// The maximum number of jobs that can be run in parallel.
     maximum: i32,
@@ -90,16 +288,35 @@ pub struct JobControl {
     /// subordinate [`JobSlots`]. Each entry consists of a [`JobSlots`] identifier and its
     /// associated sender.
     listeners: HashMap<usize, mpsc::Sender<JobMessage>>,
+
+    /// An optional GNU make jobserver providing the job slots beyond the first one.
+    maybe_jobserver: Option<Jobserver>,
 }
 
 impl JobControl {
     /// Creates a new job controller to manage the specified number of parallel jobs.
     pub fn new(maximum: i32) -> Arc<Mutex<Self>> {
+        Self::new_inner(maximum, None)
+    }
+
+    /// Creates a new job controller that obtains job slots from the given GNU make jobserver,
+    /// running at most the specified number of parallel jobs.
+    ///
+    /// Only the first slot, which the process owns implicitly, can be reserved by the subordinate
+    /// job controllers. All other slots are acquired dynamically, as the jobserver provides
+    /// tokens.
+    pub fn new_jobserver(maximum: i32, jobserver: Jobserver) -> Arc<Mutex<Self>> {
+        Self::new_inner(maximum, Some(jobserver))
+    }
+
+    /// Creates a new job controller, optionally backed by a jobserver.
+    fn new_inner(maximum: i32, maybe_jobserver: Option<Jobserver>) -> Arc<Mutex<Self>> {
         let job_control = JobControl {
             maximum,
             active: 0,
             num_children: 0,
             listeners: HashMap::new(),
+            maybe_jobserver,
         };
         Arc::new(Mutex::new(job_control))
     }
@@ -110,6 +327,8 @@ impl JobControl {
 
         // INVARIANT: The caller must not exceed the maximum number of jobs.
         assert!(reserved <= job_control.maximum - job_control.active);
+        // INVARIANT: Only the implicit slot can be reserved when using a jobserver.
+        assert!(job_control.maybe_jobserver.is_none() || job_control.active + reserved <= 1);
         job_control.active += reserved;
 
         // INVARIANT: The caller is expected to create only a limited number of subordinate job
@@ -130,6 +349,9 @@ impl JobControl {
             parent: this_rc.clone(),
             sender,
             receiver,
+            maybe_progress: None,
+            maybe_chunk_size: None,
+            polling: job_control.maybe_jobserver.is_some(),
         }
     }
 
@@ -142,12 +364,25 @@ impl JobControl {
         Self::new_slots(&job_control_rc, maximum)
     }
 
+    /// Returns the number of job slots that can be currently reserved by a new subordinate job
+    /// controller.
+    pub fn reservable(&self) -> i32 {
+        if self.maybe_jobserver.is_some() {
+            // Only the implicit slot can be reserved.
+            (1 - self.active).max(0)
+        } else {
+            self.maximum - self.active
+        }
+    }
+
     /// Unregisters a subordinate job controller and returns any of its reserved jobs.
     ///
     /// This function is invoked by [`JobSlots`] when it is dropped.
     fn unregister_slots(&mut self, child_id: usize, reserved: i32) {
         assert!(self.active >= reserved);
-        self.active -= reserved;
+        for _ in 0..reserved {
+            self.deactivate_one();
+        }
 
         let maybe_listener = self.listeners.remove(&child_id);
         // INVARIANT: A child listener is added when `new_slots()` creates a new `JobSlots`
@@ -163,21 +398,40 @@ impl JobControl {
     ///
     /// Returns `Some(())` if a job slot was available, or `None` if all slots are currently in use.
     fn acquire_one(&mut self) -> Option<()> {
-        if self.active < self.maximum {
-            self.active += 1;
-            Some(())
-        } else {
-            None
+        if self.active >= self.maximum {
+            return None;
+        }
+
+        // Slots beyond the implicit one require a jobserver token.
+        if self.active > 0
+            && let Some(jobserver) = &mut self.maybe_jobserver
+            && !jobserver.try_acquire()
+        {
+            return None;
         }
+
+        self.active += 1;
+        Some(())
     }
 
     /// Releases one job slot.
     fn release_one(&mut self) {
         assert!(self.active > 0);
-        self.active -= 1;
+        self.deactivate_one();
         self.broadcast_to_listeners(JobMessage::SlotAvailable);
     }
 
+    /// Decrements the number of active slots, returning the associated jobserver token if the slot
+    /// was not the implicit one.
+    fn deactivate_one(&mut self) {
+        self.active -= 1;
+        if self.active > 0
+            && let Some(jobserver) = &mut self.maybe_jobserver
+        {
+            jobserver.release();
+        }
+    }
+
     /// Sends a message to all subordinate job controllers.
     fn broadcast_to_listeners(&mut self, message: JobMessage) {
         for listener in self.listeners.values() {
@@ -223,6 +477,16 @@ pub struct JobSlots {
 
     /// The receiving end of our [`std::sync::mpsc::channel`].
     receiver: mpsc::Receiver<JobMessage>,
+
+    /// An optional callback to report the progress of [`run_jobs()`].
+    maybe_progress: Option<Arc<ProgressFn>>,
+
+    /// The number of works fetched at once by each worker in [`run_jobs()`], or `None` to select
+    /// the size automatically.
+    maybe_chunk_size: Option<usize>,
+
+    /// Whether the parent is backed by a jobserver and needs to be polled for available slots.
+    polling: bool,
 }
 
 impl JobSlots {
@@ -286,12 +550,54 @@ impl JobSlots {
     }
 
     /// Receives a message from the control channel. Blocks if no message is available.
+    ///
+    /// When the parent is backed by a jobserver, the wait is limited by a short interval, after
+    /// which [`JobMessage::SlotAvailable`] is returned to have the caller re-check the jobserver.
     pub fn recv_ctrl_msg(&mut self) -> JobMessage {
+        if self.polling {
+            return match self.receiver.recv_timeout(JOBSERVER_POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => JobMessage::SlotAvailable,
+                // INVARIANT: See below.
+                Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!(),
+            };
+        }
+
         // INVARIANT: At a minimum, the `self` instance and its `JobControl` parent are connected to
         // the channel, ensuring that `recv()` cannot return a `RecvError`.
         self.receiver.recv().unwrap()
     }
 
+    /// Sets a callback to report the progress of all subsequent [`run_jobs()`] operations that use
+    /// these job slots, or removes it if `None` is specified.
+    pub fn set_progress(&mut self, maybe_progress: Option<Arc<ProgressFn>>) {
+        self.maybe_progress = maybe_progress;
+    }
+
+    /// Sets the number of works that each worker of all subsequent [`run_jobs()`] operations
+    /// fetches at once, or restores the automatic selection if `None` is specified.
+    ///
+    /// Fetching works in larger chunks reduces the synchronization overhead when the individual
+    /// works are cheap, at the cost of a potentially worse balance between the workers.
+    pub fn set_chunk_size(&mut self, maybe_chunk_size: Option<usize>) {
+        // INVARIANT: Each chunk must contain at least one work.
+        assert!(maybe_chunk_size.is_none_or(|chunk_size| chunk_size > 0));
+        self.maybe_chunk_size = maybe_chunk_size;
+    }
+
+    /// Returns the chunk size to use for processing the specified number of works.
+    ///
+    /// If no chunk size was explicitly set, it is selected so that each potential job gets
+    /// several chunks to process.
+    fn get_chunk_size(&self, num_works: usize) -> usize {
+        if let Some(chunk_size) = self.maybe_chunk_size {
+            return chunk_size;
+        }
+
+        let maximum = self.parent.lock().unwrap().maximum.max(1) as usize;
+        (num_works / (maximum * CHUNKS_PER_JOB)).clamp(1, MAX_AUTO_CHUNK_SIZE)
+    }
+
     /// Retrieves a sender for the control channel.
     pub fn get_ctrl_sender(&mut self) -> mpsc::Sender<JobMessage> {
         self.sender.clone()
@@ -312,7 +618,9 @@ impl Drop for JobSlots {
 /// Invokes the specified function for each work in parallel.
 ///
 /// The process function is invoked for each value in the range `0..num_works`. The operation is
-/// executed in parallel, using the provided [`JobSlots`] for coordination.
+/// executed in parallel, using the provided [`JobSlots`] for coordination. Workers fetch the works
+/// in chunks, with the chunk size determined by [`JobSlots::set_chunk_size()`]. If a progress
+/// callback is set in the [`JobSlots`], it is invoked after each successfully processed work.
 pub fn run_jobs<F: Fn(usize) -> Result<(), Error> + Send + Sync>(
     process_fun: F,
     num_works: usize,
@@ -321,7 +629,13 @@ pub fn run_jobs<F: Fn(usize) -> Result<(), Error> + Send + Sync>(
     // Note that when running the worker threads, the main control thread is not considered as
     // contributing to the maximum number of jobs.
 
+    let chunk_size = job_slots.get_chunk_size(num_works);
     let next_work_idx = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    // Count the completed works under a lock so that the progress is reported in a monotonic order.
+    let num_completed = Mutex::new(0);
+    let maybe_progress = job_slots.maybe_progress.clone();
 
     thread::scope(|scope| {
         let mut workers = Vec::new();
@@ -336,23 +650,40 @@ pub fn run_jobs<F: Fn(usize) -> Result<(), Error> + Send + Sync>(
             if job_slots.acquire_one().is_some() {
                 let worker_sender = job_slots.get_ctrl_sender();
                 workers.push(scope.spawn(|| {
-                    // Run the worker, fetching new work one by one until everything is completed.
+                    // Run the worker, fetching new work chunk by chunk until everything is
+                    // completed.
                     let worker_sender = worker_sender;
 
                     loop {
-                        let work_idx = next_work_idx.fetch_add(1, Ordering::Relaxed);
-                        if work_idx >= num_works {
+                        let start_idx = next_work_idx.fetch_add(chunk_size, Ordering::Relaxed);
+                        if start_idx >= num_works {
                             // INVARIANT: The `job_slots` receiver has a longer lifetime than the
                             // thread, ensuring that `send()` cannot return a `SendError`.
                             worker_sender.send(JobMessage::Completed).unwrap();
                             return Ok(());
                         }
 
-                        if let Err(err) = process_fun(work_idx) {
-                            // An error occurred. Short-circuit all the remaining work.
-                            next_work_idx.store(num_works, Ordering::Relaxed);
-                            worker_sender.send(JobMessage::Completed).unwrap();
-                            return Err(err);
+                        let end_idx = num_works.min(start_idx.saturating_add(chunk_size));
+                        for work_idx in start_idx..end_idx {
+                            // Stop early if another worker encountered an error.
+                            if failed.load(Ordering::Relaxed) {
+                                worker_sender.send(JobMessage::Completed).unwrap();
+                                return Ok(());
+                            }
+
+                            if let Err(err) = process_fun(work_idx) {
+                                // An error occurred. Short-circuit all the remaining work.
+                                failed.store(true, Ordering::Relaxed);
+                                next_work_idx.store(num_works, Ordering::Relaxed);
+                                worker_sender.send(JobMessage::Completed).unwrap();
+                                return Err(err);
+                            }
+
+                            if let Some(progress) = &maybe_progress {
+                                let mut num_completed = num_completed.lock().unwrap();
+                                *num_completed += 1;
+                                progress(*num_completed, num_works);
+                            }
                         }
                     }
                 }));
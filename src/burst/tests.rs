@@ -3,9 +3,34 @@
 
 use super::*;
 use crate::{assert_ok, assert_parse_err};
+use std::io;
+use std::os::fd::AsRawFd;
 use std::thread;
 use std::time::Duration;
 
+/// Creates a pipe holding the specified number of jobserver tokens and returns its ends, together
+/// with a `MAKEFLAGS` value describing the pipe as a jobserver.
+fn new_jobserver_pipe(num_tokens: usize) -> (io::PipeReader, io::PipeWriter, String) {
+    let (reader, mut writer) = io::pipe().unwrap();
+    writer.write_all(&vec![b'+'; num_tokens]).unwrap();
+    let makeflags = format!(
+        " -j8 --jobserver-auth={},{}",
+        reader.as_raw_fd(),
+        writer.as_raw_fd()
+    );
+    (reader, writer, makeflags)
+}
+
+/// Counts the tokens currently available in the jobserver described by `makeflags`.
+fn count_jobserver_tokens(makeflags: &str) -> usize {
+    let mut jobserver = Jobserver::from_makeflags(makeflags).unwrap().unwrap();
+    let mut num_tokens = 0;
+    while jobserver.try_acquire() {
+        num_tokens += 1;
+    }
+    num_tokens
+}
+
 #[test]
 fn job_control_acquire_release() {
     // Check that the number of active slots is correctly tracked by the acquire and release
@@ -42,6 +67,7 @@ fn job_slots_acquire_release() {
     let mut job_slots = JobControl::new_slots(&job_control_rc, 1);
     let mut job_slots2 = JobControl::new_slots(&job_control_rc, 0);
     assert_eq!(job_control_rc.lock().unwrap().active, 1);
+    assert_eq!(job_control_rc.lock().unwrap().reservable(), 2);
     assert_eq!(job_slots.active, 0);
     assert_eq!(job_slots.reserved, 1);
     assert_eq!(job_slots2.active, 0);
@@ -164,8 +190,91 @@ fn run_jobs_one_task() {
     let mut vec = vec_mutex.into_inner().unwrap();
     assert_eq!(vec.len(), 100);
     vec.sort();
-    for i in 0..vec.len() {
-        assert_eq!(vec[i], i);
+    for (i, &value) in vec.iter().enumerate() {
+        assert_eq!(value, i);
+    }
+}
+
+#[test]
+fn run_jobs_progress() {
+    // Check that `burst::run_jobs()` reports the progress of all works in a monotonic order.
+    let mut job_slots = JobControl::new_simple(8);
+    let reports_rc = Arc::new(Mutex::new(Vec::new()));
+    let reports_rc2 = reports_rc.clone();
+    job_slots.set_progress(Some(Arc::new(move |done, total| {
+        reports_rc2.lock().unwrap().push((done, total));
+    })));
+
+    let result = run_jobs(|_| Ok(()), 100, &mut job_slots);
+    assert_ok!(result);
+
+    let reports = reports_rc.lock().unwrap();
+    assert_eq!(reports.len(), 100);
+    for (i, &report) in reports.iter().enumerate() {
+        assert_eq!(report, (i + 1, 100));
+    }
+}
+
+#[test]
+fn run_jobs_chunk_size() {
+    // Check that `burst::run_jobs()` processes every work exactly once for various explicit chunk
+    // sizes, including ones that don't divide the number of works or exceed it.
+    for chunk_size in [1, 7, 10, 100, 1000] {
+        let mut job_slots = JobControl::new_simple(8);
+        job_slots.set_chunk_size(Some(chunk_size));
+        let vec_mutex = Mutex::new(Vec::new());
+
+        let result = run_jobs(
+            |work_idx| {
+                vec_mutex.lock().unwrap().push(work_idx);
+                Ok(())
+            },
+            100,
+            &mut job_slots,
+        );
+        assert_ok!(result);
+
+        let mut vec = vec_mutex.into_inner().unwrap();
+        vec.sort();
+        assert_eq!(vec, (0..100).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn run_jobs_auto_chunk_size() {
+    // Check that the automatically selected chunk size depends on the number of works and the
+    // maximum number of jobs, and stays within the expected bounds.
+    let mut job_slots = JobControl::new_simple(4);
+    assert_eq!(job_slots.get_chunk_size(0), 1);
+    assert_eq!(job_slots.get_chunk_size(100), 1);
+    assert_eq!(job_slots.get_chunk_size(640), 10);
+    assert_eq!(job_slots.get_chunk_size(1000000), MAX_AUTO_CHUNK_SIZE);
+
+    job_slots.set_chunk_size(Some(3));
+    assert_eq!(job_slots.get_chunk_size(1000000), 3);
+    job_slots.set_chunk_size(None);
+    assert_eq!(job_slots.get_chunk_size(640), 10);
+}
+
+#[test]
+fn run_jobs_chunk_progress() {
+    // Check that `burst::run_jobs()` reports the progress of each individual work when the works
+    // are fetched in chunks.
+    let mut job_slots = JobControl::new_simple(8);
+    job_slots.set_chunk_size(Some(16));
+    let reports_rc = Arc::new(Mutex::new(Vec::new()));
+    let reports_rc2 = reports_rc.clone();
+    job_slots.set_progress(Some(Arc::new(move |done, total| {
+        reports_rc2.lock().unwrap().push((done, total));
+    })));
+
+    let result = run_jobs(|_| Ok(()), 100, &mut job_slots);
+    assert_ok!(result);
+
+    let reports = reports_rc.lock().unwrap();
+    assert_eq!(reports.len(), 100);
+    for (i, &report) in reports.iter().enumerate() {
+        assert_eq!(report, (i + 1, 100));
     }
 }
 
@@ -213,8 +322,8 @@ fn run_jobs_two_tasks() {
     let mut vec = vec_mutex.into_inner().unwrap();
     assert_eq!(vec.len(), 200);
     vec.sort();
-    for i in 0..vec.len() {
-        assert_eq!(vec[i], i);
+    for (i, &value) in vec.iter().enumerate() {
+        assert_eq!(value, i);
     }
 }
 
@@ -259,3 +368,114 @@ fn run_jobs_no_work() {
     let result = run_jobs(|_| Ok(()), 0, &mut job_slots);
     assert_ok!(result);
 }
+
+#[test]
+fn run_jobs_chunk_error() {
+    // Check that `burst::run_jobs()` short-circuits the remaining works of all chunks, including
+    // the ones already fetched by other workers, when any job encounters an error.
+    let mut job_slots = JobControl::new_simple(2);
+    job_slots.set_chunk_size(Some(50));
+    let vec_mutex = Mutex::new(Vec::new());
+
+    let result = run_jobs(
+        |work_idx| {
+            if work_idx == 0 {
+                return Err(Error::new_parse("#0 is bad"));
+            };
+            thread::sleep(Duration::from_millis(10));
+            vec_mutex.lock().unwrap().push(work_idx);
+            Ok(())
+        },
+        100,
+        &mut job_slots,
+    );
+    assert_parse_err!(result, "#0 is bad");
+
+    // The worker processing the second chunk should stop shortly after the error is encountered.
+    let vec = vec_mutex.into_inner().unwrap();
+    assert!(vec.len() < 50);
+}
+
+#[test]
+fn jobserver_from_makeflags() {
+    // Check that a jobserver is recognized in various forms of `MAKEFLAGS`, and that invalid
+    // specifications are rejected.
+    assert!(Jobserver::from_makeflags("").unwrap().is_none());
+    assert!(Jobserver::from_makeflags("rR -j8").unwrap().is_none());
+    assert!(
+        Jobserver::from_makeflags(" --jobserver-auth=-2,-2")
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        Jobserver::from_makeflags(" -- V=1 --jobserver-auth=3,4")
+            .unwrap()
+            .is_none()
+    );
+
+    let result = Jobserver::from_makeflags(" --jobserver-auth=bad");
+    assert_parse_err!(result, "Invalid jobserver specification 'bad'");
+    let result = Jobserver::from_makeflags(" --jobserver-auth=fifo:/nonexistent/jobserver");
+    assert!(matches!(result, Err(Error::IO { .. })));
+
+    let (_reader, _writer, makeflags) = new_jobserver_pipe(3);
+    assert_eq!(count_jobserver_tokens(&makeflags), 3);
+    let makeflags = makeflags.replace("--jobserver-auth=", "--jobserver-fds=");
+    assert_eq!(count_jobserver_tokens(&makeflags), 3);
+}
+
+#[test]
+fn job_control_jobserver() {
+    // Check that `JobControl` backed by a jobserver hands out the implicit slot and then one slot
+    // per available token, and returns all tokens when the slots are released.
+    let (_reader, _writer, makeflags) = new_jobserver_pipe(2);
+    let jobserver = Jobserver::from_makeflags(&makeflags).unwrap().unwrap();
+    let job_control_rc = JobControl::new_jobserver(8, jobserver);
+
+    assert_eq!(job_control_rc.lock().unwrap().reservable(), 1);
+    let mut job_slots = JobControl::new_slots(&job_control_rc, 1);
+    assert_eq!(job_control_rc.lock().unwrap().active, 1);
+    assert_eq!(job_control_rc.lock().unwrap().reservable(), 0);
+
+    assert_eq!(job_slots.acquire_one(), Some(()));
+    assert_eq!(job_slots.acquire_one(), Some(()));
+    assert_eq!(job_slots.acquire_one(), Some(()));
+    assert_eq!(job_slots.acquire_one(), None);
+    assert_eq!(job_control_rc.lock().unwrap().active, 3);
+    assert_eq!(count_jobserver_tokens(&makeflags), 0);
+
+    job_slots.release_one();
+    job_slots.release_one();
+    job_slots.release_one();
+    drop(job_slots);
+    assert_eq!(job_control_rc.lock().unwrap().active, 0);
+    assert_eq!(count_jobserver_tokens(&makeflags), 2);
+}
+
+#[test]
+fn run_jobs_jobserver() {
+    // Check that `burst::run_jobs()` processes all works when the job slots are obtained from
+    // a jobserver, and that all tokens are returned afterwards.
+    let (_reader, _writer, makeflags) = new_jobserver_pipe(3);
+    let jobserver = Jobserver::from_makeflags(&makeflags).unwrap().unwrap();
+    let job_control_rc = JobControl::new_jobserver(8, jobserver);
+    let mut job_slots = JobControl::new_slots(&job_control_rc, 1);
+    let vec_mutex = Mutex::new(Vec::new());
+
+    let result = run_jobs(
+        |work_idx| {
+            vec_mutex.lock().unwrap().push(work_idx);
+            Ok(())
+        },
+        100,
+        &mut job_slots,
+    );
+    assert_ok!(result);
+
+    let mut vec = vec_mutex.into_inner().unwrap();
+    vec.sort();
+    assert_eq!(vec, (0..100).collect::<Vec<_>>());
+
+    drop(job_slots);
+    assert_eq!(count_jobserver_tokens(&makeflags), 3);
+}
@@ -2,8 +2,632 @@
 // SPDX-License-Identifier: GPL-2.0-or-later
 
 //! Utility functions for parsing command-line arguments.
+//!
+//! Commands describe their options declaratively with a [`CommandSpec`], which parses the
+//! command-line arguments into [`ParsedArgs`] and generates the usage text of the command.
 
-use crate::{Error, init_debug_level};
+use crate::config::{Config, DEFAULT_CONFIG_PATH};
+use crate::log::{Level, Logger, init_logger};
+use crate::{CompareStatus, Error, TimingFormat, init_progress, init_timing_format};
+use std::fmt::Display;
+use std::iter;
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+mod tests;
+
+/// A policy selecting which comparison outcomes result in a failure exit code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FailOn {
+    /// Fail only if a breaking change is found.
+    Breaking,
+    /// Fail if any change is found, including tolerated ones.
+    Any,
+    /// Never fail because of found changes.
+    None,
+}
+
+impl FromStr for FailOn {
+    type Err = Error;
+
+    /// Obtains a [`FailOn`] matching the given policy, specified as a string.
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "breaking" => Ok(Self::Breaking),
+            "any" => Ok(Self::Any),
+            "none" => Ok(Self::None),
+            _ => Err(Self::Err::new_cli(format!(
+                "Unrecognized fail-on policy '{}'",
+                policy
+            ))),
+        }
+    }
+}
+
+impl FailOn {
+    /// Returns the exit code of a compare command for the given comparison outcome.
+    ///
+    /// The code is 1 if the outcome is a failure according to the policy, and 0 otherwise. The
+    /// removal of a protected export is reported with the distinct code 3, unless the policy never
+    /// fails.
+    pub fn exit_code(self, status: CompareStatus) -> ExitCode {
+        if status == CompareStatus::ProtectedRemoved && self != Self::None {
+            return ExitCode::from(3);
+        }
+        let failed = match self {
+            Self::Breaking => status >= CompareStatus::Breaking,
+            Self::Any => status != CompareStatus::Same,
+            Self::None => false,
+        };
+        ExitCode::from(if failed { 1 } else { 0 })
+    }
+
+    /// Returns the least severe comparison outcome that is a failure according to the policy, or
+    /// `None` if the policy never fails.
+    pub fn failing_status(self) -> Option<CompareStatus> {
+        match self {
+            Self::Breaking => Some(CompareStatus::Breaking),
+            Self::Any => Some(CompareStatus::Tolerated),
+            Self::None => None,
+        }
+    }
+}
+
+/// The column at which the descriptions of options start in a usage text.
+const HELP_COLUMN: usize = 32;
+
+/// A specification of a command option.
+#[derive(Clone, Copy, Debug)]
+pub struct OptionSpec {
+    maybe_short: Option<&'static str>,
+    long: &'static str,
+    maybe_value_name: Option<&'static str>,
+    optional_value: bool,
+    help: &'static str,
+}
+
+impl OptionSpec {
+    /// Creates a new specification of a flag option without a value.
+    ///
+    /// The `help` describes the option in the usage text. It can be split into multiple lines with
+    /// '\n'.
+    pub const fn flag(long: &'static str, help: &'static str) -> Self {
+        Self {
+            maybe_short: None,
+            long,
+            maybe_value_name: None,
+            optional_value: false,
+            help,
+        }
+    }
+
+    /// Creates a new specification of an option with a mandatory value, named `value_name` in the
+    /// usage text.
+    pub const fn value(long: &'static str, value_name: &'static str, help: &'static str) -> Self {
+        Self {
+            maybe_short: None,
+            long,
+            maybe_value_name: Some(value_name),
+            optional_value: false,
+            help,
+        }
+    }
+
+    /// Creates a new specification of an option with an optional value, which must be attached
+    /// to the option as `--long=VALUE`.
+    pub const fn optional_value(
+        long: &'static str,
+        value_name: &'static str,
+        help: &'static str,
+    ) -> Self {
+        Self {
+            maybe_short: None,
+            long,
+            maybe_value_name: Some(value_name),
+            optional_value: true,
+            help,
+        }
+    }
+
+    /// Adds a short variant of the option.
+    pub const fn with_short(mut self, short: &'static str) -> Self {
+        self.maybe_short = Some(short);
+        self
+    }
+
+    /// Formats the option names as shown in the usage text, for instance, `-j NUM, --jobs=NUM`.
+    fn format_names(&self) -> String {
+        let long = match self.maybe_value_name {
+            Some(value_name) if self.optional_value => format!("{}[={}]", self.long, value_name),
+            Some(value_name) => format!("{}={}", self.long, value_name),
+            None => self.long.to_string(),
+        };
+        match (self.maybe_short, self.maybe_value_name) {
+            (Some(short), Some(value_name)) => format!("{} {}, {}", short, value_name, long),
+            (Some(short), None) => format!("{}, {}", short, long),
+            (None, _) => long,
+        }
+    }
+
+    /// Checks whether the `arg` is this option. If so, returns `Ok(Some())` containing the
+    /// option value, which is `None` for a flag. Returns `Ok(None)` if the `arg` is a different
+    /// option, or `Err` on error.
+    fn matches<I: Iterator<Item = String>>(
+        &self,
+        arg: &str,
+        args: &mut I,
+    ) -> Result<Option<Option<String>>, Error> {
+        if self.optional_value {
+            if Some(arg) == self.maybe_short || arg == self.long {
+                return Ok(Some(None));
+            }
+            return Ok(arg
+                .strip_prefix(self.long)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|value| Some(value.to_string())));
+        }
+        if self.maybe_value_name.is_some() {
+            return Ok(handle_value_option(arg, args, self.maybe_short, self.long)?.map(Some));
+        }
+        if Some(arg) == self.maybe_short || arg == self.long {
+            return Ok(Some(None));
+        }
+        Ok(None)
+    }
+}
+
+/// The `-h`/`--help` option, which is recognized by every command.
+const HELP_OPTION: OptionSpec =
+    OptionSpec::flag("--help", "display this help and exit").with_short("-h");
+
+/// A specification of a command, describing its usage and all recognized options.
+#[derive(Clone, Copy, Debug)]
+pub struct CommandSpec {
+    /// The name of the command, used in error messages.
+    pub name: &'static str,
+    /// A short summary of the command, shown in the list of commands of a tool.
+    pub summary: &'static str,
+    /// The synopsis lines of the command, each including the program name.
+    pub synopsis: &'static [&'static str],
+    /// The description of the command.
+    pub description: &'static str,
+    /// The groups of options recognized by the command, in the order shown in the usage text.
+    pub options: &'static [&'static [OptionSpec]],
+}
+
+impl CommandSpec {
+    /// Returns an iterator over all options of the command, starting with `-h`/`--help`.
+    fn all_options(&self) -> impl Iterator<Item = &OptionSpec> {
+        iter::once(&HELP_OPTION).chain(self.options.iter().copied().flatten())
+    }
+
+    /// Returns the usage text of the command.
+    pub fn usage(&self) -> String {
+        let mut usage = String::new();
+        for (i, synopsis) in self.synopsis.iter().enumerate() {
+            let prefix = if i == 0 { "Usage: " } else { "  or:  " };
+            usage.push_str(&format!("{}{}\n", prefix, synopsis));
+        }
+        usage.push_str(&format!("\n{}\n\nOptions:\n", self.description));
+        for option in self.all_options() {
+            push_help_entry(&mut usage, &option.format_names(), option.help);
+        }
+        usage
+    }
+
+    /// Parses the command-line arguments of the command.
+    ///
+    /// Options are recognized until a `--` argument, all other arguments are collected as
+    /// positional ones. A single `-` is a positional argument, typically referring to the standard
+    /// input or output. Returns `Ok(Some())` containing the parsed arguments, `Ok(None)` if the
+    /// function handles an option directly (such as `--help`), or `Err` on error.
+    pub fn parse<I: IntoIterator<Item = String>>(
+        &self,
+        args: I,
+    ) -> Result<Option<ParsedArgs>, Error> {
+        let mut args = args.into_iter();
+        let mut parsed = ParsedArgs {
+            command: self.name,
+            options: Vec::new(),
+            positionals: Vec::new(),
+        };
+        let mut past_dash_dash = false;
+
+        'args: while let Some(arg) = args.next() {
+            if !past_dash_dash {
+                if HELP_OPTION.matches(&arg, &mut args)?.is_some() {
+                    print!("{}", self.usage());
+                    return Ok(None);
+                }
+                for option in self.options.iter().copied().flatten() {
+                    if let Some(maybe_value) = option.matches(&arg, &mut args)? {
+                        parsed.options.push((option.long, arg, maybe_value));
+                        continue 'args;
+                    }
+                }
+                if arg == "--" {
+                    past_dash_dash = true;
+                    continue;
+                }
+                if arg.starts_with('-') && arg != "-" {
+                    return Err(Error::new_cli(format!(
+                        "Unrecognized {} option '{}'",
+                        self.name, arg
+                    )));
+                }
+            }
+
+            parsed.positionals.push(arg);
+        }
+
+        Ok(Some(parsed))
+    }
+}
+
+/// Appends an entry with the given `names` and `help` to a usage text, aligning the help at
+/// [`HELP_COLUMN`].
+fn push_help_entry(usage: &mut String, names: &str, help: &str) {
+    let names = format!("  {}", names);
+    let mut help_lines = help.split('\n');
+    if names.len() < HELP_COLUMN {
+        usage.push_str(&format!(
+            "{:width$}{}\n",
+            names,
+            help_lines.next().unwrap(),
+            width = HELP_COLUMN
+        ));
+    } else {
+        usage.push_str(&format!("{}\n", names));
+    }
+    for help_line in help_lines {
+        usage.push_str(&format!(
+            "{:width$}{}\n",
+            "",
+            help_line,
+            width = HELP_COLUMN
+        ));
+    }
+}
+
+/// The global options recognized by [`process_global_args()`].
+const GLOBAL_OPTIONS: &[OptionSpec] = &[
+    OptionSpec::flag("--debug", "enable debug output, repeat for trace output").with_short("-d"),
+    OptionSpec::value(
+        "--log-level",
+        "LEVEL",
+        concat!(
+            "log messages up to LEVEL: 'warn' (default),\n",
+            "'info', 'debug', or 'trace'",
+        ),
+    ),
+    OptionSpec::value(
+        "--log-file",
+        "FILE",
+        "write log messages to FILE, instead of stderr",
+    ),
+    OptionSpec::flag("--progress", "report progress of long operations"),
+    OptionSpec::value(
+        "--config",
+        "FILE",
+        concat!(
+            "read default command options from FILE, instead\n",
+            "of 'kabi-tools.conf' in the current directory",
+        ),
+    ),
+    OptionSpec::optional_value(
+        "--timing",
+        "FORMAT",
+        concat!(
+            "measure time of operations and report it as 'text'\n",
+            "(default), or as a 'json' summary at the end",
+        ),
+    ),
+    HELP_OPTION,
+    OptionSpec::flag("--version", "output version information and exit"),
+];
+
+/// A specification of a tool, describing its global usage and all its commands.
+#[derive(Clone, Copy, Debug)]
+pub struct ToolSpec {
+    /// The name of the tool.
+    pub name: &'static str,
+    /// A one-line description of the tool, shown in its man page.
+    pub summary: &'static str,
+    /// The commands of the tool, in the order they are listed.
+    pub commands: &'static [CommandSpec],
+}
+
+impl ToolSpec {
+    /// Returns the global usage text of the tool.
+    pub fn usage(&self) -> String {
+        let mut usage = format!("Usage: {} [OPTION]... COMMAND ...\n\nOptions:\n", self.name);
+        for option in GLOBAL_OPTIONS {
+            push_help_entry(&mut usage, &option.format_names(), option.help);
+        }
+        usage.push_str("\nCommands:\n");
+        for command in self.commands {
+            push_help_entry(&mut usage, command.name, command.summary);
+        }
+        usage.push_str(&format!(
+            "\nSee '{} COMMAND --help' for more information on a specific command.\n",
+            self.name
+        ));
+        usage
+    }
+
+    /// Returns a man page of the tool in the troff format, documenting the global options and all
+    /// commands with their options.
+    pub fn man_page(&self) -> String {
+        let mut page = format!(".TH {} 1\n", self.name.to_uppercase());
+        page.push_str(&format!(
+            ".SH NAME\n{} \\- {}\n",
+            man_escape(self.name),
+            man_escape(self.summary)
+        ));
+
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| format!("\\fB{}\\fR", man_escape(command.name)))
+            .collect::<Vec<_>>();
+        page.push_str(&format!(
+            concat!(
+                ".SH SYNOPSIS\n",
+                "\\fB{}\\fR [\\fIGENERAL\\-OPTION\\fR]... {{{}}} [\\fICOMMAND\\-OPTION\\fR]... ...\n",
+            ),
+            man_escape(self.name),
+            commands.join(" | ")
+        ));
+
+        page.push_str(".SH GENERAL OPTIONS\n");
+        for option in GLOBAL_OPTIONS {
+            push_man_option(&mut page, option);
+        }
+
+        page.push_str(".SH COMMANDS\n");
+        for (command, formatted) in self.commands.iter().zip(&commands) {
+            page.push_str(&format!(
+                ".TP\n{}\n{}\n",
+                formatted,
+                man_sentence(command.summary)
+            ));
+        }
+
+        for command in self.commands {
+            page.push_str(&format!(
+                ".SH {} COMMAND\n",
+                man_escape(&command.name.to_uppercase())
+            ));
+            for (i, synopsis) in command.synopsis.iter().enumerate() {
+                if i > 0 {
+                    page.push_str(".br\n");
+                }
+                page.push_str(&format!("{}\n", man_synopsis(synopsis)));
+            }
+            page.push_str(&format!(
+                ".PP\n{}\n.PP\nAvailable options:\n",
+                man_text(&command.description.replace('\n', " "))
+            ));
+            for option in command.all_options() {
+                push_man_option(&mut page, option);
+            }
+        }
+        page
+    }
+}
+
+/// Escapes special characters in the given text for troff.
+fn man_escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// Formats the given text as a troff text line, protecting it from being interpreted as a control
+/// line.
+fn man_text(text: &str) -> String {
+    let text = man_escape(text);
+    if text.starts_with('.') || text.starts_with('\'') {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
+
+/// Formats a usage help as a sentence in a man page, joining its lines, capitalizing the first
+/// letter and appending a period.
+fn man_sentence(help: &str) -> String {
+    let mut sentence = help.replace('\n', " ");
+    if let Some(first) = sentence.chars().next() {
+        sentence.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+    }
+    if !sentence.ends_with('.') {
+        sentence.push('.');
+    }
+    man_text(&sentence)
+}
+
+/// Formats a usage synopsis, option names or a value, showing options and literal words in bold
+/// and uppercase placeholders in italics.
+fn man_synopsis(synopsis: &str) -> String {
+    let mut formatted = String::new();
+    let mut rest = synopsis;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '-' || c.is_ascii_lowercase() {
+            let len = rest
+                .find(|c: char| !(c == '-' || c.is_ascii_lowercase() || c.is_ascii_digit()))
+                .unwrap_or(rest.len());
+            formatted.push_str(&format!("\\fB{}\\fR", man_escape(&rest[..len])));
+            len
+        } else if c.is_ascii_uppercase() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit()))
+                .unwrap_or(rest.len());
+            formatted.push_str(&format!("\\fI{}\\fR", &rest[..len]));
+            len
+        } else {
+            formatted.push_str(&man_escape(&rest[..c.len_utf8()]));
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    formatted
+}
+
+/// Appends a tagged paragraph describing the given option to a man page.
+fn push_man_option(page: &mut String, option: &OptionSpec) {
+    page.push_str(&format!(
+        ".TP\n{}\n{}\n",
+        man_synopsis(&option.format_names()),
+        man_sentence(option.help)
+    ));
+}
+
+/// Command-line arguments parsed according to a [`CommandSpec`].
+#[derive(Debug)]
+pub struct ParsedArgs {
+    command: &'static str,
+    options: Vec<(&'static str, String, Option<String>)>, // (long name, argument, value)
+    positionals: Vec<String>,
+}
+
+impl ParsedArgs {
+    /// Returns whether the specified option is present.
+    pub fn is_set(&self, long: &str) -> bool {
+        self.options.iter().any(|(name, _, _)| *name == long)
+    }
+
+    /// Returns the value of the last occurrence of the specified option, or `None` if the option
+    /// is not present.
+    pub fn value(&self, long: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(name, _, _)| *name == long)
+            .and_then(|(_, _, maybe_value)| maybe_value.as_deref())
+    }
+
+    /// Returns the values of all occurrences of the specified option, in the order they were
+    /// specified.
+    pub fn values(&self, long: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(name, _, _)| *name == long)
+            .filter_map(|(_, _, maybe_value)| maybe_value.as_deref())
+            .collect()
+    }
+
+    /// Obtains the value of the last occurrence of the specified option using its [`FromStr`]
+    /// implementation, which reports its own errors.
+    pub fn parse_value<T: FromStr<Err = Error>>(&self, long: &str) -> Result<Option<T>, Error> {
+        self.value(long).map(T::from_str).transpose()
+    }
+
+    /// Converts the value of the last occurrence of the specified option with the given function.
+    ///
+    /// A conversion error is reported as an invalid value of the option, as it was specified on
+    /// the command line.
+    pub fn convert<T, E: Display, F: FnOnce(&str) -> Result<T, E>>(
+        &self,
+        long: &str,
+        convert: F,
+    ) -> Result<Option<T>, Error> {
+        let Some((_, arg, maybe_value)) =
+            self.options.iter().rev().find(|(name, _, _)| *name == long)
+        else {
+            return Ok(None);
+        };
+        let value = maybe_value.as_deref().unwrap_or_default();
+        convert(value)
+            .map(Some)
+            .map_err(|err| Error::new_cli(format!("Invalid value for '{}': {}", arg, err)))
+    }
+
+    /// Converts the value of the last occurrence of the specified option to a number.
+    pub fn parse_number<T: FromStr<Err: Display>>(&self, long: &str) -> Result<Option<T>, Error> {
+        self.convert(long, str::parse::<T>)
+    }
+
+    /// Returns the positional arguments.
+    pub fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+
+    /// Checks that at most `max` positional arguments are present.
+    pub fn check_max_positionals(&self, max: usize) -> Result<(), Error> {
+        match self.positionals.get(max) {
+            Some(arg) => Err(Error::new_cli(format!(
+                "Excess {} argument '{}' specified",
+                self.command, arg
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Consumes the parsed arguments and returns the positional ones.
+    pub fn into_positionals(self) -> Vec<String> {
+        self.positionals
+    }
+
+    /// Formats a header describing the provenance of an output produced by the command.
+    ///
+    /// The header consists of comment lines starting with '#', which list the tool name and
+    /// version, the time of the run if `maybe_time` is provided, and the inputs and options of the
+    /// command. Options are listed in their long form, in the order they were specified.
+    pub fn output_header(
+        &self,
+        tool: &str,
+        version: &str,
+        maybe_time: Option<SystemTime>,
+    ) -> String {
+        let mut header = format!("# Generated by: {} {} {}\n", tool, version, self.command);
+        if let Some(time) = maybe_time {
+            header.push_str(&format!("# Date: {}\n", format_utc_time(time)));
+        }
+        header.push_str(&format!("# Inputs: {}\n", self.positionals.join(" ")));
+        let options = self
+            .options
+            .iter()
+            .map(|(name, _, maybe_value)| match maybe_value {
+                Some(value) => format!("{}={}", name, value),
+                None => name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        header.push_str(&format!("# Options: {}\n", options.join(" ")));
+        header
+    }
+}
+
+/// Formats the given time as a UTC date and time in the ISO 8601 format.
+fn format_utc_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // Convert the number of days since the epoch to a civil date, based on the algorithm by
+    // Howard Hinnant.
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
 
 /// Handles a command-line option with a mandatory value.
 ///
@@ -62,13 +686,17 @@ pub fn handle_value_option<
 
 /// Processes command-line options, stopping at the command name.
 ///
-/// Returns `Ok(Some())` containing the command name, `Ok(None)` if the function handles an option
-/// directly (such as `--help`), or `Err` on error.
+/// The path of a configuration file selected by the `--config` option is stored in
+/// `maybe_config_path`. The undocumented `--dump-man` option outputs a man page generated from
+/// the `tool` specification, which is intended for packaging. Returns `Ok(Some())` containing the
+/// command name, `Ok(None)` if the function handles an option directly (such as `--help`), or
+/// `Err` on error.
 pub fn process_global_args<I: Iterator<Item = String>>(
     args: &mut I,
-    usage_msg: &str,
+    tool: &ToolSpec,
     version_msg: &str,
     do_timing: &mut bool,
+    maybe_config_path: &mut Option<String>,
 ) -> Result<Option<String>, Error> {
     // Skip over the program name.
     args.next()
@@ -77,18 +705,47 @@ pub fn process_global_args<I: Iterator<Item = String>>(
     // Handle global options and stop at the command.
     let mut maybe_command = None;
     let mut debug_level = 0;
-    for arg in args.by_ref() {
+    let mut maybe_log_level = None;
+    let mut maybe_log_file = None;
+    let mut do_progress = false;
+    let mut timing_format = TimingFormat::Text;
+    while let Some(arg) = args.next() {
         if arg == "-d" || arg == "--debug" {
             debug_level += 1;
             continue;
         }
+        if let Some(value) = handle_value_option(&arg, args, None, "--log-level")? {
+            maybe_log_level = Some(Level::from_str(&value)?);
+            continue;
+        }
+        if let Some(value) = handle_value_option(&arg, args, None, "--log-file")? {
+            maybe_log_file = Some(value);
+            continue;
+        }
         if arg == "--timing" {
             *do_timing = true;
             continue;
         }
+        if let Some(value) = arg.strip_prefix("--timing=") {
+            *do_timing = true;
+            timing_format = TimingFormat::from_str(value)?;
+            continue;
+        }
+        if arg == "--progress" {
+            do_progress = true;
+            continue;
+        }
+        if let Some(value) = handle_value_option(&arg, args, None, "--config")? {
+            *maybe_config_path = Some(value);
+            continue;
+        }
 
         if arg == "-h" || arg == "--help" {
-            print!("{}", usage_msg);
+            print!("{}", tool.usage());
+            return Ok(None);
+        }
+        if arg == "--dump-man" {
+            print!("{}", tool.man_page());
             return Ok(None);
         }
         if arg == "--version" {
@@ -105,10 +762,67 @@ pub fn process_global_args<I: Iterator<Item = String>>(
         break;
     }
 
-    init_debug_level(debug_level);
+    // Each --debug option increases the verbosity, unless the level is set explicitly.
+    let log_level = maybe_log_level.unwrap_or(match debug_level {
+        0 => Level::Warn,
+        1 => Level::Debug,
+        _ => Level::Trace,
+    });
+    let logger = match maybe_log_file {
+        Some(log_file) => Logger::new_file(log_level, log_file)?,
+        None => Logger::new_stderr(log_level),
+    };
+    init_logger(logger);
+    init_progress(do_progress);
+    init_timing_format(timing_format);
 
     match maybe_command {
         Some(command) => Ok(Some(command)),
         None => Err(Error::new_cli("No command specified")),
     }
 }
+
+/// Prepends options configured for the given command of a tool to its command-line arguments.
+///
+/// The configuration is read from `maybe_config_path`, or from [`DEFAULT_CONFIG_PATH`] if no path
+/// is specified and the file exists in the current directory. Per-architecture settings are
+/// selected by the `--arch` option in `args`. The configured options come first, so options given
+/// on the command line override them.
+pub fn apply_config<I: IntoIterator<Item = String>>(
+    tool: &str,
+    command: &str,
+    maybe_config_path: Option<&str>,
+    args: I,
+) -> Result<Vec<String>, Error> {
+    let args = args.into_iter().collect::<Vec<_>>();
+
+    let config_path = match maybe_config_path {
+        Some(config_path) => config_path,
+        None if Path::new(DEFAULT_CONFIG_PATH).exists() => DEFAULT_CONFIG_PATH,
+        None => return Ok(args),
+    };
+    let mut config = Config::new();
+    config.load(config_path).map_err(|err| {
+        Error::new_context(
+            format!("Failed to read configuration from '{}'", config_path),
+            err,
+        )
+    })?;
+
+    let mut maybe_arch = None;
+    let mut arg_iter = args.iter();
+    while let Some(arg) = arg_iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--arch" {
+            maybe_arch = arg_iter.next().map(String::as_str);
+        } else if let Some(value) = arg.strip_prefix("--arch=") {
+            maybe_arch = Some(value);
+        }
+    }
+
+    let mut config_args = config.command_args(tool, command, maybe_arch);
+    config_args.extend(args);
+    Ok(config_args)
+}
@@ -0,0 +1,257 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::string_vec;
+use std::time::Duration;
+
+const TEST_COMMAND: CommandSpec = CommandSpec {
+    name: "test",
+    summary: "test the parsing",
+    synopsis: &["tool test [OPTION]... PATH", "tool test --all"],
+    description: "Test the command-line parsing.",
+    options: &[
+        &[
+            OptionSpec::value("--jobs", "NUM", "use NUM workers").with_short("-j"),
+            OptionSpec::flag(
+                "--all",
+                concat!("process all files instead of\n", "just PATH"),
+            ),
+        ],
+        &[
+            OptionSpec::value("--format", "TYPE[:FILE]", "change the output format")
+                .with_short("-f"),
+        ],
+    ],
+};
+
+const TEST_TOOL: ToolSpec = ToolSpec {
+    name: "tool",
+    summary: "a tool to test",
+    commands: &[TEST_COMMAND],
+};
+
+#[test]
+fn usage() {
+    // Check that the usage text lists all options, with help descriptions aligned at the same
+    // column.
+    assert_eq!(
+        TEST_COMMAND.usage(),
+        concat!(
+            "Usage: tool test [OPTION]... PATH\n",
+            "  or:  tool test --all\n",
+            "\n",
+            "Test the command-line parsing.\n",
+            "\n",
+            "Options:\n",
+            "  -h, --help                    display this help and exit\n",
+            "  -j NUM, --jobs=NUM            use NUM workers\n",
+            "  --all                         process all files instead of\n",
+            "                                just PATH\n",
+            "  -f TYPE[:FILE], --format=TYPE[:FILE]\n",
+            "                                change the output format\n",
+        )
+    );
+}
+
+#[test]
+fn parse_options() {
+    // Check that options in all their forms are recognized and that the positional arguments are
+    // collected.
+    let parsed = TEST_COMMAND
+        .parse(string_vec![
+            "-j", "1", "a", "--jobs=2", "-", "--all", "-fshort", "--format", "pretty:b"
+        ])
+        .unwrap()
+        .unwrap();
+    assert!(parsed.is_set("--all"));
+    assert!(parsed.is_set("--jobs"));
+    assert_eq!(parsed.value("--jobs"), Some("2"));
+    assert_eq!(parsed.values("--jobs"), ["1", "2"]);
+    assert_eq!(parsed.values("--format"), ["short", "pretty:b"]);
+    assert_eq!(parsed.positionals(), ["a", "-"]);
+}
+
+#[test]
+fn output_header() {
+    // Check that the output header lists the tool, the time of the run, the inputs and the options
+    // in their long form.
+    let parsed = TEST_COMMAND
+        .parse(string_vec!["-j", "1", "a", "--all", "-fshort", "b"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        parsed.output_header(
+            "tool",
+            "1.0",
+            Some(UNIX_EPOCH + Duration::from_secs(1_709_210_096))
+        ),
+        concat!(
+            "# Generated by: tool 1.0 test\n",
+            "# Date: 2024-02-29T12:34:56Z\n",
+            "# Inputs: a b\n",
+            "# Options: --jobs=1 --all --format=short\n", //
+        )
+    );
+    assert_eq!(
+        parsed.output_header("tool", "1.0", None),
+        concat!(
+            "# Generated by: tool 1.0 test\n",
+            "# Inputs: a b\n",
+            "# Options: --jobs=1 --all --format=short\n", //
+        )
+    );
+}
+
+#[test]
+fn parse_missing_options() {
+    // Check that options not present on the command line are reported as such.
+    let parsed = TEST_COMMAND.parse(string_vec!["a"]).unwrap().unwrap();
+    assert!(!parsed.is_set("--all"));
+    assert_eq!(parsed.value("--jobs"), None);
+    assert!(parsed.values("--format").is_empty());
+    assert!(matches!(parsed.parse_number::<usize>("--jobs"), Ok(None)));
+}
+
+#[test]
+fn parse_dash_dash() {
+    // Check that arguments after '--' are treated as positional.
+    let parsed = TEST_COMMAND
+        .parse(string_vec!["--all", "--", "--jobs=2", "-a"])
+        .unwrap()
+        .unwrap();
+    assert!(parsed.is_set("--all"));
+    assert!(!parsed.is_set("--jobs"));
+    assert_eq!(parsed.positionals(), ["--jobs=2", "-a"]);
+}
+
+#[test]
+fn parse_unrecognized_option() {
+    // Check that an unknown option is rejected.
+    let result = TEST_COMMAND.parse(string_vec!["--jobs=2", "--bogus"]);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Unrecognized test option '--bogus'"
+    );
+}
+
+#[test]
+fn parse_missing_value() {
+    // Check that an option with a mandatory value is rejected when the value is missing.
+    let result = TEST_COMMAND.parse(string_vec!["a", "--jobs"]);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Missing argument for '--jobs'"
+    );
+}
+
+#[test]
+fn convert_invalid_value() {
+    // Check that a value failing to convert is reported with the option as it was specified.
+    let parsed = TEST_COMMAND
+        .parse(string_vec!["-j", "1", "--jobs=many"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        parsed
+            .parse_number::<usize>("--jobs")
+            .unwrap_err()
+            .to_string(),
+        "Invalid value for '--jobs=many': invalid digit found in string"
+    );
+
+    let parsed = TEST_COMMAND
+        .parse(string_vec!["-j", "many"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        parsed
+            .parse_number::<usize>("--jobs")
+            .unwrap_err()
+            .to_string(),
+        "Invalid value for '-j': invalid digit found in string"
+    );
+}
+
+#[test]
+fn check_max_positionals() {
+    // Check that excess positional arguments are rejected.
+    let parsed = TEST_COMMAND.parse(string_vec!["a", "b"]).unwrap().unwrap();
+    assert!(parsed.check_max_positionals(2).is_ok());
+    assert_eq!(
+        parsed.check_max_positionals(1).unwrap_err().to_string(),
+        "Excess test argument 'b' specified"
+    );
+}
+
+#[test]
+fn tool_usage() {
+    // Check that the global usage text lists the global options and all commands.
+    assert_eq!(
+        TEST_TOOL.usage(),
+        concat!(
+            "Usage: tool [OPTION]... COMMAND ...\n",
+            "\n",
+            "Options:\n",
+            "  -d, --debug                   enable debug output, repeat for trace output\n",
+            "  --log-level=LEVEL             log messages up to LEVEL: 'warn' (default),\n",
+            "                                'info', 'debug', or 'trace'\n",
+            "  --log-file=FILE               write log messages to FILE, instead of stderr\n",
+            "  --progress                    report progress of long operations\n",
+            "  --config=FILE                 read default command options from FILE, instead\n",
+            "                                of 'kabi-tools.conf' in the current directory\n",
+            "  --timing[=FORMAT]             measure time of operations and report it as 'text'\n",
+            "                                (default), or as a 'json' summary at the end\n",
+            "  -h, --help                    display this help and exit\n",
+            "  --version                     output version information and exit\n",
+            "\n",
+            "Commands:\n",
+            "  test                          test the parsing\n",
+            "\n",
+            "See 'tool COMMAND --help' for more information on a specific command.\n",
+        )
+    );
+}
+
+#[test]
+fn man_page() {
+    // Check that the man page documents the commands and their options.
+    let page = TEST_TOOL.man_page();
+    assert!(page.starts_with(concat!(
+        ".TH TOOL 1\n",
+        ".SH NAME\n",
+        "tool \\- a tool to test\n",
+        ".SH SYNOPSIS\n",
+        "\\fBtool\\fR [\\fIGENERAL\\-OPTION\\fR]... {\\fBtest\\fR} [\\fICOMMAND\\-OPTION\\fR]... ...\n",
+        ".SH GENERAL OPTIONS\n",
+        ".TP\n",
+        "\\fB\\-d\\fR, \\fB\\-\\-debug\\fR\n",
+        "Enable debug output, repeat for trace output.\n",
+    )));
+    assert!(page.ends_with(concat!(
+        ".SH COMMANDS\n",
+        ".TP\n",
+        "\\fBtest\\fR\n",
+        "Test the parsing.\n",
+        ".SH TEST COMMAND\n",
+        "\\fBtool\\fR \\fBtest\\fR [\\fIOPTION\\fR]... \\fIPATH\\fR\n",
+        ".br\n",
+        "\\fBtool\\fR \\fBtest\\fR \\fB\\-\\-all\\fR\n",
+        ".PP\n",
+        "Test the command\\-line parsing.\n",
+        ".PP\n",
+        "Available options:\n",
+        ".TP\n",
+        "\\fB\\-h\\fR, \\fB\\-\\-help\\fR\n",
+        "Display this help and exit.\n",
+        ".TP\n",
+        "\\fB\\-j\\fR \\fINUM\\fR, \\fB\\-\\-jobs\\fR=\\fINUM\\fR\n",
+        "Use NUM workers.\n",
+        ".TP\n",
+        "\\fB\\-\\-all\\fR\n",
+        "Process all files instead of just PATH.\n",
+        ".TP\n",
+        "\\fB\\-f\\fR \\fITYPE\\fR[:\\fIFILE\\fR], \\fB\\-\\-format\\fR=\\fITYPE\\fR[:\\fIFILE\\fR]\n",
+        "Change the output format.\n",
+    )));
+}
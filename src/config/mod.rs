@@ -0,0 +1,354 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A representation of tool configuration files and tools for working with the data.
+//!
+//! A configuration file is written in a subset of the TOML format. It consists of tables
+//! introduced by `[name.name...]` headers and settings in the form `key = value`. A value can be
+//! a basic `"..."` or literal `'...'` string, an integer, a boolean, or a single-line array of
+//! values. Comments start with '#' and extend to the end of the line.
+
+use crate::text::read_lines;
+use crate::{Error, PathFile, debug};
+use std::io::prelude::*;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests;
+
+/// The name of the configuration file that is looked up in the current directory when no file is
+/// explicitly specified.
+pub const DEFAULT_CONFIG_PATH: &str = "kabi-tools.conf";
+
+/// A value of a configuration setting.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+/// A configuration table, holding its settings in the order they were specified.
+type Table = Vec<(String, Value)>;
+
+/// A collection of configuration tables.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Config {
+    tables: Vec<(String, Table)>, // The root table has an empty name.
+}
+
+impl Config {
+    /// Creates a new empty `Config` object.
+    pub fn new() -> Self {
+        Self { tables: Vec::new() }
+    }
+
+    /// Loads configuration data from the specified file.
+    ///
+    /// New tables are appended to the already present ones and take precedence over them.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+
+        let file = PathFile::open(path).map_err(|err| {
+            Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+        })?;
+
+        self.load_buffer(path, file)
+    }
+
+    /// Loads configuration data from the specified reader.
+    ///
+    /// The `path` should point to the configuration file name, indicating the origin of the data.
+    /// New tables are appended to the already present ones and take precedence over them.
+    pub fn load_buffer<P: AsRef<Path>, R: Read>(
+        &mut self,
+        path: P,
+        reader: R,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        debug!("Loading configuration data from '{}'", path.display());
+
+        // Read all content from the file.
+        let lines = match read_lines(reader) {
+            Ok(lines) => lines,
+            Err(err) => return Err(Error::new_io("Failed to read configuration data", err)),
+        };
+
+        // Parse all tables, committing them only if the whole file is valid.
+        let mut new_tables = vec![(String::new(), Table::new())];
+        for (line_idx, line) in lines.iter().enumerate() {
+            let mut parser = LineParser {
+                path,
+                line_idx,
+                line,
+                pos: 0,
+            };
+            if parser.is_at_end() {
+                continue;
+            }
+
+            if parser.peek() == Some('[') {
+                let offset = parser.pos;
+                parser.pos += 1;
+                let name = parser.parse_table_name()?;
+                if new_tables.iter().any(|(other_name, _)| *other_name == name) {
+                    return Err(parser.error(&format!("Duplicate table '{}'", name), offset));
+                }
+                parser.expect_end()?;
+                new_tables.push((name, Table::new()));
+                continue;
+            }
+
+            let offset = parser.pos;
+            let key = parser.parse_key()?;
+            parser.skip_whitespace();
+            if parser.peek() != Some('=') {
+                return Err(parser.error("Expected '=' after the key", parser.pos));
+            }
+            parser.pos += 1;
+            parser.skip_whitespace();
+            let value = parser.parse_value()?;
+            parser.expect_end()?;
+
+            let (_, table) = new_tables.last_mut().unwrap();
+            if table.iter().any(|(other_key, _)| *other_key == key) {
+                return Err(parser.error(&format!("Duplicate key '{}'", key), offset));
+            }
+            table.push((key, value));
+        }
+        self.tables.append(&mut new_tables);
+
+        Ok(())
+    }
+
+    /// Returns the value of the specified setting in the given table, or `None` if the setting is
+    /// not present.
+    ///
+    /// If the table is defined multiple times, the most recently loaded definition is consulted
+    /// first.
+    pub fn get(&self, table_name: &str, key: &str) -> Option<&Value> {
+        self.tables
+            .iter()
+            .rev()
+            .filter(|(name, _)| name == table_name)
+            .find_map(|(_, table)| {
+                table
+                    .iter()
+                    .find(|(other_key, _)| other_key == key)
+                    .map(|(_, value)| value)
+            })
+    }
+
+    /// Returns command-line options that apply the configured defaults of the given command of
+    /// a tool.
+    ///
+    /// The settings are read from the `[<tool>.<command>]` table, followed by the
+    /// `[<tool>.<command>.arch.<arch>]` table. If `maybe_arch` is `None`, the architecture is
+    /// taken from the `arch` setting of the first table. Each setting is converted to a long option
+    /// named after its key. A string or integer value becomes `--key=value`, `true` becomes `--key`
+    /// and `false` is omitted. Each element of an array results in a separate option.
+    pub fn command_args(&self, tool: &str, command: &str, maybe_arch: Option<&str>) -> Vec<String> {
+        let table_name = format!("{}.{}", tool, command);
+        let maybe_arch = maybe_arch.or_else(|| match self.get(&table_name, "arch") {
+            Some(Value::String(arch)) => Some(arch.as_str()),
+            _ => None,
+        });
+
+        let mut table_names = vec![table_name.clone()];
+        if let Some(arch) = maybe_arch {
+            table_names.push(format!("{}.arch.{}", table_name, arch));
+        }
+
+        let mut args = Vec::new();
+        for table_name in &table_names {
+            for (name, table) in &self.tables {
+                if name == table_name {
+                    for (key, value) in table {
+                        push_args(key, value, &mut args);
+                    }
+                }
+            }
+        }
+        args
+    }
+}
+
+/// Converts a single setting to command-line options and appends them to `args`.
+fn push_args(key: &str, value: &Value, args: &mut Vec<String>) {
+    match value {
+        Value::String(string) => args.push(format!("--{}={}", key, string)),
+        Value::Integer(integer) => args.push(format!("--{}={}", key, integer)),
+        Value::Boolean(true) => args.push(format!("--{}", key)),
+        Value::Boolean(false) => {}
+        Value::Array(values) => {
+            for value in values {
+                push_args(key, value, args);
+            }
+        }
+    }
+}
+
+/// A parser of a single line of configuration data.
+struct LineParser<'a> {
+    path: &'a Path,
+    line_idx: usize,
+    line: &'a str,
+    pos: usize, // Byte offset of the next character to process.
+}
+
+impl LineParser<'_> {
+    /// Creates a new `Error::ParseAt` pointing to the given byte offset in the line.
+    fn error(&self, desc: &str, offset: usize) -> Error {
+        Error::new_parse_format_at(desc, self.path, self.line_idx + 1, self.line, offset)
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.line[self.pos..].chars().next()
+    }
+
+    /// Skips over any spaces and tabs.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek()
+            && (c == ' ' || c == '\t')
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips over any whitespace and returns whether the rest of the line is empty or a comment.
+    fn is_at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    /// Checks that nothing except whitespace and a comment is left on the line.
+    fn expect_end(&mut self) -> Result<(), Error> {
+        if !self.is_at_end() {
+            return Err(self.error("Unexpected data found at the end of the line", self.pos));
+        }
+        Ok(())
+    }
+
+    /// Parses a bare key, consisting of ASCII letters, digits, '-' and '_'.
+    fn parse_key(&mut self) -> Result<String, Error> {
+        let start = self.pos;
+        while let Some(c) = self.peek()
+            && (c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.error("Expected a key", start));
+        }
+        Ok(self.line[start..self.pos].to_string())
+    }
+
+    /// Parses a table name in the form `key.key...]`, after the opening '['.
+    fn parse_table_name(&mut self) -> Result<String, Error> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_whitespace();
+            keys.push(self.parse_key()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some('.') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(keys.join("."));
+                }
+                _ => {
+                    return Err(self.error("Expected '.' or ']' in the table header", self.pos));
+                }
+            }
+        }
+    }
+
+    /// Parses a single value.
+    fn parse_value(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        match self.peek() {
+            Some('"') => self.parse_basic_string(),
+            Some('\'') => {
+                self.pos += 1;
+                match self.line[self.pos..].find('\'') {
+                    Some(len) => {
+                        let string = self.line[self.pos..self.pos + len].to_string();
+                        self.pos += len + 1;
+                        Ok(Value::String(string))
+                    }
+                    None => Err(self.error("Unterminated string", start)),
+                }
+            }
+            Some('[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        return Ok(Value::Array(values));
+                    }
+                    values.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(values));
+                        }
+                        _ => return Err(self.error("Expected ',' or ']' in the array", self.pos)),
+                    }
+                }
+            }
+            _ => {
+                while let Some(c) = self.peek()
+                    && (c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '_')
+                {
+                    self.pos += 1;
+                }
+                let word = &self.line[start..self.pos];
+                match word {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => match word.replace('_', "").parse::<i64>() {
+                        Ok(integer) if !word.is_empty() => Ok(Value::Integer(integer)),
+                        _ => Err(self.error("Invalid value", start)),
+                    },
+                }
+            }
+        }
+    }
+
+    /// Parses a basic string enclosed in '"', which can contain the escape sequences `\\`, `\"`,
+    /// `\n` and `\t`.
+    fn parse_basic_string(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut string = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("Unterminated string", start));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(Value::String(string)),
+                '\\' => {
+                    let escape = match self.peek() {
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        _ => {
+                            return Err(self.error("Invalid escape sequence", self.pos - 1));
+                        }
+                    };
+                    self.pos += 1;
+                    string.push(escape);
+                }
+                _ => string.push(c),
+            }
+        }
+    }
+}
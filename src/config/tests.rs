@@ -0,0 +1,219 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::{assert_ok, assert_parse_err, bytes};
+
+#[test]
+fn read_config() {
+    // Check that tables and settings of all value types are parsed, skipping empty and comment
+    // lines.
+    let mut config = Config::new();
+    let result = config.load_buffer(
+        "kabi-tools.conf",
+        bytes!(
+            "# Defaults for the SLE builds\n",
+            "\n",
+            "[ksymvers.compare]\n",
+            "rules = \"severities # all\"  # Comment\n",
+            "arch = 'x86_64'\n",
+            "format = [\"short\", 'symbols:out\\sym.txt',]\n",
+            "\n",
+            "[ ksymtypes . compare ]\n",
+            "jobs = 1_000\n",
+            "word-diff = true\n",
+            "per-file = false\n",
+            "symbol-prefix = \"a\\\"b\\\\c\"\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        config,
+        Config {
+            tables: vec![
+                (String::new(), vec![]),
+                (
+                    "ksymvers.compare".to_string(),
+                    vec![
+                        (
+                            "rules".to_string(),
+                            Value::String("severities # all".to_string())
+                        ),
+                        ("arch".to_string(), Value::String("x86_64".to_string())),
+                        (
+                            "format".to_string(),
+                            Value::Array(vec![
+                                Value::String("short".to_string()),
+                                Value::String("symbols:out\\sym.txt".to_string()),
+                            ])
+                        ),
+                    ]
+                ),
+                (
+                    "ksymtypes.compare".to_string(),
+                    vec![
+                        ("jobs".to_string(), Value::Integer(1000)),
+                        ("word-diff".to_string(), Value::Boolean(true)),
+                        ("per-file".to_string(), Value::Boolean(false)),
+                        (
+                            "symbol-prefix".to_string(),
+                            Value::String("a\"b\\c".to_string())
+                        ),
+                    ]
+                ),
+            ]
+        }
+    );
+}
+
+#[test]
+fn read_invalid_value() {
+    // Check that an invalid value is rejected and no data is retained.
+    let mut config = Config::new();
+    let result = config.load_buffer(
+        "kabi-tools.conf",
+        bytes!(
+            "[ksymvers.compare]\n",
+            "rules = severities\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid value\n",
+            " kabi-tools.conf:2:9\n",
+            " | rules = severities\n",
+            " |         ^", //
+        )
+    );
+    assert_eq!(config, Config::new());
+}
+
+#[test]
+fn read_unterminated_string() {
+    // Check that an unterminated string is rejected.
+    let mut config = Config::new();
+    let result = config.load_buffer("kabi-tools.conf", bytes!("rules = \"severities\n"));
+    assert_parse_err!(
+        result,
+        concat!(
+            "Unterminated string\n",
+            " kabi-tools.conf:1:9\n",
+            " | rules = \"severities\n",
+            " |         ^", //
+        )
+    );
+}
+
+#[test]
+fn read_duplicate_key() {
+    // Check that a key specified twice in the same table is rejected.
+    let mut config = Config::new();
+    let result = config.load_buffer(
+        "kabi-tools.conf",
+        bytes!(
+            "[ksymvers.compare]\n",
+            "rules = 'a'\n",
+            "rules = 'b'\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Duplicate key 'rules'\n",
+            " kabi-tools.conf:3:1\n",
+            " | rules = 'b'\n",
+            " | ^", //
+        )
+    );
+}
+
+#[test]
+fn read_duplicate_table() {
+    // Check that a table defined twice in the same file is rejected.
+    let mut config = Config::new();
+    let result = config.load_buffer(
+        "kabi-tools.conf",
+        bytes!(
+            "[ksymvers.compare]\n",
+            "[ksymvers.compare]\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Duplicate table 'ksymvers.compare'\n",
+            " kabi-tools.conf:2:1\n",
+            " | [ksymvers.compare]\n",
+            " | ^", //
+        )
+    );
+}
+
+#[test]
+fn command_args() {
+    // Check that the settings of a command are converted to command-line options, followed by the
+    // settings of the selected architecture.
+    let mut config = Config::new();
+    let result = config.load_buffer(
+        "kabi-tools.conf",
+        bytes!(
+            "[ksymvers.compare]\n",
+            "rules = 'severities'\n",
+            "format = ['short', 'symbols:out.txt']\n",
+            "short-circuit = true\n",
+            "lenient = false\n",
+            "\n",
+            "[ksymvers.compare.arch.x86_64]\n",
+            "rules = 'severities-x86_64'\n",
+            "\n",
+            "[ksymtypes.compare]\n",
+            "jobs = 4\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        config.command_args("ksymvers", "compare", None),
+        [
+            "--rules=severities",
+            "--format=short",
+            "--format=symbols:out.txt",
+            "--short-circuit",
+        ]
+    );
+    assert_eq!(
+        config.command_args("ksymvers", "compare", Some("x86_64")),
+        [
+            "--rules=severities",
+            "--format=short",
+            "--format=symbols:out.txt",
+            "--short-circuit",
+            "--rules=severities-x86_64",
+        ]
+    );
+    assert_eq!(
+        config.command_args("ksymtypes", "compare", Some("x86_64")),
+        ["--jobs=4"]
+    );
+    assert!(config.command_args("ksymtypes", "show", None).is_empty());
+}
+
+#[test]
+fn command_args_configured_arch() {
+    // Check that the architecture can be selected by the configuration itself.
+    let mut config = Config::new();
+    let result = config.load_buffer(
+        "kabi-tools.conf",
+        bytes!(
+            "[ksymvers.compare]\n",
+            "arch = 'aarch64'\n",
+            "[ksymvers.compare.arch.aarch64]\n",
+            "rules = 'severities-aarch64'\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        config.command_args("ksymvers", "compare", None),
+        ["--arch=aarch64", "--rules=severities-aarch64"]
+    );
+}
@@ -0,0 +1,63 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A minimal implementation of the CRC-32 checksum, as used by genksyms to compute symbol
+//! versions.
+
+#[cfg(test)]
+mod tests;
+
+/// The lookup table for the reflected polynomial 0xedb88320.
+const TABLE: [u32; 256] = make_table();
+
+/// Computes the lookup table.
+const fn make_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// An incremental CRC-32 hasher.
+#[derive(Clone)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Creates a new `Crc32` hasher.
+    pub fn new() -> Self {
+        Self { crc: 0xffffffff }
+    }
+
+    /// Feeds the given data into the hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.crc = TABLE[((self.crc ^ byte as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    /// Finishes the computation and returns the checksum.
+    pub fn finish(&self) -> u32 {
+        self.crc ^ 0xffffffff
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+
+/// Computes the CRC-32 checksum of the given data.
+fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = Crc32::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+#[test]
+fn checksum_known_values() {
+    // Check the checksums of several standard test messages.
+    assert_eq!(crc32(b""), 0x00000000);
+    assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    assert_eq!(
+        crc32(b"The quick brown fox jumps over the lazy dog"),
+        0x414fa339
+    );
+}
+
+#[test]
+fn checksum_incremental() {
+    // Check that feeding the data in pieces gives the same checksum as a single update.
+    let data = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let expected = crc32(&data);
+    for piece_size in [1, 7, 64, 999] {
+        let mut hasher = Crc32::new();
+        for piece in data.chunks(piece_size) {
+            hasher.update(piece);
+        }
+        assert_eq!(hasher.finish(), expected);
+    }
+}
@@ -0,0 +1,632 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! Demangling of Rust symbol names in the v0 mangling scheme.
+//!
+//! Kernels built with Rust support export symbols such as
+//! `_RNvNtCsbDqzXfLQacH_6kernel5print11call_printk`. The functions in this module turn such names
+//! into their readable form, for instance, `kernel::print::call_printk`. Crate hashes are omitted
+//! from the output.
+
+use std::borrow::Cow;
+use std::mem;
+
+#[cfg(test)]
+mod tests;
+
+/// The maximum nesting of paths, types and constants, protecting against a stack overflow.
+const MAX_DEPTH: usize = 256;
+
+/// Demangles the given symbol name if it is a Rust symbol in the v0 mangling scheme.
+///
+/// Returns `None` if the name is not a valid v0 symbol.
+pub fn demangle(name: &str) -> Option<String> {
+    let sym = name.strip_prefix("_R")?;
+    if !sym.is_ascii() {
+        return None;
+    }
+
+    let mut demangler = Demangler {
+        sym: sym.as_bytes(),
+        pos: 0,
+        depth: 0,
+        bound_lifetimes: 0,
+        out: String::new(),
+    };
+    demangler.print_path(true)?;
+
+    // Skip the instantiating crate, if present.
+    if demangler
+        .peek()
+        .is_some_and(|byte| byte.is_ascii_uppercase())
+    {
+        demangler.skip(|demangler| demangler.print_path(false))?;
+    }
+
+    // Preserve a vendor-specific suffix, such as `.llvm.1234`.
+    let suffix = &sym[demangler.pos..];
+    if !suffix.is_empty() && !suffix.starts_with(['.', '$']) {
+        return None;
+    }
+    demangler.out.push_str(suffix);
+    Some(demangler.out)
+}
+
+/// Returns the name of the given symbol as it should be displayed to the user, which is its
+/// demangled form if `demangle` is `true` and the name is a Rust symbol, or the name itself
+/// otherwise.
+pub fn display_name(name: &str, demangle: bool) -> Cow<'_, str> {
+    if demangle && let Some(demangled) = self::demangle(name) {
+        return Cow::Owned(demangled);
+    }
+    Cow::Borrowed(name)
+}
+
+/// A parser of a v0 symbol name, printing its demangled form as it goes.
+struct Demangler<'a> {
+    /// The symbol name, without the `_R` prefix.
+    sym: &'a [u8],
+    /// The current parsing position in `sym`.
+    pos: usize,
+    /// The current nesting of paths, types and constants.
+    depth: usize,
+    /// The number of lifetimes bound by the enclosing binders.
+    bound_lifetimes: u64,
+    /// The demangled output.
+    out: String,
+}
+
+impl Demangler<'_> {
+    /// Returns the next byte without consuming it.
+    fn peek(&self) -> Option<u8> {
+        self.sym.get(self.pos).copied()
+    }
+
+    /// Consumes and returns the next byte.
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    /// Consumes the next byte if it is equal to `byte`, returning whether it was.
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    /// Runs the parsing function `f` with its output discarded.
+    fn skip<F>(&mut self, f: F) -> Option<()>
+    where
+        F: FnOnce(&mut Self) -> Option<()>,
+    {
+        let out = mem::take(&mut self.out);
+        let result = f(self);
+        self.out = out;
+        result
+    }
+
+    /// Runs the parsing function `f` one nesting level deeper.
+    fn nested<T, F>(&mut self, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Parses a backreference, following the `B` tag, and runs the parsing function `f` at the
+    /// referenced position.
+    fn backref<T, F>(&mut self, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        let start = self.pos - 1;
+        let target = usize::try_from(self.base62()?).ok()?;
+        if target >= start {
+            return None;
+        }
+        let pos = mem::replace(&mut self.pos, target);
+        let result = self.nested(f);
+        self.pos = pos;
+        result
+    }
+
+    /// Parses a base-62 number terminated by `_`.
+    fn base62(&mut self) -> Option<u64> {
+        if self.eat(b'_') {
+            return Some(0);
+        }
+        let mut value = 0u64;
+        loop {
+            let digit = match self.next()? {
+                byte @ b'0'..=b'9' => byte - b'0',
+                byte @ b'a'..=b'z' => byte - b'a' + 10,
+                byte @ b'A'..=b'Z' => byte - b'A' + 36,
+                b'_' => return value.checked_add(1),
+                _ => return None,
+            };
+            value = value.checked_mul(62)?.checked_add(u64::from(digit))?;
+        }
+    }
+
+    /// Parses an optional base-62 number which follows the given tag, returning 0 if the tag is
+    /// not present.
+    fn opt_base62(&mut self, tag: u8) -> Option<u64> {
+        if !self.eat(tag) {
+            return Some(0);
+        }
+        self.base62()?.checked_add(1)
+    }
+
+    /// Parses a decimal number.
+    fn decimal(&mut self) -> Option<usize> {
+        let first = self.next()?;
+        if !first.is_ascii_digit() {
+            return None;
+        }
+        let mut value = usize::from(first - b'0');
+        if value == 0 {
+            return Some(0);
+        }
+        while let Some(byte) = self.peek().filter(u8::is_ascii_digit) {
+            self.pos += 1;
+            value = value
+                .checked_mul(10)?
+                .checked_add(usize::from(byte - b'0'))?;
+        }
+        Some(value)
+    }
+
+    /// Parses an identifier without a disambiguator, decoding Punycode if needed.
+    fn undisambiguated_ident(&mut self) -> Option<String> {
+        let is_punycode = self.eat(b'u');
+        let len = self.decimal()?;
+        self.eat(b'_');
+        let bytes = self.sym.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+
+        // INVARIANT: The whole symbol was checked to be ASCII.
+        let ident = std::str::from_utf8(bytes).unwrap();
+        if is_punycode {
+            decode_punycode(ident)
+        } else {
+            Some(ident.to_string())
+        }
+    }
+
+    /// Parses an identifier, returning its disambiguator and name.
+    fn ident(&mut self) -> Option<(u64, String)> {
+        let disambiguator = self.opt_base62(b's')?;
+        Some((disambiguator, self.undisambiguated_ident()?))
+    }
+
+    /// Prints the lifetime with the given index, counted from the innermost binder.
+    fn print_lifetime(&mut self, index: u64) -> Option<()> {
+        if index == 0 {
+            self.out.push_str("'_");
+            return Some(());
+        }
+        let depth = self.bound_lifetimes.checked_sub(index)?;
+        match u8::try_from(depth) {
+            Ok(depth) if depth < 26 => {
+                self.out.push('\'');
+                self.out.push(char::from(b'a' + depth));
+            }
+            _ => self.out.push_str(&format!("'_{}", depth)),
+        }
+        Some(())
+    }
+
+    /// Parses an optional binder and runs the printing function `f` with the bound lifetimes in
+    /// scope.
+    fn with_binder<F>(&mut self, f: F) -> Option<()>
+    where
+        F: FnOnce(&mut Self) -> Option<()>,
+    {
+        let count = self.opt_base62(b'G')?;
+        if count > 0 {
+            self.out.push_str("for<");
+            for i in 0..count {
+                if i > 0 {
+                    self.out.push_str(", ");
+                }
+                self.bound_lifetimes = self.bound_lifetimes.checked_add(1)?;
+                self.print_lifetime(1)?;
+            }
+            self.out.push_str("> ");
+        }
+        let result = f(self);
+        self.bound_lifetimes -= count;
+        result
+    }
+
+    /// Parses and prints generic arguments up to the terminating `E`, separated by commas.
+    fn print_generic_args(&mut self) -> Option<()> {
+        let mut first = true;
+        while !self.eat(b'E') {
+            if !first {
+                self.out.push_str(", ");
+            }
+            first = false;
+            if self.eat(b'L') {
+                let index = self.base62()?;
+                self.print_lifetime(index)?;
+            } else if self.eat(b'K') {
+                self.print_const()?;
+            } else {
+                self.print_type()?;
+            }
+        }
+        Some(())
+    }
+
+    /// Parses and prints a path. Generic arguments are introduced by `::<` in the value namespace
+    /// and by `<` in the type namespace.
+    fn print_path(&mut self, in_value: bool) -> Option<()> {
+        self.nested(|demangler| demangler.print_path_inner(in_value))
+    }
+
+    fn print_path_inner(&mut self, in_value: bool) -> Option<()> {
+        match self.next()? {
+            b'C' => {
+                let (_, name) = self.ident()?;
+                self.out.push_str(&name);
+            }
+            b'N' => {
+                let namespace = self.next()?;
+                if !namespace.is_ascii_alphabetic() {
+                    return None;
+                }
+                self.print_path(in_value)?;
+                let (disambiguator, name) = self.ident()?;
+                if namespace.is_ascii_uppercase() {
+                    self.out.push_str("::{");
+                    match namespace {
+                        b'C' => self.out.push_str("closure"),
+                        b'S' => self.out.push_str("shim"),
+                        _ => self.out.push(char::from(namespace)),
+                    }
+                    if !name.is_empty() {
+                        self.out.push(':');
+                        self.out.push_str(&name);
+                    }
+                    self.out.push_str(&format!("#{}}}", disambiguator));
+                } else if !name.is_empty() {
+                    self.out.push_str("::");
+                    self.out.push_str(&name);
+                }
+            }
+            tag @ (b'M' | b'X' | b'Y') => {
+                if tag != b'Y' {
+                    // Skip the path of the impl block.
+                    self.opt_base62(b's')?;
+                    self.skip(|demangler| demangler.print_path(false))?;
+                }
+                self.out.push('<');
+                self.print_type()?;
+                if tag != b'M' {
+                    self.out.push_str(" as ");
+                    self.print_path(false)?;
+                }
+                self.out.push('>');
+            }
+            b'I' => {
+                self.print_path(in_value)?;
+                if in_value {
+                    self.out.push_str("::");
+                }
+                self.out.push('<');
+                self.print_generic_args()?;
+                self.out.push('>');
+            }
+            b'B' => self.backref(|demangler| demangler.print_path_inner(in_value))?,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Parses and prints a path in the type namespace, leaving its generic arguments open if it
+    /// has any. Returns whether the arguments were left open.
+    fn print_path_maybe_open_generics(&mut self) -> Option<bool> {
+        if self.eat(b'B') {
+            return self.backref(|demangler| demangler.print_path_maybe_open_generics());
+        }
+        if self.eat(b'I') {
+            self.print_path(false)?;
+            self.out.push('<');
+            self.print_generic_args()?;
+            return Some(true);
+        }
+        self.print_path(false)?;
+        Some(false)
+    }
+
+    /// Parses and prints a type.
+    fn print_type(&mut self) -> Option<()> {
+        self.nested(Self::print_type_inner)
+    }
+
+    fn print_type_inner(&mut self) -> Option<()> {
+        let tag = self.next()?;
+        if let Some(name) = basic_type(tag) {
+            self.out.push_str(name);
+            return Some(());
+        }
+
+        match tag {
+            b'R' | b'Q' => {
+                self.out.push('&');
+                if self.eat(b'L') {
+                    let index = self.base62()?;
+                    if index != 0 {
+                        self.print_lifetime(index)?;
+                        self.out.push(' ');
+                    }
+                }
+                if tag == b'Q' {
+                    self.out.push_str("mut ");
+                }
+                self.print_type()?;
+            }
+            b'P' => {
+                self.out.push_str("*const ");
+                self.print_type()?;
+            }
+            b'O' => {
+                self.out.push_str("*mut ");
+                self.print_type()?;
+            }
+            b'A' => {
+                self.out.push('[');
+                self.print_type()?;
+                self.out.push_str("; ");
+                self.print_const()?;
+                self.out.push(']');
+            }
+            b'S' => {
+                self.out.push('[');
+                self.print_type()?;
+                self.out.push(']');
+            }
+            b'T' => {
+                self.out.push('(');
+                let mut count = 0;
+                while !self.eat(b'E') {
+                    if count > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.print_type()?;
+                    count += 1;
+                }
+                if count == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            b'F' => self.with_binder(Self::print_fn_sig)?,
+            b'D' => {
+                self.with_binder(Self::print_dyn_bounds)?;
+                if !self.eat(b'L') {
+                    return None;
+                }
+                let index = self.base62()?;
+                if index != 0 {
+                    self.out.push_str(" + ");
+                    self.print_lifetime(index)?;
+                }
+            }
+            b'B' => self.backref(Self::print_type_inner)?,
+            _ => {
+                self.pos -= 1;
+                self.print_path(false)?;
+            }
+        }
+        Some(())
+    }
+
+    /// Parses and prints a function signature, following the `F` tag and an optional binder.
+    fn print_fn_sig(&mut self) -> Option<()> {
+        if self.eat(b'U') {
+            self.out.push_str("unsafe ");
+        }
+        if self.eat(b'K') {
+            let abi = if self.eat(b'C') {
+                "C".to_string()
+            } else {
+                self.undisambiguated_ident()?.replace('_', "-")
+            };
+            self.out.push_str(&format!("extern \"{}\" ", abi));
+        }
+        self.out.push_str("fn(");
+        let mut first = true;
+        while !self.eat(b'E') {
+            if !first {
+                self.out.push_str(", ");
+            }
+            first = false;
+            self.print_type()?;
+        }
+        self.out.push(')');
+        if !self.eat(b'u') {
+            self.out.push_str(" -> ");
+            self.print_type()?;
+        }
+        Some(())
+    }
+
+    /// Parses and prints the traits of a `dyn` type, following the `D` tag and an optional
+    /// binder.
+    fn print_dyn_bounds(&mut self) -> Option<()> {
+        self.out.push_str("dyn ");
+        let mut first = true;
+        while !self.eat(b'E') {
+            if !first {
+                self.out.push_str(" + ");
+            }
+            first = false;
+
+            let mut open = self.print_path_maybe_open_generics()?;
+            while self.eat(b'p') {
+                self.out.push_str(if open { ", " } else { "<" });
+                open = true;
+                let name = self.undisambiguated_ident()?;
+                self.out.push_str(&name);
+                self.out.push_str(" = ");
+                self.print_type()?;
+            }
+            if open {
+                self.out.push('>');
+            }
+        }
+        Some(())
+    }
+
+    /// Parses and prints a constant.
+    fn print_const(&mut self) -> Option<()> {
+        self.nested(Self::print_const_inner)
+    }
+
+    fn print_const_inner(&mut self) -> Option<()> {
+        let tag = self.next()?;
+        match tag {
+            b'p' => self.out.push('_'),
+            b'B' => self.backref(Self::print_const_inner)?,
+            b'a' | b's' | b'l' | b'x' | b'n' | b'i' | b'h' | b't' | b'm' | b'y' | b'o' | b'j' => {
+                let negative = self.eat(b'n');
+                let value = self.const_data()?;
+                if negative {
+                    self.out.push('-');
+                }
+                self.out.push_str(&value);
+            }
+            b'b' => match self.const_data()?.as_str() {
+                "0" => self.out.push_str("false"),
+                "1" => self.out.push_str("true"),
+                _ => return None,
+            },
+            b'c' => {
+                let value = self.const_data()?.parse::<u32>().ok()?;
+                let c = char::from_u32(value)?;
+                self.out.push_str(&format!("{:?}", c));
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Parses hexadecimal constant data terminated by `_`, returning its value in decimal, or in
+    /// hexadecimal if it is too large.
+    fn const_data(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self.peek()?.is_ascii_hexdigit() {
+            self.pos += 1;
+        }
+        // INVARIANT: The whole symbol was checked to be ASCII.
+        let digits = std::str::from_utf8(&self.sym[start..self.pos]).unwrap();
+        if !self.eat(b'_') {
+            return None;
+        }
+        if digits.is_empty() {
+            return Some("0".to_string());
+        }
+        match u128::from_str_radix(digits, 16) {
+            Ok(value) => Some(value.to_string()),
+            Err(_) => Some(format!("0x{}", digits)),
+        }
+    }
+}
+
+/// Returns the name of the basic type with the given tag.
+fn basic_type(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        b'a' => "i8",
+        b'b' => "bool",
+        b'c' => "char",
+        b'd' => "f64",
+        b'e' => "str",
+        b'f' => "f32",
+        b'h' => "u8",
+        b'i' => "isize",
+        b'j' => "usize",
+        b'l' => "i32",
+        b'm' => "u32",
+        b'n' => "i128",
+        b'o' => "u128",
+        b's' => "i16",
+        b't' => "u16",
+        b'u' => "()",
+        b'v' => "...",
+        b'x' => "i64",
+        b'y' => "u64",
+        b'z' => "!",
+        b'p' => "_",
+        _ => return None,
+    })
+}
+
+/// Decodes a Punycode-encoded identifier, in which the delimiter `-` is replaced by `_`.
+fn decode_punycode(ident: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+
+    let (basic, encoded) = match ident.rfind('_') {
+        Some(idx) => (&ident[..idx], &ident[idx + 1..]),
+        None => ("", ident),
+    };
+    let mut output = basic.chars().collect::<Vec<_>>();
+    let mut n = 128u32;
+    let mut bias = 72u32;
+    let mut i = 0u32;
+
+    let mut digits = encoded.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = match digits.next()? {
+                byte @ b'a'..=b'z' => byte - b'a',
+                byte @ b'0'..=b'9' => byte - b'0' + 26,
+                _ => return None,
+            };
+            let digit = u32::from(digit);
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = k.saturating_sub(bias).clamp(T_MIN, T_MAX);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        // Adapt the bias.
+        let len = u32::try_from(output.len()).ok()? + 1;
+        let mut delta = (i - old_i) / if old_i == 0 { DAMP } else { 2 };
+        delta += delta / len;
+        let mut k = 0;
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+        bias = k + (BASE - T_MIN + 1) * delta / (delta + SKEW);
+
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(usize::try_from(i).ok()?, char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
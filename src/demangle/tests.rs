@@ -0,0 +1,89 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+
+#[test]
+fn demangle_paths() {
+    // Check that nested paths are demangled and crate hashes are omitted.
+    assert_eq!(
+        demangle("_RNvNtCsbDqzXfLQacH_6kernel5print11call_printk").as_deref(),
+        Some("kernel::print::call_printk")
+    );
+    assert_eq!(
+        demangle("_RNvC6_123foo3bar").as_deref(),
+        Some("123foo::bar")
+    );
+    assert_eq!(
+        demangle("_RNCNCNgCs6DXkGYLi8lr_2cc5spawn00B5_").as_deref(),
+        Some("cc::spawn::{closure#0}::{closure#0}")
+    );
+}
+
+#[test]
+fn demangle_punycode() {
+    // Check that Punycode-encoded identifiers are decoded.
+    assert_eq!(
+        demangle("_RNqCs4fqI2P2rA04_11utf8_identsu30____7hkackfecea1cbdathfdh9hlq6y").as_deref(),
+        Some("utf8_idents::საჭმელად_გემრიელი_სადილი")
+    );
+}
+
+#[test]
+fn demangle_generics() {
+    // Check that generic arguments, impl blocks and dyn types are demangled.
+    assert_eq!(
+        demangle("_RNvMC0INtC8arrayvec8ArrayVechKj7b_E3new").as_deref(),
+        Some("<arrayvec::ArrayVec<u8, 123>>::new")
+    );
+    assert_eq!(
+        demangle(
+            "_RINbNbCskIICzLVDPPb_5alloc5alloc8box_freeDINbNiB4_5boxed5FnBoxuEp6OutputuEL_ECs1iopQbuBiw2_3std"
+        )
+        .as_deref(),
+        Some("alloc::alloc::box_free::<dyn alloc::boxed::FnBox<(), Output = ()>>")
+    );
+}
+
+#[test]
+fn demangle_consts() {
+    // Check that constant generic arguments are demangled.
+    assert_eq!(
+        demangle("_RMCs4fqI2P2rA04_13const_genericINtB0_8UnsignedKhb_E").as_deref(),
+        Some("<const_generic::Unsigned<11>>")
+    );
+    assert_eq!(
+        demangle("_RMCs4fqI2P2rA04_13const_genericINtB0_6SignedKanb_E").as_deref(),
+        Some("<const_generic::Signed<-11>>")
+    );
+    assert_eq!(
+        demangle("_RMCs4fqI2P2rA04_13const_genericINtB0_4BoolKb1_E").as_deref(),
+        Some("<const_generic::Bool<true>>")
+    );
+    assert_eq!(
+        demangle("_RMCs4fqI2P2rA04_13const_genericINtB0_4CharKc76_E").as_deref(),
+        Some("<const_generic::Char<'v'>>")
+    );
+}
+
+#[test]
+fn demangle_invalid() {
+    // Check that names which are not valid v0 symbols are rejected.
+    assert_eq!(demangle("foo"), None);
+    assert_eq!(
+        demangle("_ZN6kernel5print11call_printk17h0123456789abcdefE"),
+        None
+    );
+    assert_eq!(demangle("_RNvC3foo"), None);
+    assert_eq!(demangle("_RNvC3foo3barX"), None);
+    assert_eq!(demangle("_RB_"), None);
+}
+
+#[test]
+fn display_names() {
+    // Check that only Rust symbols are demangled for display, and only if requested.
+    let name = "_RNvNtCsbDqzXfLQacH_6kernel5print11call_printk";
+    assert_eq!(display_name(name, true), "kernel::print::call_printk");
+    assert_eq!(display_name(name, false), name);
+    assert_eq!(display_name("printk", true), "printk");
+}
@@ -0,0 +1,433 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! C bindings for the comparison engine.
+//!
+//! The functions allow scripts and programs written in other languages to load and compare kABI
+//! data in-process, instead of running the tools and parsing their text output. The declarations
+//! for C are provided in `include/suse_kabi_tools.h`.
+//!
+//! Corpuses, filters, rules and reports are passed as opaque handles, which must be released by the
+//! matching `*_free()` function. A function that fails returns `NULL`, or -1 if it returns an
+//! integer, and records a description of the problem which can be obtained by
+//! [`kabi_last_error()`]. An internal panic is reported in the same way instead of unwinding into
+//! the caller.
+
+use crate::Error;
+use crate::api::{
+    CompareOptions, ComparisonReport, ComparisonStatus, Filter, LoadOptions, Rules, Symtypes,
+    Symvers,
+};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+#[cfg(test)]
+mod tests;
+
+/// The status returned by [`kabi_report_status()`] when no differences were found.
+pub const KABI_STATUS_SAME: c_int = 0;
+/// The status returned by [`kabi_report_status()`] when all found differences are tolerated.
+pub const KABI_STATUS_TOLERATED: c_int = 1;
+/// The status returned by [`kabi_report_status()`] when at least one difference is breaking.
+pub const KABI_STATUS_BREAKING: c_int = 2;
+
+/// A comparison report together with its text converted for C.
+pub struct Report {
+    status: ComparisonStatus,
+    text: CString,
+}
+
+thread_local! {
+    /// The description of the last error that occurred on the current thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records the given error as the last error of the current thread.
+fn set_last_error(err: Error) {
+    let desc = err.to_string().replace('\0', "");
+    let desc = CString::new(desc).unwrap();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(desc));
+}
+
+/// Runs the given operation, converting a panic to an error so that it doesn't unwind into the C
+/// caller.
+fn catch_panic<T, F: FnOnce() -> Result<T, Error>>(f: F) -> Result<T, Error> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let desc = match payload.downcast_ref::<&str>() {
+            Some(desc) => desc,
+            None => match payload.downcast_ref::<String>() {
+                Some(desc) => desc.as_str(),
+                None => "unknown cause",
+            },
+        };
+        Err(Error::new_parse(format!("Internal error: {}", desc)))
+    })
+}
+
+/// Runs the given operation and converts its result to a raw handle, recording any error.
+fn into_handle<T, F: FnOnce() -> Result<T, Error>>(f: F) -> *mut T {
+    match catch_panic(f) {
+        Ok(value) => Box::into_raw(Box::new(value)),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a handle previously returned by [`into_handle()`], recording any error.
+///
+/// # Safety
+///
+/// The `handle` must be `NULL` or a pointer obtained from [`into_handle()`] that was not released
+/// yet.
+unsafe fn free_handle<T>(handle: *mut T) {
+    let result = catch_panic(|| {
+        if !handle.is_null() {
+            // SAFETY: The caller guarantees the pointer originates from Box::into_raw().
+            drop(unsafe { Box::from_raw(handle) });
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        set_last_error(err);
+    }
+}
+
+/// Converts a C string argument to a Rust string.
+///
+/// # Safety
+///
+/// The `arg` must be a valid pointer to a NUL-terminated string.
+unsafe fn str_arg<'a>(arg: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if arg.is_null() {
+        return Err(Error::new_cli(format!("The argument '{}' is NULL", name)));
+    }
+    // SAFETY: The caller guarantees the pointer is a valid NUL-terminated string.
+    unsafe { CStr::from_ptr(arg) }.to_str().map_err(|_| {
+        Error::new_cli(format!(
+            "The argument '{}' is not a valid UTF-8 string",
+            name
+        ))
+    })
+}
+
+/// Checks a number of jobs argument.
+fn jobs_arg(jobs: c_int) -> Result<i32, Error> {
+    if jobs < 1 {
+        return Err(Error::new_cli(format!(
+            "Invalid number of jobs '{}', the value must be at least 1",
+            jobs
+        )));
+    }
+    Ok(jobs)
+}
+
+/// Converts a nullable handle argument to an optional reference.
+///
+/// # Safety
+///
+/// The `arg` must be `NULL` or a valid handle of the type `T`.
+unsafe fn opt_arg<'a, T>(arg: *const T) -> Option<&'a T> {
+    // SAFETY: The caller guarantees the pointer is NULL or valid.
+    unsafe { arg.as_ref() }
+}
+
+/// Converts a mandatory handle argument to a reference.
+///
+/// # Safety
+///
+/// The `arg` must be `NULL` or a valid handle of the type `T`.
+unsafe fn ref_arg<'a, T>(arg: *const T, name: &str) -> Result<&'a T, Error> {
+    // SAFETY: The caller guarantees the pointer is NULL or valid.
+    unsafe { opt_arg(arg) }
+        .ok_or_else(|| Error::new_cli(format!("The argument '{}' is NULL", name)))
+}
+
+/// Creates options for a comparison from the C arguments.
+///
+/// # Safety
+///
+/// The `format` must be `NULL` or a valid pointer to a NUL-terminated string.
+unsafe fn compare_options<'a>(
+    format: *const c_char,
+    jobs: c_int,
+) -> Result<CompareOptions<'a>, Error> {
+    let mut options = CompareOptions::new();
+    if !format.is_null() {
+        // SAFETY: The caller guarantees the pointer is a valid NUL-terminated string.
+        options = options.format(unsafe { str_arg(format, "format") }?.parse()?);
+    }
+    Ok(options.jobs(jobs_arg(jobs)?))
+}
+
+/// Converts a comparison report to its C representation.
+fn into_report(report: ComparisonReport) -> Result<Report, Error> {
+    let text = CString::new(report.text())
+        .map_err(|_| Error::new_parse("The comparison report contains a NUL character"))?;
+    Ok(Report {
+        status: report.status(),
+        text,
+    })
+}
+
+/// Returns the description of the last error that occurred on the current thread, or `NULL` if no
+/// error occurred.
+///
+/// The string remains valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn kabi_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| match &*last_error.borrow() {
+        Some(desc) => desc.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Loads symtypes data from the specified file, directory or tar archive, using `jobs` workers.
+///
+/// Returns a new handle, or `NULL` on error.
+///
+/// # Safety
+///
+/// The `path` must be a valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_symtypes_load(path: *const c_char, jobs: c_int) -> *mut Symtypes {
+    into_handle(|| {
+        // SAFETY: The caller guarantees the pointer is a valid NUL-terminated string.
+        let path = unsafe { str_arg(path, "path") }?;
+        Symtypes::load(path, &LoadOptions::new().jobs(jobs_arg(jobs)?))
+    })
+}
+
+/// Releases a symtypes handle. Passing `NULL` is allowed.
+///
+/// # Safety
+///
+/// The `symtypes` must be `NULL` or a handle returned by [`kabi_symtypes_load()`] that was not
+/// released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_symtypes_free(symtypes: *mut Symtypes) {
+    // SAFETY: The caller guarantees the handle is valid.
+    unsafe { free_handle(symtypes) }
+}
+
+/// Loads symvers data from the specified file.
+///
+/// Returns a new handle, or `NULL` on error.
+///
+/// # Safety
+///
+/// The `path` must be a valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_symvers_load(path: *const c_char) -> *mut Symvers {
+    into_handle(|| {
+        // SAFETY: The caller guarantees the pointer is a valid NUL-terminated string.
+        let path = unsafe { str_arg(path, "path") }?;
+        Symvers::load(path, &LoadOptions::new())
+    })
+}
+
+/// Releases a symvers handle. Passing `NULL` is allowed.
+///
+/// # Safety
+///
+/// The `symvers` must be `NULL` or a handle returned by [`kabi_symvers_load()`] that was not
+/// released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_symvers_free(symvers: *mut Symvers) {
+    // SAFETY: The caller guarantees the handle is valid.
+    unsafe { free_handle(symvers) }
+}
+
+/// Loads filter patterns from the specified file, one pattern per line.
+///
+/// Returns a new handle, or `NULL` on error.
+///
+/// # Safety
+///
+/// The `path` must be a valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_filter_load(path: *const c_char) -> *mut Filter {
+    into_handle(|| {
+        // SAFETY: The caller guarantees the pointer is a valid NUL-terminated string.
+        let path = unsafe { str_arg(path, "path") }?;
+        Filter::load(path)
+    })
+}
+
+/// Releases a filter handle. Passing `NULL` is allowed.
+///
+/// # Safety
+///
+/// The `filter` must be `NULL` or a handle returned by [`kabi_filter_load()`] that was not
+/// released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_filter_free(filter: *mut Filter) {
+    // SAFETY: The caller guarantees the handle is valid.
+    unsafe { free_handle(filter) }
+}
+
+/// Loads severity rules from the specified file.
+///
+/// If `arch` is not `NULL`, only the rules applicable to that architecture are in effect. Returns
+/// a new handle, or `NULL` on error.
+///
+/// # Safety
+///
+/// The `path` must be a valid pointer to a NUL-terminated string. The `arch` must be `NULL` or a
+/// valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_rules_load(path: *const c_char, arch: *const c_char) -> *mut Rules {
+    into_handle(|| {
+        // SAFETY: The caller guarantees the pointers are valid NUL-terminated strings.
+        let path = unsafe { str_arg(path, "path") }?;
+        let maybe_arch = if arch.is_null() {
+            None
+        } else {
+            Some(unsafe { str_arg(arch, "arch") }?)
+        };
+        Rules::load(path, maybe_arch)
+    })
+}
+
+/// Releases a rules handle. Passing `NULL` is allowed.
+///
+/// # Safety
+///
+/// The `rules` must be `NULL` or a handle returned by [`kabi_rules_load()`] that was not released
+/// yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_rules_free(rules: *mut Rules) {
+    // SAFETY: The caller guarantees the handle is valid.
+    unsafe { free_handle(rules) }
+}
+
+/// Compares two symtypes corpuses, using `jobs` workers.
+///
+/// The comparison is restricted to the symbols matching `filter`, if it is not `NULL`. The text of
+/// the report is formatted according to `format`, which takes the same names as the `--format`
+/// option of `ksymtypes compare`, or is the pretty format if `NULL`. Returns a new report handle,
+/// or `NULL` on error.
+///
+/// # Safety
+///
+/// The `symtypes` and `symtypes2` must be valid symtypes handles. The `filter` must be `NULL` or a
+/// valid filter handle. The `format` must be `NULL` or a valid pointer to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_compare(
+    symtypes: *const Symtypes,
+    symtypes2: *const Symtypes,
+    filter: *const Filter,
+    format: *const c_char,
+    jobs: c_int,
+) -> *mut Report {
+    into_handle(|| {
+        // SAFETY: The caller guarantees the handles and the string are valid.
+        let symtypes = unsafe { ref_arg(symtypes, "symtypes") }?;
+        let symtypes2 = unsafe { ref_arg(symtypes2, "symtypes2") }?;
+        let mut options = unsafe { compare_options(format, jobs) }?;
+        if let Some(filter) = unsafe { opt_arg(filter) } {
+            options = options.filter(filter);
+        }
+        into_report(symtypes.compare(symtypes2, &options)?)
+    })
+}
+
+/// Compares two symvers corpuses.
+///
+/// The comparison is restricted to the symbols matching `filter`, if it is not `NULL`, and changes
+/// are tolerated according to `rules`, if it is not `NULL`. The text of the report is formatted
+/// according to `format`, which takes the same names as the `--format` option of
+/// `ksymvers compare`, or is the pretty format if `NULL`. Returns a new report handle, or `NULL` on
+/// error.
+///
+/// # Safety
+///
+/// The `symvers` and `symvers2` must be valid symvers handles. The `filter` and `rules` must be
+/// `NULL` or valid handles of their type. The `format` must be `NULL` or a valid pointer to a
+/// NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_symvers_compare(
+    symvers: *const Symvers,
+    symvers2: *const Symvers,
+    filter: *const Filter,
+    rules: *const Rules,
+    format: *const c_char,
+) -> *mut Report {
+    into_handle(|| {
+        // SAFETY: The caller guarantees the handles and the string are valid.
+        let symvers = unsafe { ref_arg(symvers, "symvers") }?;
+        let symvers2 = unsafe { ref_arg(symvers2, "symvers2") }?;
+        let mut options = unsafe { compare_options(format, 1) }?;
+        if let Some(filter) = unsafe { opt_arg(filter) } {
+            options = options.filter(filter);
+        }
+        if let Some(rules) = unsafe { opt_arg(rules) } {
+            options = options.rules(rules);
+        }
+        into_report(symvers.compare(symvers2, &options)?)
+    })
+}
+
+/// Returns the outcome of a comparison as one of the `KABI_STATUS_*` values, or -1 if `report` is
+/// `NULL`.
+///
+/// # Safety
+///
+/// The `report` must be `NULL` or a valid report handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_report_status(report: *const Report) -> c_int {
+    let result = catch_panic(|| {
+        // SAFETY: The caller guarantees the handle is valid.
+        let report = unsafe { ref_arg(report, "report") }?;
+        Ok(match report.status {
+            ComparisonStatus::Same => KABI_STATUS_SAME,
+            ComparisonStatus::Tolerated => KABI_STATUS_TOLERATED,
+            _ => KABI_STATUS_BREAKING,
+        })
+    });
+    match result {
+        Ok(status) => status,
+        Err(err) => {
+            set_last_error(err);
+            -1
+        }
+    }
+}
+
+/// Returns the text of a report, or `NULL` if `report` is `NULL`.
+///
+/// The string is owned by the report and remains valid until the report is released.
+///
+/// # Safety
+///
+/// The `report` must be `NULL` or a valid report handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_report_text(report: *const Report) -> *const c_char {
+    let result = catch_panic(|| {
+        // SAFETY: The caller guarantees the handle is valid.
+        let report = unsafe { ref_arg(report, "report") }?;
+        Ok(report.text.as_ptr())
+    });
+    match result {
+        Ok(text) => text,
+        Err(err) => {
+            set_last_error(err);
+            ptr::null()
+        }
+    }
+}
+
+/// Releases a report handle. Passing `NULL` is allowed.
+///
+/// # Safety
+///
+/// The `report` must be `NULL` or a handle returned by [`kabi_compare()`] or
+/// [`kabi_symvers_compare()`] that was not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kabi_report_free(report: *mut Report) {
+    // SAFETY: The caller guarantees the handle is valid.
+    unsafe { free_handle(report) }
+}
@@ -0,0 +1,125 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+
+/// Returns the last error of the current thread as a Rust string.
+fn last_error() -> String {
+    let desc = kabi_last_error();
+    assert!(!desc.is_null());
+    // SAFETY: The pointer is a valid NUL-terminated string owned by LAST_ERROR.
+    unsafe { CStr::from_ptr(desc) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn symtypes_compare() {
+    // Check that two symtypes corpuses can be loaded and compared through the C bindings.
+    let path = c"tests/it/ksymtypes/compare/a.symtypes";
+    let path2 = c"tests/it/ksymtypes/compare/b.symtypes";
+    // SAFETY: All arguments are valid and each handle is released exactly once.
+    unsafe {
+        let symtypes = kabi_symtypes_load(path.as_ptr(), 1);
+        assert!(!symtypes.is_null());
+        let symtypes2 = kabi_symtypes_load(path2.as_ptr(), 2);
+        assert!(!symtypes2.is_null());
+        let report = kabi_compare(symtypes, symtypes2, ptr::null(), c"symbols".as_ptr(), 2);
+        assert!(!report.is_null());
+        assert_eq!(kabi_report_status(report), KABI_STATUS_BREAKING);
+        assert_eq!(CStr::from_ptr(kabi_report_text(report)), c"foo\n");
+        kabi_report_free(report);
+        kabi_symtypes_free(symtypes2);
+        kabi_symtypes_free(symtypes);
+    }
+}
+
+#[test]
+fn symvers_compare_rules() {
+    // Check that severity rules can be passed to a symvers comparison through the C bindings.
+    // SAFETY: All arguments are valid and each handle is released exactly once.
+    unsafe {
+        let symvers = kabi_symvers_load(c"tests/it/ksymvers/compare_rules/a.symvers".as_ptr());
+        assert!(!symvers.is_null());
+        let symvers2 = kabi_symvers_load(c"tests/it/ksymvers/compare_rules/b.symvers".as_ptr());
+        assert!(!symvers2.is_null());
+        let rules = kabi_rules_load(
+            c"tests/it/ksymvers/compare_rules/severities.txt".as_ptr(),
+            ptr::null(),
+        );
+        assert!(!rules.is_null());
+        let report = kabi_symvers_compare(symvers, symvers2, ptr::null(), rules, ptr::null());
+        assert!(!report.is_null());
+        assert_eq!(kabi_report_status(report), KABI_STATUS_TOLERATED);
+        assert_eq!(
+            CStr::from_ptr(kabi_report_text(report)),
+            c"Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rule tests/it/ksymvers/compare_rules/severities.txt:1 'vmlinux PASS')\n"
+        );
+        kabi_report_free(report);
+        kabi_rules_free(rules);
+        kabi_symvers_free(symvers2);
+        kabi_symvers_free(symvers);
+    }
+}
+
+#[test]
+fn load_error() {
+    // Check that a failure to load data is reported through the last error.
+    // SAFETY: All arguments are valid.
+    unsafe {
+        let symvers = kabi_symvers_load(c"tests/it/ffi/missing.symvers".as_ptr());
+        assert!(symvers.is_null());
+        assert_eq!(
+            last_error(),
+            "Failed to open the file 'tests/it/ffi/missing.symvers': No such file or directory (os error 2)"
+        );
+
+        let symtypes = kabi_symtypes_load(c"tests/it/ksymtypes/compare/a.symtypes".as_ptr(), 0);
+        assert!(symtypes.is_null());
+        assert_eq!(
+            last_error(),
+            "Invalid number of jobs '0', the value must be at least 1"
+        );
+    }
+}
+
+#[test]
+fn invalid_arguments() {
+    // Check that NULL handles and unrecognized formats are rejected.
+    // SAFETY: All arguments are valid or NULL.
+    unsafe {
+        let report = kabi_compare(ptr::null(), ptr::null(), ptr::null(), ptr::null(), 1);
+        assert!(report.is_null());
+        assert_eq!(last_error(), "The argument 'symtypes' is NULL");
+
+        let symvers = kabi_symvers_load(c"tests/it/ksymvers/compare/a.symvers".as_ptr());
+        assert!(!symvers.is_null());
+        let report = kabi_symvers_compare(
+            symvers,
+            symvers,
+            ptr::null(),
+            ptr::null(),
+            c"bogus".as_ptr(),
+        );
+        assert!(report.is_null());
+        assert_eq!(last_error(), "Unrecognized format 'bogus'");
+        kabi_symvers_free(symvers);
+
+        assert_eq!(kabi_report_status(ptr::null()), -1);
+        assert!(kabi_report_text(ptr::null()).is_null());
+        kabi_report_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn panic_error() {
+    // Check that a panic in an operation is recorded as an error instead of unwinding.
+    let handle = into_handle::<(), _>(|| panic!("Unexpected state"));
+    assert!(handle.is_null());
+    assert_eq!(last_error(), "Internal error: Unexpected state");
+
+    let handle = into_handle::<(), _>(|| panic!("Unexpected value {}", 42));
+    assert!(handle.is_null());
+    assert_eq!(last_error(), "Internal error: Unexpected value 42");
+}
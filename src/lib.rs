@@ -6,16 +6,34 @@ use std::fmt::{self, Display, Formatter};
 use std::fs::File;
 use std::hash::{self, DefaultHasher, Hash, Hasher};
 use std::io::{self, prelude::*};
+use std::mem;
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
-use std::time::Instant;
-
+#[cfg(feature = "compression")]
+use std::process;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+#[cfg(feature = "compression")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub mod api;
+#[cfg(feature = "browse")]
+pub mod browse;
 pub mod burst;
 pub mod cli;
+pub mod config;
+pub mod crc32;
+pub mod demangle;
+pub mod ffi;
+pub mod log;
+pub mod rpm;
 pub mod rules;
+pub mod sha256;
+pub mod suppressions;
 pub mod symtypes;
 pub mod symvers;
 pub mod text;
+pub mod warnings;
 
 /// An error type for the crate, annotating standard errors with contextual information and
 /// providing custom errors.
@@ -25,6 +43,8 @@ pub enum Error {
     CLI(String),
     IO { desc: String, io_err: io::Error },
     Parse(String),
+    ParseAt(Box<ParseError>),
+    Multiple(Vec<Error>),
 }
 
 impl Error {
@@ -54,15 +74,44 @@ impl Error {
         Self::Parse(desc.into())
     }
 
-    /// Creates a new `Error::Parse` by formatting the given error details.
+    /// Creates a new `Error::ParseAt` pointing to the given line.
     pub fn new_parse_format(desc: &str, path: &Path, line_num: usize, line: &str) -> Self {
-        Self::Parse(format!(
-            "{}\n {}:{}\n | {}",
+        Self::ParseAt(Box::new(ParseError::new(desc, path, line_num, line, None)))
+    }
+
+    /// Creates a new `Error::ParseAt` pointing to the token at the given byte offset within the
+    /// line.
+    pub fn new_parse_format_at(
+        desc: &str,
+        path: &Path,
+        line_num: usize,
+        line: &str,
+        offset: usize,
+    ) -> Self {
+        Self::ParseAt(Box::new(ParseError::new(
             desc,
-            path.display(),
+            path,
             line_num,
-            line
-        ))
+            line,
+            Some(offset),
+        )))
+    }
+
+    /// Creates a new `Error::Multiple` aggregating the given errors.
+    ///
+    /// Nested `Error::Multiple` errors are flattened. A single error is returned unchanged.
+    pub fn new_multiple(errs: Vec<Error>) -> Self {
+        let mut flat_errs = Vec::new();
+        for err in errs {
+            match err {
+                Self::Multiple(inner_errs) => flat_errs.extend(inner_errs),
+                err => flat_errs.push(err),
+            }
+        }
+        if flat_errs.len() == 1 {
+            return flat_errs.pop().unwrap();
+        }
+        Self::Multiple(flat_errs)
     }
 }
 
@@ -81,23 +130,197 @@ impl Display for Error {
                 io_err.fmt(f)
             }
             Self::Parse(desc) => write!(f, "{}", desc),
+            Self::ParseAt(parse_err) => parse_err.fmt(f),
+            Self::Multiple(errs) => {
+                for (i, err) in errs.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    err.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A parse error with the location of the problem in the input file.
+#[derive(Debug)]
+pub struct ParseError {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The line number, starting from 1.
+    pub line: usize,
+    /// The column of the offending token, counted in characters and starting from 1.
+    pub column: Option<usize>,
+    /// The content of the line.
+    pub snippet: String,
+    /// The description of the problem.
+    pub message: String,
+}
+
+impl ParseError {
+    /// Creates a new `ParseError`, optionally pointing to the token at the given byte offset
+    /// within the line.
+    fn new(desc: &str, path: &Path, line_num: usize, line: &str, offset: Option<usize>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line: line_num,
+            column: offset.map(|offset| line[..offset].chars().count() + 1),
+            snippet: line.to_string(),
+            message: desc.to_string(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    /// Formats the error as the description, followed by the location and the content of the
+    /// line. If the column is known, a caret is placed under the offending token.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\n {}:{}",
+            self.message,
+            self.path.display(),
+            self.line
+        )?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        write!(f, "\n | {}", self.snippet)?;
+        if let Some(column) = self.column {
+            // Keep tabs in the padding so the caret lines up with the token.
+            let padding = self
+                .snippet
+                .chars()
+                .take(column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            write!(f, "\n | {}^", padding)?;
+        }
+        Ok(())
+    }
+}
+
+/// An outcome of a comparison of two corpuses.
+///
+/// The variants are ordered by their severity, which allows combining multiple outcomes using
+/// [`Ord::max()`].
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum CompareStatus {
+    /// No differences were found.
+    Same,
+    /// All found differences are tolerated.
+    Tolerated,
+    /// At least one found difference is breaking.
+    Breaking,
+    /// At least one export on the protected list was removed.
+    ProtectedRemoved,
+}
+
+/// A phase of a command, used to aggregate the measured times.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Phase {
+    /// Reading input data.
+    Load,
+    /// Merging several inputs together.
+    Merge,
+    /// Processing or querying loaded data.
+    Analyze,
+    /// Comparing two sets of data.
+    Compare,
+    /// Writing output data.
+    Write,
+}
+
+impl Phase {
+    /// All phases, in the order in which they are reported.
+    const ALL: [Phase; 5] = [
+        Self::Load,
+        Self::Merge,
+        Self::Analyze,
+        Self::Compare,
+        Self::Write,
+    ];
+
+    /// Returns the phase name as a string slice.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Load => "load",
+            Self::Merge => "merge",
+            Self::Analyze => "analyze",
+            Self::Compare => "compare",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// The format of the timing output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimingFormat {
+    /// A human-readable line output as soon as each operation completes.
+    Text,
+    /// A JSON report with the times of all operations and their per-phase totals, output at the
+    /// end of the command.
+    Json,
+}
+
+impl FromStr for TimingFormat {
+    type Err = Error;
+
+    /// Obtains a [`TimingFormat`] matching the given format type, specified as a string.
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(Self::Err::new_cli(format!(
+                "Unrecognized timing format '{}'",
+                format
+            ))),
         }
     }
 }
 
+/// A measured time of a single operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimingRecord {
+    pub phase: Phase,
+    pub desc: String,
+    pub elapsed: Duration,
+}
+
+/// Global format of the timing output.
+static TIMING_FORMAT: OnceLock<TimingFormat> = OnceLock::new();
+
+/// Measured times collected for the JSON timing report.
+static TIMING_RECORDS: Mutex<Vec<TimingRecord>> = Mutex::new(Vec::new());
+
+/// Initializes the global format of the timing output, can be called only once.
+pub fn init_timing_format(format: TimingFormat) {
+    TIMING_FORMAT
+        .set(format)
+        .expect("TIMING_FORMAT must be initialized only once");
+}
+
 /// An elapsed timer to measure time of some operation.
 ///
-/// The time is measured between when the object is instantiated and when it is dropped. A message
-/// with the elapsed time is output when the object is dropped.
+/// The time is measured between when the object is instantiated and when it is dropped. When the
+/// object is dropped, a message with the elapsed time is output, or the time is recorded for the
+/// final report, depending on the global [`TimingFormat`].
 pub enum Timing {
-    Active { desc: String, start: Instant },
+    Active {
+        phase: Phase,
+        desc: String,
+        start: Instant,
+    },
     Inactive,
 }
 
 impl Timing {
-    pub fn new<S: Into<String>>(do_timing: bool, desc: S) -> Self {
+    pub fn new<S: Into<String>>(do_timing: bool, phase: Phase, desc: S) -> Self {
         if do_timing {
             Timing::Active {
+                phase,
                 desc: desc.into(),
                 start: Instant::now(),
             }
@@ -110,14 +333,103 @@ impl Timing {
 impl Drop for Timing {
     fn drop(&mut self) {
         match self {
-            Timing::Active { desc, start } => {
-                eprintln!("{}: {:.3?}", desc, start.elapsed());
+            Timing::Active { phase, desc, start } => {
+                let elapsed = start.elapsed();
+                match TIMING_FORMAT.get() {
+                    Some(TimingFormat::Json) => TIMING_RECORDS.lock().unwrap().push(TimingRecord {
+                        phase: *phase,
+                        desc: mem::take(desc),
+                        elapsed,
+                    }),
+                    _ => eprintln!("{}: {:.3?}", desc, elapsed),
+                }
             }
             Timing::Inactive => {}
         }
     }
 }
 
+/// Writes the JSON timing report to the standard error output, if the report was requested by the
+/// global [`TimingFormat`] and any time was measured.
+pub fn write_timing_report() {
+    if TIMING_FORMAT.get() != Some(&TimingFormat::Json) {
+        return;
+    }
+    let records = TIMING_RECORDS.lock().unwrap();
+    if !records.is_empty() {
+        eprintln!("{}", format_timing_report(&records));
+    }
+}
+
+/// Formats the given measured times as a JSON report.
+///
+/// The report contains the total time of each phase and the time of each individual operation,
+/// with all times given in seconds.
+pub fn format_timing_report(records: &[TimingRecord]) -> String {
+    let phases = Phase::ALL
+        .iter()
+        .map(|phase| {
+            let total = records
+                .iter()
+                .filter(|record| record.phase == *phase)
+                .map(|record| record.elapsed)
+                .sum::<Duration>();
+            format!("\"{}\":{:.6}", phase.as_str(), total.as_secs_f64())
+        })
+        .collect::<Vec<_>>();
+    let operations = records
+        .iter()
+        .map(|record| {
+            format!(
+                "{{\"phase\":\"{}\",\"description\":\"{}\",\"seconds\":{:.6}}}",
+                record.phase.as_str(),
+                text::escape_json(&record.desc),
+                record.elapsed.as_secs_f64()
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"phases\":{{{}}},\"operations\":[{}]}}",
+        phases.join(","),
+        operations.join(",")
+    )
+}
+
+/// A reporter of the progress of a long operation.
+///
+/// The completed percentage of the operation is output in steps of 10%.
+pub struct Progress {
+    desc: String,
+    last_step: Mutex<usize>,
+}
+
+impl Progress {
+    /// Returns a progress callback for [`burst::run_jobs()`] that reports the operation described
+    /// by `desc`, or `None` if progress reporting is not enabled.
+    pub fn callback<S: Into<String>>(desc: S) -> Option<Arc<burst::ProgressFn>> {
+        if !*PROGRESS.get().unwrap_or(&false) {
+            return None;
+        }
+
+        let progress = Progress {
+            desc: desc.into(),
+            last_step: Mutex::new(0),
+        };
+        Some(Arc::new(move |done, total| progress.report(done, total)))
+    }
+
+    /// Reports that `done` out of `total` works of the operation have been completed.
+    fn report(&self, done: usize, total: usize) {
+        let step = done * 10 / total.max(1);
+        let mut last_step = self.last_step.lock().unwrap();
+        if step > *last_step {
+            eprintln!("{}: {}%", self.desc, step * 10);
+        }
+        // A lower step means that a new run of the operation has started.
+        *last_step = step;
+    }
+}
+
 /// A helper extension trait to map [`std::io::Error`] to [`Error`], as
 /// `write!(data).map_io_error(context)`.
 pub trait MapIOErr {
@@ -131,16 +443,35 @@ impl MapIOErr for Result<(), io::Error> {
 }
 
 /// A [`std::fs::File`] wrapper that tracks the file path to provide better error context.
+///
+/// When the `compression` feature is enabled, a file opened for reading that has the `.gz`, `.xz`
+/// or `.zst` extension is transparently decompressed by the respective external tool.
 pub struct PathFile {
     path: PathBuf,
     file: File,
+    #[cfg(feature = "compression")]
+    maybe_decompressor: Option<Decompressor>,
 }
 
 impl PathFile {
     pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+
+        #[cfg(feature = "compression")]
+        if let Some(tool) = compression_tool(path) {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                file: file.try_clone()?,
+                maybe_decompressor: Some(Decompressor::spawn(tool, file)?),
+            });
+        }
+
         Ok(Self {
-            path: path.as_ref().to_path_buf(),
-            file: File::open(path)?,
+            path: path.to_path_buf(),
+            file,
+            #[cfg(feature = "compression")]
+            maybe_decompressor: None,
         })
     }
 
@@ -148,12 +479,24 @@ impl PathFile {
         Ok(Self {
             path: path.as_ref().to_path_buf(),
             file: File::create(path)?,
+            #[cfg(feature = "compression")]
+            maybe_decompressor: None,
         })
     }
 }
 
 impl Read for PathFile {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(feature = "compression")]
+        if let Some(decompressor) = &mut self.maybe_decompressor {
+            return decompressor.read(buf).map_err(|err| {
+                io::Error::other(Error::new_io(
+                    format!("Failed to decompress the file '{}'", self.path.display()),
+                    err,
+                ))
+            });
+        }
+
         self.file.read(buf).map_err(|err| {
             io::Error::other(Error::new_io(
                 format!("Failed to read from the file '{}'", self.path.display()),
@@ -183,6 +526,273 @@ impl Write for PathFile {
     }
 }
 
+/// Recognized compression extensions and the external tools to decompress the respective data.
+const COMPRESSION_TOOLS: [(&str, &str); 3] = [("gz", "gzip"), ("xz", "xz"), ("zst", "zstd")];
+
+/// Recognized compressor names, as recorded in RPM package headers, and the external tools to
+/// decompress the respective data.
+const NAMED_COMPRESSION_TOOLS: [(&str, &str); 5] = [
+    ("gzip", "gzip"),
+    ("bzip2", "bzip2"),
+    ("xz", "xz"),
+    ("lzma", "xz"),
+    ("zstd", "zstd"),
+];
+
+/// Returns the name of the tool to decompress the specified file, based on its extension, or
+/// `None` if the file is not recognized as compressed.
+fn compression_tool(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?;
+    COMPRESSION_TOOLS
+        .iter()
+        .find(|(tool_ext, _)| ext == *tool_ext)
+        .map(|&(_, tool)| tool)
+}
+
+/// Returns the name of the tool to decompress data produced by the specified compressor, or `None`
+/// if the compressor is not recognized.
+fn named_compression_tool(compressor: &str) -> Option<&'static str> {
+    NAMED_COMPRESSION_TOOLS
+        .iter()
+        .find(|(name, _)| compressor == *name)
+        .map(|&(_, tool)| tool)
+}
+
+/// Returns the specified path without its compression extension, or `None` if the path is not
+/// recognized as compressed.
+///
+/// Always returns `None` when the `compression` feature is disabled.
+pub fn strip_compression_ext(path: &Path) -> Option<PathBuf> {
+    #[cfg(feature = "compression")]
+    if compression_tool(path).is_some() {
+        return Some(path.with_extension(""));
+    }
+
+    let _ = path;
+    None
+}
+
+/// Reads the error output of an external tool on a separate thread, so that the tool can't block
+/// on writing its diagnostics while its other streams are being processed.
+#[cfg(feature = "compression")]
+fn read_errors(mut stderr: process::ChildStderr) -> thread::JoinHandle<io::Result<String>> {
+    thread::spawn(move || {
+        let mut errors = Vec::new();
+        stderr.read_to_end(&mut errors)?;
+        Ok(String::from_utf8_lossy(&errors).into_owned())
+    })
+}
+
+/// Waits for an external tool to complete and checks that it succeeded, reporting its error output
+/// otherwise.
+#[cfg(feature = "compression")]
+fn wait_tool(
+    tool: &str,
+    child: &mut process::Child,
+    errors_reader: thread::JoinHandle<io::Result<String>>,
+) -> io::Result<()> {
+    let status = child.wait()?;
+    let errors = errors_reader.join().map_err(|_| {
+        io::Error::other(format!("Failed to read the error output of '{}'", tool))
+    })??;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "'{}' failed with {}: {}",
+            tool,
+            status,
+            errors.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// An external decompression process, reading compressed data from a file and providing the
+/// decompressed output.
+#[cfg(feature = "compression")]
+struct Decompressor {
+    tool: &'static str,
+    child: process::Child,
+    stdout: process::ChildStdout,
+    maybe_errors_reader: Option<thread::JoinHandle<io::Result<String>>>,
+}
+
+#[cfg(feature = "compression")]
+impl Decompressor {
+    /// Starts the specified decompression tool on the given input.
+    fn spawn<I: Into<process::Stdio>>(tool: &'static str, input: I) -> io::Result<Self> {
+        let mut child = process::Command::new(tool)
+            .args(["-d", "-c"])
+            .stdin(input)
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                io::Error::new(err.kind(), format!("Failed to run '{}': {}", tool, err))
+            })?;
+
+        // INVARIANT: Both output streams are piped.
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+
+        Ok(Self {
+            tool,
+            child,
+            stdout,
+            maybe_errors_reader: Some(read_errors(stderr)),
+        })
+    }
+
+    /// Reads the remaining output of the tool and waits for it to complete, checking that it
+    /// succeeded.
+    fn finish(&mut self) -> io::Result<()> {
+        io::copy(self, &mut io::sink()).map(|_| ())
+    }
+}
+
+#[cfg(feature = "compression")]
+impl Read for Decompressor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.stdout.read(buf)?;
+        if size > 0 || buf.is_empty() {
+            return Ok(size);
+        }
+
+        // The end of the output was reached, check that the tool succeeded.
+        match self.maybe_errors_reader.take() {
+            Some(errors_reader) => wait_tool(self.tool, &mut self.child, errors_reader).map(|_| 0),
+            None => Ok(0),
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl Drop for Decompressor {
+    fn drop(&mut self) {
+        if self.maybe_errors_reader.is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// Decompresses the data from the reader by the specified external tool and passes the output to
+/// the function `f`.
+///
+/// A failure of the tool takes precedence over an error returned by `f`.
+#[cfg(feature = "compression")]
+fn decompress<R, T, F>(tool: &'static str, mut reader: R, f: F) -> Result<T, Error>
+where
+    R: Read + Send,
+    F: FnOnce(&mut dyn Read) -> Result<T, Error>,
+{
+    let mut decompressor = Decompressor::spawn(tool, process::Stdio::piped())
+        .map_err(|err| Error::new_io("Failed to decompress the data", err))?;
+
+    // INVARIANT: The input stream is piped.
+    let mut stdin = decompressor.child.stdin.take().unwrap();
+
+    // Feed the input from a separate thread and read the whole output, so that the tool can
+    // always finish.
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            // A write error means that the tool exited early, which is detected below.
+            let _ = io::copy(&mut reader, &mut stdin);
+        });
+        let result = f(&mut decompressor.stdout);
+        decompressor
+            .finish()
+            .map_err(|err| Error::new_io("Failed to decompress the data", err))?;
+        result
+    })
+}
+
+/// Reports that compressed data cannot be read because the `compression` feature is disabled.
+#[cfg(not(feature = "compression"))]
+fn decompress<R, T, F>(tool: &'static str, _reader: R, _f: F) -> Result<T, Error>
+where
+    R: Read + Send,
+    F: FnOnce(&mut dyn Read) -> Result<T, Error>,
+{
+    Err(Error::new_parse(format!(
+        "Data compressed by '{}' cannot be read without the compression feature",
+        tool
+    )))
+}
+
+/// An external compression process, compressing the written data into a file.
+#[cfg(feature = "compression")]
+pub struct Compressor {
+    tool: &'static str,
+    child: process::Child,
+    maybe_stdin: Option<process::ChildStdin>,
+    maybe_errors_reader: Option<thread::JoinHandle<io::Result<String>>>,
+}
+
+#[cfg(feature = "compression")]
+impl Compressor {
+    /// Starts the specified compression tool, writing its output to the given file.
+    fn spawn(tool: &'static str, file: File) -> io::Result<Self> {
+        let mut child = process::Command::new(tool)
+            .arg("-c")
+            .stdin(process::Stdio::piped())
+            .stdout(file)
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                io::Error::new(err.kind(), format!("Failed to run '{}': {}", tool, err))
+            })?;
+
+        // INVARIANT: The input and error streams are piped.
+        let stdin = child.stdin.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+
+        Ok(Self {
+            tool,
+            child,
+            maybe_stdin: Some(stdin),
+            maybe_errors_reader: Some(read_errors(stderr)),
+        })
+    }
+
+    /// Closes the input of the tool and waits for it to complete, checking that it succeeded.
+    fn finish(&mut self) -> io::Result<()> {
+        drop(self.maybe_stdin.take());
+
+        match self.maybe_errors_reader.take() {
+            Some(errors_reader) => wait_tool(self.tool, &mut self.child, errors_reader),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the input stream of the tool.
+    fn stdin(&mut self) -> io::Result<&mut process::ChildStdin> {
+        self.maybe_stdin
+            .as_mut()
+            .ok_or_else(|| io::Error::other("The compressor is already finished"))
+    }
+}
+
+#[cfg(feature = "compression")]
+impl Write for Compressor {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdin()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin()?.flush()
+    }
+}
+
+#[cfg(feature = "compression")]
+impl Drop for Compressor {
+    fn drop(&mut self) {
+        if self.maybe_stdin.is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
 /// Calculates the hash of a given value.
 fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
     let mut s = DefaultHasher::new();
@@ -190,24 +800,14 @@ fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
     s.finish()
 }
 
-/// Global debugging level.
-pub static DEBUG_LEVEL: OnceLock<usize> = OnceLock::new();
+/// Global flag enabling progress reporting.
+pub static PROGRESS: OnceLock<bool> = OnceLock::new();
 
-/// Initializes the global debugging level, can be called only once.
-pub fn init_debug_level(level: usize) {
-    DEBUG_LEVEL
-        .set(level)
-        .expect("DEBUG_LEVEL must be initialized only once");
-}
-
-/// Prints a formatted message to the standard error output if debugging is enabled.
-#[macro_export]
-macro_rules! debug {
-    ($($arg:tt)*) => {
-        if *$crate::DEBUG_LEVEL.get().unwrap_or(&0) > 0 {
-            eprintln!($($arg)*);
-        }
-    }
+/// Initializes the global progress reporting flag, can be called only once.
+pub fn init_progress(enabled: bool) {
+    PROGRESS
+        .set(enabled)
+        .expect("PROGRESS must be initialized only once");
 }
 
 /// Asserts that the given string matches the expected shell wildcard pattern.
@@ -249,34 +849,44 @@ macro_rules! assert_ok_eq {
     };
 }
 
-/// Asserts that the given result is `Err` containing an [`Error::Parse`] error with the expected
-/// description.
+/// Asserts that the given result is `Err` containing an [`Error::Parse`], [`Error::ParseAt`] or
+/// [`Error::Multiple`] error with the expected description.
 #[cfg(any(test, doc))]
 #[macro_export]
 macro_rules! assert_parse_err {
     ($result:expr, $expected_desc:expr $(,)?) => {
         match $result {
-            Err($crate::Error::Parse(actual_desc)) => assert_eq!(actual_desc, $expected_desc),
+            Err(
+                err @ ($crate::Error::Parse(_)
+                | $crate::Error::ParseAt(_)
+                | $crate::Error::Multiple(_)),
+            ) => {
+                assert_eq!(err.to_string(), $expected_desc)
+            }
             result => panic!(
-                "assertion failed: {:?} is not of type Err(Error::Parse(_))",
+                "assertion failed: {:?} is not of type Err(Error::Parse(_) | Error::ParseAt(_) | Error::Multiple(_))",
                 result
             ),
         }
     };
 }
 
-/// Asserts that the given result is `Err` containing an [`Error::Parse`] error with a description
-/// matching the expected shell wildcard pattern.
+/// Asserts that the given result is `Err` containing an [`Error::Parse`], [`Error::ParseAt`] or
+/// [`Error::Multiple`] error with a description matching the expected shell wildcard pattern.
 #[cfg(any(test, doc))]
 #[macro_export]
 macro_rules! assert_inexact_parse_err {
     ($result:expr, $expected_desc:expr $(,)?) => {
         match $result {
-            Err($crate::Error::Parse(actual_desc)) => {
-                $crate::assert_inexact!(actual_desc, $expected_desc)
+            Err(
+                err @ ($crate::Error::Parse(_)
+                | $crate::Error::ParseAt(_)
+                | $crate::Error::Multiple(_)),
+            ) => {
+                $crate::assert_inexact!(err.to_string(), $expected_desc)
             }
             result => panic!(
-                "assertion failed: {:?} is not of type Err(Error::Parse(_))",
+                "assertion failed: {:?} is not of type Err(Error::Parse(_) | Error::ParseAt(_) | Error::Multiple(_))",
                 result
             ),
         }
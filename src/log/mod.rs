@@ -0,0 +1,205 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A minimal leveled logger for diagnostic messages.
+//!
+//! Messages are logged using the [`warn!`](crate::warn), [`info!`](crate::info),
+//! [`debug!`](crate::debug) and [`trace!`](crate::trace) macros. Each message is written as a
+//! single line, prefixed with the time elapsed since the logger was initialized, the message level
+//! and the module that logged it. Lines are written atomically, so messages from parallel workers
+//! do not get interleaved.
+
+use crate::{Error, PathFile};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, prelude::*};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+/// A severity level of a log message.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    /// A problem that doesn't prevent the operation from completing.
+    Warn,
+    /// A high-level progress message.
+    Info,
+    /// A detailed message useful for debugging.
+    Debug,
+    /// A very verbose message tracing individual steps.
+    Trace,
+}
+
+impl Level {
+    /// Returns the level as an uppercase string slice.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    /// Obtains a [`Level`] matching the given level name, specified as a string.
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level {
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(Self::Err::new_cli(format!(
+                "Unrecognized log level '{}'",
+                level
+            ))),
+        }
+    }
+}
+
+/// A logger writing messages up to a maximum level to an output stream.
+pub struct Logger {
+    /// The most verbose level of messages that get written.
+    level: Level,
+
+    /// The time when the logger was created, used to compute message timestamps.
+    start: Instant,
+
+    /// The output stream for messages.
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Logger {
+    /// Creates a new `Logger` writing messages up to the given `level` to the standard error
+    /// output.
+    pub fn new_stderr(level: Level) -> Self {
+        Self::new_buffer(level, io::stderr())
+    }
+
+    /// Creates a new `Logger` writing messages up to the given `level` to the specified file.
+    pub fn new_file<P: AsRef<Path>>(level: Level, path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = PathFile::create(path).map_err(|err| {
+            Error::new_io(
+                format!("Failed to create the log file '{}'", path.display()),
+                err,
+            )
+        })?;
+        Ok(Self::new_buffer(level, file))
+    }
+
+    /// Creates a new `Logger` writing messages up to the given `level` to the provided output
+    /// stream.
+    pub fn new_buffer<W: Write + Send + 'static>(level: Level, writer: W) -> Self {
+        Self {
+            level,
+            start: Instant::now(),
+            sink: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Returns whether messages of the given level are written by the logger.
+    pub fn enabled(&self, level: Level) -> bool {
+        level <= self.level
+    }
+
+    /// Writes a message of the given level, logged by the specified module.
+    ///
+    /// Any error when writing the message is silently ignored.
+    pub fn log(&self, level: Level, module: &str, args: fmt::Arguments) {
+        if !self.enabled(level) {
+            return;
+        }
+
+        let line = format_record(self.start.elapsed(), level, module, args);
+        let mut sink = self.sink.lock().unwrap();
+        let _ = sink.write_all(line.as_bytes());
+        let _ = sink.flush();
+    }
+}
+
+/// Formats a single log line.
+///
+/// The crate name is stripped from the module path to keep the lines short.
+fn format_record(elapsed: Duration, level: Level, module: &str, args: fmt::Arguments) -> String {
+    let module = module
+        .strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::"))
+        .unwrap_or(module);
+    format!(
+        "[{:>8.3}] {:<5} {}: {}\n",
+        elapsed.as_secs_f64(),
+        level,
+        module,
+        args
+    )
+}
+
+/// The global logger.
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Initializes the global logger, can be called only once.
+pub fn init_logger(logger: Logger) {
+    if LOGGER.set(logger).is_err() {
+        panic!("LOGGER must be initialized only once");
+    }
+}
+
+/// Returns whether messages of the given level are written by the global logger.
+///
+/// No messages are written if the global logger is not initialized.
+pub fn enabled(level: Level) -> bool {
+    LOGGER.get().is_some_and(|logger| logger.enabled(level))
+}
+
+/// Writes a message of the given level to the global logger, if it is initialized.
+///
+/// This function is normally invoked through the logging macros.
+pub fn log(level: Level, module: &str, args: fmt::Arguments) {
+    if let Some(logger) = LOGGER.get() {
+        logger.log(level, module, args);
+    }
+}
+
+/// Logs a formatted message at the [`Level::Warn`] level.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Warn, module_path!(), format_args!($($arg)*))
+    }
+}
+
+/// Logs a formatted message at the [`Level::Info`] level.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Info, module_path!(), format_args!($($arg)*))
+    }
+}
+
+/// Logs a formatted message at the [`Level::Debug`] level.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Debug, module_path!(), format_args!($($arg)*))
+    }
+}
+
+/// Logs a formatted message at the [`Level::Trace`] level.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::Level::Trace, module_path!(), format_args!($($arg)*))
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use std::sync::Arc;
+
+/// A writer collecting the written data in a shared buffer.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn parse_level() {
+    // Check that log levels are parsed from their names.
+    assert_eq!(Level::from_str("warn").unwrap(), Level::Warn);
+    assert_eq!(Level::from_str("info").unwrap(), Level::Info);
+    assert_eq!(Level::from_str("debug").unwrap(), Level::Debug);
+    assert_eq!(Level::from_str("trace").unwrap(), Level::Trace);
+    let result = Level::from_str("verbose");
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Unrecognized log level 'verbose'"
+    );
+}
+
+#[test]
+fn format_record_basic() {
+    // Check that a log line contains the timestamp, level and module without the crate name.
+    let line = format_record(
+        Duration::from_millis(1234),
+        Level::Debug,
+        concat!(env!("CARGO_CRATE_NAME"), "::symvers"),
+        format_args!("Loading '{}'", "a.symvers"),
+    );
+    assert_eq!(line, "[   1.234] DEBUG symvers: Loading 'a.symvers'\n");
+}
+
+#[test]
+fn logger_level_filter() {
+    // Check that the logger writes only messages up to its level.
+    let buffer = SharedBuffer::default();
+    let logger = Logger::new_buffer(Level::Info, buffer.clone());
+    assert!(logger.enabled(Level::Warn));
+    assert!(logger.enabled(Level::Info));
+    assert!(!logger.enabled(Level::Debug));
+    logger.log(Level::Warn, "test", format_args!("first"));
+    logger.log(Level::Info, "test", format_args!("second"));
+    logger.log(Level::Debug, "test", format_args!("third"));
+    logger.log(Level::Trace, "test", format_args!("fourth"));
+    let out = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("] WARN  test: first"));
+    assert!(lines[1].ends_with("] INFO  test: second"));
+}
@@ -0,0 +1,328 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! Extraction of kABI data from kernel RPM packages.
+//!
+//! An RPM package consists of a lead, a signature header, a main header and a payload. The payload
+//! is a cpio archive in the "newc" format, typically compressed by the tool named in the main
+//! header. Decompression is performed by running the respective external tool, which is available
+//! only when the `compression` feature is enabled.
+
+use crate::text::matches_wildcard;
+use crate::{
+    Error, MapIOErr, PathFile, compression_tool, debug, decompress, named_compression_tool,
+};
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests;
+
+/// Patterns matching the symvers file in kernel packages. A kernel binary package ships a gzipped
+/// copy in `/boot`, a kernel-devel package contains the plain `Module.symvers` file.
+pub const SYMVERS_PATTERNS: &[&str] = &["boot/symvers-*", "*/Module.symvers"];
+
+/// Patterns matching the consolidated symtypes file in kernel packages.
+pub const SYMTYPES_PATTERNS: &[&str] = &["boot/symtypes-*", "*/symtypes-*"];
+
+/// The magic bytes at the start of an RPM lead.
+const LEAD_MAGIC: [u8; 4] = [0xed, 0xab, 0xee, 0xdb];
+
+/// The size of an RPM lead.
+const LEAD_SIZE: usize = 96;
+
+/// The magic bytes at the start of an RPM header structure, including its version.
+const HEADER_MAGIC: [u8; 4] = [0x8e, 0xad, 0xe8, 0x01];
+
+/// The size of an RPM header intro, consisting of the magic, reserved bytes, the number of index
+/// entries and the size of the data store.
+const HEADER_INTRO_SIZE: usize = 16;
+
+/// The size of an index entry in an RPM header.
+const INDEX_ENTRY_SIZE: usize = 16;
+
+/// The maximum accepted size of an RPM header, protecting against reading garbage.
+const MAX_HEADER_SIZE: usize = 256 * 1024 * 1024;
+
+/// The tag of the payload format in the main header.
+const TAG_PAYLOAD_FORMAT: u32 = 1124;
+
+/// The tag of the payload compressor in the main header.
+const TAG_PAYLOAD_COMPRESSOR: u32 = 1125;
+
+/// The type of a NUL-terminated string value in an RPM header.
+const TYPE_STRING: u32 = 6;
+
+/// The size of a cpio "newc" header.
+const CPIO_HEADER_SIZE: usize = 110;
+
+/// The name of the last entry in a cpio archive.
+const CPIO_TRAILER: &str = "TRAILER!!!";
+
+/// Returns whether the given path refers to an RPM package, which is the case if it has the
+/// `.rpm` extension.
+pub fn is_rpm_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "rpm")
+}
+
+/// Extracts a single file matching any of the given `patterns` from the specified RPM package and
+/// returns its name and data.
+///
+/// A file with a compression extension is decompressed. It is an error if no file or multiple
+/// files match.
+pub fn extract_file<P: AsRef<Path>>(
+    path: P,
+    patterns: &[&str],
+) -> Result<(String, Vec<u8>), Error> {
+    let path = path.as_ref();
+
+    let file = PathFile::open(path).map_err(|err| {
+        Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+    })?;
+
+    extract_file_buffer(path, file, patterns)
+}
+
+/// Extracts a single file matching any of the given `patterns` from an RPM package read from the
+/// specified reader.
+///
+/// The `path` should point to the package file name, indicating the origin of the data. See
+/// [`extract_file()`] for details.
+pub fn extract_file_buffer<P: AsRef<Path>, R: Read + Send>(
+    path: P,
+    mut reader: R,
+    patterns: &[&str],
+) -> Result<(String, Vec<u8>), Error> {
+    let path = path.as_ref();
+    debug!("Extracting files from the RPM package '{}'", path.display());
+
+    let maybe_compressor = read_headers(&mut reader)?;
+    let mut files = match maybe_compressor.as_deref() {
+        None | Some("identity") => read_cpio(&mut reader, patterns)?,
+        Some(compressor) => {
+            let tool = named_compression_tool(compressor).ok_or_else(|| {
+                Error::new_parse(format!(
+                    "Unsupported RPM payload compressor '{}'",
+                    compressor
+                ))
+            })?;
+            decompress(tool, reader, |payload| read_cpio(payload, patterns))?
+        }
+    };
+
+    let (name, data) = match files.len() {
+        0 => {
+            return Err(Error::new_parse(format!(
+                "No file matching '{}' found in the package",
+                patterns.join("', '")
+            )));
+        }
+        1 => files.pop().unwrap(),
+        _ => {
+            let names = files
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            return Err(Error::new_parse(format!(
+                "Multiple files matching '{}' found in the package: '{}'",
+                patterns.join("', '"),
+                names.join("', '")
+            )));
+        }
+    };
+
+    // Decompress a nested compressed file, such as /boot/symvers-*.gz.
+    match compression_tool(Path::new(&name)) {
+        Some(tool) => {
+            let data = decompress(tool, &data[..], |reader| {
+                let mut out = Vec::new();
+                reader
+                    .read_to_end(&mut out)
+                    .map_err(|err| Error::new_io("Failed to read the decompressed data", err))?;
+                Ok(out)
+            })
+            .map_err(|err| {
+                Error::new_context(format!("Failed to decompress the file '{}'", name), err)
+            })?;
+            Ok((name, data))
+        }
+        None => Ok((name, data)),
+    }
+}
+
+/// Reads the lead, the signature header and the main header of an RPM package, leaving the reader
+/// at the start of the payload. Returns the name of the payload compressor, if specified.
+fn read_headers<R: Read>(reader: &mut R) -> Result<Option<String>, Error> {
+    let err_desc = "Failed to read the RPM headers";
+
+    let mut lead = [0u8; LEAD_SIZE];
+    reader.read_exact(&mut lead).map_io_err(err_desc)?;
+    if lead[..4] != LEAD_MAGIC {
+        return Err(Error::new_parse("Invalid RPM lead magic"));
+    }
+
+    // The signature header is padded to a multiple of 8 bytes.
+    let (_, signature_size) = read_header(reader, false)?;
+    let padding = (8 - signature_size % 8) % 8;
+    reader
+        .read_exact(&mut [0u8; 8][..padding])
+        .map_io_err(err_desc)?;
+
+    let (entries, _) = read_header(reader, true)?;
+    if let Some(format) = find_string(&entries, TAG_PAYLOAD_FORMAT)?
+        && format != "cpio"
+    {
+        return Err(Error::new_parse(format!(
+            "Unsupported RPM payload format '{}'",
+            format
+        )));
+    }
+    find_string(&entries, TAG_PAYLOAD_COMPRESSOR)
+}
+
+/// An RPM header, consisting of its index entries, as `(tag, type, offset)`, and its data store.
+type Header = (Vec<(u32, u32, usize)>, Vec<u8>);
+
+/// Reads an RPM header structure and returns it, together with its total size in bytes. The
+/// content is retained only if `keep` is `true`.
+fn read_header<R: Read>(reader: &mut R, keep: bool) -> Result<(Header, usize), Error> {
+    let err_desc = "Failed to read the RPM headers";
+
+    let mut intro = [0u8; HEADER_INTRO_SIZE];
+    reader.read_exact(&mut intro).map_io_err(err_desc)?;
+    if intro[..4] != HEADER_MAGIC {
+        return Err(Error::new_parse("Invalid RPM header magic"));
+    }
+    let num_entries = be_u32(&intro[8..12]) as usize;
+    let data_size = be_u32(&intro[12..16]) as usize;
+    let index_size = num_entries
+        .checked_mul(INDEX_ENTRY_SIZE)
+        .filter(|&size| size.saturating_add(data_size) <= MAX_HEADER_SIZE)
+        .ok_or_else(|| Error::new_parse("Invalid RPM header size"))?;
+    let total_size = HEADER_INTRO_SIZE + index_size + data_size;
+
+    if !keep {
+        skip_bytes(reader, index_size + data_size).map_io_err(err_desc)?;
+        return Ok(((Vec::new(), Vec::new()), total_size));
+    }
+
+    let mut index = vec![0u8; index_size];
+    reader.read_exact(&mut index).map_io_err(err_desc)?;
+    let mut data = vec![0u8; data_size];
+    reader.read_exact(&mut data).map_io_err(err_desc)?;
+
+    let entries = index
+        .chunks_exact(INDEX_ENTRY_SIZE)
+        .map(|entry| {
+            (
+                be_u32(&entry[0..4]),
+                be_u32(&entry[4..8]),
+                be_u32(&entry[8..12]) as usize,
+            )
+        })
+        .collect();
+    Ok(((entries, data), total_size))
+}
+
+/// Looks up a string value with the given tag in an RPM header.
+fn find_string(header: &Header, tag: u32) -> Result<Option<String>, Error> {
+    let (entries, data) = header;
+    let Some(&(_, value_type, offset)) = entries.iter().find(|(entry_tag, _, _)| *entry_tag == tag)
+    else {
+        return Ok(None);
+    };
+
+    let maybe_value = (value_type == TYPE_STRING)
+        .then(|| data.get(offset..))
+        .flatten()
+        .and_then(|rest| {
+            rest.iter()
+                .position(|&byte| byte == 0)
+                .map(|end| &rest[..end])
+        });
+    match maybe_value {
+        Some(value) => Ok(Some(String::from_utf8_lossy(value).into_owned())),
+        None => Err(Error::new_parse(format!("Invalid RPM header tag {}", tag))),
+    }
+}
+
+/// Skips the given number of bytes in the reader.
+fn skip_bytes<R: Read + ?Sized>(reader: &mut R, size: usize) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.take(size as u64), &mut io::sink())?;
+    if skipped != size as u64 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+/// Reads a big-endian 32-bit number.
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes.try_into().unwrap())
+}
+
+/// Reads a cpio archive in the "newc" format and returns the names and data of all regular files
+/// matching any of the given `patterns`, in the order in which they appear in the archive.
+///
+/// A leading `./` or `/` is removed from all names.
+fn read_cpio<R: Read + ?Sized>(
+    reader: &mut R,
+    patterns: &[&str],
+) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    let err_desc = "Failed to read the RPM payload";
+    let mut files = Vec::new();
+
+    loop {
+        let mut header = [0u8; CPIO_HEADER_SIZE];
+        reader.read_exact(&mut header).map_io_err(err_desc)?;
+        if &header[..6] != b"070701" && &header[..6] != b"070702" {
+            return Err(Error::new_parse("Invalid cpio header magic"));
+        }
+        let mode = parse_cpio_number(&header, 1)?;
+        let file_size = parse_cpio_number(&header, 6)? as usize;
+        let name_size = parse_cpio_number(&header, 11)? as usize;
+
+        // The name is terminated by NUL and padded so that the header and the name are aligned to
+        // 4 bytes.
+        let mut name = vec![0u8; name_size];
+        reader.read_exact(&mut name).map_io_err(err_desc)?;
+        let padding = (4 - (CPIO_HEADER_SIZE + name_size) % 4) % 4;
+        reader
+            .read_exact(&mut [0u8; 4][..padding])
+            .map_io_err(err_desc)?;
+        let name = String::from_utf8_lossy(name.strip_suffix(b"\0").unwrap_or(&name)).into_owned();
+        if name == CPIO_TRAILER {
+            break;
+        }
+        let name = name.trim_start_matches("./").trim_start_matches('/');
+
+        // The data is also padded to 4 bytes.
+        let is_regular = mode & 0o170000 == 0o100000;
+        if is_regular
+            && patterns
+                .iter()
+                .any(|pattern| matches_wildcard(name, pattern))
+        {
+            let mut data = vec![0u8; file_size];
+            reader.read_exact(&mut data).map_io_err(err_desc)?;
+            files.push((name.to_string(), data));
+        } else {
+            skip_bytes(reader, file_size).map_io_err(err_desc)?;
+        }
+        let padding = (4 - file_size % 4) % 4;
+        reader
+            .read_exact(&mut [0u8; 4][..padding])
+            .map_io_err(err_desc)?;
+    }
+
+    Ok(files)
+}
+
+/// Parses the hexadecimal field with the given index in a cpio "newc" header, which follows the
+/// 6-byte magic.
+fn parse_cpio_number(header: &[u8; CPIO_HEADER_SIZE], index: usize) -> Result<u32, Error> {
+    let start = 6 + index * 8;
+    let text = String::from_utf8_lossy(&header[start..start + 8]);
+    u32::from_str_radix(&text, 16)
+        .map_err(|_| Error::new_parse(format!("Invalid cpio header number '{}'", text)))
+}
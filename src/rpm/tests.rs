@@ -0,0 +1,157 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::{assert_ok_eq, assert_parse_err};
+
+/// Creates an RPM header structure with the given string tags.
+fn make_header(tags: &[(u32, &str)]) -> Vec<u8> {
+    let mut index = Vec::new();
+    let mut data = Vec::new();
+    for &(tag, value) in tags {
+        index.extend(tag.to_be_bytes());
+        index.extend(TYPE_STRING.to_be_bytes());
+        index.extend((data.len() as u32).to_be_bytes());
+        index.extend(1u32.to_be_bytes());
+        data.extend(value.as_bytes());
+        data.push(0);
+    }
+
+    let mut header = HEADER_MAGIC.to_vec();
+    header.extend([0; 4]);
+    header.extend((tags.len() as u32).to_be_bytes());
+    header.extend((data.len() as u32).to_be_bytes());
+    header.extend(index);
+    header.extend(data);
+    header
+}
+
+/// Creates a cpio archive in the "newc" format with the given regular files.
+fn make_cpio(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let trailer: (&str, &[u8]) = (CPIO_TRAILER, b"");
+    for &(name, data) in files.iter().chain([&trailer]) {
+        let mode = if name == CPIO_TRAILER { 0 } else { 0o100644 };
+        archive.extend(b"070701");
+        for value in [
+            0,
+            mode,
+            0,
+            0,
+            1,
+            0,
+            data.len(),
+            0,
+            0,
+            0,
+            0,
+            name.len() + 1,
+            0,
+        ] {
+            archive.extend(format!("{:08X}", value).as_bytes());
+        }
+        archive.extend(name.as_bytes());
+        archive.push(0);
+        archive.resize(archive.len().next_multiple_of(4), 0);
+        archive.extend(data);
+        archive.resize(archive.len().next_multiple_of(4), 0);
+    }
+    archive
+}
+
+/// Creates an RPM package with an uncompressed payload containing the given files.
+fn make_rpm(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut package = LEAD_MAGIC.to_vec();
+    package.resize(LEAD_SIZE, 0);
+    package.extend(make_header(&[(1000, "sig")]));
+    package.resize(package.len().next_multiple_of(8), 0);
+    package.extend(make_header(&[
+        (TAG_PAYLOAD_FORMAT, "cpio"),
+        (TAG_PAYLOAD_COMPRESSOR, "identity"),
+    ]));
+    package.extend(make_cpio(files));
+    package
+}
+
+#[test]
+fn rpm_path() {
+    // Check that RPM packages are recognized by their extension.
+    assert!(is_rpm_path(Path::new("kernel-default-6.4.0.x86_64.rpm")));
+    assert!(!is_rpm_path(Path::new("Module.symvers")));
+    assert!(!is_rpm_path(Path::new("rpm")));
+}
+
+#[test]
+fn extract_basic() {
+    // Check that a matching file is extracted from an uncompressed payload.
+    let package = make_rpm(&[
+        ("./usr/src/linux-obj/x86_64/default/Makefile", b"all:\n"),
+        (
+            "./usr/src/linux-obj/x86_64/default/Module.symvers",
+            b"0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n",
+        ),
+    ]);
+    let result = extract_file_buffer("kernel.rpm", &package[..], SYMVERS_PATTERNS);
+    assert_ok_eq!(
+        result,
+        (
+            "usr/src/linux-obj/x86_64/default/Module.symvers".to_string(),
+            b"0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n".to_vec()
+        )
+    );
+}
+
+#[test]
+fn extract_missing() {
+    // Check that a package without a matching file is rejected.
+    let package = make_rpm(&[("./boot/vmlinuz-6.4.0-default", b"")]);
+    let result = extract_file_buffer("kernel.rpm", &package[..], SYMVERS_PATTERNS);
+    assert_parse_err!(
+        result,
+        "No file matching 'boot/symvers-*', '*/Module.symvers' found in the package"
+    );
+}
+
+#[test]
+fn extract_multiple() {
+    // Check that a package with multiple matching files is rejected.
+    let package = make_rpm(&[
+        ("./usr/src/linux-obj/x86_64/default/Module.symvers", b""),
+        ("./usr/src/linux-obj/x86_64/kvmsmall/Module.symvers", b""),
+    ]);
+    let result = extract_file_buffer("kernel.rpm", &package[..], SYMVERS_PATTERNS);
+    assert_parse_err!(
+        result,
+        "Multiple files matching 'boot/symvers-*', '*/Module.symvers' found in the package: 'usr/src/linux-obj/x86_64/default/Module.symvers', 'usr/src/linux-obj/x86_64/kvmsmall/Module.symvers'"
+    );
+}
+
+#[test]
+fn extract_invalid_lead() {
+    // Check that a file which is not an RPM package is rejected.
+    let mut package = make_rpm(&[]);
+    package[0] = 0;
+    let result = extract_file_buffer("kernel.rpm", &package[..], SYMVERS_PATTERNS);
+    assert_parse_err!(result, "Invalid RPM lead magic");
+}
+
+#[test]
+fn extract_unsupported_compressor() {
+    // Check that an unknown payload compressor is rejected.
+    let mut package = LEAD_MAGIC.to_vec();
+    package.resize(LEAD_SIZE, 0);
+    package.extend(make_header(&[]));
+    package.extend(make_header(&[(TAG_PAYLOAD_COMPRESSOR, "lzip")]));
+    let result = extract_file_buffer("kernel.rpm", &package[..], SYMVERS_PATTERNS);
+    assert_parse_err!(result, "Unsupported RPM payload compressor 'lzip'");
+}
+
+#[test]
+fn extract_invalid_cpio() {
+    // Check that a payload which is not a cpio "newc" archive is rejected.
+    let mut package = make_rpm(&[]);
+    let len = package.len();
+    package[len - 124] = b'9';
+    let result = extract_file_buffer("kernel.rpm", &package[..], SYMVERS_PATTERNS);
+    assert_parse_err!(result, "Invalid cpio header magic");
+}
@@ -3,23 +3,27 @@
 
 //! A representation of kABI severity rules and tools for working with the data.
 
-use crate::text::{matches_wildcard, read_lines};
+use crate::text::{Writer, matches_wildcard, read_lines};
 use crate::{Error, MapIOErr, PathFile, debug};
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fmt::{self, Display, Formatter};
+use std::fs;
 use std::io::prelude::*;
 use std::iter::Peekable;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 #[cfg(test)]
 mod tests;
 
 /// A type used in the specification of a severity rule.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum RuleType {
+pub(crate) enum RuleType {
     Module,
     Namespace,
     Symbol,
+    Type,
 }
 
 impl Display for RuleType {
@@ -28,14 +32,24 @@ impl Display for RuleType {
             Self::Module => write!(f, "MODULE"),
             Self::Namespace => write!(f, "NAMESPACE"),
             Self::Symbol => write!(f, "SYMBOL"),
+            Self::Type => write!(f, "TYPE"),
         }
     }
 }
 
-/// A verdict used in the specification of a severity rule.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum Verdict {
+/// A verdict of a severity rule, describing how changes to matching symbols are treated.
+///
+/// The variants are ordered by increasing severity. All verdicts except [`Verdict::Fail`] tolerate
+/// a change, [`Verdict::Info`] and [`Verdict::Warn`] additionally request that it is reported.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Verdict {
+    /// The change is tolerated silently.
     Pass,
+    /// The change is tolerated and reported as informational.
+    Info,
+    /// The change is tolerated and reported as a warning.
+    Warn,
+    /// The change is breaking.
     Fail,
 }
 
@@ -43,22 +57,126 @@ impl Display for Verdict {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             Self::Pass => write!(f, "PASS"),
+            Self::Info => write!(f, "INFO"),
+            Self::Warn => write!(f, "WARN"),
             Self::Fail => write!(f, "FAIL"),
         }
     }
 }
 
+impl Verdict {
+    /// Obtains a [`Verdict`] matching the given keyword used in rules data, or `None` if the
+    /// keyword is not a verdict.
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "PASS" => Some(Self::Pass),
+            "INFO" => Some(Self::Info),
+            "WARN" => Some(Self::Warn),
+            "FAIL" => Some(Self::Fail),
+            _ => None,
+        }
+    }
+}
+
+/// Options that normalize module names and patterns before they are matched by module rules.
+///
+/// By default, a module pattern is matched against the complete module name, as recorded in the
+/// symvers data.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ModuleMatching {
+    /// Match a pattern only against the trailing components of a module path. A pattern with `N`
+    /// components, separated by '/', is matched against the last `N` components of the path. A
+    /// pattern starting with '/' is anchored and always matched against the complete path.
+    pub basename: bool,
+    /// Ignore a trailing ".ko" in module names and patterns.
+    pub strip_ko: bool,
+    /// Match module names and patterns case-insensitively.
+    pub ignore_case: bool,
+}
+
+impl FromStr for ModuleMatching {
+    type Err = Error;
+
+    /// Obtains a [`ModuleMatching`] from a comma-separated list of normalizations, each being
+    /// 'basename', 'strip-ko' or 'ignore-case'. The value 'exact' selects no normalization.
+    fn from_str(list: &str) -> Result<Self, Self::Err> {
+        let mut module_matching = Self::default();
+        if list == "exact" {
+            return Ok(module_matching);
+        }
+        for normalization in list.split(',') {
+            match normalization {
+                "basename" => module_matching.basename = true,
+                "strip-ko" => module_matching.strip_ko = true,
+                "ignore-case" => module_matching.ignore_case = true,
+                _ => {
+                    return Err(Self::Err::new_parse(format!(
+                        "Unrecognized module matching '{}'",
+                        normalization
+                    )));
+                }
+            }
+        }
+        Ok(module_matching)
+    }
+}
+
+impl ModuleMatching {
+    /// Returns whether the specified module name matches the given module pattern.
+    fn matches(&self, module: &str, pattern: &str) -> bool {
+        let (mut module, mut pattern) = (Cow::Borrowed(module), Cow::Borrowed(pattern));
+        if self.ignore_case {
+            module = Cow::Owned(module.to_lowercase());
+            pattern = Cow::Owned(pattern.to_lowercase());
+        }
+        let (mut module, mut pattern) = (module.as_ref(), pattern.as_ref());
+        if self.strip_ko {
+            module = module.strip_suffix(".ko").unwrap_or(module);
+            pattern = pattern.strip_suffix(".ko").unwrap_or(pattern);
+        }
+
+        // Select the trailing components of the module path, unless the pattern is anchored.
+        let anchored_pattern = pattern.strip_prefix('/');
+        if let Some(pattern) = anchored_pattern {
+            return matches_wildcard(module, pattern);
+        }
+        if self.basename {
+            let num_components = pattern.split('/').count();
+            if let Some((sep_idx, _)) = module.rmatch_indices('/').nth(num_components - 1) {
+                module = &module[sep_idx + 1..];
+            }
+        }
+        matches_wildcard(module, pattern)
+    }
+}
+
 /// A severity rule.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug)]
 struct Rule {
     rule_type: RuleType,
     pattern: String,
     verdict: Verdict,
+    archs: Vec<String>, // Empty if the rule applies to all architectures.
+    text: String,       // The rule as written, with the words separated by single spaces.
 
     source_file_idx: usize, // Index into `Rules.files`.
     source_line_idx: usize,
 }
 
+impl PartialEq for Rule {
+    /// Compares two rules by their meaning and origin, ignoring how they were written.
+    fn eq(&self, other: &Self) -> bool {
+        self.rule_type == other.rule_type
+            && self.pattern == other.pattern
+            && self.verdict == other.verdict
+            && self.archs == other.archs
+            && self.source_file_idx == other.source_file_idx
+            && self.source_line_idx == other.source_line_idx
+    }
+}
+
+impl Eq for Rule {}
+
 impl Rule {
     /// Creates a new severity rule.
     pub fn new<S: Into<String>>(
@@ -68,14 +186,50 @@ impl Rule {
         source_file_idx: usize,
         source_line_idx: usize,
     ) -> Self {
+        let pattern = pattern.into();
         Rule {
             rule_type,
-            pattern: pattern.into(),
+            text: format!("{} {}", pattern, verdict),
+            pattern,
             verdict,
+            archs: Vec::new(),
             source_file_idx,
             source_line_idx,
         }
     }
+
+    /// Restricts the rule to the specified architectures.
+    pub fn with_archs(mut self, archs: Vec<String>) -> Self {
+        self.archs = archs;
+        self
+    }
+
+    /// Sets the text of the rule as it was written.
+    fn with_text(mut self, text: String) -> Self {
+        self.text = text;
+        self
+    }
+
+    /// Returns whether the rule applies to the selected architecture.
+    ///
+    /// A rule without an architecture guard applies always. A guarded rule applies only if an
+    /// architecture listed in its guard is selected.
+    fn applies_to(&self, maybe_arch: Option<&str>) -> bool {
+        if self.archs.is_empty() {
+            return true;
+        }
+        maybe_arch.is_some_and(|arch| self.archs.iter().any(|rule_arch| rule_arch == arch))
+    }
+
+    /// Formats the architecture guard of the rule, including a trailing space, or returns an empty
+    /// string if the rule is not guarded.
+    fn format_guard(&self) -> String {
+        if self.archs.is_empty() {
+            String::new()
+        } else {
+            format!("[{}] ", self.archs.join(","))
+        }
+    }
 }
 
 /// A collection of severity rules.
@@ -83,8 +237,84 @@ impl Rule {
 pub struct Rules {
     data: Vec<Rule>,
     files: Vec<PathBuf>,
+    maybe_arch: Option<String>,
+    module_matching: ModuleMatching,
+    keep_going: bool,
+}
+
+/// A reference to a severity rule that tolerated a change, describing its origin.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ToleratingRule<'a> {
+    /// The rules file in which the rule is defined.
+    pub path: &'a Path,
+    /// The line number of the rule in the file, starting from 1.
+    pub line_num: usize,
+    /// The pattern of the rule.
+    pub pattern: &'a str,
+    /// The verdict of the rule, which is never [`Verdict::Fail`].
+    pub verdict: Verdict,
+    /// The rule as written, including any architecture guard and explicit type.
+    pub text: &'a str,
+}
+
+impl ToleratingRule<'_> {
+    /// Describes how the rule tolerates a change, for instance, "tolerated by rule
+    /// severities:3 'foo PASS'".
+    pub fn describe_toleration(&self) -> String {
+        match self.verdict {
+            Verdict::Info => format!("tolerated with a note by rule {}", self),
+            Verdict::Warn => format!("tolerated with a warning by rule {}", self),
+            _ => format!("tolerated by rule {}", self),
+        }
+    }
+}
+
+impl Display for ToleratingRule<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} '{}'",
+            self.path.display(),
+            self.line_num,
+            self.text
+        )
+    }
+}
+
+/// A reference to a severity rule that matched a symbol, describing its origin.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MatchingRule<'a> {
+    /// The index of the rule, as recorded in [`UsedRules`].
+    pub index: usize,
+    /// The rules file in which the rule is defined.
+    pub path: &'a Path,
+    /// The line number of the rule in the file, starting from 1.
+    pub line_num: usize,
+    rule: &'a Rule,
 }
 
+impl Display for MatchingRule<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} '{}{} {} {}'",
+            self.path.display(),
+            self.line_num,
+            self.rule.format_guard(),
+            self.rule.rule_type,
+            self.rule.pattern,
+            self.rule.verdict
+        )
+    }
+}
+
+/// Prefixes of type names in symtypes data, which are followed by '#'. They identify structures,
+/// unions, enums, enumeration constants and typedefs, respectively.
+const TYPE_PREFIXES: &[&str] = &["s", "u", "e", "E", "t"];
+
+/// A pseudo file name recorded as the origin of rules that were not loaded from any file.
+const GENERATED_RULES_PATH: &str = "<generated>";
+
 /// Indexes of all rules in [`Rules`] that were matched by any symvers record.
 pub type UsedRules = HashSet<usize>;
 
@@ -94,12 +324,38 @@ impl Rules {
         Self {
             data: Vec::new(),
             files: Vec::new(),
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     }
 
+    /// Selects the architecture for which the rules are evaluated.
+    ///
+    /// Rules with an architecture guard are considered only if the selected architecture is listed
+    /// in the guard. When no architecture is selected, all guarded rules are ignored.
+    pub fn set_arch<S: Into<String>>(&mut self, arch: S) {
+        self.maybe_arch = Some(arch.into());
+    }
+
+    /// Selects how module rules match module names.
+    pub fn set_module_matching(&mut self, module_matching: ModuleMatching) {
+        self.module_matching = module_matching;
+    }
+
+    /// Enables or disables the keep-going mode for subsequently loaded rules data.
+    ///
+    /// In the keep-going mode, an invalid rule doesn't stop the loading. Instead, all errors in
+    /// the file and its included files are collected and reported together in one
+    /// [`Error::Multiple`].
+    pub fn set_keep_going(&mut self, keep_going: bool) {
+        self.keep_going = keep_going;
+    }
+
     /// Loads rules data from the specified file.
     ///
-    /// New rules are appended to the already present ones.
+    /// New rules are appended to the already present ones. Files referenced by `include`
+    /// directives are resolved relative to the directory of the including file.
     pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
         let path = path.as_ref();
 
@@ -113,13 +369,36 @@ impl Rules {
     /// Loads rules data from the specified reader.
     ///
     /// The `path` should point to the rules file name, indicating the origin of the data. New rules
-    /// are appended to the already present ones.
+    /// are appended to the already present ones. Files referenced by `include` directives are
+    /// resolved relative to the directory of the `path`.
     pub fn load_buffer<P: AsRef<Path>, R: Read>(
         &mut self,
         path: P,
         reader: R,
     ) -> Result<(), Error> {
-        let path = path.as_ref();
+        let orig_data_len = self.data.len();
+        let orig_files_len = self.files.len();
+
+        let result = self.load_inner(path.as_ref(), reader, &mut Vec::new());
+        if result.is_err() {
+            // Roll back any rules loaded before the error.
+            self.data.truncate(orig_data_len);
+            self.files.truncate(orig_files_len);
+        }
+        result
+    }
+
+    /// Loads rules data from the specified reader, recursively processing any `include`
+    /// directives.
+    ///
+    /// The `include_stack` contains canonicalized paths of all files that are currently being
+    /// loaded and is used to detect include cycles.
+    fn load_inner<R: Read>(
+        &mut self,
+        path: &Path,
+        reader: R,
+        include_stack: &mut Vec<PathBuf>,
+    ) -> Result<(), Error> {
         debug!("Loading rules data from '{}'", path.display());
 
         // Read all content from the file.
@@ -128,18 +407,128 @@ impl Rules {
             Err(err) => return Err(Error::new_io("Failed to read rules data", err)),
         };
 
-        // Parse all rules.
+        // Register the file and parse all rules, inlining the content of included files.
         let file_idx = self.files.len();
-        let mut new_rules = Vec::new();
+        self.files.push(path.to_path_buf());
+        include_stack.push(canonicalize_or_keep(path));
+
+        let mut errors = Vec::new();
         for (line_idx, line) in lines.iter().enumerate() {
-            if let Some(rule) = parse_rule(path, file_idx, line_idx, line)? {
-                new_rules.push(rule);
+            if let Err(err) = self.load_line(path, file_idx, line_idx, line, include_stack) {
+                if !self.keep_going {
+                    return Err(err);
+                }
+                errors.push(err);
             }
         }
 
-        // Add the new rules.
-        self.files.push(path.to_path_buf());
-        self.data.append(&mut new_rules);
+        include_stack.pop();
+
+        if !errors.is_empty() {
+            return Err(Error::new_multiple(errors));
+        }
+
+        Ok(())
+    }
+
+    /// Processes a single line of rules data, which can be an `include` directive or a rule.
+    fn load_line(
+        &mut self,
+        path: &Path,
+        file_idx: usize,
+        line_idx: usize,
+        line: &str,
+        include_stack: &mut Vec<PathBuf>,
+    ) -> Result<(), Error> {
+        if let Some((include_offset, include)) = parse_include(line) {
+            let include_path = match path.parent() {
+                Some(parent) => parent.join(include),
+                None => PathBuf::from(include),
+            };
+            if include_stack.contains(&canonicalize_or_keep(&include_path)) {
+                return Err(Error::new_parse_format_at(
+                    &format!(
+                        "The included file '{}' is already being loaded, which creates a cycle",
+                        include_path.display()
+                    ),
+                    path,
+                    line_idx + 1,
+                    line,
+                    include_offset,
+                ));
+            }
+
+            let file = PathFile::open(&include_path).map_err(|err| {
+                Error::new_io(
+                    format!("Failed to open the file '{}'", include_path.display()),
+                    err,
+                )
+            })?;
+            return self.load_inner(&include_path, file, include_stack);
+        }
+
+        if let Some(rule) = parse_rule(path, file_idx, line_idx, line)? {
+            self.data.push(rule);
+        }
+
+        Ok(())
+    }
+
+    /// Appends a new rule that does not originate from any file.
+    ///
+    /// The rule is recorded as coming from the "<generated>" pseudo file, at a line matching its
+    /// position among all generated rules.
+    pub(crate) fn push_generated<S: Into<String>>(
+        &mut self,
+        rule_type: RuleType,
+        pattern: S,
+        verdict: Verdict,
+    ) {
+        let file_idx = match self
+            .files
+            .iter()
+            .position(|path| path == Path::new(GENERATED_RULES_PATH))
+        {
+            Some(file_idx) => file_idx,
+            None => {
+                self.files.push(PathBuf::from(GENERATED_RULES_PATH));
+                self.files.len() - 1
+            }
+        };
+        let line_idx = self
+            .data
+            .iter()
+            .filter(|rule| rule.source_file_idx == file_idx)
+            .count();
+        self.data
+            .push(Rule::new(rule_type, pattern, verdict, file_idx, line_idx));
+    }
+
+    /// Writes all rules to the specified file, in the form accepted by [`Rules::load()`].
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_buffer(Writer::new_file(path)?)
+    }
+
+    /// Writes all rules to the provided output stream, in the form accepted by
+    /// [`Rules::load_buffer()`].
+    ///
+    /// Each rule is written with an explicit type, in the form `<type> <pattern> <verdict>`.
+    pub fn write_buffer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let err_desc = "Failed to write a severity rule";
+
+        for rule in &self.data {
+            writeln!(
+                writer,
+                "{}{} {} {}",
+                rule.format_guard(),
+                rule.rule_type,
+                rule.pattern,
+                rule.verdict
+            )
+            .map_io_err(err_desc)?;
+        }
+
+        writer.flush().map_io_err(err_desc)?;
 
         Ok(())
     }
@@ -153,9 +542,12 @@ impl Rules {
         maybe_namespace: Option<&str>,
     ) -> Option<usize> {
         for (rule_idx, rule) in self.data.iter().enumerate() {
+            if !rule.applies_to(self.maybe_arch.as_deref()) {
+                continue;
+            }
             match rule.rule_type {
                 RuleType::Module => {
-                    if matches_wildcard(module, &rule.pattern) {
+                    if self.module_matching.matches(module, &rule.pattern) {
                         return Some(rule_idx);
                     }
                 }
@@ -171,20 +563,99 @@ impl Rules {
                         return Some(rule_idx);
                     }
                 }
+                RuleType::Type => {
+                    // Type rules apply only to changed types in symtypes data.
+                }
             }
         }
         None
     }
 
-    /// Searches for the first rule that matches the specified symbol. If a match is found, it
-    /// returns its verdict on whether changes to the symbol should be tolerated. Otherwise, returns
-    /// false.
-    pub fn is_tolerated(&self, symbol: &str, module: &str, maybe_namespace: Option<&str>) -> bool {
-        if let Some(rule_idx) = self.find_matching_rule(symbol, module, maybe_namespace) {
-            self.data[rule_idx].verdict == Verdict::Pass
-        } else {
-            false
+    /// Searches for the first type rule that matches the specified type name. If a match is
+    /// found, it returns the index of the rule. Otherwise, returns None.
+    fn find_matching_type_rule(&self, type_name: &str) -> Option<usize> {
+        self.data.iter().position(|rule| {
+            rule.rule_type == RuleType::Type
+                && rule.applies_to(self.maybe_arch.as_deref())
+                && matches_wildcard(type_name, &rule.pattern)
+        })
+    }
+
+    /// Returns a reference to the rule with the given index if its verdict is that changes should
+    /// be tolerated. Otherwise, returns None.
+    fn tolerating_rule(&self, rule_idx: usize) -> Option<ToleratingRule<'_>> {
+        let rule = &self.data[rule_idx];
+        if rule.verdict == Verdict::Fail {
+            return None;
         }
+        Some(ToleratingRule {
+            path: &self.files[rule.source_file_idx],
+            line_num: rule.source_line_idx + 1,
+            pattern: &rule.pattern,
+            verdict: rule.verdict,
+            text: &rule.text,
+        })
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns whether there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Searches for the first rule that matches the specified symbol. If a match is found, it
+    /// returns a reference to the rule, regardless of its verdict. Otherwise, returns None.
+    pub fn find_rule(
+        &self,
+        symbol: &str,
+        module: &str,
+        maybe_namespace: Option<&str>,
+    ) -> Option<MatchingRule<'_>> {
+        let rule_idx = self.find_matching_rule(symbol, module, maybe_namespace)?;
+        let rule = &self.data[rule_idx];
+        Some(MatchingRule {
+            index: rule_idx,
+            path: &self.files[rule.source_file_idx],
+            line_num: rule.source_line_idx + 1,
+            rule,
+        })
+    }
+
+    /// Searches for the first rule that matches the specified symbol. If a match is found, it
+    /// returns the verdict of the rule. Otherwise, returns None.
+    pub fn evaluate(
+        &self,
+        symbol: &str,
+        module: &str,
+        maybe_namespace: Option<&str>,
+    ) -> Option<Verdict> {
+        let rule_idx = self.find_matching_rule(symbol, module, maybe_namespace)?;
+        Some(self.data[rule_idx].verdict)
+    }
+
+    /// Searches for the first rule that matches the specified symbol. If a match is found and its
+    /// verdict is that changes to the symbol should be tolerated, it returns a reference to the
+    /// rule. Otherwise, returns None.
+    pub fn is_tolerated(
+        &self,
+        symbol: &str,
+        module: &str,
+        maybe_namespace: Option<&str>,
+    ) -> Option<ToleratingRule<'_>> {
+        let rule_idx = self.find_matching_rule(symbol, module, maybe_namespace)?;
+        self.tolerating_rule(rule_idx)
+    }
+
+    /// Searches for the first type rule that matches the specified type name, such as `s#foo`. If
+    /// a match is found and its verdict is that changes to the type should be tolerated, it
+    /// returns a reference to the rule. Otherwise, returns None.
+    pub fn is_type_tolerated(&self, type_name: &str) -> Option<ToleratingRule<'_>> {
+        let rule_idx = self.find_matching_type_rule(type_name)?;
+        self.tolerating_rule(rule_idx)
     }
 
     /// Searches for the first rule that matches the specified symbol. If a match is found, the
@@ -202,6 +673,8 @@ impl Rules {
     }
 
     /// Writes information about all unused rules to the provided output stream.
+    ///
+    /// Type rules are skipped, as they cannot be matched by symvers records.
     pub fn write_unused_rules_buffer<W: Write>(
         &self,
         used_rules: &UsedRules,
@@ -210,12 +683,51 @@ impl Rules {
         let err_desc = "Failed to write information about an unused rule";
 
         for (rule_idx, rule) in self.data.iter().enumerate() {
-            if !used_rules.contains(&rule_idx) {
+            if rule.rule_type != RuleType::Type && !used_rules.contains(&rule_idx) {
                 writeln!(
                     writer,
-                    "{}:{}: WARNING: Severity rule '{} {} {}' is unused",
+                    "{}:{}: WARNING: Severity rule '{}{} {} {}' is unused",
                     self.files[rule.source_file_idx].display(),
                     rule.source_line_idx + 1,
+                    rule.format_guard(),
+                    rule.rule_type,
+                    rule.pattern,
+                    rule.verdict
+                )
+                .map_io_err(err_desc)?;
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(())
+    }
+
+    /// Writes information about all module rules that cannot match any of the specified modules
+    /// to the provided output stream.
+    ///
+    /// Rules not applying to the selected architecture are skipped.
+    pub fn write_unmatched_module_rules_buffer<'a, I: IntoIterator<Item = &'a str>, W: Write>(
+        &self,
+        modules: I,
+        mut writer: W,
+    ) -> Result<(), Error> {
+        let err_desc = "Failed to write information about an unmatched module rule";
+
+        let modules = modules.into_iter().collect::<Vec<_>>();
+        for rule in &self.data {
+            if rule.rule_type == RuleType::Module
+                && rule.applies_to(self.maybe_arch.as_deref())
+                && !modules
+                    .iter()
+                    .any(|module| self.module_matching.matches(module, &rule.pattern))
+            {
+                writeln!(
+                    writer,
+                    "{}:{}: WARNING: Severity rule '{}{} {} {}' does not match any module",
+                    self.files[rule.source_file_idx].display(),
+                    rule.source_line_idx + 1,
+                    rule.format_guard(),
                     rule.rule_type,
                     rule.pattern,
                     rule.verdict
@@ -231,10 +743,15 @@ impl Rules {
 }
 
 /// Parses the next rule word from the given iterator, taking into account comments starting with
-/// '#'.
-fn get_next_rule_word<I: Iterator<Item = char>>(chars: &mut Peekable<I>) -> Option<String> {
+/// '#'. Returns the byte offset of the word in the line together with the word itself.
+///
+/// A '#' following a symtypes type prefix at the start of a word, as in `s#foo`, is a part of the
+/// word and doesn't start a comment.
+fn get_next_rule_word<I: Iterator<Item = (usize, char)> + Clone>(
+    chars: &mut Peekable<I>,
+) -> Option<(usize, String)> {
     // Skip over any whitespace.
-    while let Some(&c) = chars.peek() {
+    while let Some(&(_, c)) = chars.peek() {
         if !c.is_ascii_whitespace() {
             break;
         }
@@ -242,26 +759,57 @@ fn get_next_rule_word<I: Iterator<Item = char>>(chars: &mut Peekable<I>) -> Opti
     }
 
     // Terminate when a comment starting with '#' is found.
-    if let Some(&c) = chars.peek()
-        && c == '#'
-    {
+    let &(offset, c) = chars.peek()?;
+    if c == '#' {
         return None;
     }
 
     // Read one word.
     let mut word = String::new();
-    while let Some(&c) = chars.peek() {
-        if c.is_ascii_whitespace() || c == '#' {
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_ascii_whitespace() || (c == '#' && !is_type_prefix(&word, chars.clone())) {
             break;
         }
         word.push(c);
         chars.next();
     }
 
-    if word.is_empty() {
+    Some((offset, word))
+}
+
+/// Returns whether the given partially read word is a symtypes type prefix, such as `s` in `s#foo`.
+/// The `chars` iterator must be positioned at the '#' character which follows the word.
+fn is_type_prefix<I: Iterator<Item = (usize, char)>>(word: &str, mut chars: I) -> bool {
+    chars.next();
+    TYPE_PREFIXES.contains(&word)
+        && chars
+            .next()
+            .is_some_and(|(_, c)| !c.is_ascii_whitespace() && c != '#')
+}
+
+/// Returns the canonical form of the specified path, or the path itself if it cannot be
+/// canonicalized, for instance, because it does not exist.
+fn canonicalize_or_keep(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Parses an `include <file>` directive, returning the name of the included file if the line
+/// contains one.
+///
+/// A line where the second word is a verdict is not considered as an include directive, but as
+/// a classic rule for a symbol named "include". The name is returned together with its byte
+/// offset in the line.
+fn parse_include(line: &str) -> Option<(usize, String)> {
+    let mut chars = line.char_indices().peekable();
+
+    if get_next_rule_word(&mut chars)?.1 != "include" {
         return None;
     }
-    Some(word)
+    let (offset, include) = get_next_rule_word(&mut chars)?;
+    if Verdict::from_keyword(&include).is_some() || get_next_rule_word(&mut chars).is_some() {
+        return None;
+    }
+    Some((offset, include))
 }
 
 /// Parses a single severity rule.
@@ -271,24 +819,60 @@ fn parse_rule(
     line_idx: usize,
     line: &str,
 ) -> Result<Option<Rule>, Error> {
-    let mut chars = line.chars().peekable();
+    let mut chars = line.char_indices().peekable();
 
-    // Parse the first two words blindly.
-    let word0 = match get_next_rule_word(&mut chars) {
+    // Parse the first two words blindly, after an optional architecture guard.
+    let (mut offset0, mut word0) = match get_next_rule_word(&mut chars) {
         Some(word) => word,
         None => {
             // The line doesn't contain any rule.
             return Ok(None);
         }
     };
-    let word1 = match get_next_rule_word(&mut chars) {
+    let mut archs = Vec::new();
+    let mut text = String::new();
+    if let Some(guard) = word0.strip_prefix('[') {
+        archs = match guard.strip_suffix(']') {
+            Some(guard) if guard.split(',').all(|arch| !arch.is_empty()) => {
+                guard.split(',').map(String::from).collect()
+            }
+            _ => {
+                return Err(Error::new_parse_format_at(
+                    &format!(
+                        "Invalid architecture guard '{}', must be in the form '[arch,...]'",
+                        word0
+                    ),
+                    path,
+                    line_idx + 1,
+                    line,
+                    offset0,
+                ));
+            }
+        };
+        text.push_str(&word0);
+        text.push(' ');
+        (offset0, word0) = match get_next_rule_word(&mut chars) {
+            Some(word) => word,
+            None => {
+                return Err(Error::new_parse_format_at(
+                    "The rule is incomplete, must be in the form '[type] <pattern> <verdict>'",
+                    path,
+                    line_idx + 1,
+                    line,
+                    incomplete_offset(line),
+                ));
+            }
+        };
+    }
+    let (offset1, word1) = match get_next_rule_word(&mut chars) {
         Some(word) => word,
         None => {
-            return Err(Error::new_parse_format(
+            return Err(Error::new_parse_format_at(
                 "The rule is incomplete, must be in the form '[type] <pattern> <verdict>'",
                 path,
                 line_idx + 1,
                 line,
+                incomplete_offset(line),
             ));
         }
     };
@@ -298,39 +882,49 @@ fn parse_rule(
     // The style of a rule is determined by the number of words. Classic rules are in the form
     // `<pattern> <verdict>`. Rules with an explicit type are in the form
     // `<type> <pattern> <verdict>`.
-    let (rule_type, pattern, verdict) = match get_next_rule_word(&mut chars) {
+    let (rule_type, pattern, (verdict_offset, verdict)) = match get_next_rule_word(&mut chars) {
         Some(word2) => {
             let rule_type = match word0.as_str() {
                 "MODULE" => RuleType::Module,
                 "NAMESPACE" => RuleType::Namespace,
                 "SYMBOL" => RuleType::Symbol,
+                "TYPE" => RuleType::Type,
                 _ => {
-                    return Err(Error::new_parse_format(
+                    return Err(Error::new_parse_format_at(
                         &format!(
-                            "Invalid rule type '{}', must be either MODULE, NAMESPACE or SYMBOL",
+                            "Invalid rule type '{}', must be either MODULE, NAMESPACE, SYMBOL or TYPE",
                             word0
                         ),
                         path,
                         line_idx + 1,
                         line,
+                        offset0,
                     ));
                 }
             };
 
             // Check that nothing else is left on the line.
-            if get_next_rule_word(&mut chars).is_some() {
-                return Err(Error::new_parse_format(
+            if let Some((extra_offset, _)) = get_next_rule_word(&mut chars) {
+                return Err(Error::new_parse_format_at(
                     "Unexpected string found after the verdict",
                     path,
                     line_idx + 1,
                     line,
+                    extra_offset,
                 ));
             }
 
+            text.push_str(&word0);
+            text.push(' ');
             (rule_type, word1, word2)
         }
         None => {
-            let rule_type = if word0.contains('/') || word0 == "vmlinux" {
+            let rule_type = if word0
+                .split_once('#')
+                .is_some_and(|(prefix, _)| TYPE_PREFIXES.contains(&prefix))
+            {
+                RuleType::Type
+            } else if word0.contains('/') || word0 == "vmlinux" {
                 RuleType::Module
             } else if word0 == word0.to_uppercase() {
                 RuleType::Namespace
@@ -338,25 +932,44 @@ fn parse_rule(
                 RuleType::Symbol
             };
 
-            (rule_type, word0, word1)
+            (rule_type, word0, (offset1, word1))
         }
     };
 
     // Parse the verdict.
-    let verdict = match verdict.as_str() {
-        "PASS" => Verdict::Pass,
-        "FAIL" => Verdict::Fail,
-        _ => {
-            return Err(Error::new_parse_format(
-                &format!("Invalid verdict '{}', must be either PASS or FAIL", verdict),
+    let verdict = match Verdict::from_keyword(&verdict) {
+        Some(verdict) => verdict,
+        None => {
+            return Err(Error::new_parse_format_at(
+                &format!(
+                    "Invalid verdict '{}', must be one of PASS, INFO, WARN or FAIL",
+                    verdict
+                ),
                 path,
                 line_idx + 1,
                 line,
+                verdict_offset,
             ));
         }
     };
 
-    Ok(Some(Rule::new(
-        rule_type, pattern, verdict, file_idx, line_idx,
-    )))
+    text.push_str(&format!("{} {}", pattern, verdict));
+
+    Ok(Some(
+        Rule::new(rule_type, pattern, verdict, file_idx, line_idx)
+            .with_archs(archs)
+            .with_text(text),
+    ))
+}
+
+/// Returns the byte offset in the given rule line where a missing word is expected, which is just
+/// after the last word and before any comment.
+fn incomplete_offset(line: &str) -> usize {
+    let content = match line.find('#') {
+        Some(comment) => &line[..comment],
+        None => line,
+    };
+    content
+        .trim_end_matches(|c: char| c.is_ascii_whitespace())
+        .len()
 }
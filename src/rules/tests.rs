@@ -3,6 +3,7 @@
 
 use super::*;
 use crate::{assert_ok, assert_parse_err, bytes};
+use std::iter::zip;
 
 #[test]
 fn read_classic_module_rule() {
@@ -23,7 +24,10 @@ fn read_classic_module_rule() {
                 Rule::new(RuleType::Module, "lib/test_module.ko", Verdict::Pass, 0, 0),
                 Rule::new(RuleType::Module, "vmlinux", Verdict::Pass, 0, 1),
             ],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -49,7 +53,10 @@ fn read_classic_namespace_rule() {
                 0,
                 0
             )],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -78,7 +85,42 @@ fn read_classic_symbol_rule() {
                 Rule::new(RuleType::Symbol, "vmlinux2", Verdict::Pass, 0, 2),
                 Rule::new(RuleType::Symbol, "test_namespace", Verdict::Pass, 0, 3),
             ],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
+        }
+    );
+}
+
+#[test]
+fn read_classic_type_rule() {
+    // Check that a pattern with a symtypes type prefix is considered as a type, while a '#'
+    // following only the prefix still starts a comment.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "s#kvm_* PASS\n",
+            "t#foo_t WARN # comment\n",
+            "TYPE e#bar FAIL\n",
+            "s PASS # comment\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        rules,
+        Rules {
+            data: vec![
+                Rule::new(RuleType::Type, "s#kvm_*", Verdict::Pass, 0, 0),
+                Rule::new(RuleType::Type, "t#foo_t", Verdict::Warn, 0, 1),
+                Rule::new(RuleType::Type, "e#bar", Verdict::Fail, 0, 2),
+                Rule::new(RuleType::Symbol, "s", Verdict::Pass, 0, 3),
+            ],
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -104,7 +146,10 @@ fn read_typed_module_rule() {
                 0,
                 0
             )],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -130,7 +175,10 @@ fn read_typed_namespace_rule() {
                 0,
                 0
             )],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -156,7 +204,10 @@ fn read_typed_symbol_rule() {
                 0,
                 0
             )],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -174,16 +225,20 @@ fn read_typed_invalid_type() {
     assert_parse_err!(
         result,
         concat!(
-            "Invalid rule type 'MOD', must be either MODULE, NAMESPACE or SYMBOL\n",
-            " test.severities:1\n",
-            " | MOD lib/test_module.ko PASS", //
+            "Invalid rule type 'MOD', must be either MODULE, NAMESPACE, SYMBOL or TYPE\n",
+            " test.severities:1:1\n",
+            " | MOD lib/test_module.ko PASS\n",
+            " | ^", //
         ),
     );
     assert_eq!(
         rules,
         Rules {
             data: vec![],
-            files: vec![]
+            files: vec![],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -207,7 +262,37 @@ fn read_pass_fail_rule() {
                 Rule::new(RuleType::Symbol, "symbol_name", Verdict::Pass, 0, 0),
                 Rule::new(RuleType::Symbol, "symbol_name2", Verdict::Fail, 0, 1),
             ],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
+        }
+    );
+}
+
+#[test]
+fn read_info_warn_rule() {
+    // Check that the INFO and WARN verdicts are correctly recognized.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "symbol_name INFO\n",
+            "symbol_name2 WARN\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        rules,
+        Rules {
+            data: vec![
+                Rule::new(RuleType::Symbol, "symbol_name", Verdict::Info, 0, 0),
+                Rule::new(RuleType::Symbol, "symbol_name2", Verdict::Warn, 0, 1),
+            ],
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -226,15 +311,19 @@ fn read_incomplete_rule() {
         result,
         concat!(
             "The rule is incomplete, must be in the form '[type] <pattern> <verdict>'\n",
-            " test.severities:1\n",
-            " | symbol_name", //
+            " test.severities:1:12\n",
+            " | symbol_name\n",
+            " |            ^", //
         ),
     );
     assert_eq!(
         rules,
         Rules {
             data: vec![],
-            files: vec![]
+            files: vec![],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -252,16 +341,82 @@ fn read_invalid_verdict() {
     assert_parse_err!(
         result,
         concat!(
-            "Invalid verdict 'OK', must be either PASS or FAIL\n",
-            " test.severities:1\n",
-            " | symbol_name OK", //
+            "Invalid verdict 'OK', must be one of PASS, INFO, WARN or FAIL\n",
+            " test.severities:1:13\n",
+            " | symbol_name OK\n",
+            " |             ^", //
         ),
     );
     assert_eq!(
         rules,
         Rules {
             data: vec![],
-            files: vec![]
+            files: vec![],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
+        }
+    );
+}
+
+#[test]
+fn read_invalid_verdict_tab() {
+    // Check that the caret pointing to an invalid verdict stays aligned when the rule contains
+    // tabs.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "\tsymbol_name\tOK\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid verdict 'OK', must be one of PASS, INFO, WARN or FAIL\n",
+            " test.severities:1:14\n",
+            " | \tsymbol_name\tOK\n",
+            " | \t           \t^", //
+        ),
+    );
+}
+
+#[test]
+fn read_keep_going() {
+    // Check that all errors in a rules file are reported together in the keep-going mode and that
+    // no rules from the file are loaded.
+    let mut rules = Rules::new();
+    rules.set_keep_going(true);
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo PASS\n",
+            "symbol_name OK\n",
+            "bar FAIL\n",
+            "SYMBOL symbol_name PASS garbage\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid verdict 'OK', must be one of PASS, INFO, WARN or FAIL\n",
+            " test.severities:2:13\n",
+            " | symbol_name OK\n",
+            " |             ^\n",
+            "Unexpected string found after the verdict\n",
+            " test.severities:4:25\n",
+            " | SYMBOL symbol_name PASS garbage\n",
+            " |                         ^", //
+        ),
+    );
+    assert_eq!(
+        rules,
+        Rules {
+            data: vec![],
+            files: vec![],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: true,
         }
     );
 }
@@ -280,15 +435,19 @@ fn read_extra_data() {
         result,
         concat!(
             "Unexpected string found after the verdict\n",
-            " test.severities:1\n",
-            " | SYMBOL symbol_name PASS garbage", //
+            " test.severities:1:25\n",
+            " | SYMBOL symbol_name PASS garbage\n",
+            " |                         ^", //
         ),
     );
     assert_eq!(
         rules,
         Rules {
             data: vec![],
-            files: vec![]
+            files: vec![],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -308,7 +467,10 @@ fn read_empty_record() {
         rules,
         Rules {
             data: vec![],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -333,7 +495,10 @@ fn read_comments() {
                 Rule::new(RuleType::Module, "lib/test_module.ko", Verdict::Pass, 0, 1),
                 Rule::new(RuleType::Module, "lib/test_module2.ko", Verdict::Fail, 0, 2),
             ],
-            files: vec![PathBuf::from("test.severities")]
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -359,7 +524,10 @@ fn read_second() {
                 Rule::new(RuleType::Symbol, "foo", Verdict::Pass, 0, 0),
                 Rule::new(RuleType::Symbol, "bar", Verdict::Fail, 0, 1),
             ],
-            files: vec![PathBuf::from("test.severities"),]
+            files: vec![PathBuf::from("test.severities"),],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 
@@ -384,7 +552,10 @@ fn read_second() {
             files: vec![
                 PathBuf::from("test.severities"),
                 PathBuf::from("test2.severities"),
-            ]
+            ],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
         }
     );
 }
@@ -403,10 +574,26 @@ fn tolerate_symbol() {
         ),
     );
     assert_ok!(result);
-    assert!(rules.is_tolerated("foo", "lib/test_module.ko", None));
-    assert!(!rules.is_tolerated("bar", "lib/test_module.ko", None));
-    assert!(rules.is_tolerated("bazi", "lib/test_module.ko", None));
-    assert!(!rules.is_tolerated("qux", "lib/test_module.ko", None));
+    assert!(
+        rules
+            .is_tolerated("foo", "lib/test_module.ko", None)
+            .is_some()
+    );
+    assert!(
+        rules
+            .is_tolerated("bar", "lib/test_module.ko", None)
+            .is_none()
+    );
+    assert!(
+        rules
+            .is_tolerated("bazi", "lib/test_module.ko", None)
+            .is_some()
+    );
+    assert!(
+        rules
+            .is_tolerated("qux", "lib/test_module.ko", None)
+            .is_none()
+    );
 }
 
 #[test]
@@ -423,10 +610,76 @@ fn tolerate_module() {
         ),
     );
     assert_ok!(result);
-    assert!(rules.is_tolerated("symbol_name", "lib/foo.ko", None));
-    assert!(!rules.is_tolerated("symbol_name", "lib/bar.ko", None));
-    assert!(rules.is_tolerated("symbol_name", "lib/bazi.ko", None));
-    assert!(!rules.is_tolerated("symbol_name", "lib/qux.ko", None));
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/foo.ko", None)
+            .is_some()
+    );
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/bar.ko", None)
+            .is_none()
+    );
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/bazi.ko", None)
+            .is_some()
+    );
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/qux.ko", None)
+            .is_none()
+    );
+}
+
+#[test]
+fn tolerate_module_normalized() {
+    // Check that module names and patterns are normalized according to the selected module
+    // matching before being compared.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "MODULE e1000e PASS\n",
+            "MODULE intel/igb PASS\n",
+            "MODULE /ixgbe PASS\n",
+            "MODULE Ice.ko PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let modules = [
+        "drivers/net/ethernet/intel/e1000e/e1000e",
+        "drivers/net/ethernet/intel/igb",
+        "drivers/net/ethernet/intel/ixgbe/ixgbe",
+        "ixgbe",
+        "drivers/net/ethernet/intel/ice/ice",
+    ];
+    for (module_matching, expected) in [
+        ("exact", [false, false, false, true, false]),
+        ("basename", [true, true, false, true, false]),
+        (
+            "basename,strip-ko,ignore-case",
+            [true, true, false, true, true],
+        ),
+    ] {
+        rules.set_module_matching(ModuleMatching::from_str(module_matching).unwrap());
+        for (module, tolerated) in zip(modules, expected) {
+            assert_eq!(
+                rules.is_tolerated("symbol_name", module, None).is_some(),
+                tolerated,
+                "module '{}' with '{}'",
+                module,
+                module_matching
+            );
+        }
+    }
+}
+
+#[test]
+fn read_invalid_module_matching() {
+    // Check that an unrecognized module matching is rejected.
+    let result = ModuleMatching::from_str("basename,suffix");
+    assert_parse_err!(result, "Unrecognized module matching 'suffix'");
 }
 
 #[test]
@@ -443,10 +696,26 @@ fn tolerate_namespace() {
         ),
     );
     assert_ok!(result);
-    assert!(rules.is_tolerated("symbol_name", "lib/test_module.ko", Some("FOO_NS")));
-    assert!(!rules.is_tolerated("symbol_name", "lib/test_module.ko", Some("BAR_NS")));
-    assert!(rules.is_tolerated("symbol_name", "lib/test_module.ko", Some("BAZI_NS")));
-    assert!(!rules.is_tolerated("symbol_name", "lib/test_module.ko", Some("QUX_NS")));
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/test_module.ko", Some("FOO_NS"))
+            .is_some()
+    );
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/test_module.ko", Some("BAR_NS"))
+            .is_none()
+    );
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/test_module.ko", Some("BAZI_NS"))
+            .is_some()
+    );
+    assert!(
+        rules
+            .is_tolerated("symbol_name", "lib/test_module.ko", Some("QUX_NS"))
+            .is_none()
+    );
 }
 
 #[test]
@@ -462,7 +731,112 @@ fn tolerate_order() {
         ),
     );
     assert_ok!(result);
-    assert!(rules.is_tolerated("foobar", "lib/test_module.ko", None));
+    assert!(
+        rules
+            .is_tolerated("foobar", "lib/test_module.ko", None)
+            .is_some()
+    );
+}
+
+#[test]
+fn evaluate_verdict() {
+    // Check that evaluating a symbol returns the verdict of the first matching rule and that all
+    // verdicts except FAIL tolerate changes.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo PASS\n",
+            "bar INFO\n",
+            "baz WARN\n",
+            "qux FAIL\n", //
+        ),
+    );
+    assert_ok!(result);
+    for (symbol, maybe_verdict) in [
+        ("foo", Some(Verdict::Pass)),
+        ("bar", Some(Verdict::Info)),
+        ("baz", Some(Verdict::Warn)),
+        ("qux", Some(Verdict::Fail)),
+        ("quux", None),
+    ] {
+        assert_eq!(
+            rules.evaluate(symbol, "lib/test_module.ko", None),
+            maybe_verdict
+        );
+        assert_eq!(
+            rules
+                .is_tolerated(symbol, "lib/test_module.ko", None)
+                .map(|rule| rule.verdict),
+            maybe_verdict.filter(|&verdict| verdict != Verdict::Fail)
+        );
+    }
+}
+
+#[test]
+fn tolerate_provenance() {
+    // Check that the rule which tolerated a change is correctly identified by its file, line number
+    // and pattern.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "# Comment\n",
+            "foo FAIL\n",
+            "KVM PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let result = rules.load_buffer("test2.severities", bytes!("bar* PASS\n"));
+    assert_ok!(result);
+    let rule = rules.is_tolerated("baz", "lib/test_module.ko", Some("KVM"));
+    assert_eq!(
+        rule,
+        Some(ToleratingRule {
+            path: Path::new("test.severities"),
+            line_num: 3,
+            pattern: "KVM",
+            verdict: Verdict::Pass,
+            text: "KVM PASS",
+        })
+    );
+    assert_eq!(rule.unwrap().to_string(), "test.severities:3 'KVM PASS'");
+    let rule = rules.is_tolerated("barbar", "lib/test_module.ko", None);
+    assert_eq!(
+        rule.map(|rule| rule.to_string()),
+        Some("test2.severities:1 'bar* PASS'".to_string())
+    );
+}
+
+#[test]
+fn tolerate_type() {
+    // Check that type rules apply only to type names and symbol rules only to symbols.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "s#kvm_vcpu FAIL\n",
+            "s#kvm_* PASS\n",
+            "foo* PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        rules.is_type_tolerated("s#kvm_run"),
+        Some(ToleratingRule {
+            path: Path::new("test.severities"),
+            line_num: 2,
+            pattern: "s#kvm_*",
+            verdict: Verdict::Pass,
+            text: "s#kvm_* PASS",
+        })
+    );
+    assert_eq!(rules.is_type_tolerated("s#kvm_vcpu"), None);
+    assert_eq!(rules.is_type_tolerated("s#foo"), None);
+    assert_eq!(
+        rules.is_tolerated("s#kvm_run", "lib/test_module.ko", None),
+        None
+    );
 }
 
 #[test]
@@ -486,6 +860,31 @@ fn mark_used_rules() {
     assert_eq!(used_rules, UsedRules::from([0, 3]));
 }
 
+#[test]
+fn find_rule() {
+    // Check that the first matching rule is found regardless of its verdict.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo* PASS\n",
+            "MODULE lib/qux.ko FAIL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let rule = rules.find_rule("foobar", "vmlinux", None).unwrap();
+    assert_eq!(rule.index, 0);
+    assert_eq!(rule.to_string(), "test.severities:1 'SYMBOL foo* PASS'");
+    let rule = rules.find_rule("bar", "lib/qux.ko", None).unwrap();
+    assert_eq!(rule.index, 1);
+    assert_eq!(
+        rule.to_string(),
+        "test.severities:2 'MODULE lib/qux.ko FAIL'"
+    );
+    assert_eq!(rules.find_rule("bar", "vmlinux", None), None);
+    assert_eq!(rules.len(), 2);
+}
+
 #[test]
 fn write_unused_rules() {
     // Check that unused rules are reported as such.
@@ -512,3 +911,224 @@ fn write_unused_rules() {
         )
     );
 }
+
+#[test]
+fn write_unmatched_module_rules() {
+    // Check that module rules which cannot match any module are reported.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo* PASS\n",
+            "MODULE lib/foo PASS\n",
+            "MODULE lib/bar* FAIL\n",
+            "[x86_64] MODULE lib/baz PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = rules.write_unmatched_module_rules_buffer(["lib/bar", "vmlinux"], &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        "test.severities:2: WARNING: Severity rule 'MODULE lib/foo PASS' does not match any module\n"
+    );
+}
+
+#[test]
+fn write_rules() {
+    // Check that rules are written with an explicit type and can be read back.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo* PASS\n",
+            "lib/test_module.ko FAIL # comment\n",
+            "NAMESPACE BAZ_NS PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = rules.write_buffer(&mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "SYMBOL foo* PASS\n",
+            "MODULE lib/test_module.ko FAIL\n",
+            "NAMESPACE BAZ_NS PASS\n", //
+        )
+    );
+    let mut rules2 = Rules::new();
+    let result = rules2.load_buffer("test.severities", &out[..]);
+    assert_ok!(result);
+    assert_eq!(rules, rules2);
+}
+
+#[test]
+fn push_generated_rules() {
+    // Check that generated rules are recorded as coming from a pseudo file.
+    let mut rules = Rules::new();
+    rules.push_generated(RuleType::Module, "vmlinux", Verdict::Pass);
+    rules.push_generated(RuleType::Symbol, "foo", Verdict::Pass);
+    assert_eq!(
+        rules,
+        Rules {
+            data: vec![
+                Rule::new(RuleType::Module, "vmlinux", Verdict::Pass, 0, 0),
+                Rule::new(RuleType::Symbol, "foo", Verdict::Pass, 0, 1),
+            ],
+            files: vec![PathBuf::from("<generated>")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
+        }
+    );
+    assert_eq!(
+        rules
+            .is_tolerated("foo", "lib/test_module.ko", None)
+            .unwrap()
+            .to_string(),
+        "<generated>:2 'foo PASS'"
+    );
+}
+
+#[test]
+fn read_include_like_symbol_rule() {
+    // Check that a classic rule for a symbol named "include" is not considered as an include
+    // directive.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "include PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        rules,
+        Rules {
+            data: vec![Rule::new(RuleType::Symbol, "include", Verdict::Pass, 0, 0)],
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
+        }
+    );
+}
+
+#[test]
+fn read_include_missing() {
+    // Check that an include directive referencing a missing file is rejected and no rules are
+    // added.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo PASS\n",
+            "include missing.severities\n", //
+        ),
+    );
+    assert!(matches!(result, Err(Error::IO { .. })));
+    assert_eq!(rules, Rules::new());
+}
+
+#[test]
+fn read_arch_guard() {
+    // Check that an architecture guard is recognized for both classic and explicit rules.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "[x86_64,aarch64] KVM PASS\n",
+            "[s390x] MODULE vmlinux FAIL\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        rules,
+        Rules {
+            data: vec![
+                Rule::new(RuleType::Namespace, "KVM", Verdict::Pass, 0, 0)
+                    .with_archs(vec!["x86_64".to_string(), "aarch64".to_string()]),
+                Rule::new(RuleType::Module, "vmlinux", Verdict::Fail, 0, 1)
+                    .with_archs(vec!["s390x".to_string()]),
+            ],
+            files: vec![PathBuf::from("test.severities")],
+            maybe_arch: None,
+            module_matching: ModuleMatching::default(),
+            keep_going: false,
+        }
+    );
+}
+
+#[test]
+fn read_invalid_arch_guard() {
+    // Check that a malformed architecture guard is rejected.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "[x86_64,] KVM PASS\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid architecture guard '[x86_64,]', must be in the form '[arch,...]'\n",
+            " test.severities:1:1\n",
+            " | [x86_64,] KVM PASS\n",
+            " | ^", //
+        )
+    );
+}
+
+#[test]
+fn tolerate_arch() {
+    // Check that guarded rules are considered only for a selected matching architecture and that
+    // a tolerating rule is described as written, including its guard.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "[x86_64,aarch64] KVM PASS\n",
+            "[ppc64le]  SYMBOL foo PASS # comment\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert!(
+        rules
+            .is_tolerated("kvm_foo", "vmlinux", Some("KVM"))
+            .is_none()
+    );
+    rules.set_arch("aarch64");
+    assert_eq!(
+        rules
+            .is_tolerated("kvm_foo", "vmlinux", Some("KVM"))
+            .map(|rule| rule.to_string()),
+        Some("test.severities:1 '[x86_64,aarch64] KVM PASS'".to_string())
+    );
+    assert!(rules.is_tolerated("foo", "vmlinux", None).is_none());
+    rules.set_arch("ppc64le");
+    assert!(
+        rules
+            .is_tolerated("kvm_foo", "vmlinux", Some("KVM"))
+            .is_none()
+    );
+    assert_eq!(
+        rules
+            .is_tolerated("foo", "vmlinux", None)
+            .map(|rule| rule.to_string()),
+        Some("test.severities:2 '[ppc64le] SYMBOL foo PASS'".to_string())
+    );
+    let mut out = Vec::new();
+    let result = rules.write_buffer(&mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "[x86_64,aarch64] NAMESPACE KVM PASS\n",
+            "[ppc64le] SYMBOL foo PASS\n", //
+        )
+    );
+}
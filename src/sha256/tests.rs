@@ -0,0 +1,47 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+
+/// Computes the hexadecimal SHA-256 digest of the given data.
+fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    to_hex(&hasher.finish())
+}
+
+#[test]
+fn digest_known_values() {
+    // Check the digests of several standard test messages.
+    assert_eq!(
+        sha256_hex(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(
+        sha256_hex(&[b'a'; 1000000]),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
+
+#[test]
+fn digest_incremental() {
+    // Check that feeding the data in pieces of various sizes gives the same digest as a single
+    // update.
+    let data = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let expected = sha256_hex(&data);
+    for piece_size in [1, 7, 63, 64, 65, 999] {
+        let mut hasher = Sha256::new();
+        for piece in data.chunks(piece_size) {
+            hasher.update(piece);
+        }
+        assert_eq!(to_hex(&hasher.finish()), expected);
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! A representation of acknowledged kABI differences and tools for working with the data.
+
+use crate::text::{matches_wildcard, read_lines};
+use crate::{Error, PathFile, debug};
+use std::fmt::{self, Display, Formatter};
+use std::io::prelude::*;
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// A kind of a difference between two corpuses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChangeKind {
+    /// An export has been added.
+    Added,
+    /// An export has been removed.
+    Removed,
+    /// The CRC of an export has changed.
+    Crc,
+    /// The type of an export has changed, for instance, from `EXPORT_SYMBOL` to
+    /// `EXPORT_SYMBOL_GPL`.
+    Type,
+    /// The namespace of an export has changed.
+    Namespace,
+    /// An export has moved to a different module.
+    Module,
+    /// The definition of a type in symtypes data has changed.
+    Definition,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Added => write!(f, "added"),
+            Self::Removed => write!(f, "removed"),
+            Self::Crc => write!(f, "crc"),
+            Self::Type => write!(f, "type"),
+            Self::Namespace => write!(f, "namespace"),
+            Self::Module => write!(f, "module"),
+            Self::Definition => write!(f, "definition"),
+        }
+    }
+}
+
+impl FromStr for ChangeKind {
+    type Err = ();
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "added" => Ok(Self::Added),
+            "removed" => Ok(Self::Removed),
+            "crc" => Ok(Self::Crc),
+            "type" => Ok(Self::Type),
+            "namespace" => Ok(Self::Namespace),
+            "module" => Ok(Self::Module),
+            "definition" => Ok(Self::Definition),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A single acknowledged difference.
+#[derive(Debug, Eq, PartialEq)]
+struct Suppression {
+    pattern: String,
+    kind: ChangeKind,
+    maybe_crcs: Option<(u32, u32)>, // The old and new CRC, only for `ChangeKind::Crc`.
+}
+
+/// A collection of acknowledged differences.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Suppressions {
+    data: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Creates a new empty `Suppressions` object.
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Loads suppressions data from the specified file.
+    ///
+    /// New suppressions are appended to the already present ones.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+
+        let file = PathFile::open(path).map_err(|err| {
+            Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+        })?;
+
+        self.load_buffer(path, file)
+    }
+
+    /// Loads suppressions data from the specified reader.
+    ///
+    /// The `path` should point to the suppressions file name, indicating the origin of the data.
+    /// New suppressions are appended to the already present ones.
+    pub fn load_buffer<P: AsRef<Path>, R: Read>(
+        &mut self,
+        path: P,
+        reader: R,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        debug!("Loading suppressions data from '{}'", path.display());
+
+        // Read all content from the file.
+        let lines = match read_lines(reader) {
+            Ok(lines) => lines,
+            Err(err) => return Err(Error::new_io("Failed to read suppressions data", err)),
+        };
+
+        // Parse all suppressions, committing them only if the whole file is valid.
+        let mut new_data = Vec::new();
+        for (line_idx, line) in lines.iter().enumerate() {
+            if let Some(suppression) = parse_suppression(path, line_idx, line)? {
+                new_data.push(suppression);
+            }
+        }
+        self.data.append(&mut new_data);
+
+        Ok(())
+    }
+
+    /// Checks if a change of the given kind is acknowledged for the specified symbol.
+    ///
+    /// The `maybe_crcs` provides the old and new CRC of the symbol if the change is of the
+    /// [`ChangeKind::Crc`] kind. A suppression that specifies CRCs matches only the exact CRC
+    /// change.
+    pub fn is_suppressed(
+        &self,
+        name: &str,
+        kind: ChangeKind,
+        maybe_crcs: Option<(u32, u32)>,
+    ) -> bool {
+        self.data.iter().any(|suppression| {
+            suppression.kind == kind
+                && matches_wildcard(name, &suppression.pattern)
+                && (suppression.maybe_crcs.is_none() || suppression.maybe_crcs == maybe_crcs)
+        })
+    }
+}
+
+/// Checks whether a change of the given kind is acknowledged by the optional suppressions.
+pub(crate) fn is_suppressed(
+    maybe_suppressions: Option<&Suppressions>,
+    name: &str,
+    kind: ChangeKind,
+    maybe_crcs: Option<(u32, u32)>,
+) -> bool {
+    maybe_suppressions
+        .is_some_and(|suppressions| suppressions.is_suppressed(name, kind, maybe_crcs))
+}
+
+/// Parses a single suppression from the specified line.
+///
+/// A suppression is in the form `<pattern> <kind> [<old-crc> <new-crc>]`. Empty lines and lines
+/// starting with '#' are ignored.
+fn parse_suppression(
+    path: &Path,
+    line_idx: usize,
+    line: &str,
+) -> Result<Option<Suppression>, Error> {
+    let trimmed = line.trim_ascii_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return Ok(None);
+    }
+
+    let words = trimmed.split_ascii_whitespace().collect::<Vec<_>>();
+    let (pattern, kind, maybe_crcs) = match words[..] {
+        [pattern, kind] => (pattern, kind, None),
+        [pattern, kind, old_crc, new_crc] => (pattern, kind, Some((old_crc, new_crc))),
+        _ => {
+            return Err(Error::new_parse_format(
+                "The suppression must be in the form '<pattern> <kind> [<old-crc> <new-crc>]'",
+                path,
+                line_idx + 1,
+                line,
+            ));
+        }
+    };
+
+    let kind = ChangeKind::from_str(kind).map_err(|_| {
+        Error::new_parse_format(
+            &format!("Invalid change kind '{}'", kind),
+            path,
+            line_idx + 1,
+            line,
+        )
+    })?;
+
+    let maybe_crcs = match maybe_crcs {
+        Some(_) if kind != ChangeKind::Crc => {
+            return Err(Error::new_parse_format(
+                &format!(
+                    "CRC values cannot be specified for the change kind '{}'",
+                    kind
+                ),
+                path,
+                line_idx + 1,
+                line,
+            ));
+        }
+        Some((old_crc, new_crc)) => {
+            let parse_crc = |crc: &str| {
+                crc.strip_prefix("0x")
+                    .or_else(|| crc.strip_prefix("0X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| {
+                        Error::new_parse_format(
+                            &format!("Invalid CRC value '{}'", crc),
+                            path,
+                            line_idx + 1,
+                            line,
+                        )
+                    })
+            };
+            Some((parse_crc(old_crc)?, parse_crc(new_crc)?))
+        }
+        None => None,
+    };
+
+    Ok(Some(Suppression {
+        pattern: pattern.to_string(),
+        kind,
+        maybe_crcs,
+    }))
+}
@@ -0,0 +1,130 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::{assert_ok, assert_parse_err, bytes};
+
+#[test]
+fn read_suppressions() {
+    // Check that suppressions of all forms are parsed, skipping empty and comment lines.
+    let mut suppressions = Suppressions::new();
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "# Reviewed changes\n",
+            "\n",
+            "foo removed\n",
+            "  bar crc 0x12345678 0x9abcdef0\n",
+            "s#baz definition\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        suppressions,
+        Suppressions {
+            data: vec![
+                Suppression {
+                    pattern: "foo".to_string(),
+                    kind: ChangeKind::Removed,
+                    maybe_crcs: None,
+                },
+                Suppression {
+                    pattern: "bar".to_string(),
+                    kind: ChangeKind::Crc,
+                    maybe_crcs: Some((0x12345678, 0x9abcdef0)),
+                },
+                Suppression {
+                    pattern: "s#baz".to_string(),
+                    kind: ChangeKind::Definition,
+                    maybe_crcs: None,
+                },
+            ]
+        }
+    );
+}
+
+#[test]
+fn read_invalid_suppressions() {
+    // Check that malformed suppressions are rejected and no data is retained.
+    let mut suppressions = Suppressions::new();
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "foo removed\n",
+            "bar crc 0x12345678\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "The suppression must be in the form '<pattern> <kind> [<old-crc> <new-crc>]'\n",
+            " test.suppressions:2\n",
+            " | bar crc 0x12345678", //
+        )
+    );
+    assert_eq!(suppressions, Suppressions::new());
+
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "foo changed\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid change kind 'changed'\n",
+            " test.suppressions:1\n",
+            " | foo changed", //
+        )
+    );
+
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "foo removed 0x12345678 0x9abcdef0\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "CRC values cannot be specified for the change kind 'removed'\n",
+            " test.suppressions:1\n",
+            " | foo removed 0x12345678 0x9abcdef0", //
+        )
+    );
+
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "foo crc 12345678 0x9abcdef0\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid CRC value '12345678'\n",
+            " test.suppressions:1\n",
+            " | foo crc 12345678 0x9abcdef0", //
+        )
+    );
+}
+
+#[test]
+fn is_suppressed_match() {
+    // Check that suppressions match by the pattern, kind and optionally the exact CRC change.
+    let mut suppressions = Suppressions::new();
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "foo* removed\n",
+            "bar crc 0x12345678 0x9abcdef0\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert!(suppressions.is_suppressed("foo_1", ChangeKind::Removed, None));
+    assert!(!suppressions.is_suppressed("foo_1", ChangeKind::Added, None));
+    assert!(!suppressions.is_suppressed("baz", ChangeKind::Removed, None));
+    assert!(suppressions.is_suppressed("bar", ChangeKind::Crc, Some((0x12345678, 0x9abcdef0))));
+    assert!(!suppressions.is_suppressed("bar", ChangeKind::Crc, Some((0x12345678, 0x23456789))));
+}
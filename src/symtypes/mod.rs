@@ -5,18 +5,33 @@
 
 use crate::burst;
 use crate::burst::JobSlots;
+use crate::crc32::Crc32;
+use crate::demangle::display_name;
+use crate::log::{self, Level};
+use crate::rules::{Rules, ToleratingRule, Verdict};
+use crate::suppressions::{ChangeKind, Suppressions, is_suppressed};
 use crate::text::{
-    DirectoryWriter, Filter, WriteGenerator, Writer, matches_filter, read_lines, unified_diff,
+    ColorMode, DiffOptions, DirectoryWriter, Filter, WriteGenerator, Writer, escape_html,
+    escape_json, is_tar_path, matches_filter, matches_wildcard, read_tar, side_by_side_diff,
+    unified_diff_with_options, write_compare_summary, write_html_diff, write_html_footer,
+    write_html_header,
 };
-use crate::{Error, MapIOErr, PathFile, debug, hash};
+use crate::warnings::{WarningsRef, WarningsSink};
+use crate::{CompareStatus, Error, MapIOErr, PathFile, debug, hash, strip_compression_ext};
+use std::cmp::Reverse;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
-use std::collections::{HashMap, HashSet};
-use std::io::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::io::{self, BufReader, prelude::*};
 use std::iter::{self, Peekable, zip};
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::{Arc, Mutex, RwLock};
-use std::{fs, mem};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::SystemTime;
+use std::{env, fs, mem, process};
 
 #[cfg(test)]
 mod tests;
@@ -33,32 +48,92 @@ mod tests_format;
 //     a nightly-only experimental API and so not used by the module.
 
 /// A token used in the description of a type.
+///
+/// The token data is interned in a global table of strings. Cloning a token or creating a new one
+/// with the same data doesn't allocate a new string. Strings that are no longer used by any token
+/// are released when the last `SymtypesCorpus` is dropped.
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
-enum Token {
-    TypeRef(String),
-    Atom(String),
+pub enum Token {
+    /// A reference to another type, for instance, `s#foo`.
+    TypeRef(Arc<str>),
+    /// Any other word of the type description.
+    Atom(Arc<str>),
 }
 
 impl Token {
     /// Creates a new `Token::TypeRef`.
-    fn new_typeref<S: Into<String>>(name: S) -> Self {
-        Self::TypeRef(name.into())
+    fn new_typeref<S: AsRef<str>>(name: S) -> Self {
+        Self::TypeRef(intern(name.as_ref()))
     }
 
     /// Creates a new `Token::Atom`.
-    fn new_atom<S: Into<String>>(name: S) -> Self {
-        Self::Atom(name.into())
+    fn new_atom<S: AsRef<str>>(name: S) -> Self {
+        Self::Atom(intern(name.as_ref()))
     }
 
     /// Returns the token data as a string slice.
-    fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         match self {
-            Self::TypeRef(ref_name) => ref_name.as_str(),
-            Self::Atom(word) => word.as_str(),
+            Self::TypeRef(ref_name) => ref_name,
+            Self::Atom(word) => word,
         }
     }
 }
 
+/// The number of shards in the `TOKEN_POOL`.
+const TOKEN_POOL_SHARDS: usize = 64;
+
+/// A pool of all token strings, indexed by `hash(string) % TOKEN_POOL_SHARDS`. Splitting the pool
+/// into shards allows each to be protected by a separate lock when reading symtypes data.
+///
+/// Symtypes data consists of a relatively small number of distinct words, such as "struct", "int"
+/// or "(", which are repeated many times. Sharing a single allocation for each of them
+/// significantly reduces the memory footprint of a loaded corpus. The strings are released by
+/// [`release_unused_tokens()`].
+static TOKEN_POOL: OnceLock<Vec<RwLock<HashSet<Arc<str>>>>> = OnceLock::new();
+
+/// The number of existing `SymtypesCorpus` instances. When it drops to zero, the strings in the
+/// `TOKEN_POOL` that are no longer referenced are released.
+static LIVE_CORPUSES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a shared copy of the given string from the `TOKEN_POOL`, adding it to the pool first if
+/// it is not present yet.
+fn intern(word: &str) -> Arc<str> {
+    let pool = TOKEN_POOL.get_or_init(|| {
+        iter::repeat_with(|| RwLock::new(HashSet::new()))
+            .take(TOKEN_POOL_SHARDS)
+            .collect()
+    });
+    let shard = &pool[(hash(word) % TOKEN_POOL_SHARDS as u64) as usize];
+
+    if let Some(interned) = shard.read().unwrap().get(word) {
+        return Arc::clone(interned);
+    }
+
+    // Search again as the word could have been added by another thread in the meantime, see [2].
+    let mut shard = shard.write().unwrap();
+    if let Some(interned) = shard.get(word) {
+        return Arc::clone(interned);
+    }
+    let interned: Arc<str> = Arc::from(word);
+    shard.insert(Arc::clone(&interned));
+    interned
+}
+
+/// Removes all strings from the `TOKEN_POOL` that are not used by any token.
+fn release_unused_tokens() {
+    let Some(pool) = TOKEN_POOL.get() else {
+        return;
+    };
+    for shard in pool {
+        let mut shard = shard.write().unwrap();
+        // A string referenced only by the pool cannot be obtained by anyone else while the shard
+        // is locked for writing.
+        shard.retain(|interned| Arc::strong_count(interned) > 1);
+        shard.shrink_to_fit();
+    }
+}
+
 /// A sequence of tokens, describing one type.
 type Tokens = Vec<Token>;
 
@@ -71,6 +146,10 @@ type Types = HashMap<String, TypeVariants>;
 /// The size of the `TypeBuckets` collection.
 const TYPE_BUCKETS_SIZE: usize = 256;
 
+/// The minimum similarity of two symtypes files, in percent, for which
+/// [`SymtypesCorpus::find_moved_files()`] considers one to be moved to the other.
+const MOVE_SIMILARITY_THRESHOLD: usize = 50;
+
 /// A collection of `Types`, indexed by `type_bucket_idx(type_name)`. This allows each bucket to be
 /// protected by a separate lock when reading symtypes data.
 type TypeBuckets = Vec<Types>;
@@ -83,11 +162,53 @@ fn type_bucket_idx(type_name: &str) -> usize {
 /// A mapping from a type name to `Tokens`, specifying the type in a given file.
 type FileRecords = HashMap<String, Arc<Tokens>>;
 
+/// A mapping from an export name to the hash of its type closure in a given file, see
+/// [`closure_hashes()`].
+type ExportHashes = HashMap<String, u64>;
+
+/// A mapping from a metadata key, such as `object`, `arch` or `flavor`, to its value, describing
+/// the origin of a symtypes file.
+pub type FileMetadata = BTreeMap<String, String>;
+
 /// A representation of a single symtypes file.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct SymtypesFile {
     path: PathBuf,
     records: FileRecords,
+    export_hashes: ExportHashes,
+    metadata: FileMetadata,
+}
+
+impl SymtypesFile {
+    /// Creates a new `SymtypesFile` with the given path and records, computing the closure hashes
+    /// of its exports.
+    fn new(path: PathBuf, records: FileRecords) -> Self {
+        let export_hashes = closure_hashes(&records);
+        Self {
+            path,
+            records,
+            export_hashes,
+            metadata: FileMetadata::new(),
+        }
+    }
+
+    /// Sets the metadata of the file.
+    fn with_metadata(mut self, metadata: FileMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Returns whether the given export is known to have the same type closure in this file as in
+    /// another file, based on the closure hashes.
+    ///
+    /// A `false` result doesn't mean that the closures differ, only that a full comparison is
+    /// needed.
+    fn has_same_closure(&self, other: &SymtypesFile, name: &str) -> bool {
+        match (self.export_hashes.get(name), other.export_hashes.get(name)) {
+            (Some(hash), Some(other_hash)) => hash == other_hash,
+            _ => false,
+        }
+    }
 }
 
 /// A collection of symtypes files, which also provides fast lookup by a symtypes path.
@@ -179,11 +300,88 @@ type Exports = HashMap<String, Arc<SymtypesFile>>;
 /// limit memory needed to store the corpus. On the other hand, when comparing two `Tokens` vectors
 /// for ABI equality, the code needs to consider whether all referenced subtypes are actually equal
 /// as well.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug)]
 pub struct SymtypesCorpus {
     types: TypeBuckets,
     files: SymtypesFiles,
     exports: Exports,
+
+    /// Whether all errors in loaded files are collected, instead of stopping at the first one.
+    keep_going: bool,
+
+    /// How exports defined in multiple loaded files are resolved.
+    duplicate_exports: DuplicateExportPolicy,
+
+    /// Whether Rust export names are demangled in human-readable comparison reports.
+    demangle: bool,
+
+    /// When the pretty and short comparison reports are colorized.
+    color: ColorMode,
+
+    /// Whether comparison reports suggest kABI workarounds for changed types.
+    suggest_fixup: bool,
+
+    /// Whether types are compared by their exact tokens, without normalizing cosmetic differences.
+    strict: bool,
+
+    /// A header prepended to the pretty and short comparison reports.
+    maybe_output_header: Option<String>,
+}
+
+impl PartialEq for SymtypesCorpus {
+    /// Compares two corpuses by their data, ignoring their loading options.
+    fn eq(&self, other: &Self) -> bool {
+        self.types == other.types && self.files == other.files && self.exports == other.exports
+    }
+}
+
+impl Eq for SymtypesCorpus {}
+
+impl Drop for SymtypesCorpus {
+    fn drop(&mut self) {
+        if LIVE_CORPUSES.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Release the tokens of this corpus first.
+            self.types.clear();
+            self.files.clear();
+            self.exports.clear();
+            release_unused_tokens();
+        }
+    }
+}
+
+/// A read-only symtypes corpus that can be shared between threads, for instance, by a long-running
+/// service which loads the data once and answers many requests concurrently.
+///
+/// Cloning the object is cheap and all clones refer to the same data. The corpus is accessed
+/// through [`Deref`], which provides all read-only operations of [`SymtypesCorpus`], such as
+/// [`SymtypesCorpus::compare()`]. No locking is involved, because the data can no longer be
+/// modified. Loading options, such as [`SymtypesCorpus::set_demangle()`], need to be set before
+/// the corpus is shared.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SharedSymtypesCorpus(Arc<SymtypesCorpus>);
+
+impl SharedSymtypesCorpus {
+    /// Creates a new shared corpus from a fully loaded one.
+    ///
+    /// The corpus is optimized first, see [`SymtypesCorpus::optimize()`].
+    pub fn new(mut symtypes: SymtypesCorpus) -> Self {
+        symtypes.optimize();
+        Self(Arc::new(symtypes))
+    }
+}
+
+impl From<SymtypesCorpus> for SharedSymtypesCorpus {
+    fn from(symtypes: SymtypesCorpus) -> Self {
+        Self::new(symtypes)
+    }
+}
+
+impl Deref for SharedSymtypesCorpus {
+    type Target = SymtypesCorpus;
+
+    fn deref(&self) -> &SymtypesCorpus {
+        &self.0
+    }
 }
 
 /// An identifier indicating what kind of symtypes data is expected to be loaded.
@@ -207,13 +405,41 @@ enum LoadKind {
 /// and new data when inserting new records.
 struct LoadContext<'a> {
     load_kind: LoadKind,
+    keep_going: bool,
+    duplicate_exports: DuplicateExportPolicy,
     symtypes: &'a SymtypesCorpus,
     new_types: Vec<RwLock<Types>>,
     new_exports: Mutex<Exports>,
     new_files: Mutex<SymtypesFiles>,
-    warnings: Mutex<Box<dyn Write + Send + 'a>>,
+    warnings: Mutex<Box<dyn WarningsSink + Send + 'a>>,
+    merge_caches: Mutex<Vec<MergeCache>>,
+}
+
+/// Type variants already merged by a worker during the loading, providing for each type name the
+/// reference-counted pointers of its variants in the newly loaded data.
+///
+/// Each worker looks up types in its own cache first, which avoids taking the locks of the shared
+/// `LoadContext::new_types` buckets for types repeated in many files.
+type MergeCache = HashMap<String, Vec<Arc<Tokens>>>;
+
+/// A [`MergeCache`] borrowed from a [`LoadContext`] by a worker, which is returned back to the
+/// context when dropped.
+struct MergeCacheGuard<'a, 'b> {
+    load_context: &'a LoadContext<'b>,
+    cache: MergeCache,
+}
+
+impl Drop for MergeCacheGuard<'_, '_> {
+    fn drop(&mut self) {
+        let cache = mem::take(&mut self.cache);
+        self.load_context.merge_caches.lock().unwrap().push(cache);
+    }
 }
 
+/// A symtypes file to be loaded from one of multiple directories, specified by the directory, its
+/// path relative to the directory, and the path under which it is recorded.
+type SplitWork<'a> = (&'a Path, PathBuf, PathBuf);
+
 /// Type names active during the loading of a specific file, providing for each type its tokens and
 /// source line index.
 type LoadActiveTypes = HashMap<String, (Arc<Tokens>, usize)>;
@@ -222,18 +448,201 @@ type LoadActiveTypes = HashMap<String, (Arc<Tokens>, usize)>;
 /// its new tokens, along with a [`Vec`] of exported symbols affected by the change.
 type CompareChangedTypes<'a> = HashMap<(&'a str, &'a Tokens, &'a Tokens), Vec<&'a str>>;
 
+/// A changed type, given by its name, old tokens and new tokens, along with the exported symbols
+/// affected by the change.
+type ChangedType<'a> = ((&'a str, &'a Tokens, &'a Tokens), Vec<&'a str>);
+
+/// All differences between two corpuses, as found by [`SymtypesCorpus::find_changes()`].
+type CompareChanges<'a> = (Vec<&'a str>, Vec<&'a str>, Vec<ChangedType<'a>>);
+
 /// Type names processed during the comparison for a specific file.
 type CompareFileTypes<'a> = HashSet<&'a str>;
 
+/// A classification of how the definition of a type changed, for triaging the impact of the change.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LayoutChange {
+    /// New members are appended at the end.
+    MemberAppended,
+    /// Some members or enum constants are removed.
+    MemberRemoved,
+    /// The members or enum constants are reordered.
+    MemberReordered,
+    /// The types of some members changed, while their names stayed the same.
+    MemberTypeChanged,
+    /// Only attributes, such as `__attribute__((aligned(8)))`, changed.
+    AttributeChanged,
+    /// New enum constants are appended at the end, the values of the original ones are preserved.
+    EnumeratorAppended,
+    /// New enum constants are inserted in the middle.
+    EnumeratorInserted,
+    /// The enum constants are the same, but their values changed.
+    EnumeratorRenumbered,
+    /// The members or enum constants changed in more than one way, for instance, some were added
+    /// and others removed.
+    Mixed,
+}
+
+impl LayoutChange {
+    /// Classifies a change of the type `name` from `tokens` to `other_tokens`.
+    ///
+    /// A change of members or enum constants that doesn't match any specific class is classified as
+    /// [`LayoutChange::Mixed`]. Returns `None` if the type is not a struct, union or enum with
+    /// a known body in both definitions, if its kind or name changed, or if the change is only
+    /// cosmetic.
+    pub fn classify(name: &str, tokens: &Tokens, other_tokens: &Tokens) -> Option<Self> {
+        if is_unknown_declaration(name, tokens) || is_unknown_declaration(name, other_tokens) {
+            return None;
+        }
+        if tokens != other_tokens && strip_attributes(tokens) == strip_attributes(other_tokens) {
+            return Some(Self::AttributeChanged);
+        }
+        if tokens.get(..2) != other_tokens.get(..2) {
+            return None;
+        }
+
+        if let Some(layout_change) = classify_enumerators(tokens, other_tokens) {
+            return Some(layout_change);
+        }
+
+        let members = split_members(tokens)?;
+        let other_members = split_members(other_tokens)?;
+        if other_members.len() > members.len() && other_members.starts_with(&members) {
+            return Some(Self::MemberAppended);
+        }
+        if other_members.len() < members.len() && is_subsequence(&other_members, &members) {
+            return Some(Self::MemberRemoved);
+        }
+        if members != other_members {
+            let mut sorted = members.clone();
+            let mut other_sorted = other_members.clone();
+            sorted.sort();
+            other_sorted.sort();
+            if sorted == other_sorted {
+                return Some(Self::MemberReordered);
+            }
+        }
+        if tokens[0].as_str() != "enum"
+            && members.len() == other_members.len()
+            && zip(&members, &other_members).all(|(member, other_member)| {
+                member == other_member
+                    || member_name(member)
+                        .is_some_and(|name| member_name(other_member) == Some(name))
+            })
+        {
+            return Some(Self::MemberTypeChanged);
+        }
+
+        // Cosmetic differences, reported only in the strict mode, don't change the layout.
+        (members != other_members && normalize_tokens(tokens) != normalize_tokens(other_tokens))
+            .then_some(Self::Mixed)
+    }
+
+    /// Returns whether the change is probably safe for the kABI, as opposed to definitely
+    /// breaking it.
+    ///
+    /// Appended members are safe only if the type is allocated solely by its owner, and attributes
+    /// can affect the alignment, so the change still needs a review.
+    pub fn is_probably_safe(self) -> bool {
+        matches!(
+            self,
+            Self::MemberAppended | Self::AttributeChanged | Self::EnumeratorAppended
+        )
+    }
+}
+
+impl Display for LayoutChange {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::MemberAppended => write!(f, "member appended"),
+            Self::MemberRemoved => write!(f, "member removed"),
+            Self::MemberReordered => write!(f, "member reordered"),
+            Self::MemberTypeChanged => write!(f, "member type changed"),
+            Self::AttributeChanged => write!(f, "attribute changed"),
+            Self::EnumeratorAppended => write!(f, "enumerator appended at tail"),
+            Self::EnumeratorInserted => write!(f, "enumerator inserted in the middle"),
+            Self::EnumeratorRenumbered => write!(f, "enumerator renumbered"),
+            Self::Mixed => write!(f, "mixed changes"),
+        }
+    }
+}
+
+/// A change of a type definition between two corpuses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeChange {
+    /// The name of the type, for instance, `s#foo`.
+    pub name: String,
+    /// The tokens describing the type in the original corpus.
+    pub old_tokens: Vec<Token>,
+    /// The tokens describing the type in the other corpus.
+    pub new_tokens: Vec<Token>,
+    /// Sorted names of all exports affected by the change.
+    pub exports: Vec<String>,
+}
+
+impl TypeChange {
+    /// Writes a description of the change, followed by a diff of the type definitions formatted
+    /// according to `diff_options`, to the provided output stream.
+    pub fn write_diff<W: Write>(&self, diff_options: &DiffOptions, writer: W) -> Result<(), Error> {
+        write_type_change(
+            &self.name,
+            &self.old_tokens,
+            &self.new_tokens,
+            None,
+            diff_options,
+            false,
+            writer,
+        )
+    }
+
+    /// Returns the classification of the change, if it matches any known class.
+    pub fn layout_change(&self) -> Option<LayoutChange> {
+        LayoutChange::classify(&self.name, &self.old_tokens, &self.new_tokens)
+    }
+}
+
+/// The result of [`SymtypesCorpus::compare()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ComparisonResult {
+    /// Sorted names of exports present only in the other corpus.
+    pub added_exports: Vec<String>,
+    /// Sorted names of exports present only in the original corpus.
+    pub removed_exports: Vec<String>,
+    /// All changed types, sorted by their name and tokens.
+    pub changed_types: Vec<TypeChange>,
+}
+
+impl ComparisonResult {
+    /// Returns whether the compared corpuses are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added_exports.is_empty()
+            && self.removed_exports.is_empty()
+            && self.changed_types.is_empty()
+    }
+
+    /// Returns sorted and deduplicated names of all exports affected by any type change.
+    pub fn modified_exports(&self) -> Vec<&str> {
+        let mut exports = self
+            .changed_types
+            .iter()
+            .flat_map(|change| change.exports.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        exports.sort();
+        exports.dedup();
+        exports
+    }
+}
+
 impl<'a> LoadContext<'a> {
     /// Creates a new load context from a symtypes corpus and a warnings stream.
-    fn from<W: Write + Send + 'a>(
+    fn from<W: WarningsSink + Send + 'a>(
         symtypes: &'a SymtypesCorpus,
         load_kind: LoadKind,
         warnings: W,
     ) -> Self {
         Self {
             load_kind,
+            keep_going: symtypes.keep_going,
+            duplicate_exports: symtypes.duplicate_exports,
             symtypes,
             new_types: iter::repeat_with(|| RwLock::new(Types::new()))
                 .take(TYPE_BUCKETS_SIZE)
@@ -241,6 +650,19 @@ impl<'a> LoadContext<'a> {
             new_exports: Mutex::new(Exports::new()),
             new_files: Mutex::new(SymtypesFiles::new()),
             warnings: Mutex::new(Box::new(warnings)),
+            merge_caches: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Borrows a [`MergeCache`] for a worker, reusing one returned by a previous worker if
+    /// available.
+    ///
+    /// A cache is taken for a whole file so that the pool lock is not contended per record.
+    fn merge_cache(&self) -> MergeCacheGuard<'_, 'a> {
+        let cache = self.merge_caches.lock().unwrap().pop().unwrap_or_default();
+        MergeCacheGuard {
+            load_context: self,
+            cache,
         }
     }
 
@@ -255,6 +677,55 @@ impl<'a> LoadContext<'a> {
             self.new_files.into_inner().unwrap(),
         )
     }
+
+    /// Handles an error found while loading. In the keep-going mode, the error is recorded in
+    /// `errors` and `Ok` is returned so that the loading can continue. Otherwise, the error is
+    /// returned.
+    fn handle_error(&self, err: Error, errors: &mut Vec<Error>) -> Result<(), Error> {
+        if self.keep_going {
+            errors.push(err);
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Invokes the specified load function for each work in parallel.
+    ///
+    /// In the keep-going mode, the errors of all failed works are collected and returned together
+    /// in one [`Error::Multiple`], ordered by the work index. Otherwise, the first error stops the
+    /// operation.
+    fn run_jobs<F: Fn(usize) -> Result<(), Error> + Send + Sync>(
+        &self,
+        process_fun: F,
+        num_works: usize,
+        job_slots: &mut JobSlots,
+    ) -> Result<(), Error> {
+        if !self.keep_going {
+            return burst::run_jobs(process_fun, num_works, job_slots);
+        }
+
+        let errors = Mutex::new(Vec::new());
+        burst::run_jobs(
+            |work_idx| {
+                if let Err(err) = process_fun(work_idx) {
+                    errors.lock().unwrap().push((work_idx, err));
+                }
+                Ok(())
+            },
+            num_works,
+            job_slots,
+        )?;
+
+        let mut errors = errors.into_inner().unwrap();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        errors.sort_by_key(|&(work_idx, _)| work_idx);
+        Err(Error::new_multiple(
+            errors.into_iter().map(|(_, err)| err).collect(),
+        ))
+    }
 }
 
 /// The format of the output from [`SymtypesCorpus::compare_with()`].
@@ -266,10 +737,18 @@ pub enum CompareFormat {
     Pretty,
     /// Compact human-readable output.
     Short,
+    /// Verbose human-readable output with changed types shown side by side.
+    SideBySide,
+    /// A standalone HTML report.
+    Html,
     /// A list of all added, removed, or modified symbols.
     Symbols,
     /// A list of all modified symbols only.
     ModSymbols,
+    /// A single line with the numbers of breaking, tolerated and unchanged exports.
+    Summary,
+    /// A JSON object describing all added and removed exports and changed types.
+    Json,
 }
 
 impl FromStr for CompareFormat {
@@ -281,8 +760,12 @@ impl FromStr for CompareFormat {
             "null" => Ok(Self::Null),
             "pretty" => Ok(Self::Pretty),
             "short" => Ok(Self::Short),
+            "side-by-side" => Ok(Self::SideBySide),
+            "html" => Ok(Self::Html),
             "symbols" => Ok(Self::Symbols),
             "mod-symbols" => Ok(Self::ModSymbols),
+            "summary" => Ok(Self::Summary),
+            "json" => Ok(Self::Json),
             _ => Err(Self::Err::new_parse(format!(
                 "Unrecognized format '{}'",
                 format
@@ -291,6 +774,166 @@ impl FromStr for CompareFormat {
     }
 }
 
+/// The order of files and records in the output from [`SymtypesCorpus::write_consolidated()`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConsolidateOrder {
+    /// Files sorted by their path, with types in each file sorted by name, followed by exports
+    /// sorted by name.
+    #[default]
+    Path,
+    /// Files sorted by their path, with all records in each file sorted by name.
+    Symbol,
+    /// The order produced by the old modversions script. Files are sorted by their path without
+    /// the extension, exports in each file are sorted by name and each type is written right before
+    /// its first user. Full records end with a trailing space and the output ends with an empty
+    /// line.
+    OldScript,
+}
+
+impl FromStr for ConsolidateOrder {
+    type Err = Error;
+
+    /// Obtains a [`ConsolidateOrder`] matching the given order type, specified as a string.
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        match order {
+            "path" => Ok(Self::Path),
+            "symbol" => Ok(Self::Symbol),
+            "old-script" => Ok(Self::OldScript),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized order '{}'",
+                order
+            ))),
+        }
+    }
+}
+
+/// A method of grouping the exports affected by a changed type in the human-readable output from
+/// [`SymtypesCorpus::compare_with()`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExportGrouping {
+    /// List the exports as a single flat list.
+    #[default]
+    None,
+    /// Group the exports by the directory of their symtypes file, which approximates the module.
+    Module,
+    /// Group the exports by their symtypes file.
+    File,
+}
+
+impl FromStr for ExportGrouping {
+    type Err = Error;
+
+    /// Obtains an [`ExportGrouping`] matching the given grouping name, specified as a string.
+    fn from_str(grouping: &str) -> Result<Self, Self::Err> {
+        match grouping {
+            "none" => Ok(Self::None),
+            "module" => Ok(Self::Module),
+            "file" => Ok(Self::File),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized grouping '{}'",
+                grouping
+            ))),
+        }
+    }
+}
+
+/// A policy for handling the same symtypes file path provided by multiple inputs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DuplicatePolicy {
+    /// Reject the duplicate file.
+    Error,
+    /// Keep the file from the first input that provides it.
+    First,
+    /// Keep the file from the last input that provides it.
+    Last,
+    /// Keep all files, recording each duplicate under a path suffixed with the number of its input.
+    Rename,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = Error;
+
+    /// Obtains a [`DuplicatePolicy`] matching the given policy name, specified as a string.
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "error" => Ok(Self::Error),
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            "rename" => Ok(Self::Rename),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized duplicate policy '{}'",
+                policy
+            ))),
+        }
+    }
+}
+
+/// A policy for resolving an export defined in multiple symtypes files.
+///
+/// The occurrences are ordered by the paths of their files. Exports already present in a corpus
+/// precede those defined by newly loaded data.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DuplicateExportPolicy {
+    /// Keep the first occurrence.
+    #[default]
+    First,
+    /// Keep the last occurrence.
+    Last,
+    /// Reject the duplicate export.
+    Error,
+    /// Keep the first occurrence that is not part of an auxiliary component built separately from
+    /// vmlinux and modules, such as the boot code, purgatory, vDSO or EFI stub. If all occurrences
+    /// are in such components, keep the first one.
+    PreferVmlinux,
+}
+
+impl FromStr for DuplicateExportPolicy {
+    type Err = Error;
+
+    /// Obtains a [`DuplicateExportPolicy`] matching the given policy name, specified as a string.
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            "error" => Ok(Self::Error),
+            "prefer-vmlinux" => Ok(Self::PreferVmlinux),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized duplicate export policy '{}'",
+                policy
+            ))),
+        }
+    }
+}
+
+impl Display for DuplicateExportPolicy {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::First => write!(f, "first"),
+            Self::Last => write!(f, "last"),
+            Self::Error => write!(f, "error"),
+            Self::PreferVmlinux => write!(f, "prefer-vmlinux"),
+        }
+    }
+}
+
+impl DuplicateExportPolicy {
+    /// Decides whether an export defined in the file `path` should replace its occurrence in the
+    /// file `other_path`. The `is_other_loaded` flag indicates whether the other occurrence was
+    /// already present in the corpus before the current load.
+    fn prefers(self, path: &Path, other_path: &Path, is_other_loaded: bool) -> bool {
+        let is_first = !is_other_loaded && path < other_path;
+        match self {
+            Self::First | Self::Error => is_first,
+            Self::Last => !is_first,
+            Self::PreferVmlinux => match (is_auxiliary_path(path), is_auxiliary_path(other_path)) {
+                (false, true) => true,
+                (true, false) => false,
+                _ => is_first,
+            },
+        }
+    }
+}
+
 impl Default for SymtypesCorpus {
     fn default() -> Self {
         Self::new()
@@ -300,18 +943,86 @@ impl Default for SymtypesCorpus {
 impl SymtypesCorpus {
     /// Creates a new empty corpus.
     pub fn new() -> Self {
+        LIVE_CORPUSES.fetch_add(1, Ordering::AcqRel);
         Self {
             types: vec![Types::new(); TYPE_BUCKETS_SIZE],
             files: SymtypesFiles::new(),
             exports: Exports::new(),
+            keep_going: false,
+            duplicate_exports: DuplicateExportPolicy::First,
+            demangle: false,
+            color: ColorMode::Never,
+            suggest_fixup: false,
+            strict: false,
+            maybe_output_header: None,
         }
     }
 
+    /// Enables or disables the keep-going mode for subsequently loaded symtypes data.
+    ///
+    /// In the keep-going mode, an invalid record doesn't stop the loading. Instead, all errors in
+    /// all loaded files are collected and reported together in one [`Error::Multiple`]. The
+    /// corpus remains unchanged if any error is found.
+    pub fn set_keep_going(&mut self, keep_going: bool) {
+        self.keep_going = keep_going;
+    }
+
+    /// Sets how exports defined in multiple subsequently loaded symtypes files are resolved.
+    ///
+    /// Each duplicate export is reported as a warning and the policy selects which occurrence is
+    /// kept, or the duplicate is rejected as an error with [`DuplicateExportPolicy::Error`].
+    pub fn set_duplicate_exports(&mut self, policy: DuplicateExportPolicy) {
+        self.duplicate_exports = policy;
+    }
+
+    /// Enables or disables the demangling of Rust export names in comparison reports.
+    ///
+    /// Names are demangled only in the human-readable formats. Symbol lists always contain the
+    /// raw names.
+    pub fn set_demangle(&mut self, demangle: bool) {
+        self.demangle = demangle;
+    }
+
+    /// Sets when the pretty and short comparison reports written to a file are colorized.
+    ///
+    /// The reports are not colorized by default.
+    pub fn set_color(&mut self, color: ColorMode) {
+        self.color = color;
+    }
+
+    /// Enables or disables experimental suggestions of kABI workarounds in comparison reports.
+    ///
+    /// If enabled, each changed type in the human-readable text formats is followed by a skeleton
+    /// of a possible workaround, derived from how the members of the type changed.
+    pub fn set_suggest_fixup(&mut self, suggest_fixup: bool) {
+        self.suggest_fixup = suggest_fixup;
+    }
+
+    /// Enables or disables the strict comparison of types.
+    ///
+    /// By default, the tokens of each type are normalized before they are compared, so cosmetic
+    /// differences produced by some genksyms versions, such as punctuation glued to a word or
+    /// a different order of attributes, are not reported as changes. The strict mode compares the
+    /// raw tokens.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Sets a header prepended to the pretty and short comparison reports written to a file.
+    ///
+    /// The header is written as is, before any changes are reported. No header is written by
+    /// default.
+    pub fn set_output_header(&mut self, maybe_output_header: Option<String>) {
+        self.maybe_output_header = maybe_output_header;
+    }
+
     /// Loads symtypes data from the specified location.
     ///
-    /// The `path` can point to a single symtypes file or a directory. In the latter case, the
-    /// function recursively collects all symtypes in that directory and loads them.
-    pub fn load<P: AsRef<Path>, W: Write + Send>(
+    /// The `path` can point to a single symtypes file, a directory or a tar archive. In the case of
+    /// a directory, the function recursively collects all symtypes in that directory and loads
+    /// them. An archive is recognized by its `.tar` extension and is loaded as split data. The
+    /// special path "-" reads a single symtypes file from the standard input.
+    pub fn load<P: AsRef<Path>, W: WarningsSink + Send>(
         &mut self,
         path: P,
         warnings: W,
@@ -319,7 +1030,11 @@ impl SymtypesCorpus {
     ) -> Result<(), Error> {
         let path = path.as_ref();
 
-        // Determine if the input is a directory tree or a single symtypes file.
+        if path == Path::new("-") {
+            return self.load_reader(path, io::stdin().lock(), LoadKind::Any, warnings);
+        }
+
+        // Determine if the input is a directory tree, an archive or a single symtypes file.
         let md = fs::metadata(path).map_err(|err| {
             Error::new_io(
                 format!("Failed to query the path '{}'", path.display()),
@@ -331,6 +1046,7 @@ impl SymtypesCorpus {
             // Recursively collect symtypes files within the directory.
             let mut symfiles = Vec::new();
             Self::collect_symfiles(path, Path::new(""), &mut symfiles)?;
+            symfiles.sort();
 
             // Load all found files.
             self.load_symfiles(
@@ -340,6 +1056,8 @@ impl SymtypesCorpus {
                 warnings,
                 job_slots,
             )
+        } else if is_tar_path(path) {
+            self.load_tar(path, warnings, job_slots)
         } else {
             // Load the single file.
             self.load_symfiles(Path::new(""), &[path], LoadKind::Any, warnings, job_slots)
@@ -347,7 +1065,9 @@ impl SymtypesCorpus {
     }
 
     /// Loads consolidated symtypes data from the specified file.
-    pub fn load_consolidated<P: AsRef<Path>, W: Write + Send>(
+    ///
+    /// The special path "-" reads the data from the standard input.
+    pub fn load_consolidated<P: AsRef<Path>, W: WarningsSink + Send>(
         &mut self,
         path: P,
         warnings: W,
@@ -355,6 +1075,10 @@ impl SymtypesCorpus {
     ) -> Result<(), Error> {
         let path = path.as_ref();
 
+        if path == Path::new("-") {
+            return self.load_reader(path, io::stdin().lock(), LoadKind::Consolidated, warnings);
+        }
+
         // Load the single file.
         self.load_symfiles(
             Path::new(""),
@@ -366,7 +1090,10 @@ impl SymtypesCorpus {
     }
 
     /// Loads split symtypes data from the specified directory.
-    pub fn load_split<P: AsRef<Path>, W: Write + Send>(
+    ///
+    /// If the `path` is not a directory but has the `.tar` extension, the data is loaded from the
+    /// tar archive instead.
+    pub fn load_split<P: AsRef<Path>, W: WarningsSink + Send>(
         &mut self,
         path: P,
         warnings: W,
@@ -374,9 +1101,14 @@ impl SymtypesCorpus {
     ) -> Result<(), Error> {
         let path = path.as_ref();
 
+        if is_tar_path(path) && !path.is_dir() {
+            return self.load_tar(path, warnings, job_slots);
+        }
+
         // Recursively collect symtypes files within the directory.
         let mut symfiles = Vec::new();
         Self::collect_symfiles(path, Path::new(""), &mut symfiles)?;
+        symfiles.sort();
 
         // Load all found files.
         self.load_symfiles(
@@ -388,6 +1120,99 @@ impl SymtypesCorpus {
         )
     }
 
+    /// Loads split symtypes data from multiple directories.
+    ///
+    /// Symtypes files are identified by their path relative to their directory. The `on_duplicate`
+    /// policy determines what happens if multiple directories contain a file with the same
+    /// relative path. With [`DuplicatePolicy::Rename`], the duplicate from the N-th directory,
+    /// counting from 1, is recorded with the `.N` suffix inserted before its extension, for
+    /// instance, `fs/foo.2.symtypes`.
+    pub fn load_split_multiple<P: AsRef<Path>, W: WarningsSink + Send>(
+        &mut self,
+        paths: &[P],
+        on_duplicate: DuplicatePolicy,
+        warnings: W,
+        job_slots: &mut JobSlots,
+    ) -> Result<(), Error> {
+        let works = Self::collect_split_works(paths, on_duplicate)?;
+
+        // Load all selected files.
+        let load_context = LoadContext::from(self, LoadKind::Simple, warnings);
+
+        load_context.run_jobs(
+            |work_idx| {
+                let (root, sub_path, record_sub_path) = &works[work_idx];
+                Self::load_symfile_as(root, sub_path, record_sub_path, &load_context)
+            },
+            works.len(),
+            job_slots,
+        )?;
+
+        let (new_types, new_exports, new_files) = load_context.into_inner();
+        self.merge_new(new_types, new_exports, new_files);
+
+        Ok(())
+    }
+
+    /// Collects symtypes files within multiple directories, deciding how each one is recorded
+    /// according to the `on_duplicate` policy, see [`SymtypesCorpus::load_split_multiple()`].
+    ///
+    /// Returns, for each selected file, its directory, its path relative to the directory and the
+    /// path under which it is recorded.
+    fn collect_split_works<P: AsRef<Path>>(
+        paths: &[P],
+        on_duplicate: DuplicatePolicy,
+    ) -> Result<Vec<SplitWork<'_>>, Error> {
+        let mut works: Vec<SplitWork> = Vec::new();
+        let mut recorded = HashMap::<PathBuf, usize>::new();
+        for (input_idx, path) in paths.iter().enumerate() {
+            let path = path.as_ref();
+
+            let mut symfiles = Vec::new();
+            Self::collect_symfiles(path, Path::new(""), &mut symfiles)?;
+            symfiles.sort();
+
+            for sub_path in symfiles {
+                let maybe_plain_sub_path = strip_compression_ext(&sub_path);
+                let plain_sub_path = maybe_plain_sub_path.unwrap_or_else(|| sub_path.clone());
+
+                let Some(&work_idx) = recorded.get(&plain_sub_path) else {
+                    recorded.insert(plain_sub_path.clone(), works.len());
+                    works.push((path, sub_path, plain_sub_path));
+                    continue;
+                };
+
+                match on_duplicate {
+                    DuplicatePolicy::Error => {
+                        return Err(Error::new_parse(format!(
+                            "Duplicate file path '{}' found in '{}' and '{}'",
+                            plain_sub_path.display(),
+                            works[work_idx].0.display(),
+                            path.display()
+                        )));
+                    }
+                    DuplicatePolicy::First => {}
+                    DuplicatePolicy::Last => works[work_idx] = (path, sub_path, plain_sub_path),
+                    DuplicatePolicy::Rename => {
+                        let mut renamed_sub_path = plain_sub_path.clone();
+                        renamed_sub_path.set_extension(format!("{}.symtypes", input_idx + 1));
+                        if recorded.contains_key(&renamed_sub_path) {
+                            return Err(Error::new_parse(format!(
+                                "Duplicate file path '{}' cannot be renamed to the existing path '{}'",
+                                plain_sub_path.display(),
+                                renamed_sub_path.display()
+                            )));
+                        }
+                        recorded.insert(renamed_sub_path.clone(), works.len());
+                        works.push((path, sub_path, renamed_sub_path));
+                    }
+                }
+            }
+        }
+
+        Ok(works)
+    }
+
     /// Collects recursively all symtypes files under the given root path and its subpath.
     fn collect_symfiles(
         root: &Path,
@@ -431,7 +1256,10 @@ impl SymtypesCorpus {
                 continue;
             }
 
-            let ext = match entry_sub_path.extension() {
+            // Compressed symtypes files are recognized by their inner extension.
+            let maybe_plain_sub_path = strip_compression_ext(&entry_sub_path);
+            let plain_sub_path = maybe_plain_sub_path.as_deref().unwrap_or(&entry_sub_path);
+            let ext = match plain_sub_path.extension() {
                 Some(ext) => ext,
                 None => continue,
             };
@@ -444,7 +1272,7 @@ impl SymtypesCorpus {
     }
 
     /// Loads all specified symtypes files.
-    fn load_symfiles<W: Write + Send>(
+    fn load_symfiles<W: WarningsSink + Send>(
         &mut self,
         root: &Path,
         symfiles: &[&Path],
@@ -454,18 +1282,54 @@ impl SymtypesCorpus {
     ) -> Result<(), Error> {
         let load_context = LoadContext::from(self, load_kind, warnings);
 
-        burst::run_jobs(
-            |work_idx| {
-                let sub_path = symfiles[work_idx];
-
-                let path = root.join(sub_path);
-                let file = PathFile::open(&path).map_err(|err| {
-                    Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
-                })?;
+        load_context.run_jobs(
+            |work_idx| Self::load_symfile(root, symfiles[work_idx], &load_context),
+            symfiles.len(),
+            job_slots,
+        )?;
 
-                Self::load_inner(&path, sub_path, file, &load_context)?;
+        let (new_types, new_exports, new_files) = load_context.into_inner();
+        self.merge_new(new_types, new_exports, new_files);
 
-                Ok(())
+        Ok(())
+    }
+
+    /// Loads split symtypes data from the specified tar archive.
+    ///
+    /// All entries with the `.symtypes` extension are loaded, and are recorded under their path in
+    /// the archive. Other entries are ignored.
+    fn load_tar<W: WarningsSink + Send>(
+        &mut self,
+        path: &Path,
+        warnings: W,
+        job_slots: &mut JobSlots,
+    ) -> Result<(), Error> {
+        let file = PathFile::open(path).map_err(|err| {
+            Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+        })?;
+        let mut symfiles = read_tar(file)
+            .map_err(|err| {
+                Error::new_context(
+                    format!("Failed to read the archive '{}'", path.display()),
+                    err,
+                )
+            })?
+            .into_iter()
+            .filter(|(sub_path, _)| sub_path.extension().is_some_and(|ext| ext == "symtypes"))
+            .collect::<Vec<_>>();
+        symfiles.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let load_context = LoadContext::from(self, LoadKind::Simple, warnings);
+
+        load_context.run_jobs(
+            |work_idx| {
+                let (sub_path, data) = &symfiles[work_idx];
+                Self::load_inner(
+                    &path.join(sub_path),
+                    sub_path,
+                    data.as_slice(),
+                    &load_context,
+                )
             },
             symfiles.len(),
             job_slots,
@@ -477,17 +1341,198 @@ impl SymtypesCorpus {
         Ok(())
     }
 
+    /// Loads split symtypes data from the specified directory, reusing unmodified files from
+    /// a previously consolidated file.
+    ///
+    /// A symtypes file is taken from the consolidated data only if it was not modified after
+    /// `consolidated_path` was written and its content still matches the consolidated records. All
+    /// other files are parsed again. Files that are no longer present in the directory are dropped.
+    /// If `consolidated_path` doesn't exist, all files are parsed.
+    pub fn load_split_incremental<P: AsRef<Path>, Q: AsRef<Path>, W: WarningsSink + Send>(
+        &mut self,
+        path: P,
+        consolidated_path: Q,
+        mut warnings: W,
+        job_slots: &mut JobSlots,
+    ) -> Result<(), Error> {
+        enum Work<'a> {
+            Parse(&'a Path),
+            Check(&'a Path, &'a SymtypesFile),
+        }
+
+        let path = path.as_ref();
+        let consolidated_path = consolidated_path.as_ref();
+
+        // Load the previous consolidated data and find out when it was written.
+        let mut previous = SymtypesCorpus::new();
+        let consolidated_mtime = if consolidated_path.exists() {
+            previous.set_keep_going(self.keep_going);
+            previous.load_consolidated(consolidated_path, WarningsRef(&mut warnings), job_slots)?;
+            get_mtime(consolidated_path)?
+        } else {
+            debug!(
+                "No previous consolidated file '{}' found, reading all symtypes files",
+                consolidated_path.display()
+            );
+            SystemTime::UNIX_EPOCH
+        };
+
+        // Recursively collect symtypes files within the directory and determine which of them
+        // need to be parsed again. Files that appear unmodified are checked against their
+        // consolidated records when processed.
+        let mut symfiles = Vec::new();
+        Self::collect_symfiles(path, Path::new(""), &mut symfiles)?;
+        symfiles.sort();
+
+        let mut works = Vec::new();
+        for sub_path in &symfiles {
+            let maybe_plain_sub_path = strip_compression_ext(sub_path);
+            let plain_sub_path = maybe_plain_sub_path.as_deref().unwrap_or(sub_path);
+
+            match previous.files.get(plain_sub_path) {
+                Some(symfile_rc) if get_mtime(&path.join(sub_path))? < consolidated_mtime => {
+                    works.push(Work::Check(sub_path, symfile_rc.as_ref()))
+                }
+                _ => works.push(Work::Parse(sub_path)),
+            }
+        }
+
+        // Load all modified files and add the reused ones.
+        let load_context = LoadContext::from(self, LoadKind::Simple, warnings);
+        let num_reused = AtomicUsize::new(0);
+
+        load_context.run_jobs(
+            |work_idx| match works[work_idx] {
+                Work::Parse(sub_path) => Self::load_symfile(path, sub_path, &load_context),
+                Work::Check(sub_path, symfile) => {
+                    if file_digest(&path.join(sub_path))? == records_digest(&symfile.records) {
+                        num_reused.fetch_add(1, Ordering::Relaxed);
+                        Self::reuse_file(consolidated_path, symfile, &load_context)
+                    } else {
+                        Self::load_symfile(path, sub_path, &load_context)
+                    }
+                }
+            },
+            works.len(),
+            job_slots,
+        )?;
+        debug!(
+            "Reused '{}' of '{}' symtypes files from '{}'",
+            num_reused.into_inner(),
+            works.len(),
+            consolidated_path.display()
+        );
+
+        let (new_types, new_exports, new_files) = load_context.into_inner();
+        self.merge_new(new_types, new_exports, new_files);
+
+        Ok(())
+    }
+
+    /// Loads a single symtypes file, specified by its root and sub path, into the newly loaded
+    /// data.
+    fn load_symfile(root: &Path, sub_path: &Path, load_context: &LoadContext) -> Result<(), Error> {
+        // Record compressed files under their uncompressed names.
+        let maybe_plain_sub_path = strip_compression_ext(sub_path);
+        let plain_sub_path = maybe_plain_sub_path.as_deref().unwrap_or(sub_path);
+
+        Self::load_symfile_as(root, sub_path, plain_sub_path, load_context)
+    }
+
+    /// Loads a single symtypes file, specified by its root and sub path, into the newly loaded
+    /// data, recording it under `record_sub_path`.
+    fn load_symfile_as(
+        root: &Path,
+        sub_path: &Path,
+        record_sub_path: &Path,
+        load_context: &LoadContext,
+    ) -> Result<(), Error> {
+        let path = root.join(sub_path);
+        let file = PathFile::open(&path).map_err(|err| {
+            Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+        })?;
+
+        Self::load_inner(&path, record_sub_path, file, load_context)
+    }
+
+    /// Adds a file from another corpus to the newly loaded data.
+    ///
+    /// The `path` is the name of the input file from which the other corpus was loaded.
+    fn reuse_file(
+        path: &Path,
+        symfile: &SymtypesFile,
+        load_context: &LoadContext,
+    ) -> Result<(), Error> {
+        let mut merge_cache = load_context.merge_cache();
+        let records = symfile
+            .records
+            .iter()
+            .map(|(name, tokens_rc)| {
+                let tokens_rc = Self::merge_type(
+                    name,
+                    tokens_rc.as_ref().clone(),
+                    load_context,
+                    Some(&mut merge_cache.cache),
+                );
+                (name.clone(), tokens_rc)
+            })
+            .collect();
+
+        let symfile_rc = Arc::new(SymtypesFile {
+            path: symfile.path.clone(),
+            records,
+            export_hashes: symfile.export_hashes.clone(),
+            metadata: symfile.metadata.clone(),
+        });
+        Self::insert_file(path, symfile_rc, None, load_context)
+    }
+
     /// Loads symtypes data from the specified reader.
     ///
     /// The `path` should point to a symtypes file name, indicating the origin of the data.
-    pub fn load_buffer<P: AsRef<Path>, R: Read, W: Write + Send>(
+    pub fn load_buffer<P: AsRef<Path>, R: Read, W: WarningsSink + Send>(
         &mut self,
         path: P,
         reader: R,
         warnings: W,
     ) -> Result<(), Error> {
-        let path = path.as_ref();
-        let load_context = LoadContext::from(self, LoadKind::Any, warnings);
+        self.load_reader(path.as_ref(), reader, LoadKind::Any, warnings)
+    }
+
+    /// Creates a new corpus from in-memory symtypes files.
+    ///
+    /// The `files` map the path of each symtypes file to its content. The files are loaded in the
+    /// order of their paths, in the same way as the files of a directory by
+    /// [`SymtypesCorpus::load()`], which allows constructing a corpus without accessing the
+    /// filesystem.
+    pub fn from_files<W: WarningsSink + Send>(
+        files: HashMap<PathBuf, String>,
+        warnings: W,
+    ) -> Result<Self, Error> {
+        let mut files = files.into_iter().collect::<Vec<_>>();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut symtypes = Self::new();
+        let load_context = LoadContext::from(&symtypes, LoadKind::Simple, warnings);
+        for (path, data) in &files {
+            Self::load_inner(path, path, data.as_bytes(), &load_context)?;
+        }
+
+        let (new_types, new_exports, new_files) = load_context.into_inner();
+        symtypes.merge_new(new_types, new_exports, new_files);
+
+        Ok(symtypes)
+    }
+
+    /// Loads symtypes data of the given kind from the specified reader.
+    fn load_reader<R: Read, W: WarningsSink + Send>(
+        &mut self,
+        path: &Path,
+        reader: R,
+        load_kind: LoadKind,
+        warnings: W,
+    ) -> Result<(), Error> {
+        let load_context = LoadContext::from(self, load_kind, warnings);
 
         Self::load_inner(path, path, reader, &load_context)?;
 
@@ -497,6 +1542,255 @@ impl SymtypesCorpus {
         Ok(())
     }
 
+    /// Validates symtypes data at the specified location, without loading it into a corpus.
+    ///
+    /// The `path` can point to a single symtypes file or a directory, which is handled in the same
+    /// way as by [`SymtypesCorpus::load()`]. Unlike loading, the validation doesn't stop at the
+    /// first problem but checks all records for malformed data, duplicate records, malformed
+    /// UNKNOWN overrides, references to unknown types, and mixed plain and consolidated content.
+    ///
+    /// Returns `Ok` containing all found problems, ordered by the file and line in which they
+    /// occur, or <code>Err([Error])</code> if the data could not be read.
+    pub fn validate<P: AsRef<Path>>(
+        path: P,
+        job_slots: &mut JobSlots,
+    ) -> Result<Vec<Error>, Error> {
+        let path = path.as_ref();
+
+        // Determine if the input is a directory tree or a single symtypes file.
+        let md = fs::metadata(path).map_err(|err| {
+            Error::new_io(
+                format!("Failed to query the path '{}'", path.display()),
+                err,
+            )
+        })?;
+
+        let (root, symfiles, load_kind) = if md.is_dir() {
+            let mut symfiles = Vec::new();
+            Self::collect_symfiles(path, Path::new(""), &mut symfiles)?;
+            symfiles.sort();
+            (path, symfiles, LoadKind::Simple)
+        } else {
+            (Path::new(""), vec![path.to_path_buf()], LoadKind::Any)
+        };
+
+        // Validate all files, keeping the problems of each file separate so they can be reported
+        // in a stable order.
+        let file_problems = symfiles
+            .iter()
+            .map(|_| Mutex::new(Vec::new()))
+            .collect::<Vec<_>>();
+        burst::run_jobs(
+            |work_idx| {
+                let path = root.join(&symfiles[work_idx]);
+                let file = PathFile::open(&path).map_err(|err| {
+                    Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+                })?;
+                let problems = Self::validate_inner(&path, file, load_kind)?;
+                *file_problems[work_idx].lock().unwrap() = problems;
+                Ok(())
+            },
+            symfiles.len(),
+            job_slots,
+        )?;
+
+        Ok(file_problems
+            .into_iter()
+            .flat_map(|problems| problems.into_inner().unwrap())
+            .collect())
+    }
+
+    /// Validates symtypes data from the specified reader, without loading it into a corpus.
+    ///
+    /// The `path` should point to a symtypes file name, indicating the origin of the data.
+    ///
+    /// Returns `Ok` containing all found problems, or <code>Err([Error])</code> if the data could
+    /// not be read.
+    pub fn validate_buffer<P: AsRef<Path>, R: Read>(
+        path: P,
+        reader: R,
+    ) -> Result<Vec<Error>, Error> {
+        Self::validate_inner(path.as_ref(), reader, LoadKind::Any)
+    }
+
+    /// Validates symtypes data from the specified reader.
+    ///
+    /// The parsing follows [`SymtypesCorpus::load_inner()`], but each problem is recorded and the
+    /// validation continues with the next line.
+    fn validate_inner<R: Read>(
+        path: &Path,
+        reader: R,
+        load_kind: LoadKind,
+    ) -> Result<Vec<Error>, Error> {
+        debug!("Validating symtypes data from '{}'", path.display());
+
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        let mut line_idx = 0;
+        let mut problems = Vec::new();
+
+        // Read the first line and detect whether the input is a single or consolidated symtypes
+        // file.
+        let mut has_line = read_next_line(&mut reader, &mut line)?;
+        let has_metadata = has_line && is_format_header(&line);
+        let is_consolidated = has_metadata || (has_line && is_file_header(&line));
+        if load_kind == LoadKind::Simple && is_consolidated {
+            problems.push(Error::new_parse_format(
+                "Expected a plain symtypes file, but found consolidated data",
+                path,
+                1,
+                &line,
+            ));
+        }
+        if has_metadata {
+            if let Err(err) = check_format_header(path, &line) {
+                problems.push(err);
+            }
+            has_line = read_next_line(&mut reader, &mut line)?;
+            line_idx += 1;
+        }
+
+        // Track all records and metadata of the currently processed single (inner) file, the
+        // active types and per-file overrides for UNKNOWN definitions, and all seen file paths if
+        // this is a consolidated file.
+        let mut records = Vec::<(String, Tokens, usize)>::new();
+        let mut record_names = HashSet::new();
+        let mut metadata = FileMetadata::new();
+        let mut active_types = HashSet::new();
+        let mut local_override = HashSet::new();
+        let mut sub_paths = HashSet::new();
+
+        while has_line {
+            if is_consolidated && line.is_empty() {
+                // Skip empty lines in consolidated files.
+            } else if is_consolidated && is_metadata_line(&line) {
+                if sub_paths.is_empty() {
+                    problems.push(Error::new_parse_format(
+                        "Unexpected file metadata before the first file header",
+                        path,
+                        line_idx + 1,
+                        &line,
+                    ));
+                } else if let Err(err) =
+                    parse_metadata_line(path, line_idx, &line, has_metadata, &mut metadata)
+                {
+                    problems.push(err);
+                }
+            } else if is_file_header(&line) {
+                if is_consolidated {
+                    // Complete the current file and start a new one.
+                    Self::validate_references(
+                        path,
+                        &mem::take(&mut records),
+                        &mem::take(&mut local_override),
+                        &active_types,
+                        &mut problems,
+                    );
+                    record_names.clear();
+                    metadata.clear();
+
+                    let sub_path = &line[3..line.len() - 3];
+                    if !sub_paths.insert(sub_path.to_string()) {
+                        problems.push(Error::new_parse_format(
+                            &format!("Duplicate file path '{}'", sub_path),
+                            path,
+                            line_idx + 1,
+                            &line,
+                        ));
+                    }
+                } else {
+                    problems.push(Error::new_parse_format(
+                        "Unexpected file header in a plain symtypes file",
+                        path,
+                        line_idx + 1,
+                        &line,
+                    ));
+                }
+            } else {
+                match parse_type_record(path, line_idx, &line, is_consolidated) {
+                    Ok((name, tokens, is_local_override)) => {
+                        if name.contains("##") {
+                            // A valid UNKNOWN override in a consolidated file is already expanded
+                            // by the parser.
+                            let desc = if is_consolidated {
+                                format!("Malformed UNKNOWN override '{}'", name)
+                            } else {
+                                format!("Unexpected UNKNOWN override '{}' in a plain file", name)
+                            };
+                            problems.push(Error::new_parse_format(
+                                &desc,
+                                path,
+                                line_idx + 1,
+                                &line,
+                            ));
+                        } else if !record_names.insert(name.clone()) {
+                            problems.push(Error::new_parse_format(
+                                &format!("Duplicate record '{}'", name),
+                                path,
+                                line_idx + 1,
+                                &line,
+                            ));
+                        } else {
+                            if is_local_override {
+                                local_override.insert(name.clone());
+                            } else {
+                                active_types.insert(name.clone());
+                            }
+                            records.push((name, tokens, line_idx));
+                        }
+                    }
+                    Err(err) => problems.push(err),
+                }
+            }
+
+            has_line = read_next_line(&mut reader, &mut line)?;
+            line_idx += 1;
+        }
+
+        // Complete the last file.
+        Self::validate_references(
+            path,
+            &records,
+            &local_override,
+            &active_types,
+            &mut problems,
+        );
+
+        // Report the problems in the order of their lines.
+        problems.sort_by_key(|problem| match problem {
+            Error::ParseAt(parse_err) => parse_err.line,
+            _ => 0,
+        });
+
+        Ok(problems)
+    }
+
+    /// Checks that all types referenced by the given records are known, recording each unknown
+    /// reference in `problems`.
+    fn validate_references(
+        path: &Path,
+        records: &[(String, Tokens, usize)],
+        local_override: &HashSet<String>,
+        active_types: &HashSet<String>,
+        problems: &mut Vec<Error>,
+    ) {
+        for (name, tokens, line_idx) in records {
+            for token in tokens {
+                if let Token::TypeRef(ref_name) = token
+                    && !local_override.contains(ref_name.as_ref())
+                    && !active_types.contains(ref_name.as_ref())
+                {
+                    problems.push(Error::new_parse_format(
+                        &format!("Type '{}' is not known", ref_name),
+                        path,
+                        line_idx + 1,
+                        &format_type_record(name, tokens),
+                    ));
+                }
+            }
+        }
+    }
+
     /// Completes the loading operation by merging new data into the existing corpus.
     fn merge_new(
         &mut self,
@@ -519,6 +1813,9 @@ impl SymtypesCorpus {
     }
 
     /// Loads symtypes data from the specified reader.
+    ///
+    /// The data is parsed line by line, without reading the whole input into memory first. Only the
+    /// state of the currently processed file and the active types are kept while loading.
     fn load_inner<R: Read>(
         path: &Path,
         sub_path: &Path,
@@ -527,34 +1824,54 @@ impl SymtypesCorpus {
     ) -> Result<(), Error> {
         debug!("Loading symtypes data from '{}'", path.display());
 
-        // Read all content from the file.
-        let lines = match read_lines(reader) {
-            Ok(lines) => lines,
-            Err(err) => return Err(Error::new_io("Failed to read symtypes data", err)),
-        };
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        let mut line_idx = 0;
 
-        // Detect whether the input is a single or consolidated symtypes file.
-        let is_consolidated =
-            !lines.is_empty() && lines[0].starts_with("/* ") && lines[0].ends_with(" */");
+        // Read the first line and detect whether the input is a single or consolidated symtypes
+        // file.
+        let mut has_line = read_next_line(&mut reader, &mut line)?;
+        let has_metadata = has_line && is_format_header(&line);
+        let is_consolidated = has_metadata || (has_line && is_file_header(&line));
         if load_context.load_kind == LoadKind::Simple && is_consolidated {
             return Err(Error::new_parse_format(
                 "Expected a plain symtypes file, but found consolidated data",
                 path,
                 1,
-                &lines[0],
+                &line,
             ));
         } else if load_context.load_kind == LoadKind::Consolidated && !is_consolidated {
             return Err(Error::new_parse_format(
                 "Expected a consolidated symtypes file, but found an invalid header",
                 path,
                 1,
-                if lines.is_empty() { "" } else { &lines[0] },
+                &line,
             ));
         }
 
+        // Check the format version of consolidated data with file metadata, and that the first
+        // file follows.
+        if has_metadata {
+            check_format_header(path, &line)?;
+            has_line = read_next_line(&mut reader, &mut line)?;
+            line_idx += 1;
+            if has_line && !is_file_header(&line) {
+                return Err(Error::new_parse_format(
+                    "Expected a file header after the format header",
+                    path,
+                    line_idx + 1,
+                    &line,
+                ));
+            }
+        }
+
+        // Each type variant in consolidated data is normally defined only once, so caching the
+        // merged types is useful only for plain files.
+        let mut maybe_merge_cache = (!is_consolidated).then(|| load_context.merge_cache());
+
         // Track the name of the currently processed single (inner) file.
         let mut maybe_sub_path = if !is_consolidated {
-            Some(sub_path)
+            Some(sub_path.to_path_buf())
         } else {
             None
         };
@@ -565,51 +1882,91 @@ impl SymtypesCorpus {
         let mut local_override = LoadActiveTypes::new();
 
         let mut records = FileRecords::new();
+        let mut metadata = FileMetadata::new();
+
+        // Collect errors found in the keep-going mode.
+        let mut errors = Vec::new();
 
         // Parse all declarations.
-        for (line_idx, line) in lines.iter().enumerate() {
+        while has_line {
             // Skip empty lines in consolidated files.
             if is_consolidated && line.is_empty() {
+                has_line = read_next_line(&mut reader, &mut line)?;
+                line_idx += 1;
                 continue;
             }
 
             // Handle file headers in consolidated files.
-            if is_consolidated && line.starts_with("/* ") && line.ends_with(" */") {
+            if is_consolidated && is_file_header(&line) {
                 // Add the current file.
-                if let Some(sub_path) = maybe_sub_path {
-                    Self::add_file(
+                if let Some(sub_path) = maybe_sub_path
+                    && let Err(err) = Self::add_file(
                         path,
-                        sub_path,
-                        &lines,
+                        &sub_path,
                         mem::take(&mut records),
                         mem::take(&mut local_override),
+                        mem::take(&mut metadata),
                         &active_types,
                         load_context,
-                    )?;
+                    )
+                {
+                    load_context.handle_error(err, &mut errors)?;
                 }
 
                 // Open the new file.
-                maybe_sub_path = Some(Path::new(&line[3..line.len() - 3]));
+                maybe_sub_path = Some(PathBuf::from(&line[3..line.len() - 3]));
+
+                has_line = read_next_line(&mut reader, &mut line)?;
+                line_idx += 1;
+                continue;
+            }
 
+            // Handle file metadata in consolidated files.
+            if is_consolidated && is_metadata_line(&line) {
+                if let Err(err) =
+                    parse_metadata_line(path, line_idx, &line, has_metadata, &mut metadata)
+                {
+                    load_context.handle_error(err, &mut errors)?;
+                }
+
+                has_line = read_next_line(&mut reader, &mut line)?;
+                line_idx += 1;
                 continue;
             }
 
             // Ok, it is a regular record, parse it.
             let (name, tokens, is_local_override) =
-                parse_type_record(path, line_idx, line, is_consolidated)?;
+                match parse_type_record(path, line_idx, &line, is_consolidated) {
+                    Ok(record) => record,
+                    Err(err) => {
+                        load_context.handle_error(err, &mut errors)?;
+                        has_line = read_next_line(&mut reader, &mut line)?;
+                        line_idx += 1;
+                        continue;
+                    }
+                };
 
             // Check if the record is a duplicate of another one.
             if records.contains_key(&name) {
-                return Err(Error::new_parse_format(
+                let err = Error::new_parse_format(
                     &format!("Duplicate record '{}'", name),
                     path,
                     line_idx + 1,
-                    &lines[line_idx],
-                ));
+                    &line,
+                );
+                load_context.handle_error(err, &mut errors)?;
+                has_line = read_next_line(&mut reader, &mut line)?;
+                line_idx += 1;
+                continue;
             }
 
             // Insert the type into the future corpus and file records.
-            let tokens_rc = Self::merge_type(&name, tokens, load_context);
+            let tokens_rc = Self::merge_type(
+                &name,
+                tokens,
+                load_context,
+                maybe_merge_cache.as_mut().map(|guard| &mut guard.cache),
+            );
             records.insert(name.clone(), Arc::clone(&tokens_rc));
 
             // Record the type as currently active.
@@ -618,19 +1975,28 @@ impl SymtypesCorpus {
             } else {
                 active_types.insert(name, (tokens_rc, line_idx));
             }
+
+            has_line = read_next_line(&mut reader, &mut line)?;
+            line_idx += 1;
         }
 
         // Complete the file.
-        if let Some(sub_path) = maybe_sub_path {
-            Self::add_file(
+        if let Some(sub_path) = maybe_sub_path
+            && let Err(err) = Self::add_file(
                 path,
-                sub_path,
-                &lines,
+                &sub_path,
                 records,
                 local_override,
+                metadata,
                 &active_types,
                 load_context,
-            )?;
+            )
+        {
+            load_context.handle_error(err, &mut errors)?;
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::new_multiple(errors));
         }
 
         Ok(())
@@ -646,21 +2012,19 @@ impl SymtypesCorpus {
     fn add_file(
         path: &Path,
         sub_path: &Path,
-        lines: &Vec<String>,
         mut records: FileRecords,
         local_override: LoadActiveTypes,
+        metadata: FileMetadata,
         active_types: &LoadActiveTypes,
         load_context: &LoadContext,
     ) -> Result<(), Error> {
         // Extrapolate all records and validate references.
         let walk_records = records.keys().map(String::clone).collect::<Vec<_>>();
         for name in walk_records {
-            // Note that all explicit types are known, so it is ok to pass `usize::MAX` as
-            // `from_line_idx` because it is unused.
+            // Note that all explicit types are known, so no referencing record is needed.
             Self::complete_file_record(
                 path,
-                lines,
-                usize::MAX,
+                None,
                 &name,
                 true,
                 &local_override,
@@ -670,11 +2034,21 @@ impl SymtypesCorpus {
         }
 
         // Add the file to the future corpus.
-        let symfile_rc = Arc::new(SymtypesFile {
-            path: sub_path.to_path_buf(),
-            records,
-        });
+        let symfile_rc =
+            Arc::new(SymtypesFile::new(sub_path.to_path_buf(), records).with_metadata(metadata));
+        Self::insert_file(path, symfile_rc, Some(active_types), load_context)
+    }
 
+    /// Inserts the specified complete file and all its exports into the newly loaded data.
+    ///
+    /// The `path` is the name of an input file, which can be a consolidated file. The
+    /// `maybe_active_types` provide source line indexes of the records, if they are known.
+    fn insert_file(
+        path: &Path,
+        symfile_rc: Arc<SymtypesFile>,
+        maybe_active_types: Option<&LoadActiveTypes>,
+        load_context: &LoadContext,
+    ) -> Result<(), Error> {
         {
             let mut new_files = load_context.new_files.lock().unwrap();
 
@@ -701,44 +2075,56 @@ impl SymtypesCorpus {
                 .filter(|&name| is_export_name(name))
             {
                 // Add the export, if it is unique.
-                let other_symfile_rc = {
-                    if let Some(other_symfile_rc) =
-                        load_context.symtypes.exports.get(type_name.as_str())
-                    {
-                        Arc::clone(other_symfile_rc)
-                    } else {
-                        match new_exports.entry(type_name.clone()) // [1]
-                        {
-                            Occupied(export_entry) => Arc::clone(export_entry.get()),
-                            Vacant(export_entry) => {
-                                export_entry.insert(Arc::clone(&symfile_rc));
-                                continue;
-                            }
+                let (other_symfile_rc, is_other_loaded) = match new_exports.get(type_name.as_str())
+                {
+                    Some(other_symfile_rc) => (Arc::clone(other_symfile_rc), false),
+                    None => match load_context.symtypes.exports.get(type_name.as_str()) {
+                        Some(other_symfile_rc) => (Arc::clone(other_symfile_rc), true),
+                        None => {
+                            new_exports.insert(type_name.clone(), Arc::clone(&symfile_rc)); // [1]
+                            continue;
                         }
-                    }
+                    },
                 };
 
+                // Keep the occurrence selected by the policy.
+                if load_context.duplicate_exports.prefers(
+                    &symfile_rc.path,
+                    &other_symfile_rc.path,
+                    is_other_loaded,
+                ) {
+                    new_exports.insert(type_name.clone(), Arc::clone(&symfile_rc));
+                }
+
                 // INVARIANT: Each export is included in the active types.
-                let (_, line_idx) = active_types.get(type_name.as_str()).unwrap();
+                let location = match maybe_active_types {
+                    Some(active_types) => {
+                        let (_, line_idx) = active_types.get(type_name.as_str()).unwrap();
+                        format!("{}:{}", path.display(), line_idx + 1)
+                    }
+                    None => path.display().to_string(),
+                };
+                let message = format!(
+                    "Export '{}' defined in '{}' is duplicate, previous occurrence found in '{}'",
+                    type_name,
+                    symfile_rc.path.display(),
+                    other_symfile_rc.path.display(),
+                );
+
+                if load_context.duplicate_exports == DuplicateExportPolicy::Error {
+                    return Err(Error::new_parse(format!("{}: {}", location, message)));
+                }
 
                 // Report the duplicate export as a warning. Although technically an error, some
                 // auxiliary kernel components that are not part of vmlinux/modules may reuse logic
                 // from the rest of the kernel by including its C/assembly files, which may contain
                 // export directives. If these components aren't correctly configured to disable
                 // exports, collecting all symtypes from the build will result in duplicate symbols.
-                // This should be fixed in the kernel. However, we want to proceed, especially if
-                // this is the compare command, where we want to report actual kABI differences.
+                // This should be fixed in the kernel. However, we want to proceed by default,
+                // especially if this is the compare command, where we want to report actual kABI
+                // differences.
                 let mut warnings = load_context.warnings.lock().unwrap();
-                writeln!(
-                    warnings,
-                    "{}:{}: WARNING: Export '{}' defined in '{}' is duplicate, previous occurrence found in '{}'",
-                    path.display(),
-                    line_idx + 1,
-                    type_name,
-                    symfile_rc.path.display(),
-                    other_symfile_rc.path.display(),
-                )
-                .map_io_err("Failed to write a duplicate-export warning")?;
+                warnings.report(type_name, &location, &message)?;
             }
         }
 
@@ -747,9 +2133,45 @@ impl SymtypesCorpus {
 
     /// Adds the given type definition to the newly loaded data if it's not already present, and
     /// returns its reference-counted pointer.
-    fn merge_type(type_name: &str, tokens: Tokens, load_context: &LoadContext) -> Arc<Tokens> {
-        let bucket_idx = type_bucket_idx(type_name);
-
+    ///
+    /// If `maybe_merge_cache` of the calling worker is provided, it is searched first and updated
+    /// with the result, so that the shared data needs to be locked only on the first occurrence of
+    /// each type variant in the worker.
+    fn merge_type(
+        type_name: &str,
+        tokens: Tokens,
+        load_context: &LoadContext,
+        maybe_merge_cache: Option<&mut MergeCache>,
+    ) -> Arc<Tokens> {
+        let Some(merge_cache) = maybe_merge_cache else {
+            return Self::merge_shared_type(type_name, tokens, load_context);
+        };
+
+        // Search in the worker's cache.
+        let cached_variants = match merge_cache.get_mut(type_name) {
+            Some(variants) => variants,
+            None => merge_cache.entry(type_name.to_string()).or_default(), // [1]
+        };
+        for variant_rc in cached_variants.iter() {
+            if tokens == **variant_rc {
+                return Arc::clone(variant_rc);
+            }
+        }
+
+        let tokens_rc = Self::merge_shared_type(type_name, tokens, load_context);
+        cached_variants.push(Arc::clone(&tokens_rc));
+        tokens_rc
+    }
+
+    /// Adds the given type definition to the shared newly loaded data if it's not already present,
+    /// and returns its reference-counted pointer.
+    fn merge_shared_type(
+        type_name: &str,
+        tokens: Tokens,
+        load_context: &LoadContext,
+    ) -> Arc<Tokens> {
+        let bucket_idx = type_bucket_idx(type_name);
+
         // Search in the current types.
         if let Some(variants) = load_context.symtypes.types[bucket_idx].get(type_name) {
             for variant_rc in variants {
@@ -803,11 +2225,12 @@ impl SymtypesCorpus {
     /// a file entry and then call this function on each of those types. These root calls should be
     /// invoked with `is_explicit` set to `true`. The function then recursively adds all needed
     /// implicit types that are referenced from these roots.
-    #[allow(clippy::too_many_arguments)]
+    ///
+    /// The `maybe_from` parameter identifies the record referencing the type, specifying its name,
+    /// tokens and source line index. It is used to provide error context if the type is not known.
     fn complete_file_record(
         path: &Path,
-        lines: &Vec<String>,
-        from_line_idx: usize,
+        maybe_from: Option<(&str, &Tokens, usize)>,
         type_name: &str,
         is_explicit: bool,
         local_override: &LoadActiveTypes,
@@ -829,11 +2252,14 @@ impl SymtypesCorpus {
             None => match active_types.get(type_name) {
                 Some(&(ref tokens_rc, line_idx)) => (Arc::clone(tokens_rc), line_idx),
                 None => {
+                    // INVARIANT: All explicit types are known, an unknown type is always
+                    // referenced from another record.
+                    let (from_name, from_tokens, from_line_idx) = maybe_from.unwrap();
                     return Err(Error::new_parse_format(
                         &format!("Type '{}' is not known", type_name),
                         path,
                         from_line_idx + 1,
-                        &lines[from_line_idx],
+                        &format_type_record(from_name, from_tokens),
                     ));
                 }
             },
@@ -848,8 +2274,7 @@ impl SymtypesCorpus {
                 Token::TypeRef(ref_name) => {
                     Self::complete_file_record(
                         path,
-                        lines,
-                        line_idx,
+                        Some((type_name, &tokens_rc, line_idx)),
                         ref_name,
                         false,
                         local_override,
@@ -864,381 +2289,3563 @@ impl SymtypesCorpus {
         Ok(())
     }
 
-    /// Writes the corpus in the consolidated form to the specified file.
-    pub fn write_consolidated<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
-        self.write_consolidated_buffer(Writer::new_file(path)?)
+    /// Calls `f` for each export in the corpus, passing the export name and the path of the
+    /// symtypes file in which the export is defined.
+    ///
+    /// The exports are visited in no particular order.
+    pub fn for_each_export<F: FnMut(&str, &Path)>(&self, mut f: F) {
+        for (name, file) in &self.exports {
+            f(name, &file.path);
+        }
     }
 
-    /// Writes the corpus in the consolidated form to the provided output stream.
-    pub fn write_consolidated_buffer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
-        let err_desc = "Failed to write a consolidated record";
+    /// Calls `f` for each variant of each type in the corpus, passing the type name and the tokens
+    /// describing the variant.
+    ///
+    /// Types with several different definitions in the corpus are visited once for each variant.
+    /// The types and their variants are visited in no particular order.
+    pub fn for_each_type_variant<F: FnMut(&str, &[Token])>(&self, mut f: F) {
+        for types in &self.types {
+            for (name, variants) in types {
+                for tokens in variants {
+                    f(name, tokens);
+                }
+            }
+        }
+    }
 
-        // Track which records are currently active, mapping a type name to its tokens.
-        let mut active_types = HashMap::<&String, &Arc<Tokens>>::new();
+    /// Reports a warning for each type that has more than `max_variants` distinct variants in the
+    /// corpus.
+    ///
+    /// A high number of variants of a single type usually indicates diverging headers or a
+    /// misconfigured build, rather than a legitimate kABI difference. The warnings are reported in
+    /// the order of the type names, each located at the first file, by path, that contains the
+    /// type.
+    pub fn check_variants<W: WarningsSink>(
+        &self,
+        max_variants: usize,
+        mut warnings: W,
+    ) -> Result<(), Error> {
+        let mut exploded_types = self
+            .types
+            .iter()
+            .flat_map(|types| types.iter())
+            .filter(|(_, variants)| variants.len() > max_variants)
+            .map(|(name, variants)| (name.as_str(), variants.len()))
+            .collect::<Vec<_>>();
+        if exploded_types.is_empty() {
+            return Ok(());
+        }
+        exploded_types.sort();
 
-        // Sort all files in the corpus by their path.
         let mut sorted_files = self.files.values().collect::<Vec<_>>();
         sorted_files.sort_by_key(|&symfile_rc| &symfile_rc.path);
 
-        // Process the sorted files and add their types to the output.
-        let mut add_separator = false;
-        for symfile_rc in sorted_files {
-            let symfile = symfile_rc.as_ref();
-
-            // Sort all types in the file.
-            let mut sorted_types = symfile.records.iter().collect::<Vec<_>>();
-            sorted_types.sort_by_cached_key(|&(name, _)| (is_export_name(name), name));
-
-            // Add an empty line to separate individual files.
-            if add_separator {
-                writeln!(writer).map_io_err(err_desc)?;
-            } else {
-                add_separator = true;
-            }
-
-            // Write the file header.
-            writeln!(writer, "/* {} */", symfile.path.display()).map_io_err(err_desc)?;
-
-            // Write all output types.
-            for (name, tokens_rc) in sorted_types {
-                // Check if this is an UNKNOWN type definition, and if so, record it as a local
-                // override.
-                if let Some(short_name) = try_shorten_decl(name, tokens_rc) {
-                    writeln!(writer, "{}", short_name).map_io_err(err_desc)?;
-                    continue;
-                }
-
-                // See if the symbol matches an already active definition, or record it in the
-                // output.
-                let record = match active_types.entry(name) {
-                    Occupied(mut active_type_entry) => {
-                        if *active_type_entry.get() != tokens_rc {
-                            active_type_entry.insert(tokens_rc);
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    Vacant(active_type_entry) => {
-                        active_type_entry.insert(tokens_rc);
-                        true
-                    }
-                };
-                if record {
-                    write!(writer, "{}", name).map_io_err(err_desc)?;
-                    for token in tokens_rc.iter() {
-                        write!(writer, " {}", token.as_str()).map_io_err(err_desc)?;
-                    }
-                    writeln!(writer).map_io_err(err_desc)?;
-                }
-            }
+        for (name, count) in exploded_types {
+            let location = sorted_files
+                .iter()
+                .find(|symfile_rc| symfile_rc.records.contains_key(name))
+                .map(|symfile_rc| symfile_rc.path.display().to_string())
+                .unwrap_or_default();
+            warnings.report(
+                name,
+                &location,
+                &format!(
+                    "Type '{}' has '{}' variants, more than the maximum of '{}'",
+                    name, count, max_variants
+                ),
+            )?;
         }
 
-        writer.flush().map_io_err(err_desc)?;
-
         Ok(())
     }
 
-    /// Writes the corpus in the split form to the specified directory.
-    pub fn write_split<P: AsRef<Path>>(
+    /// Returns the records of the symtypes file with the specified path.
+    ///
+    /// Each record consists of a type or export name and the tokens describing it, as found in the
+    /// file. The records are returned in no particular order. Returns `None` if the corpus contains
+    /// no file with the given path.
+    pub fn file_records<P: AsRef<Path>>(
         &self,
         path: P,
-        job_slots: &mut JobSlots,
-    ) -> Result<(), Error> {
-        self.write_split_buffer(&mut DirectoryWriter::new_file(path), job_slots)
+    ) -> Option<impl Iterator<Item = (&str, &[Token])>> {
+        self.files.get(path.as_ref()).map(|file| {
+            file.records
+                .iter()
+                .map(|(name, tokens)| (name.as_str(), tokens.as_slice()))
+        })
     }
 
-    /// Writes the corpus in the split form to the provided output stream factory.
-    pub fn write_split_buffer<W: Write, WG: WriteGenerator<W> + Send>(
-        &self,
-        dir_writer: WG,
-        job_slots: &mut JobSlots,
-    ) -> Result<(), Error> {
-        let err_desc = "Failed to write a split record";
-        let dir_writer = Mutex::new(dir_writer);
+    /// Returns whether the names of all exports in the corpus start with the given prefix.
+    ///
+    /// An empty corpus is considered to have no symbol prefix.
+    pub fn has_symbol_prefix(&self, prefix: &str) -> bool {
+        !self.exports.is_empty() && self.exports.keys().all(|name| name.starts_with(prefix))
+    }
 
-        let works = self.files.values().collect::<Vec<_>>();
+    /// Strips the given prefix from the names of all exports in the corpus.
+    ///
+    /// Some architectures emit export names with a prefix, for instance, `_`. Removing the prefix
+    /// allows to compare the corpus with data from an architecture that doesn't use it. Exports
+    /// without the prefix are kept unchanged. Returns <code>Err([Error])</code> if a stripped name
+    /// clashes with another export, in which case the corpus is left unmodified.
+    pub fn strip_symbol_prefix(&mut self, prefix: &str) -> Result<(), Error> {
+        if prefix.is_empty() {
+            return Ok(());
+        }
 
-        burst::run_jobs(
-            |work_idx| {
-                let symfile = works[work_idx].as_ref();
+        let strip = |name: &str| -> Option<String> {
+            if !is_export_name(name) {
+                return None;
+            }
+            match name.strip_prefix(prefix) {
+                Some(stripped) if !stripped.is_empty() => Some(stripped.to_string()),
+                _ => None,
+            }
+        };
 
-                // Sort all types in the file.
-                let mut sorted_types = symfile.records.iter().collect::<Vec<_>>();
-                sorted_types.sort_by_cached_key(|&(name, _)| (is_export_name(name), name));
+        // Check for clashes first, so the corpus remains unchanged on error.
+        let mut names = self.exports.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            if let Some(stripped) = strip(name)
+                && self.exports.contains_key(&stripped)
+            {
+                return Err(Error::new_parse(format!(
+                    "Export '{}' clashes with '{}' after stripping the symbol prefix '{}'",
+                    name, stripped, prefix
+                )));
+            }
+        }
 
-                // Create an output file.
-                let mut writer = {
-                    let mut dir_writer = dir_writer.lock().unwrap();
-                    dir_writer.create(&symfile.path)?
-                };
+        // Rename the records in all files.
+        for symfile_rc in self.files.values_mut() {
+            let records = symfile_rc
+                .records
+                .iter()
+                .map(|(name, tokens_rc)| {
+                    (
+                        strip(name).unwrap_or_else(|| name.clone()),
+                        Arc::clone(tokens_rc),
+                    )
+                })
+                .collect();
+            let export_hashes = symfile_rc
+                .export_hashes
+                .iter()
+                .map(|(name, &hash)| (strip(name).unwrap_or_else(|| name.clone()), hash))
+                .collect();
+            *symfile_rc = Arc::new(SymtypesFile {
+                path: symfile_rc.path.clone(),
+                records,
+                export_hashes,
+                metadata: symfile_rc.metadata.clone(),
+            });
+        }
 
-                // Write all types into the output file.
-                for (name, tokens_rc) in sorted_types {
-                    write!(writer, "{}", name).map_io_err(err_desc)?;
-                    for token in tokens_rc.iter() {
-                        write!(writer, " {}", token.as_str()).map_io_err(err_desc)?;
-                    }
-                    writeln!(writer).map_io_err(err_desc)?;
-                }
+        // Point the renamed exports to the new files.
+        self.exports = mem::take(&mut self.exports)
+            .into_iter()
+            .map(|(name, symfile_rc)| {
+                (
+                    strip(&name).unwrap_or(name),
+                    Arc::clone(&self.files[&symfile_rc.path]),
+                )
+            })
+            .collect();
 
-                // Close the file.
-                writer.flush().map_io_err(err_desc)?;
-                let mut dir_writer = dir_writer.lock().unwrap();
-                dir_writer.close(writer);
+        // Move the type variants of the renamed exports to their new buckets.
+        let mut renamed_types = Vec::new();
+        for types in &mut self.types {
+            let names = types
+                .keys()
+                .filter(|name| strip(name).is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            for name in names {
+                let variants = types.remove(&name).unwrap();
+                renamed_types.push((strip(&name).unwrap(), variants));
+            }
+        }
+        for (name, mut variants) in renamed_types {
+            self.types[type_bucket_idx(&name)]
+                .entry(name)
+                .or_default()
+                .append(&mut variants);
+        }
 
-                Ok(())
-            },
-            works.len(),
-            job_slots,
-        )
+        Ok(())
     }
 
-    /// Compares the definitions of the given symbol in two files.
-    ///
-    /// If the immediate definition of the symbol differs between the two files then it gets added
-    /// in `changes`. The `export` parameter identifies the top-level exported symbol affected by
-    /// the change.
+    /// Retains only the exports for which the predicate `f` returns `true`.
     ///
-    /// The specified symbol is added to `processed_types`, if it's not already present, and all its
-    /// type references get recursively processed in the same way.
-    fn compare_types<'a>(
-        symfile: &'a SymtypesFile,
-        other_symfile: &'a SymtypesFile,
-        name: &'a str,
-        export: &'a str,
-        changes: &Mutex<CompareChangedTypes<'a>>,
-        processed: &mut CompareFileTypes<'a>,
-    ) {
-        // See if the symbol was already processed.
-        if processed.get(name).is_some() {
-            return;
-        }
-        processed.insert(name); // [2]
-
-        // Look up how the symbol is defined in each file.
-        // INVARIANT: Each type reference is guaranteed to have a corresponding definition.
-        let tokens = symfile.records.get(name).unwrap().as_ref();
-        let other_tokens = other_symfile.records.get(name).unwrap().as_ref();
+    /// The type data of the removed exports is kept in the corpus, but it is no longer considered
+    /// by any operation that starts from the exports, such as a comparison.
+    pub fn retain_exports<F: FnMut(&str) -> bool>(&mut self, mut f: F) {
+        self.exports.retain(|name, _| f(name));
+    }
 
-        // Compare the immediate tokens.
-        let is_equal = tokens.len() == other_tokens.len()
-            && zip(tokens, other_tokens).all(|(token, other_token)| token == other_token);
-        if !is_equal {
-            let mut changes = changes.lock().unwrap();
-            changes
-                .entry((name, tokens, other_tokens))
-                .or_default()
-                .push(export);
-        }
+    /// Reduces the memory used by the corpus, in preparation for keeping it for a longer time.
+    ///
+    /// Identical `Tokens` stored separately in different type buckets are merged into one shared
+    /// allocation and all collections are shrunk to fit their content. The data of the corpus is
+    /// left unchanged. The estimated memory savings are reported at the debug level.
+    pub fn optimize(&mut self) {
+        let maybe_size = log::enabled(Level::Debug).then(|| self.estimated_size());
+
+        // Merge identical tokens across all buckets and shrink the type collections.
+        let mut unique_tokens: HashSet<Arc<Tokens>> = HashSet::new();
+        let mut canonicalize = |tokens_rc: &mut Arc<Tokens>| match unique_tokens.get(&**tokens_rc) {
+            Some(unique_rc) => *tokens_rc = Arc::clone(unique_rc),
+            None => {
+                if tokens_rc.len() < tokens_rc.capacity() {
+                    *tokens_rc = Arc::new(tokens_rc.to_vec());
+                }
+                unique_tokens.insert(Arc::clone(tokens_rc));
+            }
+        };
 
-        // Compare recursively same referenced types. This can be done trivially if the tokens are
-        // equal. If they are not, try hard (and slowly) to find any matching types.
-        if is_equal {
-            for token in tokens {
-                if let Token::TypeRef(ref_name) = token {
-                    Self::compare_types(
-                        symfile,
-                        other_symfile,
-                        ref_name.as_str(),
-                        export,
-                        changes,
-                        processed,
-                    );
+        // The records in files share their tokens with the type buckets. Remember what each original
+        // allocation was merged into so the records can be redirected without hashing them again.
+        // The original is kept alive to prevent its address from being reused.
+        let mut merged: HashMap<*const Tokens, (Arc<Tokens>, Arc<Tokens>)> = HashMap::new();
+        for types in &mut self.types {
+            for variants in types.values_mut() {
+                for tokens_rc in variants.iter_mut() {
+                    let orig_rc = Arc::clone(tokens_rc);
+                    canonicalize(tokens_rc);
+                    merged.insert(Arc::as_ptr(&orig_rc), (orig_rc, Arc::clone(tokens_rc)));
                 }
+                variants.shrink_to_fit();
             }
-        } else {
-            for token in tokens {
-                if let Token::TypeRef(ref_name) = token {
-                    for other_token in other_tokens {
-                        if let Token::TypeRef(other_ref_name) = other_token
-                            && ref_name == other_ref_name
-                        {
-                            Self::compare_types(
-                                symfile,
-                                other_symfile,
-                                ref_name.as_str(),
-                                export,
-                                changes,
-                                processed,
-                            );
-                            break;
-                        }
-                    }
+            types.shrink_to_fit();
+        }
+
+        // Release the references held by the exports so that the files can be updated in place.
+        let export_paths = mem::take(&mut self.exports)
+            .into_iter()
+            .map(|(name, symfile_rc)| (name, symfile_rc.path.clone()))
+            .collect::<Vec<_>>();
+
+        // Point the records in all files to the merged tokens.
+        for symfile_rc in self.files.values_mut() {
+            let symfile = Arc::make_mut(symfile_rc);
+            for tokens_rc in symfile.records.values_mut() {
+                match merged.get(&Arc::as_ptr(tokens_rc)) {
+                    Some((_, merged_rc)) => *tokens_rc = Arc::clone(merged_rc),
+                    None => canonicalize(tokens_rc),
                 }
             }
+            symfile.records.shrink_to_fit();
+            symfile.export_hashes.shrink_to_fit();
+        }
+        self.files.shrink_to_fit();
+
+        // Point the exports back to the files.
+        self.exports = export_paths
+            .into_iter()
+            .map(|(name, path)| (name, Arc::clone(&self.files[&path])))
+            .collect();
+
+        if let Some(size) = maybe_size {
+            let new_size = self.estimated_size();
+            debug!(
+                "Optimized the symtypes corpus from {} to {} bytes, saving {} bytes",
+                size,
+                new_size,
+                size.saturating_sub(new_size)
+            );
         }
     }
 
-    /// Compares the symbols in this corpus with another one.
+    /// Estimates the heap memory used by the collections of the corpus, in bytes.
     ///
-    /// Writes reports about any found changes to the specified files, formatted as requested.
-    /// Returns `Ok` containing a `bool` indicating whether the corpuses are the same, or
-    /// <code>Err([Error])</code> on error.
-    pub fn compare_with<P: AsRef<Path>>(
-        &self,
-        other_symtypes: &SymtypesCorpus,
-        maybe_filter: Option<&Filter>,
-        writers_conf: &[(CompareFormat, P)],
-        job_slots: &mut JobSlots,
-    ) -> Result<bool, Error> {
-        // Materialize all writers.
-        let mut writers = Vec::new();
-        for (format, path) in writers_conf {
-            writers.push((*format, Writer::new_file(path)?));
+    /// Shared `Tokens` are counted only once. Interned token strings are not included.
+    fn estimated_size(&self) -> usize {
+        fn map_size<K, V>(map: &HashMap<K, V>) -> usize {
+            map.capacity() * mem::size_of::<(K, V)>()
         }
 
-        self.compare_with_buffer(other_symtypes, maybe_filter, &mut writers[..], job_slots)
+        let mut seen_tokens = HashSet::new();
+        let mut tokens_size = |tokens_rc: &Arc<Tokens>| {
+            if seen_tokens.insert(Arc::as_ptr(tokens_rc)) {
+                mem::size_of::<Tokens>() + tokens_rc.capacity() * mem::size_of::<Token>()
+            } else {
+                0
+            }
+        };
+
+        let mut size = 0;
+        for types in &self.types {
+            size += map_size(types);
+            for (name, variants) in types {
+                size += name.capacity() + variants.capacity() * mem::size_of::<Arc<Tokens>>();
+                size += variants.iter().map(&mut tokens_size).sum::<usize>();
+            }
+        }
+        size += map_size(&self.files);
+        for symfile in self.files.values() {
+            size += mem::size_of::<SymtypesFile>();
+            size += map_size(&symfile.records) + map_size(&symfile.export_hashes);
+            for (name, tokens_rc) in &symfile.records {
+                size += name.capacity() + tokens_size(tokens_rc);
+            }
+            size += symfile
+                .export_hashes
+                .keys()
+                .map(String::capacity)
+                .sum::<usize>();
+        }
+        size += map_size(&self.exports);
+        size += self.exports.keys().map(String::capacity).sum::<usize>();
+        size
     }
 
-    /// Compares the symbols in this corpus with another one.
+    /// Computes a hash of the symtypes data at the specified location, for validating a cache of
+    /// the corpus loaded from it.
     ///
-    /// Writes reports about any found changes to the provided output streams, formatted as
-    /// requested. Returns `Ok` containing a `bool` indicating whether the corpuses are the same, or
-    /// <code>Err([Error])</code> on error.
-    pub fn compare_with_buffer<W: Write>(
-        &self,
-        other_symtypes: &SymtypesCorpus,
-        maybe_filter: Option<&Filter>,
-        writers: &mut [(CompareFormat, W)],
-        job_slots: &mut JobSlots,
-    ) -> Result<bool, Error> {
-        let err_desc = "Failed to write a comparison result";
-
-        // Track all changed symbols, mapping a symbol name to a boolean. The flag indicates whether
-        // the symbol was modified (true), or was added/removed (false).
-        let mut output_symbols = HashMap::<&str, bool>::new();
+    /// The `path` can point to a single symtypes file or a directory. In the latter case, the hash
+    /// covers the names and contents of all symtypes files in that directory.
+    pub fn hash_source<P: AsRef<Path>>(path: P) -> Result<u64, Error> {
+        let path = path.as_ref();
 
-        // Check for symbols in `self` but not in `other_symtypes`, and vice versa.
-        for (exports_a, exports_b, change) in [
-            (&other_symtypes.exports, &self.exports, "added"),
-            (&self.exports, &other_symtypes.exports, "removed"),
-        ] {
-            let mut changed = exports_a
-                .keys()
-                .filter(|&name| matches_filter(maybe_filter, name) && !exports_b.contains_key(name))
-                .collect::<Vec<_>>();
-            changed.sort();
-            for name in changed {
-                for &mut (format, ref mut writer) in &mut *writers {
-                    if format == CompareFormat::Pretty || format == CompareFormat::Short {
-                        writeln!(writer, "Export '{}' has been {}", name, change)
-                            .map_io_err(err_desc)?
-                    }
-                }
+        let md = fs::metadata(path).map_err(|err| {
+            Error::new_io(
+                format!("Failed to query the path '{}'", path.display()),
+                err,
+            )
+        })?;
 
-                output_symbols.insert(name, false);
+        let mut hasher = SourceHasher::new();
+        if md.is_dir() {
+            let mut symfiles = Vec::new();
+            Self::collect_symfiles(path, Path::new(""), &mut symfiles)?;
+            symfiles.sort();
+            for sub_path in symfiles {
+                hasher.update(sub_path.to_string_lossy().as_bytes());
+                hasher.update_file(&path.join(sub_path))?;
             }
+        } else {
+            hasher.update_file(path)?;
         }
+        Ok(hasher.finish())
+    }
 
-        // Compare symbols that are in both corpuses.
-        let works = self
-            .exports
+    /// Returns the name of a cache file for the symtypes data at the specified location.
+    ///
+    /// The name is derived from the canonical form of `source_path`, which allows a single cache
+    /// directory to hold caches for multiple sources.
+    pub fn cache_file_name<P: AsRef<Path>>(source_path: P) -> Result<String, Error> {
+        let source_path = source_path.as_ref();
+
+        let canonical_path = fs::canonicalize(source_path).map_err(|err| {
+            Error::new_io(
+                format!("Failed to query the path '{}'", source_path.display()),
+                err,
+            )
+        })?;
+        let mut hasher = SourceHasher::new();
+        hasher.update(canonical_path.as_os_str().as_encoded_bytes());
+        Ok(format!("{:016x}.symtypes-cache", hasher.finish()))
+    }
+
+    /// Saves the corpus in a binary cache form to the specified file.
+    ///
+    /// The cache is bound to the symtypes data from which the corpus was loaded, specified by
+    /// `source_path`. See [`SymtypesCorpus::load_cache()`].
+    pub fn save_cache<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        path: P,
+        source_path: Q,
+    ) -> Result<(), Error> {
+        let source_hash = Self::hash_source(source_path)?;
+        self.save_cache_buffer(source_hash, Writer::new_exact_file(path)?)
+    }
+
+    /// Saves the corpus in a binary cache form to the provided output stream.
+    ///
+    /// The `source_hash` identifies the symtypes data from which the corpus was loaded, as
+    /// computed by [`SymtypesCorpus::hash_source()`].
+    pub fn save_cache_buffer<W: Write>(&self, source_hash: u64, writer: W) -> Result<(), Error> {
+        let mut cache = CacheWriter::new(writer);
+
+        // Collect all strings and token sequences, assigning each an index in the order of their
+        // first occurrence. Token sequences are identified by their address, which preserves their
+        // sharing between the types and files.
+        let mut strings = CacheTable::<&str, &str>::new();
+        let mut tokens_table = CacheTable::<*const Tokens, &Tokens>::new();
+
+        let mut types = Vec::new();
+        for (type_name, variants) in self.types.iter().flatten() {
+            let variants = variants
+                .iter()
+                .map(|tokens_rc| tokens_table.add(Arc::as_ptr(tokens_rc), tokens_rc))
+                .collect::<Vec<_>>();
+            types.push((strings.add(type_name, type_name), variants));
+        }
+
+        let mut files = Vec::new();
+        let mut file_indices = HashMap::<*const SymtypesFile, u32>::new();
+        for (file_idx, symfile_rc) in self.files.values().enumerate() {
+            file_indices.insert(Arc::as_ptr(symfile_rc), file_idx as u32);
+            let path = symfile_rc.path.to_str().ok_or_else(|| {
+                Error::new_parse(format!(
+                    "Path '{}' cannot be stored in a symtypes cache",
+                    symfile_rc.path.display()
+                ))
+            })?;
+            let records = symfile_rc
+                .records
+                .iter()
+                .map(|(name, tokens_rc)| {
+                    (
+                        strings.add(name, name),
+                        tokens_table.add(Arc::as_ptr(tokens_rc), tokens_rc),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let metadata = symfile_rc
+                .metadata
+                .iter()
+                .map(|(key, value)| (strings.add(key, key), strings.add(value, value)))
+                .collect::<Vec<_>>();
+            files.push((strings.add(path, path), records, metadata));
+        }
+
+        let exports = self
+            .exports
             .iter()
-            .filter(|&(name, _)| matches_filter(maybe_filter, name))
+            .map(|(name, symfile_rc)| {
+                (
+                    strings.add(name, name),
+                    *file_indices.get(&Arc::as_ptr(symfile_rc)).unwrap(),
+                )
+            })
             .collect::<Vec<_>>();
-        let changes = Mutex::new(CompareChangedTypes::new());
 
-        burst::run_jobs(
-            |work_idx| {
-                let (name, symfile_rc) = works[work_idx];
+        let tokens_list = tokens_table
+            .items
+            .iter()
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .map(|token| match token {
+                        Token::Atom(word) => (0, strings.add(word, word)),
+                        Token::TypeRef(ref_name) => (1, strings.add(ref_name, ref_name)),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
 
-                if let Some(other_symfile_rc) = other_symtypes.exports.get(name) {
-                    let mut processed = CompareFileTypes::new();
-                    Self::compare_types(
-                        symfile_rc.as_ref(),
-                        other_symfile_rc.as_ref(),
-                        name,
-                        name,
-                        &changes,
-                        &mut processed,
-                    );
-                };
+        // Write the header and all sections.
+        cache.write_bytes(CACHE_MAGIC)?;
+        cache.write_u32(CACHE_VERSION)?;
+        cache.write_u64(source_hash)?;
 
-                Ok(())
-            },
-            works.len(),
-            job_slots,
-        )?;
+        cache.write_len(strings.items.len())?;
+        for string in strings.items {
+            cache.write_len(string.len())?;
+            cache.write_bytes(string.as_bytes())?;
+        }
 
-        // Format and output collected changes.
-        let changes = changes.into_inner().unwrap(); // Get the inner HashMap.
-        let mut changes = changes.into_iter().collect::<Vec<_>>();
-        changes.iter_mut().for_each(|(_, exports)| exports.sort());
-        changes.sort();
+        cache.write_len(tokens_list.len())?;
+        for tokens in tokens_list {
+            cache.write_len(tokens.len())?;
+            for (kind, string_idx) in tokens {
+                cache.write_u8(kind)?;
+                cache.write_u32(string_idx)?;
+            }
+        }
 
-        let mut add_separator = false;
-        for ((name, tokens, other_tokens), exports) in changes {
-            for &mut (format, ref mut writer) in &mut *writers {
-                if format == CompareFormat::Pretty || format == CompareFormat::Short {
-                    let is_short = format == CompareFormat::Short;
+        cache.write_len(types.len())?;
+        for (name_idx, variants) in types {
+            cache.write_u32(name_idx)?;
+            cache.write_len(variants.len())?;
+            for tokens_idx in variants {
+                cache.write_u32(tokens_idx)?;
+            }
+        }
 
-                    // Add an empty line to separate individual changes.
-                    if add_separator {
-                        writeln!(writer).map_io_err(err_desc)?;
-                    }
+        cache.write_len(files.len())?;
+        for (path_idx, records, metadata) in files {
+            cache.write_u32(path_idx)?;
+            cache.write_len(records.len())?;
+            for (name_idx, tokens_idx) in records {
+                cache.write_u32(name_idx)?;
+                cache.write_u32(tokens_idx)?;
+            }
+            cache.write_len(metadata.len())?;
+            for (key_idx, value_idx) in metadata {
+                cache.write_u32(key_idx)?;
+                cache.write_u32(value_idx)?;
+            }
+        }
 
-                    // Output the affected exports, limit the list if the short format is selected.
-                    writeln!(
-                        writer,
-                        "The following '{}' exports are different:",
-                        exports.len()
-                    )
-                    .map_io_err(err_desc)?;
-                    let take_count = if is_short { 10 } else { exports.len() };
-                    for export in exports.iter().take(take_count) {
-                        writeln!(writer, " {}", export).map_io_err(err_desc)?;
-                    }
-                    if is_short && take_count < exports.len() {
-                        writeln!(writer, " <...>").map_io_err(err_desc)?;
-                    }
-                    writeln!(writer).map_io_err(err_desc)?;
+        cache.write_len(exports.len())?;
+        for (name_idx, file_idx) in exports {
+            cache.write_u32(name_idx)?;
+            cache.write_u32(file_idx)?;
+        }
 
-                    // Output the changed type.
-                    let is_unknown = is_unknown_declaration(name, tokens);
-                    let is_other_unknown = is_unknown_declaration(name, other_tokens);
-                    if is_unknown != is_other_unknown {
-                        let forms = ["a definition", "a forward declaration"];
-                        writeln!(
-                            writer,
-                            "because '{}' changed from {} to {}",
-                            name, forms[is_unknown as usize], forms[is_other_unknown as usize],
-                        )
-                        .map_io_err(err_desc)?;
-                    } else {
-                        writeln!(writer, "because of a changed '{}':", name)
-                            .map_io_err(err_desc)?;
-                        write_type_diff(tokens, other_tokens, writer.by_ref())?;
-                    }
-                }
+        cache.flush()
+    }
+
+    /// Loads a corpus from a binary cache in the specified file.
+    ///
+    /// Returns `Ok(None)` if the cache file doesn't exist, if it was written by an incompatible
+    /// version of the cache format, or if it doesn't match the current symtypes data at
+    /// `source_path`. Returns <code>Err([Error])</code> if the cache cannot be read or is
+    /// corrupted.
+    pub fn load_cache<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        source_path: Q,
+    ) -> Result<Option<Self>, Error> {
+        let path = path.as_ref();
+
+        let file = match PathFile::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!("No symtypes cache is found at '{}'", path.display());
+                return Ok(None);
             }
-            for export in exports {
-                output_symbols.insert(export, true);
+            Err(err) => {
+                return Err(Error::new_io(
+                    format!("Failed to open the file '{}'", path.display()),
+                    err,
+                ));
             }
-            add_separator = true;
+        };
+        let source_hash = Self::hash_source(source_path)?;
+        Self::load_cache_buffer(source_hash, file)
+    }
+
+    /// Loads a corpus from a binary cache provided by the reader.
+    ///
+    /// Returns `Ok(None)` if the cache was written by an incompatible version of the cache format,
+    /// or if it doesn't match the `source_hash`. Returns <code>Err([Error])</code> if the cache
+    /// cannot be read or is corrupted.
+    pub fn load_cache_buffer<R: Read>(source_hash: u64, reader: R) -> Result<Option<Self>, Error> {
+        let mut cache = CacheReader::new(reader);
+
+        // Validate the header.
+        let mut magic = [0; CACHE_MAGIC.len()];
+        cache.read_bytes(&mut magic)?;
+        if magic != *CACHE_MAGIC {
+            return Err(Error::new_parse("Invalid symtypes cache header"));
+        }
+        let version = cache.read_u32()?;
+        if version != CACHE_VERSION {
+            debug!(
+                "Ignoring a symtypes cache with version '{}', expected '{}'",
+                version, CACHE_VERSION
+            );
+            return Ok(None);
+        }
+        if cache.read_u64()? != source_hash {
+            debug!("Ignoring a symtypes cache that doesn't match its source data");
+            return Ok(None);
         }
 
-        // Format symbol lists.
-        let mut sorted_output_symbols = output_symbols
-            .iter()
-            .map(|(&k, &v)| (k, v))
-            .collect::<Vec<_>>();
-        sorted_output_symbols.sort();
-        for (name, modified) in sorted_output_symbols {
-            for &mut (format, ref mut writer) in &mut *writers {
-                if format == CompareFormat::Symbols
-                    || (format == CompareFormat::ModSymbols && modified)
-                {
-                    writeln!(writer, "{}", name).map_io_err(err_desc)?;
+        // Read all sections.
+        let mut strings = Vec::new();
+        for _ in 0..cache.read_u32()? {
+            let len = cache.read_u32()? as usize;
+            let mut bytes = vec![0; len];
+            cache.read_bytes(&mut bytes)?;
+            let string = String::from_utf8(bytes)
+                .map_err(|_| Error::new_parse("Invalid string in the symtypes cache"))?;
+            strings.push(string);
+        }
+
+        let mut tokens_list = Vec::new();
+        for _ in 0..cache.read_u32()? {
+            let mut tokens = Tokens::new();
+            for _ in 0..cache.read_u32()? {
+                let kind = cache.read_u8()?;
+                let string = cache.read_item(&strings)?;
+                tokens.push(match kind {
+                    0 => Token::new_atom(string),
+                    1 => Token::new_typeref(string),
+                    _ => return Err(Error::new_parse("Invalid token in the symtypes cache")),
+                });
+            }
+            tokens_list.push(Arc::new(tokens));
+        }
+
+        let mut symtypes = SymtypesCorpus::new();
+        for _ in 0..cache.read_u32()? {
+            let type_name = cache.read_item(&strings)?;
+            let mut variants = TypeVariants::new();
+            for _ in 0..cache.read_u32()? {
+                variants.push(Arc::clone(cache.read_item(&tokens_list)?));
+            }
+            symtypes.types[type_bucket_idx(type_name)].insert(type_name.clone(), variants);
+        }
+
+        let mut files = Vec::new();
+        for _ in 0..cache.read_u32()? {
+            let path = PathBuf::from(cache.read_item(&strings)?);
+            let mut records = FileRecords::new();
+            for _ in 0..cache.read_u32()? {
+                let name = cache.read_item(&strings)?;
+                let tokens_rc = cache.read_item(&tokens_list)?;
+                records.insert(name.clone(), Arc::clone(tokens_rc));
+            }
+            let mut metadata = FileMetadata::new();
+            for _ in 0..cache.read_u32()? {
+                let key = cache.read_item(&strings)?;
+                let value = cache.read_item(&strings)?;
+                metadata.insert(key.clone(), value.clone());
+            }
+            let symfile_rc = Arc::new(SymtypesFile::new(path, records).with_metadata(metadata));
+            symtypes
+                .files
+                .insert(symfile_rc.path.clone(), Arc::clone(&symfile_rc));
+            files.push(symfile_rc);
+        }
+
+        for _ in 0..cache.read_u32()? {
+            let name = cache.read_item(&strings)?;
+            let symfile_rc = cache.read_item(&files)?;
+            symtypes
+                .exports
+                .insert(name.clone(), Arc::clone(symfile_rc));
+        }
+
+        Ok(Some(symtypes))
+    }
+
+    /// Writes the corpus in the consolidated form to the specified file, with files and records
+    /// ordered according to `order`.
+    pub fn write_consolidated<P: AsRef<Path>>(
+        &self,
+        path: P,
+        order: ConsolidateOrder,
+    ) -> Result<(), Error> {
+        self.write_consolidated_buffer(Writer::new_file(path)?, order)
+    }
+
+    /// Writes the corpus in the consolidated form to the provided output stream, with files and
+    /// records ordered according to `order`.
+    pub fn write_consolidated_buffer<W: Write>(
+        &self,
+        writer: W,
+        order: ConsolidateOrder,
+    ) -> Result<(), Error> {
+        self.write_consolidated_inner(writer, order, None)
+    }
+
+    /// Writes the corpus in the consolidated form with file metadata to the specified file, with
+    /// files and records ordered according to `order`.
+    ///
+    /// See [`SymtypesCorpus::write_consolidated_with_metadata_buffer()`] for details.
+    pub fn write_consolidated_with_metadata<P: AsRef<Path>>(
+        &self,
+        path: P,
+        order: ConsolidateOrder,
+        metadata: &FileMetadata,
+    ) -> Result<(), Error> {
+        self.write_consolidated_with_metadata_buffer(Writer::new_file(path)?, order, metadata)
+    }
+
+    /// Writes the corpus in the consolidated form with file metadata to the provided output stream,
+    /// with files and records ordered according to `order`.
+    ///
+    /// The output starts with a format header and each file header is followed by the metadata of
+    /// the file. The metadata consists of the values loaded with the file, the `object` path
+    /// derived from the file path if it is not known, and the given `metadata` common to all files,
+    /// which takes precedence.
+    pub fn write_consolidated_with_metadata_buffer<W: Write>(
+        &self,
+        writer: W,
+        order: ConsolidateOrder,
+        metadata: &FileMetadata,
+    ) -> Result<(), Error> {
+        self.write_consolidated_inner(writer, order, Some(metadata))
+    }
+
+    /// Writes the corpus in the consolidated form to the provided output stream, including file
+    /// metadata if `maybe_metadata` is specified.
+    fn write_consolidated_inner<W: Write>(
+        &self,
+        mut writer: W,
+        order: ConsolidateOrder,
+        maybe_metadata: Option<&FileMetadata>,
+    ) -> Result<(), Error> {
+        let err_desc = "Failed to write a consolidated record";
+
+        // Write the format header.
+        if maybe_metadata.is_some() {
+            writeln!(writer, "{}", FORMAT_HEADER).map_io_err(err_desc)?;
+        }
+
+        // Track which records are currently active, mapping a type name to its tokens.
+        let mut active_types = HashMap::<&String, &Arc<Tokens>>::new();
+
+        // Sort all files in the corpus by their path. The old modversions script compared the paths
+        // as plain strings without the extension.
+        let mut sorted_files = self.files.values().collect::<Vec<_>>();
+        match order {
+            ConsolidateOrder::OldScript => sorted_files.sort_by_cached_key(|&symfile_rc| {
+                symfile_rc.path.with_extension("").into_os_string()
+            }),
+            _ => sorted_files.sort_by_key(|&symfile_rc| &symfile_rc.path),
+        }
+
+        // Process the sorted files and add their types to the output.
+        let mut add_separator = false;
+        for symfile_rc in sorted_files {
+            // Add an empty line to separate individual files.
+            if add_separator {
+                writeln!(writer).map_io_err(err_desc)?;
+            } else {
+                add_separator = true;
+            }
+
+            // Write the file, recording which of its types become active.
+            write_consolidated_file(
+                symfile_rc,
+                order,
+                maybe_metadata,
+                |name, tokens_rc| {
+                    Ok(match active_types.entry(name) {
+                        Occupied(mut active_type_entry) => {
+                            if *active_type_entry.get() != tokens_rc {
+                                active_type_entry.insert(tokens_rc);
+                                true
+                            } else {
+                                false
+                            }
+                        }
+                        Vacant(active_type_entry) => {
+                            active_type_entry.insert(tokens_rc);
+                            true
+                        }
+                    })
+                },
+                writer.by_ref(),
+            )?;
+        }
+
+        // The old modversions script terminated also the last file with an empty line.
+        if order == ConsolidateOrder::OldScript && add_separator {
+            writeln!(writer).map_io_err(err_desc)?;
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(())
+    }
+
+    /// Consolidates split symtypes data from one or more directories and writes the result to the
+    /// specified file, without keeping the whole corpus in memory.
+    ///
+    /// See [`SymtypesCorpus::write_consolidated_low_memory_buffer()`] for details.
+    pub fn write_consolidated_low_memory<P, Q, W>(
+        &self,
+        paths: &[P],
+        on_duplicate: DuplicatePolicy,
+        order: ConsolidateOrder,
+        maybe_metadata: Option<&FileMetadata>,
+        warnings: W,
+        path: Q,
+    ) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        W: WarningsSink + Send,
+    {
+        self.write_consolidated_low_memory_buffer(
+            paths,
+            on_duplicate,
+            order,
+            maybe_metadata,
+            warnings,
+            Writer::new_file(path)?,
+        )
+    }
+
+    /// Consolidates split symtypes data from one or more directories and writes the result to the
+    /// provided output stream, without keeping the whole corpus in memory.
+    ///
+    /// The output is the same as if the data was loaded by
+    /// [`SymtypesCorpus::load_split_multiple()`] and then written in the consolidated form, with
+    /// file metadata if `maybe_metadata` is specified. However, the files are loaded and written
+    /// one by one, and only the active variant of each type is tracked, in an append-only on-disk
+    /// store with an in-memory index of the variant hashes. This bounds the memory use at the cost
+    /// of speed.
+    ///
+    /// The corpus itself is not modified, it only provides the loading settings, such as the
+    /// keep-going mode and the duplicate export policy.
+    pub fn write_consolidated_low_memory_buffer<P, W, WS>(
+        &self,
+        paths: &[P],
+        on_duplicate: DuplicatePolicy,
+        order: ConsolidateOrder,
+        maybe_metadata: Option<&FileMetadata>,
+        mut warnings: WS,
+        mut writer: W,
+    ) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        W: Write,
+        WS: WarningsSink + Send,
+    {
+        let err_desc = "Failed to write a consolidated record";
+
+        // Collect all files and sort them in the output order.
+        let mut works = Self::collect_split_works(paths, on_duplicate)?;
+        match order {
+            ConsolidateOrder::OldScript => works.sort_by_cached_key(|(_, _, record_sub_path)| {
+                record_sub_path.with_extension("").into_os_string()
+            }),
+            _ => works.sort_by(|(_, _, record_sub_path), (_, _, other_record_sub_path)| {
+                record_sub_path.cmp(other_record_sub_path)
+            }),
+        }
+
+        // Track the exports of already processed files to detect duplicates, each pointing to
+        // a file that records only its path.
+        let mut processed = SymtypesCorpus::new();
+        processed.set_keep_going(self.keep_going);
+        processed.set_duplicate_exports(self.duplicate_exports);
+
+        let mut spill = VariantSpill::new()?;
+        let mut errors = Vec::new();
+
+        // Write the format header.
+        if maybe_metadata.is_some() {
+            writeln!(writer, "{}", FORMAT_HEADER).map_io_err(err_desc)?;
+        }
+
+        // Load and write the files one by one.
+        let mut add_separator = false;
+        for (root, sub_path, record_sub_path) in &works {
+            let load_context =
+                LoadContext::from(&processed, LoadKind::Simple, WarningsRef(&mut warnings));
+            let result = Self::load_symfile_as(root, sub_path, record_sub_path, &load_context);
+            let (_, new_exports, new_files) = load_context.into_inner();
+            if let Err(err) = result {
+                if !self.keep_going {
+                    return Err(err);
                 }
+                errors.push(err);
+                continue;
+            }
+
+            // INVARIANT: A successful load of a single file adds exactly that file.
+            let symfile_rc = new_files.into_values().next().unwrap();
+
+            // Add an empty line to separate individual files.
+            if add_separator {
+                writeln!(writer).map_io_err(err_desc)?;
+            } else {
+                add_separator = true;
+            }
+
+            write_consolidated_file(
+                &symfile_rc,
+                order,
+                maybe_metadata,
+                |name, tokens_rc| spill.update(name, tokens_rc),
+                writer.by_ref(),
+            )?;
+
+            let path_rc = Arc::new(SymtypesFile::new(
+                symfile_rc.path.clone(),
+                FileRecords::new(),
+            ));
+            for name in new_exports.into_keys() {
+                processed.exports.insert(name, Arc::clone(&path_rc));
             }
         }
 
-        for (_, writer) in &mut *writers {
-            writer.flush().map_io_err(err_desc)?;
+        if !errors.is_empty() {
+            return Err(Error::new_multiple(errors));
+        }
+
+        // The old modversions script terminated also the last file with an empty line.
+        if order == ConsolidateOrder::OldScript && add_separator {
+            writeln!(writer).map_io_err(err_desc)?;
         }
 
-        Ok(output_symbols.is_empty())
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(())
+    }
+
+    /// Writes the corpus in the split form to the specified directory.
+    pub fn write_split<P: AsRef<Path>>(
+        &self,
+        path: P,
+        job_slots: &mut JobSlots,
+    ) -> Result<(), Error> {
+        self.write_split_buffer(&mut DirectoryWriter::new_file(path), job_slots)
+    }
+
+    /// Writes the corpus in the split form to the provided output stream factory.
+    ///
+    /// The files are handed out to the workers in a stable order, sorted by their path. The content
+    /// of each file does not depend on the number of workers.
+    pub fn write_split_buffer<W: Write, WG: WriteGenerator<W> + Send>(
+        &self,
+        dir_writer: WG,
+        job_slots: &mut JobSlots,
+    ) -> Result<(), Error> {
+        let err_desc = "Failed to write a split record";
+        let dir_writer = Mutex::new(dir_writer);
+
+        // Process the files in a stable order, sorted by their path.
+        let mut works = self.files.values().collect::<Vec<_>>();
+        works.sort_by(|a, b| a.path.cmp(&b.path));
+
+        burst::run_jobs(
+            |work_idx| {
+                let symfile = works[work_idx].as_ref();
+
+                // Create an output file.
+                let mut writer = {
+                    let mut dir_writer = dir_writer.lock().unwrap();
+                    dir_writer.create(&symfile.path)?
+                };
+
+                // Write all types into the output file and close it.
+                write_split_records(&symfile.records, writer.by_ref())?;
+                writer.flush().map_io_err(err_desc)?;
+                let mut dir_writer = dir_writer.lock().unwrap();
+                dir_writer.close(writer);
+
+                Ok(())
+            },
+            works.len(),
+            job_slots,
+        )
+    }
+
+    /// Extracts the files matching any of the shell wildcard `patterns` from the consolidated
+    /// symtypes file at `path` and writes them in the split form to the specified directory.
+    ///
+    /// The special path "-" reads the data from the standard input. See
+    /// [`SymtypesCorpus::split_selected_buffer()`] for details.
+    pub fn split_selected<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        patterns: &[String],
+        output: Q,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        let mut dir_writer = DirectoryWriter::new_file(output);
+
+        if path == Path::new("-") {
+            return Self::split_selected_buffer(
+                path,
+                io::stdin().lock(),
+                patterns,
+                &mut dir_writer,
+            );
+        }
+
+        let file = PathFile::open(path).map_err(|err| {
+            Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+        })?;
+        Self::split_selected_buffer(path, file, patterns, &mut dir_writer)
+    }
+
+    /// Extracts the files matching any of the shell wildcard `patterns` from consolidated symtypes
+    /// data read from the specified reader and writes them in the split form to the provided
+    /// output stream factory.
+    ///
+    /// The data is scanned in a single pass without loading it into a corpus. Only the last
+    /// definition of each type and the records of the current file are kept in memory. Each
+    /// matching file is written as soon as its section ends, with the same content as produced by
+    /// [`SymtypesCorpus::write_split_buffer()`]. The `path` should point to the consolidated file
+    /// name, indicating the origin of the data.
+    ///
+    /// Returns <code>Err([Error])</code> if the data is malformed or no file matches the patterns.
+    pub fn split_selected_buffer<P: AsRef<Path>, R: Read, W: Write, WG: WriteGenerator<W>>(
+        path: P,
+        reader: R,
+        patterns: &[String],
+        mut dir_writer: WG,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        let err_desc = "Failed to write a split record";
+
+        debug!(
+            "Splitting selected symtypes files from '{}'",
+            path.display()
+        );
+
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        let mut line_idx = 0;
+
+        let mut has_line = read_next_line(&mut reader, &mut line)?;
+        let has_metadata = has_line && is_format_header(&line);
+        if !has_line || !(has_metadata || is_file_header(&line)) {
+            return Err(Error::new_parse_format(
+                "Expected a consolidated symtypes file, but found an invalid header",
+                path,
+                1,
+                &line,
+            ));
+        }
+        if has_metadata {
+            check_format_header(path, &line)?;
+            has_line = read_next_line(&mut reader, &mut line)?;
+            line_idx += 1;
+        }
+
+        // Track which records are currently active and all per-file overrides for UNKNOWN
+        // definitions, the same way as when loading the data.
+        let mut active_types = LoadActiveTypes::new();
+        let mut local_override = LoadActiveTypes::new();
+
+        let mut records = FileRecords::new();
+        let mut maybe_sub_path: Option<PathBuf> = None;
+        let mut found = false;
+
+        // A helper function to complete the current file and write it out if it is selected.
+        let mut complete_file = |sub_path: Option<PathBuf>,
+                                 mut records: FileRecords,
+                                 local_override: LoadActiveTypes,
+                                 active_types: &LoadActiveTypes|
+         -> Result<(), Error> {
+            let Some(sub_path) = sub_path else {
+                return Ok(());
+            };
+            let sub_path_str = sub_path.to_string_lossy();
+            if !patterns
+                .iter()
+                .any(|pattern| matches_wildcard(&sub_path_str, pattern))
+            {
+                return Ok(());
+            }
+
+            let walk_records = records.keys().map(String::clone).collect::<Vec<_>>();
+            for name in walk_records {
+                Self::complete_file_record(
+                    path,
+                    None,
+                    &name,
+                    true,
+                    &local_override,
+                    active_types,
+                    &mut records,
+                )?;
+            }
+
+            let mut writer = dir_writer.create(&sub_path)?;
+            write_split_records(&records, writer.by_ref())?;
+            writer.flush().map_io_err(err_desc)?;
+            dir_writer.close(writer);
+            found = true;
+            Ok(())
+        };
+
+        while has_line {
+            if line.is_empty() {
+                // Skip empty lines.
+            } else if is_metadata_line(&line) {
+                // Validate file metadata, which has no representation in the split form.
+                parse_metadata_line(
+                    path,
+                    line_idx,
+                    &line,
+                    has_metadata,
+                    &mut FileMetadata::new(),
+                )?;
+            } else if is_file_header(&line) {
+                complete_file(
+                    maybe_sub_path.take(),
+                    mem::take(&mut records),
+                    mem::take(&mut local_override),
+                    &active_types,
+                )?;
+                maybe_sub_path = Some(PathBuf::from(&line[3..line.len() - 3]));
+            } else {
+                let (name, tokens, is_local_override) =
+                    parse_type_record(path, line_idx, &line, true)?;
+                if records.contains_key(&name) {
+                    return Err(Error::new_parse_format(
+                        &format!("Duplicate record '{}'", name),
+                        path,
+                        line_idx + 1,
+                        &line,
+                    ));
+                }
+
+                let tokens_rc = Arc::new(tokens);
+                records.insert(name.clone(), Arc::clone(&tokens_rc));
+                if is_local_override {
+                    local_override.insert(name, (tokens_rc, line_idx));
+                } else {
+                    active_types.insert(name, (tokens_rc, line_idx));
+                }
+            }
+
+            has_line = read_next_line(&mut reader, &mut line)?;
+            line_idx += 1;
+        }
+
+        // Complete the last file.
+        complete_file(maybe_sub_path, records, local_override, &active_types)?;
+
+        if !found {
+            return Err(Error::new_parse(format!(
+                "No file matching {} found",
+                patterns
+                    .iter()
+                    .map(|pattern| format!("'{}'", pattern))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Compares the definitions of the given symbol in two files.
+    ///
+    /// If the immediate definition of the symbol differs between the two files then it gets added
+    /// in `changes`. The `export` parameter identifies the top-level exported symbol affected by
+    /// the change.
+    ///
+    /// The specified symbol is added to `processed_types`, if it's not already present, and all its
+    /// type references get recursively processed in the same way. Types matching
+    /// `maybe_ignore_types` are treated as equal and not descended into. Unless `strict` is set,
+    /// the tokens are compared after normalizing cosmetic differences, see [`normalize_tokens()`].
+    #[allow(clippy::too_many_arguments)]
+    fn compare_types<'a>(
+        symfile: &'a SymtypesFile,
+        other_symfile: &'a SymtypesFile,
+        name: &'a str,
+        export: &'a str,
+        maybe_ignore_types: Option<&Filter>,
+        strict: bool,
+        changes: &Mutex<CompareChangedTypes<'a>>,
+        processed: &mut CompareFileTypes<'a>,
+    ) {
+        // See if the symbol was already processed.
+        if processed.get(name).is_some() {
+            return;
+        }
+        processed.insert(name); // [2]
+
+        // Skip volatile types that are not relevant for the comparison.
+        if maybe_ignore_types.is_some_and(|ignore_types| ignore_types.matches(name)) {
+            return;
+        }
+
+        // Look up how the symbol is defined in each file.
+        // INVARIANT: Each type reference is guaranteed to have a corresponding definition.
+        let tokens = symfile.records.get(name).unwrap().as_ref();
+        let other_tokens = other_symfile.records.get(name).unwrap().as_ref();
+
+        // Compare the immediate tokens.
+        let is_equal = (tokens.len() == other_tokens.len()
+            && zip(tokens, other_tokens).all(|(token, other_token)| token == other_token))
+            || (!strict && normalize_tokens(tokens) == normalize_tokens(other_tokens));
+        if !is_equal {
+            let mut changes = changes.lock().unwrap();
+            changes
+                .entry((name, tokens, other_tokens))
+                .or_default()
+                .push(export);
+        }
+
+        // Compare recursively same referenced types. This can be done trivially if the tokens are
+        // equal. If they are not, try hard (and slowly) to find any matching types.
+        if is_equal {
+            for token in tokens {
+                if let Token::TypeRef(ref_name) = token {
+                    Self::compare_types(
+                        symfile,
+                        other_symfile,
+                        ref_name,
+                        export,
+                        maybe_ignore_types,
+                        strict,
+                        changes,
+                        processed,
+                    );
+                }
+            }
+        } else {
+            for token in tokens {
+                if let Token::TypeRef(ref_name) = token {
+                    for other_token in other_tokens {
+                        if let Token::TypeRef(other_ref_name) = other_token
+                            && ref_name == other_ref_name
+                        {
+                            Self::compare_types(
+                                symfile,
+                                other_symfile,
+                                ref_name,
+                                export,
+                                maybe_ignore_types,
+                                strict,
+                                changes,
+                                processed,
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finds all differences between the symbols in this corpus and another one.
+    ///
+    /// Returns sorted names of added exports, sorted names of removed exports, and all changed
+    /// types together with sorted names of the exports affected by each change. The changes are
+    /// sorted by the type name and tokens.
+    ///
+    /// The `maybe_filter` restricts which exports are compared, and `maybe_type_filter` restricts
+    /// which changed types are reported. Types matching `maybe_ignore_types` are considered equal,
+    /// so neither they nor any types reachable only through them are reported. Differences
+    /// acknowledged by `maybe_suppressions` are omitted.
+    fn find_changes<'a>(
+        &'a self,
+        other_symtypes: &'a SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        maybe_suppressions: Option<&Suppressions>,
+        job_slots: &mut JobSlots,
+    ) -> Result<CompareChanges<'a>, Error> {
+        // Check for symbols in `self` but not in `other_symtypes`, and vice versa.
+        let [added, removed] = [
+            (&other_symtypes.exports, &self.exports, ChangeKind::Added),
+            (&self.exports, &other_symtypes.exports, ChangeKind::Removed),
+        ]
+        .map(|(exports_a, exports_b, kind)| {
+            let mut changed = exports_a
+                .keys()
+                .filter(|&name| {
+                    matches_filter(maybe_filter, name)
+                        && !exports_b.contains_key(name)
+                        && !is_suppressed(maybe_suppressions, name, kind, None)
+                })
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            changed.sort();
+            changed
+        });
+
+        // Compare symbols that are in both corpuses.
+        let works = self
+            .exports
+            .iter()
+            .filter(|&(name, _)| matches_filter(maybe_filter, name))
+            .collect::<Vec<_>>();
+        let changes = Mutex::new(CompareChangedTypes::new());
+
+        burst::run_jobs(
+            |work_idx| {
+                let (name, symfile_rc) = works[work_idx];
+
+                if let Some(other_symfile_rc) = other_symtypes.exports.get(name)
+                    && !symfile_rc.has_same_closure(other_symfile_rc, name)
+                {
+                    let mut processed = CompareFileTypes::new();
+                    Self::compare_types(
+                        symfile_rc.as_ref(),
+                        other_symfile_rc.as_ref(),
+                        name,
+                        name,
+                        maybe_ignore_types,
+                        self.strict,
+                        &changes,
+                        &mut processed,
+                    );
+                };
+
+                Ok(())
+            },
+            works.len(),
+            job_slots,
+        )?;
+
+        let changes = changes.into_inner().unwrap(); // Get the inner HashMap.
+        let mut changes = changes
+            .into_iter()
+            .filter(|&((type_name, _, _), _)| {
+                matches_filter(maybe_type_filter, type_name)
+                    && !is_suppressed(maybe_suppressions, type_name, ChangeKind::Definition, None)
+            })
+            .collect::<Vec<_>>();
+        changes.iter_mut().for_each(|(_, exports)| exports.sort());
+        changes.sort();
+
+        Ok((added, removed, changes))
+    }
+
+    /// Compares the symbols in this corpus with another one.
+    ///
+    /// Returns `Ok` containing a [`ComparisonResult`] that describes all found changes, or
+    /// <code>Err([Error])</code> on error.
+    pub fn compare(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        maybe_suppressions: Option<&Suppressions>,
+        job_slots: &mut JobSlots,
+    ) -> Result<ComparisonResult, Error> {
+        let (added, removed, changes) = self.find_changes(
+            other_symtypes,
+            maybe_filter,
+            maybe_type_filter,
+            maybe_ignore_types,
+            maybe_suppressions,
+            job_slots,
+        )?;
+
+        Ok(ComparisonResult {
+            added_exports: added.into_iter().map(String::from).collect(),
+            removed_exports: removed.into_iter().map(String::from).collect(),
+            changed_types: changes
+                .into_iter()
+                .map(|((name, tokens, other_tokens), exports)| TypeChange {
+                    name: name.to_string(),
+                    old_tokens: tokens.clone(),
+                    new_tokens: other_tokens.clone(),
+                    exports: exports.into_iter().map(String::from).collect(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Determines the status of a comparison of this corpus with another one, without reporting
+    /// any changes.
+    ///
+    /// The filters and suppressions are applied the same way as by
+    /// [`SymtypesCorpus::compare_with_buffer()`], as are the type rules in `maybe_rules`. The
+    /// comparison stops as soon as the first breaking change is found. Returns `Ok` containing a
+    /// [`CompareStatus`] indicating whether the corpuses are the same, differ only in tolerated
+    /// changes, or differ in a breaking way. Returns <code>Err([Error])</code> on error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compare_status(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        job_slots: &mut JobSlots,
+    ) -> Result<CompareStatus, Error> {
+        // Check for symbols in `self` but not in `other_symtypes`, and vice versa.
+        for (exports_a, exports_b, kind) in [
+            (&other_symtypes.exports, &self.exports, ChangeKind::Added),
+            (&self.exports, &other_symtypes.exports, ChangeKind::Removed),
+        ] {
+            if exports_a.keys().any(|name| {
+                matches_filter(maybe_filter, name)
+                    && !exports_b.contains_key(name)
+                    && !is_suppressed(maybe_suppressions, name, kind, None)
+            }) {
+                return Ok(CompareStatus::Breaking);
+            }
+        }
+
+        // Compare symbols that are in both corpuses, skipping all remaining works once a breaking
+        // change is found.
+        let works = self
+            .exports
+            .iter()
+            .filter(|&(name, _)| matches_filter(maybe_filter, name))
+            .collect::<Vec<_>>();
+        let found_breaking = AtomicBool::new(false);
+        let found_tolerated = AtomicBool::new(false);
+
+        burst::run_jobs(
+            |work_idx| {
+                let (name, symfile_rc) = works[work_idx];
+
+                if !found_breaking.load(Ordering::Relaxed)
+                    && let Some(other_symfile_rc) = other_symtypes.exports.get(name)
+                    && !symfile_rc.has_same_closure(other_symfile_rc, name)
+                {
+                    let changes = Mutex::new(CompareChangedTypes::new());
+                    let mut processed = CompareFileTypes::new();
+                    Self::compare_types(
+                        symfile_rc.as_ref(),
+                        other_symfile_rc.as_ref(),
+                        name,
+                        name,
+                        maybe_ignore_types,
+                        self.strict,
+                        &changes,
+                        &mut processed,
+                    );
+                    for &(type_name, _, _) in changes.into_inner().unwrap().keys() {
+                        if !matches_filter(maybe_type_filter, type_name)
+                            || is_suppressed(
+                                maybe_suppressions,
+                                type_name,
+                                ChangeKind::Definition,
+                                None,
+                            )
+                        {
+                            continue;
+                        }
+                        if maybe_rules
+                            .and_then(|rules| rules.is_type_tolerated(type_name))
+                            .is_some()
+                        {
+                            found_tolerated.store(true, Ordering::Relaxed);
+                        } else {
+                            found_breaking.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                }
+
+                Ok(())
+            },
+            works.len(),
+            job_slots,
+        )?;
+
+        Ok(if found_breaking.into_inner() {
+            CompareStatus::Breaking
+        } else if found_tolerated.into_inner() {
+            CompareStatus::Tolerated
+        } else {
+            CompareStatus::Same
+        })
+    }
+
+    /// Compares the symbols in this corpus with another one.
+    ///
+    /// Writes reports about any found changes to the specified files, formatted as requested.
+    /// Returns `Ok` containing a [`CompareStatus`] indicating whether the corpuses are the same,
+    /// differ only in changes tolerated by `maybe_rules`, or differ in a breaking way. Returns
+    /// <code>Err([Error])</code> on error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compare_with<P: AsRef<Path>>(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        diff_options: &DiffOptions,
+        grouping: ExportGrouping,
+        writers_conf: &[(CompareFormat, P)],
+        job_slots: &mut JobSlots,
+    ) -> Result<CompareStatus, Error> {
+        // Materialize all writers.
+        let mut writers = Vec::new();
+        for (format, path) in writers_conf {
+            let mut writer = Writer::new_file(path)?;
+            if *format == CompareFormat::Pretty || *format == CompareFormat::Short {
+                if let Some(header) = &self.maybe_output_header {
+                    writer
+                        .write_all(header.as_bytes())
+                        .map_io_err("Failed to write a comparison result")?;
+                }
+                writer = writer.with_color(self.color);
+            }
+            writers.push((*format, writer));
+        }
+
+        self.compare_with_buffer(
+            other_symtypes,
+            maybe_filter,
+            maybe_type_filter,
+            maybe_ignore_types,
+            maybe_rules,
+            maybe_suppressions,
+            diff_options,
+            grouping,
+            &mut writers[..],
+            job_slots,
+        )
+    }
+
+    /// Compares the symbols in this corpus with another one.
+    ///
+    /// Writes reports about any found changes to the provided output streams, formatted as
+    /// requested. Only exports matching `maybe_filter` are compared, and only changed types matching
+    /// `maybe_type_filter` are reported. Types matching `maybe_ignore_types` are considered equal.
+    /// Differences acknowledged by `maybe_suppressions` are omitted. The exports affected by each
+    /// changed type are listed in the human-readable output grouped as specified by `grouping`.
+    ///
+    /// A changed type matching a tolerating type rule in `maybe_rules` doesn't break the affected
+    /// exports. Such a change is annotated with the rule in the human-readable output and omitted
+    /// from the short output if the rule verdict is [`Verdict::Pass`]. Added and removed exports
+    /// are always breaking. Returns `Ok` containing a [`CompareStatus`] indicating whether the
+    /// corpuses are the same, differ only in tolerated changes, or differ in a breaking way.
+    /// Returns <code>Err([Error])</code> on error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compare_with_buffer<W: Write>(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        diff_options: &DiffOptions,
+        grouping: ExportGrouping,
+        writers: &mut [(CompareFormat, W)],
+        job_slots: &mut JobSlots,
+    ) -> Result<CompareStatus, Error> {
+        let err_desc = "Failed to write a comparison result";
+
+        let (added, removed, changes) = self.find_changes(
+            other_symtypes,
+            maybe_filter,
+            maybe_type_filter,
+            maybe_ignore_types,
+            maybe_suppressions,
+            job_slots,
+        )?;
+
+        // Determine which changed types are tolerated by the severity rules. An export is breaking
+        // if it is affected by at least one change that is not tolerated.
+        let tolerating_rules = changes
+            .iter()
+            .map(|((name, _, _), _)| maybe_rules.and_then(|rules| rules.is_type_tolerated(name)))
+            .collect::<Vec<_>>();
+        let breaking_exports = zip(&changes, &tolerating_rules)
+            .filter(|(_, maybe_rule)| maybe_rule.is_none())
+            .flat_map(|((_, exports), _)| exports.iter().copied())
+            .collect::<HashSet<_>>();
+        let tolerated_count = changes
+            .iter()
+            .flat_map(|(_, exports)| exports.iter().copied())
+            .filter(|export| !breaking_exports.contains(export))
+            .collect::<HashSet<_>>()
+            .len();
+
+        // Write JSON reports.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Json {
+                write_json_comparison(
+                    &added,
+                    &removed,
+                    &changes,
+                    &tolerating_rules,
+                    writer.by_ref(),
+                )?;
+            }
+        }
+
+        // Track all symbols with a breaking change, mapping a symbol name to a boolean. The flag
+        // indicates whether the symbol was modified (true), or was added/removed (false).
+        let mut output_symbols = HashMap::<&str, bool>::new();
+
+        // Start HTML reports.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Html {
+                write_html_header("Comparison of symtypes corpuses", writer.by_ref())?;
+                if added.is_empty() && removed.is_empty() && changes.is_empty() {
+                    writeln!(writer, "<p>No differences found.</p>").map_io_err(err_desc)?;
+                } else if !added.is_empty() || !removed.is_empty() {
+                    writeln!(writer, "<h2>Added and removed exports</h2>").map_io_err(err_desc)?;
+                }
+            }
+        }
+
+        // Report symbols in `self` but not in `other_symtypes`, and vice versa.
+        for (changed, change) in [(added, "added"), (removed, "removed")] {
+            for name in changed {
+                for &mut (format, ref mut writer) in &mut *writers {
+                    if format == CompareFormat::Pretty
+                        || format == CompareFormat::Short
+                        || format == CompareFormat::SideBySide
+                    {
+                        writeln!(
+                            writer,
+                            "Export '{}' has been {}",
+                            display_name(name, self.demangle),
+                            change
+                        )
+                        .map_io_err(err_desc)?
+                    } else if format == CompareFormat::Html {
+                        writeln!(
+                            writer,
+                            "<p>Export <code>{}</code> has been {}</p>",
+                            escape_html(&display_name(name, self.demangle)),
+                            change
+                        )
+                        .map_io_err(err_desc)?
+                    }
+                }
+
+                output_symbols.insert(name, false);
+            }
+        }
+
+        // Add an index to HTML reports, linking each modified export to the changed types that
+        // affect it.
+        if !changes.is_empty()
+            && writers
+                .iter()
+                .any(|(format, _)| *format == CompareFormat::Html)
+        {
+            let mut export_changes = BTreeMap::<&str, Vec<(usize, &str)>>::new();
+            for (change_idx, ((name, _, _), exports)) in changes.iter().enumerate() {
+                for export in exports {
+                    export_changes
+                        .entry(export)
+                        .or_default()
+                        .push((change_idx, name));
+                }
+            }
+
+            for &mut (format, ref mut writer) in &mut *writers {
+                if format == CompareFormat::Html {
+                    write_html_index(&export_changes, self.demangle, writer.by_ref())?;
+                }
+            }
+        }
+
+        // Format and output collected changes. Changes tolerated silently by a rule are omitted
+        // from the short output.
+        let mut add_separators = vec![false; writers.len()];
+        for (change_idx, (((name, tokens, other_tokens), exports), maybe_rule)) in
+            zip(changes, tolerating_rules).enumerate()
+        {
+            let export_groups = self.group_exports(&exports, grouping);
+            let maybe_toleration = maybe_rule.map(|rule| rule.describe_toleration());
+
+            for (&mut (format, ref mut writer), add_separator) in
+                zip(&mut *writers, &mut add_separators)
+            {
+                if format == CompareFormat::Html {
+                    write_html_type_change(
+                        change_idx,
+                        name,
+                        tokens,
+                        other_tokens,
+                        &exports,
+                        maybe_toleration.as_deref(),
+                        diff_options,
+                        self.demangle,
+                        writer.by_ref(),
+                    )?;
+                }
+                if format == CompareFormat::Pretty
+                    || (format == CompareFormat::Short
+                        && maybe_rule.is_none_or(|rule| rule.verdict != Verdict::Pass))
+                    || format == CompareFormat::SideBySide
+                {
+                    let is_short = format == CompareFormat::Short;
+                    let is_side_by_side = format == CompareFormat::SideBySide;
+
+                    // Add an empty line to separate individual changes.
+                    if *add_separator {
+                        writeln!(writer).map_io_err(err_desc)?;
+                    }
+                    *add_separator = true;
+
+                    // Output the affected exports, limit the list if the short format is selected.
+                    writeln!(
+                        writer,
+                        "The following '{}' exports are different:",
+                        exports.len()
+                    )
+                    .map_io_err(err_desc)?;
+                    match &export_groups {
+                        Some(export_groups) => {
+                            for (group, group_exports) in export_groups {
+                                writeln!(writer, " {} ({}):", group, group_exports.len())
+                                    .map_io_err(err_desc)?;
+                                write_export_list(
+                                    group_exports,
+                                    "  ",
+                                    is_short,
+                                    self.demangle,
+                                    writer.by_ref(),
+                                )?;
+                            }
+                        }
+                        None => write_export_list(
+                            &exports,
+                            " ",
+                            is_short,
+                            self.demangle,
+                            writer.by_ref(),
+                        )?,
+                    }
+                    writeln!(writer).map_io_err(err_desc)?;
+
+                    // Output the changed type.
+                    write_type_change(
+                        name,
+                        tokens,
+                        other_tokens,
+                        maybe_toleration.as_deref(),
+                        diff_options,
+                        is_side_by_side,
+                        writer.by_ref(),
+                    )?;
+                    if self.suggest_fixup {
+                        write_fixup_suggestion(name, tokens, other_tokens, writer.by_ref())?;
+                    }
+                }
+            }
+            for export in exports {
+                if breaking_exports.contains(export) {
+                    output_symbols.insert(export, true);
+                }
+            }
+        }
+
+        // Format the one-line summary.
+        if writers
+            .iter()
+            .any(|(format, _)| *format == CompareFormat::Summary)
+        {
+            let unchanged_count = self
+                .exports
+                .keys()
+                .filter(|&name| {
+                    matches_filter(maybe_filter, name)
+                        && other_symtypes.exports.contains_key(name)
+                        && !output_symbols.contains_key(name.as_str())
+                })
+                .count()
+                - tolerated_count;
+            for &mut (format, ref mut writer) in &mut *writers {
+                if format == CompareFormat::Summary {
+                    write_compare_summary(
+                        output_symbols.len(),
+                        tolerated_count,
+                        unchanged_count,
+                        writer.by_ref(),
+                    )?;
+                }
+            }
+        }
+
+        // Format symbol lists.
+        let mut sorted_output_symbols = output_symbols
+            .iter()
+            .map(|(&k, &v)| (k, v))
+            .collect::<Vec<_>>();
+        sorted_output_symbols.sort();
+        for (name, modified) in sorted_output_symbols {
+            for &mut (format, ref mut writer) in &mut *writers {
+                if format == CompareFormat::Symbols
+                    || (format == CompareFormat::ModSymbols && modified)
+                {
+                    writeln!(writer, "{}", name).map_io_err(err_desc)?;
+                }
+            }
+        }
+
+        // Finish HTML reports.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Html {
+                write_html_footer(writer.by_ref())?;
+            }
+        }
+
+        for (_, writer) in &mut *writers {
+            writer.flush().map_io_err(err_desc)?;
+        }
+
+        Ok(if !output_symbols.is_empty() {
+            CompareStatus::Breaking
+        } else if tolerated_count > 0 {
+            CompareStatus::Tolerated
+        } else {
+            CompareStatus::Same
+        })
+    }
+
+    /// Groups the given exports according to `grouping`.
+    ///
+    /// Returns `None` if no grouping is requested, or a map from each group name to its exports
+    /// otherwise.
+    fn group_exports<'a>(
+        &self,
+        exports: &[&'a str],
+        grouping: ExportGrouping,
+    ) -> Option<BTreeMap<String, Vec<&'a str>>> {
+        if grouping == ExportGrouping::None {
+            return None;
+        }
+
+        let mut groups = BTreeMap::<String, Vec<&str>>::new();
+        for &export in exports {
+            // Exports reported as changed always come from this corpus. Files are described
+            // together with their metadata, if any is known.
+            let symfile = self.exports[export].as_ref();
+            let path = symfile.path.as_path();
+            let group = match grouping {
+                ExportGrouping::Module => match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.display().to_string(),
+                    _ => ".".to_string(),
+                },
+                _ if symfile.metadata.is_empty() => path.display().to_string(),
+                _ => format!(
+                    "{} [{}]",
+                    path.display(),
+                    format_metadata(&symfile.metadata)
+                ),
+            };
+            groups.entry(group).or_default().push(export);
+        }
+        Some(groups)
+    }
+
+    /// Compares the symtypes files in this corpus with another one, pairing the files by their
+    /// path.
+    ///
+    /// Writes reports about any found changes to the specified files, formatted as requested.
+    ///
+    /// See [`SymtypesCorpus::compare_files_with_buffer()`] for details.
+    pub fn compare_files_with<P: AsRef<Path>>(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        diff_options: &DiffOptions,
+        writers_conf: &[(CompareFormat, P)],
+    ) -> Result<CompareStatus, Error> {
+        // Materialize all writers.
+        let mut writers = Vec::new();
+        for (format, path) in writers_conf {
+            let mut writer = Writer::new_file(path)?;
+            if *format == CompareFormat::Pretty || *format == CompareFormat::Short {
+                if let Some(header) = &self.maybe_output_header {
+                    writer
+                        .write_all(header.as_bytes())
+                        .map_io_err("Failed to write a comparison result")?;
+                }
+                writer = writer.with_color(self.color);
+            }
+            writers.push((*format, writer));
+        }
+
+        self.compare_files_with_buffer(
+            other_symtypes,
+            maybe_filter,
+            maybe_type_filter,
+            maybe_ignore_types,
+            diff_options,
+            &mut writers[..],
+        )
+    }
+
+    /// Compares the symtypes files in this corpus with another one, pairing the files by their
+    /// path.
+    ///
+    /// A path naming an object file, for instance, `drivers/foo.o`, is paired with the path of its
+    /// symtypes file, `drivers/foo.symtypes`, and vice versa.
+    ///
+    /// Unlike [`SymtypesCorpus::compare_with_buffer()`], all records of each pair of files are
+    /// compared directly, including types that are not reachable from any export. This allows to
+    /// see how the symtypes data of an individual object changed between two builds.
+    ///
+    /// Writes reports about any found changes to the provided output streams, formatted as
+    /// requested. A file of this corpus and a file of the other corpus that cannot be paired by
+    /// their path are reported as moved if they define a common export and at least half of their
+    /// records are identical. The remaining ones are reported as added or removed. Each modified
+    /// file is then reported with a header containing its path, followed by its added, removed and
+    /// modified records. Only exports matching `maybe_filter` and types matching
+    /// `maybe_type_filter` are considered. Types matching `maybe_ignore_types` are considered
+    /// equal. The symbols formats list the paths of changed files, using the new path for moved
+    /// files. The HTML, summary and JSON formats are not supported and produce no output.
+    ///
+    /// Returns `Ok` containing a [`CompareStatus`] indicating whether the corpuses are the same, or
+    /// <code>Err([Error])</code> on error. All changes are considered breaking.
+    pub fn compare_files_with_buffer<W: Write>(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_type_filter: Option<&Filter>,
+        maybe_ignore_types: Option<&Filter>,
+        diff_options: &DiffOptions,
+        writers: &mut [(CompareFormat, W)],
+    ) -> Result<CompareStatus, Error> {
+        let err_desc = "Failed to write a comparison result";
+
+        // Index the files by their normalized path, so that a file recorded under the name of its
+        // object matches the same file recorded under the name of its symtypes file.
+        let [paths, other_paths] = [&self.files, &other_symtypes.files].map(|files| {
+            files
+                .keys()
+                .map(|path| (normalize_file_path(path), path.as_path()))
+                .collect::<HashMap<_, _>>()
+        });
+
+        // Check for files in `self` but not in `other_symtypes`, and vice versa.
+        let [mut added, mut removed] =
+            [(&other_paths, &paths), (&paths, &other_paths)].map(|(paths_a, paths_b)| {
+                let mut changed = paths_a
+                    .iter()
+                    .filter(|&(key, _)| !paths_b.contains_key(key))
+                    .map(|(_, &path)| path)
+                    .collect::<Vec<_>>();
+                changed.sort();
+                changed
+            });
+
+        // Pair the remaining added and removed files that were moved.
+        let moved = self.find_moved_files(other_symtypes, &removed, &added);
+        removed.retain(|&path| !moved.iter().any(|&(old_path, _)| old_path == path));
+        added.retain(|&path| !moved.iter().any(|&(_, new_path)| new_path == path));
+
+        // Compare records of files that are in both corpuses, either under the same path or moved.
+        let mut modified = Vec::new();
+        let pairs = paths
+            .iter()
+            .filter_map(|(key, &path)| other_paths.get(key).map(|&other_path| (path, other_path)))
+            .chain(moved.iter().copied());
+        for (path, other_path) in pairs {
+            let symfile = self.files[path].as_ref();
+            let other_symfile = other_symtypes.files[other_path].as_ref();
+
+            let names = symfile
+                .records
+                .keys()
+                .chain(other_symfile.records.keys())
+                .map(String::as_str)
+                .filter(|&name| {
+                    if is_export_name(name) {
+                        matches_filter(maybe_filter, name)
+                    } else {
+                        matches_filter(maybe_type_filter, name)
+                            && !maybe_ignore_types
+                                .is_some_and(|ignore_types| ignore_types.matches(name))
+                    }
+                })
+                .collect::<BTreeSet<_>>();
+
+            let mut changes = Vec::new();
+            for name in names {
+                let maybe_tokens = symfile.records.get(name).map(Arc::as_ref);
+                let maybe_other_tokens = other_symfile.records.get(name).map(Arc::as_ref);
+                let is_equal = match (maybe_tokens, maybe_other_tokens) {
+                    (Some(tokens), Some(other_tokens)) => {
+                        tokens == other_tokens
+                            || (!self.strict
+                                && normalize_tokens(tokens) == normalize_tokens(other_tokens))
+                    }
+                    _ => maybe_tokens == maybe_other_tokens,
+                };
+                if !is_equal {
+                    changes.push((name, maybe_tokens, maybe_other_tokens));
+                }
+            }
+            if !changes.is_empty() {
+                modified.push((path, other_path, changes));
+            }
+        }
+        modified.sort_by_key(|&(path, _, _)| path);
+
+        // Report the added, removed and moved files.
+        for (changed, change) in [(&added, "added"), (&removed, "removed")] {
+            for path in changed {
+                for &mut (format, ref mut writer) in &mut *writers {
+                    if format == CompareFormat::Pretty
+                        || format == CompareFormat::Short
+                        || format == CompareFormat::SideBySide
+                    {
+                        writeln!(writer, "File '{}' has been {}", path.display(), change)
+                            .map_io_err(err_desc)?;
+                    }
+                }
+            }
+        }
+        for (old_path, new_path) in &moved {
+            for &mut (format, ref mut writer) in &mut *writers {
+                if format == CompareFormat::Pretty
+                    || format == CompareFormat::Short
+                    || format == CompareFormat::SideBySide
+                {
+                    writeln!(
+                        writer,
+                        "File '{}' has been moved to '{}'",
+                        old_path.display(),
+                        new_path.display()
+                    )
+                    .map_io_err(err_desc)?;
+                }
+            }
+        }
+
+        // Report the changes in the modified files.
+        let mut add_separator = !added.is_empty() || !removed.is_empty() || !moved.is_empty();
+        for (path, other_path, changes) in &modified {
+            for &mut (format, ref mut writer) in &mut *writers {
+                if format != CompareFormat::Pretty
+                    && format != CompareFormat::Short
+                    && format != CompareFormat::SideBySide
+                {
+                    continue;
+                }
+                let is_short = format == CompareFormat::Short;
+                let is_side_by_side = format == CompareFormat::SideBySide;
+
+                // Add an empty line to separate individual files.
+                if add_separator {
+                    writeln!(writer).map_io_err(err_desc)?;
+                }
+                if normalize_file_path(path) == normalize_file_path(other_path) {
+                    writeln!(writer, "/* {} */", path.display()).map_io_err(err_desc)?;
+                } else {
+                    writeln!(
+                        writer,
+                        "/* {} -> {} */",
+                        path.display(),
+                        other_path.display()
+                    )
+                    .map_io_err(err_desc)?;
+                }
+
+                for &(name, maybe_tokens, maybe_other_tokens) in changes {
+                    let kind = if is_export_name(name) {
+                        "Export"
+                    } else {
+                        "Type"
+                    };
+                    match (maybe_tokens, maybe_other_tokens) {
+                        (None, _) => writeln!(writer, "{} '{}' has been added", kind, name)
+                            .map_io_err(err_desc)?,
+                        (_, None) => writeln!(writer, "{} '{}' has been removed", kind, name)
+                            .map_io_err(err_desc)?,
+                        (Some(_), Some(_)) if is_short => {
+                            writeln!(writer, "{} '{}' has been modified", kind, name)
+                                .map_io_err(err_desc)?
+                        }
+                        (Some(tokens), Some(other_tokens)) => {
+                            writeln!(writer, "{} '{}' has been modified:", kind, name)
+                                .map_io_err(err_desc)?;
+                            write_type_diff(
+                                tokens,
+                                other_tokens,
+                                diff_options,
+                                is_side_by_side,
+                                writer.by_ref(),
+                            )?;
+                        }
+                    }
+                }
+            }
+            add_separator = true;
+        }
+
+        // Format file lists.
+        let mut output_files = added
+            .iter()
+            .chain(&removed)
+            .map(|&path| (path, false))
+            .collect::<HashMap<_, _>>();
+        for &(_, new_path) in &moved {
+            output_files.insert(new_path, false);
+        }
+        for &(_, other_path, _) in &modified {
+            output_files.insert(other_path, true);
+        }
+        let mut sorted_output_files = output_files.into_iter().collect::<Vec<_>>();
+        sorted_output_files.sort();
+        for &(path, modified) in &sorted_output_files {
+            for &mut (format, ref mut writer) in &mut *writers {
+                if format == CompareFormat::Symbols
+                    || (format == CompareFormat::ModSymbols && modified)
+                {
+                    writeln!(writer, "{}", path.display()).map_io_err(err_desc)?;
+                }
+            }
+        }
+
+        for (_, writer) in &mut *writers {
+            writer.flush().map_io_err(err_desc)?;
+        }
+
+        Ok(if sorted_output_files.is_empty() {
+            CompareStatus::Same
+        } else {
+            CompareStatus::Breaking
+        })
+    }
+
+    /// Finds which of the given files removed from this corpus were moved to one of the given
+    /// files added in another corpus.
+    ///
+    /// The caller is expected to pair the files by their path first and pass in only the files
+    /// that were left unpaired, so a file that stayed in place is never reported as moved.
+    ///
+    /// A removed and an added file are considered a candidate pair if they define at least one
+    /// common export. The similarity of the pair is the number of records that are identical in
+    /// both files, relative to the average number of records in the files. Pairs with
+    /// a similarity of at least [`MOVE_SIMILARITY_THRESHOLD`] percent are then matched greedily,
+    /// starting with the most similar ones.
+    ///
+    /// Returns the list of the moved files as pairs of their old and new paths, sorted by the old
+    /// path.
+    fn find_moved_files<'a>(
+        &'a self,
+        other_symtypes: &'a SymtypesCorpus,
+        removed: &[&'a Path],
+        added: &[&'a Path],
+    ) -> Vec<(&'a Path, &'a Path)> {
+        // Map each export of the added files to the file that defines it.
+        let mut added_exports = HashMap::<&str, &Path>::new();
+        for &path in added {
+            for name in other_symtypes.files[path].records.keys() {
+                if is_export_name(name) {
+                    added_exports.insert(name, path);
+                }
+            }
+        }
+
+        // Score all candidate pairs.
+        let mut candidates = Vec::new();
+        for &path in removed {
+            let symfile = self.files[path].as_ref();
+            let other_paths = symfile
+                .records
+                .keys()
+                .filter_map(|name| added_exports.get(name.as_str()).copied())
+                .collect::<BTreeSet<_>>();
+            for other_path in other_paths {
+                let other_symfile = other_symtypes.files[other_path].as_ref();
+                let common = symfile
+                    .records
+                    .iter()
+                    .filter(|&(name, tokens)| other_symfile.records.get(name) == Some(tokens))
+                    .count();
+                let total = symfile.records.len() + other_symfile.records.len();
+                let similarity = 200 * common / total;
+                if similarity >= MOVE_SIMILARITY_THRESHOLD {
+                    candidates.push((Reverse(similarity), path, other_path));
+                }
+            }
+        }
+        candidates.sort();
+
+        // Match the most similar pairs first.
+        let mut matched = HashSet::new();
+        let mut moved = Vec::new();
+        for (_, path, other_path) in candidates {
+            if matched.contains(path) || matched.contains(other_path) {
+                continue;
+            }
+            matched.insert(path);
+            matched.insert(other_path);
+            moved.push((path, other_path));
+        }
+        moved.sort();
+        moved
+    }
+
+    /// Writes an explanation of how the given export differs between this corpus and another one
+    /// to the provided output stream.
+    ///
+    /// All changed types reachable from the export are listed, each together with a diff of its
+    /// definition. Nothing is written if the export has the same type graph in both corpuses.
+    ///
+    /// Returns `Ok` containing a `bool` indicating whether the export is present in both corpuses,
+    /// or <code>Err([Error])</code> on error.
+    pub fn explain_export_buffer<W: Write>(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        name: &str,
+        mut writer: W,
+    ) -> Result<bool, Error> {
+        let err_desc = "Failed to write an export explanation";
+
+        let (Some(symfile_rc), Some(other_symfile_rc)) =
+            (self.exports.get(name), other_symtypes.exports.get(name))
+        else {
+            return Ok(false);
+        };
+
+        let changes = Mutex::new(CompareChangedTypes::new());
+        let mut processed = CompareFileTypes::new();
+        Self::compare_types(
+            symfile_rc.as_ref(),
+            other_symfile_rc.as_ref(),
+            name,
+            name,
+            None,
+            self.strict,
+            &changes,
+            &mut processed,
+        );
+
+        let changes = changes.into_inner().unwrap(); // Get the inner HashMap.
+        let mut changes = changes.into_keys().collect::<Vec<_>>();
+        changes.sort();
+
+        for (change_idx, (type_name, tokens, other_tokens)) in changes.into_iter().enumerate() {
+            // Add an empty line to separate individual changes.
+            if change_idx > 0 {
+                writeln!(writer).map_io_err(err_desc)?;
+            }
+            write_type_change(
+                type_name,
+                tokens,
+                other_tokens,
+                None,
+                &DiffOptions::default(),
+                false,
+                writer.by_ref(),
+            )?;
+            if self.suggest_fixup {
+                write_fixup_suggestion(type_name, tokens, other_tokens, writer.by_ref())?;
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(true)
+    }
+
+    /// Finds all exports whose definition references the given type, directly or indirectly.
+    ///
+    /// Returns a sorted list of symtypes files that contain any such export, each paired with
+    /// sorted names of the exports. The type references are followed separately in each file, as
+    /// defined by that file.
+    pub fn exports_using(&self, type_name: &str) -> Vec<(&Path, Vec<&str>)> {
+        let mut res = Vec::new();
+
+        for symfile_rc in self.files.values() {
+            if !symfile_rc.records.contains_key(type_name) {
+                continue;
+            }
+
+            let referrers = file_referrers(symfile_rc);
+            let mut exports = self.file_exports_using(symfile_rc, &referrers, type_name);
+            if !exports.is_empty() {
+                exports.sort();
+                res.push((symfile_rc.path.as_path(), exports));
+            }
+        }
+
+        res.sort();
+        res
+    }
+
+    /// Finds all types declared as `{ UNKNOWN }` and the exports whose definition references them,
+    /// directly or indirectly.
+    ///
+    /// Returns a list of the types sorted by name, each paired with sorted names of the exports.
+    /// A type is included if it has an UNKNOWN declaration in at least one file, and only the
+    /// exports of the files with such a declaration are considered for it. The list of exports can
+    /// be empty if no export depends on the type.
+    pub fn unknown_types(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut unknowns = BTreeMap::<&str, BTreeSet<&str>>::new();
+
+        for symfile_rc in self.files.values() {
+            let mut referrers = None;
+            for (name, tokens) in &symfile_rc.records {
+                if !is_unknown_declaration(name, tokens) {
+                    continue;
+                }
+
+                let referrers = referrers.get_or_insert_with(|| file_referrers(symfile_rc));
+                unknowns
+                    .entry(name.as_str())
+                    .or_default()
+                    .extend(self.file_exports_using(symfile_rc, referrers, name));
+            }
+        }
+
+        unknowns
+            .into_iter()
+            .map(|(name, exports)| (name, exports.into_iter().collect()))
+            .collect()
+    }
+
+    /// Finds all exports of the given file whose definition references the given type, directly or
+    /// indirectly, walking the provided map of referrers in the file.
+    fn file_exports_using<'a>(
+        &'a self,
+        symfile_rc: &'a Arc<SymtypesFile>,
+        referrers: &HashMap<&'a str, Vec<&'a str>>,
+        type_name: &str,
+    ) -> Vec<&'a str> {
+        let mut visited = HashSet::from([type_name]);
+        let mut pending = vec![type_name];
+        let mut exports = Vec::new();
+        while let Some(name) = pending.pop() {
+            for &referrer in referrers.get(name).into_iter().flatten() {
+                if !visited.insert(referrer) {
+                    continue;
+                }
+                if let Some(export_symfile_rc) = self.exports.get(referrer)
+                    && Arc::ptr_eq(export_symfile_rc, symfile_rc)
+                {
+                    exports.push(referrer);
+                }
+                pending.push(referrer);
+            }
+        }
+        exports
+    }
+
+    /// Writes all exports whose definition references the given type to the specified file.
+    ///
+    /// See [`SymtypesCorpus::write_users_buffer()`] for details.
+    pub fn write_users<P: AsRef<Path>>(&self, type_name: &str, path: P) -> Result<bool, Error> {
+        self.write_users_buffer(type_name, Writer::new_file(path)?)
+    }
+
+    /// Writes all exports whose definition references the given type to the provided output
+    /// stream.
+    ///
+    /// The exports are grouped by the symtypes file in which they are found, as determined by
+    /// [`SymtypesCorpus::exports_using()`]. Each group is preceded by a header with the name of the
+    /// file.
+    ///
+    /// Returns `Ok` containing a `bool` indicating whether the type was found, or
+    /// <code>Err([Error])</code> on error.
+    pub fn write_users_buffer<W: Write>(
+        &self,
+        type_name: &str,
+        mut writer: W,
+    ) -> Result<bool, Error> {
+        let err_desc = "Failed to write users of a type";
+
+        if !self
+            .files
+            .values()
+            .any(|symfile_rc| symfile_rc.records.contains_key(type_name))
+        {
+            return Ok(false);
+        }
+
+        for (file_idx, (path, exports)) in self.exports_using(type_name).into_iter().enumerate() {
+            // Add an empty line to separate individual files.
+            if file_idx > 0 {
+                writeln!(writer).map_io_err(err_desc)?;
+            }
+            writeln!(writer, "/* {} */", path.display()).map_io_err(err_desc)?;
+            for export in exports {
+                writeln!(writer, "{}", export).map_io_err(err_desc)?;
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(true)
+    }
+
+    /// Writes all types declared as `{ UNKNOWN }` and the exports that depend on them to the
+    /// specified file.
+    ///
+    /// See [`SymtypesCorpus::write_unknowns_buffer()`] for details.
+    pub fn write_unknowns<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_unknowns_buffer(Writer::new_file(path)?)
+    }
+
+    /// Writes all types declared as `{ UNKNOWN }` and the exports that depend on them to the
+    /// provided output stream.
+    ///
+    /// Each type, as determined by [`SymtypesCorpus::unknown_types()`], is written on its own line,
+    /// followed by the names of the dependent exports, each indented by a single space.
+    pub fn write_unknowns_buffer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let err_desc = "Failed to write unknown types";
+
+        for (type_name, exports) in self.unknown_types() {
+            writeln!(writer, "{}", type_name).map_io_err(err_desc)?;
+            for export in exports {
+                writeln!(writer, " {}", export).map_io_err(err_desc)?;
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(())
+    }
+
+    /// Writes the type-reference graph of the given export to the specified file.
+    ///
+    /// See [`SymtypesCorpus::write_graph_buffer()`] for details.
+    pub fn write_graph<P: AsRef<Path>>(&self, name: &str, path: P) -> Result<bool, Error> {
+        self.write_graph_buffer(name, Writer::new_file(path)?)
+    }
+
+    /// Writes the type-reference graph of the given export to the provided output stream.
+    ///
+    /// The graph is written in the Graphviz DOT format. Each node is a type and each edge points
+    /// from a type to another type it references, as defined in the symtypes file of the export.
+    /// Nodes are expanded in the depth-first order, following the order of references in the type
+    /// definitions.
+    ///
+    /// Returns `Ok` containing a `bool` indicating whether the export was found, or
+    /// <code>Err([Error])</code> on error.
+    pub fn write_graph_buffer<W: Write>(&self, name: &str, mut writer: W) -> Result<bool, Error> {
+        let err_desc = "Failed to write a type graph";
+
+        let symfile = match self.exports.get(name) {
+            Some(symfile_rc) => symfile_rc.as_ref(),
+            None => return Ok(false),
+        };
+
+        writeln!(writer, "digraph {} {{", quote_dot_id(name)).map_io_err(err_desc)?;
+
+        let mut visited = HashSet::from([name]);
+        let mut pending = vec![name];
+        while let Some(type_name) = pending.pop() {
+            // INVARIANT: Each type reference is guaranteed to have a corresponding definition.
+            let tokens = symfile.records.get(type_name).unwrap();
+
+            let mut targets = Vec::new();
+            for token in tokens.iter() {
+                if let Token::TypeRef(ref_name) = token
+                    && !targets.contains(&&**ref_name)
+                {
+                    targets.push(&**ref_name);
+                }
+            }
+
+            for &ref_name in &targets {
+                writeln!(
+                    writer,
+                    "\t{} -> {};",
+                    quote_dot_id(type_name),
+                    quote_dot_id(ref_name)
+                )
+                .map_io_err(err_desc)?;
+            }
+
+            // Push the new references in reverse so they are processed in their order.
+            pending.extend(
+                targets
+                    .into_iter()
+                    .filter(|&ref_name| visited.insert(ref_name))
+                    .rev(),
+            );
+        }
+
+        writeln!(writer, "}}").map_io_err(err_desc)?;
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(true)
+    }
+
+    /// Computes the modversion CRC of the given export, as genksyms does when building the kernel.
+    ///
+    /// The definition of the export is expanded recursively, as defined in its symtypes file, and
+    /// the CRC-32 checksum is computed over all words, each followed by a space. A type that was
+    /// already expanded is referred to only by its short form, for instance, `struct foo`. The
+    /// `extern` storage class is not included.
+    ///
+    /// Returns `Some` containing the CRC, or `None` if the export is not found.
+    pub fn export_crc(&self, name: &str) -> Option<u32> {
+        let symfile = self.exports.get(name)?.as_ref();
+
+        let mut crc = Crc32::new();
+        let mut expanded = HashSet::from([name]);
+        Self::expand_and_crc(symfile, name, &mut expanded, &mut crc);
+        Some(crc.finish())
+    }
+
+    /// Feeds the expanded definition of the given type into the CRC hasher.
+    fn expand_and_crc<'a>(
+        symfile: &'a SymtypesFile,
+        type_name: &str,
+        expanded: &mut HashSet<&'a str>,
+        crc: &mut Crc32,
+    ) {
+        // INVARIANT: Each type reference is guaranteed to have a corresponding definition.
+        let tokens = symfile.records.get(type_name).unwrap();
+
+        for token in tokens.iter() {
+            match token {
+                Token::TypeRef(ref_name) => {
+                    if expanded.insert(ref_name) {
+                        Self::expand_and_crc(symfile, ref_name, expanded, crc);
+                        continue;
+                    }
+
+                    // The type was already expanded, use its short form.
+                    let base_name = match split_type_name(ref_name, "#") {
+                        Some((short_type, expanded_type, base_name)) => {
+                            if short_type != "t" {
+                                crc.update(expanded_type.as_bytes());
+                                crc.update(b" ");
+                            }
+                            base_name
+                        }
+                        None => ref_name
+                            .split_once('#')
+                            .map_or(&**ref_name, |(_, base)| base),
+                    };
+                    let base_name = base_name
+                        .strip_prefix('\'')
+                        .and_then(|base| base.strip_suffix('\''))
+                        .unwrap_or(base_name);
+                    crc.update(base_name.as_bytes());
+                    crc.update(b" ");
+                }
+                Token::Atom(word) => {
+                    // The storage class of the export is irrelevant to its linkage and genksyms
+                    // leaves it out of the checksum.
+                    if &**word == "extern" {
+                        continue;
+                    }
+                    crc.update(word.as_bytes());
+                    crc.update(b" ");
+                }
+            }
+        }
+    }
+
+    /// Writes summary statistics about the corpus to the specified file.
+    ///
+    /// See [`SymtypesCorpus::write_stats_buffer()`] for details.
+    pub fn write_stats<P: AsRef<Path>>(&self, top_count: usize, path: P) -> Result<(), Error> {
+        self.write_stats_buffer(top_count, Writer::new_file(path)?)
+    }
+
+    /// Writes summary statistics about the corpus to the provided output stream.
+    ///
+    /// The statistics include the number of files, exports, distinct types and their variants, the
+    /// distribution of variant counts, the `top_count` types with the most variants, and an
+    /// estimate of the memory used by the corpus data.
+    pub fn write_stats_buffer<W: Write>(
+        &self,
+        top_count: usize,
+        mut writer: W,
+    ) -> Result<(), Error> {
+        let err_desc = "Failed to write corpus statistics";
+
+        // Collect the variant counts of all types.
+        let mut type_variants = self
+            .types
+            .iter()
+            .flat_map(|types| types.iter())
+            .map(|(name, variants)| (name.as_str(), variants.len()))
+            .collect::<Vec<_>>();
+        type_variants.sort_by(|(name, count), (other_name, other_count)| {
+            other_count.cmp(count).then(name.cmp(other_name))
+        });
+        let variant_total = type_variants.iter().map(|&(_, count)| count).sum::<usize>();
+
+        let mut count_distribution = BTreeMap::<usize, usize>::new();
+        for &(_, count) in &type_variants {
+            *count_distribution.entry(count).or_default() += 1;
+        }
+
+        // Estimate the memory footprint. The estimate considers only the payload of the main
+        // data structures, not any allocator or hash table overhead.
+        let mut token_strings = HashSet::new();
+        let mut tokens_size = 0;
+        for variants in self.types.iter().flat_map(|types| types.values()) {
+            for tokens in variants {
+                tokens_size += mem::size_of::<Tokens>() + tokens.len() * mem::size_of::<Token>();
+                token_strings.extend(tokens.iter().map(Token::as_str));
+            }
+        }
+        let strings_size = token_strings.iter().map(|word| word.len()).sum::<usize>();
+        let records_size = self
+            .files
+            .values()
+            .flat_map(|symfile| symfile.records.keys())
+            .map(|name| mem::size_of::<(String, Arc<Tokens>)>() + name.len())
+            .sum::<usize>();
+
+        writeln!(writer, "Files: {}", self.files.len()).map_io_err(err_desc)?;
+        writeln!(writer, "Exports: {}", self.exports.len()).map_io_err(err_desc)?;
+        writeln!(writer, "Types: {}", type_variants.len()).map_io_err(err_desc)?;
+        writeln!(writer, "Type variants: {}", variant_total).map_io_err(err_desc)?;
+
+        // Summarize the file metadata, if any is present. Object paths are unique to each file and
+        // so are only counted.
+        let mut metadata_counts = BTreeMap::<(&str, &str), usize>::new();
+        let mut metadata_files = 0;
+        for symfile in self.files.values() {
+            if symfile.metadata.is_empty() {
+                continue;
+            }
+            metadata_files += 1;
+            for (key, value) in &symfile.metadata {
+                if key != "object" {
+                    *metadata_counts.entry((key, value)).or_default() += 1;
+                }
+            }
+        }
+        if metadata_files > 0 {
+            writeln!(writer, "Files with metadata: {}", metadata_files).map_io_err(err_desc)?;
+            writeln!(writer, "Files by metadata:").map_io_err(err_desc)?;
+            for ((key, value), count) in metadata_counts {
+                writeln!(writer, " {}={}: {}", key, value, count).map_io_err(err_desc)?;
+            }
+        }
+
+        writeln!(writer, "Types by the number of variants:").map_io_err(err_desc)?;
+        for (count, num_types) in count_distribution {
+            writeln!(writer, " {}: {}", count, num_types).map_io_err(err_desc)?;
+        }
+
+        writeln!(writer, "Types with the most variants:").map_io_err(err_desc)?;
+        for (name, count) in type_variants.iter().take(top_count) {
+            writeln!(writer, " {}: {}", name, count).map_io_err(err_desc)?;
+        }
+
+        writeln!(writer, "Estimated memory footprint:").map_io_err(err_desc)?;
+        writeln!(writer, " Type tokens: {} bytes", tokens_size).map_io_err(err_desc)?;
+        writeln!(writer, " Token strings: {} bytes", strings_size).map_io_err(err_desc)?;
+        writeln!(writer, " File records: {} bytes", records_size).map_io_err(err_desc)?;
+        writeln!(
+            writer,
+            " Total: {} bytes",
+            tokens_size + strings_size + records_size
+        )
+        .map_io_err(err_desc)?;
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(())
+    }
+
+    /// Writes the pretty-formatted definition of the given export or type to the specified file.
+    ///
+    /// See [`SymtypesCorpus::show_buffer()`] for details.
+    pub fn show<P: AsRef<Path>>(
+        &self,
+        name: &str,
+        maybe_max_depth: Option<usize>,
+        path: P,
+    ) -> Result<bool, Error> {
+        self.show_buffer(name, maybe_max_depth, Writer::new_file(path)?)
+    }
+
+    /// Writes the pretty-formatted definition of the given export or type to the provided output
+    /// stream.
+    ///
+    /// Each distinct variant of the symbol is output, preceded by a header with the name of the
+    /// first symtypes file in which the variant is found. If `maybe_max_depth` is `Some`, all types
+    /// referenced by the definition are recursively output as well, up to the specified depth. Each
+    /// referenced type is shown only once, as it is defined in the file of the given variant.
+    ///
+    /// Returns `Ok` containing a `bool` indicating whether the symbol was found, or
+    /// <code>Err([Error])</code> on error.
+    pub fn show_buffer<W: Write>(
+        &self,
+        name: &str,
+        maybe_max_depth: Option<usize>,
+        mut writer: W,
+    ) -> Result<bool, Error> {
+        let err_desc = "Failed to write a symbol definition";
+
+        // Find all variants of the symbol and the first file in which each of them is present.
+        let mut sorted_files = self
+            .files
+            .values()
+            .filter(|symfile_rc| symfile_rc.records.contains_key(name))
+            .collect::<Vec<_>>();
+        sorted_files.sort_by_key(|&symfile_rc| &symfile_rc.path);
+
+        let mut variants = Vec::<(&Arc<Tokens>, &SymtypesFile)>::new();
+        for symfile_rc in sorted_files {
+            let tokens_rc = symfile_rc.records.get(name).unwrap();
+            if !variants
+                .iter()
+                .any(|(other_tokens_rc, _)| Arc::ptr_eq(tokens_rc, other_tokens_rc))
+            {
+                variants.push((tokens_rc, symfile_rc));
+            }
+        }
+
+        for (variant_idx, (tokens_rc, symfile)) in variants.iter().enumerate() {
+            // Add an empty line to separate individual variants.
+            if variant_idx > 0 {
+                writeln!(writer).map_io_err(err_desc)?;
+            }
+            writeln!(writer, "/* {} */", symfile.path.display()).map_io_err(err_desc)?;
+
+            // Collect the symbol and all referenced types up to the maximum depth.
+            let mut shown = HashSet::from([name]);
+            let mut pending = vec![(name, tokens_rc.as_ref(), 0)];
+            let mut add_separator = false;
+            while let Some((type_name, tokens, depth)) = pending.pop() {
+                if add_separator {
+                    writeln!(writer).map_io_err(err_desc)?;
+                } else {
+                    add_separator = true;
+                }
+                if type_name != name {
+                    writeln!(writer, "{}:", type_name).map_io_err(err_desc)?;
+                }
+                for line in pretty_format_type(tokens) {
+                    writeln!(writer, "{}", line).map_io_err(err_desc)?;
+                }
+
+                if let Some(max_depth) = maybe_max_depth
+                    && depth < max_depth
+                {
+                    let mut new_refs = Vec::new();
+                    for token in tokens.iter() {
+                        if let Token::TypeRef(ref_name) = token
+                            && shown.insert(&**ref_name)
+                        {
+                            // INVARIANT: Each type reference is guaranteed to have a corresponding
+                            // definition.
+                            let ref_tokens = symfile.records.get(&**ref_name).unwrap();
+                            new_refs.push((&**ref_name, ref_tokens.as_ref(), depth + 1));
+                        }
+                    }
+                    // Push the references in reverse so they are processed in their order.
+                    pending.extend(new_refs.into_iter().rev());
+                }
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(!variants.is_empty())
+    }
+
+    /// Returns whether the given export or type is present in the corpus.
+    fn has_symbol(&self, name: &str) -> bool {
+        if is_export_name(name) {
+            self.exports.contains_key(name)
+        } else {
+            self.types[type_bucket_idx(name)].contains_key(name)
+        }
+    }
+
+    /// Writes an explanation of how the given export or type differs between this corpus and
+    /// another one to the provided output stream.
+    ///
+    /// An export is explained by [`SymtypesCorpus::explain_export_buffer()`]. A type is explained
+    /// by a diff of all its variants, sorted by their pretty-formatted definition. Nothing is
+    /// written if the symbol is the same in both corpuses, or if it is missing in either of them.
+    fn explain_symbol_buffer<W: Write>(
+        &self,
+        other_symtypes: &SymtypesCorpus,
+        name: &str,
+        mut writer: W,
+    ) -> Result<(), Error> {
+        if is_export_name(name) {
+            self.explain_export_buffer(other_symtypes, name, writer)?;
+            return Ok(());
+        }
+
+        let [lines, other_lines] = [self, other_symtypes].map(|symtypes| {
+            let mut variants = symtypes.types[type_bucket_idx(name)]
+                .get(name)
+                .into_iter()
+                .flatten()
+                .map(|tokens_rc| pretty_format_type(tokens_rc))
+                .collect::<Vec<_>>();
+            variants.sort();
+            variants.dedup();
+            variants.join(&String::new())
+        });
+        if lines.is_empty() || other_lines.is_empty() || lines == other_lines {
+            return Ok(());
+        }
+
+        unified_diff_with_options(
+            &lines,
+            &other_lines,
+            &DiffOptions::default(),
+            writer.by_ref(),
+        )?;
+        writer
+            .flush()
+            .map_io_err("Failed to write a type explanation")
+    }
+}
+
+/// Writes the history of the given export or type across a sequence of symtypes corpuses to the
+/// specified file.
+///
+/// See [`write_history_buffer()`] for details.
+pub fn write_history<I, P>(name: &str, corpuses: I, path: P) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Result<(String, SymtypesCorpus), Error>>,
+    P: AsRef<Path>,
+{
+    write_history_buffer(name, corpuses, Writer::new_file(path)?)
+}
+
+/// Writes the history of the given export or type across a sequence of symtypes corpuses to the
+/// provided output stream.
+///
+/// The `corpuses` provide the corpuses in chronological order, each paired with a label that
+/// identifies it in the output. They are obtained one by one, so that at most two corpuses are kept
+/// in memory at the same time. For each corpus, a line with the label and the state of the symbol
+/// compared to the previous corpus is written: whether it is defined, not found, added, removed,
+/// unchanged or changed. A change is followed by an explanation of the differences, as written by
+/// [`SymtypesCorpus::explain_export_buffer()`] for an export, or by a diff of all variants for
+/// a type.
+pub fn write_history_buffer<I, W>(name: &str, corpuses: I, mut writer: W) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Result<(String, SymtypesCorpus), Error>>,
+    W: Write,
+{
+    let err_desc = "Failed to write a symbol history";
+
+    let mut maybe_prev_symtypes: Option<SymtypesCorpus> = None;
+    let mut add_separator = false;
+    for corpus in corpuses {
+        let (label, symtypes) = corpus?;
+
+        let mut explanation = Vec::new();
+        let is_present = symtypes.has_symbol(name);
+        let state = match &maybe_prev_symtypes {
+            None if is_present => "is defined",
+            None => "is not found",
+            Some(prev_symtypes) => match (prev_symtypes.has_symbol(name), is_present) {
+                (false, false) => "is not found",
+                (false, true) => "has been added",
+                (true, false) => "has been removed",
+                (true, true) => {
+                    prev_symtypes.explain_symbol_buffer(&symtypes, name, &mut explanation)?;
+                    if explanation.is_empty() {
+                        "is unchanged"
+                    } else {
+                        "has changed"
+                    }
+                }
+            },
+        };
+
+        // Add an empty line to separate the explanation of a previous change.
+        if add_separator {
+            writeln!(writer).map_io_err(err_desc)?;
+        }
+        writeln!(writer, "{}: '{}' {}", label, name, state).map_io_err(err_desc)?;
+        writer.write_all(&explanation).map_io_err(err_desc)?;
+        add_separator = !explanation.is_empty();
+
+        maybe_prev_symtypes = Some(symtypes);
+    }
+
+    writer.flush().map_io_err(err_desc)?;
+
+    Ok(())
+}
+
+/// Checks that exports are defined consistently across multiple symtypes corpuses, such as the
+/// corpuses of individual kernel flavors, and writes a report of all differences to the specified
+/// file.
+///
+/// See [`cross_check_buffer()`] for details.
+pub fn cross_check<P: AsRef<Path>>(
+    corpuses: &[(String, SymtypesCorpus)],
+    maybe_type_filter: Option<&Filter>,
+    path: P,
+) -> Result<bool, Error> {
+    cross_check_buffer(corpuses, maybe_type_filter, Writer::new_file(path)?)
+}
+
+/// Checks that exports are defined consistently across multiple symtypes corpuses, such as the
+/// corpuses of individual kernel flavors, and writes a report of all differences to the provided
+/// output stream.
+///
+/// Each corpus is paired with a label that identifies it in the output. For every export present in
+/// at least two corpuses, the types reachable from the export are compared between the corpuses.
+/// Each type found with different definitions is reported once, together with all exports through
+/// which it is reachable and a diff of each of its additional variants against the first one. If
+/// `maybe_type_filter` is specified, only types matching the filter are checked.
+///
+/// Returns `true` if any differences were found, and `false` otherwise.
+pub fn cross_check_buffer<W: Write>(
+    corpuses: &[(String, SymtypesCorpus)],
+    maybe_type_filter: Option<&Filter>,
+    mut writer: W,
+) -> Result<bool, Error> {
+    let err_desc = "Failed to write a cross-check report";
+
+    // Collect, for each type with differing definitions, its variants with the indices of corpuses
+    // that use them, and the exports through which the type is reachable.
+    type Variants<'a> = Vec<(&'a Tokens, Vec<usize>)>;
+    let mut divergences = BTreeMap::<&str, (Variants, BTreeSet<&str>)>::new();
+
+    let export_names = corpuses
+        .iter()
+        .flat_map(|(_, symtypes)| symtypes.exports.keys().map(String::as_str))
+        .collect::<BTreeSet<_>>();
+    for export in export_names {
+        let mut type_variants = HashMap::<&str, Variants>::new();
+        for (corpus_idx, (_, symtypes)) in corpuses.iter().enumerate() {
+            let Some(symfile) = symtypes.exports.get(export) else {
+                continue;
+            };
+
+            // Walk all types reachable from the export in its file.
+            let mut visited = HashSet::new();
+            let mut pending = vec![export];
+            while let Some(name) = pending.pop() {
+                if !visited.insert(name) {
+                    continue;
+                }
+                let Some((name, tokens)) = symfile.records.get_key_value(name) else {
+                    continue;
+                };
+                add_cross_check_variant(type_variants.entry(name).or_default(), tokens, corpus_idx);
+                pending.extend(tokens.iter().filter_map(|token| match token {
+                    Token::TypeRef(ref_name) => Some(ref_name.as_ref()),
+                    Token::Atom(_) => None,
+                }));
+            }
+        }
+
+        for (name, variants) in type_variants {
+            if variants.len() < 2 || maybe_type_filter.is_some_and(|filter| !filter.matches(name)) {
+                continue;
+            }
+            let (all_variants, exports) = divergences.entry(name).or_default();
+            for (tokens, corpus_indices) in variants {
+                for corpus_idx in corpus_indices {
+                    add_cross_check_variant(all_variants, tokens, corpus_idx);
+                }
+            }
+            exports.insert(export);
+        }
+    }
+
+    // Report the found differences, with the variants and their corpuses in the order of the
+    // corpuses.
+    let format_labels = |corpus_indices: &[usize]| {
+        corpus_indices
+            .iter()
+            .map(|&corpus_idx| corpuses[corpus_idx].0.as_str())
+            .collect::<Vec<_>>()
+            .join("', '")
+    };
+    let mut add_separator = false;
+    for (name, (variants, exports)) in &mut divergences {
+        for (_, corpus_indices) in variants.iter_mut() {
+            corpus_indices.sort();
+        }
+        variants.sort_by_key(|(_, corpus_indices)| corpus_indices[0]);
+
+        if add_separator {
+            writeln!(writer).map_io_err(err_desc)?;
+        } else {
+            add_separator = true;
+        }
+
+        writeln!(
+            writer,
+            "The following '{}' exports use differing definitions of '{}':",
+            exports.len(),
+            name
+        )
+        .map_io_err(err_desc)?;
+        for export in exports.iter() {
+            writeln!(writer, " {}", export).map_io_err(err_desc)?;
+        }
+
+        let (base_tokens, base_indices) = &variants[0];
+        for (tokens, corpus_indices) in &variants[1..] {
+            writeln!(writer).map_io_err(err_desc)?;
+            writeln!(
+                writer,
+                "'{}' vs. '{}':",
+                format_labels(base_indices),
+                format_labels(corpus_indices)
+            )
+            .map_io_err(err_desc)?;
+            write_type_diff(
+                base_tokens,
+                tokens,
+                &DiffOptions::default(),
+                false,
+                writer.by_ref(),
+            )?;
+        }
+    }
+
+    writer.flush().map_io_err(err_desc)?;
+
+    Ok(!divergences.is_empty())
+}
+
+/// Records that the corpus with the given index uses the specified type variant, as part of
+/// [`cross_check_buffer()`].
+fn add_cross_check_variant<'a>(
+    variants: &mut Vec<(&'a Tokens, Vec<usize>)>,
+    tokens: &'a Tokens,
+    corpus_idx: usize,
+) {
+    match variants
+        .iter_mut()
+        .find(|(other_tokens, _)| *other_tokens == tokens)
+    {
+        Some((_, corpus_indices)) => {
+            if !corpus_indices.contains(&corpus_idx) {
+                corpus_indices.push(corpus_idx);
+            }
+        }
+        None => variants.push((tokens, vec![corpus_idx])),
+    }
+}
+
+/// Returns the last modification time of the specified file.
+fn get_mtime(path: &Path) -> Result<SystemTime, Error> {
+    fs::metadata(path)
+        .and_then(|md| md.modified())
+        .map_err(|err| {
+            Error::new_io(
+                format!("Failed to query the path '{}'", path.display()),
+                err,
+            )
+        })
+}
+
+/// Returns an order-independent digest of the records of a symtypes file, as they are written in
+/// the split form by [`write_split_records()`].
+fn records_digest(records: &FileRecords) -> u64 {
+    records.iter().fold(0, |digest: u64, (name, tokens_rc)| {
+        let mut line = name.clone();
+        for token in tokens_rc.iter() {
+            line.push(' ');
+            line.push_str(token.as_str());
+        }
+        digest.wrapping_add(hash(line.as_str()))
+    })
+}
+
+/// Returns an order-independent digest of the non-empty lines of the specified symtypes file,
+/// comparable with [`records_digest()`].
+fn file_digest(path: &Path) -> Result<u64, Error> {
+    let file = PathFile::open(path).map_err(|err| {
+        Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+    })?;
+
+    let mut digest: u64 = 0;
+    for maybe_line in BufReader::new(file).lines() {
+        let line = maybe_line.map_err(|err| {
+            Error::new_io(format!("Failed to read the file '{}'", path.display()), err)
+        })?;
+        if !line.is_empty() {
+            digest = digest.wrapping_add(hash(line.as_str()));
+        }
+    }
+    Ok(digest)
+}
+
+/// The magic bytes at the start of a symtypes cache.
+const CACHE_MAGIC: &[u8; 8] = b"KSYMTYPC";
+
+/// The version of the symtypes cache format, to be increased on any change of the format.
+const CACHE_VERSION: u32 = 2;
+
+/// An append-only on-disk store of type variants, used by
+/// [`SymtypesCorpus::write_consolidated_low_memory_buffer()`] to track the active variant of each
+/// type.
+///
+/// The variants are kept in an anonymous temporary file. Only an index that maps each type name
+/// to the hash, offset and length of its active variant is kept in memory. The stored data is read
+/// back only to confirm that a variant with a matching hash is indeed the same.
+struct VariantSpill {
+    file: fs::File,
+    len: u64,
+    index: HashMap<String, (u64, u64, usize)>,
+}
+
+impl VariantSpill {
+    /// Creates a new empty store in the temporary directory.
+    fn new() -> Result<Self, Error> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "ksymtypes-spill-{}-{}",
+            process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|err| {
+                Error::new_io(
+                    format!("Failed to create the file '{}'", path.display()),
+                    err,
+                )
+            })?;
+
+        // Remove the file right away, the data remains accessible through the open handle and is
+        // released when the store is dropped, even if the process is terminated.
+        fs::remove_file(&path).map_err(|err| {
+            Error::new_io(
+                format!("Failed to remove the file '{}'", path.display()),
+                err,
+            )
+        })?;
+
+        Ok(Self {
+            file,
+            len: 0,
+            index: HashMap::new(),
+        })
+    }
+
+    /// Records the given tokens as the active variant of the type, returning whether they differ
+    /// from the previously active variant.
+    fn update(&mut self, name: &str, tokens: &Tokens) -> Result<bool, Error> {
+        let data = tokens
+            .iter()
+            .map(Token::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let data_hash = hash(&data);
+
+        if let Some(&(active_hash, offset, len)) = self.index.get(name)
+            && active_hash == data_hash
+            && len == data.len()
+        {
+            let mut active_data = vec![0; len];
+            self.file
+                .seek(io::SeekFrom::Start(offset))
+                .and_then(|_| self.file.read_exact(&mut active_data))
+                .map_err(|err| Error::new_io("Failed to read a spilled type variant", err))?;
+            if active_data == data.as_bytes() {
+                return Ok(false);
+            }
+        }
+
+        self.file
+            .seek(io::SeekFrom::Start(self.len))
+            .and_then(|_| self.file.write_all(data.as_bytes()))
+            .map_err(|err| Error::new_io("Failed to spill a type variant", err))?;
+        self.index
+            .insert(name.to_string(), (data_hash, self.len, data.len()));
+        self.len += data.len() as u64;
+        Ok(true)
+    }
+}
+
+/// A hasher of symtypes source data, implementing the 64-bit FNV-1a algorithm.
+///
+/// A fixed algorithm is used instead of [`std::hash::DefaultHasher`] so that the resulting hash
+/// remains stable across builds of the tool.
+struct SourceHasher {
+    state: u64,
+}
+
+impl SourceHasher {
+    /// Creates a new hasher.
+    fn new() -> Self {
+        Self {
+            state: 0xcbf29ce484222325,
+        }
+    }
+
+    /// Adds the given bytes to the hash.
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// Adds the contents of the specified file to the hash.
+    fn update_file(&mut self, path: &Path) -> Result<(), Error> {
+        let err_desc = format!("Failed to read the file '{}'", path.display());
+        let mut file = fs::File::open(path).map_err(|err| Error::new_io(&err_desc, err))?;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let size = file
+                .read(&mut buffer)
+                .map_err(|err| Error::new_io(&err_desc, err))?;
+            if size == 0 {
+                break;
+            }
+            self.update(&buffer[..size]);
+        }
+        Ok(())
+    }
+
+    /// Returns the resulting hash.
+    fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+/// A table assigning sequential indices to distinct items when writing a symtypes cache.
+struct CacheTable<K, V> {
+    indices: HashMap<K, u32>,
+    items: Vec<V>,
+}
+
+impl<K: Eq + Hash, V> CacheTable<K, V> {
+    fn new() -> Self {
+        Self {
+            indices: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Adds an item identified by `key` to the table, if not already present, and returns its
+    /// index.
+    fn add(&mut self, key: K, item: V) -> u32 {
+        *self.indices.entry(key).or_insert_with(|| {
+            self.items.push(item);
+            (self.items.len() - 1) as u32
+        })
+    }
+}
+
+/// A helper to write binary symtypes cache data.
+struct CacheWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CacheWriter<W> {
+    const ERR_DESC: &str = "Failed to write symtypes cache data";
+
+    fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.writer.write_all(bytes).map_io_err(Self::ERR_DESC)
+    }
+
+    fn write_u8(&mut self, value: u8) -> Result<(), Error> {
+        self.write_bytes(&[value])
+    }
+
+    fn write_u32(&mut self, value: u32) -> Result<(), Error> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, value: u64) -> Result<(), Error> {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    /// Writes a length of a collection, which must fit in 32 bits.
+    fn write_len(&mut self, len: usize) -> Result<(), Error> {
+        let len = u32::try_from(len)
+            .map_err(|_| Error::new_parse("Symtypes data is too large to be cached"))?;
+        self.write_u32(len)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush().map_io_err(Self::ERR_DESC)
+    }
+}
+
+/// A helper to read binary symtypes cache data.
+struct CacheReader<R: Read> {
+    reader: BufReader<R>,
+}
+
+impl<R: Read> CacheReader<R> {
+    const ERR_DESC: &str = "Failed to read symtypes cache data";
+
+    fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+        }
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), Error> {
+        self.reader.read_exact(bytes).map_io_err(Self::ERR_DESC)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        let mut bytes = [0; 1];
+        self.read_bytes(&mut bytes)?;
+        Ok(bytes[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0; 4];
+        self.read_bytes(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0; 8];
+        self.read_bytes(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads an index and returns the referenced item from the given collection.
+    fn read_item<'a, T>(&mut self, items: &'a [T]) -> Result<&'a T, Error> {
+        let idx = self.read_u32()?;
+        items.get(idx as usize).ok_or_else(|| {
+            Error::new_parse(format!("Invalid index '{}' in the symtypes cache", idx))
+        })
+    }
+}
+
+/// Reads the next line from the given reader into `line`, stripping the line terminator.
+///
+/// Returns `Ok(true)` if a line was read, or `Ok(false)` if the end of the input was reached.
+fn read_next_line<R: BufRead>(reader: &mut R, line: &mut String) -> Result<bool, Error> {
+    line.clear();
+    let size = reader
+        .read_line(line)
+        .map_err(|err| Error::new_io("Failed to read symtypes data", err))?;
+    if size == 0 {
+        return Ok(false);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(true)
+}
+
+/// Returns whether the specified line is a file header in a consolidated symtypes file.
+fn is_file_header(line: &str) -> bool {
+    // The header must contain a non-empty path between its delimiters, which must not overlap.
+    line.len() > "/*  */".len() && line.starts_with("/* ") && line.ends_with(" */")
+}
+
+/// The format header of consolidated symtypes data that records file metadata.
+const FORMAT_HEADER: &str = "/*! consolidated 2 */";
+
+/// Returns whether the specified line is a format header of consolidated symtypes data.
+fn is_format_header(line: &str) -> bool {
+    line.len() >= "/*!  */".len() && line.starts_with("/*! ") && line.ends_with(" */")
+}
+
+/// Checks that the specified format header declares a supported version of the consolidated
+/// format.
+fn check_format_header(path: &Path, line: &str) -> Result<(), Error> {
+    if line == FORMAT_HEADER {
+        return Ok(());
+    }
+    let desc = match line[4..line.len() - 3].split_once(' ') {
+        Some(("consolidated", version)) => {
+            format!("Unsupported consolidated format version '{}'", version)
+        }
+        _ => "Invalid format header".to_string(),
+    };
+    Err(Error::new_parse_format(&desc, path, 1, line))
+}
+
+/// Returns whether the specified line is a file metadata record in a consolidated symtypes file.
+fn is_metadata_line(line: &str) -> bool {
+    line.starts_with("/*@ ") && line.ends_with(" */")
+}
+
+/// Parses a file metadata record in the form `/*@ <key> <value> */` and adds it to `metadata`.
+///
+/// The `has_metadata` indicates whether the data started with a format header, which is required
+/// for any metadata to be present.
+fn parse_metadata_line(
+    path: &Path,
+    line_idx: usize,
+    line: &str,
+    has_metadata: bool,
+    metadata: &mut FileMetadata,
+) -> Result<(), Error> {
+    if !has_metadata {
+        return Err(Error::new_parse_format(
+            "Unexpected file metadata without a format header",
+            path,
+            line_idx + 1,
+            line,
+        ));
+    }
+
+    let Some((key, value)) = line
+        .get(4..line.len() - 3)
+        .and_then(|content| content.split_once(' '))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+    else {
+        return Err(Error::new_parse_format(
+            "Invalid file metadata",
+            path,
+            line_idx + 1,
+            line,
+        ));
+    };
+
+    if metadata.contains_key(key) {
+        return Err(Error::new_parse_format(
+            &format!("Duplicate file metadata '{}'", key),
+            path,
+            line_idx + 1,
+            line,
+        ));
+    }
+    metadata.insert(key.to_string(), value.to_string());
+    Ok(())
+}
+
+/// Formats the specified file metadata as a comma-separated list of `<key>=<value>` pairs.
+fn format_metadata(metadata: &FileMetadata) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats a type record as a line in the symtypes format.
+fn format_type_record(type_name: &str, tokens: &Tokens) -> String {
+    let mut res = type_name.to_string();
+    for token in tokens {
+        res.push(' ');
+        res.push_str(token.as_str());
+    }
+    res
+}
+
+/// Maps each type in the given symtypes file to all types in the file that reference it.
+fn file_referrers(symfile: &SymtypesFile) -> HashMap<&str, Vec<&str>> {
+    let mut referrers = HashMap::<&str, Vec<&str>>::new();
+    for (name, tokens) in &symfile.records {
+        for token in tokens.iter() {
+            if let Token::TypeRef(ref_name) = token {
+                referrers.entry(ref_name).or_default().push(name);
+            }
+        }
+    }
+    referrers
+}
+
+/// Computes the hash of the type closure of each export in the given file records.
+///
+/// The closure of an export consists of its own record and all records reachable from it through
+/// type references. Equal closure hashes of an export in two files therefore indicate that all
+/// its reachable types are defined the same, in which case a detailed comparison can be skipped.
+///
+/// The hashes are computed in a Merkle style over the strongly connected components of the type
+/// graph, which handles recursive types and visits each record only once. The hash of a component
+/// covers the names and tokens of its records, together with the hashes of all components directly
+/// reachable from it.
+fn closure_hashes(records: &FileRecords) -> ExportHashes {
+    // Index the records and their references.
+    let names = records.keys().map(String::as_str).collect::<Vec<_>>();
+    let indices = names
+        .iter()
+        .enumerate()
+        .map(|(idx, &name)| (name, idx))
+        .collect::<HashMap<_, _>>();
+    let edges = names
+        .iter()
+        .map(|&name| {
+            let mut targets = records[name]
+                .iter()
+                .filter_map(|token| match token {
+                    Token::TypeRef(ref_name) => indices.get(&**ref_name).copied(),
+                    Token::Atom(_) => None,
+                })
+                .collect::<Vec<_>>();
+            targets.sort();
+            targets.dedup();
+            targets
+        })
+        .collect::<Vec<_>>();
+
+    // Find the strongly connected components using an iterative version of Tarjan's algorithm.
+    // The components are produced in a reverse topological order, so all components reachable
+    // from a given one are always hashed before it.
+    const UNVISITED: usize = usize::MAX;
+    let mut order = vec![UNVISITED; names.len()];
+    let mut low_link = vec![0; names.len()];
+    let mut on_stack = vec![false; names.len()];
+    let mut component = vec![UNVISITED; names.len()];
+    let mut component_hashes = Vec::new();
+    let mut stack = Vec::new();
+    let mut next_order = 0;
+
+    for root in 0..names.len() {
+        if order[root] != UNVISITED {
+            continue;
+        }
+
+        let mut pending = vec![(root, 0)];
+        while let Some((node, edge_idx)) = pending.pop() {
+            if edge_idx == 0 {
+                order[node] = next_order;
+                low_link[node] = next_order;
+                next_order += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+
+            // Descend into the next unvisited target, if any.
+            if let Some(&target) = edges[node].get(edge_idx) {
+                pending.push((node, edge_idx + 1));
+                if order[target] == UNVISITED {
+                    pending.push((target, 0));
+                } else if on_stack[target] {
+                    low_link[node] = low_link[node].min(order[target]);
+                }
+                continue;
+            }
+
+            // All targets are processed, propagate the low link to the parent.
+            if let Some(&(parent, _)) = pending.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] != order[node] {
+                continue;
+            }
+
+            // The node is a root of a component, pop it and hash it.
+            let component_idx = component_hashes.len();
+            let mut members = Vec::new();
+            loop {
+                let member = stack.pop().unwrap();
+                on_stack[member] = false;
+                component[member] = component_idx;
+                members.push(member);
+                if member == node {
+                    break;
+                }
+            }
+
+            // Export names are left out so that the hashes remain valid when the exports are
+            // renamed by `SymtypesCorpus::strip_symbol_prefix()`. They cannot be referenced by
+            // other records anyway.
+            let mut member_hashes = members
+                .iter()
+                .map(|&member| {
+                    let name = names[member];
+                    let hashed_name = if is_export_name(name) { "" } else { name };
+                    hash(&(hashed_name, &records[name]))
+                })
+                .collect::<Vec<_>>();
+            member_hashes.sort();
+            let mut reachable_hashes = members
+                .iter()
+                .flat_map(|&member| &edges[member])
+                .filter(|&&target| component[target] != component_idx)
+                .map(|&target| component_hashes[component[target]])
+                .collect::<Vec<u64>>();
+            reachable_hashes.sort();
+            reachable_hashes.dedup();
+            component_hashes.push(hash(&(member_hashes, reachable_hashes)));
+        }
+    }
+
+    names
+        .iter()
+        .enumerate()
+        .filter(|&(_, name)| is_export_name(name))
+        .map(|(idx, &name)| {
+            let own_hash = hash(&records[name]);
+            (
+                name.to_string(),
+                hash(&(own_hash, component_hashes[component[idx]])),
+            )
+        })
+        .collect()
+}
+
+/// Returns the path of a symtypes file in the form used to pair it with files of another corpus.
+///
+/// Consolidated files can refer to an individual file by the path of either its object, for
+/// instance, `drivers/foo.o`, or its symtypes file, `drivers/foo.symtypes`. The object path is
+/// converted to the symtypes one.
+fn normalize_file_path(path: &Path) -> PathBuf {
+    if path.extension().is_some_and(|ext| ext == "o") {
+        path.with_extension("symtypes")
+    } else {
+        path.to_path_buf()
     }
 }
 
@@ -1267,6 +5874,43 @@ fn is_unknown_declaration(type_name: &str, tokens: &Tokens) -> bool {
     false
 }
 
+/// Orders the records of a file so that each type comes right before its first user.
+///
+/// Exports are processed in the order of their names and the types referenced by each export are
+/// visited depth-first, in the order of their references. Any types not reachable from an export
+/// are appended at the end, sorted by name.
+fn dependency_ordered_records(records: &FileRecords) -> Vec<(&String, &Arc<Tokens>)> {
+    fn visit<'a>(
+        records: &'a FileRecords,
+        name: &str,
+        visited: &mut HashSet<&'a str>,
+        ordered: &mut Vec<(&'a String, &'a Arc<Tokens>)>,
+    ) {
+        let Some((name, tokens_rc)) = records.get_key_value(name) else {
+            return;
+        };
+        if !visited.insert(name) {
+            return;
+        }
+        for token in tokens_rc.iter() {
+            if let Token::TypeRef(ref_name) = token {
+                visit(records, ref_name, visited, ordered);
+            }
+        }
+        ordered.push((name, tokens_rc));
+    }
+
+    let mut names = records.keys().collect::<Vec<_>>();
+    names.sort_by_key(|&name| (!is_export_name(name), name));
+
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::with_capacity(records.len());
+    for name in names {
+        visit(records, name, &mut visited, &mut ordered);
+    }
+    ordered
+}
+
 /// Tries to shorten the specified type if it represents an UNKNOWN declaration.
 ///
 /// The function maps records like
@@ -1352,7 +5996,7 @@ fn get_next_type_word<I: Iterator<Item = char>>(chars: &mut Peekable<I>) -> Opti
 
         word.push(c);
 
-        if word.len() == 3 && &word[1..] == "#'" {
+        if word.len() == 3 && word.get(1..) == Some("#'") {
             in_quoted = true;
         } else if in_quoted && c == '\'' {
             in_quoted = false;
@@ -1376,10 +6020,23 @@ fn parse_type_record(
 ) -> Result<(String, Tokens, bool), Error> {
     let mut chars = line.chars().peekable();
 
-    let raw_name = get_next_type_word(&mut chars).ok_or_else(|| {
+    let mut raw_name = get_next_type_word(&mut chars).ok_or_else(|| {
         Error::new_parse_format("Expected a record name", path, line_idx + 1, line)
     })?;
 
+    // Skip the override marker that genksyms can emit in front of a record, for instance,
+    // `override s#foo struct foo { int a ; }`.
+    if raw_name == "override" {
+        raw_name = get_next_type_word(&mut chars).ok_or_else(|| {
+            Error::new_parse_format(
+                "Expected a record name after the override marker",
+                path,
+                line_idx + 1,
+                line,
+            )
+        })?;
+    }
+
     if is_consolidated {
         // Check if it is an UNKNOWN override.
         if let Some((name, tokens)) = try_expand_decl(&raw_name) {
@@ -1398,22 +6055,59 @@ fn parse_type_record(
     // Read all remaining tokens on the line that form the type declaration.
     let mut tokens = Tokens::new();
     while let Some(word) = get_next_type_word(&mut chars) {
-        let mut is_typeref = false;
-        if let Some(ch) = word.chars().nth(1)
-            && ch == '#'
-        {
-            is_typeref = true;
-        }
-        tokens.push(if is_typeref {
-            Token::new_typeref(word)
-        } else {
-            Token::new_atom(word)
-        });
+        tokens.push(new_token(word));
     }
 
     Ok((raw_name, tokens, false))
 }
 
+/// Creates a new token from a word of a type declaration, recognizing references to other types.
+fn new_token(word: String) -> Token {
+    let mut is_typeref = false;
+    if let Some(ch) = word.chars().nth(1)
+        && ch == '#'
+    {
+        is_typeref = true;
+    }
+    if is_typeref {
+        Token::new_typeref(word)
+    } else {
+        Token::new_atom(word)
+    }
+}
+
+/// Formats the given name as a quoted Graphviz DOT identifier.
+fn quote_dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Produces a pretty-formatted version of a type as a [`Vec`] of [`String`] lines.
+///
+/// The `tokens_or_line` can be either a complete symtypes record, such as
+/// `s#foo struct foo { int a ; }`, or only the tokens of a type declaration, such as
+/// `struct foo { int a ; }`. The leading word is recognized as a record name if it is the name of
+/// an enumerator constant (`E#`), or if its name, without any type prefix and variant suffix,
+/// appears again in the declaration. The record name is not part of the output.
+pub fn format_type(tokens_or_line: &str) -> Vec<String> {
+    let mut chars = tokens_or_line.chars().peekable();
+    let mut words = Vec::new();
+    while let Some(word) = get_next_type_word(&mut chars) {
+        words.push(word);
+    }
+
+    // Skip the record name, if present.
+    if let Some((first, rest)) = words.split_first() {
+        let (prefix, name) = first.split_once('#').unwrap_or(("", first));
+        let name = name.split_once('@').map_or(name, |(name, _)| name);
+        if prefix == "E" || rest.iter().any(|word| word == name) {
+            words.remove(0);
+        }
+    }
+
+    let tokens = words.into_iter().map(new_token).collect::<Tokens>();
+    pretty_format_type(&tokens)
+}
+
 /// Processes tokens describing a type and produces its pretty-formatted version as a [`Vec`] of
 /// [`String`] lines.
 fn pretty_format_type(tokens: &Tokens) -> Vec<String> {
@@ -1487,14 +6181,880 @@ fn pretty_format_type(tokens: &Tokens) -> Vec<String> {
     res
 }
 
-/// Formats a unified diff between two supposedly different types and writes it to the provided
-/// output stream.
+/// Writes a list of exports affected by a type change to the provided output stream, each on a
+/// separate line prefixed with `indent`.
+///
+/// If `is_short` is set, at most 10 exports are listed and the rest is indicated by `<...>`.
+fn write_export_list<W: Write>(
+    exports: &[&str],
+    indent: &str,
+    is_short: bool,
+    demangle: bool,
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write a comparison result";
+
+    let take_count = if is_short { 10 } else { exports.len() };
+    for export in exports.iter().take(take_count) {
+        writeln!(writer, "{}{}", indent, display_name(export, demangle)).map_io_err(err_desc)?;
+    }
+    if take_count < exports.len() {
+        writeln!(writer, "{}<...>", indent).map_io_err(err_desc)?;
+    }
+    Ok(())
+}
+
+/// Describes a change of the given type and writes it to the provided output stream.
+///
+/// A change between a definition and a forward declaration is summarized in a single line. Other
+/// changes are described by a unified or side-by-side diff of the type. The layout classification
+/// of the change is shown together with its severity, which is replaced by `maybe_toleration` if
+/// the change is tolerated by a severity rule.
+fn write_type_change<W: Write>(
+    name: &str,
+    tokens: &Tokens,
+    other_tokens: &Tokens,
+    maybe_toleration: Option<&str>,
+    diff_options: &DiffOptions,
+    is_side_by_side: bool,
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write a type change";
+
+    let is_unknown = is_unknown_declaration(name, tokens);
+    let is_other_unknown = is_unknown_declaration(name, other_tokens);
+    if is_unknown != is_other_unknown {
+        let forms = ["a definition", "a forward declaration"];
+        let suffix = match maybe_toleration {
+            Some(toleration) => format!(" ({})", toleration),
+            None => String::new(),
+        };
+        writeln!(
+            writer,
+            "because '{}' changed from {} to {}{}",
+            name, forms[is_unknown as usize], forms[is_other_unknown as usize], suffix,
+        )
+        .map_io_err(err_desc)?;
+    } else {
+        let class = match (
+            LayoutChange::classify(name, tokens, other_tokens),
+            maybe_toleration,
+        ) {
+            (Some(layout_change), Some(toleration)) => {
+                format!(" ({}, {})", layout_change, toleration)
+            }
+            (Some(layout_change), None) => format!(
+                " ({}, {})",
+                layout_change,
+                if layout_change.is_probably_safe() {
+                    "probably safe"
+                } else {
+                    "breaking"
+                }
+            ),
+            (None, Some(toleration)) => format!(" ({})", toleration),
+            (None, None) => String::new(),
+        };
+        writeln!(writer, "because of a changed '{}'{}:", name, class).map_io_err(err_desc)?;
+        write_type_diff(tokens, other_tokens, diff_options, is_side_by_side, writer)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a JSON object describing the given comparison result to the provided output stream.
+///
+/// The object lists the names of the `added` and `removed` exports, and describes each changed type
+/// with its name, the sorted names of the affected exports, its [`LayoutChange`] class and
+/// a verdict. The verdict is "probably safe" or "breaking" according to the class, or "tolerated"
+/// if the change is tolerated by a rule, which is then included as well. The class and verdict are
+/// `null` if the change cannot be classified.
+fn write_json_comparison<W: Write>(
+    added: &[&str],
+    removed: &[&str],
+    changes: &[ChangedType],
+    tolerating_rules: &[Option<ToleratingRule>],
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write a JSON comparison result";
+
+    let json_string = |text: &str| format!("\"{}\"", escape_json(text));
+    let json_list = |names: &[&str]| {
+        let names = names
+            .iter()
+            .map(|name| json_string(name))
+            .collect::<Vec<_>>();
+        format!("[{}]", names.join(","))
+    };
+
+    let types = zip(changes, tolerating_rules)
+        .map(|(((name, tokens, other_tokens), exports), maybe_rule)| {
+            let maybe_layout_change = LayoutChange::classify(name, tokens, other_tokens);
+            let verdict = match (maybe_layout_change, maybe_rule) {
+                (_, Some(_)) => json_string("tolerated"),
+                (Some(layout_change), None) if layout_change.is_probably_safe() => {
+                    json_string("probably safe")
+                }
+                (Some(_), None) => json_string("breaking"),
+                (None, None) => "null".to_string(),
+            };
+            format!(
+                "{{\"type\":{},\"exports\":{},\"class\":{},\"verdict\":{},\"rule\":{}}}",
+                json_string(name),
+                json_list(exports),
+                maybe_layout_change.map_or("null".to_string(), |layout_change| {
+                    json_string(&layout_change.to_string())
+                }),
+                verdict,
+                maybe_rule
+                    .as_ref()
+                    .map_or("null".to_string(), |rule| json_string(&rule.to_string())),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    writeln!(
+        writer,
+        "{{\"added\":{},\"removed\":{},\"changed_types\":[{}]}}",
+        json_list(added),
+        json_list(removed),
+        types.join(",")
+    )
+    .map_io_err(err_desc)
+}
+
+/// Punctuation characters that are always separate tokens in the normalized form of a type.
+const NORMALIZED_PUNCTUATION: &[char] = &['*', '(', ')', '[', ']', '{', '}', ',', ';'];
+
+/// Normalizes the tokens of a type so that cosmetic differences don't affect their comparison.
+///
+/// The normalization splits punctuation glued to a word into separate tokens, for instance,
+/// `*foo` becomes `*` and `foo`. Consecutive `__attribute__ ( ( ... ) )` specifiers are merged into
+/// one with the attributes sorted.
+fn normalize_tokens(tokens: &Tokens) -> Tokens {
+    // Split punctuation glued to other characters.
+    let mut split = Tokens::new();
+    for token in tokens {
+        let word = token.as_str();
+        if matches!(token, Token::TypeRef(_))
+            || word.len() <= 1
+            || !word.contains(NORMALIZED_PUNCTUATION)
+        {
+            split.push(token.clone());
+            continue;
+        }
+        let mut start = 0;
+        for (idx, c) in word.match_indices(NORMALIZED_PUNCTUATION) {
+            if start < idx {
+                split.push(Token::new_atom(&word[start..idx]));
+            }
+            split.push(Token::new_atom(c));
+            start = idx + c.len();
+        }
+        if start < word.len() {
+            split.push(Token::new_atom(&word[start..]));
+        }
+    }
+
+    // Merge and sort attributes.
+    let mut normalized = Tokens::new();
+    let mut idx = 0;
+    while idx < split.len() {
+        let mut attributes = Vec::new();
+        while let Some((items, end)) = parse_attribute(&split, idx) {
+            attributes.extend(items);
+            idx = end;
+        }
+        if attributes.is_empty() {
+            normalized.push(split[idx].clone());
+            idx += 1;
+            continue;
+        }
+        attributes.sort();
+        normalized.push(Token::new_atom("__attribute__"));
+        normalized.push(Token::new_atom("("));
+        normalized.push(Token::new_atom("("));
+        for (attribute_idx, attribute) in attributes.into_iter().enumerate() {
+            if attribute_idx > 0 {
+                normalized.push(Token::new_atom(","));
+            }
+            normalized.extend_from_slice(attribute);
+        }
+        normalized.push(Token::new_atom(")"));
+        normalized.push(Token::new_atom(")"));
+    }
+    normalized
+}
+
+/// Parses an `__attribute__ ( ( ... ) )` specifier starting at `tokens[idx]`.
+///
+/// Returns the individual attributes together with the index of the first token after the
+/// specifier, or `None` if no specifier starts at the given index.
+fn parse_attribute(tokens: &[Token], idx: usize) -> Option<(Vec<&[Token]>, usize)> {
+    if !matches!(tokens.get(idx)?.as_str(), "__attribute__" | "__attribute")
+        || tokens.get(idx + 1)?.as_str() != "("
+        || tokens.get(idx + 2)?.as_str() != "("
+    {
+        return None;
+    }
+
+    let mut items = Vec::new();
+    let mut item_start = idx + 3;
+    let mut depth: usize = 2;
+    for (token_idx, token) in tokens.iter().enumerate().skip(idx + 3) {
+        match token.as_str() {
+            "(" => depth += 1,
+            ")" => {
+                depth -= 1;
+                if depth == 1 {
+                    if item_start < token_idx {
+                        items.push(&tokens[item_start..token_idx]);
+                    }
+                    // The specifier must be closed by the outer parenthesis right away.
+                    return (tokens.get(token_idx + 1)?.as_str() == ")")
+                        .then_some((items, token_idx + 2));
+                }
+            }
+            "," if depth == 2 => {
+                if item_start < token_idx {
+                    items.push(&tokens[item_start..token_idx]);
+                }
+                item_start = token_idx + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits the definition of a struct, union or enum into the textual form of its members.
+///
+/// Returns `None` if the tokens don't describe any such definition.
+fn split_members(tokens: &Tokens) -> Option<Vec<String>> {
+    let separator = match tokens.first()?.as_str() {
+        "struct" | "union" => ";",
+        "enum" => ",",
+        _ => return None,
+    };
+    if tokens.len() < 4 || tokens[2].as_str() != "{" || tokens.last()?.as_str() != "}" {
+        return None;
+    }
+
+    let mut members = Vec::new();
+    let mut member = String::new();
+    let mut depth: usize = 0;
+    for token in &tokens[3..tokens.len() - 1] {
+        let token = token.as_str();
+        match token {
+            "{" | "(" | "[" => depth += 1,
+            "}" | ")" | "]" => depth = depth.saturating_sub(1),
+            _ if depth == 0 && token == separator => {
+                members.push(mem::take(&mut member));
+                continue;
+            }
+            _ => {}
+        }
+        if !member.is_empty() && token != "," {
+            member.push(' ');
+        }
+        member.push_str(token);
+    }
+    if !member.is_empty() {
+        members.push(member);
+    }
+
+    Some(members)
+}
+
+/// The value of an enum constant, as an offset from the last explicitly specified expression, or
+/// from 0 if the expression is not known.
+type EnumeratorValue = (Option<String>, i64);
+
+/// Parses the definition of an enum into its constants, each paired with its implied value.
+///
+/// Returns `None` if the tokens don't describe any enum definition.
+fn split_enumerators(tokens: &Tokens) -> Option<Vec<(String, EnumeratorValue)>> {
+    if tokens.first()?.as_str() != "enum" {
+        return None;
+    }
+
+    let mut enumerators = Vec::new();
+    let mut value: EnumeratorValue = (None, -1);
+    for member in split_members(tokens)? {
+        let (name, maybe_expr) = match member.split_once(" = ") {
+            Some((name, expr)) => (name, Some(expr)),
+            None => (member.as_str(), None),
+        };
+        value = match maybe_expr {
+            Some(expr) => match parse_enumerator_literal(expr) {
+                Some(number) => (None, number),
+                None => (Some(expr.to_string()), 0),
+            },
+            None => (value.0, value.1.wrapping_add(1)),
+        };
+        enumerators.push((name.to_string(), value.clone()));
+    }
+
+    Some(enumerators)
+}
+
+/// Parses an integer literal used as the value of an enum constant, for instance, `-1` or `0x10`.
+fn parse_enumerator_literal(expr: &str) -> Option<i64> {
+    let (is_negative, literal) = match expr.strip_prefix("- ") {
+        Some(literal) => (true, literal),
+        None => (false, expr),
+    };
+    let literal = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+    let number = match literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None if literal.len() > 1 && literal.starts_with('0') => {
+            i64::from_str_radix(&literal[1..], 8).ok()?
+        }
+        None => literal.parse::<i64>().ok()?,
+    };
+    Some(if is_negative { -number } else { number })
+}
+
+/// Classifies a change of an enum by comparing its constants together with their values.
+///
+/// Returns `None` if the constants changed in a way that is not specific to enums, for instance,
+/// if some of them were removed.
+fn classify_enumerators(tokens: &Tokens, other_tokens: &Tokens) -> Option<LayoutChange> {
+    let enumerators = split_enumerators(tokens)?;
+    let other_enumerators = split_enumerators(other_tokens)?;
+
+    if other_enumerators.len() > enumerators.len() && other_enumerators.starts_with(&enumerators) {
+        return Some(LayoutChange::EnumeratorAppended);
+    }
+
+    let names = enumerators
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    let other_names = other_enumerators
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    if names == other_names {
+        return (enumerators != other_enumerators).then_some(LayoutChange::EnumeratorRenumbered);
+    }
+    if other_names.len() > names.len() && is_subsequence(&names, &other_names) {
+        return Some(LayoutChange::EnumeratorInserted);
+    }
+
+    None
+}
+
+/// Obtains the name of a struct or union member, given in the form produced by [`split_members()`].
+fn member_name(member: &str) -> Option<&str> {
+    let is_identifier =
+        |token: &str| token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+    let tokens = member.split(' ').collect::<Vec<_>>();
+
+    // A function pointer, for instance, `void ( * cb ) ( int )`, is named after the asterisk.
+    if let Some(idx) = tokens.windows(2).position(|pair| pair == ["(", "*"]) {
+        return tokens[idx + 2..]
+            .iter()
+            .find(|token| is_identifier(token))
+            .copied();
+    }
+
+    // Skip a bit-field width and array dimensions.
+    let mut end = tokens.len();
+    if end >= 2 && tokens[end - 2] == ":" {
+        end -= 2;
+    }
+    while end > 0 && tokens[end - 1] == "]" {
+        end = tokens[..end].iter().rposition(|token| *token == "[")?;
+    }
+    tokens[..end]
+        .last()
+        .filter(|token| is_identifier(token))
+        .copied()
+}
+
+/// Returns the given tokens without any `__attribute__ ( ( ... ) )` specifiers.
+fn strip_attributes(tokens: &Tokens) -> Vec<&Token> {
+    let mut stripped = Vec::new();
+    let mut iter = tokens.iter().peekable();
+    while let Some(token) = iter.next() {
+        if !matches!(token.as_str(), "__attribute__" | "__attribute")
+            || iter.peek().map(|token| token.as_str()) != Some("(")
+        {
+            stripped.push(token);
+            continue;
+        }
+        let mut depth: usize = 0;
+        for token in iter.by_ref() {
+            match token.as_str() {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+    stripped
+}
+
+/// Checks whether the given member looks like a reserved padding.
+fn is_padding_member(member: &str) -> bool {
+    ["reserve", "pad", "unused"]
+        .iter()
+        .any(|word| member.contains(word))
+}
+
+/// Checks whether `members` are all contained in `other_members` in the same order.
+fn is_subsequence(members: &[String], other_members: &[String]) -> bool {
+    let mut other_iter = other_members.iter();
+    members
+        .iter()
+        .all(|member| other_iter.any(|other_member| other_member == member))
+}
+
+/// Suggests a workaround for a type change that preserves the kABI checksums.
+///
+/// The suggestion is derived only from the tokens of the type, so it describes how the members
+/// changed and outlines a typical fix, such as hiding new members from genksyms by a
+/// `__GENKSYMS__` guard. Returns the lines of the suggestion.
+fn suggest_fixup(name: &str, tokens: &Tokens, other_tokens: &Tokens) -> Vec<String> {
+    let is_unknown = is_unknown_declaration(name, tokens);
+    let is_other_unknown = is_unknown_declaration(name, other_tokens);
+    if is_unknown != is_other_unknown {
+        return if is_other_unknown {
+            vec![
+                format!(
+                    "The definition of '{}' is no longer visible to genksyms.",
+                    name
+                ),
+                "Include the header that defines it under '#ifdef __GENKSYMS__'.".to_string(),
+            ]
+        } else {
+            vec![
+                format!("The definition of '{}' became visible to genksyms.", name),
+                "Guard the newly included header with '#ifndef __GENKSYMS__'.".to_string(),
+            ]
+        };
+    }
+
+    let (Some(members), Some(other_members)) = (split_members(tokens), split_members(other_tokens))
+    else {
+        return vec![
+            "No suggestion is available for this type, review the change manually.".to_string(),
+        ];
+    };
+    let is_enum = tokens[0].as_str() == "enum";
+    let (what, terminator) = if is_enum {
+        ("constants", ",")
+    } else {
+        ("members", ";")
+    };
+    let guarded = |lines: &mut Vec<String>, guard: &str, guarded: &[&String]| {
+        lines.push(format!("#{} __GENKSYMS__", guard));
+        for member in guarded {
+            lines.push(format!("\t{}{}", member, terminator));
+        }
+    };
+
+    let mut lines = Vec::new();
+    match LayoutChange::classify(name, tokens, other_tokens) {
+        Some(LayoutChange::MemberAppended | LayoutChange::EnumeratorAppended) => {
+            let added = other_members[members.len()..].iter().collect::<Vec<_>>();
+            lines.push(format!(
+                "New {} are appended at the end of '{}'.",
+                what, name
+            ));
+            if is_enum {
+                lines.push(
+                    "If the enum doesn't size any array or structure, hide them from genksyms:"
+                        .to_string(),
+                );
+            } else if tokens[0].as_str() == "union" {
+                lines.push(
+                    "If the size of the union is unchanged, hide them from genksyms:".to_string(),
+                );
+            } else {
+                lines.push(
+                    "If only the owner allocates the struct and other code uses it through pointers,"
+                        .to_string(),
+                );
+                lines.push("hide them from genksyms:".to_string());
+            }
+            guarded(&mut lines, "ifndef", &added);
+            lines.push("#endif".to_string());
+        }
+        Some(LayoutChange::MemberReordered) => {
+            lines.push(format!(
+                "The {} of '{}' are reordered, which changes their {}.",
+                what,
+                name,
+                if is_enum { "values" } else { "offsets" }
+            ));
+            lines.push(format!("Restore the original order of the {}.", what));
+        }
+        Some(LayoutChange::EnumeratorRenumbered) => {
+            lines.push(format!(
+                "The constants of '{}' keep their names, but their values changed.",
+                name
+            ));
+            lines.push("Restore the original values of the constants.".to_string());
+        }
+        Some(LayoutChange::MemberRemoved) => {
+            let removed = members
+                .iter()
+                .filter(|member| !other_members.contains(member))
+                .map(|member| format!("'{}'", member))
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "The {} {} are removed from '{}'.",
+                what,
+                removed.join(", "),
+                name
+            ));
+            lines.push(
+                "Keep them in place to preserve the layout, for instance, renamed as unused."
+                    .to_string(),
+            );
+        }
+        Some(
+            LayoutChange::MemberTypeChanged | LayoutChange::AttributeChanged | LayoutChange::Mixed,
+        )
+        | None
+            if members.len() == other_members.len() =>
+        {
+            for (member, other_member) in zip(&members, &other_members) {
+                if member == other_member {
+                    continue;
+                }
+                if is_padding_member(member) {
+                    lines.push(format!(
+                        "The reserved member '{}' is replaced by '{}'.",
+                        member, other_member
+                    ));
+                    lines.push(
+                        "If the new member fits into the reserved space, keep the original for genksyms:"
+                            .to_string(),
+                    );
+                } else {
+                    lines.push(format!("'{}' is changed to '{}'.", member, other_member));
+                    lines.push(
+                        "If the size and alignment are unchanged, keep the original for genksyms:"
+                            .to_string(),
+                    );
+                }
+                guarded(&mut lines, "ifdef", &[member]);
+                lines.push("#else".to_string());
+                lines.push(format!("\t{}{}", other_member, terminator));
+                lines.push("#endif".to_string());
+            }
+        }
+        Some(LayoutChange::EnumeratorInserted) => {
+            suggest_inserted(&mut lines, name, &members, &other_members, is_enum)
+        }
+        Some(LayoutChange::Mixed) | None if is_subsequence(&members, &other_members) => {
+            suggest_inserted(&mut lines, name, &members, &other_members, is_enum)
+        }
+        _ => {
+            lines.push(format!(
+                "The {} of '{}' changed in a complex way, review the change manually.",
+                what, name
+            ));
+        }
+    }
+
+    lines
+}
+
+/// Adds a suggestion for members or constants inserted in the middle of the type `name` to `lines`.
+fn suggest_inserted(
+    lines: &mut Vec<String>,
+    name: &str,
+    members: &[String],
+    other_members: &[String],
+    is_enum: bool,
+) {
+    let what = if is_enum { "constants" } else { "members" };
+    let added = other_members
+        .iter()
+        .filter(|member| !members.contains(member))
+        .map(|member| format!("'{}'", member))
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "The {} {} are inserted into '{}', which changes the {} of the following {}.",
+        what,
+        added.join(", "),
+        name,
+        if is_enum { "values" } else { "offsets" },
+        what
+    ));
+    if is_enum {
+        lines.push("Move the new constants to the end of the enum.".to_string());
+    } else {
+        lines.push(
+            "Move the new members to the end of the struct, or into a reserved padding."
+                .to_string(),
+        );
+    }
+}
+
+/// Writes a suggestion of a workaround for a type change, as provided by [`suggest_fixup()`], to
+/// the provided output stream.
+fn write_fixup_suggestion<W: Write>(
+    name: &str,
+    tokens: &Tokens,
+    other_tokens: &Tokens,
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write a fixup suggestion";
+
+    writeln!(writer, "Suggested fixup (experimental):").map_io_err(err_desc)?;
+    for line in suggest_fixup(name, tokens, other_tokens) {
+        writeln!(writer, " {}", line).map_io_err(err_desc)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a single file in the consolidated form to the provided output stream, with records
+/// ordered according to `order`, including file metadata if `maybe_metadata` is specified.
+///
+/// The `update_active` function is called for each type that is not written as a local override.
+/// It records the type as active and returns whether it differs from the previously active
+/// definition, in which case the type is written.
+fn write_consolidated_file<'a, W, F>(
+    symfile: &'a SymtypesFile,
+    order: ConsolidateOrder,
+    maybe_metadata: Option<&FileMetadata>,
+    mut update_active: F,
+    mut writer: W,
+) -> Result<(), Error>
+where
+    W: Write,
+    F: FnMut(&'a String, &'a Arc<Tokens>) -> Result<bool, Error>,
+{
+    let err_desc = "Failed to write a consolidated record";
+
+    // Sort all types in the file.
+    let sorted_types = match order {
+        ConsolidateOrder::Path => {
+            let mut sorted_types = symfile.records.iter().collect::<Vec<_>>();
+            sorted_types.sort_by_cached_key(|&(name, _)| (is_export_name(name), name));
+            sorted_types
+        }
+        ConsolidateOrder::Symbol => {
+            let mut sorted_types = symfile.records.iter().collect::<Vec<_>>();
+            sorted_types.sort_by_key(|&(name, _)| name);
+            sorted_types
+        }
+        ConsolidateOrder::OldScript => dependency_ordered_records(&symfile.records),
+    };
+
+    // Write the file header and metadata.
+    writeln!(writer, "/* {} */", symfile.path.display()).map_io_err(err_desc)?;
+    if let Some(common_metadata) = maybe_metadata {
+        let mut metadata = symfile.metadata.clone();
+        metadata
+            .entry("object".to_string())
+            .or_insert_with(|| symfile.path.with_extension("o").display().to_string());
+        metadata.extend(
+            common_metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        for (key, value) in &metadata {
+            writeln!(writer, "/*@ {} {} */", key, value).map_io_err(err_desc)?;
+        }
+    }
+
+    // Write all output types.
+    for (name, tokens_rc) in sorted_types {
+        // Check if this is an UNKNOWN type definition, and if so, record it as a local
+        // override.
+        if let Some(short_name) = try_shorten_decl(name, tokens_rc) {
+            writeln!(writer, "{}", short_name).map_io_err(err_desc)?;
+            continue;
+        }
+
+        // See if the symbol matches an already active definition, or record it in the
+        // output.
+        if update_active(name, tokens_rc)? {
+            write!(writer, "{}", name).map_io_err(err_desc)?;
+            for token in tokens_rc.iter() {
+                write!(writer, " {}", token.as_str()).map_io_err(err_desc)?;
+            }
+            if order == ConsolidateOrder::OldScript {
+                write!(writer, " ").map_io_err(err_desc)?;
+            }
+            writeln!(writer).map_io_err(err_desc)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes an HTML table of modified exports to the provided output stream.
+///
+/// The `export_changes` maps each modified export to the indices and names of changed types that
+/// affect it. Each type links to its section written by [`write_html_type_change()`].
+fn write_html_index<W: Write>(
+    export_changes: &BTreeMap<&str, Vec<(usize, &str)>>,
+    demangle: bool,
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write an HTML index";
+
+    writeln!(
+        writer,
+        concat!(
+            "<h2>Modified exports</h2>\n",
+            "<table>\n",
+            "<tr><th>Export</th><th>Changed types</th></tr>",
+        )
+    )
+    .map_io_err(err_desc)?;
+    for (export, type_changes) in export_changes {
+        let links = type_changes
+            .iter()
+            .map(|(change_idx, name)| {
+                format!(
+                    "<a href=\"#change-{}\"><code>{}</code></a>",
+                    change_idx,
+                    escape_html(name)
+                )
+            })
+            .collect::<Vec<_>>();
+        writeln!(
+            writer,
+            "<tr id=\"export-{}\"><td><code>{}</code></td><td>{}</td></tr>",
+            escape_html(export),
+            escape_html(&display_name(export, demangle)),
+            links.join(", ")
+        )
+        .map_io_err(err_desc)?;
+    }
+    writeln!(writer, "</table>\n<h2>Changed types</h2>").map_io_err(err_desc)
+}
+
+/// Writes a collapsible HTML section describing a change of the given type to the provided output
+/// stream.
+#[allow(clippy::too_many_arguments)]
+fn write_html_type_change<W: Write>(
+    change_idx: usize,
+    name: &str,
+    tokens: &Tokens,
+    other_tokens: &Tokens,
+    exports: &[&str],
+    maybe_toleration: Option<&str>,
+    diff_options: &DiffOptions,
+    demangle: bool,
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write an HTML type change";
+
+    writeln!(
+        writer,
+        concat!(
+            "<details id=\"change-{}\" open>\n",
+            "<summary>Changed type <code>{}</code> affecting '{}' exports</summary>",
+        ),
+        change_idx,
+        escape_html(name),
+        exports.len()
+    )
+    .map_io_err(err_desc)?;
+
+    let links = exports
+        .iter()
+        .map(|export| {
+            format!(
+                "<a href=\"#export-{}\"><code>{}</code></a>",
+                escape_html(export),
+                escape_html(&display_name(export, demangle))
+            )
+        })
+        .collect::<Vec<_>>();
+    writeln!(writer, "<p>Affected exports: {}</p>", links.join(", ")).map_io_err(err_desc)?;
+
+    // Describe the change in the text form and highlight its diff.
+    let mut description = Vec::new();
+    write_type_change(
+        name,
+        tokens,
+        other_tokens,
+        maybe_toleration,
+        diff_options,
+        false,
+        &mut description,
+    )?;
+    let description = String::from_utf8_lossy(&description);
+    let mut lines = description.lines();
+    if let Some(summary) = lines.next() {
+        writeln!(writer, "<p>{}</p>", escape_html(summary)).map_io_err(err_desc)?;
+    }
+    let diff = lines.collect::<Vec<_>>();
+    if !diff.is_empty() {
+        write_html_diff(&diff, writer.by_ref())?;
+    }
+
+    writeln!(writer, "</details>").map_io_err(err_desc)
+}
+
+/// Checks whether the given symtypes file belongs to an auxiliary component that is built
+/// separately from vmlinux and modules, such as the boot code, purgatory, vDSO or EFI stub. Such
+/// components can include regular kernel sources, duplicating their exports.
+fn is_auxiliary_path(path: &Path) -> bool {
+    let components = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    if components.len() >= 3
+        && components[0] == "arch"
+        && (components[2] == "boot" || components[2] == "purgatory")
+    {
+        return true;
+    }
+    components
+        .iter()
+        .any(|component| component.starts_with("vdso") || component == "libstub")
+}
+
+/// Writes the records of a symtypes file in the split form to the provided output stream. The types
+/// are sorted by their name and precede all exports.
+fn write_split_records<W: Write>(records: &FileRecords, mut writer: W) -> Result<(), Error> {
+    let err_desc = "Failed to write a split record";
+
+    let mut sorted_types = records.iter().collect::<Vec<_>>();
+    sorted_types.sort_by_cached_key(|&(name, _)| (is_export_name(name), name));
+
+    for (name, tokens_rc) in sorted_types {
+        write!(writer, "{}", name).map_io_err(err_desc)?;
+        for token in tokens_rc.iter() {
+            write!(writer, " {}", token.as_str()).map_io_err(err_desc)?;
+        }
+        writeln!(writer).map_io_err(err_desc)?;
+    }
+    Ok(())
+}
+
+/// Formats a unified diff, or a side-by-side diff if `is_side_by_side` is set, between two
+/// supposedly different types and writes it to the provided output stream.
 fn write_type_diff<W: Write>(
     tokens: &Tokens,
     other_tokens: &Tokens,
+    diff_options: &DiffOptions,
+    is_side_by_side: bool,
     writer: W,
 ) -> Result<(), Error> {
     let pretty = pretty_format_type(tokens);
     let other_pretty = pretty_format_type(other_tokens);
-    unified_diff(&pretty, &other_pretty, writer)
+    if is_side_by_side {
+        side_by_side_diff(&pretty, &other_pretty, writer)
+    } else {
+        unified_diff_with_options(&pretty, &other_pretty, diff_options, writer)
+    }
 }
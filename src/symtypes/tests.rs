@@ -3,7 +3,9 @@
 
 use super::*;
 use crate::burst::JobControl;
-use crate::{assert_ok, assert_ok_eq, assert_parse_err, bytes};
+use crate::rules::Rules;
+use crate::warnings::{Warnings, WarningsMode};
+use crate::{assert_ok, assert_ok_eq, assert_parse_err, bytes, string_vec};
 
 #[test]
 fn read_single_basic() {
@@ -40,14 +42,14 @@ fn read_single_basic() {
         Token::new_atom("("),
         Token::new_atom(")"),
     ]);
-    let test_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test.symtypes"),
-        records: HashMap::from([
+    let test_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("bar".to_string(), Arc::clone(&bar_tokens_rc)),
             ("baz".to_string(), Arc::clone(&baz_tokens_rc)),
         ]),
-    });
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([(test_symfile_rc.path.clone(), Arc::clone(&test_symfile_rc))]),
@@ -55,6 +57,13 @@ fn read_single_basic() {
             ("bar".to_string(), Arc::clone(&test_symfile_rc)),
             ("baz".to_string(), Arc::clone(&test_symfile_rc)),
         ]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#foo")]
         .insert("s#foo".to_string(), vec![Arc::clone(&foo_tokens_rc)]);
@@ -65,6 +74,106 @@ fn read_single_basic() {
     assert_eq!(symtypes, exp_symtypes);
 }
 
+#[test]
+fn read_from_files() {
+    // Check that a corpus created from in-memory files is the same as one loaded file by file.
+    let mut warnings = Vec::new();
+    let result = SymtypesCorpus::from_files(
+        HashMap::from([
+            (
+                PathBuf::from("b.symtypes"),
+                "s#foo struct foo { int a ; }\nbar int bar ( s#foo )\n".to_string(),
+            ),
+            (
+                PathBuf::from("a.symtypes"),
+                "s#foo struct foo { long a ; }\nbaz int baz ( s#foo )\n".to_string(),
+            ),
+        ]),
+        &mut warnings,
+    );
+    let symtypes = result.unwrap();
+    assert!(warnings.is_empty());
+
+    let mut exp_symtypes = SymtypesCorpus::new();
+    let result = exp_symtypes.load_buffer(
+        "a.symtypes",
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let result = exp_symtypes.load_buffer(
+        "b.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert_eq!(symtypes, exp_symtypes);
+}
+
+#[test]
+fn read_malformed_headers() {
+    // Check that lines too short to be file or format headers are read as plain records, and that
+    // a non-ASCII record name doesn't cause a panic.
+    for data in ["/* */\n", "/*! */\n", "\u{e9}#'foo bar' int\n"] {
+        let mut warnings = Vec::new();
+        let mut symtypes = SymtypesCorpus::new();
+        let result = symtypes.load_buffer("test.symtypes", data.as_bytes(), &mut warnings);
+        assert_ok!(result);
+        let result = SymtypesCorpus::validate_buffer("test.symtypes", data.as_bytes());
+        assert!(result.unwrap().is_empty());
+    }
+}
+
+#[test]
+fn read_interned_tokens() {
+    // Check that tokens with the same data share the same string.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "bar int bar ( )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let bar_tokens = &symtypes.files[Path::new("test.symtypes")].records["bar"];
+    let baz_tokens = &symtypes.files[Path::new("test.symtypes")].records["baz"];
+    match (&bar_tokens[0], &baz_tokens[0]) {
+        (Token::Atom(bar_word), Token::Atom(baz_word)) => {
+            assert_eq!(bar_word.as_ref(), "int");
+            assert!(Arc::ptr_eq(bar_word, baz_word));
+        }
+        tokens => panic!("unexpected tokens: {:?}", tokens),
+    }
+}
+
+#[test]
+fn release_unused_tokens_basic() {
+    // Check that only strings no longer used by any token are released from the pool.
+    let used = Token::new_atom("release_used");
+    drop(Token::new_typeref("s#release_unused"));
+    release_unused_tokens();
+    let is_interned = |word: &str| {
+        TOKEN_POOL
+            .get()
+            .unwrap()
+            .iter()
+            .any(|shard| shard.read().unwrap().contains(word))
+    };
+    assert!(is_interned("release_used"));
+    assert!(!is_interned("s#release_unused"));
+    assert_eq!(used.as_str(), "release_used");
+}
+
 #[test]
 fn read_consolidated_basic() {
     // Check basic reading of a consolidated file.
@@ -103,17 +212,17 @@ fn read_consolidated_basic() {
         Token::new_atom("("),
         Token::new_atom(")"),
     ]);
-    let test_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test.symtypes"),
-        records: HashMap::from([
+    let test_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("bar".to_string(), Arc::clone(&bar_tokens_rc)),
         ]),
-    });
-    let test2_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test2.symtypes"),
-        records: HashMap::from([("baz".to_string(), Arc::clone(&baz_tokens_rc))]),
-    });
+    ));
+    let test2_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test2.symtypes"),
+        HashMap::from([("baz".to_string(), Arc::clone(&baz_tokens_rc))]),
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([
@@ -124,6 +233,13 @@ fn read_consolidated_basic() {
             ("bar".to_string(), Arc::clone(&test_symfile_rc)),
             ("baz".to_string(), Arc::clone(&test2_symfile_rc)),
         ]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#foo")]
         .insert("s#foo".to_string(), vec![Arc::clone(&foo_tokens_rc)]);
@@ -195,27 +311,27 @@ fn read_consolidated_unknown() {
         Token::new_typeref("s#foo"),
         Token::new_atom(")"),
     ]);
-    let test_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test.symtypes"),
-        records: HashMap::from([
+    let test_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("bar".to_string(), Arc::clone(&bar_tokens_rc)),
         ]),
-    });
-    let test2_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test2.symtypes"),
-        records: HashMap::from([
+    ));
+    let test2_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test2.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_unknown_tokens_rc)),
             ("baz".to_string(), Arc::clone(&baz_tokens_rc)),
         ]),
-    });
-    let test3_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test3.symtypes"),
-        records: HashMap::from([
+    ));
+    let test3_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test3.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("qux".to_string(), Arc::clone(&qux_tokens_rc)),
         ]),
-    });
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([
@@ -228,6 +344,13 @@ fn read_consolidated_unknown() {
             ("baz".to_string(), Arc::clone(&test2_symfile_rc)),
             ("qux".to_string(), Arc::clone(&test3_symfile_rc)),
         ]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#foo")].insert(
         "s#foo".to_string(),
@@ -276,17 +399,24 @@ fn read_second() {
         Token::new_typeref("s#foo"),
         Token::new_atom(")"),
     ]);
-    let test_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test.symtypes"),
-        records: HashMap::from([
+    let test_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("bar".to_string(), Arc::clone(&bar_tokens_rc)),
         ]),
-    });
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([(test_symfile_rc.path.clone(), Arc::clone(&test_symfile_rc))]),
         exports: HashMap::from([("bar".to_string(), Arc::clone(&test_symfile_rc))]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#foo")]
         .insert("s#foo".to_string(), vec![Arc::clone(&foo_tokens_rc)]);
@@ -320,14 +450,14 @@ fn read_second() {
         Token::new_typeref("s#foo"),
         Token::new_atom(")"),
     ]);
-    let test2_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test2.symtypes"),
-        records: HashMap::from([
+    let test2_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test2.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("baz".to_string(), Arc::clone(&baz_tokens_rc)),
             ("qux".to_string(), Arc::clone(&qux_tokens_rc)),
         ]),
-    });
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([
@@ -339,6 +469,13 @@ fn read_second() {
             ("baz".to_string(), Arc::clone(&test2_symfile_rc)),
             ("qux".to_string(), Arc::clone(&test2_symfile_rc)),
         ]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#foo")]
         .insert("s#foo".to_string(), vec![Arc::clone(&foo_tokens_rc)]);
@@ -382,17 +519,24 @@ fn read_second_error() {
         Token::new_typeref("s#foo"),
         Token::new_atom(")"),
     ]);
-    let test_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test.symtypes"),
-        records: HashMap::from([
+    let test_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test.symtypes"),
+        HashMap::from([
             ("s#foo".to_string(), Arc::clone(&foo_tokens_rc)),
             ("bar".to_string(), Arc::clone(&bar_tokens_rc)),
         ]),
-    });
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([(test_symfile_rc.path.clone(), Arc::clone(&test_symfile_rc))]),
         exports: HashMap::from([("bar".to_string(), Arc::clone(&test_symfile_rc))]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#foo")]
         .insert("s#foo".to_string(), vec![Arc::clone(&foo_tokens_rc)]);
@@ -450,17 +594,24 @@ fn read_quoted_type() {
         Token::new_typeref("s#'foo foo'"),
         Token::new_atom(")"),
     ]);
-    let test_symfile_rc = Arc::new(SymtypesFile {
-        path: PathBuf::from("test.symtypes"),
-        records: HashMap::from([
+    let test_symfile_rc = Arc::new(SymtypesFile::new(
+        PathBuf::from("test.symtypes"),
+        HashMap::from([
             ("s#'foo foo'".to_string(), Arc::clone(&foo_tokens_rc)),
             ("bar".to_string(), Arc::clone(&bar_tokens_rc)),
         ]),
-    });
+    ));
     let mut exp_symtypes = SymtypesCorpus {
         types: vec![Types::new(); TYPE_BUCKETS_SIZE],
         files: HashMap::from([(test_symfile_rc.path.clone(), Arc::clone(&test_symfile_rc))]),
         exports: HashMap::from([("bar".to_string(), Arc::clone(&test_symfile_rc))]),
+        keep_going: false,
+        duplicate_exports: DuplicateExportPolicy::First,
+        demangle: false,
+        color: ColorMode::Never,
+        suggest_fixup: false,
+        strict: false,
+        maybe_output_header: None,
     };
     exp_symtypes.types[type_bucket_idx("s#'foo foo'")]
         .insert("s#'foo foo'".to_string(), vec![Arc::clone(&foo_tokens_rc)]);
@@ -545,6 +696,50 @@ fn read_override_extra_data() {
     assert!(warnings.is_empty());
 }
 
+#[test]
+fn read_genksyms_override_marker() {
+    // Check that the genksyms override marker in front of a record is skipped.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "override s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* test.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        )
+    );
+
+    let result = symtypes.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "override\n", //
+        ),
+        &mut warnings,
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Expected a record name after the override marker\n",
+            " test2.symtypes:1\n",
+            " | override", //
+        ),
+    );
+}
+
 #[test]
 fn read_duplicate_type_record() {
     // Check that type records with duplicate names are rejected when reading a symtypes file.
@@ -609,6 +804,98 @@ fn read_invalid_reference() {
     assert!(warnings.is_empty());
 }
 
+#[test]
+fn read_invalid_reference_consolidated() {
+    // Check that a record in a consolidated file referencing a symbol with a missing declaration is
+    // rejected and the error points to the correct line.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test_consolidated.symtypes",
+        bytes!(
+            "/* test.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "bar void bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Type 's#foo' is not known\n",
+            " test_consolidated.symtypes:5\n",
+            " | bar void bar ( s#foo )", //
+        ),
+    );
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn read_keep_going() {
+    // Check that all errors in a symtypes file are reported together in the keep-going mode and
+    // that the corpus remains unchanged.
+    let mut symtypes = SymtypesCorpus::new();
+    symtypes.set_keep_going(true);
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "s#foo struct foo { int b ; }\n",
+            "\n",
+            "bar int bar ( s#baz )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Duplicate record 's#foo'\n",
+            " test.symtypes:2\n",
+            " | s#foo struct foo { int b ; }\n",
+            "Expected a record name\n",
+            " test.symtypes:3\n",
+            " | \n",
+            "Type 's#baz' is not known\n",
+            " test.symtypes:4\n",
+            " | bar int bar ( s#baz )", //
+        ),
+    );
+    assert!(warnings.is_empty());
+    assert_eq!(symtypes, SymtypesCorpus::new());
+}
+
+#[test]
+fn read_crlf_line_endings() {
+    // Check that lines terminated by CRLF are read the same as lines terminated by LF.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\r\n",
+            "bar int bar ( s#foo )\r\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert_eq!(symtypes, symtypes2);
+}
+
 #[test]
 fn read_duplicate_type_export_single() {
     // Check that two exports with the same name in two different symtypes files produce a warning.
@@ -662,41 +949,109 @@ fn read_duplicate_type_export_consolidated() {
 }
 
 #[test]
-fn read_write_basic() {
-    // Check reading of a single file and writing the consolidated output.
+fn read_duplicate_type_export_error() {
+    // Check that a duplicate export is reported as an error when warnings are treated as errors,
+    // leaving the corpus unchanged.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut out = Vec::new();
+    let result = symtypes.load_buffer(
+        "test_consolidated.symtypes",
+        bytes!(
+            "/* test.symtypes */\n",
+            "foo int foo ( )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "foo int foo ( )", //
+        ),
+        Warnings::new(&mut out, WarningsMode::Error, None),
+    );
+    assert_parse_err!(
+        result,
+        "test_consolidated.symtypes:5: Export 'foo' defined in 'test2.symtypes' is duplicate, previous occurrence found in 'test.symtypes'"
+    );
+    assert_eq!(symtypes, SymtypesCorpus::new());
+    assert!(out.is_empty());
+}
+
+#[test]
+fn read_duplicate_type_export_policy() {
+    // Check that the duplicate export policy selects which occurrence of an export is kept.
+    let data = bytes!(
+        "/* arch/x86/boot/string.symtypes */\n",
+        "foo int foo ( char )\n",
+        "\n",
+        "/* lib/string.symtypes */\n",
+        "foo int foo ( int )\n",
+        "\n",
+        "/* lib/string2.symtypes */\n",
+        "foo int foo ( long )", //
+    );
+    for (policy, expected_path) in [
+        (
+            DuplicateExportPolicy::First,
+            "arch/x86/boot/string.symtypes",
+        ),
+        (DuplicateExportPolicy::Last, "lib/string2.symtypes"),
+        (DuplicateExportPolicy::PreferVmlinux, "lib/string.symtypes"),
+    ] {
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_duplicate_exports(policy);
+        let mut warnings = Vec::new();
+        let result = symtypes.load_buffer("test_consolidated.symtypes", data, &mut warnings);
+        assert_ok!(result);
+        assert_eq!(symtypes.exports["foo"].path, Path::new(expected_path));
+        assert_eq!(str::from_utf8(&warnings).unwrap().lines().count(), 2);
+    }
+
+    // Check that an export already present in the corpus is replaced only by the last policy.
+    for (policy, expected_path) in [
+        (DuplicateExportPolicy::First, "b.symtypes"),
+        (DuplicateExportPolicy::Last, "a.symtypes"),
+    ] {
+        let mut symtypes = SymtypesCorpus::new();
+        symtypes.set_duplicate_exports(policy);
+        let mut warnings = Vec::new();
+        let result = symtypes.load_buffer("b.symtypes", bytes!("foo int foo ( )\n"), &mut warnings);
+        assert_ok!(result);
+        let result = symtypes.load_buffer("a.symtypes", bytes!("foo int foo ( )\n"), &mut warnings);
+        assert_ok!(result);
+        assert_eq!(symtypes.exports["foo"].path, Path::new(expected_path));
+    }
+}
+
+#[test]
+fn read_duplicate_type_export_policy_error() {
+    // Check that a duplicate export is rejected with the error policy, leaving the corpus
+    // unchanged.
     let mut symtypes = SymtypesCorpus::new();
+    symtypes.set_duplicate_exports(DuplicateExportPolicy::Error);
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "test.symtypes",
+        "test_consolidated.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
+            "/* test.symtypes */\n",
+            "foo int foo ( )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "foo int foo ( )", //
         ),
         &mut warnings,
     );
-    assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut out = Vec::new();
-    let result = symtypes.write_consolidated_buffer(&mut out);
-    assert_ok!(result);
-    assert_eq!(
-        str::from_utf8(&out).unwrap(),
-        concat!(
-            "/* test.symtypes */\n",
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
-        )
+    assert_parse_err!(
+        result,
+        "test_consolidated.symtypes:5: Export 'foo' defined in 'test2.symtypes' is duplicate, previous occurrence found in 'test.symtypes'"
     );
+    assert_eq!(symtypes, SymtypesCorpus::new());
+    assert!(warnings.is_empty());
 }
 
 #[test]
-fn read_write_shared_struct() {
-    // Check that a structure declaration shared by two files appears only once in the consolidated
-    // output.
+fn iterate_exports_and_types() {
+    // Check that the exports, type variants and file records of a corpus can be visited.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "test.symtypes",
+        "a.symtypes",
         bytes!(
             "s#foo struct foo { int a ; }\n",
             "bar int bar ( s#foo )\n", //
@@ -704,619 +1059,891 @@ fn read_write_shared_struct() {
         &mut warnings,
     );
     assert_ok!(result);
-    assert!(warnings.is_empty());
     let result = symtypes.load_buffer(
-        "test2.symtypes",
+        "b.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
+            "s#foo struct foo { UNKNOWN }\n",
             "baz int baz ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut out = Vec::new();
-    let result = symtypes.write_consolidated_buffer(&mut out);
-    assert_ok!(result);
+
+    let mut exports = Vec::new();
+    symtypes.for_each_export(|name, path| exports.push((name.to_string(), path.to_path_buf())));
+    exports.sort();
     assert_eq!(
-        str::from_utf8(&out).unwrap(),
-        concat!(
-            "/* test.symtypes */\n",
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n",
-            "\n",
-            "/* test2.symtypes */\n",
-            "baz int baz ( s#foo )\n", //
-        )
+        exports,
+        [
+            ("bar".to_string(), PathBuf::from("a.symtypes")),
+            ("baz".to_string(), PathBuf::from("b.symtypes")),
+        ]
+    );
+
+    let mut variants = Vec::new();
+    symtypes.for_each_type_variant(|name, tokens| {
+        let words = tokens.iter().map(Token::as_str).collect::<Vec<_>>();
+        variants.push(format!("{} {}", name, words.join(" ")));
+    });
+    variants.sort();
+    assert_eq!(
+        variants,
+        [
+            "bar int bar ( s#foo )",
+            "baz int baz ( s#foo )",
+            "s#foo struct foo { UNKNOWN }",
+            "s#foo struct foo { int a ; }",
+        ]
     );
+
+    let mut records = symtypes
+        .file_records("b.symtypes")
+        .unwrap()
+        .map(|(name, tokens)| (name, tokens.len()))
+        .collect::<Vec<_>>();
+    records.sort();
+    assert_eq!(records, [("baz", 5), ("s#foo", 5)]);
+    assert!(symtypes.file_records("c.symtypes").is_none());
 }
 
 #[test]
-fn read_write_differing_struct() {
-    // Check that a structure declaration different in two files appears in all variants in the
-    // consolidated output and they are correctly referenced by the file entries.
+fn strip_symbol_prefix() {
+    // Check that a symbol prefix is stripped from all export names, leaving other records intact.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
         "test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
+            "s#_foo struct _foo { int a ; }\n",
+            "_bar int bar ( s#_foo )\n",
+            "_baz int baz ( )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let result = symtypes.load_buffer(
-        "test2.symtypes",
-        bytes!(
-            "s#foo struct foo { long a ; }\n",
-            "baz int baz ( s#foo )\n", //
-        ),
-        &mut warnings,
-    );
+    assert!(symtypes.has_symbol_prefix("_"));
+    let result = symtypes.strip_symbol_prefix("_");
     assert_ok!(result);
-    assert!(warnings.is_empty());
+    assert!(!symtypes.has_symbol_prefix("_"));
     let mut out = Vec::new();
-    let result = symtypes.write_consolidated_buffer(&mut out);
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
     assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
             "/* test.symtypes */\n",
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n",
-            "\n",
-            "/* test2.symtypes */\n",
-            "s#foo struct foo { long a ; }\n",
-            "baz int baz ( s#foo )\n", //
+            "s#_foo struct _foo { int a ; }\n",
+            "bar int bar ( s#_foo )\n",
+            "baz int baz ( )\n", //
         )
     );
 }
 
 #[test]
-fn read_write_differing_struct_unknown() {
-    // Check that a structure declaration with UNKNOWN content appears in the consolidated output as
-    // an ## override.
+fn strip_symbol_prefix_clash() {
+    // Check that stripping a symbol prefix is rejected if it results in a duplicate export.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
         "test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
+            "_bar int bar ( )\n",
+            "bar int bar ( )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
+    assert!(!symtypes.has_symbol_prefix("_"));
+    let result = symtypes.strip_symbol_prefix("_");
+    assert_parse_err!(
+        result,
+        "Export '_bar' clashes with 'bar' after stripping the symbol prefix '_'"
+    );
+}
+
+#[test]
+fn optimize() {
+    // Check that optimizing a corpus merges identical tokens and leaves its data unchanged.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "test2.symtypes",
+        "test.symtypes",
         bytes!(
-            "s#foo struct foo { UNKNOWN }\n",
-            "baz int baz ( s#foo )\n", //
+            "t#foo_t int\n",
+            "t#bar_t int\n",
+            "baz int baz ( t#foo_t , t#bar_t )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let result = symtypes.load_buffer(
-        "test3.symtypes",
+    let tokens = |symtypes: &SymtypesCorpus, name: &str| {
+        Arc::clone(&symtypes.types[type_bucket_idx(name)][name][0])
+    };
+    assert!(!Arc::ptr_eq(
+        &tokens(&symtypes, "t#foo_t"),
+        &tokens(&symtypes, "t#bar_t")
+    ));
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "qux int qux ( s#foo )\n", //
+            "t#foo_t int\n",
+            "t#bar_t int\n",
+            "baz int baz ( t#foo_t , t#bar_t )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut out = Vec::new();
-    let result = symtypes.write_consolidated_buffer(&mut out);
-    assert_ok!(result);
-    assert_eq!(
-        str::from_utf8(&out).unwrap(),
-        concat!(
-            "/* test.symtypes */\n",
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n",
-            "\n",
-            "/* test2.symtypes */\n",
-            "s##foo\n",
-            "baz int baz ( s#foo )\n",
-            "\n",
-            "/* test3.symtypes */\n",
-            "qux int qux ( s#foo )\n", //
-        )
-    );
+    symtypes.optimize();
+    assert!(Arc::ptr_eq(
+        &tokens(&symtypes, "t#foo_t"),
+        &tokens(&symtypes, "t#bar_t")
+    ));
+    let symfile = &symtypes.files[Path::new("test.symtypes")];
+    assert!(Arc::ptr_eq(
+        &symfile.records["t#bar_t"],
+        &tokens(&symtypes, "t#foo_t")
+    ));
+    assert!(Arc::ptr_eq(&symtypes.exports["baz"], symfile));
+    assert_eq!(symtypes, symtypes2);
 }
 
 #[test]
-fn write_split_basic() {
-    // Check basic writing of split files.
+fn read_write_basic() {
+    // Check reading of a single file and writing the consolidated output.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "consolidated.symtypes",
+        "test.symtypes",
         bytes!(
-            "/* test.symtypes */\n",
             "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n",
-            "\n",
-            "/* test2.symtypes */\n",
-            "s#foo struct foo { long a ; }\n",
-            "baz int baz ( s#foo )\n", //
+            "bar int bar ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut out = DirectoryWriter::new_buffer("split");
-    let result = symtypes.write_split_buffer(&mut out, &mut JobControl::new_simple(1));
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
     assert_ok!(result);
-    let files = out.into_inner_map();
-    assert_eq!(files.len(), 2);
     assert_eq!(
-        str::from_utf8(&files[Path::new("split/test.symtypes")]).unwrap(),
+        str::from_utf8(&out).unwrap(),
         concat!(
+            "/* test.symtypes */\n",
             "s#foo struct foo { int a ; }\n",
             "bar int bar ( s#foo )\n", //
         )
     );
-    assert_eq!(
-        str::from_utf8(&files[Path::new("split/test2.symtypes")]).unwrap(),
-        concat!(
-            "s#foo struct foo { long a ; }\n",
-            "baz int baz ( s#foo )\n", //
-        )
-    );
 }
 
 #[test]
-fn compare_identical() {
-    // Check that the comparison of two identical corpuses shows no differences.
+fn read_write_shared_struct() {
+    // Check that a structure declaration shared by two files appears only once in the consolidated
+    // output.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "test.symtypes",
         bytes!(
-            "bar int bar ( )\n", //
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
+    let result = symtypes.load_buffer(
+        "test2.symtypes",
         bytes!(
-            "bar int bar ( )\n", //
+            "s#foo struct foo { int a ; }\n",
+            "baz int baz ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
-    );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, true);
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
+    assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "", //
+            "/* test.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "baz int baz ( s#foo )\n", //
         )
     );
 }
 
 #[test]
-fn compare_added_export() {
-    // Check that the comparison of two corpuses reports any newly added export.
+fn read_write_differing_struct() {
+    // Check that a structure declaration different in two files appears in all variants in the
+    // consolidated output and they are correctly referenced by the file entries.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "test.symtypes",
         bytes!(
-            "foo int foo ( )\n", //
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
+    let result = symtypes.load_buffer(
+        "test2.symtypes",
         bytes!(
-            "foo int foo ( )\n",
-            "bar int bar ( )\n",
-            "baz int baz ( )\n", //
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
-    );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
+    assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'bar' has been added\n",
-            "Export 'baz' has been added\n", //
+            "/* test.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
         )
     );
 }
 
 #[test]
-fn compare_removed_export() {
-    // Check that the comparison of two corpuses reports any removed export.
+fn read_write_differing_struct_unknown() {
+    // Check that a structure declaration with UNKNOWN content appears in the consolidated output as
+    // an ## override.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "test.symtypes",
         bytes!(
-            "foo int foo ( )\n",
-            "bar int bar ( )\n",
-            "baz int baz ( )\n", //
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
+    let result = symtypes.load_buffer(
+        "test2.symtypes",
         bytes!(
-            "baz int baz ( )\n", //
+            "s#foo struct foo { UNKNOWN }\n",
+            "baz int baz ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
+    let result = symtypes.load_buffer(
+        "test3.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "qux int qux ( s#foo )\n", //
+        ),
+        &mut warnings,
     );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
+    assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'bar' has been removed\n",
-            "Export 'foo' has been removed\n", //
+            "/* test.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "s##foo\n",
+            "baz int baz ( s#foo )\n",
+            "\n",
+            "/* test3.symtypes */\n",
+            "qux int qux ( s#foo )\n", //
         )
     );
 }
 
 #[test]
-fn compare_changed_type() {
-    // Check that the comparison of two corpuses reports changed types and affected exports.
+fn write_consolidated_order() {
+    // Check that the consolidated output can be ordered by path, by symbol, or as the old
+    // modversions script did.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
+    let result = symtypes.load_buffer("a-b.symtypes", bytes!("baz int baz ( )\n"), &mut warnings);
+    assert_ok!(result);
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "a.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
+            "t#bool typedef _Bool bool\n",
+            "s#foo struct foo { t#bool a ; s#bar * b ; }\n",
+            "s#bar struct bar { UNKNOWN }\n",
+            "qux int qux ( s#foo )\n",
+            "aaa t#bool aaa ( )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
-        bytes!(
-            "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo )\n", //
-        ),
-        &mut warnings,
+
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Path);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* a-b.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* a.symtypes */\n",
+            "s##bar\n",
+            "s#foo struct foo { t#bool a ; s#bar * b ; }\n",
+            "t#bool typedef _Bool bool\n",
+            "aaa t#bool aaa ( )\n",
+            "qux int qux ( s#foo )\n", //
+        )
     );
+
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::Symbol);
     assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* a-b.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* a.symtypes */\n",
+            "aaa t#bool aaa ( )\n",
+            "qux int qux ( s#foo )\n",
+            "s##bar\n",
+            "s#foo struct foo { t#bool a ; s#bar * b ; }\n",
+            "t#bool typedef _Bool bool\n", //
+        )
     );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_buffer(&mut out, ConsolidateOrder::OldScript);
+    assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "The following '1' exports are different:\n",
-            " bar\n",
+            "/* a.symtypes */\n",
+            "t#bool typedef _Bool bool \n",
+            "aaa t#bool aaa ( ) \n",
+            "s##bar\n",
+            "s#foo struct foo { t#bool a ; s#bar * b ; } \n",
+            "qux int qux ( s#foo ) \n",
             "\n",
-            "because of a changed 's#foo':\n",
-            "@@ -1,3 +1,4 @@\n",
-            " struct foo {\n",
-            " \tint a;\n",
-            "+\tint b;\n",
-            " }\n", //
+            "/* a-b.symtypes */\n",
+            "baz int baz ( ) \n",
+            "\n", //
         )
     );
 }
 
 #[test]
-fn compare_changed_nested_type() {
-    // Check that the comparison of two corpuses reports also changes in subtypes even if the parent
-    // type itself is modified, as long as each subtype is referenced by the parent type in both
-    // inputs.
+fn read_write_metadata() {
+    // Check that file metadata in consolidated data is read and written back, extended with the
+    // derived object path and the common metadata.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "test_consolidated.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( int a , s#foo )\n", //
+            "/*! consolidated 2 */\n",
+            "/* a.symtypes */\n",
+            "/*@ arch aarch64 */\n",
+            "/*@ object a.o */\n",
+            "bar int bar ( )\n",
+            "\n",
+            "/* drivers/b.symtypes */\n",
+            "baz int baz ( )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
-        bytes!(
-            "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo , int a )\n", //
-        ),
-        &mut warnings,
+    assert_eq!(
+        symtypes.files[Path::new("a.symtypes")].metadata,
+        FileMetadata::from([
+            ("arch".to_string(), "aarch64".to_string()),
+            ("object".to_string(), "a.o".to_string()),
+        ])
     );
-    assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
+    assert!(
+        symtypes.files[Path::new("drivers/b.symtypes")]
+            .metadata
+            .is_empty()
     );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    let mut out = Vec::new();
+    let result = symtypes.write_consolidated_with_metadata_buffer(
+        &mut out,
+        ConsolidateOrder::Path,
+        &FileMetadata::from([("flavor".to_string(), "default".to_string())]),
+    );
+    assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "The following '1' exports are different:\n",
-            " bar\n",
-            "\n",
-            "because of a changed 'bar':\n",
-            "@@ -1,1 +1,1 @@\n",
-            "-int bar ( int a, s#foo )\n",
-            "+int bar ( s#foo, int a )\n",
-            "\n",
-            "The following '1' exports are different:\n",
-            " bar\n",
+            "/*! consolidated 2 */\n",
+            "/* a.symtypes */\n",
+            "/*@ arch aarch64 */\n",
+            "/*@ flavor default */\n",
+            "/*@ object a.o */\n",
+            "bar int bar ( )\n",
             "\n",
-            "because of a changed 's#foo':\n",
-            "@@ -1,3 +1,4 @@\n",
-            " struct foo {\n",
-            " \tint a;\n",
-            "+\tint b;\n",
-            " }\n", //
+            "/* drivers/b.symtypes */\n",
+            "/*@ flavor default */\n",
+            "/*@ object drivers/b.o */\n",
+            "baz int baz ( )\n", //
         )
     );
 }
 
 #[test]
-fn compare_changed_unknown_type() {
-    // Check that the comparison of two corpuses reports changes from a forward declaration to
-    // a definition, without outputting the complete new definition.
+fn read_metadata_invalid() {
+    // Check that an unsupported format version and misplaced or duplicate file metadata are
+    // rejected.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "test_consolidated.symtypes",
         bytes!(
-            "s#foo struct foo { UNKNOWN }\n",
-            "s#bar struct bar { long a }\n",
-            "baz int baz ( s#foo , s#bar )\n", //
+            "/*! consolidated 3 */\n",
+            "/* a.symtypes */\n", //
         ),
         &mut warnings,
     );
-    assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
+    assert_parse_err!(
+        result,
+        concat!(
+            "Unsupported consolidated format version '3'\n",
+            " test_consolidated.symtypes:1\n",
+            " | /*! consolidated 3 */", //
+        )
+    );
+    let mut symtypes = SymtypesCorpus::new();
+    let result = symtypes.load_buffer(
+        "test_consolidated.symtypes",
         bytes!(
-            "s#foo struct foo { int a }\n",
-            "s#bar struct bar { UNKNOWN }\n",
-            "baz int baz ( s#foo , s#bar )\n", //
+            "/* a.symtypes */\n",
+            "/*@ arch x86_64 */\n", //
         ),
         &mut warnings,
     );
-    assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
+    assert_parse_err!(
+        result,
+        concat!(
+            "Unexpected file metadata without a format header\n",
+            " test_consolidated.symtypes:2\n",
+            " | /*@ arch x86_64 */", //
+        )
     );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
-    assert_eq!(
-        str::from_utf8(&out).unwrap(),
+    let mut symtypes = SymtypesCorpus::new();
+    let result = symtypes.load_buffer(
+        "test_consolidated.symtypes",
+        bytes!(
+            "/*! consolidated 2 */\n",
+            "/* a.symtypes */\n",
+            "/*@ arch x86_64 */\n",
+            "/*@ arch s390x */\n", //
+        ),
+        &mut warnings,
+    );
+    assert_parse_err!(
+        result,
         concat!(
-            "The following '1' exports are different:\n",
-            " baz\n",
-            "\n",
-            "because 's#bar' changed from a definition to a forward declaration\n",
-            "\n",
-            "The following '1' exports are different:\n",
-            " baz\n",
-            "\n",
-            "because 's#foo' changed from a forward declaration to a definition\n", //
+            "Duplicate file metadata 'arch'\n",
+            " test_consolidated.symtypes:4\n",
+            " | /*@ arch s390x */", //
         )
     );
+    assert!(warnings.is_empty());
 }
 
 #[test]
-fn compare_filter() {
-    // Check that the comparison of two corpuses can be restricted to specific exports.
+fn variant_spill() {
+    // Check that the on-disk store of type variants detects changes of the active variant.
+    let mut spill = VariantSpill::new().expect("Unable to create the variant store");
+    let foo_tokens = vec![
+        Token::new_atom("struct"),
+        Token::new_atom("foo"),
+        Token::new_atom("{"),
+        Token::new_atom("}"),
+    ];
+    let foo2_tokens = vec![
+        Token::new_atom("struct"),
+        Token::new_atom("foo"),
+        Token::new_atom("{"),
+        Token::new_atom("int"),
+        Token::new_atom("a"),
+        Token::new_atom(";"),
+        Token::new_atom("}"),
+    ];
+    assert_ok_eq!(spill.update("s#foo", &foo_tokens), true);
+    assert_ok_eq!(spill.update("s#foo", &foo_tokens), false);
+    assert_ok_eq!(spill.update("s#bar", &foo_tokens), true);
+    assert_ok_eq!(spill.update("s#foo", &foo2_tokens), true);
+    assert_ok_eq!(spill.update("s#foo", &foo2_tokens), false);
+    assert_ok_eq!(spill.update("s#foo", &foo_tokens), true);
+    assert_ok_eq!(spill.update("s#bar", &foo_tokens), false);
+}
+
+#[test]
+fn write_split_basic() {
+    // Check basic writing of split files.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "consolidated.symtypes",
         bytes!(
+            "/* test.symtypes */\n",
             "s#foo struct foo { int a ; }\n",
             "bar int bar ( s#foo )\n",
-            "baz int baz ( s#foo )\n",
-            "qux int qux ( s#foo )\n", //
+            "\n",
+            "/* test2.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
-        bytes!(
-            "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo )\n",
-            "baz int baz ( s#foo )\n",
-            "qux int qux ( s#foo )\n", //
-        ),
-        &mut warnings,
-    );
+    let mut out = DirectoryWriter::new_buffer("split");
+    let result = symtypes.write_split_buffer(&mut out, &mut JobControl::new_simple(1));
     assert_ok!(result);
-    assert!(warnings.is_empty());
-
-    // Check that the result is sensible without a filter.
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
+    let files = out.into_inner_map();
+    assert_eq!(files.len(), 2);
+    assert_eq!(
+        str::from_utf8(&files[Path::new("split/test.symtypes")]).unwrap(),
+        concat!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        )
     );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
     assert_eq!(
-        str::from_utf8(&out).unwrap(),
+        str::from_utf8(&files[Path::new("split/test2.symtypes")]).unwrap(),
         concat!(
-            "The following '3' exports are different:\n",
-            " bar\n",
-            " baz\n",
-            " qux\n",
-            "\n",
-            "because of a changed 's#foo':\n",
-            "@@ -1,3 +1,4 @@",
-            "\n",
-            " struct foo {\n",
-            " \tint a;\n",
-            "+\tint b;\n",
-            " }\n", //
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
         )
     );
+}
 
-    // Check the result when using a filter.
-    let mut symbol_filter = Filter::new();
-    let result = symbol_filter.load_buffer(
-        "filter-symbol-list.txt",
+#[test]
+fn split_selected() {
+    // Check that only files matching the patterns are extracted from consolidated data, with
+    // omitted types taken from their last definition and UNKNOWN overrides expanded.
+    let mut out = DirectoryWriter::new_buffer("split");
+    let result = SymtypesCorpus::split_selected_buffer(
+        "consolidated.symtypes",
         bytes!(
-            "bar\n", "baz\n", //
+            "/* net/a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#bar struct bar { int b ; }\n",
+            "net_a int net_a ( s#foo , s#bar )\n",
+            "\n",
+            "/* scsi/b.symtypes */\n",
+            "s##bar\n",
+            "scsi_b int scsi_b ( s#foo , s#bar )\n",
+            "\n",
+            "/* fs/c.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "fs_c int fs_c ( s#foo )\n",
+            "\n",
+            "/* net/d.symtypes */\n",
+            "net_d int net_d ( s#bar )\n", //
         ),
+        &["scsi/*".to_string(), "net/d.*".to_string()],
+        &mut out,
     );
     assert_ok!(result);
-
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
-        &symtypes2,
-        Some(&symbol_filter),
-        &mut [(CompareFormat::Pretty, &mut writer)],
-        &mut JobControl::new_simple(1),
+    let files = out.into_inner_map();
+    assert_eq!(files.len(), 2);
+    assert_eq!(
+        str::from_utf8(&files[Path::new("split/scsi/b.symtypes")]).unwrap(),
+        concat!(
+            "s#bar struct bar { UNKNOWN }\n",
+            "s#foo struct foo { int a ; }\n",
+            "scsi_b int scsi_b ( s#foo , s#bar )\n", //
+        )
     );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
     assert_eq!(
-        str::from_utf8(&out).unwrap(),
+        str::from_utf8(&files[Path::new("split/net/d.symtypes")]).unwrap(),
         concat!(
-            "The following '2' exports are different:\n",
-            " bar\n",
-            " baz\n",
-            "\n",
-            "because of a changed 's#foo':\n",
-            "@@ -1,3 +1,4 @@",
-            "\n",
-            " struct foo {\n",
-            " \tint a;\n",
-            "+\tint b;\n",
-            " }\n", //
+            "s#bar struct bar { int b ; }\n",
+            "net_d int net_d ( s#bar )\n", //
         )
     );
 }
 
 #[test]
-fn compare_format_null() {
-    // Check that when using the null format, the comparison output is empty and only the return
-    // code indicates any changes.
+fn split_selected_no_match() {
+    // Check that extracting files fails if no file matches the patterns.
+    let mut out = DirectoryWriter::new_buffer("split");
+    let result = SymtypesCorpus::split_selected_buffer(
+        "consolidated.symtypes",
+        bytes!(
+            "/* test.symtypes */\n",
+            "bar int bar ( )\n", //
+        ),
+        &["foo/*".to_string(), "baz/*".to_string()],
+        &mut out,
+    );
+    assert_parse_err!(result, "No file matching 'foo/*', 'baz/*' found");
+}
+
+#[test]
+fn cache_roundtrip() {
+    // Check that a corpus saved in the binary cache form can be loaded back.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
-        "a/test.symtypes",
+        "test_consolidated.symtypes",
         bytes!(
+            "/*! consolidated 2 */\n",
+            "/* test.symtypes */\n",
+            "/*@ arch x86_64 */\n",
             "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
-    let mut symtypes2 = SymtypesCorpus::new();
-    let result = symtypes2.load_buffer(
-        "b/test.symtypes",
-        bytes!(
-            "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo )\n", //
-        ),
-        &mut warnings,
-    );
+    let mut out = Vec::new();
+    let result = symtypes.save_cache_buffer(0x12345678, &mut out);
     assert_ok!(result);
-    assert!(warnings.is_empty());
-    let mut writer = Writer::new_buffer();
-    let result = symtypes.compare_with_buffer(
+    let result = SymtypesCorpus::load_cache_buffer(0x12345678, out.as_slice());
+    let cached_symtypes = result.unwrap().unwrap();
+    assert_eq!(cached_symtypes, symtypes);
+    let bar_tokens = &cached_symtypes.files[Path::new("test.symtypes")].records["bar"];
+    assert!(Arc::ptr_eq(
+        bar_tokens,
+        &cached_symtypes.types[type_bucket_idx("bar")]["bar"][0]
+    ));
+}
+
+#[test]
+fn cache_source_mismatch() {
+    // Check that a cache is ignored if it doesn't match the source data or the format version.
+    let symtypes = SymtypesCorpus::new();
+    let mut out = Vec::new();
+    let result = symtypes.save_cache_buffer(0x12345678, &mut out);
+    assert_ok!(result);
+    let result = SymtypesCorpus::load_cache_buffer(0x9abcdef0, out.as_slice());
+    assert_eq!(result.unwrap(), None);
+    out[CACHE_MAGIC.len()] ^= 0xff;
+    let result = SymtypesCorpus::load_cache_buffer(0x12345678, out.as_slice());
+    assert_eq!(result.unwrap(), None);
+}
+
+#[test]
+fn cache_invalid() {
+    // Check that corrupted cache data is rejected.
+    let result = SymtypesCorpus::load_cache_buffer(0x12345678, bytes!("KSYMTYPS"));
+    assert_parse_err!(result, "Invalid symtypes cache header");
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = symtypes.save_cache_buffer(0x12345678, &mut out);
+    assert_ok!(result);
+    let len = out.len();
+    out[len - 4..].copy_from_slice(&100u32.to_le_bytes());
+    let result = SymtypesCorpus::load_cache_buffer(0x12345678, out.as_slice());
+    assert_parse_err!(result, "Invalid index '100' in the symtypes cache");
+}
+
+#[test]
+fn compare_identical() {
+    // Check that the comparison of two identical corpuses shows no differences.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
         &symtypes2,
         None,
-        &mut [(CompareFormat::Null, &mut writer)],
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
         &mut JobControl::new_simple(1),
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Same);
+    assert_eq!(str::from_utf8(&out).unwrap(), "");
+}
+
+#[test]
+fn compare_shared_concurrently() {
+    // Check that a shared corpus can be compared from multiple threads at the same time.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "bar int bar ( )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let shared = SharedSymtypesCorpus::new(symtypes);
+    let shared2 = SharedSymtypesCorpus::from(symtypes2);
+    std::thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                let shared2 = shared2.clone();
+                scope.spawn(move || {
+                    shared.compare(
+                        &shared2,
+                        None,
+                        None,
+                        None,
+                        None,
+                        &mut JobControl::new_simple(1),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let result = handle.join().unwrap();
+            assert_ok_eq!(
+                result,
+                ComparisonResult {
+                    added_exports: string_vec!["baz"],
+                    removed_exports: vec![],
+                    changed_types: vec![],
+                }
+            );
+        }
+    });
+}
+
+#[test]
+fn compare_added_export() {
+    // Check that the comparison of two corpuses reports any newly added export.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "foo int foo ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "foo int foo ( )\n",
+            "bar int bar ( )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "", //
+            "Export 'bar' has been added\n",
+            "Export 'baz' has been added\n", //
         )
     );
 }
 
 #[test]
-fn compare_format_symbols() {
-    // Check that when using the symbols format, the comparison output is in alphabetical order,
-    // and lists each symbol only once, even if it has multiple changes.
+fn compare_removed_export() {
+    // Check that the comparison of two corpuses reports any removed export.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
         "a/test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo , int )\n",
+            "foo int foo ( )\n",
+            "bar int bar ( )\n",
             "baz int baz ( )\n", //
         ),
         &mut warnings,
@@ -1327,9 +1954,7 @@ fn compare_format_symbols() {
     let result = symtypes2.load_buffer(
         "b/test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo , long )\n",
-            "qux int qux ( )\n", //
+            "baz int baz ( )\n", //
         ),
         &mut warnings,
     );
@@ -1339,31 +1964,36 @@ fn compare_format_symbols() {
     let result = symtypes.compare_with_buffer(
         &symtypes2,
         None,
-        &mut [(CompareFormat::Symbols, &mut writer)],
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
         &mut JobControl::new_simple(1),
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Breaking);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "bar\n", "baz\n", "qux\n", //
+            "Export 'bar' has been removed\n",
+            "Export 'foo' has been removed\n", //
         )
     );
 }
 
 #[test]
-fn compare_format_mod_symbols() {
-    // Check that when using the mod-symbols format, the comparison output lists only modified
-    // symbols and exludes any additions and removals.
+fn compare_retained_exports() {
+    // Check that exports removed by retain_exports() are no longer considered by the comparison.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
         "a/test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo , int )\n",
-            "baz int baz ( )\n", //
+            "foo int foo ( )\n",
+            "bar int bar ( )\n", //
         ),
         &mut warnings,
     );
@@ -1373,55 +2003,46 @@ fn compare_format_mod_symbols() {
     let result = symtypes2.load_buffer(
         "b/test.symtypes",
         bytes!(
-            "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo , long )\n",
-            "qux int qux ( )\n", //
+            "foo int foo ( )\n",
+            "bar int bar ( )\n",
+            "baz int baz ( )\n", //
         ),
         &mut warnings,
     );
     assert_ok!(result);
     assert!(warnings.is_empty());
+    symtypes2.retain_exports(|name| name != "bar" && name != "baz");
     let mut writer = Writer::new_buffer();
     let result = symtypes.compare_with_buffer(
         &symtypes2,
         None,
-        &mut [(CompareFormat::ModSymbols, &mut writer)],
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
         &mut JobControl::new_simple(1),
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Breaking);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
-        concat!(
-            "bar\n" //
-        )
+        "Export 'bar' has been removed\n"
     );
 }
 
 #[test]
-fn compare_format_short() {
-    // Check that when using the short format, the comparison output limits the list of different
-    // exports to 10.
-    //
-    // NOTE: Keep this test synchronized with the ksymtypes_compare_format integration test.
+fn compare_changed_type() {
+    // Check that the comparison of two corpuses reports changed types and affected exports.
     let mut symtypes = SymtypesCorpus::new();
     let mut warnings = Vec::new();
     let result = symtypes.load_buffer(
         "a/test.symtypes",
         bytes!(
             "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo , int )\n",
-            "bar2 int bar2 ( s#foo , int )\n",
-            "bar3 int bar3 ( s#foo , int )\n",
-            "bar4 int bar4 ( s#foo , int )\n",
-            "bar5 int bar5 ( s#foo , int )\n",
-            "bar6 int bar6 ( s#foo , int )\n",
-            "bar7 int bar7 ( s#foo , int )\n",
-            "bar8 int bar8 ( s#foo , int )\n",
-            "bar9 int bar9 ( s#foo , int )\n",
-            "bar10 int bar10 ( s#foo , int )\n",
-            "bar11 int bar11 ( s#foo , int )\n",
-            "baz int baz ( )\n", //
+            "bar int bar ( s#foo )\n", //
         ),
         &mut warnings,
     );
@@ -1432,18 +2053,7 @@ fn compare_format_short() {
         "b/test.symtypes",
         bytes!(
             "s#foo struct foo { int a ; int b ; }\n",
-            "bar int bar ( s#foo , long )\n",
-            "bar2 int bar2 ( s#foo , int )\n",
-            "bar3 int bar3 ( s#foo , int )\n",
-            "bar4 int bar4 ( s#foo , int )\n",
-            "bar5 int bar5 ( s#foo , int )\n",
-            "bar6 int bar6 ( s#foo , int )\n",
-            "bar7 int bar7 ( s#foo , int )\n",
-            "bar8 int bar8 ( s#foo , int )\n",
-            "bar9 int bar9 ( s#foo , int )\n",
-            "bar10 int bar10 ( s#foo , int )\n",
-            "bar11 int bar11 ( s#foo , int )\n",
-            "qux int qux ( )\n", //
+            "bar int bar ( s#foo )\n", //
         ),
         &mut warnings,
     );
@@ -1453,38 +2063,24 @@ fn compare_format_short() {
     let result = symtypes.compare_with_buffer(
         &symtypes2,
         None,
-        &mut [(CompareFormat::Short, &mut writer)],
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
         &mut JobControl::new_simple(1),
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Breaking);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'qux' has been added\n",
-            "Export 'baz' has been removed\n",
             "The following '1' exports are different:\n",
             " bar\n",
             "\n",
-            "because of a changed 'bar':\n",
-            "@@ -1,1 +1,1 @@\n",
-            "-int bar ( s#foo, int )\n",
-            "+int bar ( s#foo, long )\n",
-            "\n",
-            "The following '11' exports are different:\n",
-            " bar\n",
-            " bar10\n",
-            " bar11\n",
-            " bar2\n",
-            " bar3\n",
-            " bar4\n",
-            " bar5\n",
-            " bar6\n",
-            " bar7\n",
-            " bar8\n",
-            " <...>\n",
-            "\n",
-            "because of a changed 's#foo':\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
             "@@ -1,3 +1,4 @@\n",
             " struct foo {\n",
             " \tint a;\n",
@@ -1493,3 +2089,2857 @@ fn compare_format_short() {
         )
     );
 }
+
+#[test]
+fn compare_changed_nested_type() {
+    // Check that the comparison of two corpuses reports also changes in subtypes even if the parent
+    // type itself is modified, as long as each subtype is referenced by the parent type in both
+    // inputs.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( int a , s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo , int a )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 'bar':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-int bar ( int a, s#foo )\n",
+            "+int bar ( s#foo, int a )\n",
+            "\n",
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn closure_hashes() {
+    // Check that the closure hash of an export changes with any type reachable from it, including
+    // through recursive types, and only with those.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#list struct list { s#list * next ; s#foo * data ; }\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#unrelated struct unrelated { int a ; }\n",
+            "bar int bar ( s#list * )\n",
+            "baz int baz ( s#unrelated * )\n",
+            "/* b.symtypes */\n",
+            "s#list struct list { s#list * next ; s#foo * data ; }\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#unrelated struct unrelated { long a ; }\n",
+            "bar2 int bar ( s#list * )\n",
+            "baz2 int baz ( s#unrelated * )\n",
+            "/* c.symtypes */\n",
+            "s#list struct list { s#list * next ; s#foo * data ; }\n",
+            "s#foo struct foo { long a ; }\n",
+            "bar3 int bar ( s#list * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let export_hash = |path: &str, name: &str| symtypes.files[Path::new(path)].export_hashes[name];
+    assert_eq!(
+        export_hash("a.symtypes", "bar"),
+        export_hash("b.symtypes", "bar2")
+    );
+    assert_ne!(
+        export_hash("a.symtypes", "bar"),
+        export_hash("c.symtypes", "bar3")
+    );
+    assert_ne!(
+        export_hash("a.symtypes", "baz"),
+        export_hash("b.symtypes", "baz2")
+    );
+}
+
+#[test]
+fn compare_changed_unknown_type() {
+    // Check that the comparison of two corpuses reports changes from a forward declaration to
+    // a definition, without outputting the complete new definition.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { UNKNOWN }\n",
+            "s#bar struct bar { long a }\n",
+            "baz int baz ( s#foo , s#bar )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a }\n",
+            "s#bar struct bar { UNKNOWN }\n",
+            "baz int baz ( s#foo , s#bar )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because 's#bar' changed from a definition to a forward declaration\n",
+            "\n",
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because 's#foo' changed from a forward declaration to a definition\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_filter() {
+    // Check that the comparison of two corpuses can be restricted to specific exports.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#foo )\n",
+            "qux int qux ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#foo )\n",
+            "qux int qux ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    // Check that the result is sensible without a filter.
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '3' exports are different:\n",
+            " bar\n",
+            " baz\n",
+            " qux\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@",
+            "\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+
+    // Check the result when using a filter.
+    let mut symbol_filter = Filter::new();
+    let result = symbol_filter.load_buffer(
+        "filter-symbol-list.txt",
+        bytes!(
+            "bar\n", "baz\n", //
+        ),
+    );
+    assert_ok!(result);
+
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        Some(&symbol_filter),
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '2' exports are different:\n",
+            " bar\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@",
+            "\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_type_filter() {
+    // Check that the comparison reports only changed types matching the type filter.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "s#bar struct bar { int a ; }\n",
+            "baz int baz ( s#foo , s#bar )\n",
+            "qux int qux ( s#bar )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "s#bar struct bar { long a ; }\n",
+            "baz int baz ( s#foo , s#bar )\n",
+            "qux int qux ( s#bar )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut type_filter = Filter::new();
+    let result = type_filter.load_buffer(
+        "filter-type-list.txt",
+        bytes!(
+            "s#f*\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        Some(&type_filter),
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_ignore_types() {
+    // Check that types matching the ignore list are treated as equal, which also suppresses
+    // reporting of changes in their subtypes.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#lock_inner struct lock_inner { int a ; }\n",
+            "s#lock struct lock { s#lock_inner inner ; }\n",
+            "s#foo struct foo { int a ; s#lock lock ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#lock )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#lock_inner struct lock_inner { long a ; }\n",
+            "s#lock struct lock { s#lock_inner inner ; int debug ; }\n",
+            "s#foo struct foo { long a ; s#lock lock ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#lock )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut ignore_types = Filter::new();
+    let result = ignore_types.load_buffer(
+        "ignore-types.txt",
+        bytes!(
+            "s#lock*\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        Some(&ignore_types),
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,4 +1,4 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " \ts#lock lock;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_rules() {
+    // Check that changes in types matched by tolerating type rules don't break the affected
+    // exports, while other changes still do.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#kvm_vcpu struct kvm_vcpu { int a ; }\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#kvm_vcpu )\n",
+            "baz int baz ( s#kvm_vcpu , s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#kvm_vcpu struct kvm_vcpu { long a ; }\n",
+            "s#foo struct foo { long a ; }\n",
+            "bar int bar ( s#kvm_vcpu )\n",
+            "baz int baz ( s#kvm_vcpu , s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "s#kvm_* PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let mut short_writer = Writer::new_buffer();
+    let mut symbols_writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        Some(&rules),
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [
+            (CompareFormat::Pretty, &mut writer),
+            (CompareFormat::Short, &mut short_writer),
+            (CompareFormat::Symbols, &mut symbols_writer),
+        ],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    let short_out = short_writer.into_inner_vec();
+    let symbols_out = symbols_writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n",
+            "\n",
+            "The following '2' exports are different:\n",
+            " bar\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#kvm_vcpu' (member type changed, tolerated by rule test.severities:1 's#kvm_* PASS'):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct kvm_vcpu {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(
+        str::from_utf8(&short_out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(str::from_utf8(&symbols_out).unwrap(), "baz\n");
+
+    // Check that the comparison is reported as tolerated if all changes are tolerated.
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "s#kvm_* PASS\n",
+            "TYPE s#foo WARN\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        Some(&rules),
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Summary, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Tolerated);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        "kABI: 0 breaking, 2 tolerated, 0 unchanged\n"
+    );
+    let result = symtypes.compare_status(
+        &symtypes2,
+        None,
+        None,
+        None,
+        Some(&rules),
+        None,
+        &mut JobControl::new_simple(1),
+    );
+    assert_ok_eq!(result, CompareStatus::Tolerated);
+}
+
+#[test]
+fn compare_group_by_module() {
+    // Check that the exports affected by a changed type can be grouped by the directory of their
+    // symtypes file.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut symtypes2 = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    for (path, export) in [
+        ("drivers/a.symtypes", "bar"),
+        ("drivers/b.symtypes", "baz"),
+        ("fs/c.symtypes", "qux"),
+    ] {
+        let result = symtypes.load_buffer(
+            path,
+            format!(
+                "s#foo struct foo {{ int a ; }}\n{} int {} ( s#foo )\n",
+                export, export
+            )
+            .as_bytes(),
+            &mut warnings,
+        );
+        assert_ok!(result);
+        let result = symtypes2.load_buffer(
+            path,
+            format!(
+                "s#foo struct foo {{ long a ; }}\n{} int {} ( s#foo )\n",
+                export, export
+            )
+            .as_bytes(),
+            &mut warnings,
+        );
+        assert_ok!(result);
+    }
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::Module,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '3' exports are different:\n",
+            " drivers (2):\n",
+            "  bar\n",
+            "  baz\n",
+            " fs (1):\n",
+            "  qux\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_group_by_file_metadata() {
+    // Check that the exports grouped by their symtypes file are shown with the file metadata.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut symtypes2 = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    for (symtypes, type_name) in [(&mut symtypes, "int"), (&mut symtypes2, "long")] {
+        let result = symtypes.load_buffer(
+            "test_consolidated.symtypes",
+            format!(
+                concat!(
+                    "/*! consolidated 2 */\n",
+                    "/* a.symtypes */\n",
+                    "/*@ arch x86_64 */\n",
+                    "/*@ object a.o */\n",
+                    "s#foo struct foo {{ {} a ; }}\n",
+                    "bar int bar ( s#foo )\n", //
+                ),
+                type_name
+            )
+            .as_bytes(),
+            &mut warnings,
+        );
+        assert_ok!(result);
+    }
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::File,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " a.symtypes [arch=x86_64, object=a.o] (1):\n",
+            "  bar\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_suppressions() {
+    // Check that acknowledged differences are not reported.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut suppressions = Suppressions::new();
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "s#foo definition\n",
+            "baz removed\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        Some(&suppressions),
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        "Export 'qux' has been added\n"
+    );
+}
+
+#[test]
+fn compare_format_null() {
+    // Check that when using the null format, the comparison output is empty and only the return
+    // code indicates any changes.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Null, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(str::from_utf8(&out).unwrap(), "");
+}
+
+#[test]
+fn compare_format_symbols() {
+    // Check that when using the symbols format, the comparison output is in alphabetical order,
+    // and lists each symbol only once, even if it has multiple changes.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo , int )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo , long )\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Symbols, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "bar\n", "baz\n", "qux\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_mod_symbols() {
+    // Check that when using the mod-symbols format, the comparison output lists only modified
+    // symbols and exludes any additions and removals.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo , int )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo , long )\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::ModSymbols, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(str::from_utf8(&out).unwrap(), "bar\n");
+}
+
+#[test]
+fn compare_format_short() {
+    // Check that when using the short format, the comparison output limits the list of different
+    // exports to 10.
+    //
+    // NOTE: Keep this test synchronized with the ksymtypes_compare_format integration test.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo , int )\n",
+            "bar2 int bar2 ( s#foo , int )\n",
+            "bar3 int bar3 ( s#foo , int )\n",
+            "bar4 int bar4 ( s#foo , int )\n",
+            "bar5 int bar5 ( s#foo , int )\n",
+            "bar6 int bar6 ( s#foo , int )\n",
+            "bar7 int bar7 ( s#foo , int )\n",
+            "bar8 int bar8 ( s#foo , int )\n",
+            "bar9 int bar9 ( s#foo , int )\n",
+            "bar10 int bar10 ( s#foo , int )\n",
+            "bar11 int bar11 ( s#foo , int )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo , long )\n",
+            "bar2 int bar2 ( s#foo , int )\n",
+            "bar3 int bar3 ( s#foo , int )\n",
+            "bar4 int bar4 ( s#foo , int )\n",
+            "bar5 int bar5 ( s#foo , int )\n",
+            "bar6 int bar6 ( s#foo , int )\n",
+            "bar7 int bar7 ( s#foo , int )\n",
+            "bar8 int bar8 ( s#foo , int )\n",
+            "bar9 int bar9 ( s#foo , int )\n",
+            "bar10 int bar10 ( s#foo , int )\n",
+            "bar11 int bar11 ( s#foo , int )\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Short, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'qux' has been added\n",
+            "Export 'baz' has been removed\n",
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 'bar':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-int bar ( s#foo, int )\n",
+            "+int bar ( s#foo, long )\n",
+            "\n",
+            "The following '11' exports are different:\n",
+            " bar\n",
+            " bar10\n",
+            " bar11\n",
+            " bar2\n",
+            " bar3\n",
+            " bar4\n",
+            " bar5\n",
+            " bar6\n",
+            " bar7\n",
+            " bar8\n",
+            " <...>\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_json() {
+    // Check that the JSON format describes added and removed exports and each changed type with
+    // its affected exports, layout classification and verdict.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#kvm_vcpu struct kvm_vcpu { int a ; }\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#qux struct qux { int a ; int b ; }\n",
+            "t#quux_t typedef int quux_t\n",
+            "bar int bar ( s#kvm_vcpu , s#foo )\n",
+            "baz int baz ( s#qux , t#quux_t )\n",
+            "old int old ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#kvm_vcpu struct kvm_vcpu { long a ; }\n",
+            "s#foo struct foo { int a ; int b ; }\n",
+            "s#qux struct qux { long a ; int c ; }\n",
+            "t#quux_t typedef long quux_t\n",
+            "bar int bar ( s#kvm_vcpu , s#foo )\n",
+            "baz int baz ( s#qux , t#quux_t )\n",
+            "new int new ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "s#kvm_* PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        Some(&rules),
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Json, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "{\"added\":[\"new\"],\"removed\":[\"old\"],\"changed_types\":[",
+            "{\"type\":\"s#foo\",\"exports\":[\"bar\"],\"class\":\"member appended\",",
+            "\"verdict\":\"probably safe\",\"rule\":null},",
+            "{\"type\":\"s#kvm_vcpu\",\"exports\":[\"bar\"],\"class\":\"member type changed\",",
+            "\"verdict\":\"tolerated\",\"rule\":\"test.severities:1 's#kvm_* PASS'\"},",
+            "{\"type\":\"s#qux\",\"exports\":[\"baz\"],\"class\":\"mixed changes\",",
+            "\"verdict\":\"breaking\",\"rule\":null},",
+            "{\"type\":\"t#quux_t\",\"exports\":[\"baz\"],\"class\":null,",
+            "\"verdict\":null,\"rule\":null}",
+            "]}\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_side_by_side() {
+    // Check that the side-by-side format shows the old and new type definitions in two columns.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "s#foo struct foo { long a ; int b ; int c ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::SideBySide, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo' (mixed changes, breaking):\n",
+            "struct foo {     struct foo {\n",
+            "        int a; |         long a;\n",
+            "        int b;           int b;\n",
+            "               >         int c;\n",
+            "}                }\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_html() {
+    // Check that the HTML format produces a report with the modified exports linked to the changed
+    // types that affect them, and vice versa.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        ExportGrouping::None,
+        &mut [(CompareFormat::Html, &mut writer)],
+        &mut JobControl::new_simple(1),
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    let out = str::from_utf8(&out).unwrap();
+    assert!(out.starts_with("<!DOCTYPE html>\n"));
+    assert!(out.ends_with(concat!(
+        "<h1>Comparison of symtypes corpuses</h1>\n",
+        "<h2>Added and removed exports</h2>\n",
+        "<p>Export <code>baz</code> has been removed</p>\n",
+        "<h2>Modified exports</h2>\n",
+        "<table>\n",
+        "<tr><th>Export</th><th>Changed types</th></tr>\n",
+        "<tr id=\"export-bar\"><td><code>bar</code></td><td><a href=\"#change-0\"><code>s#foo</code></a></td></tr>\n",
+        "</table>\n",
+        "<h2>Changed types</h2>\n",
+        "<details id=\"change-0\" open>\n",
+        "<summary>Changed type <code>s#foo</code> affecting '1' exports</summary>\n",
+        "<p>Affected exports: <a href=\"#export-bar\"><code>bar</code></a></p>\n",
+        "<p>because of a changed &#39;s#foo&#39; (member appended, probably safe):</p>\n",
+        "<pre class=\"diff\">\n",
+        "<span class=\"hunk\">@@ -1,3 +1,4 @@</span>\n",
+        " struct foo {\n",
+        " \tint a;\n",
+        "<span class=\"add\">+\tint b;</span>\n",
+        " }\n",
+        "</pre>\n",
+        "</details>\n",
+        "</body>\n",
+        "</html>\n", //
+    )));
+}
+
+#[test]
+fn compare_structured() {
+    // Check that the structured comparison of two corpuses returns added and removed exports, and
+    // changed types together with their affected exports.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#foo )\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; int b ; }\n",
+            "bar int bar ( s#foo )\n",
+            "baz int baz ( s#foo )\n",
+            "quux int quux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let result = symtypes.compare(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        &mut JobControl::new_simple(1),
+    );
+    let comparison = result.unwrap();
+    assert!(!comparison.is_empty());
+    assert_eq!(
+        comparison,
+        ComparisonResult {
+            added_exports: vec!["quux".to_string()],
+            removed_exports: vec!["qux".to_string()],
+            changed_types: vec![TypeChange {
+                name: "s#foo".to_string(),
+                old_tokens: vec![
+                    Token::new_atom("struct"),
+                    Token::new_atom("foo"),
+                    Token::new_atom("{"),
+                    Token::new_atom("int"),
+                    Token::new_atom("a"),
+                    Token::new_atom(";"),
+                    Token::new_atom("}"),
+                ],
+                new_tokens: vec![
+                    Token::new_atom("struct"),
+                    Token::new_atom("foo"),
+                    Token::new_atom("{"),
+                    Token::new_atom("int"),
+                    Token::new_atom("a"),
+                    Token::new_atom(";"),
+                    Token::new_atom("int"),
+                    Token::new_atom("b"),
+                    Token::new_atom(";"),
+                    Token::new_atom("}"),
+                ],
+                exports: vec!["bar".to_string(), "baz".to_string()],
+            }],
+        }
+    );
+    assert_eq!(comparison.modified_exports(), vec!["bar", "baz"]);
+}
+
+#[test]
+fn compare_structured_identical() {
+    // Check that the structured comparison of two identical corpuses returns an empty result.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let result = symtypes.compare(
+        &symtypes,
+        None,
+        None,
+        None,
+        None,
+        &mut JobControl::new_simple(1),
+    );
+    let comparison = result.unwrap();
+    assert!(comparison.is_empty());
+    assert_eq!(comparison, ComparisonResult::default());
+}
+
+#[test]
+fn compare_files() {
+    // Check that the per-file comparison pairs files by their path and reports all changed
+    // records, including types that are not reachable from any export.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#unused struct unused { int a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "/* b.symtypes */\n",
+            "baz int baz ( )\n",
+            "/* c.symtypes */\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#unused struct unused { long a ; }\n",
+            "s#new struct new { int a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "/* b.symtypes */\n",
+            "baz int baz ( )\n",
+            "/* d.symtypes */\n",
+            "quux int quux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut writer = Writer::new_buffer();
+    let mut writer2 = Writer::new_buffer();
+    let mut writer3 = Writer::new_buffer();
+    let result = symtypes.compare_files_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        &mut [
+            (CompareFormat::Pretty, &mut writer),
+            (CompareFormat::Short, &mut writer2),
+            (CompareFormat::Symbols, &mut writer3),
+        ],
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    let out = writer.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "File 'd.symtypes' has been added\n",
+            "File 'c.symtypes' has been removed\n",
+            "\n",
+            "/* a.symtypes */\n",
+            "Type 's#new' has been added\n",
+            "Type 's#unused' has been modified:\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct unused {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    let out2 = writer2.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out2).unwrap(),
+        concat!(
+            "File 'd.symtypes' has been added\n",
+            "File 'c.symtypes' has been removed\n",
+            "\n",
+            "/* a.symtypes */\n",
+            "Type 's#new' has been added\n",
+            "Type 's#unused' has been modified\n", //
+        )
+    );
+    let out3 = writer3.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out3).unwrap(),
+        concat!(
+            "a.symtypes\n",
+            "c.symtypes\n",
+            "d.symtypes\n", //
+        )
+    );
+
+    // Check that types matching the ignore list are considered equal.
+    let mut ignore_types = Filter::new();
+    let result = ignore_types.load_buffer(
+        "ignore-types.txt",
+        bytes!(
+            "s#unused\n",
+            "s#new\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_files_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        Some(&ignore_types),
+        &DiffOptions::default(),
+        &mut [(CompareFormat::ModSymbols, &mut writer)],
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert!(writer.into_inner_vec().is_empty());
+}
+
+#[test]
+fn compare_files_object_paths() {
+    // Check that the per-file comparison pairs a file named after its object with the same file
+    // named after its symtypes file.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* drivers/foo.o */\n",
+            "s#foo struct foo { int a ; }\n",
+            "foo int foo ( s#foo * )\n",
+            "/* drivers/bar.symtypes */\n",
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "/* drivers/foo.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "foo int foo ( s#foo * )\n",
+            "/* drivers/bar.o */\n",
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut writer = Writer::new_buffer();
+    let mut writer2 = Writer::new_buffer();
+    let result = symtypes.compare_files_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        &mut [
+            (CompareFormat::Short, &mut writer),
+            (CompareFormat::Symbols, &mut writer2),
+        ],
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    let out = writer.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* drivers/foo.o */\n",
+            "Type 's#foo' has been modified\n", //
+        )
+    );
+    let out2 = writer2.into_inner_vec();
+    assert_eq!(str::from_utf8(&out2).unwrap(), "drivers/foo.symtypes\n");
+}
+
+#[test]
+fn compare_files_moved() {
+    // Check that the per-file comparison reports files that were moved, pairing them by their
+    // content, and compares their records.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* drivers/foo.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#bar struct bar { int a ; }\n",
+            "s#baz struct baz { int a ; }\n",
+            "foo int foo ( s#foo * , s#bar * , s#baz * )\n",
+            "/* drivers/qux.symtypes */\n",
+            "s#qux struct qux { int a ; }\n",
+            "s#quux struct quux { int a ; }\n",
+            "qux int qux ( s#qux * , s#quux * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "/* drivers/foo/core.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#bar struct bar { int a ; }\n",
+            "s#baz struct baz { long a ; }\n",
+            "foo int foo ( s#foo * , s#bar * , s#baz * )\n",
+            "/* drivers/qux/core.symtypes */\n",
+            "s#qux struct qux { long a ; }\n",
+            "s#quux struct quux { long a ; }\n",
+            "qux int qux ( s#qux * , s#quux * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut writer = Writer::new_buffer();
+    let mut writer2 = Writer::new_buffer();
+    let result = symtypes.compare_files_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        &mut [
+            (CompareFormat::Short, &mut writer),
+            (CompareFormat::ModSymbols, &mut writer2),
+        ],
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    let out = writer.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "File 'drivers/qux/core.symtypes' has been added\n",
+            "File 'drivers/qux.symtypes' has been removed\n",
+            "File 'drivers/foo.symtypes' has been moved to 'drivers/foo/core.symtypes'\n",
+            "\n",
+            "/* drivers/foo.symtypes -> drivers/foo/core.symtypes */\n",
+            "Type 's#baz' has been modified\n", //
+        )
+    );
+    let out2 = writer2.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out2).unwrap(),
+        "drivers/foo/core.symtypes\n"
+    );
+}
+
+#[test]
+fn compare_files_moved_paired_by_path() {
+    // Check that the per-file comparison pairs files by their path before looking for moved files,
+    // even if another added file is more similar.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* drivers/foo.o */\n",
+            "s#foo struct foo { int a ; }\n",
+            "foo int foo ( s#foo * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "/* drivers/foo.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "foo_v2 int foo_v2 ( s#foo * )\n",
+            "/* drivers/foo/core.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "foo int foo ( s#foo * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut writer = Writer::new_buffer();
+    let result = symtypes.compare_files_with_buffer(
+        &symtypes2,
+        None,
+        None,
+        None,
+        &DiffOptions::default(),
+        &mut [(CompareFormat::Short, &mut writer)],
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    let out = writer.into_inner_vec();
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "File 'drivers/foo/core.symtypes' has been added\n",
+            "\n",
+            "/* drivers/foo.o */\n",
+            "Export 'foo' has been removed\n",
+            "Export 'foo_v2' has been added\n",
+            "Type 's#foo' has been modified\n", //
+        )
+    );
+}
+
+#[test]
+fn write_history() {
+    // Check that the history of an export and a type lists their state in each corpus, with the
+    // changes explained.
+    let history_data = [
+        bytes!("baz int baz ( )\n"),
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo * )\n", //
+        ),
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo * )\n", //
+        ),
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "bar int bar ( s#foo * )\n", //
+        ),
+        bytes!("baz int baz ( )\n"),
+    ];
+    let corpuses = || {
+        zip(["v1", "v2", "v3", "v4", "v5"], history_data).map(|(label, data)| {
+            let mut symtypes = SymtypesCorpus::new();
+            symtypes.load_buffer("test.symtypes", data, Vec::new())?;
+            Ok((label.to_string(), symtypes))
+        })
+    };
+
+    let mut out = Vec::new();
+    let result = write_history_buffer("bar", corpuses(), &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "v1: 'bar' is not found\n",
+            "v2: 'bar' has been added\n",
+            "v3: 'bar' is unchanged\n",
+            "v4: 'bar' has changed\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n",
+            "\n",
+            "v5: 'bar' has been removed\n", //
+        )
+    );
+
+    let mut out = Vec::new();
+    let result = write_history_buffer("s#foo", corpuses(), &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "v1: 's#foo' is not found\n",
+            "v2: 's#foo' has been added\n",
+            "v3: 's#foo' is unchanged\n",
+            "v4: 's#foo' has changed\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n",
+            "\n",
+            "v5: 's#foo' has been removed\n", //
+        )
+    );
+}
+
+#[test]
+fn cross_check() {
+    // Check that types with differing definitions across corpuses are reported once, together with
+    // all exports that use them and the corpuses of each variant.
+    let corpus_data = [
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "baz int baz ( s#foo * )\n",
+            "qux int qux ( )\n", //
+        ),
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "baz int baz ( s#foo * )\n",
+            "qux int qux ( )\n", //
+        ),
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "qux long qux ( )\n", //
+        ),
+    ];
+    let mut corpuses = zip(["default", "rt", "kvmsmall"], corpus_data)
+        .map(|(label, data)| {
+            let mut symtypes = SymtypesCorpus::new();
+            let result = symtypes.load_buffer("test.symtypes", data, Vec::new());
+            assert_ok!(result);
+            (label.to_string(), symtypes)
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = Vec::new();
+    let result = cross_check_buffer(&corpuses, None, &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "The following '1' exports use differing definitions of 'qux':\n",
+            " qux\n",
+            "\n",
+            "'default', 'rt' vs. 'kvmsmall':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-int qux ( )\n",
+            "+long qux ( )\n",
+            "\n",
+            "The following '2' exports use differing definitions of 's#foo':\n",
+            " bar\n",
+            " baz\n",
+            "\n",
+            "'default', 'kvmsmall' vs. 'rt':\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+
+    // Check that only types matching the filter are checked.
+    let mut type_filter = Filter::new();
+    let result = type_filter.load_buffer("test_filter.txt", bytes!("s#*\n"));
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = cross_check_buffer(&corpuses[..2], Some(&type_filter), &mut out);
+    assert_ok_eq!(result, true);
+    assert!(
+        str::from_utf8(&out)
+            .unwrap()
+            .starts_with("The following '2' exports use differing definitions of 's#foo':\n")
+    );
+    let mut out = Vec::new();
+    let result = cross_check_buffer(&[corpuses.swap_remove(0)], None, &mut out);
+    assert_ok_eq!(result, false);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn exports_using() {
+    // Check that exports referencing a type directly or indirectly are found in each file.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#bar struct bar { s#foo * f ; }\n",
+            "baz int baz ( s#bar * )\n",
+            "qux int qux ( s#foo * )\n",
+            "quux int quux ( int )\n",
+            "/* b.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "corge int corge ( s#foo * )\n",
+            "/* c.symtypes */\n",
+            "grault int grault ( void )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    assert_eq!(
+        symtypes.exports_using("s#foo"),
+        vec![
+            (Path::new("a.symtypes"), vec!["baz", "qux"]),
+            (Path::new("b.symtypes"), vec!["corge"]),
+        ]
+    );
+    assert_eq!(
+        symtypes.exports_using("s#bar"),
+        vec![(Path::new("a.symtypes"), vec!["baz"])]
+    );
+    assert_eq!(symtypes.exports_using("baz"), vec![]);
+}
+
+#[test]
+fn write_users() {
+    // Check that exports referencing a type are written grouped by their file.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "/* b.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_users_buffer("s#foo", &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* a.symtypes */\n",
+            "bar\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "baz\n", //
+        )
+    );
+    let mut out = Vec::new();
+    let result = symtypes.write_users_buffer("s#qux", &mut out);
+    assert_ok_eq!(result, false);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn write_unknowns() {
+    // Check that types declared as UNKNOWN are written with the exports that depend on them, only
+    // considering the files in which the type is UNKNOWN.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo * )\n",
+            "/* b.symtypes */\n",
+            "s#foo struct foo { UNKNOWN }\n",
+            "s#qux struct qux { s#foo * f ; }\n",
+            "baz int baz ( s#qux * )\n",
+            "quux int quux ( s#foo * )\n",
+            "/* c.symtypes */\n",
+            "e#corge enum corge { UNKNOWN }\n",
+            "grault int grault ( void )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_unknowns_buffer(&mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "e#corge\n",
+            "s#foo\n",
+            " baz\n",
+            " quux\n", //
+        )
+    );
+}
+
+#[test]
+fn write_graph() {
+    // Check that the type-reference graph of an export contains each referenced type once.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; s#baz * b ; }\n",
+            "s#bar struct bar { s#foo * f ; s#baz * b ; }\n",
+            "s#baz struct baz { s#foo * f ; }\n",
+            "qux int qux ( s#bar * , s#foo * , s#bar * )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_graph_buffer("qux", &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "digraph \"qux\" {\n",
+            "\t\"qux\" -> \"s#bar\";\n",
+            "\t\"qux\" -> \"s#foo\";\n",
+            "\t\"s#bar\" -> \"s#foo\";\n",
+            "\t\"s#bar\" -> \"s#baz\";\n",
+            "\t\"s#baz\" -> \"s#foo\";\n",
+            "\t\"s#foo\" -> \"s#baz\";\n",
+            "}\n", //
+        )
+    );
+}
+
+#[test]
+fn write_graph_missing() {
+    // Check that a graph is not written for a type that is not an export.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_graph_buffer("s#foo", &mut out);
+    assert_ok_eq!(result, false);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn export_crc() {
+    // Check that the CRC of an export is computed over its expanded definition, with already
+    // expanded types referred to by their short form and the storage class left out. The expected
+    // values are those recorded by genksyms in a kernel build.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "t#__u32 typedef unsigned int __u32\n",
+            "t#u32 typedef t#__u32 u32\n",
+            "t#__kernel_ulong_t typedef unsigned long __kernel_ulong_t\n",
+            "t#__kernel_size_t typedef t#__kernel_ulong_t __kernel_size_t\n",
+            "t#size_t typedef t#__kernel_size_t size_t\n",
+            "crc32_le t#u32 __attribute__ ( ( __pure__ ) ) crc32_le ( t#u32 , unsigned char const * , t#size_t )\n",
+            "NlsUniUpperTable extern signed char NlsUniUpperTable [ 512 ]\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    assert_eq!(symtypes.export_crc("crc32_le"), Some(0x69dd3b5b));
+    // The CRC of "signed char NlsUniUpperTable [ 512 ] ".
+    assert_eq!(symtypes.export_crc("NlsUniUpperTable"), Some(0x4c81e490));
+    assert_eq!(symtypes.export_crc("t#u32"), None);
+}
+
+#[test]
+fn validate_valid() {
+    // Check that validating correct plain and consolidated data finds no problems.
+    let result = SymtypesCorpus::validate_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+    );
+    assert!(result.unwrap().is_empty());
+    let result = SymtypesCorpus::validate_buffer(
+        "test_consolidated.symtypes",
+        bytes!(
+            "/* test.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "s##foo\n",
+            "baz int baz ( s#foo )\n", //
+        ),
+    );
+    assert!(result.unwrap().is_empty());
+}
+
+#[test]
+fn validate_plain_problems() {
+    // Check that validating a plain file reports all found problems.
+    let result = SymtypesCorpus::validate_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo , s#baz )\n",
+            "s#foo struct foo { int b ; }\n",
+            "\n",
+            "s##qux\n",
+            "/* test2.symtypes */\n", //
+        ),
+    );
+    let problems = result.unwrap();
+    assert_eq!(
+        problems
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>(),
+        [
+            concat!(
+                "Type 's#baz' is not known\n",
+                " test.symtypes:2\n",
+                " | bar int bar ( s#foo , s#baz )", //
+            ),
+            concat!(
+                "Duplicate record 's#foo'\n",
+                " test.symtypes:3\n",
+                " | s#foo struct foo { int b ; }", //
+            ),
+            concat!(
+                "Expected a record name\n",
+                " test.symtypes:4\n",
+                " | ", //
+            ),
+            concat!(
+                "Unexpected UNKNOWN override 's##qux' in a plain file\n",
+                " test.symtypes:5\n",
+                " | s##qux", //
+            ),
+            concat!(
+                "Unexpected file header in a plain symtypes file\n",
+                " test.symtypes:6\n",
+                " | /* test2.symtypes */", //
+            ),
+        ]
+    );
+}
+
+#[test]
+fn validate_consolidated_problems() {
+    // Check that validating a consolidated file reports all found problems.
+    let result = SymtypesCorpus::validate_buffer(
+        "test_consolidated.symtypes",
+        bytes!(
+            "/* test.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* test.symtypes */\n",
+            "x##foo\n",
+            "s##foo extra\n",
+            "baz int baz ( s#qux )\n", //
+        ),
+    );
+    let problems = result.unwrap();
+    assert_eq!(
+        problems
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>(),
+        [
+            concat!(
+                "Duplicate file path 'test.symtypes'\n",
+                " test_consolidated.symtypes:5\n",
+                " | /* test.symtypes */", //
+            ),
+            concat!(
+                "Malformed UNKNOWN override 'x##foo'\n",
+                " test_consolidated.symtypes:6\n",
+                " | x##foo", //
+            ),
+            concat!(
+                "Unexpected string found at the end of the override record\n",
+                " test_consolidated.symtypes:7\n",
+                " | s##foo extra", //
+            ),
+            concat!(
+                "Type 's#qux' is not known\n",
+                " test_consolidated.symtypes:8\n",
+                " | baz int baz ( s#qux )", //
+            ),
+        ]
+    );
+}
+
+#[test]
+fn write_stats() {
+    // Check that the statistics count distinct types and their variants across files.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "/* b.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_stats_buffer(1, &mut out);
+    assert_ok!(result);
+    let out = str::from_utf8(&out).unwrap();
+    assert!(out.starts_with(concat!(
+        "Files: 2\n",
+        "Exports: 2\n",
+        "Types: 3\n",
+        "Type variants: 4\n",
+        "Types by the number of variants:\n",
+        " 1: 2\n",
+        " 2: 1\n",
+        "Types with the most variants:\n",
+        " s#foo: 2\n",
+        "Estimated memory footprint:\n", //
+    )));
+}
+
+#[test]
+fn write_stats_metadata() {
+    // Check that the statistics summarize the file metadata.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/*! consolidated 2 */\n",
+            "/* a.symtypes */\n",
+            "/*@ arch x86_64 */\n",
+            "/*@ object a.o */\n",
+            "bar int bar ( )\n",
+            "/* b.symtypes */\n",
+            "/*@ arch x86_64 */\n",
+            "/*@ flavor default */\n",
+            "baz int baz ( )\n",
+            "/* c.symtypes */\n",
+            "qux int qux ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.write_stats_buffer(1, &mut out);
+    assert_ok!(result);
+    let out = str::from_utf8(&out).unwrap();
+    assert!(out.starts_with(concat!(
+        "Files: 3\n",
+        "Exports: 3\n",
+        "Types: 3\n",
+        "Type variants: 3\n",
+        "Files with metadata: 2\n",
+        "Files by metadata:\n",
+        " arch=x86_64: 2\n",
+        " flavor=default: 1\n",
+        "Types by the number of variants:\n", //
+    )));
+}
+
+#[test]
+fn check_variants() {
+    // Check that types with more variants than the given maximum are reported.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "s#bar struct bar { int a ; }\n",
+            "baz1 int baz1 ( s#foo , s#bar )\n",
+            "/* b.symtypes */\n",
+            "s#foo struct foo { long a ; }\n",
+            "s#bar struct bar { long a ; }\n",
+            "baz2 int baz2 ( s#foo , s#bar )\n",
+            "/* c.symtypes */\n",
+            "s#foo struct foo { char a ; }\n",
+            "baz3 int baz3 ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut warnings = Vec::new();
+    let result = symtypes.check_variants(1, &mut warnings);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&warnings).unwrap(),
+        concat!(
+            "a.symtypes: WARNING: Type 's#bar' has '2' variants, more than the maximum of '1'\n",
+            "a.symtypes: WARNING: Type 's#foo' has '3' variants, more than the maximum of '1'\n", //
+        )
+    );
+
+    let mut warnings = Vec::new();
+    let result = symtypes.check_variants(3, &mut warnings);
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn show_export() {
+    // Check that showing an export outputs its definition without referenced types.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.show_buffer("bar", None, &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* test.symtypes */\n",
+            "int bar ( s#foo )\n", //
+        )
+    );
+}
+
+#[test]
+fn show_missing() {
+    // Check that showing a non-existent symbol is reported.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer("test.symtypes", bytes!("bar int bar ( )\n"), &mut warnings);
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.show_buffer("baz", None, &mut out);
+    assert_ok_eq!(result, false);
+    assert_eq!(str::from_utf8(&out).unwrap(), "");
+}
+
+#[test]
+fn show_variants() {
+    // Check that showing a type outputs each of its distinct variants.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let result = symtypes.load_buffer(
+        "test2.symtypes",
+        bytes!(
+            "s#foo struct foo { long a ; }\n",
+            "baz int baz ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let result = symtypes.load_buffer(
+        "test3.symtypes",
+        bytes!(
+            "s#foo struct foo { int a ; }\n",
+            "qux int qux ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut out = Vec::new();
+    let result = symtypes.show_buffer("s#foo", None, &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* test.symtypes */\n",
+            "struct foo {\n",
+            "\tint a;\n",
+            "}\n",
+            "\n",
+            "/* test2.symtypes */\n",
+            "struct foo {\n",
+            "\tlong a;\n",
+            "}\n", //
+        )
+    );
+}
+
+#[test]
+fn show_recursive_max_depth() {
+    // Check that showing an export recursively outputs its referenced types up to the specified
+    // depth, each of them only once.
+    let mut symtypes = SymtypesCorpus::new();
+    let mut warnings = Vec::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "t#baz_t typedef int baz_t\n",
+            "s#foo struct foo { t#baz_t a ; }\n",
+            "s#qux struct qux { }\n",
+            "bar int bar ( s#foo , s#qux , s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let mut out = Vec::new();
+    let result = symtypes.show_buffer("bar", Some(1), &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* test.symtypes */\n",
+            "int bar ( s#foo, s#qux, s#foo )\n",
+            "\n",
+            "s#foo:\n",
+            "struct foo {\n",
+            "\tt#baz_t a;\n",
+            "}\n",
+            "\n",
+            "s#qux:\n",
+            "struct qux {\n",
+            "}\n", //
+        )
+    );
+
+    let mut out = Vec::new();
+    let result = symtypes.show_buffer("bar", Some(usize::MAX), &mut out);
+    assert_ok_eq!(result, true);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "/* test.symtypes */\n",
+            "int bar ( s#foo, s#qux, s#foo )\n",
+            "\n",
+            "s#foo:\n",
+            "struct foo {\n",
+            "\tt#baz_t a;\n",
+            "}\n",
+            "\n",
+            "t#baz_t:\n",
+            "typedef int baz_t\n",
+            "\n",
+            "s#qux:\n",
+            "struct qux {\n",
+            "}\n", //
+        )
+    );
+}
+
+#[test]
+fn normalize_tokens_cosmetic() {
+    // Check that glued punctuation is split and attributes are merged and sorted.
+    let atoms = |words: &str| words.split(' ').map(Token::new_atom).collect::<Vec<_>>();
+    assert_eq!(
+        normalize_tokens(&atoms("int *a[4];")),
+        atoms("int * a [ 4 ] ;")
+    );
+    assert_eq!(
+        normalize_tokens(&atoms(
+            "int b __attribute__ ( ( packed ) ) __attribute__ ( ( aligned ( 8 ) ) ) ;"
+        )),
+        atoms("int b __attribute__ ( ( aligned ( 8 ) , packed ) ) ;")
+    );
+    assert_eq!(
+        normalize_tokens(&atoms("int c __attribute__ ( ( packed ) ;")),
+        atoms("int c __attribute__ ( ( packed ) ;")
+    );
+    assert_eq!(
+        normalize_tokens(&atoms("void f ( ... )")),
+        atoms("void f ( ... )")
+    );
+}
+
+#[test]
+fn compare_strict() {
+    // Check that types differing only cosmetically are equal, unless the strict comparison is
+    // enabled.
+    let mut warnings = Vec::new();
+    let mut symtypes = SymtypesCorpus::new();
+    let result = symtypes.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int * a ; int b __attribute__ ( ( aligned ( 8 ) , packed ) ) ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "test.symtypes",
+        bytes!(
+            "s#foo struct foo { int *a ; int b __attribute__ ( ( packed ) ) __attribute__ ( ( aligned ( 8 ) ) ) ; }\n",
+            "bar int bar ( s#foo )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+
+    let result = symtypes.compare(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        &mut JobControl::new_simple(1),
+    );
+    assert!(result.unwrap().is_empty());
+
+    symtypes.set_strict(true);
+    let result = symtypes.compare(
+        &symtypes2,
+        None,
+        None,
+        None,
+        None,
+        &mut JobControl::new_simple(1),
+    );
+    let result = result.unwrap();
+    assert_eq!(result.changed_types.len(), 1);
+    assert_eq!(result.changed_types[0].name, "s#foo");
+    assert_eq!(result.changed_types[0].exports, vec!["bar"]);
+}
+
+#[test]
+fn classify_layout_change() {
+    // Check that changes of struct members and attributes are classified.
+    let struct_foo = |members: &[&str]| {
+        let mut tokens = vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+        ];
+        for member in members {
+            tokens.extend(member.split(' ').map(Token::new_atom));
+            tokens.push(Token::new_atom(";"));
+        }
+        tokens.push(Token::new_atom("}"));
+        tokens
+    };
+    let classify = |members: &[&str], other_members: &[&str]| {
+        LayoutChange::classify("s#foo", &struct_foo(members), &struct_foo(other_members))
+    };
+
+    assert_eq!(
+        classify(&["int a"], &["int a", "int b"]),
+        Some(LayoutChange::MemberAppended)
+    );
+    assert_eq!(
+        classify(&["int a", "int b", "int c"], &["int a", "int c"]),
+        Some(LayoutChange::MemberRemoved)
+    );
+    assert_eq!(
+        classify(&["int a", "int b"], &["int b", "int a"]),
+        Some(LayoutChange::MemberReordered)
+    );
+    assert_eq!(
+        classify(
+            &["int a [ 4 ]", "unsigned b : 1", "void ( * c ) ( int )"],
+            &["long a [ 8 ]", "t#bool b", "void ( * c ) ( long )"]
+        ),
+        Some(LayoutChange::MemberTypeChanged)
+    );
+    assert_eq!(
+        classify(&["int a"], &["int a __attribute__ ( ( aligned ( 8 ) ) )"]),
+        Some(LayoutChange::AttributeChanged)
+    );
+    assert_eq!(classify(&["int a"], &["int b"]), Some(LayoutChange::Mixed));
+    assert_eq!(
+        classify(&["int a", "int b"], &["int c"]),
+        Some(LayoutChange::Mixed)
+    );
+    assert_eq!(
+        classify(&["int a", "int b"], &["int a", "long c", "int b"]),
+        Some(LayoutChange::Mixed)
+    );
+
+    // Check that a type without members is not classified.
+    let typedef_foo = |base: &str| {
+        vec![
+            Token::new_atom("typedef"),
+            Token::new_atom(base),
+            Token::new_atom("foo"),
+        ]
+    };
+    assert_eq!(
+        LayoutChange::classify("t#foo", &typedef_foo("int"), &typedef_foo("long")),
+        None
+    );
+
+    // Check that the verdict is probably safe only for appended members and changed attributes.
+    assert!(LayoutChange::MemberAppended.is_probably_safe());
+    assert!(LayoutChange::AttributeChanged.is_probably_safe());
+    assert!(!LayoutChange::MemberRemoved.is_probably_safe());
+    assert!(!LayoutChange::MemberReordered.is_probably_safe());
+    assert!(!LayoutChange::MemberTypeChanged.is_probably_safe());
+    assert!(!LayoutChange::Mixed.is_probably_safe());
+}
+
+#[test]
+fn classify_enum_change() {
+    // Check that changes of enum constants are classified with respect to their values.
+    let enum_foo = |enumerators: &[&str]| {
+        let mut tokens = vec![
+            Token::new_atom("enum"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+        ];
+        for (idx, enumerator) in enumerators.iter().enumerate() {
+            if idx > 0 {
+                tokens.push(Token::new_atom(","));
+            }
+            tokens.extend(enumerator.split(' ').map(Token::new_atom));
+        }
+        tokens.push(Token::new_atom("}"));
+        tokens
+    };
+    let classify = |enumerators: &[&str], other_enumerators: &[&str]| {
+        LayoutChange::classify(
+            "e#foo",
+            &enum_foo(enumerators),
+            &enum_foo(other_enumerators),
+        )
+    };
+
+    assert_eq!(
+        classify(&["A", "B"], &["A", "B", "C"]),
+        Some(LayoutChange::EnumeratorAppended)
+    );
+    assert_eq!(
+        classify(&["A", "B"], &["A", "C", "B"]),
+        Some(LayoutChange::EnumeratorInserted)
+    );
+    assert_eq!(
+        classify(&["A", "B"], &["A = 1", "B"]),
+        Some(LayoutChange::EnumeratorRenumbered)
+    );
+    assert_eq!(
+        classify(&["A = 0x10", "B"], &["A = 16", "B = 17", "C"]),
+        Some(LayoutChange::EnumeratorAppended)
+    );
+    assert_eq!(
+        classify(&["A = X", "B"], &["A = Y", "B"]),
+        Some(LayoutChange::EnumeratorRenumbered)
+    );
+    assert_eq!(
+        classify(&["A", "B", "C"], &["A", "C"]),
+        Some(LayoutChange::MemberRemoved)
+    );
+    assert_eq!(
+        classify(&["A", "B"], &["B", "A"]),
+        Some(LayoutChange::MemberReordered)
+    );
+    assert_eq!(classify(&["A", "B"], &["C"]), Some(LayoutChange::Mixed));
+
+    // Check that only the enumerators appended at the tail are probably safe.
+    assert!(LayoutChange::EnumeratorAppended.is_probably_safe());
+    assert!(!LayoutChange::EnumeratorInserted.is_probably_safe());
+    assert!(!LayoutChange::EnumeratorRenumbered.is_probably_safe());
+}
+
+#[test]
+fn suggest_fixup_appended() {
+    // Check that members and constants appended at the end are suggested to be hidden from
+    // genksyms.
+    let result = suggest_fixup(
+        "s#foo",
+        &vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("int"),
+            Token::new_atom("a"),
+            Token::new_atom(";"),
+            Token::new_atom("}"),
+        ],
+        &vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("int"),
+            Token::new_atom("a"),
+            Token::new_atom(";"),
+            Token::new_atom("void"),
+            Token::new_atom("("),
+            Token::new_atom("*"),
+            Token::new_atom("b"),
+            Token::new_atom(")"),
+            Token::new_atom("("),
+            Token::new_atom("int"),
+            Token::new_atom(","),
+            Token::new_atom("int"),
+            Token::new_atom(")"),
+            Token::new_atom(";"),
+            Token::new_atom("}"),
+        ],
+    );
+    assert_eq!(
+        result,
+        vec![
+            "New members are appended at the end of 's#foo'.",
+            "If only the owner allocates the struct and other code uses it through pointers,",
+            "hide them from genksyms:",
+            "#ifndef __GENKSYMS__",
+            "\tvoid ( * b ) ( int, int );",
+            "#endif",
+        ]
+    );
+
+    let result = suggest_fixup(
+        "e#foo",
+        &vec![
+            Token::new_atom("enum"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("A"),
+            Token::new_atom("}"),
+        ],
+        &vec![
+            Token::new_atom("enum"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("A"),
+            Token::new_atom(","),
+            Token::new_atom("B"),
+            Token::new_atom("}"),
+        ],
+    );
+    assert_eq!(
+        result,
+        vec![
+            "New constants are appended at the end of 'e#foo'.",
+            "If the enum doesn't size any array or structure, hide them from genksyms:",
+            "#ifndef __GENKSYMS__",
+            "\tB,",
+            "#endif",
+        ]
+    );
+}
+
+#[test]
+fn suggest_fixup_renumbered() {
+    // Check that constants which keep their names but change their values are reported as
+    // renumbered, consistently with the layout change classification.
+    let tokens = vec![
+        Token::new_atom("enum"),
+        Token::new_atom("foo"),
+        Token::new_atom("{"),
+        Token::new_atom("A"),
+        Token::new_atom(","),
+        Token::new_atom("B"),
+        Token::new_atom("}"),
+    ];
+    let other_tokens = vec![
+        Token::new_atom("enum"),
+        Token::new_atom("foo"),
+        Token::new_atom("{"),
+        Token::new_atom("A"),
+        Token::new_atom("="),
+        Token::new_atom("1"),
+        Token::new_atom(","),
+        Token::new_atom("B"),
+        Token::new_atom("}"),
+    ];
+    assert_eq!(
+        LayoutChange::classify("e#foo", &tokens, &other_tokens),
+        Some(LayoutChange::EnumeratorRenumbered)
+    );
+    let result = suggest_fixup("e#foo", &tokens, &other_tokens);
+    assert_eq!(
+        result,
+        vec![
+            "The constants of 'e#foo' keep their names, but their values changed.",
+            "Restore the original values of the constants.",
+        ]
+    );
+}
+
+#[test]
+fn suggest_fixup_changed() {
+    // Check that a changed member is suggested to be kept in its original form for genksyms, and
+    // that a replaced padding is recognized.
+    let result = suggest_fixup(
+        "s#foo",
+        &vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("int"),
+            Token::new_atom("a"),
+            Token::new_atom(";"),
+            Token::new_atom("long"),
+            Token::new_atom("reserved1"),
+            Token::new_atom(";"),
+            Token::new_atom("}"),
+        ],
+        &vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("long"),
+            Token::new_atom("a"),
+            Token::new_atom(";"),
+            Token::new_atom("long"),
+            Token::new_atom("b"),
+            Token::new_atom(";"),
+            Token::new_atom("}"),
+        ],
+    );
+    assert_eq!(
+        result,
+        vec![
+            "'int a' is changed to 'long a'.",
+            "If the size and alignment are unchanged, keep the original for genksyms:",
+            "#ifdef __GENKSYMS__",
+            "\tint a;",
+            "#else",
+            "\tlong a;",
+            "#endif",
+            "The reserved member 'long reserved1' is replaced by 'long b'.",
+            "If the new member fits into the reserved space, keep the original for genksyms:",
+            "#ifdef __GENKSYMS__",
+            "\tlong reserved1;",
+            "#else",
+            "\tlong b;",
+            "#endif",
+        ]
+    );
+}
+
+#[test]
+fn suggest_fixup_layout() {
+    // Check that reordered, removed and inserted members are reported.
+    let struct_foo = |members: &[&str]| {
+        let mut tokens = vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+        ];
+        for member in members {
+            tokens.push(Token::new_atom("int"));
+            tokens.push(Token::new_atom(*member));
+            tokens.push(Token::new_atom(";"));
+        }
+        tokens.push(Token::new_atom("}"));
+        tokens
+    };
+
+    let result = suggest_fixup("s#foo", &struct_foo(&["a", "b"]), &struct_foo(&["b", "a"]));
+    assert_eq!(
+        result,
+        vec![
+            "The members of 's#foo' are reordered, which changes their offsets.",
+            "Restore the original order of the members.",
+        ]
+    );
+
+    let result = suggest_fixup(
+        "s#foo",
+        &struct_foo(&["a", "b", "c"]),
+        &struct_foo(&["a", "c"]),
+    );
+    assert_eq!(
+        result,
+        vec![
+            "The members 'int b' are removed from 's#foo'.",
+            "Keep them in place to preserve the layout, for instance, renamed as unused.",
+        ]
+    );
+
+    let result = suggest_fixup(
+        "s#foo",
+        &struct_foo(&["a", "c"]),
+        &struct_foo(&["a", "b", "c"]),
+    );
+    assert_eq!(
+        result,
+        vec![
+            "The members 'int b' are inserted into 's#foo', which changes the offsets of the following members.",
+            "Move the new members to the end of the struct, or into a reserved padding.",
+        ]
+    );
+
+    let result = suggest_fixup("s#foo", &struct_foo(&["a", "b"]), &struct_foo(&["c"]));
+    assert_eq!(
+        result,
+        vec!["The members of 's#foo' changed in a complex way, review the change manually."]
+    );
+}
+
+#[test]
+fn suggest_fixup_declaration() {
+    // Check that a definition turned into a declaration, and a non-aggregate type, are handled.
+    let result = suggest_fixup(
+        "s#foo",
+        &vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("int"),
+            Token::new_atom("a"),
+            Token::new_atom(";"),
+            Token::new_atom("}"),
+        ],
+        &vec![
+            Token::new_atom("struct"),
+            Token::new_atom("foo"),
+            Token::new_atom("{"),
+            Token::new_atom("UNKNOWN"),
+            Token::new_atom("}"),
+        ],
+    );
+    assert_eq!(
+        result,
+        vec![
+            "The definition of 's#foo' is no longer visible to genksyms.",
+            "Include the header that defines it under '#ifdef __GENKSYMS__'.",
+        ]
+    );
+
+    let result = suggest_fixup(
+        "t#foo_t",
+        &vec![
+            Token::new_atom("typedef"),
+            Token::new_atom("int"),
+            Token::new_atom("foo_t"),
+        ],
+        &vec![
+            Token::new_atom("typedef"),
+            Token::new_atom("long"),
+            Token::new_atom("foo_t"),
+        ],
+    );
+    assert_eq!(
+        result,
+        vec!["No suggestion is available for this type, review the change manually."]
+    );
+}
@@ -248,6 +248,45 @@ fn format_typeref() {
     );
 }
 
+#[test]
+fn format_type_record() {
+    // Check that a complete symtypes record is pretty formatted without its name.
+    let pretty = format_type("s#foo struct foo { int a ; s#bar * b ; }");
+    assert_eq!(
+        pretty,
+        crate::string_vec!(
+            "struct foo {",
+            "\tint a;",
+            "\ts#bar * b;",
+            "}", //
+        )
+    );
+
+    let pretty = format_type("foo int foo ( s#bar * )");
+    assert_eq!(pretty, crate::string_vec!("int foo ( s#bar * )"));
+
+    let pretty = format_type("E#FOO 7");
+    assert_eq!(pretty, crate::string_vec!("7"));
+}
+
+#[test]
+fn format_type_declaration() {
+    // Check that tokens of a type declaration without a record name are pretty formatted as a whole.
+    let pretty = format_type("s#foo * bar ( int )");
+    assert_eq!(pretty, crate::string_vec!("s#foo * bar ( int )"));
+
+    let pretty = format_type("enum foo { A , B }");
+    assert_eq!(
+        pretty,
+        crate::string_vec!(
+            "enum foo {",
+            "\tA,",
+            "\tB",
+            "}", //
+        )
+    );
+}
+
 #[test]
 fn format_removal() {
     // Check the diff format when a struct member is removed.
@@ -274,6 +313,8 @@ fn format_removal() {
             Token::new_atom(";"),
             Token::new_atom("}"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -325,6 +366,8 @@ fn format_removal_top() {
             Token::new_atom("ivalue5"),
             Token::new_atom(";"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -376,6 +419,8 @@ fn format_removal_end() {
             Token::new_atom("ivalue4"),
             Token::new_atom(";"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -442,6 +487,8 @@ fn format_max_context() {
             Token::new_atom("ivalue7"),
             Token::new_atom(";"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -518,6 +565,8 @@ fn format_max_context2() {
             Token::new_atom("ivalue8"),
             Token::new_atom(";"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -564,6 +613,8 @@ fn format_addition() {
             Token::new_atom(";"),
             Token::new_atom("}"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -602,6 +653,8 @@ fn format_modification() {
             Token::new_atom(";"),
             Token::new_atom("}"),
         ],
+        &DiffOptions::default(),
+        false,
         &mut out,
     );
     assert_ok!(result);
@@ -3,24 +3,32 @@
 
 //! A representation of a kABI symvers corpus and tools for working with the data.
 
-use crate::rules::{Rules, UsedRules};
-use crate::text::{Filter, Writer, matches_filter, read_lines};
-use crate::{Error, MapIOErr, PathFile, debug};
-use std::collections::HashMap;
-use std::io::prelude::*;
-use std::path::Path;
+use crate::demangle::display_name;
+use crate::rules::{RuleType, Rules, ToleratingRule, UsedRules, Verdict};
+use crate::suppressions::{ChangeKind, Suppressions, is_suppressed};
+use crate::symtypes::SymtypesCorpus;
+use crate::text::{
+    ColorMode, DirectoryWriter, Filter, WriteGenerator, Writer, escape_html, matches_filter,
+    read_lines, write_compare_summary, write_html_diff, write_html_footer, write_html_header,
+};
+use crate::{CompareStatus, Error, MapIOErr, PathFile, debug};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{self, prelude::*};
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 
 #[cfg(test)]
 mod tests;
 
 /// An export data.
-#[derive(Debug, Eq, PartialEq)]
-struct ExportInfo {
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExportInfo {
     crc: u32,
     module: String,
     is_gpl_only: bool,
     namespace: Option<String>,
+    extra_columns: Vec<String>,
 }
 
 impl ExportInfo {
@@ -36,9 +44,42 @@ impl ExportInfo {
             module: module.into(),
             is_gpl_only,
             namespace: namespace.map(|n| n.into()),
+            extra_columns: Vec::new(),
         }
     }
 
+    /// Attaches unrecognized trailing columns to the export, which are preserved when the record
+    /// is written out.
+    pub fn with_extra_columns(mut self, extra_columns: Vec<String>) -> Self {
+        self.extra_columns = extra_columns;
+        self
+    }
+
+    /// Returns the CRC of the export.
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    /// Returns the name of the module that provides the export.
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Returns whether the export is available only to GPL-compatible modules.
+    pub fn is_gpl_only(&self) -> bool {
+        self.is_gpl_only
+    }
+
+    /// Returns the namespace of the export, or None if the export is not in a namespace.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Returns the unrecognized columns that followed the namespace in the original record.
+    pub fn extra_columns(&self) -> &[String] {
+        &self.extra_columns
+    }
+
     /// Returns the type as a string slice.
     pub fn type_as_str(&self) -> &str {
         if self.is_gpl_only {
@@ -47,15 +88,111 @@ impl ExportInfo {
             "EXPORT_SYMBOL"
         }
     }
+
+    /// Returns the namespace as a string slice, or "(none)" if the export is not in a namespace.
+    pub fn namespace_as_str(&self) -> &str {
+        self.namespace.as_deref().unwrap_or("(none)")
+    }
 }
 
 /// A collection of export records.
 type Exports = HashMap<String, ExportInfo>;
 
 /// A representation of a kernel ABI, loaded from symvers files.
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default)]
 pub struct SymversCorpus {
     exports: Exports,
+
+    /// Positions of the exports in the order in which they were added to the corpus.
+    input_order: HashMap<String, usize>,
+
+    /// The position assigned to the next added export.
+    next_input_pos: usize,
+
+    /// Whether unknown trailing columns in records are accepted, instead of being rejected.
+    lenient: bool,
+
+    /// The layout of records in loaded files.
+    format: SymversFormat,
+
+    /// Whether all errors in loaded files are collected, instead of stopping at the first one.
+    keep_going: bool,
+
+    /// Whether Rust export names are demangled in human-readable comparison reports.
+    demangle: bool,
+
+    /// When the pretty and short comparison reports are colorized.
+    color: ColorMode,
+
+    /// Exports whose changes are always breaking, regardless of any severity rules.
+    maybe_protected: Option<Filter>,
+
+    /// A header prepended to the pretty and short comparison reports.
+    maybe_output_header: Option<String>,
+}
+
+impl PartialEq for SymversCorpus {
+    /// Compares two corpuses by their records, ignoring the order in which they were added.
+    fn eq(&self, other: &Self) -> bool {
+        self.exports == other.exports
+    }
+}
+
+impl Eq for SymversCorpus {}
+
+/// The order of records in the output from [`SymversCorpus::write()`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WriteOrder {
+    /// Records sorted by their export name.
+    Sorted,
+    /// Records in the order in which they were added to the corpus.
+    Input,
+}
+
+impl FromStr for WriteOrder {
+    type Err = Error;
+
+    /// Obtains a [`WriteOrder`] matching the given order type, specified as a string.
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        match order {
+            "sorted" => Ok(Self::Sorted),
+            "input" => Ok(Self::Input),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized order '{}'",
+                order
+            ))),
+        }
+    }
+}
+
+/// The layout of records in a symvers file.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SymversFormat {
+    /// The layout detected from the first record in each file.
+    #[default]
+    Auto,
+    /// Records in the form `<crc> <name> <module> <type> [<namespace>]`.
+    Modern,
+    /// Records in the pre-namespace form `<crc> <name> [<module> [<type>]]`, as produced by old
+    /// kernels. A missing module defaults to `vmlinux` and a missing type to `EXPORT_SYMBOL`.
+    Legacy,
+}
+
+impl FromStr for SymversFormat {
+    type Err = Error;
+
+    /// Obtains a [`SymversFormat`] matching the given format type, specified as a string.
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "auto" => Ok(Self::Auto),
+            "modern" => Ok(Self::Modern),
+            "legacy" => Ok(Self::Legacy),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized symvers format '{}'",
+                format
+            ))),
+        }
+    }
 }
 
 /// The format of the output from [`SymversCorpus::compare_with()`].
@@ -71,6 +208,16 @@ pub enum CompareFormat {
     Symbols,
     /// A list of all modified symbols only.
     ModSymbols,
+    /// A standalone HTML report.
+    Html,
+    /// Breaking changes reported in the format of the legacy kabi.pl script.
+    KabiPl,
+    /// A list of exports changed to GPL-only, grouped by their module.
+    GplReport,
+    /// A single line with the numbers of breaking, tolerated and unchanged exports.
+    Summary,
+    /// The numbers of added, removed, modified and tolerated exports, listed for each module.
+    ModuleStats,
 }
 
 impl FromStr for CompareFormat {
@@ -84,6 +231,11 @@ impl FromStr for CompareFormat {
             "short" => Ok(Self::Short),
             "symbols" => Ok(Self::Symbols),
             "mod-symbols" => Ok(Self::ModSymbols),
+            "html" => Ok(Self::Html),
+            "kabi-pl" => Ok(Self::KabiPl),
+            "gpl-report" => Ok(Self::GplReport),
+            "summary" => Ok(Self::Summary),
+            "module-stats" => Ok(Self::ModuleStats),
             _ => Err(Self::Err::new_parse(format!(
                 "Unrecognized format '{}'",
                 format
@@ -97,9 +249,75 @@ impl SymversCorpus {
     pub fn new() -> Self {
         Self {
             exports: Exports::new(),
+            input_order: HashMap::new(),
+            next_input_pos: 0,
+            lenient: false,
+            format: SymversFormat::Auto,
+            keep_going: false,
+            demangle: false,
+            color: ColorMode::Never,
+            maybe_protected: None,
+            maybe_output_header: None,
         }
     }
 
+    /// Enables or disables the lenient parsing of symvers data.
+    ///
+    /// Newer kernels may add extra columns after the namespace. In the lenient mode, such columns
+    /// are accepted and preserved in the loaded records, instead of being reported as an error.
+    /// Tab-separated records are split on tabs so that an empty namespace column is recognized.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Selects the layout of records in subsequently loaded symvers data.
+    ///
+    /// With [`SymversFormat::Auto`], which is the default, the layout is detected separately for
+    /// each file from its first record.
+    pub fn set_format(&mut self, format: SymversFormat) {
+        self.format = format;
+    }
+
+    /// Enables or disables the keep-going mode for subsequently loaded symvers data.
+    ///
+    /// In the keep-going mode, a malformed record doesn't stop the loading. Instead, all invalid
+    /// records in a file are collected and reported together in one [`Error::Multiple`].
+    pub fn set_keep_going(&mut self, keep_going: bool) {
+        self.keep_going = keep_going;
+    }
+
+    /// Enables or disables the demangling of Rust export names in comparison reports.
+    ///
+    /// Names are demangled only in the human-readable formats. Symbol lists and the output of the
+    /// legacy kabi.pl format always contain the raw names.
+    pub fn set_demangle(&mut self, demangle: bool) {
+        self.demangle = demangle;
+    }
+
+    /// Sets when the pretty and short comparison reports written to a file are colorized.
+    ///
+    /// The reports are not colorized by default.
+    pub fn set_color(&mut self, color: ColorMode) {
+        self.color = color;
+    }
+
+    /// Sets the list of protected exports to use in comparisons.
+    ///
+    /// Any change of a protected export is breaking, even if it is tolerated by the severity rules
+    /// or implicitly. A removal of a protected export is reported with the
+    /// [`CompareStatus::ProtectedRemoved`] status.
+    pub fn set_protected(&mut self, maybe_protected: Option<Filter>) {
+        self.maybe_protected = maybe_protected;
+    }
+
+    /// Sets a header prepended to the pretty and short comparison reports written to a file.
+    ///
+    /// The header is written as is, before any changes are reported. No header is written by
+    /// default.
+    pub fn set_output_header(&mut self, maybe_output_header: Option<String>) {
+        self.maybe_output_header = maybe_output_header;
+    }
+
     /// Loads symvers data from the specified file.
     ///
     /// New symvers records are appended to the already present ones.
@@ -122,7 +340,56 @@ impl SymversCorpus {
         path: P,
         reader: R,
     ) -> Result<(), Error> {
+        self.load_inner(path.as_ref(), reader, false)
+    }
+
+    /// Creates a new `SymversCorpus` object from in-memory symvers files.
+    ///
+    /// The `files` map the path of each symvers file to its content. The files are loaded in the
+    /// order of their paths, as if each was passed to [`SymversCorpus::load_buffer()`].
+    pub fn from_files(files: HashMap<PathBuf, String>) -> Result<Self, Error> {
+        let mut files = files.into_iter().collect::<Vec<_>>();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut symvers = Self::new();
+        for (path, data) in &files {
+            symvers.load_buffer(path, data.as_bytes())?;
+        }
+
+        Ok(symvers)
+    }
+
+    /// Loads symvers data from the specified file, merging it with the already present records.
+    ///
+    /// A record identical to an already present one is skipped. A record that specifies the same
+    /// export as an already present one, but differs from it, for instance by having a different
+    /// CRC, is considered a conflict. All conflicts found in the file are reported together in one
+    /// error.
+    pub fn merge<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
         let path = path.as_ref();
+
+        let file = PathFile::open(path).map_err(|err| {
+            Error::new_io(format!("Failed to open the file '{}'", path.display()), err)
+        })?;
+
+        self.merge_buffer(path, file)
+    }
+
+    /// Loads symvers data from the specified reader, merging it with the already present records.
+    ///
+    /// The `path` should point to the symvers file name, indicating the origin of the data. See
+    /// [`SymversCorpus::merge()`] for details on how the records are merged.
+    pub fn merge_buffer<P: AsRef<Path>, R: Read>(
+        &mut self,
+        path: P,
+        reader: R,
+    ) -> Result<(), Error> {
+        self.load_inner(path.as_ref(), reader, true)
+    }
+
+    /// Loads symvers data from the specified reader, optionally merging it with the already present
+    /// records.
+    fn load_inner<R: Read>(&mut self, path: &Path, reader: R, merge: bool) -> Result<(), Error> {
         debug!("Loading symvers data from '{}'", path.display());
 
         // Read all content from the file.
@@ -131,26 +398,397 @@ impl SymversCorpus {
             Err(err) => return Err(Error::new_io("Failed to read symvers data", err)),
         };
 
+        let format = match self.format {
+            SymversFormat::Auto => detect_format(&lines),
+            format => format,
+        };
+
         // Parse all records.
         let mut new_exports = Exports::new();
+        let mut new_names = Vec::new();
+        // Collect all conflicts and, in the keep-going mode, also all other errors.
+        let mut errors = Vec::new();
         for (line_idx, line) in lines.iter().enumerate() {
-            let (name, info) = parse_export(path, line_idx, line)?;
+            let (name, info) = match parse_export(path, line_idx, line, format, self.lenient) {
+                Ok(record) => record,
+                Err(err) if self.keep_going => {
+                    errors.push(err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
 
             // Check if the record is a duplicate of another one.
-            if new_exports.contains_key(&name) || self.exports.contains_key(&name) {
-                return Err(Error::new_parse_format(
+            let maybe_other_info = self.exports.get(&name);
+            if new_exports.contains_key(&name) || (!merge && maybe_other_info.is_some()) {
+                let err = Error::new_parse_format(
                     &format!("Duplicate record '{}'", name),
                     path,
                     line_idx + 1,
                     &lines[line_idx],
-                ));
+                );
+                if self.keep_going {
+                    errors.push(err);
+                    continue;
+                }
+                return Err(err);
             }
 
+            // When merging, skip records that are already present and check for conflicts.
+            if let Some(other_info) = maybe_other_info {
+                if *other_info != info {
+                    errors.push(Error::new_parse_format(
+                        &format!(
+                            "Conflicting record '{}', previously loaded as '{}'",
+                            name,
+                            format_export(&name, other_info)
+                        ),
+                        path,
+                        line_idx + 1,
+                        &lines[line_idx],
+                    ));
+                }
+                continue;
+            }
+
+            new_names.push(name.clone());
             new_exports.insert(name, info);
         }
 
+        if !errors.is_empty() {
+            return Err(Error::new_multiple(errors));
+        }
+
         // Add the new exports.
         self.exports.extend(new_exports);
+        for name in new_names {
+            self.record_input_pos(name);
+        }
+
+        Ok(())
+    }
+
+    /// Loads symvers data from the specified kernel build tree, merging it with the already present
+    /// records.
+    ///
+    /// The records are read from the `vmlinux.symvers` file in the root of the tree and merged with
+    /// all `*.symvers` files found in its subdirectories, such as those of separately built
+    /// modules. The top-level `Module.symvers` is ignored, so the tree doesn't need to contain an
+    /// already merged file. Afterwards, the CRCs of imported symbols listed in the `__versions`
+    /// tables of all `*.mod.c` files are checked against the loaded records. All CRC mismatches
+    /// are reported together in one error.
+    pub fn load_build_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+
+        self.merge(path.join("vmlinux.symvers"))?;
+
+        // Recursively collect the per-module files.
+        let mut symvers_files = Vec::new();
+        let mut mod_c_files = Vec::new();
+        collect_build_files(path, Path::new(""), &mut symvers_files, &mut mod_c_files)?;
+        symvers_files.sort();
+        mod_c_files.sort();
+
+        for sub_path in symvers_files {
+            self.merge(path.join(sub_path))?;
+        }
+
+        // Check the CRCs of all imported symbols.
+        let mut conflicts = Vec::new();
+        for sub_path in mod_c_files {
+            let mod_c_path = path.join(sub_path);
+            let file = PathFile::open(&mod_c_path).map_err(|err| {
+                Error::new_io(
+                    format!("Failed to open the file '{}'", mod_c_path.display()),
+                    err,
+                )
+            })?;
+            let lines = read_lines(file).map_err(|err| {
+                Error::new_io(
+                    format!("Failed to read the file '{}'", mod_c_path.display()),
+                    err,
+                )
+            })?;
+
+            for (line_idx, name, crc) in parse_mod_versions(&mod_c_path, &lines)? {
+                if let Some(info) = self.exports.get(&name)
+                    && info.crc != crc
+                {
+                    conflicts.push(Error::new_parse_format(
+                        &format!(
+                            "Conflicting CRC '{:#010x}' of imported symbol '{}', previously loaded as '{}'",
+                            crc,
+                            name,
+                            format_export(&name, info)
+                        ),
+                        &mod_c_path,
+                        line_idx + 1,
+                        &lines[line_idx],
+                    ));
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(Error::new_multiple(conflicts));
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the corpus contains a record for the given export.
+    pub fn contains_export(&self, name: &str) -> bool {
+        self.exports.contains_key(name)
+    }
+
+    /// Returns the record of the given export, or None if the corpus doesn't contain it.
+    pub fn get(&self, name: &str) -> Option<&ExportInfo> {
+        self.exports.get(name)
+    }
+
+    /// Inserts a record for the given export.
+    ///
+    /// If the corpus already contains a record for the export, it is replaced and the previous
+    /// record is returned. The replaced record keeps its original input position.
+    pub fn insert<S: Into<String>>(&mut self, name: S, info: ExportInfo) -> Option<ExportInfo> {
+        let name = name.into();
+        let maybe_old_info = self.exports.insert(name.clone(), info);
+        if maybe_old_info.is_none() {
+            self.record_input_pos(name);
+        }
+        maybe_old_info
+    }
+
+    /// Removes the record of the given export, returning it if the corpus contained it.
+    pub fn remove(&mut self, name: &str) -> Option<ExportInfo> {
+        self.input_order.remove(name);
+        self.exports.remove(name)
+    }
+
+    /// Calls `f` for each export in the corpus, passing the export name and its record.
+    ///
+    /// The exports are visited in no particular order.
+    pub fn for_each_export<F: FnMut(&str, &ExportInfo)>(&self, mut f: F) {
+        for (name, info) in &self.exports {
+            f(name, info);
+        }
+    }
+
+    /// Retains only the exports for which `f` returns true, given the export name and its record.
+    pub fn retain_exports<F: FnMut(&str, &ExportInfo) -> bool>(&mut self, mut f: F) {
+        self.exports.retain(|name, info| f(name, info));
+        self.input_order
+            .retain(|name, _| self.exports.contains_key(name));
+    }
+
+    /// Writes a summary of all namespaces in the corpus to the specified file.
+    ///
+    /// See [`SymversCorpus::write_namespaces_buffer()`] for the details.
+    pub fn write_namespaces<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_namespaces_buffer(Writer::new_file(path)?)
+    }
+
+    /// Writes a summary of all namespaces in the corpus to the provided output stream.
+    ///
+    /// Each line lists a namespace together with the number of its exports. The namespaces are
+    /// sorted by their name. Exports that are not in any namespace are counted under "(none)".
+    pub fn write_namespaces_buffer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let err_desc = "Failed to write a namespace summary";
+
+        let mut namespaces = BTreeMap::<&str, usize>::new();
+        for info in self.exports.values() {
+            *namespaces.entry(info.namespace_as_str()).or_default() += 1;
+        }
+
+        for (namespace, count) in namespaces {
+            writeln!(writer, "{}: {}", namespace, count).map_io_err(err_desc)?;
+        }
+
+        writer.flush().map_io_err(err_desc)
+    }
+
+    /// Writes the churn trend of exports over a series of snapshots to the specified file.
+    ///
+    /// See [`SymversCorpus::write_trend_buffer()`] for the details.
+    pub fn write_trend<P: AsRef<Path>>(
+        snapshots: &[(&str, &SymversCorpus)],
+        path: P,
+    ) -> Result<(), Error> {
+        Self::write_trend_buffer(snapshots, Writer::new_file(path)?)
+    }
+
+    /// Writes the churn trend of exports over a series of snapshots to the provided output stream.
+    ///
+    /// The snapshots are labeled and ordered from the oldest to the newest. The first part of the
+    /// output lists each snapshot with its number of exports and, except for the first snapshot,
+    /// the numbers of exports added, removed and modified since the previous snapshot. The second
+    /// part lists each export with its stability score, sorted by the export name. The score is
+    /// the fraction of transitions between consecutive snapshots in which the export remained
+    /// unchanged, counting only the transitions where the export is present in at least one of the
+    /// two snapshots.
+    pub fn write_trend_buffer<W: Write>(
+        snapshots: &[(&str, &SymversCorpus)],
+        mut writer: W,
+    ) -> Result<(), Error> {
+        let err_desc = "Failed to write an export trend";
+
+        // Track the number of unchanged and all relevant transitions of each export.
+        let mut stability = BTreeMap::<&str, (usize, usize)>::new();
+
+        for (i, &(label, symvers)) in snapshots.iter().enumerate() {
+            if i == 0 {
+                writeln!(
+                    writer,
+                    "Snapshot '{}': '{}' exports",
+                    label,
+                    symvers.exports.len()
+                )
+                .map_io_err(err_desc)?;
+                continue;
+            }
+
+            let prev_symvers = snapshots[i - 1].1;
+            let (mut added, mut removed, mut modified) = (0, 0, 0);
+            for (name, info) in &prev_symvers.exports {
+                let (unchanged, total) = stability.entry(name).or_default();
+                *total += 1;
+                match symvers.exports.get(name) {
+                    Some(other_info) if is_modification(info, other_info) => modified += 1,
+                    Some(_) => *unchanged += 1,
+                    None => removed += 1,
+                }
+            }
+            for name in symvers.exports.keys() {
+                if !prev_symvers.exports.contains_key(name) {
+                    stability.entry(name).or_default().1 += 1;
+                    added += 1;
+                }
+            }
+
+            writeln!(
+                writer,
+                "Snapshot '{}': '{}' exports, '{}' added, '{}' removed, '{}' modified",
+                label,
+                symvers.exports.len(),
+                added,
+                removed,
+                modified
+            )
+            .map_io_err(err_desc)?;
+        }
+
+        for (name, (unchanged, total)) in stability {
+            writeln!(
+                writer,
+                "Export '{}': stability '{:.2}', '{}' of '{}' transitions unchanged",
+                name,
+                unchanged as f64 / total as f64,
+                unchanged,
+                total
+            )
+            .map_io_err(err_desc)?;
+        }
+
+        writer.flush().map_io_err(err_desc)
+    }
+
+    /// Records the input position of a newly added export.
+    fn record_input_pos(&mut self, name: String) {
+        self.input_order.insert(name, self.next_input_pos);
+        self.next_input_pos += 1;
+    }
+
+    /// Writes the corpus in the consolidated form to the specified file.
+    pub fn write_consolidated<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write(path, WriteOrder::Sorted)
+    }
+
+    /// Writes the corpus in the consolidated form to the provided output stream.
+    ///
+    /// The records are sorted by their export name. See [`SymversCorpus::write_buffer()`] for the
+    /// details.
+    pub fn write_consolidated_buffer<W: Write>(&self, writer: W) -> Result<(), Error> {
+        self.write_buffer(writer, WriteOrder::Sorted)
+    }
+
+    /// Writes the corpus as a symvers file to the specified path.
+    pub fn write<P: AsRef<Path>>(&self, path: P, order: WriteOrder) -> Result<(), Error> {
+        self.write_buffer(Writer::new_file(path)?, order)
+    }
+
+    /// Writes the corpus as a symvers file to the provided output stream.
+    ///
+    /// The records are ordered as requested by `order` and formatted in the same tab-separated way
+    /// as produced by modpost.
+    pub fn write_buffer<W: Write>(&self, mut writer: W, order: WriteOrder) -> Result<(), Error> {
+        let err_desc = "Failed to write a symvers record";
+
+        let mut sorted_exports = self.exports.iter().collect::<Vec<_>>();
+        match order {
+            WriteOrder::Sorted => sorted_exports.sort_by_key(|&(name, _)| name),
+            WriteOrder::Input => sorted_exports.sort_by_key(|&(name, _)| self.input_order[name]),
+        }
+
+        for (name, info) in sorted_exports {
+            write_export(&mut writer, name, info).map_io_err(err_desc)?;
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(())
+    }
+
+    /// Writes the corpus in the split form to the specified directory.
+    ///
+    /// Each module gets its own symvers file, named after the module with the `.symvers` extension.
+    pub fn write_split<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_split_buffer(&mut DirectoryWriter::new_file(path))
+    }
+
+    /// Writes the corpus in the split form to the provided output stream factory.
+    ///
+    /// The records in each file are sorted by their export name and formatted in the same
+    /// tab-separated way as produced by modpost.
+    pub fn write_split_buffer<W: Write, WG: WriteGenerator<W>>(
+        &self,
+        mut dir_writer: WG,
+    ) -> Result<(), Error> {
+        let err_desc = "Failed to write a split record";
+
+        // Group the exports by their module.
+        let mut modules = HashMap::<&str, Vec<(&String, &ExportInfo)>>::new();
+        for (name, info) in &self.exports {
+            modules.entry(&info.module).or_default().push((name, info));
+        }
+
+        // Determine the output files, making sure that all of them stay within the target
+        // directory before anything gets written.
+        let mut sorted_modules = Vec::new();
+        for (module, exports) in modules {
+            let sub_path = PathBuf::from(format!("{}.symvers", module));
+            if !sub_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(Error::new_parse(format!(
+                    "Module name '{}' is not a valid relative path",
+                    module
+                )));
+            }
+            sorted_modules.push((sub_path, exports));
+        }
+        sorted_modules.sort_by(|(sub_path, _), (other_sub_path, _)| sub_path.cmp(other_sub_path));
+
+        for (sub_path, mut exports) in sorted_modules {
+            exports.sort_by_key(|&(name, _)| name);
+
+            let mut writer = dir_writer.create(&sub_path)?;
+            for (name, info) in exports {
+                write_export(&mut writer, name, info).map_io_err(err_desc)?;
+            }
+            writer.flush().map_io_err(err_desc)?;
+            dir_writer.close(writer);
+        }
 
         Ok(())
     }
@@ -158,91 +796,223 @@ impl SymversCorpus {
     /// Compares the symbols in this corpus with another one.
     ///
     /// Writes reports about any found changes to the specified files, formatted as requested.
-    /// Returns `Ok` containing a `bool` indicating whether the corpuses are the same, or
+    /// Returns `Ok` containing a [`CompareStatus`] indicating whether the corpuses are the same,
+    /// differ only in tolerated changes, or differ in a breaking way. Returns
     /// <code>Err([Error])</code> on error.
     pub fn compare_with<P: AsRef<Path>>(
         &self,
         other_symvers: &SymversCorpus,
         maybe_filter: Option<&Filter>,
         maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        maybe_explain: Option<(&SymtypesCorpus, &SymtypesCorpus)>,
         writers_conf: &[(CompareFormat, P)],
-    ) -> Result<bool, Error> {
+    ) -> Result<CompareStatus, Error> {
         // Materialize all writers.
         let mut writers = Vec::new();
         for (format, path) in writers_conf {
-            writers.push((*format, Writer::new_file(path)?));
+            let mut writer = Writer::new_file(path)?;
+            if *format == CompareFormat::Pretty || *format == CompareFormat::Short {
+                if let Some(header) = &self.maybe_output_header {
+                    writer
+                        .write_all(header.as_bytes())
+                        .map_io_err("Failed to write a comparison result")?;
+                }
+                writer = writer.with_color(self.color);
+            }
+            writers.push((*format, writer));
         }
 
-        self.compare_with_buffer(other_symvers, maybe_filter, maybe_rules, &mut writers[..])
+        self.compare_with_buffer(
+            other_symvers,
+            maybe_filter,
+            maybe_rules,
+            maybe_suppressions,
+            maybe_explain,
+            &mut writers[..],
+        )
     }
 
     /// Compares the symbols in this corpus with another one.
     ///
     /// Writes reports about any found changes to the provided output streams, formatted as
-    /// requested. Returns `Ok` containing a `bool` indicating whether the corpuses are the same, or
-    /// <code>Err([Error])</code> on error.
+    /// requested. If `maybe_explain` provides symtypes data matching the two symvers corpuses, each
+    /// CRC change in the pretty output is followed by a description of the type changes that caused
+    /// it. Changes acknowledged by `maybe_suppressions` are not reported at all. Returns `Ok`
+    /// containing a [`CompareStatus`] indicating whether the corpuses are the same, differ only in
+    /// tolerated changes, or differ in a breaking way. Returns <code>Err([Error])</code> on error.
     pub fn compare_with_buffer<W: Write>(
         &self,
         other_symvers: &SymversCorpus,
         maybe_filter: Option<&Filter>,
         maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        maybe_explain: Option<(&SymtypesCorpus, &SymtypesCorpus)>,
         writers: &mut [(CompareFormat, W)],
-    ) -> Result<bool, Error> {
-        #[derive(Clone, Copy, Eq, PartialEq)]
-        enum ChangeStatus {
-            Breaking,
-            ImplicitlyTolerated,
-            RulesTolerated,
-        }
-
-        // A helper function to handle common logic related to reporting a change. It determines if
-        // the change should be tolerated and updates the `output_symbols` map.
-        fn process_change<'a>(
-            maybe_rules: Option<&Rules>,
-            name: &'a str,
-            info: &ExportInfo,
-            modified: bool,
-            always_tolerated: bool,
-            output_symbols: &mut HashMap<&'a str, bool>,
-        ) -> ChangeStatus {
-            if let Some(rules) = maybe_rules
-                && rules.is_tolerated(name, &info.module, info.namespace.as_deref())
-            {
-                return ChangeStatus::RulesTolerated;
-            }
-            if always_tolerated {
-                return ChangeStatus::ImplicitlyTolerated;
-            }
-            output_symbols.insert(name, modified);
-            ChangeStatus::Breaking
+    ) -> Result<CompareStatus, Error> {
+        // The numbers of changed exports in a single module.
+        #[derive(Default)]
+        struct ModuleStats {
+            added: usize,
+            removed: usize,
+            modified: usize,
+            tolerated: usize,
         }
 
         // A helper function to obtain the appropriate tolerated suffix string.
-        fn tolerated_suffix(tolerated: ChangeStatus) -> &'static str {
+        fn tolerated_suffix(tolerated: ChangeStatus) -> String {
             match tolerated {
-                ChangeStatus::Breaking => "",
-                ChangeStatus::ImplicitlyTolerated => " (implicitly tolerated)",
-                ChangeStatus::RulesTolerated => " (tolerated by rules)",
+                ChangeStatus::Breaking => String::new(),
+                ChangeStatus::ProtectedRemoved => " (protected symbol removed)".to_string(),
+                ChangeStatus::ImplicitlyTolerated => " (implicitly tolerated)".to_string(),
+                ChangeStatus::RulesTolerated(rule) => format!(" ({})", rule.describe_toleration()),
             }
         }
 
         // A helper function to determine whether a specific change needs to be pretty-printed.
+        // Changes tolerated by a rule are omitted from the short output, unless the rule requests
+        // them to be reported.
         fn needs_pretty_print(format: CompareFormat, tolerated: ChangeStatus) -> bool {
-            format == CompareFormat::Pretty
-                || (format == CompareFormat::Short && tolerated != ChangeStatus::RulesTolerated)
+            match format {
+                CompareFormat::Pretty | CompareFormat::Html => true,
+                CompareFormat::Short => match tolerated {
+                    ChangeStatus::RulesTolerated(rule) => rule.verdict != Verdict::Pass,
+                    _ => true,
+                },
+                _ => false,
+            }
+        }
+
+        // A helper function to describe the type changes that caused a CRC change of the given
+        // export, returning the description as individual lines.
+        fn explain_crc_change(
+            symtypes: &SymtypesCorpus,
+            other_symtypes: &SymtypesCorpus,
+            name: &str,
+        ) -> Result<Vec<String>, Error> {
+            let mut explanation = Vec::new();
+            if !symtypes.explain_export_buffer(other_symtypes, name, &mut explanation)? {
+                return Ok(vec![
+                    "no symtypes data is available for the export".to_string(),
+                ]);
+            }
+            if explanation.is_empty() {
+                return Ok(vec![
+                    "no type changes are found in the symtypes data".to_string(),
+                ]);
+            }
+            Ok(String::from_utf8_lossy(&explanation)
+                .lines()
+                .map(String::from)
+                .collect())
+        }
+
+        // A helper function to write a single change, optionally followed by its explanation, in
+        // the given human-readable format.
+        fn write_change<W: Write>(
+            format: CompareFormat,
+            message: &str,
+            maybe_explanation: Option<&Vec<String>>,
+            mut writer: W,
+        ) -> Result<(), Error> {
+            let err_desc = "Failed to write a comparison result";
+
+            if format == CompareFormat::Html {
+                match maybe_explanation {
+                    Some(explanation) => {
+                        writeln!(
+                            writer,
+                            "<li><details><summary>{}</summary>",
+                            escape_html(message)
+                        )
+                        .map_io_err(err_desc)?;
+                        write_html_diff(explanation, writer.by_ref())?;
+                        writeln!(writer, "</details></li>").map_io_err(err_desc)
+                    }
+                    None => {
+                        writeln!(writer, "<li>{}</li>", escape_html(message)).map_io_err(err_desc)
+                    }
+                }
+            } else {
+                writeln!(writer, "{}", message).map_io_err(err_desc)?;
+                if format == CompareFormat::Pretty
+                    && let Some(explanation) = maybe_explanation
+                {
+                    for line in explanation {
+                        writeln!(writer, "  {}", line).map_io_err(err_desc)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        // A helper function to write a breaking change in the format of the legacy kabi.pl script.
+        fn write_kabi_pl_change<W: Write>(
+            name: &str,
+            info: &ExportInfo,
+            message: &str,
+            mut writer: W,
+        ) -> Result<(), Error> {
+            writeln!(writer, "KABI: symbol {}({}) {}", name, info.module, message)
+                .map_io_err("Failed to write a comparison result")
+        }
+
+        // A helper function to write exports changed to GPL-only, grouped by their module. Each line
+        // is prefixed with the given indentation.
+        fn write_gpl_transitions<W: Write>(
+            gpl_transitions: &BTreeMap<&str, Vec<&str>>,
+            indent: &str,
+            demangle: bool,
+            mut writer: W,
+        ) -> Result<(), Error> {
+            let err_desc = "Failed to write a comparison result";
+
+            for (module, names) in gpl_transitions {
+                writeln!(writer, "{}Module '{}':", indent, module).map_io_err(err_desc)?;
+                for name in names {
+                    writeln!(writer, "{} {}", indent, display_name(name, demangle))
+                        .map_io_err(err_desc)?;
+                }
+            }
+            Ok(())
         }
 
         let err_desc = "Failed to write a comparison result";
 
+        // Start HTML reports.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Html {
+                write_html_header("Comparison of symvers corpuses", writer.by_ref())?;
+                writeln!(writer, "<ul>").map_io_err(err_desc)?;
+            }
+        }
+
         // Track all changed symbols, mapping a symbol name to a boolean. The flag indicates whether
         // the symbol was modified (true), or was added/removed (false).
         let mut output_symbols = HashMap::<&str, bool>::new();
 
+        // Track all symbols with any change, including tolerated ones.
+        let mut changed_symbols = HashSet::<&str>::new();
+
+        // Count symbols that are in both symvers and have no change.
+        let mut unchanged_count = 0;
+
+        // Track whether any protected export has been removed.
+        let mut protected_removed = false;
+
+        // Track exports that changed from EXPORT_SYMBOL to EXPORT_SYMBOL_GPL, grouped by their
+        // module in the second corpus.
+        let mut gpl_transitions = BTreeMap::<&str, Vec<&str>>::new();
+
         // Record the number of changes tolerated by the explicit rules.
         let mut rules_tolerated_additions = 0;
         let mut rules_tolerated_removals = 0;
         let mut rules_tolerated_modifications = 0;
 
+        // Collect the statistics of changed exports for each module. Added exports are counted in
+        // their new module, all others in their original module.
+        let mut module_stats = BTreeMap::<&str, ModuleStats>::new();
+
         let mut names = self
             .exports
             .keys()
@@ -263,51 +1033,72 @@ impl SymversCorpus {
         // rule patterns. An exception is added symbols, which lack a record in the original
         // symvers. These are always implicitly tolerated but nonetheless get checked against the
         // rules.
-        for (names_a, exports_a, exports_b, change, always_tolerated, rules_tolerated_count) in [
+        for (names_a, exports_a, exports_b, kind, rules_tolerated_count) in [
             (
                 &other_names,
                 &other_symvers.exports,
                 &self.exports,
-                "added",
-                true,
+                ChangeKind::Added,
                 &mut rules_tolerated_additions,
             ),
             (
                 &names,
                 &self.exports,
                 &other_symvers.exports,
-                "removed",
-                false,
+                ChangeKind::Removed,
                 &mut rules_tolerated_removals,
             ),
         ] {
             for &name in names_a {
-                if !exports_b.contains_key(name) {
-                    let info = exports_a.get(name).unwrap();
-                    let tolerated = process_change(
-                        maybe_rules,
-                        name,
-                        info,
-                        false,
-                        always_tolerated,
-                        &mut output_symbols,
-                    );
+                if exports_b.contains_key(name) {
+                    continue;
+                }
+                let info = exports_a.get(name).unwrap();
+                let (maybe_info, maybe_other_info) = match kind {
+                    ChangeKind::Added => (None, Some(info)),
+                    _ => (Some(info), None),
+                };
+                for (_, tolerated) in classify_export_changes(
+                    name,
+                    maybe_info,
+                    maybe_other_info,
+                    maybe_rules,
+                    self.maybe_protected.as_ref(),
+                    maybe_suppressions,
+                ) {
+                    changed_symbols.insert(name);
+                    if tolerated.is_breaking() {
+                        output_symbols.insert(name, false);
+                    }
                     for &mut (format, ref mut writer) in &mut *writers {
                         if needs_pretty_print(format, tolerated) {
-                            writeln!(
-                                writer,
+                            let message = format!(
                                 "Export '{}' has been {}{}",
-                                name,
-                                change,
+                                display_name(name, self.demangle),
+                                kind,
                                 tolerated_suffix(tolerated)
-                            )
-                            .map_io_err(err_desc)?;
+                            );
+                            write_change(format, &message, None, writer.by_ref())?;
+                        } else if format == CompareFormat::KabiPl && tolerated.is_breaking() {
+                            // Only removals can be breaking here, additions are always tolerated.
+                            write_kabi_pl_change(name, info, "lost", writer.by_ref())?;
                         }
                     }
 
-                    if tolerated == ChangeStatus::RulesTolerated {
+                    if matches!(tolerated, ChangeStatus::RulesTolerated(_)) {
                         *rules_tolerated_count += 1;
                     }
+
+                    let stats = module_stats.entry(&info.module).or_default();
+                    match kind {
+                        ChangeKind::Added => stats.added += 1,
+                        _ => stats.removed += 1,
+                    }
+                    if tolerated == ChangeStatus::ProtectedRemoved {
+                        protected_removed = true;
+                    } else if tolerated != ChangeStatus::Breaking {
+                        stats.tolerated += 1;
+                    }
                 }
             }
         }
@@ -319,57 +1110,142 @@ impl SymversCorpus {
                 let mut modified = false;
                 let mut rules_tolerated = true;
 
-                if info.crc != other_info.crc {
-                    let tolerated =
-                        process_change(maybe_rules, name, info, true, false, &mut output_symbols);
-                    for &mut (format, ref mut writer) in &mut *writers {
-                        if needs_pretty_print(format, tolerated) {
-                            writeln!(
-                                writer,
+                for (kind, tolerated) in classify_export_changes(
+                    name,
+                    Some(info),
+                    Some(other_info),
+                    maybe_rules,
+                    self.maybe_protected.as_ref(),
+                    maybe_suppressions,
+                ) {
+                    changed_symbols.insert(name);
+                    if tolerated.is_breaking() {
+                        output_symbols.insert(name, true);
+                    }
+
+                    // Describe the change in the human-readable formats and, if it is not a module
+                    // move, in the format of the kabi.pl script.
+                    let (message, maybe_kabi_pl_message) = match kind {
+                        ChangeKind::Crc => (
+                            format!(
                                 "Export '{}' changed CRC from '{:#010x}' to '{:#010x}'{}",
-                                name,
+                                display_name(name, self.demangle),
                                 info.crc,
                                 other_info.crc,
                                 tolerated_suffix(tolerated)
-                            )
-                            .map_io_err(err_desc)?;
-                        }
-                    }
-
-                    modified = true;
-                    rules_tolerated &= tolerated == ChangeStatus::RulesTolerated;
-                }
-
-                if info.is_gpl_only != other_info.is_gpl_only {
-                    let tolerated = process_change(
-                        maybe_rules,
-                        name,
-                        info,
-                        true,
-                        info.is_gpl_only && !other_info.is_gpl_only,
-                        &mut output_symbols,
-                    );
-                    for &mut (format, ref mut writer) in &mut *writers {
-                        if needs_pretty_print(format, tolerated) {
-                            writeln!(
-                                writer,
+                            ),
+                            Some(format!(
+                                "changed crc from {:#010x} to {:#010x}",
+                                info.crc, other_info.crc
+                            )),
+                        ),
+                        ChangeKind::Type => (
+                            format!(
                                 "Export '{}' changed type from '{}' to '{}'{}",
-                                name,
+                                display_name(name, self.demangle),
                                 info.type_as_str(),
                                 other_info.type_as_str(),
                                 tolerated_suffix(tolerated)
-                            )
-                            .map_io_err(err_desc)?;
+                            ),
+                            Some(format!(
+                                "changed type from {} to {}",
+                                info.type_as_str(),
+                                other_info.type_as_str()
+                            )),
+                        ),
+                        ChangeKind::Module => (
+                            format!(
+                                "Export '{}' moved from '{}' to '{}'{}",
+                                display_name(name, self.demangle),
+                                info.module,
+                                other_info.module,
+                                tolerated_suffix(tolerated)
+                            ),
+                            None,
+                        ),
+                        _ => (
+                            format!(
+                                "Export '{}' changed namespace from '{}' to '{}'{}",
+                                display_name(name, self.demangle),
+                                info.namespace_as_str(),
+                                other_info.namespace_as_str(),
+                                tolerated_suffix(tolerated)
+                            ),
+                            Some(format!(
+                                "changed namespace from {} to {}",
+                                info.namespace_as_str(),
+                                other_info.namespace_as_str()
+                            )),
+                        ),
+                    };
+
+                    // Explain CRC changes, if requested and any human-readable format needs it.
+                    let maybe_explanation = match maybe_explain {
+                        Some((symtypes, other_symtypes))
+                            if kind == ChangeKind::Crc
+                                && writers.iter().any(|(format, _)| {
+                                    *format == CompareFormat::Pretty
+                                        || *format == CompareFormat::Html
+                                }) =>
+                        {
+                            Some(explain_crc_change(symtypes, other_symtypes, name)?)
+                        }
+                        _ => None,
+                    };
+
+                    for &mut (format, ref mut writer) in &mut *writers {
+                        if needs_pretty_print(format, tolerated) {
+                            write_change(
+                                format,
+                                &message,
+                                maybe_explanation.as_ref(),
+                                writer.by_ref(),
+                            )?;
+                        } else if format == CompareFormat::KabiPl
+                            && tolerated == ChangeStatus::Breaking
+                            && let Some(kabi_pl_message) = &maybe_kabi_pl_message
+                        {
+                            write_kabi_pl_change(name, info, kabi_pl_message, writer.by_ref())?;
                         }
                     }
 
+                    if kind == ChangeKind::Type && other_info.is_gpl_only {
+                        gpl_transitions
+                            .entry(&other_info.module)
+                            .or_default()
+                            .push(name);
+                    }
+
                     modified = true;
-                    rules_tolerated &= tolerated == ChangeStatus::RulesTolerated;
+                    rules_tolerated &= matches!(tolerated, ChangeStatus::RulesTolerated(_));
                 }
 
                 if modified && rules_tolerated {
                     rules_tolerated_modifications += 1;
                 }
+                if modified {
+                    let stats = module_stats.entry(&info.module).or_default();
+                    stats.modified += 1;
+                    if !output_symbols.contains_key(name.as_str()) {
+                        stats.tolerated += 1;
+                    }
+                } else {
+                    unchanged_count += 1;
+                }
+            }
+        }
+
+        // Format the summary of GPL-only transitions.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Pretty && !gpl_transitions.is_empty() {
+                writeln!(
+                    writer,
+                    "Exports changed from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL':"
+                )
+                .map_io_err(err_desc)?;
+                write_gpl_transitions(&gpl_transitions, " ", self.demangle, writer.by_ref())?;
+            } else if format == CompareFormat::GplReport {
+                write_gpl_transitions(&gpl_transitions, "", self.demangle, writer.by_ref())?;
             }
         }
 
@@ -387,6 +1263,32 @@ impl SymversCorpus {
             }
         }
 
+        // Format the per-module statistics.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::ModuleStats {
+                for (module, stats) in &module_stats {
+                    writeln!(
+                        writer,
+                        "Module '{}': {} added, {} removed, {} modified, {} tolerated",
+                        module, stats.added, stats.removed, stats.modified, stats.tolerated
+                    )
+                    .map_io_err(err_desc)?;
+                }
+            }
+        }
+
+        // Format the one-line summary.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Summary {
+                write_compare_summary(
+                    output_symbols.len(),
+                    changed_symbols.len() - output_symbols.len(),
+                    unchanged_count,
+                    writer.by_ref(),
+                )?;
+            }
+        }
+
         // Format symbol lists.
         let mut sorted_output_symbols = output_symbols
             .iter()
@@ -403,11 +1305,355 @@ impl SymversCorpus {
             }
         }
 
+        // Finish HTML reports.
+        for &mut (format, ref mut writer) in &mut *writers {
+            if format == CompareFormat::Html {
+                writeln!(writer, "</ul>").map_io_err(err_desc)?;
+                if changed_symbols.is_empty() {
+                    writeln!(writer, "<p>No differences found.</p>").map_io_err(err_desc)?;
+                }
+                write_html_footer(writer.by_ref())?;
+            }
+        }
+
         for (_, writer) in &mut *writers {
             writer.flush().map_io_err(err_desc)?;
         }
 
-        Ok(output_symbols.is_empty())
+        Ok(if protected_removed {
+            CompareStatus::ProtectedRemoved
+        } else if !output_symbols.is_empty() {
+            CompareStatus::Breaking
+        } else if !changed_symbols.is_empty() {
+            CompareStatus::Tolerated
+        } else {
+            CompareStatus::Same
+        })
+    }
+
+    /// Compares the symbols in this corpus, as a baseline, with several other ones.
+    ///
+    /// Writes a matrix-style report to the specified file. See
+    /// [`SymversCorpus::compare_many_with_buffer()`] for the details. Returns `Ok` containing the
+    /// most severe [`CompareStatus`] across all comparisons. Returns <code>Err([Error])</code> on
+    /// error.
+    pub fn compare_many_with<P: AsRef<Path>>(
+        &self,
+        others: &[(&str, &SymversCorpus)],
+        maybe_filter: Option<&Filter>,
+        maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        path: P,
+    ) -> Result<CompareStatus, Error> {
+        self.compare_many_with_buffer(
+            others,
+            maybe_filter,
+            maybe_rules,
+            maybe_suppressions,
+            Writer::new_file(path)?,
+        )
+    }
+
+    /// Compares the symbols in this corpus, as a baseline, with several other ones.
+    ///
+    /// Each other corpus is identified by a label, typically the path of its symvers file. Writes
+    /// a matrix-style report to the provided output stream. The report contains one row for each
+    /// export that changed in at least one of the other corpuses, and one column for each of these
+    /// corpuses. Each cell describes the change of the export as `breaking` or `tolerated`, or
+    /// contains `-` if the export is unchanged. Changes are classified the same way as by
+    /// [`SymversCorpus::compare_with_buffer()`], including the handling of `maybe_suppressions`.
+    /// Returns `Ok` containing the most severe
+    /// [`CompareStatus`] across all comparisons. Returns <code>Err([Error])</code> on error.
+    pub fn compare_many_with_buffer<W: Write>(
+        &self,
+        others: &[(&str, &SymversCorpus)],
+        maybe_filter: Option<&Filter>,
+        maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        mut writer: W,
+    ) -> Result<CompareStatus, Error> {
+        let err_desc = "Failed to write a comparison result";
+
+        // Determine the status of each changed export in every other corpus.
+        let mut rows = HashMap::<&str, Vec<CompareStatus>>::new();
+        for (i, (_, other_symvers)) in others.iter().enumerate() {
+            let names = self
+                .exports
+                .keys()
+                .chain(
+                    other_symvers
+                        .exports
+                        .keys()
+                        .filter(|&name| !self.exports.contains_key(name)),
+                )
+                .filter(|&name| matches_filter(maybe_filter, name));
+            for name in names {
+                let status = export_status(
+                    name,
+                    self.exports.get(name),
+                    other_symvers.exports.get(name),
+                    maybe_rules,
+                    self.maybe_protected.as_ref(),
+                    maybe_suppressions,
+                );
+                if status != CompareStatus::Same {
+                    rows.entry(name)
+                        .or_insert_with(|| vec![CompareStatus::Same; others.len()])[i] = status;
+                }
+            }
+        }
+
+        let mut rows = rows.into_iter().collect::<Vec<_>>();
+        rows.sort_by_key(|&(name, _)| name);
+
+        // Format the matrix, aligning all columns except the last one.
+        if !rows.is_empty() {
+            let display_names = rows
+                .iter()
+                .map(|&(name, _)| display_name(name, self.demangle))
+                .collect::<Vec<_>>();
+            let mut widths = vec![
+                display_names
+                    .iter()
+                    .map(|name| name.chars().count())
+                    .chain(["Export".len()])
+                    .max()
+                    .unwrap(),
+            ];
+            widths.extend(
+                others
+                    .iter()
+                    .map(|(label, _)| label.len().max("tolerated".len())),
+            );
+
+            let write_row = |writer: &mut W, cells: &[&str]| -> Result<(), Error> {
+                let mut line = String::new();
+                for (i, cell) in cells.iter().enumerate() {
+                    if i == cells.len() - 1 {
+                        line.push_str(cell);
+                    } else {
+                        line.push_str(&format!("{:width$}  ", cell, width = widths[i]));
+                    }
+                }
+                writeln!(writer, "{}", line).map_io_err(err_desc)
+            };
+
+            let header = ["Export"]
+                .into_iter()
+                .chain(others.iter().map(|&(label, _)| label))
+                .collect::<Vec<_>>();
+            write_row(&mut writer, &header)?;
+            for (name, (_, statuses)) in display_names.iter().zip(&rows) {
+                let cells = [name.as_ref()]
+                    .into_iter()
+                    .chain(statuses.iter().map(|status| match status {
+                        CompareStatus::Same => "-",
+                        CompareStatus::Tolerated => "tolerated",
+                        CompareStatus::Breaking => "breaking",
+                        CompareStatus::ProtectedRemoved => "protected",
+                    }))
+                    .collect::<Vec<_>>();
+                write_row(&mut writer, &cells)?;
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+
+        Ok(rows
+            .iter()
+            .flat_map(|(_, statuses)| statuses.iter().copied())
+            .max()
+            .unwrap_or(CompareStatus::Same))
+    }
+
+    /// Compares several named baselines, each with its own severity rules, with the symbols in
+    /// this corpus.
+    ///
+    /// Writes a combined report to the specified file. See
+    /// [`SymversCorpus::compare_baselines_with_buffer()`] for the details. Returns `Ok` containing
+    /// the most severe [`CompareStatus`] across all comparisons. Returns <code>Err([Error])</code>
+    /// on error.
+    pub fn compare_baselines_with<P: AsRef<Path>>(
+        &self,
+        baselines: &[(&str, &SymversCorpus, Option<&Rules>)],
+        maybe_filter: Option<&Filter>,
+        maybe_suppressions: Option<&Suppressions>,
+        path: P,
+    ) -> Result<CompareStatus, Error> {
+        let mut writer = Writer::new_file(path)?;
+        if let Some(header) = &self.maybe_output_header {
+            writer
+                .write_all(header.as_bytes())
+                .map_io_err("Failed to write a comparison result")?;
+        }
+
+        self.compare_baselines_with_buffer(baselines, maybe_filter, maybe_suppressions, writer)
+    }
+
+    /// Compares several named baselines, each with its own severity rules, with the symbols in
+    /// this corpus.
+    ///
+    /// Each baseline is compared with this corpus the same way as by
+    /// [`SymversCorpus::compare_with_buffer()`] called on the baseline, which means that the
+    /// settings of the baseline, such as its protected exports, apply. Writes a combined report to
+    /// the provided output stream, in the pretty format with each line prefixed by the name of the
+    /// baseline whose comparison produced it. Returns `Ok` containing the most severe
+    /// [`CompareStatus`] across all comparisons. Returns <code>Err([Error])</code> on error.
+    pub fn compare_baselines_with_buffer<W: Write>(
+        &self,
+        baselines: &[(&str, &SymversCorpus, Option<&Rules>)],
+        maybe_filter: Option<&Filter>,
+        maybe_suppressions: Option<&Suppressions>,
+        mut writer: W,
+    ) -> Result<CompareStatus, Error> {
+        let err_desc = "Failed to write a comparison result";
+
+        let mut status = CompareStatus::Same;
+        for &(name, baseline, maybe_rules) in baselines {
+            let mut report = Vec::new();
+            status = status.max(baseline.compare_with_buffer(
+                self,
+                maybe_filter,
+                maybe_rules,
+                maybe_suppressions,
+                None,
+                &mut [(CompareFormat::Pretty, &mut report)],
+            )?);
+            for line in String::from_utf8_lossy(&report).lines() {
+                writeln!(writer, "Baseline '{}': {}", name, line).map_io_err(err_desc)?;
+            }
+        }
+
+        writer.flush().map_io_err(err_desc)?;
+        Ok(status)
+    }
+
+    /// Determines the status of a comparison of this corpus with another one, without reporting
+    /// any changes.
+    ///
+    /// Changes are classified the same way as by [`SymversCorpus::compare_with_buffer()`]. The
+    /// comparison stops as soon as a change of the `stop_at` severity or worse is found, in which
+    /// case its status is returned. Otherwise, returns the most severe status of all changes.
+    pub fn compare_status(
+        &self,
+        other_symvers: &SymversCorpus,
+        maybe_filter: Option<&Filter>,
+        maybe_rules: Option<&Rules>,
+        maybe_suppressions: Option<&Suppressions>,
+        stop_at: CompareStatus,
+    ) -> CompareStatus {
+        let names = self
+            .exports
+            .keys()
+            .chain(
+                other_symvers
+                    .exports
+                    .keys()
+                    .filter(|&name| !self.exports.contains_key(name)),
+            )
+            .filter(|&name| matches_filter(maybe_filter, name));
+
+        let mut status = CompareStatus::Same;
+        for name in names {
+            status = status.max(export_status(
+                name,
+                self.exports.get(name),
+                other_symvers.exports.get(name),
+                maybe_rules,
+                self.maybe_protected.as_ref(),
+                maybe_suppressions,
+            ));
+            if status >= stop_at {
+                break;
+            }
+        }
+        status
+    }
+
+    /// Suggests severity rules that tolerate all breaking changes between this corpus and another
+    /// one.
+    ///
+    /// The rules are derived from the records in this corpus. A namespace rule is suggested when
+    /// all exports in the namespace have a breaking change, and similarly a module rule when all
+    /// exports of the module have a breaking change. Any remaining changes are covered by symbol
+    /// rules. Namespace and module rules are suggested only if they cover at least two exports.
+    pub fn suggest_rules(
+        &self,
+        other_symvers: &SymversCorpus,
+        maybe_filter: Option<&Filter>,
+    ) -> Rules {
+        // Collect all exports with a breaking change.
+        let mut changed = self
+            .exports
+            .iter()
+            .filter(|&(name, info)| {
+                matches_filter(maybe_filter, name)
+                    && classify_export_changes(
+                        name,
+                        Some(info),
+                        other_symvers.exports.get(name),
+                        None,
+                        None,
+                        None,
+                    )
+                    .iter()
+                    .any(|(_, status)| status.is_breaking())
+            })
+            .collect::<Vec<_>>();
+        changed.sort_by_key(|&(name, _)| name);
+
+        // A helper function to find groups of exports that changed completely.
+        fn find_complete_groups<'a, F: Fn(&ExportInfo) -> Option<&str>>(
+            exports: &'a Exports,
+            changed: &[(&String, &'a ExportInfo)],
+            get_group: F,
+        ) -> Vec<String> {
+            let mut changed_counts = HashMap::<&str, usize>::new();
+            for &(_, info) in changed {
+                if let Some(group) = get_group(info) {
+                    *changed_counts.entry(group).or_default() += 1;
+                }
+            }
+            let mut total_counts = HashMap::<&str, usize>::new();
+            for info in exports.values() {
+                if let Some(group) = get_group(info) {
+                    *total_counts.entry(group).or_default() += 1;
+                }
+            }
+            let mut groups = changed_counts
+                .into_iter()
+                .filter(|&(group, count)| count >= 2 && total_counts[group] == count)
+                .map(|(group, _)| group.to_string())
+                .collect::<Vec<_>>();
+            groups.sort();
+            groups
+        }
+
+        let mut rules = Rules::new();
+
+        let namespaces =
+            find_complete_groups(&self.exports, &changed, |info| info.namespace.as_deref());
+        changed.retain(|&(_, info)| {
+            !info
+                .namespace
+                .as_ref()
+                .is_some_and(|namespace| namespaces.contains(namespace))
+        });
+        for namespace in namespaces {
+            rules.push_generated(RuleType::Namespace, namespace, Verdict::Pass);
+        }
+
+        let modules = find_complete_groups(&self.exports, &changed, |info| Some(&info.module));
+        changed.retain(|&(_, info)| !modules.contains(&info.module));
+        for module in modules {
+            rules.push_generated(RuleType::Module, module, Verdict::Pass);
+        }
+
+        for (name, _) in changed {
+            rules.push_generated(RuleType::Symbol, name, Verdict::Pass);
+        }
+
+        rules
     }
 
     /// Iterates over all symbols in the corpus and adds indexes of matched rules to `used_rules`.
@@ -416,23 +1662,256 @@ impl SymversCorpus {
             rules.mark_used_rule(name, &info.module, info.namespace.as_deref(), used_rules)
         }
     }
+
+    /// Checks which severity rule matches each export in the corpus and writes a report to the
+    /// specified file.
+    ///
+    /// See [`SymversCorpus::check_rules_buffer()`] for the details.
+    pub fn check_rules<P: AsRef<Path>>(&self, rules: &Rules, path: P) -> Result<(), Error> {
+        self.check_rules_buffer(rules, Writer::new_file(path)?)
+    }
+
+    /// Checks which severity rule matches each export in the corpus and writes a report to the
+    /// provided output stream.
+    ///
+    /// The report lists all exports, sorted by their name, together with the first rule that
+    /// matches them, followed by all rules that are not matched by any export and a summary.
+    pub fn check_rules_buffer<W: Write>(&self, rules: &Rules, mut writer: W) -> Result<(), Error> {
+        let err_desc = "Failed to write a rules check result";
+
+        let mut names = self.exports.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut used_rules = UsedRules::new();
+        let mut num_unmatched = 0;
+        for name in names {
+            let info = self.exports.get(name).unwrap();
+            match rules.find_rule(name, &info.module, info.namespace.as_deref()) {
+                Some(rule) => {
+                    writeln!(writer, "Export '{}' matches rule {}", name, rule)
+                        .map_io_err(err_desc)?;
+                    used_rules.insert(rule.index);
+                }
+                None => {
+                    writeln!(writer, "Export '{}' matches no rule", name).map_io_err(err_desc)?;
+                    num_unmatched += 1;
+                }
+            }
+        }
+
+        rules.write_unused_rules_buffer(&used_rules, writer.by_ref())?;
+
+        writeln!(
+            writer,
+            "Summary: '{}' exports matched, '{}' exports unmatched, '{}' of '{}' rules unused",
+            self.exports.len() - num_unmatched,
+            num_unmatched,
+            rules.len() - used_rules.len(),
+            rules.len()
+        )
+        .map_io_err(err_desc)?;
+
+        writer.flush().map_io_err(err_desc)
+    }
+}
+
+/// Returns whether two records of the same export differ in any of the compared properties.
+fn is_modification(info: &ExportInfo, other_info: &ExportInfo) -> bool {
+    info.crc != other_info.crc
+        || info.is_gpl_only != other_info.is_gpl_only
+        || info.module != other_info.module
+        || info.namespace != other_info.namespace
+}
+
+/// The status of a single change of an export, as determined by [`classify_export_changes()`].
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum ChangeStatus<'a> {
+    Breaking,
+    ProtectedRemoved,
+    ImplicitlyTolerated,
+    RulesTolerated(ToleratingRule<'a>),
+}
+
+impl ChangeStatus<'_> {
+    /// Returns whether the change is reported as breaking the kABI.
+    fn is_breaking(&self) -> bool {
+        matches!(self, Self::Breaking | Self::ProtectedRemoved)
+    }
 }
 
-/// Parses a single symvers record.
-fn parse_export(path: &Path, line_idx: usize, line: &str) -> Result<(String, ExportInfo), Error> {
-    let mut words = line.split_ascii_whitespace();
+/// Classifies all changes of a single export between two corpuses, given its record in each of
+/// them.
+///
+/// Returns the kind and status of each change that is not acknowledged by `maybe_suppressions`.
+/// Changes of protected exports are never tolerated, except for additions. Otherwise, a change is
+/// tolerated if it is backward compatible or if `maybe_rules` tolerate it. The rules are matched
+/// against the original record, or the new record for an added export.
+fn classify_export_changes<'r>(
+    name: &str,
+    maybe_info: Option<&ExportInfo>,
+    maybe_other_info: Option<&ExportInfo>,
+    maybe_rules: Option<&'r Rules>,
+    maybe_protected: Option<&Filter>,
+    maybe_suppressions: Option<&Suppressions>,
+) -> Vec<(ChangeKind, ChangeStatus<'r>)> {
+    // Collect all changes of the export, each with a flag indicating whether the change is
+    // backward compatible.
+    let (info, changes) = match (maybe_info, maybe_other_info) {
+        (Some(info), Some(other_info)) => {
+            let mut changes = Vec::new();
+            if info.crc != other_info.crc {
+                changes.push((ChangeKind::Crc, false));
+            }
+            if info.is_gpl_only != other_info.is_gpl_only {
+                changes.push((ChangeKind::Type, !other_info.is_gpl_only));
+            }
+            // Moving an export between modules is backward compatible, as the module dependencies
+            // are resolved by the export name.
+            if info.module != other_info.module {
+                changes.push((ChangeKind::Module, true));
+            }
+            // Moving an export out of a namespace is backward compatible, as modules can still
+            // reference it, only their namespace import becomes unnecessary.
+            if info.namespace != other_info.namespace {
+                changes.push((ChangeKind::Namespace, other_info.namespace.is_none()));
+            }
+            (info, changes)
+        }
+        (Some(info), None) => (info, vec![(ChangeKind::Removed, false)]),
+        (None, Some(other_info)) => (other_info, vec![(ChangeKind::Added, true)]),
+        (None, None) => return Vec::new(),
+    };
+
+    let protected = maybe_protected.is_some_and(|protected| protected.matches(name));
+    let maybe_rule = maybe_rules
+        .and_then(|rules| rules.is_tolerated(name, &info.module, info.namespace.as_deref()));
+    changes
+        .into_iter()
+        .filter(|&(kind, _)| {
+            let maybe_crcs = match (kind, maybe_other_info) {
+                (ChangeKind::Crc, Some(other_info)) => Some((info.crc, other_info.crc)),
+                _ => None,
+            };
+            !is_suppressed(maybe_suppressions, name, kind, maybe_crcs)
+        })
+        .map(|(kind, compatible)| {
+            let status = if protected && kind == ChangeKind::Removed {
+                ChangeStatus::ProtectedRemoved
+            } else if protected && kind != ChangeKind::Added {
+                ChangeStatus::Breaking
+            } else if let Some(rule) = maybe_rule {
+                ChangeStatus::RulesTolerated(rule)
+            } else if compatible {
+                ChangeStatus::ImplicitlyTolerated
+            } else {
+                ChangeStatus::Breaking
+            };
+            (kind, status)
+        })
+        .collect()
+}
+
+/// Returns the status of a single export between two corpuses, combining the statuses of its
+/// changes as determined by [`classify_export_changes()`].
+fn export_status(
+    name: &str,
+    maybe_info: Option<&ExportInfo>,
+    maybe_other_info: Option<&ExportInfo>,
+    maybe_rules: Option<&Rules>,
+    maybe_protected: Option<&Filter>,
+    maybe_suppressions: Option<&Suppressions>,
+) -> CompareStatus {
+    classify_export_changes(
+        name,
+        maybe_info,
+        maybe_other_info,
+        maybe_rules,
+        maybe_protected,
+        maybe_suppressions,
+    )
+    .into_iter()
+    .map(|(_, status)| match status {
+        ChangeStatus::Breaking => CompareStatus::Breaking,
+        ChangeStatus::ProtectedRemoved => CompareStatus::ProtectedRemoved,
+        ChangeStatus::ImplicitlyTolerated | ChangeStatus::RulesTolerated(_) => {
+            CompareStatus::Tolerated
+        }
+    })
+    .max()
+    .unwrap_or(CompareStatus::Same)
+}
+
+/// Writes a single symvers record to the provided output stream, in the tab-separated format
+/// produced by modpost.
+fn write_export<W: Write>(writer: &mut W, name: &str, info: &ExportInfo) -> io::Result<()> {
+    write!(
+        writer,
+        "{:#010x}\t{}\t{}\t{}\t{}",
+        info.crc,
+        name,
+        info.module,
+        info.type_as_str(),
+        info.namespace.as_deref().unwrap_or("")
+    )?;
+    for column in &info.extra_columns {
+        write!(writer, "\t{}", column)?;
+    }
+    writeln!(writer)
+}
+
+/// Formats a single symvers record as a space-separated string, suitable for use in messages.
+fn format_export(name: &str, info: &ExportInfo) -> String {
+    let mut res = format!(
+        "{:#010x} {} {} {}",
+        info.crc,
+        name,
+        info.module,
+        info.type_as_str()
+    );
+    if let Some(namespace) = &info.namespace {
+        res.push(' ');
+        res.push_str(namespace);
+    }
+    for column in &info.extra_columns {
+        res.push(' ');
+        res.push_str(column);
+    }
+    res
+}
+
+/// Parses a single symvers record in the given `format`, which must not be
+/// [`SymversFormat::Auto`].
+///
+/// In the `lenient` mode, any columns following the namespace are preserved in the returned
+/// [`ExportInfo`], instead of being rejected.
+fn parse_export(
+    path: &Path,
+    line_idx: usize,
+    line: &str,
+    format: SymversFormat,
+    lenient: bool,
+) -> Result<(String, ExportInfo), Error> {
+    let mut words: Box<dyn Iterator<Item = (usize, &str)>> = if lenient && line.contains('\t') {
+        Box::new(split_columns(line))
+    } else {
+        Box::new(split_words(line))
+    };
 
     // Parse the CRC value.
-    let crc = words.next().ok_or_else(|| {
-        Error::new_parse_format(
-            "The export does not specify a CRC",
-            path,
-            line_idx + 1,
-            line,
-        )
-    })?;
+    let (crc_offset, crc) = words
+        .next()
+        .filter(|(_, word)| !word.is_empty())
+        .ok_or_else(|| {
+            Error::new_parse_format_at(
+                "The export does not specify a CRC",
+                path,
+                line_idx + 1,
+                line,
+                line.len(),
+            )
+        })?;
     if !crc.starts_with("0x") && !crc.starts_with("0X") {
-        return Err(Error::new_parse_format(
+        return Err(Error::new_parse_format_at(
             &format!(
                 "Failed to parse the CRC value '{}': string does not start with 0x or 0X",
                 crc
@@ -440,51 +1919,55 @@ fn parse_export(path: &Path, line_idx: usize, line: &str) -> Result<(String, Exp
             path,
             line_idx + 1,
             line,
+            crc_offset,
         ));
     }
     let crc = u32::from_str_radix(&crc[2..], 16).map_err(|err| {
-        Error::new_parse_format(
+        Error::new_parse_format_at(
             &format!("Failed to parse the CRC value '{}': {}", crc, err),
             path,
             line_idx + 1,
             line,
+            crc_offset,
         )
     })?;
 
     // Parse the export name.
-    let name = words.next().ok_or_else(|| {
-        Error::new_parse_format(
-            "The export does not specify a name",
-            path,
-            line_idx + 1,
-            line,
-        )
-    })?;
+    let (_, name) = words
+        .next()
+        .filter(|(_, word)| !word.is_empty())
+        .ok_or_else(|| {
+            Error::new_parse_format_at(
+                "The export does not specify a name",
+                path,
+                line_idx + 1,
+                line,
+                line.len(),
+            )
+        })?;
 
-    // Parse the module name.
-    let module = words.next().ok_or_else(|| {
-        Error::new_parse_format(
-            "The export does not specify a module",
-            path,
-            line_idx + 1,
-            line,
-        )
-    })?;
+    // Parse the module name. Legacy records may omit it, in which case the export is assumed to
+    // be provided by vmlinux.
+    let module = match words.next().filter(|(_, word)| !word.is_empty()) {
+        Some((_, module)) => module,
+        None if format == SymversFormat::Legacy => "vmlinux",
+        None => {
+            return Err(Error::new_parse_format_at(
+                "The export does not specify a module",
+                path,
+                line_idx + 1,
+                line,
+                line.len(),
+            ));
+        }
+    };
 
-    // Parse the export type.
-    let export_type = words.next().ok_or_else(|| {
-        Error::new_parse_format(
-            "The export does not specify a type",
-            path,
-            line_idx + 1,
-            line,
-        )
-    })?;
-    let is_gpl_only = match export_type {
-        "EXPORT_SYMBOL" => false,
-        "EXPORT_SYMBOL_GPL" => true,
-        _ => {
-            return Err(Error::new_parse_format(
+    // Parse the export type. Legacy records may omit it as well.
+    let is_gpl_only = match words.next().filter(|(_, word)| !word.is_empty()) {
+        Some((_, "EXPORT_SYMBOL")) => false,
+        Some((_, "EXPORT_SYMBOL_GPL")) => true,
+        Some((type_offset, export_type)) => {
+            return Err(Error::new_parse_format_at(
                 &format!(
                     "Invalid export type '{}', must be either EXPORT_SYMBOL or EXPORT_SYMBOL_GPL",
                     export_type
@@ -492,25 +1975,185 @@ fn parse_export(path: &Path, line_idx: usize, line: &str) -> Result<(String, Exp
                 path,
                 line_idx + 1,
                 line,
+                type_offset,
+            ));
+        }
+        None if format == SymversFormat::Legacy => false,
+        None => {
+            return Err(Error::new_parse_format_at(
+                "The export does not specify a type",
+                path,
+                line_idx + 1,
+                line,
+                line.len(),
             ));
         }
     };
 
-    // Parse an optional namespace.
-    let namespace = words.next().map(String::from);
+    // Parse an optional namespace, which is never present in legacy records.
+    let namespace = if format == SymversFormat::Legacy {
+        None
+    } else {
+        words
+            .next()
+            .filter(|(_, namespace)| !namespace.is_empty())
+            .map(|(_, namespace)| namespace.to_string())
+    };
 
-    // Check that nothing else is left on the line.
-    if words.next().is_some() {
-        return Err(Error::new_parse_format(
-            "Unexpected string found at the end of the export record",
-            path,
-            line_idx + 1,
-            line,
-        ));
+    // Check that nothing else is left on the line, or collect the extra columns.
+    let mut extra_columns = Vec::new();
+    for (extra_offset, column) in words {
+        if !lenient {
+            return Err(Error::new_parse_format_at(
+                "Unexpected string found at the end of the export record",
+                path,
+                line_idx + 1,
+                line,
+                extra_offset,
+            ));
+        }
+        extra_columns.push(column.to_string());
     }
 
     Ok((
         name.to_string(),
-        ExportInfo::new(crc, module, is_gpl_only, namespace),
+        ExportInfo::new(crc, module, is_gpl_only, namespace).with_extra_columns(extra_columns),
     ))
 }
+
+/// Collects recursively all per-module `*.symvers` and `*.mod.c` files under the given root path
+/// and its subpath, skipping `vmlinux.symvers` and `Module.symvers` in the root.
+fn collect_build_files(
+    root: &Path,
+    sub_path: &Path,
+    symvers_files: &mut Vec<PathBuf>,
+    mod_c_files: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let path = root.join(sub_path);
+
+    let dir_iter = fs::read_dir(&path).map_err(|err| {
+        Error::new_io(
+            format!("Failed to read the directory '{}'", path.display()),
+            err,
+        )
+    })?;
+
+    for maybe_entry in dir_iter {
+        let entry = maybe_entry.map_err(|err| {
+            Error::new_io(
+                format!("Failed to read the directory '{}'", path.display()),
+                err,
+            )
+        })?;
+
+        let entry_path = entry.path();
+
+        let md = fs::symlink_metadata(&entry_path).map_err(|err| {
+            Error::new_io(
+                format!("Failed to query the path '{}'", entry_path.display()),
+                err,
+            )
+        })?;
+
+        if md.is_symlink() {
+            continue;
+        }
+
+        let entry_sub_path = sub_path.join(entry.file_name());
+
+        if md.is_dir() {
+            collect_build_files(root, &entry_sub_path, symvers_files, mod_c_files)?;
+            continue;
+        }
+
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.ends_with(".mod.c") {
+            mod_c_files.push(entry_sub_path);
+        } else if file_name.ends_with(".symvers")
+            && !(sub_path.as_os_str().is_empty()
+                && (file_name == "vmlinux.symvers" || file_name == "Module.symvers"))
+        {
+            symvers_files.push(entry_sub_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the `__versions` table in the given lines of a `*.mod.c` file.
+///
+/// Returns the line index, name and CRC of each symbol imported by the module. A file without the
+/// table, for instance, from a build without `CONFIG_MODVERSIONS`, yields no symbols.
+fn parse_mod_versions(path: &Path, lines: &[String]) -> Result<Vec<(usize, String, u32)>, Error> {
+    let mut versions = Vec::new();
+    let mut in_table = false;
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        let line = line.trim();
+
+        if !in_table {
+            in_table = line.contains("\"__versions\"") && line.ends_with('{');
+            continue;
+        }
+        if line == "};" {
+            break;
+        }
+
+        // Parse an entry in the form '{ 0x12345678, "name" },'.
+        let maybe_entry = line
+            .strip_prefix('{')
+            .and_then(|entry| entry.strip_suffix("},"))
+            .and_then(|entry| entry.split_once(','))
+            .and_then(|(crc, name)| {
+                let crc = crc.trim();
+                let crc = crc
+                    .strip_prefix("0x")
+                    .and_then(|crc| u32::from_str_radix(crc, 16).ok())?;
+                let name = name.trim().strip_prefix('"')?.strip_suffix('"')?;
+                Some((name, crc))
+            });
+        match maybe_entry {
+            Some((name, crc)) => versions.push((line_idx, name.to_string(), crc)),
+            None => {
+                return Err(Error::new_parse_format(
+                    "Invalid entry in the __versions table, must be in the form '{ <crc>, \"<name>\" },'",
+                    path,
+                    line_idx + 1,
+                    &lines[line_idx],
+                ));
+            }
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Detects the layout of the given symvers records from the first one.
+///
+/// A record with fewer than four columns can only be in the legacy form. Records with four
+/// columns are accepted by both layouts, and are parsed as modern ones.
+fn detect_format<S: AsRef<str>>(lines: &[S]) -> SymversFormat {
+    match lines.first() {
+        Some(line) if line.as_ref().split_ascii_whitespace().count() < 4 => SymversFormat::Legacy,
+        _ => SymversFormat::Modern,
+    }
+}
+
+/// Splits the given line into words separated by ASCII whitespace, returning each word together
+/// with its byte offset in the line.
+fn split_words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_ascii_whitespace()
+        .map(move |word| (word.as_ptr() as usize - line.as_ptr() as usize, word))
+}
+
+/// Splits the given tab-separated line into columns, returning each column together with its byte
+/// offset in the line.
+///
+/// Unlike [`split_words()`], empty columns are preserved, except that trailing empty columns are
+/// dropped.
+fn split_columns(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.trim_end_matches('\t')
+        .split('\t')
+        .map(move |column| (column.as_ptr() as usize - line.as_ptr() as usize, column))
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: GPL-2.0-or-later
 
 use super::*;
-use crate::text::Writer;
+use crate::text::{DirectoryWriter, Writer};
 use crate::{assert_inexact_parse_err, assert_ok, assert_ok_eq, assert_parse_err, bytes};
 
 #[test]
@@ -22,7 +22,8 @@ fn read_export_basic() {
             exports: HashMap::from([(
                 "foo".to_string(),
                 ExportInfo::new(0x12345678, "vmlinux", false, None::<&str>)
-            )])
+            )]),
+            ..Default::default()
         }
     );
 }
@@ -43,8 +44,9 @@ fn read_empty_record() {
         result,
         concat!(
             "The export does not specify a CRC\n",
-            " test.symvers:2\n",
-            " | ", //
+            " test.symvers:2:1\n",
+            " | \n",
+            " | ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -71,6 +73,87 @@ fn read_duplicate_symbol_record() {
     );
 }
 
+#[test]
+fn read_from_files() {
+    // Check that a corpus can be created from in-memory files and that a duplicate record in
+    // another file is rejected.
+    let result = SymversCorpus::from_files(HashMap::from([
+        (
+            PathBuf::from("b.symvers"),
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL\n".to_string(),
+        ),
+        (
+            PathBuf::from("a.symvers"),
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n".to_string(),
+        ),
+    ]));
+    assert_eq!(
+        result.unwrap(),
+        SymversCorpus {
+            exports: HashMap::from([
+                (
+                    "foo".to_string(),
+                    ExportInfo::new(0x12345678, "vmlinux", false, None::<&str>)
+                ),
+                (
+                    "bar".to_string(),
+                    ExportInfo::new(0x9abcdef0, "vmlinux", true, None::<&str>)
+                ),
+            ]),
+            ..Default::default()
+        }
+    );
+
+    let result = SymversCorpus::from_files(HashMap::from([
+        (
+            PathBuf::from("a.symvers"),
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n".to_string(),
+        ),
+        (
+            PathBuf::from("b.symvers"),
+            "0x12345678 foo vmlinux EXPORT_SYMBOL_GPL\n".to_string(),
+        ),
+    ]));
+    assert_parse_err!(
+        result.map(|_| ()),
+        concat!(
+            "Duplicate record 'foo'\n",
+            " b.symvers:1\n",
+            " | 0x12345678 foo vmlinux EXPORT_SYMBOL_GPL", //
+        ),
+    );
+}
+
+#[test]
+fn read_keep_going() {
+    // Check that all errors in a symvers file are reported together in the keep-going mode and
+    // that the corpus remains unchanged.
+    let mut symvers = SymversCorpus::new();
+    symvers.set_keep_going(true);
+    let result = symvers.load_buffer(
+        "test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0 bar vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 foo vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x12345678 baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Failed to parse the CRC value '0': string does not start with 0x or 0X\n",
+            " test.symvers:2:1\n",
+            " | 0 bar vmlinux EXPORT_SYMBOL\n",
+            " | ^\n",
+            "Duplicate record 'foo'\n",
+            " test.symvers:3\n",
+            " | 0x12345678 foo vmlinux EXPORT_SYMBOL_GPL", //
+        ),
+    );
+    assert_eq!(symvers, SymversCorpus::new());
+}
+
 #[test]
 fn read_invalid_crc() {
     // Check that a CRC value not starting with 0x/0X is rejected.
@@ -85,8 +168,9 @@ fn read_invalid_crc() {
         result,
         concat!(
             "Failed to parse the CRC value '0': string does not start with 0x or 0X\n",
-            " test.symvers:1\n",
-            " | 0 foo vmlinux EXPORT_SYMBOL", //
+            " test.symvers:1:1\n",
+            " | 0 foo vmlinux EXPORT_SYMBOL\n",
+            " | ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -106,8 +190,9 @@ fn read_invalid_crc2() {
         result,
         concat!(
             "Failed to parse the CRC value '0xabcdefgh': *\n",
-            " test.symvers:1\n",
-            " | 0xabcdefgh foo vmlinux EXPORT_SYMBOL", //
+            " test.symvers:1:1\n",
+            " | 0xabcdefgh foo vmlinux EXPORT_SYMBOL\n",
+            " | ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -127,8 +212,9 @@ fn read_no_name() {
         result,
         concat!(
             "The export does not specify a name\n",
-            " test.symvers:1\n",
-            " | 0x12345678", //
+            " test.symvers:1:11\n",
+            " | 0x12345678\n",
+            " |           ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -136,8 +222,9 @@ fn read_no_name() {
 
 #[test]
 fn read_no_module() {
-    // Check that records without a module are rejected.
+    // Check that records without a module are rejected in the modern format.
     let mut symvers = SymversCorpus::new();
+    symvers.set_format(SymversFormat::Modern);
     let result = symvers.load_buffer(
         "test.symvers",
         bytes!(
@@ -148,8 +235,9 @@ fn read_no_module() {
         result,
         concat!(
             "The export does not specify a module\n",
-            " test.symvers:1\n",
-            " | 0x12345678 foo", //
+            " test.symvers:1:15\n",
+            " | 0x12345678 foo\n",
+            " |               ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -179,15 +267,17 @@ fn read_type() {
                     "bar".to_string(),
                     ExportInfo::new(0x9abcdef0, "vmlinux", true, None::<&str>)
                 ),
-            ])
+            ]),
+            ..Default::default()
         }
     );
 }
 
 #[test]
 fn read_no_type() {
-    // Check that records without a type are rejected.
+    // Check that records without a type are rejected in the modern format.
     let mut symvers = SymversCorpus::new();
+    symvers.set_format(SymversFormat::Modern);
     let result = symvers.load_buffer(
         "test.symvers",
         bytes!(
@@ -198,8 +288,68 @@ fn read_no_type() {
         result,
         concat!(
             "The export does not specify a type\n",
-            " test.symvers:1\n",
-            " | 0x12345678 foo vmlinux", //
+            " test.symvers:1:23\n",
+            " | 0x12345678 foo vmlinux\n",
+            " |                       ^", //
+        ),
+    );
+    assert_eq!(symvers, SymversCorpus::new());
+}
+
+#[test]
+fn read_legacy() {
+    // Check that records in the legacy format are accepted and that the format is detected from
+    // the first record.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "test.symvers",
+        bytes!(
+            "0x12345678 foo\n",
+            "0x23456789 bar lib/test\n",
+            "0x3456789a baz lib/test EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        symvers,
+        SymversCorpus {
+            exports: HashMap::from([
+                (
+                    "foo".to_string(),
+                    ExportInfo::new(0x12345678, "vmlinux", false, None::<&str>)
+                ),
+                (
+                    "bar".to_string(),
+                    ExportInfo::new(0x23456789, "lib/test", false, None::<&str>)
+                ),
+                (
+                    "baz".to_string(),
+                    ExportInfo::new(0x3456789a, "lib/test", true, None::<&str>)
+                ),
+            ]),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn read_legacy_namespace() {
+    // Check that a namespace is rejected when the legacy format is explicitly selected.
+    let mut symvers = SymversCorpus::new();
+    symvers.set_format(SymversFormat::Legacy);
+    let result = symvers.load_buffer(
+        "test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL FOO_NS\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Unexpected string found at the end of the export record\n",
+            " test.symvers:1:38\n",
+            " | 0x12345678 foo vmlinux EXPORT_SYMBOL FOO_NS\n",
+            " |                                      ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -219,8 +369,9 @@ fn read_invalid_type() {
         result,
         concat!(
             "Invalid export type 'EXPORT_UNUSED_SYMBOL', must be either EXPORT_SYMBOL or EXPORT_SYMBOL_GPL\n",
-            " test.symvers:1\n",
-            " | 0x12345678 foo vmlinux EXPORT_UNUSED_SYMBOL", //
+            " test.symvers:1:24\n",
+            " | 0x12345678 foo vmlinux EXPORT_UNUSED_SYMBOL\n",
+            " |                        ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
@@ -243,7 +394,8 @@ fn read_namespace() {
             exports: HashMap::from([(
                 "foo".to_string(),
                 ExportInfo::new(0x12345678, "vmlinux", true, Some("FOO_NS"))
-            )])
+            )]),
+            ..Default::default()
         }
     );
 }
@@ -262,320 +414,492 @@ fn read_extra_data() {
         result,
         concat!(
             "Unexpected string found at the end of the export record\n",
-            " test.symvers:1\n",
-            " | 0x12345678 foo vmlinux EXPORT_SYMBOL_GPL FOO_NS garbage", //
+            " test.symvers:1:49\n",
+            " | 0x12345678 foo vmlinux EXPORT_SYMBOL_GPL FOO_NS garbage\n",
+            " |                                                 ^", //
         ),
     );
     assert_eq!(symvers, SymversCorpus::new());
 }
 
 #[test]
-fn compare_identical() {
-    // Check that the comparison of two identical symvers shows no differences.
+fn read_lenient_extra_data() {
+    // Check that extra data after the namespace is preserved in the lenient mode, including when
+    // the namespace column is empty in a tab-separated record.
     let mut symvers = SymversCorpus::new();
+    symvers.set_lenient(true);
     let result = symvers.load_buffer(
-        "a/test.symvers",
+        "test.symvers",
         bytes!(
-            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL_GPL FOO_NS extra1 extra2\n",
+            "0x9abcdef0\tbar\tvmlinux\tEXPORT_SYMBOL\t\textra3\n", //
         ),
     );
     assert_ok!(result);
-    let mut symvers2 = SymversCorpus::new();
-    let result = symvers2.load_buffer(
-        "b/test.symvers",
-        bytes!(
-            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\n", //
-        ),
+    assert_eq!(
+        symvers,
+        SymversCorpus {
+            exports: HashMap::from([
+                (
+                    "foo".to_string(),
+                    ExportInfo::new(0x12345678, "vmlinux", true, Some("FOO_NS"))
+                        .with_extra_columns(vec!["extra1".to_string(), "extra2".to_string()])
+                ),
+                (
+                    "bar".to_string(),
+                    ExportInfo::new(0x9abcdef0, "vmlinux", false, None::<&str>)
+                        .with_extra_columns(vec!["extra3".to_string()])
+                ),
+            ]),
+            ..Default::default()
+        }
     );
+
+    // Check that the extra columns are written back.
+    let mut out = Vec::new();
+    let result = symvers.write_buffer(&mut out, WriteOrder::Sorted);
     assert_ok!(result);
-    let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-    );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, true);
     assert_eq!(
-        str::from_utf8(&out).unwrap(),
+        String::from_utf8(out).unwrap(),
         concat!(
-            "", //
+            "0x9abcdef0\tbar\tvmlinux\tEXPORT_SYMBOL\t\textra3\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL_GPL\tFOO_NS\textra1\textra2\n", //
         )
     );
 }
 
 #[test]
-fn compare_added_export() {
-    // Check that the comparison of two symvers reports any newly added export.
+fn parse_mod_versions_basic() {
+    // Check that the CRCs of imported symbols are read from the __versions table.
+    let lines = [
+        "MODULE_INFO(name, KBUILD_MODNAME);",
+        "",
+        "static const struct modversion_info ____versions[]",
+        "__used __section(\"__versions\") = {",
+        "\t{ 0x12345678, \"foo\" },",
+        "\t{ 0x9abcdef0, \"bar\" },",
+        "};",
+        "",
+        "MODULE_INFO(depends, \"\");",
+    ]
+    .map(String::from);
+    let result = parse_mod_versions(Path::new("test.mod.c"), &lines);
+    assert_ok_eq!(
+        result,
+        vec![
+            (4, "foo".to_string(), 0x12345678),
+            (5, "bar".to_string(), 0x9abcdef0)
+        ]
+    );
+}
+
+#[test]
+fn parse_mod_versions_invalid() {
+    // Check that an invalid entry in the __versions table is rejected.
+    let lines = [
+        "static const struct modversion_info ____versions[]",
+        "__used __section(\"__versions\") = {",
+        "\t{ 12345678, \"foo\" },",
+        "};",
+    ]
+    .map(String::from);
+    let result = parse_mod_versions(Path::new("test.mod.c"), &lines);
+    assert_parse_err!(
+        result,
+        concat!(
+            "Invalid entry in the __versions table, must be in the form '{ <crc>, \"<name>\" },'\n",
+            " test.mod.c:3\n",
+            " | \t{ 12345678, \"foo\" },", //
+        ),
+    );
+}
+
+#[test]
+fn merge_identical() {
+    // Check that merging skips records identical to the already loaded ones.
     let mut symvers = SymversCorpus::new();
-    let result = symvers.load_buffer(
+    let result = symvers.merge_buffer(
         "a/test.symvers",
         bytes!(
-            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
-    let mut symvers2 = SymversCorpus::new();
-    let result = symvers2.load_buffer(
+    let result = symvers.merge_buffer(
         "b/test.symvers",
         bytes!(
-            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL BAR_NS\n", //
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
-    let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-    );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, true);
     assert_eq!(
-        str::from_utf8(&out).unwrap(),
-        concat!(
-            "Export 'bar' has been added (implicitly tolerated)\n", //
-        )
+        symvers,
+        SymversCorpus {
+            exports: HashMap::from([
+                (
+                    "foo".to_string(),
+                    ExportInfo::new(0x12345678, "vmlinux", false, None::<&str>)
+                ),
+                (
+                    "bar".to_string(),
+                    ExportInfo::new(0x23456789, "lib/test", true, Some("BAR_NS"))
+                ),
+                (
+                    "baz".to_string(),
+                    ExportInfo::new(0x3456789a, "vmlinux", false, None::<&str>)
+                ),
+            ]),
+            ..Default::default()
+        }
     );
 }
 
 #[test]
-fn compare_removed_export() {
-    // Check that the comparison of two symvers reports any removed export.
+fn merge_conflict() {
+    // Check that merging reports all records conflicting with the already loaded ones and leaves
+    // the corpus unchanged.
     let mut symvers = SymversCorpus::new();
-    let result = symvers.load_buffer(
+    let result = symvers.merge_buffer(
         "a/test.symvers",
         bytes!(
             "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL BAR_NS\n", //
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
-    let mut symvers2 = SymversCorpus::new();
-    let result = symvers2.load_buffer(
+    let result = symvers.merge_buffer(
         "b/test.symvers",
         bytes!(
-            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL BAR_NS\n", //
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
         ),
     );
-    assert_ok!(result);
-    let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-    );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
-    assert_eq!(
-        str::from_utf8(&out).unwrap(),
+    assert_parse_err!(
+        result,
         concat!(
-            "Export 'foo' has been removed\n", //
-        )
+            "Conflicting record 'foo', previously loaded as '0x12345678 foo vmlinux EXPORT_SYMBOL'\n",
+            " b/test.symvers:1\n",
+            " | 0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "Conflicting record 'bar', previously loaded as '0x23456789 bar vmlinux EXPORT_SYMBOL'\n",
+            " b/test.symvers:2\n",
+            " | 0x23456789 bar vmlinux EXPORT_SYMBOL_GPL", //
+        ),
     );
+    assert_eq!(symvers.exports.len(), 2);
 }
 
 #[test]
-fn compare_changed_crc() {
-    // Check that the comparison of two symvers reports exports with changed CRCs.
+fn merge_duplicate_symbol_record() {
+    // Check that symbol records with duplicate names in the same file are rejected when merging.
     let mut symvers = SymversCorpus::new();
-    let result = symvers.load_buffer(
-        "a/test.symvers",
+    let result = symvers.merge_buffer(
+        "test.symvers",
         bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
             "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
         ),
     );
-    assert_ok!(result);
-    let mut symvers2 = SymversCorpus::new();
-    let result = symvers2.load_buffer(
-        "b/test.symvers",
+    assert_parse_err!(
+        result,
+        concat!(
+            "Duplicate record 'foo'\n",
+            " test.symvers:2\n",
+            " | 0x12345678 foo vmlinux EXPORT_SYMBOL", //
+        ),
+    );
+}
+
+#[test]
+fn write_consolidated_basic() {
+    // Check that the consolidated output is sorted and uses the tab-separated modpost format.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "test.symvers",
         bytes!(
-            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
     let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-    );
+    let result = symvers.write_consolidated_buffer(&mut writer);
+    assert_ok!(result);
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+            "0x23456789\tbar\tlib/test\tEXPORT_SYMBOL_GPL\tBAR_NS\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n", //
         )
     );
 }
 
 #[test]
-fn compare_changed_type() {
-    // Check that the comparison of two symvers reports exports with changed types.
+fn insert_remove_get() {
+    // Check that records can be inserted, looked up and removed programmatically.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
-        "a/test.symvers",
+        "test.symvers",
         bytes!(
             "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
-            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
-    let mut symvers2 = SymversCorpus::new();
-    let result = symvers2.load_buffer(
-        "b/test.symvers",
-        bytes!(
-            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x3456789a baz vmlinux EXPORT_SYMBOL\n",
-            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
-        ),
+    let info = symvers.get("bar").unwrap();
+    assert_eq!(info.crc(), 0x23456789);
+    assert_eq!(info.module(), "lib/test");
+    assert!(info.is_gpl_only());
+    assert_eq!(info.namespace(), Some("BAR_NS"));
+    let old_info = symvers.insert(
+        "foo",
+        ExportInfo::new(0x3456789a, "vmlinux", true, None::<&str>),
     );
-    assert_ok!(result);
-    let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
+    assert_eq!(
+        old_info,
+        Some(ExportInfo::new(0x12345678, "vmlinux", false, None::<&str>))
+    );
+    let old_info = symvers.insert(
+        "baz",
+        ExportInfo::new(0x456789ab, "lib/test2", false, Some("BAZ_NS")),
     );
+    assert_eq!(old_info, None);
+    let old_info = symvers.remove("bar");
+    assert_eq!(
+        old_info,
+        Some(ExportInfo::new(
+            0x23456789,
+            "lib/test",
+            true,
+            Some("BAR_NS")
+        ))
+    );
+    assert_eq!(symvers.remove("bar"), None);
+    assert_eq!(symvers.get("bar"), None);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.write_buffer(&mut writer, WriteOrder::Sorted);
+    assert_ok!(result);
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'bar' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
-            "Export 'baz' changed type from 'EXPORT_SYMBOL_GPL' to 'EXPORT_SYMBOL' (implicitly tolerated)\n", //
+            "0x456789ab\tbaz\tlib/test2\tEXPORT_SYMBOL\tBAZ_NS\n",
+            "0x3456789a\tfoo\tvmlinux\tEXPORT_SYMBOL_GPL\t\n", //
         )
     );
 }
 
 #[test]
-fn compare_filter() {
-    // Check that the comparison of two symvers can be restricted to specific exports.
+fn write_input_order() {
+    // Check that records can be written in the order in which they were added to the corpus.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
-        "a/test.symvers",
+        "test.symvers",
         bytes!(
             "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n",
             "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
-    let mut symvers2 = SymversCorpus::new();
-    let result = symvers2.load_buffer(
-        "b/test.symvers",
+    symvers.remove("bar");
+    symvers.insert(
+        "qux",
+        ExportInfo::new(0x456789ab, "vmlinux", false, None::<&str>),
+    );
+    symvers.insert(
+        "foo",
+        ExportInfo::new(0x56789abc, "vmlinux", false, None::<&str>),
+    );
+    let result = symvers.merge_buffer(
+        "test2.symvers",
         bytes!(
-            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
-            "0xabcdef01 bar vmlinux EXPORT_SYMBOL\n",
-            "0xbcdef012 baz vmlinux EXPORT_SYMBOL\n", //
+            "0x6789abcd bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
-
-    // Check that the result is sensible without a filter.
     let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-    );
+    let result = symvers.write_buffer(&mut writer, WriteOrder::Input);
+    assert_ok!(result);
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
-            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
-            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+            "0x56789abc\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n",
+            "0x3456789a\tbaz\tvmlinux\tEXPORT_SYMBOL\t\n",
+            "0x456789ab\tqux\tvmlinux\tEXPORT_SYMBOL\t\n",
+            "0x6789abcd\tbar\tlib/test\tEXPORT_SYMBOL_GPL\tBAR_NS\n", //
         )
     );
+}
 
-    // Check the result when using a filter.
-    let mut symbol_filter = Filter::new();
-    let result = symbol_filter.load_buffer(
-        "filter-symbol-list.txt",
+#[test]
+fn write_namespaces() {
+    // Check that namespaces are listed sorted, with the number of their exports.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "test.symvers",
         bytes!(
-            "bar\n", "baz\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL FOO_NS\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL FOO_NS\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
-    let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        Some(&symbol_filter),
-        None,
-        &mut [(CompareFormat::Pretty, &mut writer)],
-    );
-    let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    let mut out = Vec::new();
+    let result = symvers.write_namespaces_buffer(&mut out);
+    assert_ok!(result);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
-            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n", //
+            "(none): 1\n",
+            "BAR_NS: 1\n",
+            "FOO_NS: 2\n", //
         )
     );
 }
 
 #[test]
-fn compare_rules() {
-    // Check that severity rules can be used to tolerate changes.
+fn write_trend() {
+    // Check that the trend lists the changes between consecutive snapshots and the stability score
+    // of each export.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
-        "a/test.symvers",
+        "1.symvers",
         bytes!(
-            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
     let mut symvers2 = SymversCorpus::new();
     let result = symvers2.load_buffer(
-        "b/test.symvers",
+        "2.symvers",
         bytes!(
-            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
-    let mut rules = Rules::new();
-    let result = rules.load_buffer(
-        "test.severities",
+    let mut symvers3 = SymversCorpus::new();
+    let result = symvers3.load_buffer(
+        "3.symvers",
         bytes!(
-            "vmlinux PASS\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL\n",
+            "0x456789ab qux lib/test EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
-    let mut writer = Writer::new_buffer();
-    let result = symvers.compare_with_buffer(
-        &symvers2,
-        None,
-        Some(&rules),
-        &mut [(CompareFormat::Pretty, &mut writer)],
+    let mut out = Vec::new();
+    let result = SymversCorpus::write_trend_buffer(
+        &[("1", &symvers), ("2", &symvers2), ("3", &symvers3)],
+        &mut out,
+    );
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Snapshot '1': '3' exports\n",
+            "Snapshot '2': '3' exports, '1' added, '1' removed, '1' modified\n",
+            "Snapshot '3': '3' exports, '0' added, '0' removed, '1' modified\n",
+            "Export 'bar': stability '0.50', '1' of '2' transitions unchanged\n",
+            "Export 'baz': stability '0.00', '0' of '1' transitions unchanged\n",
+            "Export 'foo': stability '1.00', '2' of '2' transitions unchanged\n",
+            "Export 'qux': stability '0.00', '0' of '2' transitions unchanged\n", //
+        )
+    );
+}
+
+#[test]
+fn retain_exports() {
+    // Check that exports can be filtered by their record.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL FOO_NS\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL FOO_NS\n", //
+        ),
     );
+    assert_ok!(result);
+    symvers.retain_exports(|_, info| info.namespace() == Some("FOO_NS"));
+    let mut names = Vec::new();
+    symvers.for_each_export(|name, _| names.push(name.to_string()));
+    names.sort();
+    assert_eq!(names, ["baz", "foo"]);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.write_buffer(&mut writer, WriteOrder::Input);
+    assert_ok!(result);
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, true);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
         concat!(
-            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rules)\n", //
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\tFOO_NS\n",
+            "0x3456789a\tbaz\tvmlinux\tEXPORT_SYMBOL\tFOO_NS\n", //
         )
     );
 }
 
 #[test]
-fn compare_format_null() {
-    // Check that when using the null format, the comparison output is empty and only the return
-    // code indicates any changes.
+fn write_split_basic() {
+    // Check that the split output contains one sorted file per module.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL BAR_NS\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = DirectoryWriter::new_buffer("split");
+    let result = symvers.write_split_buffer(&mut out);
+    assert_ok!(result);
+    let files = out.into_inner_map();
+    assert_eq!(files.len(), 2);
+    assert_eq!(
+        str::from_utf8(&files[Path::new("split/vmlinux.symvers")]).unwrap(),
+        concat!(
+            "0x3456789a\tbaz\tvmlinux\tEXPORT_SYMBOL_GPL\t\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n", //
+        )
+    );
+    assert_eq!(
+        str::from_utf8(&files[Path::new("split/lib/test.symvers")]).unwrap(),
+        "0x23456789\tbar\tlib/test\tEXPORT_SYMBOL_GPL\tBAR_NS\n"
+    );
+}
+
+#[test]
+fn write_split_invalid_module() {
+    // Check that a module name escaping the output directory is rejected.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "test.symvers",
+        bytes!("0x12345678 foo ../vmlinux EXPORT_SYMBOL\n"),
+    );
+    assert_ok!(result);
+    let mut out = DirectoryWriter::new_buffer("split");
+    let result = symvers.write_split_buffer(&mut out);
+    assert_parse_err!(
+        result,
+        "Module name '../vmlinux' is not a valid relative path"
+    );
+    assert!(out.into_inner_map().is_empty());
+}
+
+#[test]
+fn compare_identical() {
+    // Check that the comparison of two identical symvers shows no differences.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
         "a/test.symvers",
@@ -588,7 +912,7 @@ fn compare_format_null() {
     let result = symvers2.load_buffer(
         "b/test.symvers",
         bytes!(
-            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n", //
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
@@ -597,30 +921,23 @@ fn compare_format_null() {
         &symvers2,
         None,
         None,
-        &mut [(CompareFormat::Null, &mut writer)],
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
-    assert_eq!(
-        str::from_utf8(&out).unwrap(),
-        concat!(
-            "", //
-        )
-    );
+    assert_ok_eq!(result, CompareStatus::Same);
+    assert_eq!(str::from_utf8(&out).unwrap(), "");
 }
 
 #[test]
-fn compare_format_symbols() {
-    // Check that when using the symbols format, the comparison output is in alphabetical order,
-    // doesn't contain tolerated changes, and lists each symbol only once, even if it has multiple
-    // changes.
+fn compare_added_export() {
+    // Check that the comparison of two symvers reports any newly added export.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
         "a/test.symvers",
         bytes!(
-            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
-            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
@@ -628,9 +945,8 @@ fn compare_format_symbols() {
     let result = symvers2.load_buffer(
         "b/test.symvers",
         bytes!(
-            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
@@ -639,29 +955,27 @@ fn compare_format_symbols() {
         &symvers2,
         None,
         None,
-        &mut [(CompareFormat::Symbols, &mut writer)],
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Tolerated);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
-        concat!(
-            "bar\n", "baz\n", "foo\n", //
-        )
+        "Export 'bar' has been added (implicitly tolerated)\n"
     );
 }
 
 #[test]
-fn compare_format_mod_symbols() {
-    // Check that when using the mod-symbols format, the comparison output lists only modified
-    // symbols and exludes any additions and removals.
+fn compare_removed_export() {
+    // Check that the comparison of two symvers reports any removed export.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
         "a/test.symvers",
         bytes!(
             "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
-            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n", //
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
@@ -669,9 +983,7 @@ fn compare_format_mod_symbols() {
     let result = symvers2.load_buffer(
         "b/test.symvers",
         bytes!(
-            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL_GPL BAR_NS\n", //
         ),
     );
     assert_ok!(result);
@@ -680,51 +992,26 @@ fn compare_format_mod_symbols() {
         &symvers2,
         None,
         None,
-        &mut [(CompareFormat::ModSymbols, &mut writer)],
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Breaking);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
-        concat!(
-            "bar\n", "foo\n", //
-        )
+        "Export 'foo' has been removed\n"
     );
 }
 
 #[test]
-fn compare_format_short() {
-    // Check that when using the short format, the comparison output details all breaking and
-    // implicitly-tolerated changes, followed by a summary of changes tolerated by the rules.
-    //
-    // Cases:
-    // aaa: Added -> separately reported as implicitly tolerated.
-    // bbb: Removed -> reported separately.
-    // ccc: Changes its CRC and type from regular to GPL -> reported twice separately.
-    // ddd: Changes its type from regular to GPL -> reported separately.
-    // eee: Changes its type from GPL to regular -> reported separately as implicitly tolerated.
-    //
-    // .. and the same tests but with the symbols matching the rules:
-    // fff: Added -> included in the tolerated-by-rules summary.
-    // ggg: Removed -> included in the tolerated-by-rules summary.
-    // hhh: Changes its CRC and type from regular to GPL -> included once in the tolerated-by-rules
-    //      summary.
-    // iii: Changes its type from regular to GPL -> included in the tolerated-by-rules summary.
-    // jjj: Changes its type from GPL to regular -> included in the tolerated-by-rules summary.
-    //
-    // NOTE: Keep this test synchronized with the ksymtypes_compare_format integration test.
+fn compare_changed_crc() {
+    // Check that the comparison of two symvers reports exports with changed CRCs.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
         "a/test.symvers",
         bytes!(
-            "0x12345678 bbb vmlinux EXPORT_SYMBOL\n",
-            "0x12345678 ccc vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 ddd vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 eee vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x12345678 ggg vmlinux EXPORT_SYMBOL\n",
-            "0x12345678 hhh vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 iii vmlinux EXPORT_SYMBOL\n",
-            "0x23456789 jjj vmlinux EXPORT_SYMBOL_GPL\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
@@ -732,26 +1019,7 @@ fn compare_format_short() {
     let result = symvers2.load_buffer(
         "b/test.symvers",
         bytes!(
-            "0x12345678 aaa vmlinux EXPORT_SYMBOL\n",
-            "0x9abcdef0 ccc vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x23456789 ddd vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x23456789 eee vmlinux EXPORT_SYMBOL\n",
-            "0x12345678 fff vmlinux EXPORT_SYMBOL\n",
-            "0x9abcdef0 hhh vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x23456789 iii vmlinux EXPORT_SYMBOL_GPL\n",
-            "0x23456789 jjj vmlinux EXPORT_SYMBOL\n", //
-        ),
-    );
-    assert_ok!(result);
-    let mut rules = Rules::new();
-    let result = rules.load_buffer(
-        "test.severities",
-        bytes!(
-            "fff PASS\n",
-            "ggg PASS\n",
-            "hhh PASS\n",
-            "iii PASS\n",
-            "jjj PASS\n", //
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n", //
         ),
     );
     assert_ok!(result);
@@ -759,44 +1027,1233 @@ fn compare_format_short() {
     let result = symvers.compare_with_buffer(
         &symvers2,
         None,
-        Some(&rules),
-        &mut [(CompareFormat::Short, &mut writer)],
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
     );
     let out = writer.into_inner_vec();
-    assert_ok_eq!(result, false);
+    assert_ok_eq!(result, CompareStatus::Breaking);
     assert_eq!(
         str::from_utf8(&out).unwrap(),
-        concat!(
-            "Export 'aaa' has been added (implicitly tolerated)\n",
-            "Export 'bbb' has been removed\n",
-            "Export 'ccc' changed CRC from '0x12345678' to '0x9abcdef0'\n",
-            "Export 'ccc' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
-            "Export 'ddd' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
-            "Export 'eee' changed type from 'EXPORT_SYMBOL_GPL' to 'EXPORT_SYMBOL' (implicitly tolerated)\n",
-            "Changes tolerated by rules: '1' additions, '1' removals, '3' modifications\n", //
-        )
+        "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n"
     );
 }
 
 #[test]
-fn mark_used_rules() {
-    // Check that used rules are properly marked.
+fn compare_changed_type() {
+    // Check that the comparison of two symvers reports exports with changed types.
     let mut symvers = SymversCorpus::new();
     let result = symvers.load_buffer(
         "a/test.symvers",
         bytes!(
-            "0x12345678 foobar lib/test_module.ko EXPORT_SYMBOL\n",
-            "0x23456789 baz lib/test_module.ko EXPORT_SYMBOL BAZ_NS\n", //
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
         ),
     );
     assert_ok!(result);
-    let mut rules = Rules::new();
-    let result = rules.load_buffer(
-        "test.severities",
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
         bytes!(
-            "foo* PASS\n",
-            "foobar FAIL\n",
-            "MODULE lib/qux.ko FAIL\n",
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'bar' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "Export 'baz' changed type from 'EXPORT_SYMBOL_GPL' to 'EXPORT_SYMBOL' (implicitly tolerated)\n",
+            "Exports changed from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL':\n",
+            " Module 'vmlinux':\n",
+            "  bar\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_gpl_report() {
+    // Check that the gpl-report format lists exports changed to GPL-only, grouped by module.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz lib/test EXPORT_SYMBOL\n",
+            "0x456789ab qux lib/test EXPORT_SYMBOL_GPL\n",
+            "0x56789abc quux lib/test2 EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x9abcdef0 baz lib/test EXPORT_SYMBOL_GPL\n",
+            "0x456789ab qux lib/test EXPORT_SYMBOL\n",
+            "0x56789abc quux lib/test2 EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::GplReport, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Module 'lib/test':\n",
+            " baz\n",
+            "Module 'vmlinux':\n",
+            " bar\n",
+            " foo\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_changed_namespace() {
+    // Check that the comparison of two symvers reports exports with changed namespaces.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL BAZ_NS\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL QUX_NS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL BAR_NS\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL QUX2_NS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'bar' changed namespace from '(none)' to 'BAR_NS'\n",
+            "Export 'baz' changed namespace from 'BAZ_NS' to '(none)' (implicitly tolerated)\n",
+            "Export 'qux' changed namespace from 'QUX_NS' to 'QUX2_NS'\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_moved_export() {
+    // Check that the comparison of two symvers reports exports moved between modules.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test_module EXPORT_SYMBOL\n",
+            "0x3456789a baz lib/test_module EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 foo drivers/net/foo EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test_module EXPORT_SYMBOL\n",
+            "0x4456789a baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'baz' changed CRC from '0x3456789a' to '0x4456789a'\n",
+            "Export 'baz' moved from 'lib/test_module' to 'vmlinux' (implicitly tolerated)\n",
+            "Export 'foo' moved from 'vmlinux' to 'drivers/net/foo' (implicitly tolerated)\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_filter() {
+    // Check that the comparison of two symvers can be restricted to specific exports.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0xabcdef01 bar vmlinux EXPORT_SYMBOL\n",
+            "0xbcdef012 baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+
+    // Check that the result is sensible without a filter.
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+        )
+    );
+
+    // Check the result when using a filter.
+    let mut symbol_filter = Filter::new();
+    let result = symbol_filter.load_buffer(
+        "filter-symbol-list.txt",
+        bytes!(
+            "bar\n", "baz\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        Some(&symbol_filter),
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_rules() {
+    // Check that severity rules can be used to tolerate changes.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "vmlinux PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Tolerated);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rule test.severities:1 'vmlinux PASS')\n"
+    );
+}
+
+#[test]
+fn compare_rules_warn() {
+    // Check that changes tolerated by INFO and WARN rules are reported also in the short format.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0xabcdef01 bar vmlinux EXPORT_SYMBOL\n",
+            "0xbcdef012 baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo PASS\n",
+            "bar INFO\n",
+            "baz WARN\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::Short, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Tolerated);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01' (tolerated with a note by rule test.severities:2 'bar INFO')\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012' (tolerated with a warning by rule test.severities:3 'baz WARN')\n",
+            "Changes tolerated by rules: '0' additions, '0' removals, '3' modifications\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_suppressions() {
+    // Check that acknowledged differences are not reported.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 bar vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut suppressions = Suppressions::new();
+    let result = suppressions.load_buffer(
+        "test.suppressions",
+        bytes!(
+            "foo crc 0x12345678 0x9abcdef0\n",
+            "bar crc 0x12345678 0x9abcdef0\n",
+            "baz removed\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        Some(&suppressions),
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        "Export 'bar' changed CRC from '0x12345678' to '0x23456789'\n"
+    );
+}
+
+#[test]
+fn compare_protected() {
+    // Check that changes of protected exports are breaking regardless of the rules, and that their
+    // removal is reported with a distinct status.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 bar vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 qux vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "vmlinux PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut protected = Filter::new();
+    let result = protected.load_buffer(
+        "protected.txt",
+        bytes!(
+            "foo\n", "ba*\n", "qux\n", //
+        ),
+    );
+    assert_ok!(result);
+    symvers.set_protected(Some(protected));
+
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::Pretty, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::ProtectedRemoved);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'qux' has been added (tolerated by rule test.severities:1 'vmlinux PASS')\n",
+            "Export 'baz' has been removed (protected symbol removed)\n",
+            "Export 'bar' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+        )
+    );
+    let result = symvers.compare_status(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        CompareStatus::ProtectedRemoved,
+    );
+    assert_eq!(result, CompareStatus::ProtectedRemoved);
+
+    // Check that the other changes of protected exports are breaking.
+    let mut symbol_filter = Filter::new();
+    let result = symbol_filter.load_buffer(
+        "filter-symbol-list.txt",
+        bytes!(
+            "foo\n", "qux\n", //
+        ),
+    );
+    assert_ok!(result);
+    let result = symvers.compare_status(
+        &symvers2,
+        Some(&symbol_filter),
+        Some(&rules),
+        None,
+        CompareStatus::ProtectedRemoved,
+    );
+    assert_eq!(result, CompareStatus::Breaking);
+}
+
+#[test]
+fn compare_explain_with_symtypes() {
+    // Check that CRC changes in the pretty output are explained using the provided symtypes data,
+    // while other formats remain unaffected.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0xabcdef01 bar vmlinux EXPORT_SYMBOL\n",
+            "0xbcdef012 baz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut warnings = Vec::new();
+    let mut symtypes = SymtypesCorpus::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "s#qux struct qux { int a ; }\n",
+            "foo int foo ( s#qux )\n",
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "s#qux struct qux { int a ; int b ; }\n",
+            "foo int foo ( s#qux )\n",
+            "bar int bar ( )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let mut writer2 = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        Some((&symtypes, &symtypes2)),
+        &mut [
+            (CompareFormat::Pretty, &mut writer),
+            (CompareFormat::Short, &mut writer2),
+        ],
+    );
+    let out = writer.into_inner_vec();
+    let out2 = writer2.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
+            "  no type changes are found in the symtypes data\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
+            "  no symtypes data is available for the export\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "  because of a changed 's#qux' (member appended, probably safe):\n",
+            "  @@ -1,3 +1,4 @@\n",
+            "   struct qux {\n",
+            "   \tint a;\n",
+            "  +\tint b;\n",
+            "   }\n", //
+        )
+    );
+    assert_eq!(
+        str::from_utf8(&out2).unwrap(),
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "Changes tolerated by rules: '0' additions, '0' removals, '0' modifications\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_null() {
+    // Check that when using the null format, the comparison output is empty and only the return
+    // code indicates any changes.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Null, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(str::from_utf8(&out).unwrap(), "");
+}
+
+#[test]
+fn compare_format_symbols() {
+    // Check that when using the symbols format, the comparison output is in alphabetical order,
+    // doesn't contain tolerated changes, and lists each symbol only once, even if it has multiple
+    // changes.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::Symbols, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "bar\n", "baz\n", "foo\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_mod_symbols() {
+    // Check that when using the mod-symbols format, the comparison output lists only modified
+    // symbols and exludes any additions and removals.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        None,
+        &mut [(CompareFormat::ModSymbols, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "bar\n", "foo\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_short() {
+    // Check that when using the short format, the comparison output details all breaking and
+    // implicitly-tolerated changes, followed by a summary of changes tolerated by the rules.
+    //
+    // Cases:
+    // aaa: Added -> separately reported as implicitly tolerated.
+    // bbb: Removed -> reported separately.
+    // ccc: Changes its CRC and type from regular to GPL -> reported twice separately.
+    // ddd: Changes its type from regular to GPL -> reported separately.
+    // eee: Changes its type from GPL to regular -> reported separately as implicitly tolerated.
+    //
+    // .. and the same tests but with the symbols matching the rules:
+    // fff: Added -> included in the tolerated-by-rules summary.
+    // ggg: Removed -> included in the tolerated-by-rules summary.
+    // hhh: Changes its CRC and type from regular to GPL -> included once in the tolerated-by-rules
+    //      summary.
+    // iii: Changes its type from regular to GPL -> included in the tolerated-by-rules summary.
+    // jjj: Changes its type from GPL to regular -> included in the tolerated-by-rules summary.
+    //
+    // NOTE: Keep this test synchronized with the ksymtypes_compare_format integration test.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 bbb vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 ccc vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 ddd vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 eee vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x12345678 ggg vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 hhh vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 iii vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 jjj vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 aaa vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 ccc vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 ddd vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 eee vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 fff vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 hhh vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 iii vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 jjj vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "fff PASS\n",
+            "ggg PASS\n",
+            "hhh PASS\n",
+            "iii PASS\n",
+            "jjj PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::Short, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'aaa' has been added (implicitly tolerated)\n",
+            "Export 'bbb' has been removed\n",
+            "Export 'ccc' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "Export 'ccc' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "Export 'ddd' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "Export 'eee' changed type from 'EXPORT_SYMBOL_GPL' to 'EXPORT_SYMBOL' (implicitly tolerated)\n",
+            "Changes tolerated by rules: '1' additions, '1' removals, '3' modifications\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_summary() {
+    // Check that the summary format counts each export once, as breaking if any of its changes is
+    // breaking, as tolerated if all its changes are tolerated, or as unchanged.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 bbb vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 ccc vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 ddd vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x12345678 ggg vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 hhh vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 zzz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 aaa vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 ccc vmlinux EXPORT_SYMBOL_GPL\n",
+            "0x23456789 ddd vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 ggg vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 hhh vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 zzz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer("test.severities", bytes!("hhh PASS\n"));
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::Summary, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        "kABI: 2 breaking, 3 tolerated, 2 unchanged\n"
+    );
+}
+
+#[test]
+fn compare_format_module_stats() {
+    // Check that the module-stats format lists the numbers of changed exports for each module,
+    // counting added exports in their new module.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 bbb vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 ccc drivers/foo EXPORT_SYMBOL\n",
+            "0x12345678 ddd drivers/foo EXPORT_SYMBOL\n",
+            "0x12345678 eee drivers/bar EXPORT_SYMBOL\n",
+            "0x12345678 zzz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 aaa drivers/baz EXPORT_SYMBOL\n",
+            "0x9abcdef0 ccc drivers/foo EXPORT_SYMBOL\n",
+            "0x9abcdef0 ddd drivers/foo EXPORT_SYMBOL\n",
+            "0x12345678 eee drivers/qux EXPORT_SYMBOL\n",
+            "0x12345678 zzz vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer("test.severities", bytes!("ddd PASS\n"));
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::ModuleStats, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Module 'drivers/bar': 0 added, 0 removed, 1 modified, 1 tolerated\n",
+            "Module 'drivers/baz': 1 added, 0 removed, 0 modified, 1 tolerated\n",
+            "Module 'drivers/foo': 0 added, 0 removed, 2 modified, 1 tolerated\n",
+            "Module 'vmlinux': 0 added, 1 removed, 0 modified, 0 tolerated\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_format_html() {
+    // Check that the HTML format lists all changes and makes CRC changes with an explanation
+    // collapsible.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut warnings = Vec::new();
+    let mut symtypes = SymtypesCorpus::new();
+    let result = symtypes.load_buffer(
+        "a/test.symtypes",
+        bytes!(
+            "foo int foo ( int )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    let mut symtypes2 = SymtypesCorpus::new();
+    let result = symtypes2.load_buffer(
+        "b/test.symtypes",
+        bytes!(
+            "foo int foo ( long )\n", //
+        ),
+        &mut warnings,
+    );
+    assert_ok!(result);
+    assert!(warnings.is_empty());
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        None,
+        None,
+        Some((&symtypes, &symtypes2)),
+        &mut [(CompareFormat::Html, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    let out = str::from_utf8(&out).unwrap();
+    assert!(out.starts_with("<!DOCTYPE html>\n"));
+    assert!(out.ends_with(concat!(
+        "<h1>Comparison of symvers corpuses</h1>\n",
+        "<ul>\n",
+        "<li>Export &#39;bar&#39; changed type from &#39;EXPORT_SYMBOL&#39; to &#39;EXPORT_SYMBOL_GPL&#39;</li>\n",
+        "<li><details><summary>Export &#39;foo&#39; changed CRC from &#39;0x12345678&#39; to &#39;0x9abcdef0&#39;</summary>\n",
+        "<pre class=\"diff\">\n",
+        "because of a changed &#39;foo&#39;:\n",
+        "<span class=\"hunk\">@@ -1,1 +1,1 @@</span>\n",
+        "<span class=\"del\">-int foo ( int )</span>\n",
+        "<span class=\"add\">+int foo ( long )</span>\n",
+        "</pre>\n",
+        "</details></li>\n",
+        "</ul>\n",
+        "</body>\n",
+        "</html>\n", //
+    )));
+}
+
+#[test]
+fn compare_format_kabi_pl() {
+    // Check that the kabi-pl format reports only breaking changes, using the report lines of the
+    // legacy kabi.pl script.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL\n",
+            "0x3456789a baz vmlinux EXPORT_SYMBOL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL_GPL FOO_NS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar lib/test EXPORT_SYMBOL_GPL\n",
+            "0x456789ab qux vmlinux EXPORT_SYMBOL BAR_NS\n",
+            "0x56789abc quux vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "lib/test PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::KabiPl, &mut writer)],
+    );
+    let out = writer.into_inner_vec();
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "KABI: symbol baz(vmlinux) lost\n",
+            "KABI: symbol foo(vmlinux) changed crc from 0x12345678 to 0x9abcdef0\n",
+            "KABI: symbol qux(vmlinux) changed namespace from FOO_NS to BAR_NS\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_many() {
+    // Check that comparing a baseline with several corpuses produces a matrix of changes.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "base.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 bar vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 baz vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 qux lib/test_module EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "default.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 bar vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 baz vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 qux lib/test_module EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers3 = SymversCorpus::new();
+    let result = symvers3.load_buffer(
+        "rt.symvers",
+        bytes!(
+            "0x9abcdef0 foo vmlinux EXPORT_SYMBOL\n",
+            "0x12345678 baz lib/test_module EXPORT_SYMBOL\n",
+            "0x12345678 qux lib/test_module EXPORT_SYMBOL\n",
+            "0x12345678 quux vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "lib/test_module PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = symvers.compare_many_with_buffer(
+        &[("default.symvers", &symvers2), ("rt.symvers", &symvers3)],
+        None,
+        Some(&rules),
+        None,
+        &mut out,
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export  default.symvers  rt.symvers\n",
+            "bar     -                breaking\n",
+            "baz     -                tolerated\n",
+            "foo     breaking         breaking\n",
+            "quux    -                tolerated\n",
+            "qux     tolerated        -\n", //
+        )
+    );
+}
+
+#[test]
+fn compare_many_identical() {
+    // Check that comparing a baseline with identical corpuses produces no output.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "base.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "default.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = symvers.compare_many_with_buffer(
+        &[("default.symvers", &symvers2), ("rt.symvers", &symvers2)],
+        None,
+        None,
+        None,
+        &mut out,
+    );
+    assert_ok_eq!(result, CompareStatus::Same);
+    assert_eq!(str::from_utf8(&out).unwrap(), "");
+}
+
+#[test]
+fn compare_baselines() {
+    // Check that the comparison with multiple named baselines uses the rules of each baseline and
+    // prefixes the reported changes with the baseline name.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "ga.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "lu.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers3 = SymversCorpus::new();
+    let result = symvers3.load_buffer(
+        "new.symvers",
+        bytes!(
+            "0x3456789a foo vmlinux EXPORT_SYMBOL\n",
+            "0x9abcdef0 bar vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "lu.severities",
+        bytes!(
+            "foo PASS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = symvers3.compare_baselines_with_buffer(
+        &[("GA", &symvers, None), ("LU", &symvers2, Some(&rules))],
+        None,
+        None,
+        &mut out,
+    );
+    assert_ok_eq!(result, CompareStatus::Breaking);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Baseline 'GA': Export 'bar' changed CRC from '0x23456789' to '0x9abcdef0'\n",
+            "Baseline 'GA': Export 'foo' changed CRC from '0x12345678' to '0x3456789a'\n",
+            "Baseline 'LU': Export 'foo' changed CRC from '0x12345678' to '0x3456789a' ",
+            "(tolerated by rule lu.severities:1 'foo PASS')\n", //
+        )
+    );
+
+    // Check that the status reflects only the baselines that are compared.
+    let mut out = Vec::new();
+    let result = symvers3.compare_baselines_with_buffer(
+        &[("LU", &symvers2, Some(&rules))],
+        None,
+        None,
+        &mut out,
+    );
+    assert_ok_eq!(result, CompareStatus::Tolerated);
+}
+
+#[test]
+fn mark_used_rules() {
+    // Check that used rules are properly marked.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foobar lib/test_module.ko EXPORT_SYMBOL\n",
+            "0x23456789 baz lib/test_module.ko EXPORT_SYMBOL BAZ_NS\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo* PASS\n",
+            "foobar FAIL\n",
+            "MODULE lib/qux.ko FAIL\n",
             "NAMESPACE BAZ_NS FAIL\n", //
         ),
     );
@@ -805,3 +2262,126 @@ fn mark_used_rules() {
     symvers.mark_used_rules(&rules, &mut used_rules);
     assert_eq!(used_rules, UsedRules::from([0, 3]));
 }
+
+#[test]
+fn check_rules() {
+    // Check that the matching rule is reported for each export, followed by unused rules and
+    // a summary.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foobar lib/test_module.ko EXPORT_SYMBOL\n",
+            "0x23456789 baz lib/test_module.ko EXPORT_SYMBOL BAZ_NS\n",
+            "0x3456789a qux vmlinux EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut rules = Rules::new();
+    let result = rules.load_buffer(
+        "test.severities",
+        bytes!(
+            "foo* PASS\n",
+            "foobar FAIL\n",
+            "NAMESPACE BAZ_NS FAIL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut out = Vec::new();
+    let result = symvers.check_rules_buffer(&rules, &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "Export 'baz' matches rule test.severities:3 'NAMESPACE BAZ_NS FAIL'\n",
+            "Export 'foobar' matches rule test.severities:1 'SYMBOL foo* PASS'\n",
+            "Export 'qux' matches no rule\n",
+            "test.severities:2: WARNING: Severity rule 'SYMBOL foobar FAIL' is unused\n",
+            "Summary: '2' exports matched, '1' exports unmatched, '1' of '3' rules unused\n", //
+        )
+    );
+}
+
+#[test]
+fn suggest_rules() {
+    // Check that suggested rules tolerate all breaking changes, preferring namespace and module
+    // rules when all exports in the group are changed.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x23456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz lib/test_module EXPORT_SYMBOL\n",
+            "0x456789ab qux lib/test_module EXPORT_SYMBOL\n",
+            "0x56789abc quux lib/test_module2 EXPORT_SYMBOL TEST_NS\n",
+            "0x6789abcd corge lib/test_module3 EXPORT_SYMBOL TEST_NS\n",
+            "0x789abcde grault lib/test_module3 EXPORT_SYMBOL_GPL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL\n",
+            "0x33456789 bar vmlinux EXPORT_SYMBOL\n",
+            "0x3456789a baz lib/test_module EXPORT_SYMBOL_GPL\n",
+            "0x56789abc quux lib/test_module2 EXPORT_SYMBOL TEST_NS\n",
+            "0x7789abcd corge lib/test_module3 EXPORT_SYMBOL TEST_NS2\n",
+            "0x789abcde grault lib/test_module3 EXPORT_SYMBOL\n",
+            "0x89abcdef garply lib/test_module3 EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let rules = symvers.suggest_rules(&symvers2, None);
+    let mut out = Vec::new();
+    let result = rules.write_buffer(&mut out);
+    assert_ok!(result);
+    assert_eq!(
+        str::from_utf8(&out).unwrap(),
+        concat!(
+            "MODULE lib/test_module PASS\n",
+            "SYMBOL bar PASS\n",
+            "SYMBOL corge PASS\n", //
+        )
+    );
+    let mut writer = Writer::new_buffer();
+    let result = symvers.compare_with_buffer(
+        &symvers2,
+        None,
+        Some(&rules),
+        None,
+        None,
+        &mut [(CompareFormat::Null, &mut writer)],
+    );
+    assert_ok_eq!(result, CompareStatus::Tolerated);
+}
+
+#[test]
+fn suggest_rules_namespace() {
+    // Check that a namespace rule is suggested when all exports in the namespace are changed.
+    let mut symvers = SymversCorpus::new();
+    let result = symvers.load_buffer(
+        "a/test.symvers",
+        bytes!(
+            "0x12345678 foo vmlinux EXPORT_SYMBOL TEST_NS\n",
+            "0x23456789 bar lib/test_module EXPORT_SYMBOL TEST_NS\n",
+            "0x3456789a baz lib/test_module EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let mut symvers2 = SymversCorpus::new();
+    let result = symvers2.load_buffer(
+        "b/test.symvers",
+        bytes!(
+            "0x3456789a baz lib/test_module EXPORT_SYMBOL\n", //
+        ),
+    );
+    assert_ok!(result);
+    let rules = symvers.suggest_rules(&symvers2, None);
+    let mut out = Vec::new();
+    let result = rules.write_buffer(&mut out);
+    assert_ok!(result);
+    assert_eq!(str::from_utf8(&out).unwrap(), "NAMESPACE TEST_NS PASS\n");
+}
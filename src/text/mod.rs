@@ -3,19 +3,33 @@
 
 //! Utility functions for working with text.
 
-use crate::{Error, MapIOErr, PathFile, debug};
-use std::collections::{HashMap, HashSet};
+use crate::sha256::{self, Sha256};
+#[cfg(feature = "compression")]
+use crate::{Compressor, compression_tool};
+use crate::{Error, MapIOErr, PathFile, debug, strip_compression_ext};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
-use std::io::{self, BufReader, BufWriter, prelude::*};
+#[cfg(feature = "compression")]
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, IsTerminal, prelude::*};
 use std::ops::{Index, IndexMut};
 use std::path::{Path, PathBuf};
-use std::{cmp, fs};
+use std::str::FromStr;
+use std::{cmp, env, fs, iter};
 
+#[cfg(test)]
+mod tests_color;
 #[cfg(test)]
 mod tests_diff;
 #[cfg(test)]
 mod tests_filter;
 #[cfg(test)]
+mod tests_manifest;
+#[cfg(test)]
+mod tests_property;
+#[cfg(test)]
+mod tests_tar;
+#[cfg(test)]
 mod tests_wildcard;
 
 // Implementation of the Myers diff algorithm:
@@ -135,13 +149,84 @@ fn myers<T: AsRef<str> + PartialEq>(a: &[T], b: &[T]) -> EditScript {
     unreachable!();
 }
 
+/// Options for formatting a diff.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DiffOptions {
+    /// The number of unchanged lines shown around each change.
+    pub context_size: usize,
+    /// Whether changed lines are compared word by word, marking removed words as `[-word-]` and
+    /// added words as `{+word+}`.
+    pub word_diff: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            context_size: 3,
+            word_diff: false,
+        }
+    }
+}
+
+/// Splits a line into its leading whitespace and the remaining words.
+fn split_words(line: &str) -> (&str, Vec<&str>) {
+    let trimmed = line.trim_ascii_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    (indent, trimmed.split(' ').collect())
+}
+
+/// Compares two lines word by word and produces a single line that marks the removed and added
+/// words.
+fn word_diff_line(line_a: &str, line_b: &str) -> String {
+    let (indent, words_a) = split_words(line_a);
+    let (_, words_b) = split_words(line_b);
+
+    let mut pieces = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let flush = |pieces: &mut Vec<String>, removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        let mut piece = String::new();
+        if !removed.is_empty() {
+            piece.push_str(&format!("[-{}-]", removed.join(" ")));
+            removed.clear();
+        }
+        if !added.is_empty() {
+            piece.push_str(&format!("{{+{}+}}", added.join(" ")));
+            added.clear();
+        }
+        if !piece.is_empty() {
+            pieces.push(piece);
+        }
+    };
+    for edit in myers(&words_a, &words_b) {
+        match edit {
+            Edit::KeepA(index_a) => {
+                flush(&mut pieces, &mut removed, &mut added);
+                pieces.push(words_a[index_a].to_string());
+            }
+            Edit::RemoveA(index_a) => removed.push(words_a[index_a]),
+            Edit::InsertB(index_b) => added.push(words_b[index_b]),
+        }
+    }
+    flush(&mut pieces, &mut removed, &mut added);
+
+    format!("{}{}", indent, pieces.join(" "))
+}
+
 /// Writes a single diff hunk to the provided output stream.
-fn write_hunk<W: Write>(
+///
+/// The `hunk_edits` describe the content of the hunk. If the word diff is requested, each removed
+/// line is paired with an added line from the same change and the pair is written as a single line
+/// marking the changed words.
+#[allow(clippy::too_many_arguments)]
+fn write_hunk<T: AsRef<str> + Display, W: Write>(
+    a: &[T],
+    b: &[T],
     hunk_pos_a: usize,
     hunk_len_a: usize,
     hunk_pos_b: usize,
     hunk_len_b: usize,
-    hunk_data: &[String],
+    hunk_edits: &[Edit],
+    word_diff: bool,
     mut writer: W,
 ) -> Result<(), Error> {
     let err_desc = "Failed to write a diff hunk";
@@ -152,8 +237,54 @@ fn write_hunk<W: Write>(
         hunk_pos_a, hunk_len_a, hunk_pos_b, hunk_len_b
     )
     .map_io_err(err_desc)?;
-    for hunk_str in hunk_data {
-        writeln!(writer, "{}", hunk_str).map_io_err(err_desc)?;
+
+    if !word_diff {
+        for edit in hunk_edits {
+            match *edit {
+                Edit::KeepA(index_a) => writeln!(writer, " {}", a[index_a]),
+                Edit::RemoveA(index_a) => writeln!(writer, "-{}", a[index_a]),
+                Edit::InsertB(index_b) => writeln!(writer, "+{}", b[index_b]),
+            }
+            .map_io_err(err_desc)?;
+        }
+        return Ok(());
+    }
+
+    let mut edit_idx = 0;
+    while edit_idx < hunk_edits.len() {
+        if let Edit::KeepA(index_a) = hunk_edits[edit_idx] {
+            writeln!(writer, "{}", a[index_a]).map_io_err(err_desc)?;
+            edit_idx += 1;
+            continue;
+        }
+
+        // Collect the whole change and pair its removed and added lines.
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        while edit_idx < hunk_edits.len() {
+            match hunk_edits[edit_idx] {
+                Edit::KeepA(_) => break,
+                Edit::RemoveA(index_a) => removed.push(a[index_a].as_ref()),
+                Edit::InsertB(index_b) => added.push(b[index_b].as_ref()),
+            }
+            edit_idx += 1;
+        }
+        for i in 0..cmp::max(removed.len(), added.len()) {
+            match (removed.get(i), added.get(i)) {
+                (Some(line_a), Some(line_b)) => {
+                    writeln!(writer, "{}", word_diff_line(line_a, line_b))
+                }
+                (Some(line_a), None) => {
+                    let (indent, _) = split_words(line_a);
+                    writeln!(writer, "{}[-{}-]", indent, &line_a[indent.len()..])
+                }
+                (None, Some(line_b)) => {
+                    let (indent, _) = split_words(line_b);
+                    writeln!(writer, "{}{{+{}+}}", indent, &line_b[indent.len()..])
+                }
+                (None, None) => unreachable!(),
+            }
+            .map_io_err(err_desc)?;
+        }
     }
     Ok(())
 }
@@ -162,17 +293,28 @@ fn write_hunk<W: Write>(
 pub fn unified_diff<T: AsRef<str> + PartialEq + Display, W: Write>(
     a: &[T],
     b: &[T],
+    writer: W,
+) -> Result<(), Error> {
+    unified_diff_with_options(a, b, &DiffOptions::default(), writer)
+}
+
+/// Compares two texts and writes their diff, formatted according to the given options, to the
+/// provided output stream.
+pub fn unified_diff_with_options<T: AsRef<str> + PartialEq + Display, W: Write>(
+    a: &[T],
+    b: &[T],
+    options: &DiffOptions,
     mut writer: W,
 ) -> Result<(), Error> {
     // Diff the two inputs and calculate the edit script.
     let edit_script = myers(a, b);
 
     // Turn the edit script into hunks in the unified format.
-    const CONTEXT_SIZE: usize = 3;
+    let context_size = options.context_size;
     let (mut context_begin, mut context_end) = (0, 0);
     let (mut pos_a, mut pos_b) = (1, 1);
     let (mut hunk_pos_a, mut hunk_len_a, mut hunk_pos_b, mut hunk_len_b) = (0, 0, 0, 0);
-    let mut hunk_data = Vec::new();
+    let mut hunk_edits = Vec::new();
 
     for edit in edit_script {
         match edit {
@@ -190,30 +332,33 @@ pub fn unified_diff<T: AsRef<str> + PartialEq + Display, W: Write>(
                 pos_b += 1;
 
                 // If handling a hunk, check if it should be closed off.
-                if !hunk_data.is_empty() && context_end - context_begin > 2 * CONTEXT_SIZE {
-                    for line in a.iter().skip(context_begin).take(CONTEXT_SIZE) {
-                        hunk_data.push(format!(" {}", line));
+                if !hunk_edits.is_empty() && context_end - context_begin > 2 * context_size {
+                    for index_a in context_begin..context_begin + context_size {
+                        hunk_edits.push(Edit::KeepA(index_a));
                     }
-                    hunk_len_a += CONTEXT_SIZE;
-                    hunk_len_b += CONTEXT_SIZE;
-                    context_begin += CONTEXT_SIZE;
+                    hunk_len_a += context_size;
+                    hunk_len_b += context_size;
+                    context_begin += context_size;
                     write_hunk(
+                        a,
+                        b,
                         hunk_pos_a,
                         hunk_len_a,
                         hunk_pos_b,
                         hunk_len_b,
-                        &hunk_data,
+                        &hunk_edits,
+                        options.word_diff,
                         writer.by_ref(),
                     )?;
-                    hunk_data.clear();
+                    hunk_edits.clear();
                 }
             }
 
             Edit::RemoveA(_) | Edit::InsertB(_) => {
                 // Open a new hunk if not already handling one.
-                if hunk_data.is_empty() {
-                    if context_end - context_begin > CONTEXT_SIZE {
-                        context_begin = context_end - CONTEXT_SIZE;
+                if hunk_edits.is_empty() {
+                    if context_end - context_begin > context_size {
+                        context_begin = context_end - context_size;
                     }
                     hunk_pos_a = pos_a - (context_end - context_begin);
                     hunk_len_a = 0;
@@ -229,19 +374,18 @@ pub fn unified_diff<T: AsRef<str> + PartialEq + Display, W: Write>(
                 }
 
                 // Add any accumulated context.
-                for line in a.iter().take(context_end).skip(context_begin) {
-                    hunk_data.push(format!(" {}", line));
+                for index_a in context_begin..context_end {
+                    hunk_edits.push(Edit::KeepA(index_a));
                 }
                 hunk_len_a += context_end - context_begin;
                 hunk_len_b += context_end - context_begin;
                 context_begin = context_end;
 
                 // Record the removed/added string.
-                if let Edit::RemoveA(index_a) = edit {
-                    hunk_data.push(format!("-{}", a[index_a]));
+                hunk_edits.push(edit);
+                if let Edit::RemoveA(_) = edit {
                     hunk_len_a += 1;
-                } else if let Edit::InsertB(index_b) = edit {
-                    hunk_data.push(format!("+{}", b[index_b]));
+                } else {
                     hunk_len_b += 1;
                 }
             }
@@ -249,21 +393,24 @@ pub fn unified_diff<T: AsRef<str> + PartialEq + Display, W: Write>(
     }
 
     // Close off the last hunk, if one is open.
-    if !hunk_data.is_empty() {
-        if context_end - context_begin > CONTEXT_SIZE {
-            context_end = context_begin + CONTEXT_SIZE;
+    if !hunk_edits.is_empty() {
+        if context_end - context_begin > context_size {
+            context_end = context_begin + context_size;
         }
-        for line in a.iter().take(context_end).skip(context_begin) {
-            hunk_data.push(format!(" {}", line));
+        for index_a in context_begin..context_end {
+            hunk_edits.push(Edit::KeepA(index_a));
         }
         hunk_len_a += context_end - context_begin;
         hunk_len_b += context_end - context_begin;
         write_hunk(
+            a,
+            b,
             hunk_pos_a,
             hunk_len_a,
             hunk_pos_b,
             hunk_len_b,
-            &hunk_data,
+            &hunk_edits,
+            options.word_diff,
             writer.by_ref(),
         )?;
     }
@@ -273,6 +420,213 @@ pub fn unified_diff<T: AsRef<str> + PartialEq + Display, W: Write>(
     Ok(())
 }
 
+/// Expands tabs in the given line to spaces, assuming tab stops every 8 columns.
+pub(crate) fn expand_tabs(line: &str) -> String {
+    let mut res = String::new();
+    let mut column = 0;
+    for ch in line.chars() {
+        if ch == '\t' {
+            let width = 8 - column % 8;
+            res.extend(iter::repeat_n(' ', width));
+            column += width;
+        } else {
+            res.push(ch);
+            column += 1;
+        }
+    }
+    res
+}
+
+/// Compares two texts and writes them side by side to the provided output stream.
+///
+/// Each output line shows a line of `a` in the left column and a line of `b` in the right column.
+/// The columns are separated by a marker that indicates whether the line is unchanged (` `),
+/// changed (`|`), removed (`<`) or added (`>`). A removed line is paired with an added line from
+/// the same change, if possible, and shown as changed.
+pub fn side_by_side_diff<T: AsRef<str> + PartialEq, W: Write>(
+    a: &[T],
+    b: &[T],
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write a side-by-side diff";
+
+    let a_expanded = a
+        .iter()
+        .map(|line| expand_tabs(line.as_ref()))
+        .collect::<Vec<_>>();
+    let b_expanded = b
+        .iter()
+        .map(|line| expand_tabs(line.as_ref()))
+        .collect::<Vec<_>>();
+    let width = a_expanded
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let write_row = |writer: &mut W, left: &str, marker: char, right: &str| {
+        let line = format!("{:width$} {} {}", left, marker, right);
+        writeln!(writer, "{}", line.trim_end()).map_io_err(err_desc)
+    };
+
+    let edit_script = myers(a, b);
+    let mut edit_idx = 0;
+    let mut index_b = 0;
+    while edit_idx < edit_script.len() {
+        if let Edit::KeepA(index_a) = edit_script[edit_idx] {
+            write_row(&mut writer, &a_expanded[index_a], ' ', &b_expanded[index_b])?;
+            index_b += 1;
+            edit_idx += 1;
+            continue;
+        }
+
+        // Collect the whole change and pair its removed and added lines.
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        while edit_idx < edit_script.len() {
+            match edit_script[edit_idx] {
+                Edit::KeepA(_) => break,
+                Edit::RemoveA(index_a) => removed.push(a_expanded[index_a].as_str()),
+                Edit::InsertB(insert_b) => {
+                    added.push(b_expanded[insert_b].as_str());
+                    index_b = insert_b + 1;
+                }
+            }
+            edit_idx += 1;
+        }
+        for i in 0..cmp::max(removed.len(), added.len()) {
+            match (removed.get(i), added.get(i)) {
+                (Some(left), Some(right)) => write_row(&mut writer, left, '|', right)?,
+                (Some(left), None) => write_row(&mut writer, left, '<', "")?,
+                (None, Some(right)) => write_row(&mut writer, "", '>', right)?,
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+
+    writer.flush().map_io_err(err_desc)?;
+
+    Ok(())
+}
+
+/// Escapes the characters with a special meaning in HTML in the given text.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            _ => res.push(ch),
+        }
+    }
+    res
+}
+
+/// Escapes the given text for use in a JSON string.
+pub(crate) fn escape_json(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", ch as u32)),
+            _ => res.push(ch),
+        }
+    }
+    res
+}
+
+/// Writes the beginning of a standalone HTML report with the given title to the provided output
+/// stream.
+pub(crate) fn write_html_header<W: Write>(title: &str, mut writer: W) -> Result<(), Error> {
+    let err_desc = "Failed to write an HTML header";
+
+    writeln!(
+        writer,
+        concat!(
+            "<!DOCTYPE html>\n",
+            "<html>\n",
+            "<head>\n",
+            "<meta charset=\"utf-8\">\n",
+            "<title>{}</title>\n",
+            "<style>\n",
+            "body {{ font-family: sans-serif; margin: 2em; }}\n",
+            "details {{ border: 1px solid #ccc; margin: 0.5em 0; padding: 0.5em; }}\n",
+            "summary {{ cursor: pointer; }}\n",
+            "table {{ border-collapse: collapse; }}\n",
+            "th, td {{ border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; }}\n",
+            "pre.diff {{ background: #f6f8fa; padding: 0.5em; }}\n",
+            "pre.diff .hunk {{ color: #0550ae; }}\n",
+            "pre.diff .del {{ color: #82071e; background: #ffebe9; }}\n",
+            "pre.diff .add {{ color: #116329; background: #dafbe1; }}\n",
+            "</style>\n",
+            "</head>\n",
+            "<body>\n",
+            "<h1>{}</h1>",
+        ),
+        escape_html(title),
+        escape_html(title)
+    )
+    .map_io_err(err_desc)
+}
+
+/// Writes the end of a standalone HTML report to the provided output stream.
+pub(crate) fn write_html_footer<W: Write>(mut writer: W) -> Result<(), Error> {
+    writeln!(writer, "</body>\n</html>").map_io_err("Failed to write an HTML footer")
+}
+
+/// Writes a one-line summary of a comparison with the given numbers of breaking, tolerated and
+/// unchanged exports to the provided output stream.
+pub(crate) fn write_compare_summary<W: Write>(
+    breaking: usize,
+    tolerated: usize,
+    unchanged: usize,
+    mut writer: W,
+) -> Result<(), Error> {
+    writeln!(
+        writer,
+        "kABI: {} breaking, {} tolerated, {} unchanged",
+        breaking, tolerated, unchanged
+    )
+    .map_io_err("Failed to write a comparison summary")
+}
+
+/// Writes lines of a unified diff as an HTML preformatted block to the provided output stream,
+/// highlighting hunk headers, removed lines and added lines.
+pub(crate) fn write_html_diff<S: AsRef<str>, W: Write>(
+    lines: &[S],
+    mut writer: W,
+) -> Result<(), Error> {
+    let err_desc = "Failed to write an HTML diff";
+
+    writeln!(writer, "<pre class=\"diff\">").map_io_err(err_desc)?;
+    for line in lines {
+        let line = line.as_ref();
+        let maybe_class = match line.chars().next() {
+            Some('@') => Some("hunk"),
+            Some('-') => Some("del"),
+            Some('+') => Some("add"),
+            _ => None,
+        };
+        match maybe_class {
+            Some(class) => writeln!(
+                writer,
+                "<span class=\"{}\">{}</span>",
+                class,
+                escape_html(line)
+            ),
+            None => writeln!(writer, "{}", escape_html(line)),
+        }
+        .map_io_err(err_desc)?;
+    }
+    writeln!(writer, "</pre>").map_io_err(err_desc)
+}
+
 // Rust implementation of the Salz's wildcard method:
 // https://github.com/richsalz/wildmat
 // Original code has been placed in the public domain.
@@ -339,7 +693,7 @@ fn do_match(mut text: &[char], mut p: &[char]) -> DoMatchResult {
                 p = &p[1..];
                 while p[0] != '\0' && p[0] != ']' {
                     // This next line requires a good C compiler.
-                    if if p[0] == '-' && p[1] != ']' {
+                    if if p[0] == '-' && p[1] != ']' && p[1] != '\0' {
                         p = &p[1..];
                         text[0] <= p[0] && text[0] >= last
                     } else {
@@ -350,6 +704,10 @@ fn do_match(mut text: &[char], mut p: &[char]) -> DoMatchResult {
                     last = p[0];
                     p = &p[1..];
                 }
+                if p[0] == '\0' {
+                    // Unterminated character class.
+                    return DoMatchResult::Abort;
+                }
                 if matched == reverse {
                     return DoMatchResult::False;
                 }
@@ -394,8 +752,11 @@ pub fn read_lines<R: Read>(reader: R) -> io::Result<Vec<String>> {
 pub enum Writer {
     Stdout(io::Stdout),
     File(BufWriter<PathFile>),
+    #[cfg(feature = "compression")]
+    Compressed(PathBuf, BufWriter<Compressor>),
     Buffer(Vec<u8>),
     NamedBuffer(PathBuf, Vec<u8>),
+    Colored(Box<ColorWriter<Writer>>),
 }
 
 impl Writer {
@@ -424,6 +785,30 @@ impl Writer {
         }
     }
 
+    /// Creates a new [`Writer`] that writes to the specified file, compressing the data if the
+    /// `compression` feature is enabled and the file has the `.gz`, `.xz` or `.zst` extension.
+    ///
+    /// The [`Writer::finish()`] method must be called to complete the compressed output.
+    pub fn new_compressed_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        #[cfg(feature = "compression")]
+        if let Some(tool) = compression_tool(path) {
+            let file = File::create(path).map_err(|err| {
+                Error::new_io(format!("Failed to create file '{}'", path.display()), err)
+            })?;
+            let compressor = Compressor::spawn(tool, file).map_err(|err| {
+                Error::new_io(format!("Failed to compress file '{}'", path.display()), err)
+            })?;
+            return Ok(Self::Compressed(
+                path.to_path_buf(),
+                BufWriter::new(compressor),
+            ));
+        }
+
+        Self::new_exact_file(path)
+    }
+
     /// Creates a new [`Writer`] that writes to an internal buffer.
     pub fn new_buffer() -> Self {
         Self::Buffer(Vec::new())
@@ -434,6 +819,44 @@ impl Writer {
         Self::NamedBuffer(path.as_ref().to_path_buf(), Vec::new())
     }
 
+    /// Wraps the writer to colorize the lines of a human-readable comparison report, according to
+    /// the given mode.
+    ///
+    /// In the auto mode, the output is colorized only if it is the standard output connected to
+    /// a terminal and the `NO_COLOR` environment variable is not set to a non-empty value.
+    pub fn with_color(self, mode: ColorMode) -> Self {
+        let enabled = match mode {
+            ColorMode::Auto => {
+                matches!(&self, Self::Stdout(stdout) if stdout.is_terminal())
+                    && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        };
+        if enabled {
+            Self::Colored(Box::new(ColorWriter::new(self)))
+        } else {
+            self
+        }
+    }
+
+    /// Flushes all written data and, for compressed output, waits for the compression to complete.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.flush().map_io_err("Failed to flush the output")?;
+
+        #[cfg(feature = "compression")]
+        if let Self::Compressed(path, writer) = self {
+            let mut compressor = writer
+                .into_inner()
+                .map_err(|err| Error::new_io("Failed to flush the output", err.into_error()))?;
+            compressor.finish().map_err(|err| {
+                Error::new_io(format!("Failed to compress file '{}'", path.display()), err)
+            })?;
+        }
+
+        Ok(())
+    }
+
     /// Obtains the internal buffer if the writer is of the [`Writer::Buffer`] type.
     pub fn into_inner_vec(self) -> Vec<u8> {
         match self {
@@ -456,8 +879,11 @@ impl Write for Writer {
         match self {
             Self::Stdout(stdout) => stdout.write(buf),
             Self::File(file) => file.write(buf),
+            #[cfg(feature = "compression")]
+            Self::Compressed(_, compressor) => compressor.write(buf),
             Self::Buffer(vec) => vec.write(buf),
             Self::NamedBuffer(_, vec) => vec.write(buf),
+            Self::Colored(writer) => writer.write(buf),
         }
     }
 
@@ -465,9 +891,132 @@ impl Write for Writer {
         match self {
             Self::Stdout(stdout) => stdout.flush(),
             Self::File(file) => file.flush(),
+            #[cfg(feature = "compression")]
+            Self::Compressed(_, compressor) => compressor.flush(),
             Self::Buffer(vec) => vec.flush(),
             Self::NamedBuffer(_, vec) => vec.flush(),
+            Self::Colored(writer) => writer.flush(),
+        }
+    }
+}
+
+/// The mode of colorizing human-readable output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorMode {
+    /// Colorize the output only if it is written to a terminal.
+    #[default]
+    Auto,
+    /// Always colorize the output.
+    Always,
+    /// Never colorize the output.
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = Error;
+
+    /// Obtains a [`ColorMode`] matching the given mode, specified as a string.
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized color mode '{}'",
+                mode
+            ))),
+        }
+    }
+}
+
+// ANSI escape sequences used to colorize the output.
+const STYLE_ADDED: &str = "\x1b[32m";
+const STYLE_REMOVED: &str = "\x1b[31m";
+const STYLE_TOLERATED: &str = "\x1b[2m";
+const STYLE_HUNK: &str = "\x1b[36m";
+pub(crate) const STYLE_RESET: &str = "\x1b[0m";
+
+/// Determines the style of a single line of a human-readable comparison report.
+///
+/// Diff lines are styled according to their prefix, also when indented by spaces. Other lines
+/// describe a change, which is dimmed if tolerated, or otherwise styled according to whether it is
+/// an addition or removal.
+pub(crate) fn line_style(line: &[u8]) -> Option<&'static str> {
+    let Ok(line) = str::from_utf8(line) else {
+        return None;
+    };
+
+    let trimmed = line.trim_start_matches(' ');
+    if trimmed.starts_with("@@ ") {
+        Some(STYLE_HUNK)
+    } else if trimmed.starts_with('+') {
+        Some(STYLE_ADDED)
+    } else if trimmed.starts_with('-') {
+        Some(STYLE_REMOVED)
+    } else if line.contains(" (tolerated ") || line.contains(" (implicitly tolerated)") {
+        Some(STYLE_TOLERATED)
+    } else if line.ends_with(" has been added") {
+        Some(STYLE_ADDED)
+    } else if line.ends_with(" has been removed") {
+        Some(STYLE_REMOVED)
+    } else {
+        None
+    }
+}
+
+/// A writer that colorizes the lines of a human-readable comparison report using ANSI escape
+/// sequences.
+///
+/// The data is buffered until a complete line is available, so each line can be styled as a whole.
+/// An incomplete last line is written unstyled when the writer is flushed.
+pub struct ColorWriter<W: Write> {
+    inner: W,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> ColorWriter<W> {
+    /// Creates a new [`ColorWriter`] that writes the colorized output to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Flushes the writer and returns the inner writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for ColorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        let mut start = 0;
+        while let Some(len) = self.pending[start..].iter().position(|&b| b == b'\n') {
+            let line = &self.pending[start..start + len];
+            match line_style(line) {
+                Some(style) => {
+                    self.inner.write_all(style.as_bytes())?;
+                    self.inner.write_all(line)?;
+                    self.inner.write_all(STYLE_RESET.as_bytes())?;
+                    self.inner.write_all(b"\n")?;
+                }
+                None => self.inner.write_all(&self.pending[start..=start + len])?,
+            }
+            start += len + 1;
         }
+        self.pending.drain(..start);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.pending)?;
+        self.pending.clear();
+        self.inner.flush()
     }
 }
 
@@ -534,13 +1083,339 @@ impl WriteGenerator<Writer> for &mut DirectoryWriter {
     }
 }
 
+/// The size of a block in a tar archive.
+const TAR_BLOCK_SIZE: usize = 512;
+
+/// A factory for writing multiple files into a tar archive, streamed to the provided output.
+///
+/// Each file is collected in memory until it is closed, at which point it is appended to the
+/// archive as a regular file in the ustar format. The entries appear in the order in which they are
+/// closed. All metadata, such as the modification time and ownership, is fixed to make the archive
+/// reproducible.
+///
+/// Any error when writing an entry is recorded and reported by [`TarWriter::finish()`].
+pub struct TarWriter<W: Write> {
+    inner: W,
+    maybe_err: Option<Error>,
+}
+
+impl<W: Write> TarWriter<W> {
+    /// Creates a new [`TarWriter`] that writes the archive to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            maybe_err: None,
+        }
+    }
+
+    /// Terminates the archive and returns the underlying output, or the first error that occurred
+    /// when writing the archive.
+    pub fn finish(mut self) -> Result<W, Error> {
+        if let Some(err) = self.maybe_err {
+            return Err(err);
+        }
+
+        let err_desc = "Failed to write the end of the tar archive";
+        self.inner
+            .write_all(&[0; 2 * TAR_BLOCK_SIZE])
+            .map_io_err(err_desc)?;
+        self.inner.flush().map_io_err(err_desc)?;
+        Ok(self.inner)
+    }
+
+    /// Appends a regular file with the given path and data to the archive.
+    fn append(&mut self, path: &Path, data: &[u8]) -> Result<(), Error> {
+        let header = tar_header(path, data.len())?;
+        let padding = (TAR_BLOCK_SIZE - data.len() % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+
+        let err_desc = format!("Failed to write '{}' to the tar archive", path.display());
+        self.inner.write_all(&header).map_io_err(&err_desc)?;
+        self.inner.write_all(data).map_io_err(&err_desc)?;
+        self.inner
+            .write_all(&[0; TAR_BLOCK_SIZE][..padding])
+            .map_io_err(&err_desc)
+    }
+}
+
+impl<W: Write> WriteGenerator<Writer> for &mut TarWriter<W> {
+    fn create<P: AsRef<Path>>(&mut self, sub_path: P) -> Result<Writer, Error> {
+        Ok(Writer::new_named_buffer(sub_path))
+    }
+
+    fn close(&mut self, writer: Writer) {
+        let (path, vec) = writer.into_inner_path_vec();
+        if self.maybe_err.is_none()
+            && let Err(err) = self.append(&path, &vec)
+        {
+            self.maybe_err = Some(err);
+        }
+    }
+}
+
+/// Creates a ustar header block for a regular file with the given path and size.
+fn tar_header(path: &Path, size: usize) -> Result<[u8; TAR_BLOCK_SIZE], Error> {
+    let path_str = path.to_str().unwrap_or("");
+    let too_long = || {
+        Error::new_parse(format!(
+            "The path '{}' cannot be stored in a tar archive",
+            path.display()
+        ))
+    };
+
+    // Paths longer than 100 bytes are split into a prefix and a name at a directory separator.
+    let (prefix, name) = if path_str.len() <= 100 {
+        ("", path_str)
+    } else {
+        path_str
+            .char_indices()
+            .filter(|&(idx, c)| c == '/' && idx <= 155 && path_str.len() - idx - 1 <= 100)
+            .map(|(idx, _)| (&path_str[..idx], &path_str[idx + 1..]))
+            .next()
+            .ok_or_else(too_long)?
+    };
+    if name.is_empty() {
+        return Err(too_long());
+    }
+
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+    let mut set_field = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    set_field(0, name.as_bytes());
+    set_field(100, b"0000644\0");
+    set_field(108, b"0000000\0");
+    set_field(116, b"0000000\0");
+    set_field(124, format!("{:011o}\0", size).as_bytes());
+    set_field(136, b"00000000000\0");
+    set_field(148, b"        ");
+    set_field(156, b"0");
+    set_field(257, b"ustar\0");
+    set_field(263, b"00");
+    set_field(345, prefix.as_bytes());
+
+    // The checksum is computed with the checksum field itself filled with spaces.
+    let checksum = header.iter().map(|&byte| byte as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+    Ok(header)
+}
+
+/// Returns whether the given path refers to a tar archive, which is the case if it has the `.tar`
+/// extension, optionally followed by a compression extension recognized by [`PathFile`].
+pub fn is_tar_path(path: &Path) -> bool {
+    let path = strip_compression_ext(path).unwrap_or_else(|| path.to_path_buf());
+    path.extension().is_some_and(|ext| ext == "tar")
+}
+
+/// Reads all regular files from a tar archive and returns their paths and data, in the order in
+/// which they appear in the archive.
+///
+/// The ustar, pax and GNU formats are recognized, including long path names stored in pax
+/// extended headers and GNU long name entries. A leading `./` is removed from all paths. Entries
+/// other than regular files, such as directories, are skipped.
+pub fn read_tar<R: Read>(mut reader: R) -> Result<Vec<(PathBuf, Vec<u8>)>, Error> {
+    let err_desc = "Failed to read the tar archive";
+    let mut entries = Vec::new();
+    let mut maybe_long_path = None;
+
+    loop {
+        // Read the next header. The archive ends with a zero block, but tolerate a missing one.
+        let mut header = [0u8; TAR_BLOCK_SIZE];
+        if !read_tar_block(&mut reader, &mut header).map_err(|err| Error::new_io(err_desc, err))? {
+            break;
+        }
+        if header.iter().all(|&byte| byte == 0) {
+            break;
+        }
+
+        let checksum = header
+            .iter()
+            .enumerate()
+            .map(|(i, &byte)| if (148..156).contains(&i) { b' ' } else { byte } as u32)
+            .sum::<u32>();
+        if parse_tar_number(&header[148..156])? != checksum as u64 {
+            return Err(Error::new_parse("Invalid tar header checksum"));
+        }
+
+        let size = usize::try_from(parse_tar_number(&header[124..136])?)
+            .map_err(|_| Error::new_parse("Invalid tar entry size"))?;
+        let mut data = vec![0u8; size];
+        reader.read_exact(&mut data).map_io_err(err_desc)?;
+        let padding = (TAR_BLOCK_SIZE - size % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+        reader
+            .read_exact(&mut [0u8; TAR_BLOCK_SIZE][..padding])
+            .map_io_err(err_desc)?;
+
+        match header[156] {
+            b'0' | b'\0' | b'7' => {
+                let path = match maybe_long_path.take() {
+                    Some(path) => path,
+                    None => tar_header_path(&header),
+                };
+                let path = path.trim_start_matches("./");
+                entries.push((PathBuf::from(path), data));
+            }
+            // A GNU long name of the next entry.
+            b'L' => {
+                let end = data
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(data.len());
+                maybe_long_path = Some(String::from_utf8_lossy(&data[..end]).into_owned());
+            }
+            // A pax extended header of the next entry, consisting of "LEN KEY=VALUE\n" records.
+            b'x' => {
+                let records = String::from_utf8_lossy(&data);
+                for record in records.lines() {
+                    if let Some((_, key_value)) = record.split_once(' ')
+                        && let Some(path) = key_value.strip_prefix("path=")
+                    {
+                        maybe_long_path = Some(path.to_string());
+                    }
+                }
+            }
+            // Any other entry, for instance a directory, is skipped.
+            _ => maybe_long_path = None,
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Reads one tar block, returning `Ok(false)` if the end of the input is reached before any data.
+fn read_tar_block<R: Read>(reader: &mut R, block: &mut [u8; TAR_BLOCK_SIZE]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < block.len() {
+        match reader.read(&mut block[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(size) => filled += size,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+/// Parses an octal number stored in a tar header field.
+fn parse_tar_number(field: &[u8]) -> Result<u64, Error> {
+    let text = String::from_utf8_lossy(field);
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8)
+        .map_err(|_| Error::new_parse(format!("Invalid tar header number '{}'", text)))
+}
+
+/// Returns the path stored in the name and, for the ustar format, the prefix fields of a tar
+/// header.
+fn tar_header_path(header: &[u8; TAR_BLOCK_SIZE]) -> String {
+    let field = |offset: usize, len: usize| {
+        let raw = &header[offset..offset + len];
+        let end = raw.iter().position(|&byte| byte == 0).unwrap_or(len);
+        String::from_utf8_lossy(&raw[..end]).into_owned()
+    };
+
+    let name = field(0, 100);
+    // The GNU format uses the prefix field for other purposes and has a different magic.
+    let prefix = if &header[257..263] == b"ustar\0" {
+        field(345, 155)
+    } else {
+        String::new()
+    };
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+/// A [`Write`] wrapper that computes a SHA-256 digest of all written data.
+pub struct DigestWriter<W: Write> {
+    inner: W,
+    sub_path: PathBuf,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A [`WriteGenerator`] wrapper that records a SHA-256 digest of each written file, allowing to
+/// produce a manifest of the output.
+pub struct ManifestWriter<WG> {
+    inner: WG,
+    digests: BTreeMap<PathBuf, [u8; 32]>,
+}
+
+impl<WG> ManifestWriter<WG> {
+    /// Creates a new [`ManifestWriter`] that forwards all files to `inner`.
+    pub fn new(inner: WG) -> Self {
+        Self {
+            inner,
+            digests: BTreeMap::new(),
+        }
+    }
+
+    /// Writes the manifest of all closed files to the specified file.
+    pub fn write_manifest<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_manifest_buffer(Writer::new_file(path)?)
+    }
+
+    /// Writes the manifest of all closed files to the provided output stream.
+    ///
+    /// Each line contains the SHA-256 digest of a file and its path, in the format used by
+    /// `sha256sum`. The lines are sorted by the path.
+    pub fn write_manifest_buffer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let err_desc = "Failed to write a manifest record";
+
+        for (sub_path, digest) in &self.digests {
+            writeln!(writer, "{}  {}", sha256::to_hex(digest), sub_path.display())
+                .map_io_err(err_desc)?;
+        }
+
+        writer.flush().map_io_err(err_desc)
+    }
+}
+
+impl<W: Write, WG: WriteGenerator<W>> WriteGenerator<DigestWriter<W>> for &mut ManifestWriter<WG> {
+    fn create<P: AsRef<Path>>(&mut self, sub_path: P) -> Result<DigestWriter<W>, Error> {
+        Ok(DigestWriter {
+            inner: self.inner.create(&sub_path)?,
+            sub_path: sub_path.as_ref().to_path_buf(),
+            hasher: Sha256::new(),
+        })
+    }
+
+    fn close(&mut self, writer: DigestWriter<W>) {
+        self.digests.insert(writer.sub_path, writer.hasher.finish());
+        self.inner.close(writer.inner);
+    }
+}
+
 /// A collection of shell wildcard patterns used to filter symbol or file names.
-#[derive(Debug, Default, Eq, PartialEq)]
+///
+/// Patterns prefixed with '!' are negated. A name matches the filter if it matches at least one
+/// regular pattern, or if there are only negated patterns, and it matches no negated pattern.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Filter {
     // Literal patterns.
     literals: HashSet<String>,
     // Wildcard patterns.
     wildcards: Vec<String>,
+    // Negated literal patterns.
+    negated_literals: HashSet<String>,
+    // Negated wildcard patterns.
+    negated_wildcards: Vec<String>,
 }
 
 impl Filter {
@@ -549,6 +1424,8 @@ impl Filter {
         Self {
             literals: HashSet::new(),
             wildcards: Vec::new(),
+            negated_literals: HashSet::new(),
+            negated_wildcards: Vec::new(),
         }
     }
 
@@ -574,7 +1451,42 @@ impl Filter {
         path: P,
         reader: R,
     ) -> Result<(), Error> {
+        self.load_buffer_impl(path.as_ref(), reader, false)
+    }
+
+    /// Loads exclusion patterns from the specified file.
+    ///
+    /// See [`Filter::load_exclusions_buffer()`] for the details.
+    pub fn load_exclusions<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
         let path = path.as_ref();
+
+        let file = PathFile::open(path).map_err(|err| {
+            Error::new_io(format!("Failed to open file '{}'", path.display()), err)
+        })?;
+
+        self.load_exclusions_buffer(path, file)
+    }
+
+    /// Loads exclusion patterns from the specified reader.
+    ///
+    /// The data has the same format as for [`Filter::load_buffer()`], but each pattern is added as
+    /// a negated one. Patterns prefixed with '!' are rejected.
+    pub fn load_exclusions_buffer<P: AsRef<Path>, R: Read>(
+        &mut self,
+        path: P,
+        reader: R,
+    ) -> Result<(), Error> {
+        self.load_buffer_impl(path.as_ref(), reader, true)
+    }
+
+    /// Loads filter data from the specified reader, optionally adding all patterns as negated
+    /// ones.
+    fn load_buffer_impl<R: Read>(
+        &mut self,
+        path: &Path,
+        reader: R,
+        exclusions: bool,
+    ) -> Result<(), Error> {
         debug!("Loading filter data from '{}'", path.display());
 
         // Read all content from the file.
@@ -585,47 +1497,90 @@ impl Filter {
 
         // Validate the patterns, reject empty ones.
         for (line_idx, line) in lines.iter().enumerate() {
-            if line.is_empty() {
-                return Err(Error::new_parse_format(
+            let (pattern, col) = match line.strip_prefix('!') {
+                Some(_) if exclusions => {
+                    return Err(Error::new_parse_format_at(
+                        "Unexpected negated pattern in an exclusion list",
+                        path,
+                        line_idx + 1,
+                        line,
+                        0,
+                    ));
+                }
+                Some(pattern) => (pattern, 1),
+                None => (line.as_str(), 0),
+            };
+            if pattern.is_empty() {
+                return Err(Error::new_parse_format_at(
                     "Expected a pattern",
                     path,
                     line_idx + 1,
                     line,
+                    col,
                 ));
             }
         }
 
         // Insert the new patterns.
         for line in lines {
-            if line
-                .chars()
-                .any(|x| x == '\\' || x == '?' || x == '*' || x == '[')
-            {
-                self.wildcards.push(line);
-            } else {
-                self.literals.insert(line);
+            match line.strip_prefix('!') {
+                Some(pattern) => self.insert(pattern.to_string(), true),
+                None => self.insert(line, exclusions),
             }
         }
 
         Ok(())
     }
 
-    /// Checks if the given text matches any of the filter patterns.
-    pub fn matches(&self, name: &str) -> bool {
-        if self.literals.contains(name) {
-            return true;
-        }
+    /// Adds a single pattern, such as one specified on the command line.
+    ///
+    /// The pattern is taken as is, a '!' prefix has no special meaning.
+    pub fn add_pattern(&mut self, pattern: &str) {
+        self.insert(pattern.to_string(), false);
+    }
 
-        for pattern in &self.wildcards {
-            if matches_wildcard(name, pattern) {
-                return true;
-            }
+    /// Adds a single exclusion pattern, such as one specified on the command line.
+    pub fn add_exclusion(&mut self, pattern: &str) {
+        self.insert(pattern.to_string(), true);
+    }
+
+    /// Inserts a pattern into the literal or wildcard patterns, optionally as a negated one.
+    fn insert(&mut self, pattern: String, negated: bool) {
+        let (literals, wildcards) = if negated {
+            (&mut self.negated_literals, &mut self.negated_wildcards)
+        } else {
+            (&mut self.literals, &mut self.wildcards)
+        };
+        if pattern
+            .chars()
+            .any(|x| x == '\\' || x == '?' || x == '*' || x == '[')
+        {
+            wildcards.push(pattern);
+        } else {
+            literals.insert(pattern);
         }
+    }
+
+    /// Checks if the given text matches the filter.
+    pub fn matches(&self, name: &str) -> bool {
+        let included = if self.literals.is_empty() && self.wildcards.is_empty() {
+            !self.negated_literals.is_empty() || !self.negated_wildcards.is_empty()
+        } else {
+            matches_patterns(&self.literals, &self.wildcards, name)
+        };
 
-        false
+        included && !matches_patterns(&self.negated_literals, &self.negated_wildcards, name)
     }
 }
 
+/// Checks if the given text matches any of the literal or wildcard patterns.
+fn matches_patterns(literals: &HashSet<String>, wildcards: &[String], name: &str) -> bool {
+    literals.contains(name)
+        || wildcards
+            .iter()
+            .any(|pattern| matches_wildcard(name, pattern))
+}
+
 /// Checks if the given text matches any of the filter patterns. If the filter is `None`, the
 /// function always returns `true`.
 pub fn matches_filter(maybe_filter: Option<&Filter>, name: &str) -> bool {
@@ -0,0 +1,89 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+
+/// Writes the given data through a [`ColorWriter`] and returns the colorized output.
+fn colorize(data: &str) -> String {
+    let mut writer = ColorWriter::new(Vec::new());
+    writer.write_all(data.as_bytes()).unwrap();
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+#[test]
+fn color_mode() {
+    // Check that color modes are recognized by their names.
+    assert_eq!("auto".parse::<ColorMode>().unwrap(), ColorMode::Auto);
+    assert_eq!("always".parse::<ColorMode>().unwrap(), ColorMode::Always);
+    assert_eq!("never".parse::<ColorMode>().unwrap(), ColorMode::Never);
+    assert!("yes".parse::<ColorMode>().is_err());
+}
+
+#[test]
+fn color_changes() {
+    // Check that added, removed and tolerated changes are colorized.
+    let output = colorize(concat!(
+        "Export 'foo' has been added\n",
+        "Export 'bar' has been removed\n",
+        "Export 'baz' has been added (implicitly tolerated)\n",
+        "Export 'qux' has been removed (tolerated by rule test.severities:1 'qux PASS')\n",
+        "Export 'quux' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+    ));
+    assert_eq!(
+        output,
+        concat!(
+            "\x1b[32mExport 'foo' has been added\x1b[0m\n",
+            "\x1b[31mExport 'bar' has been removed\x1b[0m\n",
+            "\x1b[2mExport 'baz' has been added (implicitly tolerated)\x1b[0m\n",
+            "\x1b[2mExport 'qux' has been removed (tolerated by rule test.severities:1 'qux PASS')\x1b[0m\n",
+            "Export 'quux' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+        )
+    );
+}
+
+#[test]
+fn color_diff() {
+    // Check that diff hunks are colorized, also when indented.
+    let output = colorize(concat!(
+        "because of a changed 's#foo':\n",
+        "@@ -1,3 +1,3 @@\n",
+        " struct foo {\n",
+        "-\tint a;\n",
+        "+\tlong a;\n",
+        "  @@ -1,1 +1,1 @@\n",
+        "  -int bar ( )\n",
+        "  +long bar ( )\n",
+    ));
+    assert_eq!(
+        output,
+        concat!(
+            "because of a changed 's#foo':\n",
+            "\x1b[36m@@ -1,3 +1,3 @@\x1b[0m\n",
+            " struct foo {\n",
+            "\x1b[31m-\tint a;\x1b[0m\n",
+            "\x1b[32m+\tlong a;\x1b[0m\n",
+            "\x1b[36m  @@ -1,1 +1,1 @@\x1b[0m\n",
+            "\x1b[31m  -int bar ( )\x1b[0m\n",
+            "\x1b[32m  +long bar ( )\x1b[0m\n",
+        )
+    );
+}
+
+#[test]
+fn color_split_writes() {
+    // Check that lines written in several pieces are colorized as a whole, and that an incomplete
+    // last line is written unstyled.
+    let mut writer = ColorWriter::new(Vec::new());
+    writer.write_all(b"Export 'foo' has ").unwrap();
+    writer.write_all(b"been added\n-int").unwrap();
+    writer.write_all(b" a;\n+long").unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(
+        output,
+        concat!(
+            "\x1b[32mExport 'foo' has been added\x1b[0m\n",
+            "\x1b[31m-int a;\x1b[0m\n",
+            "+long", //
+        )
+    );
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: GPL-2.0-or-later
 
 use super::*;
+use crate::assert_ok;
 
 #[test]
 fn myers_trivial_empty() {
@@ -160,3 +161,98 @@ fn myers_keep_subsequent() {
         ]
     );
 }
+
+#[test]
+fn unified_diff_context_size() {
+    // Check that the diff includes only the requested number of context lines around a change.
+    let a = ["A", "B", "C", "D", "E"];
+    let b = ["A", "B", "X", "D", "E"];
+    let mut out = Vec::new();
+    let options = DiffOptions {
+        context_size: 1,
+        ..Default::default()
+    };
+    let result = unified_diff_with_options(&a, &b, &options, &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        concat!(
+            "@@ -2,3 +2,3 @@\n",
+            " B\n",
+            "-C\n",
+            "+X\n",
+            " D\n", //
+        )
+    );
+}
+
+#[test]
+fn unified_diff_word() {
+    // Check that the word diff pairs removed and added lines and marks the changed words.
+    let a = ["struct foo {", "\tint a;", "}", "int bar ( int , long )"];
+    let b = [
+        "struct foo {",
+        "\tlong a;",
+        "\tint b;",
+        "}",
+        "int bar ( int , int )",
+    ];
+    let mut out = Vec::new();
+    let options = DiffOptions {
+        word_diff: true,
+        ..Default::default()
+    };
+    let result = unified_diff_with_options(&a, &b, &options, &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        concat!(
+            "@@ -1,4 +1,5 @@\n",
+            "struct foo {\n",
+            "\t[-int-]{+long+} a;\n",
+            "\t{+int b;+}\n",
+            "}\n",
+            "int bar ( int , [-long-]{+int+} )\n", //
+        )
+    );
+}
+
+#[test]
+fn side_by_side_diff_basic() {
+    // Check that the side-by-side diff pairs changed lines and marks removed and added ones.
+    let a = ["struct foo {", "\tint a;", "\tint b;", "}"];
+    let b = ["struct foo {", "\tlong a;", "}", "int bar;"];
+    let mut out = Vec::new();
+    let result = side_by_side_diff(&a, &b, &mut out);
+    assert_ok!(result);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        concat!(
+            "struct foo {     struct foo {\n",
+            "        int a; |         long a;\n",
+            "        int b; <\n",
+            "}                }\n",
+            "               > int bar;\n", //
+        )
+    );
+}
+
+#[test]
+fn escape_html_special() {
+    // Check that characters with a special meaning in HTML are escaped.
+    assert_eq!(
+        escape_html("<a href=\"x\">'&'</a>"),
+        "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+    );
+    assert_eq!(escape_html("int foo;"), "int foo;");
+}
+
+#[test]
+fn escape_json_special() {
+    // Check that characters with a special meaning in JSON strings are escaped.
+    assert_eq!(
+        escape_json("'a\\b' \"c\"\n\t\u{1}"),
+        "'a\\\\b' \\\"c\\\"\\n\\t\\u0001"
+    );
+    assert_eq!(escape_json("int foo;"), "int foo;");
+}
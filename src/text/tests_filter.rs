@@ -20,6 +20,8 @@ fn read_literal_pattern() {
         Filter {
             literals: HashSet::from(["abc".to_string(), "ABC".to_string(), "_09".to_string()]),
             wildcards: vec![],
+            negated_literals: HashSet::new(),
+            negated_wildcards: vec![],
         }
     );
 }
@@ -40,6 +42,8 @@ fn read_wildcard_pattern() {
         Filter {
             literals: HashSet::new(),
             wildcards: string_vec!["\\abc", "a?bc", "ab*c", "abc["],
+            negated_literals: HashSet::new(),
+            negated_wildcards: vec![],
         }
     );
 }
@@ -58,8 +62,98 @@ fn read_empty_record() {
         result,
         concat!(
             "Expected a pattern\n",
-            " test.filter:2\n",
-            " | ", //
+            " test.filter:2:1\n",
+            " | \n",
+            " | ^", //
+        ),
+    );
+    assert_eq!(filter, Filter::new());
+}
+
+#[test]
+fn read_negated_pattern() {
+    // Check that patterns prefixed with '!' are considered as negated.
+    let mut filter = Filter::new();
+    let result = filter.load_buffer(
+        "test.filter",
+        bytes!(
+            "abc\n", "!abd\n", "!ab*\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        filter,
+        Filter {
+            literals: HashSet::from(["abc".to_string()]),
+            wildcards: vec![],
+            negated_literals: HashSet::from(["abd".to_string()]),
+            negated_wildcards: string_vec!["ab*"],
+        }
+    );
+}
+
+#[test]
+fn read_empty_negated_record() {
+    // Check that negated records without a pattern are rejected when reading a filter file.
+    let mut filter = Filter::new();
+    let result = filter.load_buffer(
+        "test.filter",
+        bytes!(
+            "foo\n", "!\n", "bar\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Expected a pattern\n",
+            " test.filter:2:2\n",
+            " | !\n",
+            " |  ^", //
+        ),
+    );
+    assert_eq!(filter, Filter::new());
+}
+
+#[test]
+fn read_exclusions() {
+    // Check that patterns loaded as exclusions are considered as negated.
+    let mut filter = Filter::new();
+    let result = filter.load_exclusions_buffer(
+        "test.filter",
+        bytes!(
+            "kvm_*\n",
+            "staging_foo\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert_eq!(
+        filter,
+        Filter {
+            literals: HashSet::new(),
+            wildcards: vec![],
+            negated_literals: HashSet::from(["staging_foo".to_string()]),
+            negated_wildcards: string_vec!["kvm_*"],
+        }
+    );
+}
+
+#[test]
+fn read_negated_exclusion() {
+    // Check that negated patterns are rejected when reading exclusions.
+    let mut filter = Filter::new();
+    let result = filter.load_exclusions_buffer(
+        "test.filter",
+        bytes!(
+            "foo\n", "!bar\n", //
+        ),
+    );
+    assert_parse_err!(
+        result,
+        concat!(
+            "Unexpected negated pattern in an exclusion list\n",
+            " test.filter:2:1\n",
+            " | !bar\n",
+            " | ^", //
         ),
     );
     assert_eq!(filter, Filter::new());
@@ -94,3 +188,47 @@ fn matches_wildcard_pattern() {
     assert!(filter.matches("abc"));
     assert!(!filter.matches("Xbc"));
 }
+
+#[test]
+fn matches_negated_pattern() {
+    // Check that a negated pattern excludes names matched by the regular patterns.
+    let mut filter = Filter::new();
+    let result = filter.load_buffer(
+        "test.filter",
+        bytes!(
+            "a*\n", "!ab*\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert!(filter.matches("acd"));
+    assert!(!filter.matches("abc"));
+    assert!(!filter.matches("Xbc"));
+}
+
+#[test]
+fn matches_only_negated_patterns() {
+    // Check that a filter with only negated patterns matches everything else.
+    let mut filter = Filter::new();
+    let result = filter.load_exclusions_buffer(
+        "test.filter",
+        bytes!(
+            "kvm_*\n",
+            "staging_foo\n", //
+        ),
+    );
+    assert_ok!(result);
+    assert!(filter.matches("foo"));
+    assert!(!filter.matches("kvm_init"));
+    assert!(!filter.matches("staging_foo"));
+}
+
+#[test]
+fn matches_added_patterns() {
+    // Check that patterns can be added to a filter individually.
+    let mut filter = Filter::new();
+    filter.add_pattern("a*");
+    filter.add_exclusion("abc");
+    assert!(filter.matches("acd"));
+    assert!(!filter.matches("abc"));
+    assert!(!filter.matches("Xbc"));
+}
@@ -0,0 +1,29 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::assert_ok;
+
+#[test]
+fn manifest_sorted() {
+    // Check that the manifest lists the digests of all closed files, sorted by their path.
+    let mut dir_writer = DirectoryWriter::new_buffer("split");
+    let mut manifest_writer = ManifestWriter::new(&mut dir_writer);
+    for (name, data) in [("foo", "foo\n"), ("bar", "bar\n")] {
+        let mut writer = (&mut manifest_writer).create(name).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+        (&mut manifest_writer).close(writer);
+    }
+    let mut out = Vec::new();
+    let result = manifest_writer.write_manifest_buffer(&mut out);
+    assert_ok!(result);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        concat!(
+            "7d865e959b2466918c9863afca942d0fb89d7c9ac0c99bafc3749504ded97730  bar\n",
+            "b5bb9d8014a0f9b1d61e21e796d78dccdf1352f23cd32812f4850b878ae4944c  foo\n", //
+        )
+    );
+    let files = dir_writer.into_inner_map();
+    assert_eq!(files[Path::new("split/foo")], b"foo\n");
+}
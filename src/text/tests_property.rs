@@ -0,0 +1,201 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::assert_ok;
+
+/// A simple deterministic xorshift generator of pseudo-random test inputs.
+struct TestRng(u64);
+
+impl TestRng {
+    /// Returns the next pseudo-random number.
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a pseudo-random number in the range `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Returns a string of up to `max_len` characters randomly chosen from `alphabet`.
+    fn string(&mut self, alphabet: &[char], max_len: usize) -> String {
+        let len = self.below(max_len + 1);
+        (0..len)
+            .map(|_| alphabet[self.below(alphabet.len())])
+            .collect()
+    }
+}
+
+/// Applies a diff in the unified format, as produced by [`unified_diff()`], to `a` and returns
+/// the result, or panics if the diff doesn't apply.
+fn apply_unified_diff(a: &[String], diff: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut pos_a = 0;
+    let mut lines = diff.lines().peekable();
+    while let Some(header) = lines.next() {
+        // Parse the hunk header in the form `@@ -<pos_a>,<len_a> +<pos_b>,<len_b> @@`.
+        let ranges = header
+            .strip_prefix("@@ -")
+            .and_then(|rest| rest.strip_suffix(" @@"))
+            .unwrap_or_else(|| panic!("invalid hunk header '{}'", header));
+        let (range_a, range_b) = ranges.split_once(" +").unwrap();
+        let parse_range = |range: &str| {
+            let (pos, len) = range.split_once(',').unwrap();
+            (pos.parse::<usize>().unwrap(), len.parse::<usize>().unwrap())
+        };
+        let (hunk_pos_a, hunk_len_a) = parse_range(range_a);
+        let (hunk_pos_b, hunk_len_b) = parse_range(range_b);
+
+        // Copy the unchanged lines before the hunk.
+        assert!(
+            hunk_pos_a > pos_a,
+            "hunk '{}' overlaps the previous one",
+            header
+        );
+        result.extend_from_slice(&a[pos_a..hunk_pos_a - 1]);
+        pos_a = hunk_pos_a - 1;
+        assert_eq!(
+            result.len() + 1,
+            hunk_pos_b,
+            "hunk '{}' is misplaced",
+            header
+        );
+
+        // Apply the hunk.
+        let (mut len_a, mut len_b) = (0, 0);
+        while let Some(line) = lines.next_if(|line| !line.starts_with("@@ ")) {
+            let (op, text) = line.split_at(1);
+            if op == " " || op == "-" {
+                assert_eq!(a[pos_a], text, "hunk '{}' doesn't apply", header);
+                pos_a += 1;
+                len_a += 1;
+            }
+            if op == " " || op == "+" {
+                result.push(text.to_string());
+                len_b += 1;
+            }
+        }
+        assert_eq!((len_a, len_b), (hunk_len_a, hunk_len_b));
+    }
+    result.extend_from_slice(&a[pos_a..]);
+    result
+}
+
+/// Checks whether the given text matches the specified shell wildcard pattern, by a simple
+/// backtracking reference implementation of the semantics of [`matches_wildcard()`].
+fn reference_match(text: &[char], pattern: &[char]) -> bool {
+    let Some((&p, rest)) = pattern.split_first() else {
+        return text.is_empty();
+    };
+    match p {
+        '*' => {
+            reference_match(text, rest)
+                || (!text.is_empty() && reference_match(&text[1..], pattern))
+        }
+        '?' => !text.is_empty() && reference_match(&text[1..], rest),
+        '\\' => match rest.split_first() {
+            Some((&literal, rest)) => {
+                text.first() == Some(&literal) && reference_match(&text[1..], rest)
+            }
+            None => false,
+        },
+        '[' => {
+            let Some(&c) = text.first() else {
+                return false;
+            };
+            let mut idx = 0;
+            let reverse = rest.first() == Some(&'^');
+            if reverse {
+                idx += 1;
+            }
+            let mut matched = false;
+            // A leading ']' or '-' is a literal, otherwise the opening bracket or the caret is
+            // remembered as the start of a possible range.
+            let mut last = if reverse { '^' } else { '[' };
+            if let Some(&first @ (']' | '-')) = rest.get(idx) {
+                matched = c == first;
+                last = first;
+                idx += 1;
+            }
+            loop {
+                match rest.get(idx) {
+                    None => return false,
+                    Some(']') => break,
+                    Some('-') if rest.get(idx + 1).is_some_and(|&hi| hi != ']') => {
+                        let hi = rest[idx + 1];
+                        matched |= c >= last && c <= hi;
+                        last = hi;
+                        idx += 2;
+                    }
+                    Some(&literal) => {
+                        matched |= c == literal;
+                        last = literal;
+                        idx += 1;
+                    }
+                }
+            }
+            matched != reverse && reference_match(&text[1..], &rest[idx + 1..])
+        }
+        literal => text.first() == Some(&literal) && reference_match(&text[1..], rest),
+    }
+}
+
+#[test]
+fn property_unified_diff_reconstructs() {
+    // Check that applying the unified diff of two random texts to the first one reconstructs the
+    // second one, for various context sizes.
+    let mut rng = TestRng(0x5eed_1234_abcd_0001);
+    for _ in 0..2000 {
+        let a = (0..rng.below(12))
+            .map(|_| rng.string(&['A', 'B', 'C'], 2))
+            .collect::<Vec<_>>();
+        let b = (0..rng.below(12))
+            .map(|_| rng.string(&['A', 'B', 'C'], 2))
+            .collect::<Vec<_>>();
+        let options = DiffOptions {
+            context_size: rng.below(4),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let result = unified_diff_with_options(&a, &b, &options, &mut out);
+        assert_ok!(result);
+        let diff = String::from_utf8(out).unwrap();
+        assert_eq!(
+            apply_unified_diff(&a, &diff),
+            b,
+            "diff of {:?} and {:?}:\n{}",
+            a,
+            b,
+            diff
+        );
+        assert_eq!(diff.is_empty(), a == b);
+    }
+}
+
+#[test]
+fn property_wildcard_agrees_with_reference() {
+    // Check that the wildcard matching agrees with the reference implementation for random
+    // patterns, including character classes, ranges and escaped characters.
+    let mut rng = TestRng(0x5eed_1234_abcd_0002);
+    let text_alphabet = ['a', 'b', 'c', '-', ']', '^', '[', '\\', '*', '?'];
+    let pattern_alphabet = ['a', 'b', 'c', '-', ']', '^', '[', '\\', '*', '?', '*', '['];
+    for _ in 0..50000 {
+        let text = rng.string(&text_alphabet, 6);
+        let pattern = rng.string(&pattern_alphabet, 8);
+        let expected = reference_match(
+            &text.chars().collect::<Vec<_>>(),
+            &pattern.chars().collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            matches_wildcard(&text, &pattern),
+            expected,
+            "text '{}', pattern '{}'",
+            text,
+            pattern
+        );
+    }
+}
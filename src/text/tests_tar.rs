@@ -0,0 +1,184 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::assert_parse_err;
+
+/// Extracts the path and data of each entry in the given tar archive, checking the header
+/// checksums and the archive terminator.
+fn parse_archive(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let field = |header: &[u8], offset: usize, len: usize| {
+        let raw = &header[offset..offset + len];
+        let end = raw.iter().position(|&byte| byte == 0).unwrap_or(len);
+        String::from_utf8(raw[..end].to_vec()).unwrap()
+    };
+
+    assert_eq!(archive.len() % TAR_BLOCK_SIZE, 0);
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    loop {
+        let header = &archive[offset..offset + TAR_BLOCK_SIZE];
+        if header.iter().all(|&byte| byte == 0) {
+            break;
+        }
+
+        let checksum = header
+            .iter()
+            .enumerate()
+            .map(|(i, &byte)| if (148..156).contains(&i) { b' ' } else { byte } as u32)
+            .sum::<u32>();
+        assert_eq!(
+            u32::from_str_radix(field(header, 148, 7).trim(), 8).unwrap(),
+            checksum
+        );
+        assert_eq!(field(header, 257, 6), "ustar");
+        assert_eq!(field(header, 156, 1), "0");
+
+        let name = field(header, 0, 100);
+        let prefix = field(header, 345, 155);
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let size = usize::from_str_radix(field(header, 124, 12).as_str(), 8).unwrap();
+        offset += TAR_BLOCK_SIZE;
+        entries.push((path, archive[offset..offset + size].to_vec()));
+        offset += size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE;
+    }
+
+    // The archive is terminated by two zero blocks.
+    assert_eq!(archive.len(), offset + 2 * TAR_BLOCK_SIZE);
+    entries
+}
+
+#[test]
+fn tar_entries() {
+    // Check that closed files are appended to the archive in the order in which they are closed.
+    let mut tar_writer = TarWriter::new(Vec::new());
+    for (name, data) in [("foo.symtypes", "foo\n"), ("dir/bar.symtypes", "")] {
+        let mut writer = (&mut tar_writer).create(name).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+        (&mut tar_writer).close(writer);
+    }
+    let archive = tar_writer.finish().unwrap();
+    assert_eq!(archive.len(), 5 * TAR_BLOCK_SIZE);
+    assert_eq!(
+        parse_archive(&archive),
+        vec![
+            ("foo.symtypes".to_string(), b"foo\n".to_vec()),
+            ("dir/bar.symtypes".to_string(), b"".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn tar_long_path() {
+    // Check that a path longer than 100 bytes is split into the prefix and name fields.
+    let path = format!("{}/{}", "d".repeat(120), "f".repeat(90));
+    let mut tar_writer = TarWriter::new(Vec::new());
+    let mut writer = (&mut tar_writer).create(&path).unwrap();
+    writer.write_all(&[b'x'; 600]).unwrap();
+    (&mut tar_writer).close(writer);
+    let archive = tar_writer.finish().unwrap();
+    assert_eq!(parse_archive(&archive), vec![(path, vec![b'x'; 600])]);
+}
+
+#[test]
+fn tar_too_long_path() {
+    // Check that a path which cannot be stored in the ustar format is reported when finishing the
+    // archive.
+    let path = "f".repeat(101);
+    let mut tar_writer = TarWriter::new(Vec::new());
+    let writer = (&mut tar_writer).create(&path).unwrap();
+    (&mut tar_writer).close(writer);
+    let result = tar_writer.finish();
+    assert_parse_err!(
+        result,
+        format!("The path '{}' cannot be stored in a tar archive", path)
+    );
+}
+
+/// Creates a raw tar header block with the given path and size, and a valid checksum.
+fn raw_tar_header(path: &str, size: usize, typeflag: u8) -> Vec<u8> {
+    let mut header = tar_header(Path::new(path), size).unwrap();
+    header[156] = typeflag;
+    header[148..156].copy_from_slice(b"        ");
+    let checksum = header.iter().map(|&byte| byte as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    header.to_vec()
+}
+
+/// Appends the given data to the archive, padded to the block size.
+fn push_tar_data(archive: &mut Vec<u8>, data: &[u8]) {
+    archive.extend_from_slice(data);
+    archive.resize(archive.len().div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE, 0);
+}
+
+#[test]
+fn read_tar_written() {
+    // Check that an archive produced by `TarWriter` can be read back.
+    let long_path = format!("{}/{}", "d".repeat(120), "f".repeat(90));
+    let mut tar_writer = TarWriter::new(Vec::new());
+    for (name, data) in [("a.symtypes", "a\n"), (long_path.as_str(), "b\n")] {
+        let mut writer = (&mut tar_writer).create(name).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+        (&mut tar_writer).close(writer);
+    }
+    let archive = tar_writer.finish().unwrap();
+    let entries = read_tar(archive.as_slice()).unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (PathBuf::from("a.symtypes"), b"a\n".to_vec()),
+            (PathBuf::from(long_path), b"b\n".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn read_tar_extensions() {
+    // Check that directories are skipped, a leading './' is removed, and long paths from GNU long
+    // name entries and pax extended headers are recognized.
+    let gnu_path = format!("gnu/{}", "g".repeat(120));
+    let pax_path = format!("pax/{}", "p".repeat(120));
+    let pax_record = format!("{} path={}\n", pax_path.len() + 11, pax_path);
+
+    let mut archive = Vec::new();
+    archive.extend(raw_tar_header("./dir/", 0, b'5'));
+    archive.extend(raw_tar_header("./dir/a.symtypes", 2, b'0'));
+    push_tar_data(&mut archive, b"a\n");
+    archive.extend(raw_tar_header("././@LongLink", gnu_path.len() + 1, b'L'));
+    push_tar_data(&mut archive, format!("{}\0", gnu_path).as_bytes());
+    archive.extend(raw_tar_header("truncated-gnu", 2, b'0'));
+    push_tar_data(&mut archive, b"g\n");
+    archive.extend(raw_tar_header("PaxHeaders/p", pax_record.len(), b'x'));
+    push_tar_data(&mut archive, pax_record.as_bytes());
+    archive.extend(raw_tar_header("truncated-pax", 2, b'0'));
+    push_tar_data(&mut archive, b"p\n");
+    archive.extend([0; 2 * TAR_BLOCK_SIZE]);
+
+    let entries = read_tar(archive.as_slice()).unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (PathBuf::from("dir/a.symtypes"), b"a\n".to_vec()),
+            (PathBuf::from(gnu_path), b"g\n".to_vec()),
+            (PathBuf::from(pax_path), b"p\n".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn read_tar_invalid() {
+    // Check that a corrupted header and a truncated entry are rejected.
+    let mut archive = raw_tar_header("a.symtypes", 2, b'0');
+    archive[0] = b'b';
+    push_tar_data(&mut archive, b"a\n");
+    let result = read_tar(archive.as_slice());
+    assert_parse_err!(result, "Invalid tar header checksum");
+
+    let archive = raw_tar_header("a.symtypes", 2, b'0');
+    let result = read_tar(archive.as_slice());
+    assert!(matches!(result, Err(Error::IO { .. })));
+}
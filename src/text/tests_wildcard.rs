@@ -79,3 +79,14 @@ fn matches_range() {
     assert!(matches_wildcard("a", "[^-]"));
     assert!(matches_wildcard("a", "[^]-]"));
 }
+
+#[test]
+fn matches_malformed() {
+    // Check that unterminated character classes and ranges don't match anything.
+    assert!(!matches_wildcard("a", "["));
+    assert!(!matches_wildcard("a", "[a"));
+    assert!(!matches_wildcard("a", "[a-"));
+    assert!(!matches_wildcard("a", "[^"));
+    assert!(!matches_wildcard("ab", "*[b-"));
+    assert!(!matches_wildcard("a", "a\\"));
+}
@@ -0,0 +1,101 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+//! Handling of warnings reported while processing input data.
+
+use crate::text::Filter;
+use crate::{Error, MapIOErr};
+use std::io::prelude::*;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// A mode of handling reported warnings.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WarningsMode {
+    /// Warnings are silently discarded.
+    Ignore,
+    /// Warnings are written to the output stream.
+    #[default]
+    Print,
+    /// The first warning is turned into an error.
+    Error,
+}
+
+impl FromStr for WarningsMode {
+    type Err = Error;
+
+    /// Obtains a [`WarningsMode`] matching the given mode name, specified as a string.
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "ignore" => Ok(Self::Ignore),
+            "print" => Ok(Self::Print),
+            "error" => Ok(Self::Error),
+            _ => Err(Self::Err::new_parse(format!(
+                "Unrecognized warnings mode '{}'",
+                mode
+            ))),
+        }
+    }
+}
+
+/// A destination for warnings.
+///
+/// The trait is implemented for any [`Write`] stream, which simply prints all warnings, and for
+/// [`Warnings`], which additionally applies a [`WarningsMode`] and a filter of silenced names.
+pub trait WarningsSink {
+    /// Reports a warning about the given name, located at `location`.
+    ///
+    /// Returns `Err` if the warning cannot be written or if it should be treated as an error.
+    fn report(&mut self, name: &str, location: &str, message: &str) -> Result<(), Error>;
+}
+
+impl<W: Write> WarningsSink for W {
+    fn report(&mut self, _name: &str, location: &str, message: &str) -> Result<(), Error> {
+        writeln!(self, "{}: WARNING: {}", location, message).map_io_err("Failed to write a warning")
+    }
+}
+
+/// A warnings stream with a policy how to handle reported warnings.
+pub struct Warnings<'a, W: Write> {
+    writer: W,
+    mode: WarningsMode,
+    maybe_filter: Option<&'a Filter>,
+}
+
+impl<'a, W: Write> Warnings<'a, W> {
+    /// Creates a new `Warnings` object, handling warnings according to `mode`.
+    ///
+    /// Warnings about names that match `maybe_filter` are silenced, regardless of the mode.
+    pub fn new(writer: W, mode: WarningsMode, maybe_filter: Option<&'a Filter>) -> Self {
+        Self {
+            writer,
+            mode,
+            maybe_filter,
+        }
+    }
+}
+
+impl<W: Write> WarningsSink for Warnings<'_, W> {
+    fn report(&mut self, name: &str, location: &str, message: &str) -> Result<(), Error> {
+        if self.maybe_filter.is_some_and(|filter| filter.matches(name)) {
+            return Ok(());
+        }
+
+        match self.mode {
+            WarningsMode::Ignore => Ok(()),
+            WarningsMode::Print => self.writer.report(name, location, message),
+            WarningsMode::Error => Err(Error::new_parse(format!("{}: {}", location, message))),
+        }
+    }
+}
+
+/// A [`WarningsSink`] that forwards all warnings to a borrowed sink.
+pub(crate) struct WarningsRef<'a, S: WarningsSink + ?Sized>(pub(crate) &'a mut S);
+
+impl<S: WarningsSink + ?Sized> WarningsSink for WarningsRef<'_, S> {
+    fn report(&mut self, name: &str, location: &str, message: &str) -> Result<(), Error> {
+        self.0.report(name, location, message)
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use super::*;
+use crate::{assert_ok, assert_parse_err, bytes};
+
+#[test]
+fn report_writer() {
+    // Check that a plain writer prints all warnings.
+    let mut out = Vec::new();
+    let result = out.report("foo", "test.symtypes:1", "Test warning");
+    assert_ok!(result);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "test.symtypes:1: WARNING: Test warning\n"
+    );
+}
+
+#[test]
+fn report_modes() {
+    // Check that warnings are handled according to the selected mode.
+    let mut out = Vec::new();
+    let mut warnings = Warnings::new(&mut out, WarningsMode::Ignore, None);
+    let result = warnings.report("foo", "test.symtypes:1", "Test warning");
+    assert_ok!(result);
+    assert_eq!(String::from_utf8(out).unwrap(), "");
+
+    let mut out = Vec::new();
+    let mut warnings = Warnings::new(&mut out, WarningsMode::Print, None);
+    let result = warnings.report("foo", "test.symtypes:1", "Test warning");
+    assert_ok!(result);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "test.symtypes:1: WARNING: Test warning\n"
+    );
+
+    let mut out = Vec::new();
+    let mut warnings = Warnings::new(&mut out, WarningsMode::Error, None);
+    let result = warnings.report("foo", "test.symtypes:1", "Test warning");
+    assert_parse_err!(result, "test.symtypes:1: Test warning");
+    assert_eq!(String::from_utf8(out).unwrap(), "");
+}
+
+#[test]
+fn report_filtered() {
+    // Check that warnings about names matching the filter are silenced in any mode.
+    let mut filter = Filter::new();
+    let result = filter.load_buffer(
+        "test.filter",
+        bytes!(
+            "foo*\n", //
+        ),
+    );
+    assert_ok!(result);
+
+    let mut out = Vec::new();
+    let mut warnings = Warnings::new(&mut out, WarningsMode::Error, Some(&filter));
+    let result = warnings.report("foo_1", "test.symtypes:1", "Test warning");
+    assert_ok!(result);
+    let result = warnings.report("bar", "test.symtypes:2", "Test warning");
+    assert_parse_err!(result, "test.symtypes:2: Test warning");
+    assert_eq!(String::from_utf8(out).unwrap(), "");
+}
@@ -1,10 +1,14 @@
 // Copyright (C) 2025 SUSE LLC
 // SPDX-License-Identifier: GPL-2.0-or-later
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::{OsStr, OsString};
 use std::fs;
+use std::io;
+use std::io::prelude::*;
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
 
 pub struct RunResult {
     pub status: ExitStatus,
@@ -15,12 +19,35 @@ pub struct RunResult {
 pub fn tool_run<P: AsRef<OsStr>, I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     program: P,
     args: I,
+) -> RunResult {
+    tool_run_stdin(program, args, "")
+}
+
+pub fn tool_run_stdin<P: AsRef<OsStr>, I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
+    program: P,
+    args: I,
+    input: &str,
 ) -> RunResult {
     let program = program.as_ref();
-    let output = Command::new(program)
+    let mut child = Command::new(program)
         .args(args)
-        .output()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .unwrap_or_else(|err| panic!("failed to execute {:?}: {}", program, err));
+    // Feed the input from a separate thread so a large input cannot deadlock against the output
+    // pipes. The tool may exit without reading all of it, which is not an error.
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    let writer = thread::spawn(move || match stdin.write_all(input.as_bytes()) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+            panic!("failed to write input: {}", err)
+        }
+        _ => {}
+    });
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
     RunResult {
         status: output.status,
         stdout: String::from_utf8(output.stdout).unwrap(),
@@ -32,6 +59,14 @@ pub fn ksymtypes_run<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(args: I) -> Run
     tool_run(env!("CARGO_BIN_EXE_ksymtypes"), args)
 }
 
+#[allow(dead_code)]
+pub fn ksymtypes_run_stdin<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
+    args: I,
+    input: &str,
+) -> RunResult {
+    tool_run_stdin(env!("CARGO_BIN_EXE_ksymtypes"), args, input)
+}
+
 pub fn ksymvers_run<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(args: I) -> RunResult {
     tool_run(env!("CARGO_BIN_EXE_ksymvers"), args)
 }
@@ -53,3 +88,102 @@ pub fn concat_os<S: AsRef<OsStr>, S2: AsRef<OsStr>>(s: S, s2: S2) -> OsString {
     res.push(s2);
     res
 }
+
+/// A man page split into its sections, each with the long and short option names that it
+/// documents in tagged paragraphs.
+struct ManSections(BTreeMap<String, (String, BTreeSet<String>)>);
+
+impl ManSections {
+    fn parse(page: &str) -> Self {
+        let mut sections = BTreeMap::new();
+        let mut maybe_current: Option<&mut (String, BTreeSet<String>)> = None;
+        let mut is_tag = false;
+        for line in page.lines() {
+            let line = line.replace("\\-", "-");
+            if let Some(name) = line.strip_prefix(".SH ") {
+                maybe_current = Some(sections.entry(name.to_string()).or_default());
+                is_tag = false;
+                continue;
+            }
+            let Some(current) = maybe_current.as_mut() else {
+                continue;
+            };
+            current.0.push_str(&line);
+            current.0.push('\n');
+            if is_tag {
+                for word in line.split("\\fB").skip(1) {
+                    let word = word.split("\\fR").next().unwrap();
+                    if word.starts_with('-') {
+                        current.1.insert(word.to_string());
+                    }
+                }
+            }
+            is_tag = line == ".TP";
+        }
+        Self(sections)
+    }
+
+    fn commands(&self) -> BTreeSet<String> {
+        let synopsis = &self.0["SYNOPSIS"].0;
+        let (_, rest) = synopsis.split_once('{').unwrap();
+        let (commands, _) = rest.split_once('}').unwrap();
+        commands
+            .split('|')
+            .map(|command| command.trim().replace("\\fB", "").replace("\\fR", ""))
+            .collect()
+    }
+
+    fn options(&self, section: &str) -> &BTreeSet<String> {
+        match self.0.get(section) {
+            Some((_, options)) => options,
+            None => panic!("section '{}' is missing", section),
+        }
+    }
+}
+
+/// Checks that a hand-written man page documents the same commands and options as the man page
+/// generated from the command-line definitions of the tool. Commands listed in `optional_commands`
+/// may be documented even if they are not compiled in.
+#[allow(dead_code)]
+pub fn assert_man_page_matches<P: AsRef<Path>>(
+    generated: &str,
+    doc_path: P,
+    optional_commands: &[&str],
+) {
+    let generated = ManSections::parse(generated);
+    let doc = ManSections::parse(&fs::read_to_string(doc_path).unwrap());
+
+    let commands = generated.commands();
+    let mut doc_commands = doc.commands();
+    doc_commands.retain(|command| {
+        commands.contains(command) || !optional_commands.contains(&command.as_str())
+    });
+    assert_eq!(commands, doc_commands);
+
+    assert_eq!(
+        generated.options("GENERAL OPTIONS"),
+        doc.options("GENERAL OPTIONS")
+    );
+    let universal = doc.options("UNIVERSAL COMMAND OPTIONS");
+    for command in &commands {
+        let section = format!("{} COMMAND", command.to_uppercase());
+        let options = generated.options(&section);
+        let doc_options = doc.options(&section);
+        assert!(
+            doc_options.is_subset(options),
+            "section '{}' documents unknown options {:?}",
+            section,
+            doc_options.difference(options).collect::<Vec<_>>()
+        );
+        let missing = options
+            .iter()
+            .filter(|&option| !doc_options.contains(option) && !universal.contains(option))
+            .collect::<Vec<_>>();
+        assert!(
+            missing.is_empty(),
+            "section '{}' does not document options {:?}",
+            section,
+            missing
+        );
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (C) 2025 SUSE LLC
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use suse_kabi_tools::api::{
+    CompareOptions, ComparisonStatus, Filter, LoadOptions, ReportFormat, Rules, Symtypes, Symvers,
+};
+
+#[test]
+fn api_symtypes_compare() {
+    // Check that two symtypes corpuses can be compared through the stable API.
+    let load_options = LoadOptions::new().jobs(2);
+    let symtypes = Symtypes::load("tests/it/ksymtypes/compare/a.symtypes", &load_options).unwrap();
+    let symtypes2 = Symtypes::load("tests/it/ksymtypes/compare/b.symtypes", &load_options).unwrap();
+    let report = symtypes
+        .compare(&symtypes2, &CompareOptions::new().jobs(2))
+        .unwrap();
+    assert_eq!(report.status(), ComparisonStatus::Breaking);
+    assert!(report.is_breaking());
+    assert_eq!(
+        report.text(),
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+}
+
+#[test]
+fn api_symtypes_compare_filter() {
+    // Check that a filter restricts the symbols compared through the stable API.
+    let load_options = LoadOptions::new();
+    let symtypes = Symtypes::load("tests/it/ksymtypes/compare/a.symtypes", &load_options).unwrap();
+    let symtypes2 = Symtypes::load("tests/it/ksymtypes/compare/b.symtypes", &load_options).unwrap();
+    let filter = Filter::from_patterns(["bar*"]).unwrap();
+    assert!(filter.matches("bar_baz"));
+    assert!(!filter.matches("foo"));
+    let report = symtypes
+        .compare(
+            &symtypes2,
+            &CompareOptions::new()
+                .filter(&filter)
+                .format(ReportFormat::Symbols),
+        )
+        .unwrap();
+    assert_eq!(report.status(), ComparisonStatus::Same);
+    assert_eq!(report.text(), "");
+}
+
+#[test]
+fn api_symvers_compare_rules() {
+    // Check that severity rules tolerate changes in a symvers comparison through the stable API.
+    let load_options = LoadOptions::new();
+    let symvers =
+        Symvers::load("tests/it/ksymvers/compare_rules/a.symvers", &load_options).unwrap();
+    let symvers2 =
+        Symvers::load("tests/it/ksymvers/compare_rules/b.symvers", &load_options).unwrap();
+    assert_eq!(symvers.crc("foo"), Some(0x12345678));
+    assert_eq!(symvers.crc("bar"), None);
+
+    let report = symvers.compare(&symvers2, &CompareOptions::new()).unwrap();
+    assert_eq!(report.status(), ComparisonStatus::Breaking);
+    assert_eq!(
+        report.text(),
+        "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n"
+    );
+
+    let rules = Rules::load("tests/it/ksymvers/compare_rules/severities.txt", None).unwrap();
+    assert_eq!(rules.len(), 1);
+    let report = symvers
+        .compare(&symvers2, &CompareOptions::new().rules(&rules))
+        .unwrap();
+    assert_eq!(report.status(), ComparisonStatus::Tolerated);
+    assert_eq!(
+        report.text(),
+        concat!(
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rule ",
+            "tests/it/ksymvers/compare_rules/severities.txt:1 'vmlinux PASS')\n", //
+        )
+    );
+}
+
+#[test]
+fn api_load_missing() {
+    // Check that a failure to load a corpus is reported as an error.
+    let result = Symvers::load("tests/it/api/missing.symvers", &LoadOptions::new());
+    assert!(result.is_err());
+}
@@ -5,6 +5,7 @@ use crate::common::*;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
 use suse_kabi_tools::assert_inexact;
 
 #[test]
@@ -15,7 +16,7 @@ fn ksymtypes_consolidate() {
     let result = ksymtypes_run([
         AsRef::<OsStr>::as_ref("consolidate"),
         "--output".as_ref(),
-        &output_path.as_ref(),
+        output_path.as_ref(),
         "tests/it/ksymtypes/consolidate".as_ref(),
     ]);
     assert_eq!(result.status.code().unwrap(), 0);
@@ -36,277 +37,2187 @@ fn ksymtypes_consolidate() {
 }
 
 #[test]
-fn ksymtypes_consolidate_missing_output() {
-    // Check that the consolidate command fails if no --output is specified.
-    let result = ksymtypes_run(["consolidate", "tests/it/ksymtypes/consolidate"]);
-    assert_eq!(result.status.code().unwrap(), 2);
+fn ksymtypes_consolidate_sort_old_script() {
+    // Check that the --sort=old-script option orders the output as the old modversions script.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_sort_old_script.symtypes");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--sort=old-script".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(result.stdout, "");
-    assert_eq!(result.stderr, "The consolidate output is missing\n");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; } \n",
+            "bar int bar ( s#foo ) \n",
+            "\n",
+            "/* b.symtypes */\n",
+            "baz int baz ( s#foo ) \n",
+            "\n", //
+        )
+    );
 }
 
 #[test]
-fn ksymtypes_consolidate_invalid_input() {
-    // Check that the consolidate command correctly propagates inner errors and writes them on the
-    // standard error output.
-    let output_path = tmp_path("tests/it/ksymtypes/consolidate_invalid_input.symtypes");
+fn ksymtypes_consolidate_with_metadata() {
+    // Check that the --with-metadata option records the object path, architecture and config
+    // flavor of each file.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_with_metadata.symtypes");
     fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
         AsRef::<OsStr>::as_ref("consolidate"),
+        "--with-metadata".as_ref(),
+        "--arch=x86_64".as_ref(),
+        "--flavor=default".as_ref(),
         "--output".as_ref(),
-        &output_path.as_ref(),
-        "tests/it/ksymtypes/missing".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(&output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "/*! consolidated 2 */\n",
+            "/* a.symtypes */\n",
+            "/*@ arch x86_64 */\n",
+            "/*@ flavor default */\n",
+            "/*@ object a.o */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "/*@ arch x86_64 */\n",
+            "/*@ flavor default */\n",
+            "/*@ object b.o */\n",
+            "baz int baz ( s#foo )\n", //
+        )
+    );
+
+    // Check that the metadata is accepted when the output is read back.
+    let result = ksymtypes_run([AsRef::<OsStr>::as_ref("stats"), output_path.as_ref()]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert!(result.stdout.contains(concat!(
+        "Files with metadata: 2\n",
+        "Files by metadata:\n",
+        " arch=x86_64: 2\n",
+        " flavor=default: 2\n", //
+    )));
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_consolidate_arch_without_metadata() {
+    // Check that the --arch option is rejected without --with-metadata.
+    let result = ksymtypes_run([
+        "consolidate",
+        "--arch=x86_64",
+        "--output=unused.symtypes",
+        "tests/it/ksymtypes/consolidate",
     ]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
-    assert_inexact!(
+    assert_eq!(
         result.stderr,
-        "Failed to read symtypes from 'tests/it/ksymtypes/missing': Failed to read the directory 'tests/it/ksymtypes/missing/': *\n"
+        "The --arch option requires --with-metadata\n"
     );
 }
 
 #[test]
-fn ksymtypes_consolidate_non_directory() {
-    // Check that the consolidate command rejects an input path that is not a directory.
-    let output_path = tmp_path("tests/it/ksymtypes/consolidate_non_directory.symtypes");
+fn ksymtypes_consolidate_low_memory() {
+    // Check that the consolidate command produces the same output with the --low-memory option.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_low_memory.symtypes");
     fs::remove_file(&output_path).ok();
-    let input_path = Path::new("tests/it/ksymtypes/consolidate_non_directory/a.symtypes");
-    assert!(input_path.is_file());
     let result = ksymtypes_run([
         AsRef::<OsStr>::as_ref("consolidate"),
+        "--low-memory".as_ref(),
         "--output".as_ref(),
-        &output_path.as_ref(),
-        input_path.as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "/* a.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "baz int baz ( s#foo )\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_consolidate_low_memory_update() {
+    // Check that the --low-memory option is rejected together with --update.
+    let result = ksymtypes_run([
+        "consolidate",
+        "--low-memory",
+        "--update",
+        "--output=unused.symtypes",
+        "tests/it/ksymtypes/consolidate",
     ]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
-    assert_inexact!(
+    assert_eq!(
         result.stderr,
-        "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_non_directory/a.symtypes': Failed to read the directory 'tests/it/ksymtypes/consolidate_non_directory/a.symtypes/': *\n"
+        "The --low-memory option cannot be used together with --update\n"
     );
 }
 
 #[test]
-fn ksymtypes_consolidate_reject_consolidated() {
-    // Check that the consolidate command rejects loading any symtypes files in the consolidated
-    // format.
-    let output_path = tmp_path("tests/it/ksymtypes/consolidate_reject_consolidated.symtypes");
+fn ksymtypes_consolidate_max_variants() {
+    // Check that the --max-variants option reports types with too many variants, and that the
+    // warnings follow the --warnings mode.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_max_variants.symtypes");
     fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
         AsRef::<OsStr>::as_ref("consolidate"),
+        "--max-variants=1".as_ref(),
         "--output".as_ref(),
-        &output_path.as_ref(),
-        "tests/it/ksymtypes/consolidate_reject_consolidated".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate_max_variants".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "a.symtypes: WARNING: Type 's#foo' has '2' variants, more than the maximum of '1'\n"
+    );
+    assert!(output_path.is_file());
+
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--max-variants=2".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate_max_variants".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--max-variants=1".as_ref(),
+        "--warnings=error".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate_max_variants".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_max_variants': a.symtypes: Type 's#foo' has '2' variants, more than the maximum of '1'\n"
+    );
+}
+
+#[test]
+fn ksymtypes_consolidate_sort_invalid() {
+    // Check that an unrecognized --sort value is rejected.
+    let result = ksymtypes_run([
+        "consolidate",
+        "--sort=random",
+        "--output=unused.symtypes",
+        "tests/it/ksymtypes/consolidate",
     ]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "Unrecognized order 'random'\n");
+}
+
+#[test]
+fn ksymtypes_consolidate_progress() {
+    // Check that the --progress option reports the progress of reading the symtypes files.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_progress.symtypes");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("--progress"),
+        "consolidate".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
     assert_eq!(
         result.stderr,
         concat!(
-            "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_reject_consolidated': Expected a plain symtypes file, but found consolidated data\n",
-            " tests/it/ksymtypes/consolidate_reject_consolidated/consolidated.symtypes:1\n",
-            " | /* a.symtypes */\n", //
+            "Reading symtypes from 'tests/it/ksymtypes/consolidate': 50%\n",
+            "Reading symtypes from 'tests/it/ksymtypes/consolidate': 100%\n", //
         )
     );
 }
 
 #[test]
-fn ksymtypes_split() {
-    // Check that the split command trivially works.
-    let output_path = tmp_path("tests/it/ksymtypes/split");
-    fs::remove_dir_all(&output_path).ok();
+fn ksymtypes_consolidate_update() {
+    // Check that the consolidate command with --update reuses data of symtypes files that were not
+    // modified after the previous consolidation, parses new and modified files, and drops removed
+    // files. A file that has an old modification time but differs from its consolidated data is
+    // parsed again.
+    fn write_with_mtime(path: &Path, data: &str, secs: u64) {
+        fs::write(path, data).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    let input_path = tmp_path("tests/it/ksymtypes/consolidate_update/input");
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_update/consolidated.symtypes");
+    let log_path = tmp_path("tests/it/ksymtypes/consolidate_update/consolidate.log");
+    fs::remove_dir_all(&input_path).ok();
+    fs::create_dir_all(&input_path).unwrap();
+    fs::remove_file(&log_path).ok();
+
+    write_with_mtime(&input_path.join("a.symtypes"), "bar long bar ( )\n", 1000);
+    write_with_mtime(
+        &output_path,
+        concat!(
+            "/* a.symtypes */\n",
+            "bar long bar ( )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "baz long baz ( )\n",
+            "\n",
+            "/* d.symtypes */\n",
+            "qux long qux ( )\n",
+            "\n",
+            "/* e.symtypes */\n",
+            "quuz long quuz ( )\n", //
+        ),
+        2000,
+    );
+    write_with_mtime(&input_path.join("b.symtypes"), "baz int baz ( )\n", 3000);
+    write_with_mtime(&input_path.join("c.symtypes"), "quux int quux ( )\n", 1000);
+    write_with_mtime(&input_path.join("e.symtypes"), "quuz int quuz ( )\n", 1000);
+
     let result = ksymtypes_run([
-        AsRef::<OsStr>::as_ref("split"),
-        "--output".as_ref(),
-        &output_path.as_ref(),
-        "tests/it/ksymtypes/split/consolidated.symtypes".as_ref(),
+        AsRef::<OsStr>::as_ref("--debug"),
+        &concat_os("--log-file=", &log_path),
+        "consolidate".as_ref(),
+        &concat_os("--update=", &output_path),
+        input_path.as_ref(),
     ]);
     assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(result.stdout, "");
     assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
     assert_eq!(
-        fs::read_to_string(output_path.join("a.symtypes")).unwrap(),
+        output_data,
         concat!(
-            "s#foo struct foo { int a ; }\n",
-            "bar int bar ( s#foo )\n", //
+            "/* a.symtypes */\n",
+            "bar long bar ( )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* c.symtypes */\n",
+            "quux int quux ( )\n",
+            "\n",
+            "/* e.symtypes */\n",
+            "quuz int quuz ( )\n", //
         )
     );
+    let log_data = fs::read_to_string(log_path).expect("Unable to read the log file");
+    assert!(
+        log_data.contains("Reused '1' of '4' symtypes files"),
+        "unexpected log:\n{}",
+        log_data
+    );
+}
+
+#[test]
+fn ksymtypes_consolidate_update_missing() {
+    // Check that the consolidate command with --update performs a full consolidation if the
+    // specified file doesn't exist yet.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_update_missing.symtypes");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        &concat_os("--update=", &output_path),
+        "tests/it/ksymtypes/consolidate".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
     assert_eq!(
-        fs::read_to_string(output_path.join("b.symtypes")).unwrap(),
+        output_data,
         concat!(
+            "/* a.symtypes */\n",
             "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n",
+            "\n",
+            "/* b.symtypes */\n",
             "baz int baz ( s#foo )\n", //
         )
     );
 }
 
 #[test]
-fn ksymtypes_split_missing_output() {
-    // Check that the split command fails if no --output is specified.
-    let result = ksymtypes_run(["split", "tests/it/ksymtypes/split/consolidated.symtypes"]);
+fn ksymtypes_consolidate_missing_output() {
+    // Check that the consolidate command fails if no --output is specified.
+    let result = ksymtypes_run(["consolidate", "tests/it/ksymtypes/consolidate"]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
-    assert_eq!(result.stderr, "The split output is missing\n");
+    assert_eq!(result.stderr, "The consolidate output is missing\n");
 }
 
 #[test]
-fn ksymtypes_split_invalid_input() {
-    // Check that the split command correctly propagates inner errors and writes them on the
+fn ksymtypes_consolidate_invalid_input() {
+    // Check that the consolidate command correctly propagates inner errors and writes them on the
     // standard error output.
-    let output_path = tmp_path("tests/it/ksymtypes/split_invalid_input");
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_invalid_input.symtypes");
     fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
-        AsRef::<OsStr>::as_ref("split"),
+        AsRef::<OsStr>::as_ref("consolidate"),
         "--output".as_ref(),
-        &output_path.as_ref(),
+        output_path.as_ref(),
         "tests/it/ksymtypes/missing".as_ref(),
     ]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
     assert_inexact!(
         result.stderr,
-        "Failed to read symtypes from 'tests/it/ksymtypes/missing': Failed to open the file 'tests/it/ksymtypes/missing': *\n"
+        "Failed to read symtypes from 'tests/it/ksymtypes/missing': Failed to read the directory 'tests/it/ksymtypes/missing/': *\n"
     );
 }
 
 #[test]
-fn ksymtypes_split_non_file() {
-    // Check that the split command rejects an input path that is not a file.
-    let output_path = tmp_path("tests/it/ksymtypes/split_non_file");
+fn ksymtypes_consolidate_non_directory() {
+    // Check that the consolidate command rejects an input path that is not a directory.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_non_directory.symtypes");
     fs::remove_file(&output_path).ok();
-    let input_path = Path::new("tests/it/ksymtypes/split_non_file");
-    assert!(input_path.is_dir());
+    let input_path = Path::new("tests/it/ksymtypes/consolidate_non_directory/a.symtypes");
+    assert!(input_path.is_file());
     let result = ksymtypes_run([
-        AsRef::<OsStr>::as_ref("split"),
+        AsRef::<OsStr>::as_ref("consolidate"),
         "--output".as_ref(),
-        &output_path.as_ref(),
+        output_path.as_ref(),
         input_path.as_ref(),
     ]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
     assert_inexact!(
         result.stderr,
-        "Failed to read symtypes from 'tests/it/ksymtypes/split_non_file': Failed to read symtypes data: Failed to read from the file 'tests/it/ksymtypes/split_non_file': *\n"
+        "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_non_directory/a.symtypes': Failed to read the directory 'tests/it/ksymtypes/consolidate_non_directory/a.symtypes/': *\n"
     );
 }
 
 #[test]
-fn ksymtypes_split_reject_plain() {
-    // Check that the split command rejects loading a symtypes file in the non-consolidated format.
-    let output_path = tmp_path("tests/it/ksymtypes/split/reject_plain");
+fn ksymtypes_consolidate_reject_consolidated() {
+    // Check that the consolidate command rejects loading any symtypes files in the consolidated
+    // format.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_reject_consolidated.symtypes");
     fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
-        AsRef::<OsStr>::as_ref("split"),
+        AsRef::<OsStr>::as_ref("consolidate"),
         "--output".as_ref(),
-        &output_path.as_ref(),
-        "tests/it/ksymtypes/split_reject_plain/a.symtypes".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate_reject_consolidated".as_ref(),
     ]);
     assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
     assert_eq!(
         result.stderr,
         concat!(
-            "Failed to read symtypes from 'tests/it/ksymtypes/split_reject_plain/a.symtypes': Expected a consolidated symtypes file, but found an invalid header\n",
-            " tests/it/ksymtypes/split_reject_plain/a.symtypes:1\n",
-            " | s#foo struct foo { int a ; }\n", //
-        ),
+            "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_reject_consolidated': Expected a plain symtypes file, but found consolidated data\n",
+            " tests/it/ksymtypes/consolidate_reject_consolidated/consolidated.symtypes:1\n",
+            " | /* a.symtypes */\n", //
+        )
     );
 }
 
 #[test]
-fn ksymtypes_compare() {
-    // Check that the comparison of two different symtypes files shows relevant differences and
-    // results in the command exiting with a status of 1.
+fn ksymtypes_consolidate_keep_going() {
+    // Check that the consolidate command with --keep-going reports errors from all symtypes files.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_keep_going.symtypes");
+    fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
-        "compare",
-        "tests/it/ksymtypes/compare/a.symtypes",
-        "tests/it/ksymtypes/compare/b.symtypes",
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--keep-going".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/consolidate_keep_going".as_ref(),
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
     assert_eq!(
-        result.stdout,
+        result.stderr,
         concat!(
-            "The following '1' exports are different:\n",
-            " foo\n",
-            "\n",
-            "because of a changed 'foo':\n",
-            "@@ -1,1 +1,1 @@\n",
-            "-void foo ( int a )\n",
-            "+void foo ( long a )\n", //
+            "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_keep_going': Duplicate record 's#foo'\n",
+            " tests/it/ksymtypes/consolidate_keep_going/a.symtypes:2\n",
+            " | s#foo struct foo { int b ; }\n",
+            "Type 's#qux' is not known\n",
+            " tests/it/ksymtypes/consolidate_keep_going/b.symtypes:1\n",
+            " | baz int baz ( s#qux )\n", //
         )
     );
-    assert_eq!(result.stderr, "");
+    assert!(!output_path.exists());
 }
 
-#[test]
-fn ksymtypes_compare_dash_dash() {
-    // Check that operands of the compare command can be specified after '--'.
+fn ksymtypes_consolidate_on_duplicate_run(policy: &str) -> (RunResult, String) {
+    let output_path = tmp_path(format!(
+        "tests/it/ksymtypes/consolidate_on_duplicate_{}.symtypes",
+        policy
+    ));
+    fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
-        "compare",
-        "--",
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        format!("--on-duplicate={}", policy).as_ref(),
+        "tests/it/ksymtypes/consolidate_on_duplicate/a".as_ref(),
+        "tests/it/ksymtypes/consolidate_on_duplicate/b".as_ref(),
+    ]);
+    let output_data = fs::read_to_string(output_path).unwrap_or_default();
+    (result, output_data)
+}
+
+#[test]
+fn ksymtypes_consolidate_on_duplicate() {
+    // Check that the consolidate command handles a file present in multiple input directories
+    // according to the selected policy.
+    let (result, output_data) = ksymtypes_consolidate_on_duplicate_run("error");
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Failed to read symtypes from 'tests/it/ksymtypes/consolidate_on_duplicate/a', 'tests/it/ksymtypes/consolidate_on_duplicate/b': Duplicate file path 'common.symtypes' found in 'tests/it/ksymtypes/consolidate_on_duplicate/a' and 'tests/it/ksymtypes/consolidate_on_duplicate/b'\n"
+    );
+    assert_eq!(output_data, "");
+
+    let (result, output_data) = ksymtypes_consolidate_on_duplicate_run("first");
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        output_data,
+        concat!(
+            "/* a.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "qux int qux ( )\n",
+            "\n",
+            "/* common.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        )
+    );
+
+    let (result, output_data) = ksymtypes_consolidate_on_duplicate_run("last");
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        output_data,
+        concat!(
+            "/* a.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "qux int qux ( )\n",
+            "\n",
+            "/* common.symtypes */\n",
+            "s#foo struct foo { int b ; }\n",
+            "bar2 int bar2 ( s#foo )\n", //
+        )
+    );
+
+    let (result, output_data) = ksymtypes_consolidate_on_duplicate_run("rename");
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        output_data,
+        concat!(
+            "/* a.symtypes */\n",
+            "baz int baz ( )\n",
+            "\n",
+            "/* b.symtypes */\n",
+            "qux int qux ( )\n",
+            "\n",
+            "/* common.2.symtypes */\n",
+            "s#foo struct foo { int b ; }\n",
+            "bar2 int bar2 ( s#foo )\n",
+            "\n",
+            "/* common.symtypes */\n",
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_consolidate_update_multiple() {
+    // Check that the consolidate command rejects combining --update with multiple input paths.
+    let result = ksymtypes_run([
+        "consolidate",
+        "--update=tests/it/ksymtypes/consolidate.symtypes",
+        "tests/it/ksymtypes/consolidate_on_duplicate/a",
+        "tests/it/ksymtypes/consolidate_on_duplicate/b",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The --update option cannot be used with multiple consolidate sources\n"
+    );
+}
+
+#[test]
+fn ksymtypes_split() {
+    // Check that the split command trivially works.
+    let output_path = tmp_path("tests/it/ksymtypes/split");
+    fs::remove_dir_all(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/split/consolidated.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        fs::read_to_string(output_path.join("a.symtypes")).unwrap(),
+        concat!(
+            "s#foo struct foo { int a ; }\n",
+            "bar int bar ( s#foo )\n", //
+        )
+    );
+    assert_eq!(
+        fs::read_to_string(output_path.join("b.symtypes")).unwrap(),
+        concat!(
+            "s#foo struct foo { int a ; }\n",
+            "baz int baz ( s#foo )\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_split_manifest() {
+    // Check that the split command can write a sorted manifest of the output files.
+    let output_path = tmp_path("tests/it/ksymtypes/split_manifest");
+    let manifest_path = tmp_path("tests/it/ksymtypes/split_manifest.sha256");
+    fs::remove_dir_all(&output_path).ok();
+    fs::remove_file(&manifest_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "-j4".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        &concat_os("--manifest=", &manifest_path),
+        "tests/it/ksymtypes/split/consolidated.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        fs::read_to_string(manifest_path).unwrap(),
+        concat!(
+            "2aa3e09a2a0dc16d28a92ad6dae47a375f32f3a870b7ea8be0e9c5b4e437af36  a.symtypes\n",
+            "4fcb94a8e30d9853d794e3790c4c84f831aad63946b4035cf2c7f8bfb1e7de88  b.symtypes\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_split_tar() {
+    // Check that the split command can write the output files into a tar archive.
+    let output_path = tmp_path("tests/it/ksymtypes/split_tar.tar");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "-j1".as_ref(),
+        &concat_os("--output=", &output_path),
+        "tests/it/ksymtypes/split/consolidated.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    // Each file occupies a header block and a data block, followed by two terminating blocks.
+    let archive = fs::read(output_path).unwrap();
+    assert_eq!(archive.len(), 6 * 512);
+    assert_eq!(&archive[0..11], b"a.symtypes\0");
+    assert_eq!(&archive[257..263], b"ustar\0");
+    assert_eq!(
+        &archive[512..563],
+        b"s#foo struct foo { int a ; }\nbar int bar ( s#foo )\n"
+    );
+    assert_eq!(&archive[1024..1035], b"b.symtypes\0");
+    assert_eq!(
+        &archive[1536..1587],
+        b"s#foo struct foo { int a ; }\nbaz int baz ( s#foo )\n"
+    );
+    assert!(archive[2048..].iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn ksymtypes_split_only() {
+    // Check that the split command can extract only the files matching the --only patterns.
+    let output_path = tmp_path("tests/it/ksymtypes/split_only");
+    fs::remove_dir_all(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--only=drivers/scsi/*".as_ref(),
+        "--only=drivers/net/d.symtypes".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/split_only/consolidated.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert!(!output_path.join("drivers/net/a.symtypes").exists());
+    assert!(!output_path.join("fs/c.symtypes").exists());
+    assert_eq!(
+        fs::read_to_string(output_path.join("drivers/scsi/b.symtypes")).unwrap(),
+        concat!(
+            "s#bar struct bar { UNKNOWN }\n",
+            "s#foo struct foo { int a ; }\n",
+            "scsi_b int scsi_b ( s#foo , s#bar )\n", //
+        )
+    );
+    assert_eq!(
+        fs::read_to_string(output_path.join("drivers/net/d.symtypes")).unwrap(),
+        concat!(
+            "s#foo struct foo { long a ; }\n",
+            "net_d int net_d ( s#foo )\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_split_only_no_match() {
+    // Check that the split command fails if no file matches the --only patterns.
+    let output_path = tmp_path("tests/it/ksymtypes/split_only_no_match");
+    fs::remove_dir_all(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--only=net/*".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/split_only/consolidated.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        format!(
+            "Failed to write split symtypes to '{}': No file matching 'net/*' found\n",
+            output_path.display()
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_split_missing_output() {
+    // Check that the split command fails if no --output is specified.
+    let result = ksymtypes_run(["split", "tests/it/ksymtypes/split/consolidated.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "The split output is missing\n");
+}
+
+#[test]
+fn ksymtypes_split_invalid_input() {
+    // Check that the split command correctly propagates inner errors and writes them on the
+    // standard error output.
+    let output_path = tmp_path("tests/it/ksymtypes/split_invalid_input");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/missing".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_inexact!(
+        result.stderr,
+        "Failed to read symtypes from 'tests/it/ksymtypes/missing': Failed to open the file 'tests/it/ksymtypes/missing': *\n"
+    );
+}
+
+#[test]
+fn ksymtypes_split_non_file() {
+    // Check that the split command rejects an input path that is not a file.
+    let output_path = tmp_path("tests/it/ksymtypes/split_non_file");
+    fs::remove_file(&output_path).ok();
+    let input_path = Path::new("tests/it/ksymtypes/split_non_file");
+    assert!(input_path.is_dir());
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        input_path.as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_inexact!(
+        result.stderr,
+        "Failed to read symtypes from 'tests/it/ksymtypes/split_non_file': Failed to read symtypes data: Failed to read from the file 'tests/it/ksymtypes/split_non_file': *\n"
+    );
+}
+
+#[test]
+fn ksymtypes_split_reject_plain() {
+    // Check that the split command rejects loading a symtypes file in the non-consolidated format.
+    let output_path = tmp_path("tests/it/ksymtypes/split/reject_plain");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymtypes/split_reject_plain/a.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read symtypes from 'tests/it/ksymtypes/split_reject_plain/a.symtypes': Expected a consolidated symtypes file, but found an invalid header\n",
+            " tests/it/ksymtypes/split_reject_plain/a.symtypes:1\n",
+            " | s#foo struct foo { int a ; }\n", //
+        ),
+    );
+}
+
+#[test]
+fn ksymtypes_compare() {
+    // Check that the comparison of two different symtypes files shows relevant differences and
+    // results in the command exiting with a status of 1.
+    let result = ksymtypes_run([
+        "compare",
         "tests/it/ksymtypes/compare/a.symtypes",
         "tests/it/ksymtypes/compare/b.symtypes",
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_with_header() {
+    // Check that the --with-header option prepends a header describing the comparison to the pretty
+    // output.
+    let result = ksymtypes_run([
+        "compare",
+        "--with-header",
+        "--reproducible",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        format!(
+            concat!(
+                "# Generated by: ksymtypes {} compare\n",
+                "# Inputs: tests/it/ksymtypes/compare/a.symtypes tests/it/ksymtypes/compare/b.symtypes\n",
+                "# Options: --with-header --reproducible\n",
+                "The following '1' exports are different:\n",
+                " foo\n",
+                "\n",
+                "because of a changed 'foo':\n",
+                "@@ -1,1 +1,1 @@\n",
+                "-void foo ( int a )\n",
+                "+void foo ( long a )\n", //
+            ),
+            env!("SUSE_KABI_TOOLS_VERSION")
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_color() {
+    // Check that the --color=always option colorizes the diff of a changed type, even if the
+    // output is not a terminal.
+    let result = ksymtypes_run([
+        "compare",
+        "--color=always",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "\x1b[36m@@ -1,1 +1,1 @@\x1b[0m\n",
+            "\x1b[31m-void foo ( int a )\x1b[0m\n",
+            "\x1b[32m+void foo ( long a )\x1b[0m\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_strict() {
+    // Check that cosmetic differences of types are ignored by default and reported with the
+    // --strict option.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_strict/a.symtypes",
+        "tests/it/ksymtypes/compare_strict/b.symtypes",
+    ]);
+    assert!(result.status.success());
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "compare",
+        "--strict",
+        "tests/it/ksymtypes/compare_strict/a.symtypes",
+        "tests/it/ksymtypes/compare_strict/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo':\n",
+            "@@ -1,4 +1,4 @@\n",
+            " struct foo {\n",
+            "-\tint * a;\n",
+            "-\tint b __attribute__ ( ( aligned ( 8 ), packed ) );\n",
+            "+\tint *a;\n",
+            "+\tint b __attribute__ ( ( packed ) ) __attribute__ ( ( aligned ( 8 ) ) );\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_suggest_fixup() {
+    // Check that the --suggest-fixup option follows a changed type with a suggested workaround.
+    let result = ksymtypes_run([
+        "compare",
+        "--suggest-fixup",
+        "tests/it/ksymtypes/compare_suggest_fixup/a.symtypes",
+        "tests/it/ksymtypes/compare_suggest_fixup/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,4 +1,5 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            " \tint b;\n",
+            "+\tunsigned long c;\n",
+            " }\n",
+            "Suggested fixup (experimental):\n",
+            " New members are appended at the end of 's#foo'.\n",
+            " If only the owner allocates the struct and other code uses it through pointers,\n",
+            " hide them from genksyms:\n",
+            " #ifndef __GENKSYMS__\n",
+            " \tunsigned long c;\n",
+            " #endif\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_demangle() {
+    // Check that the --demangle option shows Rust export names in their demangled form, while
+    // symbol lists keep the raw names.
+    let result = ksymtypes_run([
+        "compare",
+        "--demangle",
+        "tests/it/ksymtypes/compare_demangle/a.symtypes",
+        "tests/it/ksymtypes/compare_demangle/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " kernel::print::call_printk\n",
+            "\n",
+            "because of a changed '_RNvNtCs1234_6kernel5print11call_printk':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-int _RNvNtCs1234_6kernel5print11call_printk ( int a )\n",
+            "+int _RNvNtCs1234_6kernel5print11call_printk ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "compare",
+        "--demangle",
+        "--format=symbols",
+        "tests/it/ksymtypes/compare_demangle/a.symtypes",
+        "tests/it/ksymtypes/compare_demangle/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "_RNvNtCs1234_6kernel5print11call_printk\n");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_jobs_auto() {
+    // Check that the compare command accepts '-j auto' to size the worker pool automatically.
+    let result = ksymtypes_run([
+        "compare",
+        "-j",
+        "auto",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_invalid_jobs() {
+    // Check that an invalid number of jobs is rejected.
+    let result = ksymtypes_run([
+        "compare",
+        "--jobs=many",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Invalid value for '--jobs=many': invalid digit found in string\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_stdin() {
+    // Check that the compare command can read one of the corpuses from the standard input.
+    let result = ksymtypes_run_stdin(
+        ["compare", "tests/it/ksymtypes/compare/a.symtypes", "-"],
+        &fs::read_to_string("tests/it/ksymtypes/compare/b.symtypes").unwrap(),
+    );
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_stdin_twice() {
+    // Check that the compare command rejects reading both corpuses from the standard input.
+    let result = ksymtypes_run_stdin(["compare", "-", "-"], "");
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Only one compare source can be read from the standard input\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_tar() {
+    // Check that the compare command can read split symtypes data from a tar archive.
+    let archive_path = tmp_path("tests/it/ksymtypes/compare_tar.tar");
+    fs::remove_file(&archive_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("split"),
+        &concat_os("--output=", &archive_path),
+        "tests/it/ksymtypes/split/consolidated.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("compare"),
+        "tests/it/ksymtypes/split/consolidated.symtypes".as_ref(),
+        archive_path.as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_consolidate_stdin() {
+    // Check that the consolidate command can read a consolidated corpus from the standard input.
+    let output_path = tmp_path("tests/it/ksymtypes/consolidate_stdin.symtypes");
+    let input = fs::read_to_string("tests/it/ksymtypes/split/consolidated.symtypes").unwrap();
+    let result = ksymtypes_run_stdin(
+        [
+            AsRef::<OsStr>::as_ref("consolidate"),
+            &concat_os("--output=", &output_path),
+            "-".as_ref(),
+        ],
+        &input,
+    );
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(fs::read_to_string(output_path).unwrap(), input);
+}
+
+#[test]
+fn ksymtypes_compare_word_diff() {
+    // Check that the --word-diff option marks the changed words in the type diff and that the
+    // --diff-context option is accepted.
+    let result = ksymtypes_run([
+        "compare",
+        "--word-diff",
+        "--diff-context=0",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "void foo ( [-int-]{+long+} a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_side_by_side() {
+    // Check that the side-by-side format shows the changed type in two columns.
+    let result = ksymtypes_run([
+        "compare",
+        "--format=side-by-side",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "void foo ( int a ) | void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_html() {
+    // Check that the HTML format writes a standalone report to the specified file.
+    let output_path = tmp_path("tests/it/ksymtypes/compare_html.html");
+    fs::remove_file(&output_path).ok();
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("compare"),
+        "--format=null".as_ref(),
+        format!("--format=html:{}", output_path.display()).as_ref(),
+        "tests/it/ksymtypes/compare/a.symtypes".as_ref(),
+        "tests/it/ksymtypes/compare/b.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert!(output_data.starts_with("<!DOCTYPE html>\n"));
+    assert!(output_data.contains(concat!(
+        "<tr id=\"export-foo\"><td><code>foo</code></td>",
+        "<td><a href=\"#change-0\"><code>foo</code></a></td></tr>\n", //
+    )));
+    assert!(output_data.contains(concat!(
+        "<span class=\"del\">-void foo ( int a )</span>\n",
+        "<span class=\"add\">+void foo ( long a )</span>\n", //
+    )));
+    assert!(output_data.ends_with("</html>\n"));
+}
+
+#[test]
+fn ksymtypes_compare_invalid_diff_context() {
+    // Check that the --diff-context option rejects a value that is not a number.
+    let result = ksymtypes_run([
+        "compare",
+        "--diff-context=many",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Invalid value for '--diff-context=many': invalid digit found in string\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_fail_on() {
+    // Check that the --fail-on option selects whether changes result in the exit code 1.
+    for (policy, code) in [("breaking", 1), ("any", 1), ("none", 0)] {
+        let result = ksymtypes_run([
+            "compare",
+            "--format=null",
+            &format!("--fail-on={}", policy),
+            "tests/it/ksymtypes/compare/a.symtypes",
+            "tests/it/ksymtypes/compare/b.symtypes",
+        ]);
+        assert_eq!(result.status.code().unwrap(), code);
+        assert_eq!(result.stdout, "");
+        assert_eq!(result.stderr, "");
+    }
+}
+
+#[test]
+fn ksymtypes_compare_short_circuit() {
+    // Check that the --short-circuit option only reports the comparison outcome via the exit code.
+    let result = ksymtypes_run([
+        "compare",
+        "--short-circuit",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "compare",
+        "--short-circuit",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/a.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "compare",
+        "--short-circuit",
+        "--suppressions=tests/it/ksymtypes/compare_suppressions/suppressions.txt",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_short_circuit_fail_on_none() {
+    // Check that the --short-circuit option is rejected when no change can fail the comparison.
+    let result = ksymtypes_run([
+        "compare",
+        "--short-circuit",
+        "--fail-on=none",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The --short-circuit option cannot be used together with --fail-on=none\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_suppressions() {
+    // Check that the compare command ignores acknowledged differences.
+    let result = ksymtypes_run([
+        "compare",
+        "--suppressions=tests/it/ksymtypes/compare_suppressions/suppressions.txt",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_cache_dir() {
+    // Check that the compare command with --cache-dir caches the parsed corpuses and produces the
+    // same result when the caches are reused.
+    let cache_dir = tmp_path("tests/it/ksymtypes/compare_cache_dir");
+    fs::remove_dir_all(&cache_dir).ok();
+    for _ in 0..2 {
+        let result = ksymtypes_run([
+            AsRef::<OsStr>::as_ref("compare"),
+            &concat_os("--cache-dir=", &cache_dir),
+            "tests/it/ksymtypes/compare/a.symtypes".as_ref(),
+            "tests/it/ksymtypes/compare/b.symtypes".as_ref(),
+        ]);
+        assert_eq!(result.status.code().unwrap(), 1);
+        assert_eq!(
+            result.stdout,
+            concat!(
+                "The following '1' exports are different:\n",
+                " foo\n",
+                "\n",
+                "because of a changed 'foo':\n",
+                "@@ -1,1 +1,1 @@\n",
+                "-void foo ( int a )\n",
+                "+void foo ( long a )\n", //
+            )
+        );
+        assert_eq!(result.stderr, "");
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 2);
+    }
+}
+
+#[test]
+fn ksymtypes_compare_dash_dash() {
+    // Check that operands of the compare command can be specified after '--'.
+    let result = ksymtypes_run([
+        "compare",
+        "--",
+        "tests/it/ksymtypes/compare/a.symtypes",
+        "tests/it/ksymtypes/compare/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_build_tree() {
+    // Check that when an input is a kernel build directory with symvers files, only symbols that
+    // are actually exported by the build are compared.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_build_tree/a.symtypes",
+        "tests/it/ksymtypes/compare_build_tree/build",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "Export 'baz' has been removed\n");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_symbol_prefix() {
+    // Check that a symbol prefix used by one of the corpuses is detected and stripped
+    // automatically, and that the detection can be disabled by specifying an empty prefix.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_symbol_prefix/a.symtypes",
+        "tests/it/ksymtypes/compare_symbol_prefix/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "compare",
+        "--symbol-prefix=",
+        "--format=symbols",
+        "tests/it/ksymtypes/compare_symbol_prefix/a.symtypes",
+        "tests/it/ksymtypes/compare_symbol_prefix/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "_bar\n", "_foo\n", "bar\n", "foo\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_split_and_consolidated() {
+    // Check that the compare command works when one input is a directory with split symtypes files
+    // and the second input is a consolidated symtypes file.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_split_and_consolidated/a",
+        "tests/it/ksymtypes/compare_split_and_consolidated/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn ksymtypes_compare_compressed() {
+    // Check that the compare command transparently decompresses symtypes files, both when found
+    // in a directory and when specified directly.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_compressed/a",
+        "tests/it/ksymtypes/compare_compressed/b.symtypes.gz",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-void foo ( int a )\n",
+            "+void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_filter_symbol_list() {
+    // Check that the comparison of two symtypes files can be restricted to specific exports.
+
+    // Check that the result is sensible without a filter.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_filter_symbol_list/a.symtypes",
+        "tests/it/ksymtypes/compare_filter_symbol_list/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '3' exports are different:\n",
+            " bar\n",
+            " baz\n",
+            " qux\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    // Check the result when using a filter.
+    let result = ksymtypes_run([
+        "compare",
+        "--filter-symbol-list=tests/it/ksymtypes/compare_filter_symbol_list/filter-symbol-list.txt",
+        "tests/it/ksymtypes/compare_filter_symbol_list/a.symtypes",
+        "tests/it/ksymtypes/compare_filter_symbol_list/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '2' exports are different:\n",
+            " bar\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_exclude_symbol_list() {
+    // Check that specific exports can be excluded from the comparison of two symtypes files.
+    let result = ksymtypes_run([
+        "compare",
+        "--exclude-symbol-list=tests/it/ksymtypes/compare_exclude_symbol_list/exclude-symbol-list.txt",
+        "tests/it/ksymtypes/compare_exclude_symbol_list/a.symtypes",
+        "tests/it/ksymtypes/compare_exclude_symbol_list/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_symbol_patterns() {
+    // Check that the comparison of two symtypes files can be restricted using patterns specified
+    // on the command line.
+    let result = ksymtypes_run([
+        "compare",
+        "--filter-symbol=ba*",
+        "--exclude-symbol=baz",
+        "tests/it/ksymtypes/compare_symbol_patterns/a.symtypes",
+        "tests/it/ksymtypes/compare_symbol_patterns/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct foo {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_filter_type_list() {
+    // Check that the comparison of two symtypes files can be restricted to specific types.
+    let result = ksymtypes_run([
+        "compare",
+        "--filter-type-list=tests/it/ksymtypes/compare_filter_type_list/filter-type-list.txt",
+        "tests/it/ksymtypes/compare_filter_type_list/a.symtypes",
+        "tests/it/ksymtypes/compare_filter_type_list/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_ignore_types() {
+    // Check that changes in types listed in the ignore list, including their subtypes, are not
+    // reported.
+    let result = ksymtypes_run([
+        "compare",
+        "--ignore-types=tests/it/ksymtypes/compare_ignore_types/ignore-types.txt",
+        "tests/it/ksymtypes/compare_ignore_types/a.symtypes",
+        "tests/it/ksymtypes/compare_ignore_types/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_rules() {
+    // Check that changes in types matched by tolerating severity rules are annotated with the rule
+    // in place of their severity, even if the change is classified as breaking, and don't break the
+    // affected exports.
+    let result = ksymtypes_run([
+        "compare",
+        "--rules=tests/it/ksymtypes/compare_rules/tolerate-kvm.severities",
+        "tests/it/ksymtypes/compare_rules/a.symtypes",
+        "tests/it/ksymtypes/compare_rules/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n",
+            "\n",
+            "The following '1' exports are different:\n",
+            " bar\n",
+            "\n",
+            "because of a changed 's#kvm_vcpu' (member type changed, tolerated by rule tests/it/ksymtypes/compare_rules/tolerate-kvm.severities:1 's#kvm_* PASS'):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct kvm_vcpu {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_rules_json() {
+    // Check that the JSON format classifies each changed type and records the tolerating rule.
+    let result = ksymtypes_run([
+        "compare",
+        "--format=json",
+        "--rules=tests/it/ksymtypes/compare_rules/tolerate-kvm.severities",
+        "tests/it/ksymtypes/compare_rules/a.symtypes",
+        "tests/it/ksymtypes/compare_rules/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "{\"added\":[],\"removed\":[],\"changed_types\":[",
+            "{\"type\":\"s#foo\",\"exports\":[\"baz\"],\"class\":\"member type changed\",",
+            "\"verdict\":\"breaking\",\"rule\":null},",
+            "{\"type\":\"s#kvm_vcpu\",\"exports\":[\"bar\"],\"class\":\"member type changed\",",
+            "\"verdict\":\"tolerated\",\"rule\":",
+            "\"tests/it/ksymtypes/compare_rules/tolerate-kvm.severities:1 's#kvm_* PASS'\"}",
+            "]}\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_rules_arch() {
+    // Check that severity rules guarded by an architecture apply only when it is selected.
+    let result = ksymtypes_run([
+        "compare",
+        "--rules=tests/it/ksymtypes/compare_rules_arch/severities.txt",
+        "--arch=x86_64",
+        "--format=short",
+        "tests/it/ksymtypes/compare_rules_arch/a.symtypes",
+        "tests/it/ksymtypes/compare_rules_arch/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "compare",
+        "--rules=tests/it/ksymtypes/compare_rules_arch/severities.txt",
+        "--format=symbols",
+        "tests/it/ksymtypes/compare_rules_arch/a.symtypes",
+        "tests/it/ksymtypes/compare_rules_arch/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "bar\n", "baz\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_rules_tolerated() {
+    // Check that the comparison succeeds if all changes are tolerated and that the short format
+    // omits changes tolerated by a PASS rule.
+    let result = ksymtypes_run([
+        "compare",
+        "-r",
+        "tests/it/ksymtypes/compare_rules/tolerate-all.severities",
+        "--format=short",
+        "tests/it/ksymtypes/compare_rules/a.symtypes",
+        "tests/it/ksymtypes/compare_rules/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " baz\n",
+            "\n",
+            "because of a changed 's#foo' (member type changed, tolerated with a warning by rule tests/it/ksymtypes/compare_rules/tolerate-all.severities:2 's#foo WARN'):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_rules_per_file() {
+    // Check that the --rules option is rejected in the per-file mode.
+    let result = ksymtypes_run([
+        "compare",
+        "--rules=tests/it/ksymtypes/compare_rules/tolerate-all.severities",
+        "--per-file",
+        "tests/it/ksymtypes/compare_rules/a.symtypes",
+        "tests/it/ksymtypes/compare_rules/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The --rules option cannot be used together with --per-file\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_duplicates() {
+    // Check that the --duplicates option selects which occurrence of a duplicate export is
+    // compared.
+    let result = ksymtypes_run([
+        "compare",
+        "tests/it/ksymtypes/compare_duplicates/a.symtypes",
+        "tests/it/ksymtypes/compare_duplicates/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports are different:\n",
+            " foo\n",
+            "\n",
+            "because of a changed 'foo':\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-int foo ( )\n",
+            "+long foo ( )\n", //
+        )
+    );
+    assert_eq!(
+        result.stderr,
+        "tests/it/ksymtypes/compare_duplicates/a.symtypes:5: WARNING: Export 'foo' defined in 'b.symtypes' is duplicate, previous occurrence found in 'a.symtypes'\n"
+    );
+
+    let result = ksymtypes_run([
+        "compare",
+        "--duplicates=last",
+        "tests/it/ksymtypes/compare_duplicates/a.symtypes",
+        "tests/it/ksymtypes/compare_duplicates/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "tests/it/ksymtypes/compare_duplicates/a.symtypes:5: WARNING: Export 'foo' defined in 'b.symtypes' is duplicate, previous occurrence found in 'a.symtypes'\n"
+    );
+
+    let result = ksymtypes_run([
+        "compare",
+        "--duplicates=error",
+        "tests/it/ksymtypes/compare_duplicates/a.symtypes",
+        "tests/it/ksymtypes/compare_duplicates/b.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Failed to read symtypes from 'tests/it/ksymtypes/compare_duplicates/a.symtypes': tests/it/ksymtypes/compare_duplicates/a.symtypes:5: Export 'foo' defined in 'b.symtypes' is duplicate, previous occurrence found in 'a.symtypes'\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_group_by() {
+    // Check that the exports affected by a changed type can be grouped by their symtypes file.
+    let result = ksymtypes_run([
+        "compare",
+        "--group-by=file",
+        "tests/it/ksymtypes/compare_group_by/a",
+        "tests/it/ksymtypes/compare_group_by/b",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '4' exports are different:\n",
+            " kernel/fork.symtypes (1):\n",
+            "  fork_task\n",
+            " kernel/sched.symtypes (2):\n",
+            "  sched_task\n",
+            "  sched_yield_task\n",
+            " mm/slab.symtypes (1):\n",
+            "  slab_task\n",
+            "\n",
+            "because of a changed 's#task' (member type changed, breaking):\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct task {\n",
+            "-\tint pid;\n",
+            "+\tlong pid;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_invalid_group_by() {
+    // Check that an unrecognized grouping mode is rejected.
+    let result = ksymtypes_run([
+        "compare",
+        "--group-by=subsystem",
+        "tests/it/ksymtypes/compare_group_by/a",
+        "tests/it/ksymtypes/compare_group_by/b",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "Unrecognized grouping 'subsystem'\n");
+}
+
+#[test]
+fn ksymtypes_compare_per_file() {
+    // Check that the --per-file option compares files with the same path, detects moved files and
+    // reports changes in types that are not reachable from any export.
+    let result = ksymtypes_run([
+        "compare",
+        "--per-file",
+        "tests/it/ksymtypes/compare_per_file/a",
+        "tests/it/ksymtypes/compare_per_file/b",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "File 'fresh.symtypes' has been added\n",
+            "File 'gone.symtypes' has been removed\n",
+            "File 'old.symtypes' has been moved to 'new.symtypes'\n",
+            "\n",
+            "/* drivers/test.symtypes */\n",
+            "Type 's#priv' has been modified:\n",
+            "@@ -1,3 +1,4 @@\n",
+            " struct priv {\n",
+            " \tint a;\n",
+            "+\tint b;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_compare_per_file_invalid_format() {
+    // Check that the --per-file option rejects formats that it does not support.
+    let result = ksymtypes_run([
+        "compare",
+        "--per-file",
+        "--format=summary",
+        "tests/it/ksymtypes/compare_per_file/a",
+        "tests/it/ksymtypes/compare_per_file/b",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The html, summary and json formats cannot be used with '--per-file'\n"
+    );
+}
+
+#[test]
+fn ksymtypes_compare_format() {
+    // Check that the comparison allows specifying the output format.
+    //
+    // NOTE: Keep this test synchronized with the compare_format_short symtypes unit test.
+    fn expected_path(file: &str) -> PathBuf {
+        Path::new("tests/it/ksymtypes/compare_format/").join(file)
+    }
+
+    fn tmp_path(file: &str) -> PathBuf {
+        crate::common::tmp_path(Path::new("tests/it/ksymtypes/compare_format/").join(file))
+    }
+
+    fs::remove_dir_all(tmp_path("")).ok();
+
+    let pretty_out_path = tmp_path("pretty.out");
+    let short_out_path = tmp_path("short.out");
+    let symbols_out_path = tmp_path("symbols.out");
+    let mod_symbols_out_path = tmp_path("mod_symbols.out");
+    let summary_out_path = tmp_path("summary.out");
+    let result = ksymtypes_run([
+        AsRef::<OsStr>::as_ref("compare"),
+        "--format=null".as_ref(),
+        &concat_os("--format=pretty:", &pretty_out_path),
+        &concat_os("--format=short:", &short_out_path),
+        &concat_os("--format=symbols:", &symbols_out_path),
+        &concat_os("--format=mod-symbols:", &mod_symbols_out_path),
+        &concat_os("--format=summary:", &summary_out_path),
+        "tests/it/ksymtypes/compare_format/a.symtypes".as_ref(),
+        "tests/it/ksymtypes/compare_format/b.symtypes".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    let pretty_out = fs::read_to_string(&pretty_out_path).unwrap();
+    let pretty_exp = fs::read_to_string(expected_path("pretty.exp")).unwrap();
+    assert_eq!(pretty_out, pretty_exp);
+
+    let short_out = fs::read_to_string(&short_out_path).unwrap();
+    let short_exp = fs::read_to_string(expected_path("short.exp")).unwrap();
+    assert_eq!(short_out, short_exp);
+
+    let symbols_out = fs::read_to_string(&symbols_out_path).unwrap();
+    let symbols_exp = fs::read_to_string(expected_path("symbols.exp")).unwrap();
+    assert_eq!(symbols_out, symbols_exp);
+
+    let mod_symbols_out = fs::read_to_string(&mod_symbols_out_path).unwrap();
+    let mod_symbols_exp = fs::read_to_string(expected_path("mod_symbols.exp")).unwrap();
+    assert_eq!(mod_symbols_out, mod_symbols_exp);
+
+    let summary_out = fs::read_to_string(&summary_out_path).unwrap();
+    let summary_exp = fs::read_to_string(expected_path("summary.exp")).unwrap();
+    assert_eq!(summary_out, summary_exp);
+}
+
+#[test]
+fn ksymtypes_show() {
+    // Check that the show command outputs the definition of a given export.
+    let result = ksymtypes_run(["show", "bar", "tests/it/ksymtypes/show"]);
+    assert!(result.status.success());
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "/* test.symtypes */\n",
+            "int bar ( s#foo )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_show_recursive() {
+    // Check that the show command with '--recursive' outputs also all referenced types.
+    let result = ksymtypes_run(["show", "--recursive", "bar", "tests/it/ksymtypes/show"]);
+    assert!(result.status.success());
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "/* test.symtypes */\n",
+            "int bar ( s#foo )\n",
+            "\n",
+            "s#foo:\n",
+            "struct foo {\n",
+            "\tint a;\n",
+            "}\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_show_missing() {
+    // Check that the show command reports an error when the symbol is not found.
+    let result = ksymtypes_run(["show", "baz", "tests/it/ksymtypes/show"]);
+    assert!(!result.status.success());
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Symbol 'baz' is not found in 'tests/it/ksymtypes/show'\n"
+    );
+}
+
+#[test]
+fn ksymtypes_cross_check() {
+    // Check that the cross-check command reports types defined differently across the corpuses and
+    // exits with 1.
+    let result = ksymtypes_run([
+        "cross-check",
+        "tests/it/ksymtypes/cross_check/default.symtypes",
+        "tests/it/ksymtypes/cross_check/rt.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "The following '1' exports use differing definitions of 's#foo':\n",
+            " bar\n",
+            "\n",
+            "'tests/it/ksymtypes/cross_check/default.symtypes' vs. 'tests/it/ksymtypes/cross_check/rt.symtypes':\n",
+            "@@ -1,3 +1,3 @@\n",
+            " struct foo {\n",
+            "-\tint a;\n",
+            "+\tlong a;\n",
+            " }\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_cross_check_filter_type_list() {
+    // Check that the cross-check command checks only types matching the --filter-type-list
+    // patterns.
+    let result = ksymtypes_run([
+        "cross-check",
+        "--filter-type-list=tests/it/ksymtypes/cross_check/qux.types",
+        "tests/it/ksymtypes/cross_check/default.symtypes",
+        "tests/it/ksymtypes/cross_check/rt.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_cross_check_single_source() {
+    // Check that the cross-check command requires at least two corpuses.
+    let result = ksymtypes_run([
+        "cross-check",
+        "tests/it/ksymtypes/cross_check/default.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
     assert_eq!(
+        result.stderr,
+        "The cross-check requires at least two sources\n"
+    );
+}
+
+#[cfg(feature = "browse")]
+#[test]
+fn ksymtypes_browse_missing_source() {
+    // Check that the browse command requires two sources.
+    let result = ksymtypes_run(["browse", "tests/it/ksymtypes/compare/a.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "The second browse source is missing\n");
+}
+
+#[test]
+fn ksymtypes_stats() {
+    // Check that the stats command shows summary statistics about a corpus.
+    let result = ksymtypes_run(["stats", "--top=2", "tests/it/ksymtypes/stats/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_inexact!(
         result.stdout,
         concat!(
-            "The following '1' exports are different:\n",
-            " foo\n",
-            "\n",
-            "because of a changed 'foo':\n",
-            "@@ -1,1 +1,1 @@\n",
-            "-void foo ( int a )\n",
-            "+void foo ( long a )\n", //
+            "Files: 3\n",
+            "Exports: 3\n",
+            "Types: 4\n",
+            "Type variants: 6\n",
+            "Types by the number of variants:\n",
+            " 1: 3\n",
+            " 3: 1\n",
+            "Types with the most variants:\n",
+            " s#foo: 3\n",
+            " bar: 1\n",
+            "Estimated memory footprint:\n",
+            " Type tokens: * bytes\n",
+            " Token strings: * bytes\n",
+            " File records: * bytes\n",
+            " Total: * bytes\n", //
         )
     );
     assert_eq!(result.stderr, "");
 }
 
 #[test]
-fn ksymtypes_compare_split_and_consolidated() {
-    // Check that the compare command works when one input is a directory with split symtypes files
-    // and the second input is a consolidated symtypes file.
+fn ksymtypes_graph() {
+    // Check that the graph command writes the type-reference graph of an export to a file.
+    let output_path = tmp_path("tests/ksymtypes/graph/qux.dot");
+    fs::remove_file(&output_path).ok();
     let result = ksymtypes_run([
-        "compare",
-        "tests/it/ksymtypes/compare_split_and_consolidated/a",
-        "tests/it/ksymtypes/compare_split_and_consolidated/b.symtypes",
+        AsRef::<OsStr>::as_ref("graph"),
+        &concat_os("--output=", &output_path),
+        "qux".as_ref(),
+        "tests/it/ksymtypes/graph/test.symtypes".as_ref(),
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(&output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "digraph \"qux\" {\n",
+            "\t\"qux\" -> \"s#bar\";\n",
+            "\t\"qux\" -> \"s#foo\";\n",
+            "\t\"s#bar\" -> \"s#foo\";\n",
+            "\t\"s#bar\" -> \"s#baz\";\n",
+            "\t\"s#baz\" -> \"s#foo\";\n",
+            "\t\"s#foo\" -> \"s#baz\";\n",
+            "}\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymtypes_graph_missing() {
+    // Check that the graph command fails if the export is not found.
+    let result = ksymtypes_run(["graph", "s#foo", "tests/it/ksymtypes/graph/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Export 's#foo' is not found in 'tests/it/ksymtypes/graph/test.symtypes'\n"
+    );
+}
+
+#[test]
+fn ksymtypes_users() {
+    // Check that the users command shows all exports referencing a type, grouped by file.
+    let result = ksymtypes_run(["users", "s#foo", "tests/it/ksymtypes/users/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(
         result.stdout,
         concat!(
-            "The following '1' exports are different:\n",
-            " foo\n",
+            "/* a.symtypes */\n",
+            "baz\n",
+            "qux\n",
             "\n",
-            "because of a changed 'foo':\n",
-            "@@ -1,1 +1,1 @@\n",
-            "-void foo ( int a )\n",
-            "+void foo ( long a )\n", //
+            "/* b.symtypes */\n",
+            "corge\n", //
         )
     );
     assert_eq!(result.stderr, "");
 }
 
 #[test]
-fn ksymtypes_compare_filter_symbol_list() {
-    // Check that the comparison of two symtypes files can be restricted to specific exports.
+fn ksymtypes_users_missing() {
+    // Check that the users command fails if the type is not found.
+    let result = ksymtypes_run(["users", "s#bar2", "tests/it/ksymtypes/users/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Type 's#bar2' is not found in 'tests/it/ksymtypes/users/test.symtypes'\n"
+    );
+}
 
-    // Check that the result is sensible without a filter.
+#[test]
+fn ksymtypes_history() {
+    // Check that the history command shows how an export changed across the specified corpuses.
     let result = ksymtypes_run([
-        "compare",
-        "tests/it/ksymtypes/compare_filter_symbol_list/a.symtypes",
-        "tests/it/ksymtypes/compare_filter_symbol_list/b.symtypes",
+        "history",
+        "bar",
+        "tests/it/ksymtypes/history/2024-01.symtypes",
+        "tests/it/ksymtypes/history/2024-06.symtypes",
+        "tests/it/ksymtypes/history/2025-01.symtypes",
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(
         result.stdout,
         concat!(
-            "The following '3' exports are different:\n",
-            " bar\n",
-            " baz\n",
-            " qux\n",
-            "\n",
-            "because of a changed 's#foo':\n",
+            "tests/it/ksymtypes/history/2024-01.symtypes: 'bar' is defined\n",
+            "tests/it/ksymtypes/history/2024-06.symtypes: 'bar' is unchanged\n",
+            "tests/it/ksymtypes/history/2025-01.symtypes: 'bar' has changed\n",
+            "because of a changed 's#foo' (member appended, probably safe):\n",
             "@@ -1,3 +1,4 @@\n",
             " struct foo {\n",
             " \tint a;\n",
@@ -315,23 +2226,20 @@ fn ksymtypes_compare_filter_symbol_list() {
         )
     );
     assert_eq!(result.stderr, "");
+}
 
-    // Check the result when using a filter.
-    let result = ksymtypes_run([
-        "compare",
-        "--filter-symbol-list=tests/it/ksymtypes/compare_filter_symbol_list/filter-symbol-list.txt",
-        "tests/it/ksymtypes/compare_filter_symbol_list/a.symtypes",
-        "tests/it/ksymtypes/compare_filter_symbol_list/b.symtypes",
-    ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+#[test]
+fn ksymtypes_history_directory() {
+    // Check that the history command reads a single directory as a collection of consolidated
+    // files, ordered by their name.
+    let result = ksymtypes_run(["history", "s#foo", "tests/it/ksymtypes/history"]);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(
         result.stdout,
         concat!(
-            "The following '2' exports are different:\n",
-            " bar\n",
-            " baz\n",
-            "\n",
-            "because of a changed 's#foo':\n",
+            "tests/it/ksymtypes/history/2024-01.symtypes: 's#foo' is defined\n",
+            "tests/it/ksymtypes/history/2024-06.symtypes: 's#foo' is unchanged\n",
+            "tests/it/ksymtypes/history/2025-01.symtypes: 's#foo' has changed\n",
             "@@ -1,3 +1,4 @@\n",
             " struct foo {\n",
             " \tint a;\n",
@@ -343,51 +2251,213 @@ fn ksymtypes_compare_filter_symbol_list() {
 }
 
 #[test]
-fn ksymtypes_compare_format() {
-    // Check that the comparison allows specifying the output format.
-    //
-    // NOTE: Keep this test synchronized with the compare_format_short symtypes unit test.
-    fn expected_path(file: &str) -> PathBuf {
-        Path::new("tests/it/ksymtypes/compare_format/").join(file)
-    }
+fn ksymtypes_unknowns() {
+    // Check that the unknowns command shows all types declared as UNKNOWN and the exports that
+    // depend on them.
+    let result = ksymtypes_run(["unknowns", "tests/it/ksymtypes/unknowns/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "s#foo\n", " baz\n", " corge\n", " quux\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
 
-    fn tmp_path(file: &str) -> PathBuf {
-        crate::common::tmp_path(Path::new("tests/it/ksymtypes/compare_format/").join(file))
-    }
+#[test]
+fn ksymtypes_format() {
+    // Check that the format command pretty formats records read from the standard input.
+    let result = ksymtypes_run_stdin(
+        ["format"],
+        concat!(
+            "s#foo struct foo { int a ; }\n",
+            "\n",
+            "bar int bar ( s#foo * )\n", //
+        ),
+    );
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "struct foo {\n",
+            "\tint a;\n",
+            "}\n",
+            "\n",
+            "int bar ( s#foo * )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
 
-    fs::remove_dir_all(tmp_path("")).ok();
+#[test]
+fn ksymtypes_format_excess_argument() {
+    // Check that the format command rejects any positional argument.
+    let result = ksymtypes_run_stdin(["format", "test.symtypes"], "");
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Excess format argument 'test.symtypes' specified\n"
+    );
+}
+
+#[test]
+fn ksymtypes_warnings() {
+    // Check that warnings are printed by default and can be ignored or treated as errors.
+    let result = ksymtypes_run(["show", "foo", "tests/it/ksymtypes/warnings/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "/* a.symtypes */\n",
+            "int foo ( )\n", //
+        )
+    );
+    assert_eq!(
+        result.stderr,
+        "tests/it/ksymtypes/warnings/test.symtypes:6: WARNING: Export 'foo' defined in 'b.symtypes' is duplicate, previous occurrence found in 'a.symtypes'\n"
+    );
 
-    let pretty_out_path = tmp_path("pretty.out");
-    let short_out_path = tmp_path("short.out");
-    let symbols_out_path = tmp_path("symbols.out");
-    let mod_symbols_out_path = tmp_path("mod_symbols.out");
     let result = ksymtypes_run([
-        AsRef::<OsStr>::as_ref("compare"),
-        "--format=null".as_ref(),
-        &concat_os("--format=pretty:", &pretty_out_path),
-        &concat_os("--format=short:", &short_out_path),
-        &concat_os("--format=symbols:", &symbols_out_path),
-        &concat_os("--format=mod-symbols:", &mod_symbols_out_path),
-        "tests/it/ksymtypes/compare_format/a.symtypes".as_ref(),
-        "tests/it/ksymtypes/compare_format/b.symtypes".as_ref(),
+        "show",
+        "--warnings=ignore",
+        "foo",
+        "tests/it/ksymtypes/warnings/test.symtypes",
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "/* a.symtypes */\n",
+            "int foo ( )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    let result = ksymtypes_run([
+        "show",
+        "--warnings=error",
+        "foo",
+        "tests/it/ksymtypes/warnings/test.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
     assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Failed to read symtypes from 'tests/it/ksymtypes/warnings/test.symtypes': tests/it/ksymtypes/warnings/test.symtypes:6: Export 'foo' defined in 'b.symtypes' is duplicate, previous occurrence found in 'a.symtypes'\n"
+    );
+}
+
+#[test]
+fn ksymtypes_warnings_ignore_list() {
+    // Check that warnings about names in the ignore list are silenced, even if warnings are
+    // treated as errors.
+    let result = ksymtypes_run([
+        "show",
+        "--warnings=error",
+        "--ignore-warnings-list=tests/it/ksymtypes/warnings/ignore.list",
+        "foo",
+        "tests/it/ksymtypes/warnings/test.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "/* a.symtypes */\n",
+            "int foo ( )\n", //
+        )
+    );
     assert_eq!(result.stderr, "");
+}
 
-    let pretty_out = fs::read_to_string(&pretty_out_path).unwrap();
-    let pretty_exp = fs::read_to_string(expected_path("pretty.exp")).unwrap();
-    assert_eq!(pretty_out, pretty_exp);
+#[test]
+fn ksymtypes_crc() {
+    // Check that the crc command computes CRCs of all exports in a symtypes corpus.
+    let result = ksymtypes_run(["crc", "tests/it/ksymtypes/crc/test.symtypes"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "0x9aedbfd8 NlsUniUpperRange\n",
+            "0x4c81e490 NlsUniUpperTable\n",
+            "0x69dd3b5b crc32_le\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
 
-    let short_out = fs::read_to_string(&short_out_path).unwrap();
-    let short_exp = fs::read_to_string(expected_path("short.exp")).unwrap();
-    assert_eq!(short_out, short_exp);
+#[test]
+fn ksymtypes_crc_symvers() {
+    // Check that the crc command reports exports whose CRC differs from a symvers file.
+    let result = ksymtypes_run([
+        "crc",
+        "--symvers=tests/it/ksymtypes/crc/test.symvers",
+        "tests/it/ksymtypes/crc/test.symtypes",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'NlsUniUpperRange' has CRC '0x9aedbfd8' in symtypes but '0x12345678' in symvers\n"
+    );
+    assert_eq!(result.stderr, "");
+}
 
-    let symbols_out = fs::read_to_string(&symbols_out_path).unwrap();
-    let symbols_exp = fs::read_to_string(expected_path("symbols.exp")).unwrap();
-    assert_eq!(symbols_out, symbols_exp);
+#[test]
+fn ksymtypes_crc_missing() {
+    // Check that the crc command fails if a specified export is not found.
+    let result = ksymtypes_run([
+        "crc",
+        "tests/it/ksymtypes/crc/test.symtypes",
+        "crc32_le",
+        "qux",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "0x69dd3b5b crc32_le\n");
+    assert_eq!(
+        result.stderr,
+        "Export 'qux' is not found in 'tests/it/ksymtypes/crc/test.symtypes'\n"
+    );
+}
 
-    let mod_symbols_out = fs::read_to_string(&mod_symbols_out_path).unwrap();
-    let mod_symbols_exp = fs::read_to_string(expected_path("mod_symbols.exp")).unwrap();
-    assert_eq!(mod_symbols_out, mod_symbols_exp);
+#[test]
+fn ksymtypes_validate() {
+    // Check that validating a correct symtypes corpus reports no problems.
+    let result = ksymtypes_run(["validate", "tests/it/ksymtypes/validate/ok"]);
+    assert!(result.status.success());
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_validate_problems() {
+    // Check that validating a symtypes corpus reports all found problems and results in the
+    // command exiting with a status of 1.
+    let result = ksymtypes_run(["validate", "tests/it/ksymtypes/validate/bad"]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Type 's#qux' is not known\n",
+            " tests/it/ksymtypes/validate/bad/b.symtypes:2\n",
+            " | baz int baz ( s#qux )\n",
+            "Duplicate record 'baz'\n",
+            " tests/it/ksymtypes/validate/bad/b.symtypes:3\n",
+            " | baz int baz ( s#foo )\n",
+            "Expected a plain symtypes file, but found consolidated data\n",
+            " tests/it/ksymtypes/validate/bad/c.symtypes:1\n",
+            " | /* c.symtypes */\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymtypes_man_page() {
+    // Check that the shipped man page documents the same commands and options as the man page
+    // generated from the command-line definitions.
+    let result = ksymtypes_run(["--dump-man"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_man_page_matches(&result.stdout, "doc/ksymtypes.1", &["browse"]);
+    assert_eq!(result.stderr, "");
 }
@@ -5,97 +5,1113 @@ use crate::common::*;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
+use suse_kabi_tools::assert_inexact;
+
+#[test]
+fn ksymvers_consolidate() {
+    // Check that the consolidate command merges multiple symvers files into one.
+    let output_path = tmp_path("tests/it/ksymvers/consolidate.symvers");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/consolidate/a.symvers".as_ref(),
+        "tests/it/ksymvers/consolidate/b.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "0x23456789\tbar\tvmlinux\tEXPORT_SYMBOL_GPL\t\n",
+            "0x3456789a\tbaz\tlib/test\tEXPORT_SYMBOL\tBAZ_NS\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_consolidate_input_order() {
+    // Check that the consolidate command can preserve the input order of records.
+    let output_path = tmp_path("tests/it/ksymvers/consolidate_input_order.symvers");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "--order=input".as_ref(),
+        "tests/it/ksymvers/consolidate/a.symvers".as_ref(),
+        "tests/it/ksymvers/consolidate/b.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n",
+            "0x23456789\tbar\tvmlinux\tEXPORT_SYMBOL_GPL\t\n",
+            "0x3456789a\tbaz\tlib/test\tEXPORT_SYMBOL\tBAZ_NS\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_consolidate_lenient() {
+    // Check that the consolidate command rejects unknown trailing columns by default and preserves
+    // them with --lenient.
+    let output_path = tmp_path("tests/it/ksymvers/consolidate_lenient.symvers");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/consolidate_lenient/a.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read symvers from 'tests/it/ksymvers/consolidate_lenient/a.symvers': ",
+            "Unexpected string found at the end of the export record\n",
+            " tests/it/ksymvers/consolidate_lenient/a.symvers:2:49\n",
+            " | 0x23456789\tbar\tvmlinux\tEXPORT_SYMBOL_GPL\tBAR_NS\tRUST\tPATCHABLE\n",
+            " |           \t   \t       \t                 \t      \t^\n", //
+        )
+    );
+
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--lenient".as_ref(),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/consolidate_lenient/a.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "0x23456789\tbar\tvmlinux\tEXPORT_SYMBOL_GPL\tBAR_NS\tRUST\tPATCHABLE\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\tRUST\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_consolidate_build_dir() {
+    // Check that the consolidate command can read symvers data from a kernel build tree.
+    let output_path = tmp_path("tests/it/ksymvers/consolidate_build_dir.symvers");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/consolidate_build_dir/ok".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert_eq!(
+        output_data,
+        concat!(
+            "0x23456789\tbar\tvmlinux\tEXPORT_SYMBOL_GPL\t\n",
+            "0x3456789a\tbaz\textra/baz\tEXPORT_SYMBOL\tBAZ_NS\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_consolidate_build_dir_conflict() {
+    // Check that the consolidate command reports modules importing symbols with mismatched CRCs
+    // in a kernel build tree.
+    let output_path = tmp_path("tests/it/ksymvers/consolidate_build_dir_conflict.symvers");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/consolidate_build_dir/conflict".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read symvers from 'tests/it/ksymvers/consolidate_build_dir/conflict': ",
+            "Conflicting CRC '0x9abcdef0' of imported symbol 'foo', previously loaded as ",
+            "'0x12345678 foo vmlinux EXPORT_SYMBOL'\n",
+            " tests/it/ksymvers/consolidate_build_dir/conflict/drivers/test/test.mod.c:9\n",
+            " | \t{ 0x9abcdef0, \"foo\" },\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_consolidate_conflict() {
+    // Check that the consolidate command reports exports with conflicting CRCs.
+    let output_path = tmp_path("tests/it/ksymvers/consolidate_conflict.symvers");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("consolidate"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/consolidate/a.symvers".as_ref(),
+        "tests/it/ksymvers/consolidate_conflict/b.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read symvers from 'tests/it/ksymvers/consolidate_conflict/b.symvers': Conflicting record 'foo', previously loaded as '0x12345678 foo vmlinux EXPORT_SYMBOL'\n",
+            " tests/it/ksymvers/consolidate_conflict/b.symvers:1\n",
+            " | 0x9abcdef0\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n", //
+        )
+    );
+    assert!(!output_path.exists());
+}
+
+#[test]
+fn ksymvers_split() {
+    // Check that the split command writes one symvers file per module.
+    let output_path = tmp_path("tests/it/ksymvers/split");
+    fs::remove_dir_all(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        "tests/it/ksymvers/split/test.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        fs::read_to_string(output_path.join("vmlinux.symvers")).unwrap(),
+        concat!(
+            "0x23456789\tbar\tvmlinux\tEXPORT_SYMBOL_GPL\t\n",
+            "0x12345678\tfoo\tvmlinux\tEXPORT_SYMBOL\t\n", //
+        )
+    );
+    assert_eq!(
+        fs::read_to_string(output_path.join("drivers/test/test_module.symvers")).unwrap(),
+        "0x3456789a\tbaz\tdrivers/test/test_module\tEXPORT_SYMBOL\tTEST_NS\n"
+    );
+}
+
+#[test]
+fn ksymvers_split_manifest() {
+    // Check that the split command can write a sorted manifest of the output files.
+    let output_path = tmp_path("tests/it/ksymvers/split_manifest");
+    let manifest_path = tmp_path("tests/it/ksymvers/split_manifest.sha256");
+    fs::remove_dir_all(&output_path).ok();
+    fs::remove_file(&manifest_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("split"),
+        "--output".as_ref(),
+        output_path.as_ref(),
+        &concat_os("--manifest=", &manifest_path),
+        "tests/it/ksymvers/split/test.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        fs::read_to_string(manifest_path).unwrap(),
+        concat!(
+            "8563b24a50ff6c857bfdc4d66962717ea16e5884d482e49e19caab7bf5197388  drivers/test/test_module.symvers\n",
+            "738a3ad29f2dca37110f05e7b0ff515c1d607270a91d720a79008c5f7a29f0fd  vmlinux.symvers\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_split_missing_output() {
+    // Check that the split command fails if no --output is specified.
+    let result = ksymvers_run(["split", "tests/it/ksymvers/split/test.symvers"]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "The split output is missing\n");
+}
 
 #[test]
 fn ksymvers_compare_identical() {
     // Check that the comparison of two identical symvers files shows no differences.
     let result = ksymvers_run([
         "compare",
-        "tests/it/ksymvers/compare/a.symvers",
-        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/a.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_log_file() {
+    // Check that debug messages can be written to a log file.
+    let log_path = tmp_path("tests/it/ksymvers/compare_log_file.log");
+    fs::remove_file(&log_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("--debug"),
+        &concat_os("--log-file=", &log_path),
+        "compare".as_ref(),
+        "tests/it/ksymvers/compare/a.symvers".as_ref(),
+        "tests/it/ksymvers/compare/a.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let log_data = fs::read_to_string(log_path).expect("Unable to read the log file");
+    assert_inexact!(
+        log_data,
+        concat!(
+            "*] DEBUG symvers: Loading symvers data from 'tests/it/ksymvers/compare/a.symvers'\n",
+            "*] DEBUG symvers: Loading symvers data from 'tests/it/ksymvers/compare/a.symvers'\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_compare_timing_json() {
+    // Check that the --timing=json option reports the measured times as a JSON summary.
+    let result = ksymvers_run([
+        "--timing=json",
+        "compare",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n"
+    );
+    assert_inexact!(
+        result.stderr,
+        concat!(
+            "{\"phases\":{\"load\":*,\"merge\":0.000000,\"analyze\":0.000000,\"compare\":*,\"write\":0.000000},",
+            // Match the opening bracket with '?' as it is special in wildcard patterns.
+            "\"operations\":?",
+            "{\"phase\":\"load\",\"description\":\"Reading symvers from 'tests/it/ksymvers/compare/a.symvers'\",\"seconds\":*},",
+            "{\"phase\":\"load\",\"description\":\"Reading symvers from 'tests/it/ksymvers/compare/b.symvers'\",\"seconds\":*},",
+            "{\"phase\":\"compare\",\"description\":\"Comparison\",\"seconds\":*}",
+            "]}\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_compare_changed() {
+    // Check that the comparison of two different symvers files shows relevant differences and
+    // results in the command exiting with a status of 1.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n"
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_with_header() {
+    // Check that the --with-header option prepends a header describing the comparison to the pretty
+    // and short output, and that the --reproducible option omits its timestamp.
+    let result = ksymvers_run([
+        "compare",
+        "--with-header",
+        "--reproducible",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        format!(
+            concat!(
+                "# Generated by: ksymvers {} compare\n",
+                "# Inputs: tests/it/ksymvers/compare/a.symvers tests/it/ksymvers/compare/b.symvers\n",
+                "# Options: --with-header --reproducible\n",
+                "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n", //
+            ),
+            env!("SUSE_KABI_TOOLS_VERSION")
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    let result = ksymvers_run([
+        "compare",
+        "--with-header",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert!(
+        result
+            .stdout
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("# Date: ")
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_filter_symbol_list() {
+    // Check that the comparison of two symvers files can be restricted to specific exports.
+
+    // Check that the result is sensible without a filter.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare_filter_symbol_list/a.symvers",
+        "tests/it/ksymvers/compare_filter_symbol_list/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    // Check the result when using a filter.
+    let result = ksymvers_run([
+        "compare",
+        "--filter-symbol-list=tests/it/ksymvers/compare_filter_symbol_list/filter-symbol-list.txt",
+        "tests/it/ksymvers/compare_filter_symbol_list/a.symvers",
+        "tests/it/ksymvers/compare_filter_symbol_list/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_exclude_symbol_list() {
+    // Check that specific exports can be excluded from the comparison of two symvers files.
+    let result = ksymvers_run([
+        "compare",
+        "--exclude-symbol-list=tests/it/ksymvers/compare_exclude_symbol_list/exclude-symbol-list.txt",
+        "tests/it/ksymvers/compare_exclude_symbol_list/a.symvers",
+        "tests/it/ksymvers/compare_exclude_symbol_list/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_symbol_patterns() {
+    // Check that the comparison of two symvers files can be restricted using patterns specified
+    // on the command line.
+    let result = ksymvers_run([
+        "compare",
+        "--filter-symbol=ba*",
+        "--filter-symbol=foo",
+        "--exclude-symbol=bar",
+        "tests/it/ksymvers/compare_symbol_patterns/a.symvers",
+        "tests/it/ksymvers/compare_symbol_patterns/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_namespace() {
+    // Check that the comparison can be restricted to exports in a single namespace, including
+    // those that are in the namespace only in one of the files.
+    let result = ksymvers_run([
+        "compare",
+        "--namespace=FOO_NS",
+        "tests/it/ksymvers/compare_namespace/a.symvers",
+        "tests/it/ksymvers/compare_namespace/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'baz' changed namespace from 'FOO_NS' to '(none)' (implicitly tolerated)\n",
+            "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_legacy() {
+    // Check that a legacy symvers file without the type and namespace columns is detected and can
+    // be compared with a modern one.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare_legacy/a.symvers",
+        "tests/it/ksymvers/compare_legacy/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'bar' changed CRC from '0x23456789' to '0x9abcdef0'\n",
+            "Export 'bar' changed namespace from '(none)' to 'BAR_NS'\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+
+    // Check that the detection can be overridden.
+    let result = ksymvers_run([
+        "compare",
+        "--symvers-format=modern",
+        "tests/it/ksymvers/compare_legacy/a.symvers",
+        "tests/it/ksymvers/compare_legacy/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read symvers from 'tests/it/ksymvers/compare_legacy/a.symvers': ",
+            "The export does not specify a type\n",
+            " tests/it/ksymvers/compare_legacy/a.symvers:1:23\n",
+            " | 0x12345678\tfoo\tvmlinux\n",
+            " |           \t   \t       ^\n", //
+        )
+    );
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn ksymvers_compare_compressed() {
+    // Check that the compare command transparently decompresses gzip and zstd inputs.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare_compressed/a.symvers.gz",
+        "tests/it/ksymvers/compare_compressed/b.symvers.zst",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n"
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn ksymvers_compare_compressed_corrupted() {
+    // Check that a failure to decompress an input is reported.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare_compressed/a.symvers.gz",
+        "tests/it/ksymvers/compare_compressed/corrupted.symvers.gz",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    suse_kabi_tools::assert_inexact!(
+        result.stderr,
+        concat!(
+            "Failed to read symvers from 'tests/it/ksymvers/compare_compressed/corrupted.symvers.gz': ",
+            "Failed to read symvers data: ",
+            "Failed to decompress the file 'tests/it/ksymvers/compare_compressed/corrupted.symvers.gz': ",
+            "'gzip' failed with *\n",
+        )
+    );
+}
+
+#[test]
+fn ksymvers_compare_color() {
+    // Check that the --color=always option colorizes added, removed and tolerated changes, even if
+    // the output is not a terminal.
+    let result = ksymvers_run([
+        "compare",
+        "--color=always",
+        "tests/it/ksymvers/compare_format/a.symvers",
+        "tests/it/ksymvers/compare_format/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "\x1b[2mExport 'aaa' has been added (implicitly tolerated)\x1b[0m\n",
+            "\x1b[2mExport 'fff' has been added (implicitly tolerated)\x1b[0m\n",
+            "\x1b[31mExport 'bbb' has been removed\x1b[0m\n",
+            "\x1b[31mExport 'ggg' has been removed\x1b[0m\n",
+            "Export 'ccc' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "Export 'ccc' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "Export 'ddd' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "\x1b[2mExport 'eee' changed type from 'EXPORT_SYMBOL_GPL' to 'EXPORT_SYMBOL' (implicitly tolerated)\x1b[0m\n",
+            "Export 'hhh' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "Export 'hhh' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "Export 'iii' changed type from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL'\n",
+            "\x1b[2mExport 'jjj' changed type from 'EXPORT_SYMBOL_GPL' to 'EXPORT_SYMBOL' (implicitly tolerated)\x1b[0m\n",
+            "Exports changed from 'EXPORT_SYMBOL' to 'EXPORT_SYMBOL_GPL':\n",
+            " Module 'vmlinux':\n",
+            "  ccc\n",
+            "  ddd\n",
+            "  hhh\n",
+            "  iii\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_demangle() {
+    // Check that the --demangle option shows Rust export names in their demangled form, while
+    // symbol lists keep the raw names.
+    let result = ksymvers_run([
+        "compare",
+        "--demangle",
+        "tests/it/ksymvers/compare_demangle/a.symvers",
+        "tests/it/ksymvers/compare_demangle/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'kernel::print::call_printk' changed CRC from '0x12345678' to '0x09abcdef'\n"
+    );
+    assert_eq!(result.stderr, "");
+
+    let result = ksymvers_run([
+        "compare",
+        "--demangle",
+        "--format=symbols",
+        "tests/it/ksymvers/compare_demangle/a.symvers",
+        "tests/it/ksymvers/compare_demangle/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "_RNvNtCs1234_6kernel5print11call_printk\n");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_rpm() {
+    // Check that the compare command extracts Module.symvers from a kernel-devel package.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare_rpm/kernel-devel.rpm",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n"
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn ksymvers_compare_rpm_compressed() {
+    // Check that the compare command extracts the gzipped /boot/symvers-* file from a kernel
+    // package with a compressed payload.
+    let result = ksymvers_run([
+        "compare",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare_rpm/kernel-default.rpm",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n"
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_rpm_missing() {
+    // Check that a package without the requested file is rejected.
+    let result = ksymvers_run([
+        "compare",
+        "--explain-with-symtypes=tests/it/ksymvers/compare_explain/a.symtypes:tests/it/ksymvers/compare_rpm/kernel-devel.rpm",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare_rpm/kernel-devel.rpm",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read symtypes from 'tests/it/ksymvers/compare_rpm/kernel-devel.rpm': ",
+            "No file matching 'boot/symtypes-*', '*/symtypes-*' found in the package\n",
+        )
+    );
+}
+
+#[test]
+fn ksymvers_compare_explain_with_symtypes() {
+    // Check that the comparison can describe the type changes behind each CRC change.
+    let result = ksymvers_run([
+        "compare",
+        "--explain-with-symtypes=tests/it/ksymvers/compare_explain/a.symtypes:tests/it/ksymvers/compare_explain/b.symtypes",
+        "tests/it/ksymvers/compare_explain/a.symvers",
+        "tests/it/ksymvers/compare_explain/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n",
+            "  because of a changed 'foo':\n",
+            "  @@ -1,1 +1,1 @@\n",
+            "  -void foo ( int a )\n",
+            "  +void foo ( long a )\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_html() {
+    // Check that the HTML format writes a standalone report to the specified file, including the
+    // explanation of each CRC change.
+    let output_path = tmp_path("tests/it/ksymvers/compare_html.html");
+    fs::remove_file(&output_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("compare"),
+        "--format=null".as_ref(),
+        format!("--format=html:{}", output_path.display()).as_ref(),
+        "--explain-with-symtypes=tests/it/ksymvers/compare_explain/a.symtypes:tests/it/ksymvers/compare_explain/b.symtypes".as_ref(),
+        "tests/it/ksymvers/compare_explain/a.symvers".as_ref(),
+        "tests/it/ksymvers/compare_explain/b.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+    let output_data = fs::read_to_string(output_path).expect("Unable to read the output file");
+    assert!(output_data.starts_with("<!DOCTYPE html>\n"));
+    assert!(output_data.contains(concat!(
+        "<li><details><summary>Export &#39;foo&#39; changed CRC from &#39;0x12345678&#39; to &#39;0x9abcdef0&#39;</summary>\n",
+        "<pre class=\"diff\">\n",
+        "because of a changed &#39;foo&#39;:\n",
+        "<span class=\"hunk\">@@ -1,1 +1,1 @@</span>\n",
+        "<span class=\"del\">-void foo ( int a )</span>\n",
+        "<span class=\"add\">+void foo ( long a )</span>\n",
+        "</pre>\n",
+        "</details></li>\n", //
+    )));
+    assert!(output_data.ends_with("</html>\n"));
+}
+
+#[test]
+fn ksymvers_compare_explain_with_symtypes_invalid() {
+    // Check that the --explain-with-symtypes option requires two paths.
+    let result = ksymvers_run([
+        "compare",
+        "--explain-with-symtypes=tests/it/ksymvers/compare_explain/a.symtypes",
+        "tests/it/ksymvers/compare_explain/a.symvers",
+        "tests/it/ksymvers/compare_explain/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "Invalid value for '--explain-with-symtypes=tests/it/ksymvers/compare_explain/a.symtypes': must be in the form PATH:PATH2\n"
+    );
+}
+
+#[test]
+fn ksymvers_compare_rules() {
+    // Check that severity rules can be used to tolerate changes.
+    let result = ksymvers_run([
+        "compare",
+        "--rules=tests/it/ksymvers/compare_rules/severities.txt",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
     ]);
     assert_eq!(result.status.code().unwrap(), 0);
-    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rule ",
+            "tests/it/ksymvers/compare_rules/severities.txt:1 'vmlinux PASS')\n", //
+        )
+    );
     assert_eq!(result.stderr, "");
 }
 
 #[test]
-fn ksymvers_compare_changed() {
-    // Check that the comparison of two different symvers files shows relevant differences and
-    // results in the command exiting with a status of 1.
+fn ksymvers_compare_kabi_pl() {
+    // Check that the kabi-pl format reports only breaking changes in the format of the legacy
+    // kabi.pl script and that the exit code indicates them.
     let result = ksymvers_run([
         "compare",
-        "tests/it/ksymvers/compare/a.symvers",
-        "tests/it/ksymvers/compare/b.symvers",
+        "--format=kabi-pl",
+        "--rules=tests/it/ksymvers/compare_kabi_pl/severities.txt",
+        "tests/it/ksymvers/compare_kabi_pl/a.symvers",
+        "tests/it/ksymvers/compare_kabi_pl/b.symvers",
     ]);
     assert_eq!(result.status.code().unwrap(), 1);
     assert_eq!(
         result.stdout,
         concat!(
-            "Export 'foo' changed CRC from '0x12345678' to '0x09abcdef'\n", //
+            "KABI: symbol baz(vmlinux) lost\n",
+            "KABI: symbol foo(vmlinux) changed crc from 0x12345678 to 0x9abcdef0\n",
+            "KABI: symbol qux(vmlinux) changed type from EXPORT_SYMBOL to EXPORT_SYMBOL_GPL\n", //
         )
     );
     assert_eq!(result.stderr, "");
 }
 
 #[test]
-fn ksymvers_compare_filter_symbol_list() {
-    // Check that the comparison of two symvers files can be restricted to specific exports.
-
-    // Check that the result is sensible without a filter.
+fn ksymvers_compare_rules_include() {
+    // Check that severity rules can include other rules files, relative to the including file.
     let result = ksymvers_run([
         "compare",
-        "tests/it/ksymvers/compare_filter_symbol_list/a.symvers",
-        "tests/it/ksymvers/compare_filter_symbol_list/b.symvers",
+        "--rules=tests/it/ksymvers/compare_rules_include/severities.txt",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(
         result.stdout,
         concat!(
-            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
-            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n",
-            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rule ",
+            "tests/it/ksymvers/compare_rules_include/common/vmlinux.severities:1 'vmlinux PASS')\n", //
         )
     );
     assert_eq!(result.stderr, "");
+}
 
-    // Check the result when using a filter.
+#[test]
+fn ksymvers_compare_rules_include_cycle() {
+    // Check that an include cycle in severity rules is detected.
     let result = ksymvers_run([
         "compare",
-        "--filter-symbol-list=tests/it/ksymvers/compare_filter_symbol_list/filter-symbol-list.txt",
-        "tests/it/ksymvers/compare_filter_symbol_list/a.symvers",
-        "tests/it/ksymvers/compare_filter_symbol_list/b.symvers",
+        "--rules=tests/it/ksymvers/compare_rules_include/cycle/a.severities",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
     ]);
-    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        concat!(
+            "Failed to read severity rules from 'tests/it/ksymvers/compare_rules_include/cycle/a.severities': ",
+            "The included file 'tests/it/ksymvers/compare_rules_include/cycle/a.severities' is already being loaded, which creates a cycle\n",
+            " tests/it/ksymvers/compare_rules_include/cycle/b.severities:1:9\n",
+            " | include a.severities\n",
+            " |         ^\n", //
+        )
+    );
+}
+
+#[test]
+fn ksymvers_compare_rules_arch() {
+    // Check that severity rules guarded by an architecture apply only when it is selected.
+    let result = ksymvers_run([
+        "compare",
+        "--rules=tests/it/ksymvers/compare_rules_arch/severities.txt",
+        "--arch=aarch64",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(
         result.stdout,
         concat!(
-            "Export 'bar' changed CRC from '0x23456789' to '0xabcdef01'\n",
-            "Export 'baz' changed CRC from '0x3456789a' to '0xbcdef012'\n", //
+            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rule ",
+            "tests/it/ksymvers/compare_rules_arch/severities.txt:1 '[x86_64,aarch64] vmlinux PASS')\n", //
         )
     );
     assert_eq!(result.stderr, "");
+
+    let result = ksymvers_run([
+        "compare",
+        "--rules=tests/it/ksymvers/compare_rules_arch/severities.txt",
+        "--arch=s390x",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n"
+    );
+    assert_eq!(result.stderr, "");
 }
 
 #[test]
-fn ksymvers_compare_rules() {
-    // Check that severity rules can be used to tolerate changes.
+fn ksymvers_compare_fail_on() {
+    // Check that the --fail-on option selects which changes result in the exit code 1.
+    for (policy, code) in [("breaking", 0), ("any", 1), ("none", 0)] {
+        let result = ksymvers_run([
+            "compare",
+            "--rules=tests/it/ksymvers/compare_rules/severities.txt",
+            &format!("--fail-on={}", policy),
+            "tests/it/ksymvers/compare_rules/a.symvers",
+            "tests/it/ksymvers/compare_rules/b.symvers",
+        ]);
+        assert_eq!(result.status.code().unwrap(), code);
+        assert_eq!(result.stderr, "");
+    }
+    for (policy, code) in [("breaking", 1), ("any", 1), ("none", 0)] {
+        let result = ksymvers_run([
+            "compare",
+            &format!("--fail-on={}", policy),
+            "tests/it/ksymvers/compare_rules/a.symvers",
+            "tests/it/ksymvers/compare_rules/b.symvers",
+        ]);
+        assert_eq!(result.status.code().unwrap(), code);
+        assert_eq!(result.stderr, "");
+    }
+}
+
+#[test]
+fn ksymvers_compare_fail_on_invalid() {
+    // Check that an unrecognized --fail-on policy is rejected.
     let result = ksymvers_run([
         "compare",
-        "--rules=tests/it/ksymvers/compare_rules/severities.txt",
+        "--fail-on=some",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "Unrecognized fail-on policy 'some'\n");
+}
+
+#[test]
+fn ksymvers_compare_short_circuit() {
+    // Check that the --short-circuit option stops at the first change failing the --fail-on policy
+    // and only reports the outcome via the exit code.
+    for (policy, code) in [("breaking", 0), ("any", 1)] {
+        let result = ksymvers_run([
+            "compare",
+            "--short-circuit",
+            "--rules=tests/it/ksymvers/compare_rules/severities.txt",
+            &format!("--fail-on={}", policy),
+            "tests/it/ksymvers/compare_rules/a.symvers",
+            "tests/it/ksymvers/compare_rules/b.symvers",
+        ]);
+        assert_eq!(result.status.code().unwrap(), code);
+        assert_eq!(result.stdout, "");
+        assert_eq!(result.stderr, "");
+    }
+    let result = ksymvers_run([
+        "compare",
+        "--short-circuit",
+        "tests/it/ksymvers/compare_rules/a.symvers",
+        "tests/it/ksymvers/compare_rules/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_short_circuit_format() {
+    // Check that the --short-circuit option is rejected together with an explicit output format.
+    let result = ksymvers_run([
+        "compare",
+        "--short-circuit",
+        "--format=short",
         "tests/it/ksymvers/compare_rules/a.symvers",
         "tests/it/ksymvers/compare_rules/b.symvers",
     ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The --short-circuit option cannot be used together with --format\n"
+    );
+}
+
+#[test]
+fn ksymvers_compare_config() {
+    // Check that the compare command applies defaults from a configuration file, including
+    // per-architecture settings, and that command-line options override them.
+    for (args, code) in [
+        (&[][..], 0),
+        (&["--arch=x86_64"][..], 1),
+        (&["--arch=x86_64", "--fail-on=breaking"][..], 0),
+    ] {
+        let result = ksymvers_run(
+            [
+                "--config=tests/it/ksymvers/compare_config/kabi-tools.conf",
+                "compare",
+            ]
+            .into_iter()
+            .chain(args.iter().copied())
+            .chain([
+                "tests/it/ksymvers/compare_rules/a.symvers",
+                "tests/it/ksymvers/compare_rules/b.symvers",
+            ]),
+        );
+        assert_eq!(result.status.code().unwrap(), code);
+        assert_eq!(result.stdout, "");
+        assert_eq!(result.stderr, "");
+    }
+}
+
+#[test]
+fn ksymvers_compare_suppressions() {
+    // Check that the compare command ignores acknowledged differences.
+    let result = ksymvers_run([
+        "compare",
+        "--suppressions=tests/it/ksymvers/compare_suppressions/suppressions.txt",
+        "tests/it/ksymvers/compare/a.symvers",
+        "tests/it/ksymvers/compare/b.symvers",
+    ]);
     assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_protected_list() {
+    // Check that changes of symbols on the protected list are breaking regardless of the rules, and
+    // that the removal of a protected symbol results in the exit code 3.
+    for (policy, code) in [("breaking", 3), ("any", 3), ("none", 0)] {
+        let result = ksymvers_run([
+            "compare",
+            "--rules=tests/it/ksymvers/compare_protected/severities.txt",
+            "--protected-list=tests/it/ksymvers/compare_protected/protected.txt",
+            &format!("--fail-on={}", policy),
+            "tests/it/ksymvers/compare_protected/a.symvers",
+            "tests/it/ksymvers/compare_protected/b.symvers",
+        ]);
+        assert_eq!(result.status.code().unwrap(), code);
+        assert_eq!(
+            result.stdout,
+            concat!(
+                "Export 'bar' has been removed (protected symbol removed)\n",
+                "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0'\n", //
+            )
+        );
+        assert_eq!(result.stderr, "");
+    }
+    let result = ksymvers_run([
+        "compare",
+        "--short-circuit",
+        "--rules=tests/it/ksymvers/compare_protected/severities.txt",
+        "--protected-list=tests/it/ksymvers/compare_protected/protected.txt",
+        "tests/it/ksymvers/compare_protected/a.symvers",
+        "tests/it/ksymvers/compare_protected/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 3);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_baseline() {
+    // Check that the compare command can compare a baseline with multiple symvers files.
+    let result = ksymvers_run([
+        "compare",
+        "--baseline=tests/it/ksymvers/compare_baseline/base.symvers",
+        "tests/it/ksymvers/compare_baseline/default.symvers",
+        "tests/it/ksymvers/compare_baseline/rt.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export  tests/it/ksymvers/compare_baseline/default.symvers  tests/it/ksymvers/compare_baseline/rt.symvers\n",
+            "bar     -                                                   breaking\n",
+            "foo     breaking                                            -\n",
+            "qux     -                                                   tolerated\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_compare_baseline_format() {
+    // Check that the --format option is rejected when comparing with a baseline.
+    let result = ksymvers_run([
+        "compare",
+        "--baseline=tests/it/ksymvers/compare_baseline/base.symvers",
+        "--format=short",
+        "tests/it/ksymvers/compare_baseline/default.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The --format option cannot be used together with --baseline\n"
+    );
+}
+
+#[test]
+fn ksymvers_compare_named_baselines() {
+    // Check that the compare command can compare a symvers file with multiple named baselines, each
+    // with its own severity rules.
+    let result = ksymvers_run([
+        "compare",
+        "--baseline=GA:tests/it/ksymvers/compare_named_baselines/ga.symvers",
+        concat!(
+            "--baseline=LU:tests/it/ksymvers/compare_named_baselines/lu.symvers:",
+            "tests/it/ksymvers/compare_named_baselines/lu-severities.txt",
+        ),
+        "tests/it/ksymvers/compare_named_baselines/new.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 1);
     assert_eq!(
         result.stdout,
         concat!(
-            "Export 'foo' changed CRC from '0x12345678' to '0x9abcdef0' (tolerated by rules)\n", //
+            "Baseline 'GA': Export 'qux' has been added (implicitly tolerated)\n",
+            "Baseline 'GA': Export 'bar' changed CRC from '0x23456789' to '0x9abcdef0'\n",
+            "Baseline 'GA': Export 'foo' changed CRC from '0x12345678' to '0x3456789a'\n",
+            "Baseline 'LU': Export 'qux' has been added (implicitly tolerated)\n",
+            "Baseline 'LU': Export 'foo' changed CRC from '0x12345678' to '0x3456789a' (tolerated by rule ",
+            "tests/it/ksymvers/compare_named_baselines/lu-severities.txt:1 'foo PASS')\n", //
         )
     );
     assert_eq!(result.stderr, "");
 }
 
+#[test]
+fn ksymvers_compare_named_baselines_mixed() {
+    // Check that a baseline in the form FILE cannot be combined with named baselines.
+    let result = ksymvers_run([
+        "compare",
+        "--baseline=GA:tests/it/ksymvers/compare_named_baselines/ga.symvers",
+        "--baseline=tests/it/ksymvers/compare_named_baselines/lu.symvers",
+        "tests/it/ksymvers/compare_named_baselines/new.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The --baseline option can be repeated only in the form NAME:PATH[:RULES]\n"
+    );
+}
+
 #[test]
 fn ksymvers_compare_warn_unused_rules() {
     // Check that the unused-rules subcommand reports all unused severity rules.
@@ -106,15 +1122,150 @@ fn ksymvers_compare_warn_unused_rules() {
         "tests/it/ksymvers/unused_rules/b.symvers",
     ]);
     assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        "tests/it/ksymvers/unused_rules/severities.txt:2: WARNING: Severity rule 'SYMBOL bar FAIL' is unused\n"
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_unused_rules_module_match() {
+    // Check that the unused-rules subcommand normalizes module names according to --module-match
+    // and reports module rules that do not match any module.
+    let result = ksymvers_run([
+        "unused-rules",
+        "--rules=tests/it/ksymvers/unused_rules_module_match/severities.txt",
+        "--module-match=basename,ignore-case",
+        "tests/it/ksymvers/unused_rules_module_match/a.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "tests/it/ksymvers/unused_rules_module_match/severities.txt:2: WARNING: Severity rule 'MODULE igb PASS' is unused\n",
+            "tests/it/ksymvers/unused_rules_module_match/severities.txt:2: WARNING: Severity rule 'MODULE igb PASS' does not match any module\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_rules_check() {
+    // Check that the rules-check command reports which rule matches each symbol.
+    let result = ksymvers_run([
+        "rules-check",
+        "--rules=tests/it/ksymvers/rules_check/severities.txt",
+        "tests/it/ksymvers/rules_check/a.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Export 'bar' matches rule tests/it/ksymvers/rules_check/severities.txt:2 'MODULE vmlinux FAIL'\n",
+            "Export 'baz' matches no rule\n",
+            "Export 'foo' matches rule tests/it/ksymvers/rules_check/severities.txt:1 'SYMBOL foo PASS'\n",
+            "tests/it/ksymvers/rules_check/severities.txt:3: WARNING: Severity rule 'NAMESPACE TEST_NS PASS' is unused\n",
+            "Summary: '2' exports matched, '1' exports unmatched, '1' of '3' rules unused\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_namespaces() {
+    // Check that the namespaces command lists all namespaces with the number of their exports.
+    let result = ksymvers_run(["namespaces", "tests/it/ksymvers/namespaces/a.symvers"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "(none): 1\n",
+            "BAR_NS: 1\n",
+            "FOO_NS: 2\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_trend() {
+    // Check that the trend command reports the churn of exports over snapshots in a directory,
+    // ordered by their file name.
+    let result = ksymvers_run(["trend", "tests/it/ksymvers/trend"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(
+        result.stdout,
+        concat!(
+            "Snapshot '2024-01-15.symvers': '2' exports\n",
+            "Snapshot '2024-06-15.symvers': '2' exports, '1' added, '1' removed, '0' modified\n",
+            "Snapshot '2025-01-15.symvers': '2' exports, '0' added, '0' removed, '1' modified\n",
+            "Export 'bar': stability '0.00', '0' of '1' transitions unchanged\n",
+            "Export 'baz': stability '0.00', '0' of '2' transitions unchanged\n",
+            "Export 'foo': stability '1.00', '2' of '2' transitions unchanged\n", //
+        )
+    );
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_trend_single_snapshot() {
+    // Check that the trend command requires at least two snapshots.
+    let result = ksymvers_run(["trend", "tests/it/ksymvers/namespaces"]);
+    assert_eq!(result.status.code().unwrap(), 2);
+    assert_eq!(result.stdout, "");
+    assert_eq!(
+        result.stderr,
+        "The trend directory 'tests/it/ksymvers/namespaces' must contain at least two snapshots\n"
+    );
+}
+
+#[test]
+fn ksymvers_rules_suggest() {
+    // Check that the rules-suggest command produces rules tolerating all breaking changes.
+    let result = ksymvers_run([
+        "rules-suggest",
+        "tests/it/ksymvers/rules_suggest/a.symvers",
+        "tests/it/ksymvers/rules_suggest/b.symvers",
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
     assert_eq!(
         result.stdout,
         concat!(
-            "tests/it/ksymvers/unused_rules/severities.txt:2: WARNING: Severity rule 'SYMBOL bar FAIL' is unused\n", //
+            "MODULE lib/test_module PASS\n",
+            "SYMBOL bar PASS\n", //
         )
     );
     assert_eq!(result.stderr, "");
 }
 
+#[test]
+fn ksymvers_rules_suggest_output() {
+    // Check that the suggested rules can be written to a file and used by the compare command.
+    let rules_path = tmp_path("tests/ksymvers/rules_suggest_output/severities.txt");
+    fs::remove_file(&rules_path).ok();
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("rules-suggest"),
+        &concat_os("--output=", &rules_path),
+        "tests/it/ksymvers/rules_suggest/a.symvers".as_ref(),
+        "tests/it/ksymvers/rules_suggest/b.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+
+    let result = ksymvers_run([
+        AsRef::<OsStr>::as_ref("compare"),
+        "--format=null".as_ref(),
+        &concat_os("--rules=", &rules_path),
+        "tests/it/ksymvers/rules_suggest/a.symvers".as_ref(),
+        "tests/it/ksymvers/rules_suggest/b.symvers".as_ref(),
+    ]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
 #[test]
 fn ksymvers_compare_format() {
     // Check that the comparison allows specifying the output format.
@@ -134,6 +1285,9 @@ fn ksymvers_compare_format() {
     let short_out_path = tmp_path("short.out");
     let symbols_out_path = tmp_path("symbols.out");
     let mod_symbols_out_path = tmp_path("mod_symbols.out");
+    let gpl_report_out_path = tmp_path("gpl_report.out");
+    let summary_out_path = tmp_path("summary.out");
+    let module_stats_out_path = tmp_path("module_stats.out");
     let result = ksymvers_run([
         AsRef::<OsStr>::as_ref("compare"),
         "--rules=tests/it/ksymvers/compare_format/severities.txt".as_ref(),
@@ -142,6 +1296,9 @@ fn ksymvers_compare_format() {
         &concat_os("--format=short:", &short_out_path),
         &concat_os("--format=symbols:", &symbols_out_path),
         &concat_os("--format=mod-symbols:", &mod_symbols_out_path),
+        &concat_os("--format=gpl-report:", &gpl_report_out_path),
+        &concat_os("--format=summary:", &summary_out_path),
+        &concat_os("--format=module-stats:", &module_stats_out_path),
         "tests/it/ksymvers/compare_format/a.symvers".as_ref(),
         "tests/it/ksymvers/compare_format/b.symvers".as_ref(),
     ]);
@@ -164,4 +1321,50 @@ fn ksymvers_compare_format() {
     let mod_symbols_out = fs::read_to_string(&mod_symbols_out_path).unwrap();
     let mod_symbols_exp = fs::read_to_string(expected_path("mod_symbols.exp")).unwrap();
     assert_eq!(mod_symbols_out, mod_symbols_exp);
+
+    let gpl_report_out = fs::read_to_string(&gpl_report_out_path).unwrap();
+    let gpl_report_exp = fs::read_to_string(expected_path("gpl_report.exp")).unwrap();
+    assert_eq!(gpl_report_out, gpl_report_exp);
+
+    let summary_out = fs::read_to_string(&summary_out_path).unwrap();
+    let summary_exp = fs::read_to_string(expected_path("summary.exp")).unwrap();
+    assert_eq!(summary_out, summary_exp);
+
+    let module_stats_out = fs::read_to_string(&module_stats_out_path).unwrap();
+    let module_stats_exp = fs::read_to_string(expected_path("module_stats.exp")).unwrap();
+    assert_eq!(module_stats_out, module_stats_exp);
+}
+
+#[test]
+fn ksymvers_dump_man() {
+    // Check that the man page generated from the command-line definitions documents all commands.
+    let result = ksymvers_run(["--dump-man"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert!(result.stdout.starts_with(".TH KSYMVERS 1\n"));
+    for command in [
+        "CONSOLIDATE",
+        "SPLIT",
+        "COMPARE",
+        "UNUSED\\-RULES",
+        "RULES\\-SUGGEST",
+        "RULES\\-CHECK",
+        "NAMESPACES",
+    ] {
+        assert!(
+            result
+                .stdout
+                .contains(&format!("\n.SH {} COMMAND\n", command))
+        );
+    }
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn ksymvers_man_page() {
+    // Check that the shipped man page documents the same commands and options as the man page
+    // generated from the command-line definitions.
+    let result = ksymvers_run(["--dump-man"]);
+    assert_eq!(result.status.code().unwrap(), 0);
+    assert_man_page_matches(&result.stdout, "doc/ksymvers.1", &[]);
+    assert_eq!(result.stderr, "");
 }
@@ -0,0 +1,14 @@
+#include <linux/module.h>
+#include <linux/export-internal.h>
+#include <linux/compiler.h>
+
+MODULE_INFO(name, KBUILD_MODNAME);
+
+static const struct modversion_info ____versions[]
+__used __section("__versions") = {
+	{ 0x12345678, "foo" },
+	{ 0x3456789a, "baz" },
+	{ 0x456789ab, "external" },
+};
+
+MODULE_INFO(depends, "baz");